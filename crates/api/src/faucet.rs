@@ -0,0 +1,445 @@
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Address, Amount, Hash, Transaction};
+use spirachain_crypto::KeyPair;
+use spirachain_rpc::{encode_amount, RpcClient};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use warp::http::StatusCode;
+use warp::Filter;
+
+use crate::rest::ApiResponse;
+
+/// Faucet settings; the funded account is the keypair handed to [`Faucet::new`]
+#[derive(Debug, Clone)]
+pub struct FaucetConfig {
+    /// Sent per successful request
+    pub amount: Amount,
+    /// Minimum time between two payouts to the same address
+    pub address_cooldown: Duration,
+    /// Minimum time between two payouts requested from the same IP
+    pub ip_cooldown: Duration,
+    pub rpc_host: String,
+    pub rpc_port: u16,
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        Self {
+            amount: Amount::qbt(10),
+            address_cooldown: Duration::from_secs(24 * 3600),
+            ip_cooldown: Duration::from_secs(3600),
+            rpc_host: "127.0.0.1".to_string(),
+            rpc_port: 9933,
+        }
+    }
+}
+
+/// Hook for a captcha provider. The faucet passes the token from the request
+/// and the requester's IP; anything but `true` refuses the payout.
+pub trait CaptchaVerifier: Send + Sync {
+    fn verify(&self, token: Option<&str>, ip: IpAddr) -> bool;
+}
+
+/// Accepts every request, for private testnets
+pub struct NoCaptcha;
+
+impl CaptchaVerifier for NoCaptcha {
+    fn verify(&self, _token: Option<&str>, _ip: IpAddr) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaucetError {
+    InvalidAddress(String),
+    CaptchaFailed,
+    RateLimited { retry_after: Duration },
+    Rejected(String),
+}
+
+impl FaucetError {
+    fn status(&self) -> StatusCode {
+        match self {
+            FaucetError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+            FaucetError::CaptchaFailed => StatusCode::FORBIDDEN,
+            FaucetError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            FaucetError::Rejected(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+impl fmt::Display for FaucetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaucetError::InvalidAddress(address) => write!(f, "Invalid address: {}", address),
+            FaucetError::CaptchaFailed => f.write_str("Captcha verification failed"),
+            FaucetError::RateLimited { retry_after } => {
+                write!(f, "Rate limited, retry in {}s", retry_after.as_secs())
+            }
+            FaucetError::Rejected(reason) => write!(f, "Node rejected the payout: {}", reason),
+        }
+    }
+}
+
+/// Cooldowns per recipient address and per requesting IP
+pub struct FaucetLimiter {
+    address_cooldown: Duration,
+    ip_cooldown: Duration,
+    by_address: HashMap<Address, Instant>,
+    by_ip: HashMap<IpAddr, Instant>,
+}
+
+impl FaucetLimiter {
+    pub fn new(address_cooldown: Duration, ip_cooldown: Duration) -> Self {
+        Self {
+            address_cooldown,
+            ip_cooldown,
+            by_address: HashMap::new(),
+            by_ip: HashMap::new(),
+        }
+    }
+
+    /// Reserve a payout, or return how long the caller has to wait
+    pub fn try_acquire(
+        &mut self,
+        address: Address,
+        ip: IpAddr,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let (address_cooldown, ip_cooldown) = (self.address_cooldown, self.ip_cooldown);
+        self.by_address
+            .retain(|_, last| now.duration_since(*last) < address_cooldown);
+        self.by_ip
+            .retain(|_, last| now.duration_since(*last) < ip_cooldown);
+
+        let wait_address = self
+            .by_address
+            .get(&address)
+            .map(|last| address_cooldown - now.duration_since(*last));
+        let wait_ip = self
+            .by_ip
+            .get(&ip)
+            .map(|last| ip_cooldown - now.duration_since(*last));
+        if let Some(wait) = wait_address.max(wait_ip) {
+            return Err(wait);
+        }
+
+        self.by_address.insert(address, now);
+        self.by_ip.insert(ip, now);
+        Ok(())
+    }
+
+    /// Give a reservation back after the node refused the payout
+    pub fn release(&mut self, address: &Address, ip: &IpAddr) {
+        self.by_address.remove(address);
+        self.by_ip.remove(ip);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FaucetMetrics {
+    requests: AtomicU64,
+    payouts: AtomicU64,
+    rate_limited: AtomicU64,
+    captcha_failed: AtomicU64,
+    failed: AtomicU64,
+    dispensed: Mutex<u128>,
+}
+
+impl FaucetMetrics {
+    pub fn export_prometheus(&self) -> String {
+        format!(
+            "# HELP spirachain_faucet_requests Faucet requests received\n\
+             # TYPE spirachain_faucet_requests counter\n\
+             spirachain_faucet_requests {}\n\
+             # HELP spirachain_faucet_payouts Payouts accepted by the node\n\
+             # TYPE spirachain_faucet_payouts counter\n\
+             spirachain_faucet_payouts {}\n\
+             # HELP spirachain_faucet_rate_limited Requests refused by a cooldown\n\
+             # TYPE spirachain_faucet_rate_limited counter\n\
+             spirachain_faucet_rate_limited {}\n\
+             # HELP spirachain_faucet_captcha_failed Requests refused by the captcha\n\
+             # TYPE spirachain_faucet_captcha_failed counter\n\
+             spirachain_faucet_captcha_failed {}\n\
+             # HELP spirachain_faucet_failed Payouts the node rejected\n\
+             # TYPE spirachain_faucet_failed counter\n\
+             spirachain_faucet_failed {}\n\
+             # HELP spirachain_faucet_dispensed Base units sent\n\
+             # TYPE spirachain_faucet_dispensed counter\n\
+             spirachain_faucet_dispensed {}\n",
+            self.requests.load(Ordering::Relaxed),
+            self.payouts.load(Ordering::Relaxed),
+            self.rate_limited.load(Ordering::Relaxed),
+            self.captcha_failed.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+            *self.dispensed.lock(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetRequest {
+    pub address: String,
+    #[serde(default)]
+    pub captcha_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetReceipt {
+    pub tx_hash: String,
+    pub address: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetStatus {
+    pub faucet_address: String,
+    pub amount: String,
+    pub address_cooldown_secs: u64,
+    pub ip_cooldown_secs: u64,
+}
+
+/// Testnet faucet paying out of a configured account through a node's RPC
+pub struct Faucet {
+    keypair: KeyPair,
+    config: FaucetConfig,
+    rpc: RpcClient,
+    limiter: Mutex<FaucetLimiter>,
+    captcha: Arc<dyn CaptchaVerifier>,
+    metrics: FaucetMetrics,
+}
+
+impl Faucet {
+    pub fn new(keypair: KeyPair, config: FaucetConfig) -> Self {
+        Self {
+            rpc: RpcClient::new(&config.rpc_host, config.rpc_port),
+            limiter: Mutex::new(FaucetLimiter::new(
+                config.address_cooldown,
+                config.ip_cooldown,
+            )),
+            captcha: Arc::new(NoCaptcha),
+            metrics: FaucetMetrics::default(),
+            keypair,
+            config,
+        }
+    }
+
+    pub fn with_captcha(mut self, captcha: Arc<dyn CaptchaVerifier>) -> Self {
+        self.captcha = captcha;
+        self
+    }
+
+    pub fn metrics(&self) -> &FaucetMetrics {
+        &self.metrics
+    }
+
+    pub fn status(&self) -> FaucetStatus {
+        FaucetStatus {
+            faucet_address: self.keypair.to_address().to_string(),
+            amount: encode_amount(self.config.amount),
+            address_cooldown_secs: self.config.address_cooldown.as_secs(),
+            ip_cooldown_secs: self.config.ip_cooldown.as_secs(),
+        }
+    }
+
+    /// Refuse to serve a mainnet node
+    pub async fn check_network(&self) -> anyhow::Result<String> {
+        let network = self.rpc.get_chain_limits().await?.network;
+        if network == "mainnet" {
+            anyhow::bail!("The faucet only runs against test networks");
+        }
+        Ok(network)
+    }
+
+    pub async fn dispense(
+        &self,
+        request: &FaucetRequest,
+        ip: IpAddr,
+    ) -> Result<FaucetReceipt, FaucetError> {
+        self.metrics.requests.fetch_add(1, Ordering::Relaxed);
+
+        if !self.captcha.verify(request.captcha_token.as_deref(), ip) {
+            self.metrics.captcha_failed.fetch_add(1, Ordering::Relaxed);
+            return Err(FaucetError::CaptchaFailed);
+        }
+
+        let address = parse_address(&request.address)
+            .ok_or_else(|| FaucetError::InvalidAddress(request.address.clone()))?;
+
+        if let Err(retry_after) = self.limiter.lock().try_acquire(address, ip, Instant::now()) {
+            self.metrics.rate_limited.fetch_add(1, Ordering::Relaxed);
+            return Err(FaucetError::RateLimited { retry_after });
+        }
+
+        match self.send(address).await {
+            Ok(tx_hash) => {
+                self.metrics.payouts.fetch_add(1, Ordering::Relaxed);
+                *self.metrics.dispensed.lock() += self.config.amount.value();
+                info!(
+                    "🚰 Sent {} QBT to {}",
+                    self.config.amount.to_qbt_string(),
+                    address
+                );
+                Ok(FaucetReceipt {
+                    tx_hash,
+                    address: address.to_string(),
+                    amount: encode_amount(self.config.amount),
+                })
+            }
+            Err(reason) => {
+                self.limiter.lock().release(&address, &ip);
+                self.metrics.failed.fetch_add(1, Ordering::Relaxed);
+                warn!("Faucet payout to {} failed: {}", address, reason);
+                Err(FaucetError::Rejected(reason))
+            }
+        }
+    }
+
+    async fn send(&self, to: Address) -> Result<String, String> {
+        let fork_id = self
+            .rpc
+            .get_status()
+            .await
+            .ok()
+            .and_then(|status| hex::decode(status.fork_id.trim_start_matches("0x")).ok())
+            .and_then(|bytes| Hash::from_slice(&bytes).ok())
+            .ok_or_else(|| "node unreachable".to_string())?;
+
+        let mut tx = Transaction::new(
+            self.keypair.to_address(),
+            to,
+            self.config.amount,
+            Amount::zero(),
+        )
+        .with_purpose("Testnet faucet payout")
+        .with_fork_id(fork_id);
+        tx.fee = tx.min_fee();
+        tx.compute_hash();
+        self.keypair.sign_transaction(&mut tx);
+
+        let response = self
+            .rpc
+            .submit_transaction(&tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.success {
+            return Err(response.message);
+        }
+        Ok(response.tx_hash)
+    }
+}
+
+fn parse_address(address: &str) -> Option<Address> {
+    let bytes = hex::decode(address.trim_start_matches("0x")).ok()?;
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+    Some(Address::new(bytes))
+}
+
+pub struct FaucetServer {
+    faucet: Arc<Faucet>,
+    port: u16,
+}
+
+impl FaucetServer {
+    pub fn new(faucet: Arc<Faucet>, port: u16) -> Self {
+        Self { faucet, port }
+    }
+
+    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🚰 Starting faucet on port {}", self.port);
+
+        let faucet = Arc::clone(&self.faucet);
+        let with_faucet = warp::any().map(move || Arc::clone(&faucet));
+
+        let dispense_route = warp::path("faucet")
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(warp::addr::remote())
+            .and(with_faucet.clone())
+            .and_then(handle_dispense);
+
+        let status_route = warp::path!("faucet" / "status")
+            .and(warp::get())
+            .and(with_faucet.clone())
+            .map(|faucet: Arc<Faucet>| warp::reply::json(&ApiResponse::success(faucet.status())));
+
+        let metrics_route = warp::path("metrics")
+            .and(warp::get())
+            .and(with_faucet)
+            .map(|faucet: Arc<Faucet>| faucet.metrics().export_prometheus());
+
+        let routes = dispense_route.or(status_route).or(metrics_route);
+
+        info!("✅ Faucet ready");
+        info!("   Endpoints:");
+        info!("   - POST /faucet");
+        info!("   - GET /faucet/status");
+        info!("   - GET /metrics");
+
+        warp::serve(routes).run(([0, 0, 0, 0], self.port)).await;
+
+        Ok(())
+    }
+}
+
+async fn handle_dispense(
+    request: FaucetRequest,
+    remote: Option<SocketAddr>,
+    faucet: Arc<Faucet>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let ip = remote
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::from([0, 0, 0, 0]));
+
+    let reply = match faucet.dispense(&request, ip).await {
+        Ok(receipt) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse::success(receipt)),
+            StatusCode::OK,
+        ),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse::<FaucetReceipt>::error(e.to_string())),
+            e.status(),
+        ),
+    };
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_applies_address_and_ip_cooldowns() {
+        let mut limiter = FaucetLimiter::new(Duration::from_secs(100), Duration::from_secs(10));
+        let alice = Address::new([1u8; 32]);
+        let bob = Address::new([2u8; 32]);
+        let home = IpAddr::from([10, 0, 0, 1]);
+        let office = IpAddr::from([10, 0, 0, 2]);
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire(alice, home, start).is_ok());
+        assert_eq!(
+            limiter.try_acquire(bob, home, start + Duration::from_secs(4)),
+            Err(Duration::from_secs(6))
+        );
+        assert_eq!(
+            limiter.try_acquire(alice, office, start + Duration::from_secs(4)),
+            Err(Duration::from_secs(96))
+        );
+        assert!(limiter
+            .try_acquire(bob, home, start + Duration::from_secs(10))
+            .is_ok());
+
+        limiter.release(&bob, &home);
+        assert!(limiter
+            .try_acquire(bob, home, start + Duration::from_secs(11))
+            .is_ok());
+    }
+}
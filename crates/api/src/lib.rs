@@ -1,7 +1,9 @@
+pub mod faucet;
 pub mod handlers;
 pub mod rest;
 pub mod websocket;
 
+pub use faucet::*;
 pub use handlers::*;
 pub use rest::RestServer;
 pub use websocket::*;
@@ -1,12 +1,21 @@
 use serde::{Deserialize, Serialize};
+use spirachain_core::{ErrorCategory, SpiraChainError, Transaction};
+use spirachain_rpc::{BlockDto, TransactionDto};
 use tracing::info;
-use warp::{Filter, Rejection, Reply};
+use warp::Filter;
+
+use crate::handlers::{handle_get_block, handle_get_transaction, handle_status};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    /// Stable error code, same as the RPC server reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<ErrorCategory>,
 }
 
 impl<T> ApiResponse<T> {
@@ -15,6 +24,8 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
+            category: None,
         }
     }
 
@@ -23,6 +34,16 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message),
+            code: None,
+            category: None,
+        }
+    }
+
+    pub fn from_error(error: &SpiraChainError) -> Self {
+        Self {
+            code: Some(error.code()),
+            category: Some(error.category()),
+            ..Self::error(error.public_message())
         }
     }
 }
@@ -38,31 +59,25 @@ pub struct NetworkStatus {
     pub total_staked: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BlockResponse {
-    pub height: u64,
-    pub hash: String,
-    pub previous_hash: String,
-    pub timestamp: u64,
-    pub tx_count: u32,
-    pub validator: String,
-    pub spiral_type: String,
-    pub complexity: f64,
-    pub transactions: Vec<String>,
-}
+/// Blocks use the canonical JSON of the RPC server, see [`spirachain_rpc::types`]
+pub type BlockResponse = BlockDto;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionResponse {
-    pub hash: String,
-    pub from: String,
-    pub to: String,
-    pub amount: String,
-    pub fee: String,
-    pub purpose: Option<String>,
-    pub timestamp: u64,
+    #[serde(flatten)]
+    pub transaction: TransactionDto,
     pub block_height: Option<u64>,
 }
 
+impl TransactionResponse {
+    pub fn new(tx: &Transaction, block_height: Option<u64>) -> Self {
+        Self {
+            transaction: TransactionDto::from(tx),
+            block_height,
+        }
+    }
+}
+
 pub struct RestServer {
     port: u16,
 }
@@ -105,54 +120,3 @@ impl RestServer {
         Ok(())
     }
 }
-
-async fn handle_status() -> Result<impl Reply, Rejection> {
-    let status = NetworkStatus {
-        chain_id: spirachain_core::CHAIN_ID,
-        current_height: 0,
-        peer_count: 0,
-        validator_count: 0,
-        pending_transactions: 0,
-        total_supply: "21000000.0".to_string(),
-        total_staked: "0.0".to_string(),
-    };
-
-    Ok(warp::reply::json(&ApiResponse::success(status)))
-}
-
-async fn handle_get_block(height: u64) -> Result<impl Reply, Rejection> {
-    let block = BlockResponse {
-        height,
-        hash: format!("0x{:064x}", height),
-        previous_hash: format!("0x{:064x}", height.saturating_sub(1)),
-        timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-        tx_count: 0,
-        validator: "0x0000000000000000000000000000000000000000".to_string(),
-        spiral_type: "Fibonacci".to_string(),
-        complexity: 0.85,
-        transactions: vec![],
-    };
-
-    Ok(warp::reply::json(&ApiResponse::success(block)))
-}
-
-async fn handle_get_transaction(hash: String) -> Result<impl Reply, Rejection> {
-    let tx = TransactionResponse {
-        hash: hash.clone(),
-        from: "0x0000000000000000000000000000000000000000".to_string(),
-        to: "0x0000000000000000000000000000000000000000".to_string(),
-        amount: "0.0".to_string(),
-        fee: "0.001".to_string(),
-        purpose: Some("Example transaction".to_string()),
-        timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-        block_height: None,
-    };
-
-    Ok(warp::reply::json(&ApiResponse::success(tx)))
-}
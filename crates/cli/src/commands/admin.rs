@@ -0,0 +1,38 @@
+use anyhow::{anyhow, Result};
+use spirachain_core::GenesisConfig;
+use spirachain_node::reset_testnet;
+use std::fs;
+use std::path::Path;
+
+/// Reset a testnet node: every operator runs this with the same genesis file
+/// while their node is stopped, then starts the node again
+pub fn handle_admin_reset(
+    network: String,
+    genesis_path: String,
+    data_dir: String,
+    reset_identity: bool,
+) -> Result<()> {
+    let json = fs::read_to_string(&genesis_path)
+        .map_err(|e| anyhow!("Could not read {}: {}", genesis_path, e))?;
+    let genesis = GenesisConfig::from_json(&json)
+        .map_err(|e| anyhow!("{} is not a genesis config: {}", genesis_path, e))?;
+
+    let report = reset_testnet(Path::new(&data_dir), &network, &genesis, reset_identity)?;
+
+    println!("✅ {} data reset", network.to_uppercase());
+    println!("   Archived to: {}", report.archive_dir.display());
+    println!("   Archived: {}", report.archived.join(", "));
+    println!("   Kept: {}", report.kept.join(", "));
+    println!("   New genesis: {}", report.genesis_hash);
+    if reset_identity {
+        println!(
+            "   A new PeerId is created on next start; update bootstrap lists that pin the old one"
+        );
+    }
+    println!(
+        "   Start the node with --network {} to join the reset chain",
+        network
+    );
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use anyhow::{anyhow, Result};
+use spirachain_core::{assemble_genesis, GenesisConfig, SignedGenesis, ValidatorRegistration};
+use std::fs;
+
+use super::tx::load_keypair;
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str, what: &str) -> Result<T> {
+    let json = fs::read_to_string(path).map_err(|e| anyhow!("Could not read {}: {}", path, e))?;
+    serde_json::from_str(&json).map_err(|e| anyhow!("{} is not a {}: {}", path, what, e))
+}
+
+/// Template plus registrations, exactly as every party assembles it
+fn assemble(
+    registrations: &[String],
+    template: Option<&str>,
+    timestamp: Option<u64>,
+) -> Result<GenesisConfig> {
+    let mut template = match template {
+        Some(path) => read_json(path, "genesis config")?,
+        None => GenesisConfig::default(),
+    };
+    if let Some(timestamp) = timestamp {
+        template.timestamp = timestamp;
+    }
+    let registrations = registrations
+        .iter()
+        .map(|path| read_json::<ValidatorRegistration>(path, "validator registration"))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(assemble_genesis(&template, &registrations)?)
+}
+
+/// Sign this validator's registration for the coordinator to collect
+pub fn handle_register(
+    wallet: String,
+    name: String,
+    region: String,
+    stake: u128,
+    output: String,
+) -> Result<()> {
+    let keypair = load_keypair(&wallet)?;
+    let mut registration = ValidatorRegistration {
+        name,
+        pubkey: hex::encode(keypair.public_key().0),
+        geographic_region: region,
+        stake,
+        signature: String::new(),
+    };
+    registration.signature = hex::encode(keypair.sign(&registration.signing_message()?));
+    registration.verify(0)?;
+
+    fs::write(&output, serde_json::to_string_pretty(&registration)?)?;
+    println!(
+        "✅ Registration of {} written to {}",
+        registration.name, output
+    );
+    println!("   Validator key: {}", registration.pubkey);
+    println!("   Send it to the ceremony coordinator");
+    Ok(())
+}
+
+/// Build the unsigned genesis from every registration
+pub fn handle_assemble(
+    registrations: Vec<String>,
+    template: Option<String>,
+    timestamp: Option<u64>,
+    output: String,
+) -> Result<()> {
+    let genesis = assemble(&registrations, template.as_deref(), timestamp)?;
+    let signed = SignedGenesis::new(genesis);
+    fs::write(&output, signed.to_json())?;
+
+    println!(
+        "✅ Genesis assembled from {} registrations",
+        registrations.len()
+    );
+    println!("   Written to: {}", output);
+    println!("   Config hash: {}", signed.config_hash);
+    println!("   Genesis hash: {}", signed.genesis_hash);
+    println!("   Every validator now verifies these hashes and runs `spira ceremony sign`");
+    Ok(())
+}
+
+/// Co-sign the genesis, after re-assembling it locally when the
+/// registrations are given
+pub fn handle_sign(
+    genesis_path: String,
+    wallet: String,
+    registrations: Vec<String>,
+    template: Option<String>,
+) -> Result<()> {
+    let mut signed: SignedGenesis = read_json(&genesis_path, "signed genesis")?;
+    signed.verify_hashes()?;
+
+    if !registrations.is_empty() {
+        let local = assemble(
+            &registrations,
+            template.as_deref(),
+            Some(signed.genesis.timestamp),
+        )?;
+        if local.config_hash() != signed.config_hash {
+            return Err(anyhow!(
+                "Genesis differs from the local assembly ({} vs {}); not signing",
+                signed.config_hash,
+                local.config_hash()
+            ));
+        }
+        println!("✅ Genesis matches the local assembly");
+    }
+
+    let keypair = load_keypair(&wallet)?;
+    let signature = keypair.sign(&signed.signing_message());
+    signed.add_signature(&keypair.public_key().0, &signature)?;
+    fs::write(&genesis_path, signed.to_json())?;
+
+    println!(
+        "✅ Signed {} as {}",
+        genesis_path,
+        hex::encode(keypair.public_key().0)
+    );
+    let missing = signed.missing_signers();
+    if missing.is_empty() {
+        println!("   All genesis validators have signed");
+    } else {
+        println!("   Still missing {} signatures", missing.len());
+    }
+    Ok(())
+}
+
+/// Check hashes and signatures; fails until every validator signed
+pub fn handle_verify(genesis_path: String) -> Result<()> {
+    let signed: SignedGenesis = read_json(&genesis_path, "signed genesis")?;
+    signed.verify_hashes()?;
+
+    println!("🌀 Genesis ceremony: {}", genesis_path);
+    println!("   Config hash: {}", signed.config_hash);
+    println!("   Genesis hash: {}", signed.genesis_hash);
+    println!("   Validators:");
+    let missing = signed.missing_signers();
+    for validator in &signed.genesis.initial_validators {
+        let status = if missing
+            .iter()
+            .any(|missing| missing.pubkey == validator.pubkey)
+        {
+            "missing"
+        } else {
+            "signed"
+        };
+        println!(
+            "     {} ({}, {}) stake {}: {}",
+            validator.name,
+            hex::encode(&validator.pubkey),
+            validator.geographic_region,
+            validator.stake,
+            status
+        );
+    }
+
+    signed.verify()?;
+    println!("✅ Fully signed; install it as genesis.json in every node's data directory");
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Wallet address book
+// Named addresses are kept next to the wallet they belong to, in
+// `<wallet>.contacts`, sealed under a key derived from the wallet's secret
+// key: the file tells nobody without the wallet who it pays. `--to @name` on
+// the send commands looks names up here, and transactions the CLI prints name
+// the counterparties it knows.
+
+use super::wallet::WalletFileStore;
+use anyhow::{anyhow, Result};
+use spirachain_core::Address;
+use std::collections::BTreeMap;
+
+/// Domain of the contacts key, derived from the wallet secret key
+const CONTACTS_KEY_CONTEXT: &str = "SpiraChain wallet contacts v1";
+
+/// Prefix of a contact name given where an address is expected
+pub const CONTACT_PREFIX: char = '@';
+
+pub struct AddressBook {
+    store: WalletFileStore,
+    contacts: BTreeMap<String, Address>,
+}
+
+impl AddressBook {
+    /// Contacts of the wallet at `wallet_path`; none before the first is added
+    pub fn open(wallet_path: &str) -> Result<Self> {
+        let store = WalletFileStore::open(wallet_path, "contacts", CONTACTS_KEY_CONTEXT)?;
+        Ok(Self {
+            contacts: store.load()?,
+            store,
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        self.store.save(&self.contacts)
+    }
+
+    /// Name of a saved address, for display
+    pub fn name_of(&self, address: &Address) -> Option<&str> {
+        self.contacts
+            .iter()
+            .find(|(_, saved)| *saved == address)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// `recipient` with an `@name` replaced by the saved address
+    pub fn resolve(&self, recipient: &str) -> Result<String> {
+        let Some(name) = recipient.strip_prefix(CONTACT_PREFIX) else {
+            return Ok(recipient.to_string());
+        };
+        let address = self
+            .contacts
+            .get(&normalize(name)?)
+            .ok_or_else(|| anyhow!("No contact named {}{}", CONTACT_PREFIX, name))?;
+        println!("📇 {}{} is {}", CONTACT_PREFIX, name, address);
+        Ok(address.to_string())
+    }
+}
+
+/// Contact names are case-insensitive and carry no spaces
+fn normalize(name: &str) -> Result<String> {
+    let name = name.trim_start_matches(CONTACT_PREFIX).to_lowercase();
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return Err(anyhow!("Invalid contact name {:?}", name));
+    }
+    Ok(name)
+}
+
+pub fn handle_add(wallet: String, name: String, address: String) -> Result<()> {
+    let address: Address = address
+        .parse()
+        .map_err(|e| anyhow!("Invalid address {}: {}", address, e))?;
+    let name = normalize(&name)?;
+
+    let mut book = AddressBook::open(&wallet)?;
+    match book.contacts.insert(name.clone(), address) {
+        Some(previous) if previous != address => {
+            println!("✅ {}{} changed from {}", CONTACT_PREFIX, name, previous)
+        }
+        _ => println!("✅ Saved {}{} as {}", CONTACT_PREFIX, name, address),
+    }
+    book.save()
+}
+
+pub fn handle_remove(wallet: String, name: String) -> Result<()> {
+    let name = normalize(&name)?;
+    let mut book = AddressBook::open(&wallet)?;
+    if book.contacts.remove(&name).is_none() {
+        return Err(anyhow!("No contact named {}{}", CONTACT_PREFIX, name));
+    }
+    book.save()?;
+    println!("🗑️  Removed {}{}", CONTACT_PREFIX, name);
+    Ok(())
+}
+
+pub fn handle_list(wallet: String) -> Result<()> {
+    let book = AddressBook::open(&wallet)?;
+    if book.contacts.is_empty() {
+        println!("No contacts yet; add one with `spira wallet contacts add`");
+        return Ok(());
+    }
+
+    let width = book.contacts.keys().map(String::len).max().unwrap_or(0) + 1;
+    for (name, address) in &book.contacts {
+        println!(
+            "{:<width$}  {}",
+            format!("{}{}", CONTACT_PREFIX, name),
+            address,
+            width = width
+        );
+    }
+    Ok(())
+}
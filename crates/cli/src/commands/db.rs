@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Result};
+use spirachain_node::{load_genesis, restore_database, verify_chain, BackupManifest, BlockStorage};
+use std::path::Path;
+
+/// Have the running node copy its database into `output`
+pub async fn handle_db_backup(rpc_url: String, output: String) -> Result<()> {
+    // The node resolves relative paths against its own working directory
+    let output = std::path::absolute(&output)?;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(600))
+        .build()?;
+
+    let url = format!("{}/admin/db/backup", rpc_url.trim_end_matches('/'));
+    let response = client
+        .post(&url)
+        .json(&serde_json::json!({ "output": output }))
+        .send()
+        .await
+        .map_err(|e| anyhow!("Could not connect to node at {}: {}", rpc_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        return Err(anyhow!(
+            "Node returned {}: {}",
+            status,
+            body["message"].as_str().unwrap_or("unknown error")
+        ));
+    }
+
+    let manifest: BackupManifest = response.json().await?;
+    println!("✅ Database backed up to {}", output.display());
+    print_manifest(&manifest);
+    Ok(())
+}
+
+/// Replace a stopped node's database with a backup, checked before and after
+pub fn handle_db_restore(input: String, data_dir: String, network: String) -> Result<()> {
+    let report = restore_database(Path::new(&input), Path::new(&data_dir), &network)?;
+
+    println!("✅ Database restored from {}", input);
+    print_manifest(&report.manifest);
+    println!(
+        "   Previous database kept at: {}",
+        report.previous_dir.display()
+    );
+    Ok(())
+}
+
+/// Replay the chain in `data_dir` from genesis; fails on the first divergence
+pub fn handle_verify_chain(data_dir: String, network: String, to: Option<u64>) -> Result<()> {
+    let path = Path::new(&data_dir);
+    let genesis = load_genesis(path, &network)?;
+    let storage = BlockStorage::new(path).map_err(|e| {
+        anyhow!(
+            "Cannot open {} ({}); stop the node before verifying its chain",
+            data_dir,
+            e
+        )
+    })?;
+
+    println!(
+        "🔍 Replaying the {} chain in {} from genesis...",
+        network, data_dir
+    );
+    let report = verify_chain(&storage, &network, genesis.as_ref(), to, |height| {
+        if height > 0 && height % 10_000 == 0 {
+            println!("   ... block {}", height);
+        }
+    })?;
+
+    println!(
+        "   Verified: {} of {} blocks, {} transactions ({} skipped by the chain)",
+        report.verified_height.map_or(0, |height| height + 1),
+        report.target_height + 1,
+        report.transactions,
+        report.skipped_transactions
+    );
+    if let Some(root) = report.state_root {
+        println!("   State root: {}", root);
+    }
+
+    if let Some(divergence) = &report.divergence {
+        println!("❌ First divergence at block {}", divergence.height);
+        if let Some(hash) = divergence.block_hash {
+            println!("   Block: {}", hash);
+        }
+        println!("   Check: {}", divergence.check.name());
+        println!("   Reason: {}", divergence.reason);
+        return Err(anyhow!(
+            "Chain diverges at block {} ({})",
+            divergence.height,
+            divergence.check.name()
+        ));
+    }
+
+    if let Some(stored) = report
+        .stored_state_root
+        .filter(|_| report.stored_state_differs())
+    {
+        println!(
+            "⚠️  Stored accounts hash to {}, not the replayed state",
+            stored
+        );
+        return Err(anyhow!("Stored state does not match the replayed chain"));
+    }
+
+    println!("✅ Chain is consistent");
+    Ok(())
+}
+
+fn print_manifest(manifest: &BackupManifest) {
+    println!("   Network: {}", manifest.network);
+    println!("   Height: {}", manifest.height);
+    println!("   Block: {}", manifest.block_hash);
+    println!("   State root: {}", manifest.state_root);
+    println!("   Schema: v{}", manifest.schema_version);
+}
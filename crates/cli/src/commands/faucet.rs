@@ -0,0 +1,38 @@
+use anyhow::{anyhow, Result};
+use spirachain_api::{Faucet, FaucetConfig, FaucetServer};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub struct FaucetOptions {
+    pub wallet: String,
+    pub port: u16,
+    pub rpc_host: String,
+    pub rpc_port: u16,
+    pub amount: String,
+    pub address_cooldown_hours: u64,
+    pub ip_cooldown_minutes: u64,
+}
+
+pub async fn handle_faucet(options: FaucetOptions) -> Result<()> {
+    let keypair = super::tx::load_keypair(&options.wallet)?;
+    let config = FaucetConfig {
+        amount: super::tx::parse_qbt(&options.amount)?,
+        address_cooldown: Duration::from_secs(options.address_cooldown_hours * 3600),
+        ip_cooldown: Duration::from_secs(options.ip_cooldown_minutes * 60),
+        rpc_host: options.rpc_host,
+        rpc_port: options.rpc_port,
+    };
+
+    let faucet = Faucet::new(keypair, config);
+    let network = faucet.check_network().await?;
+    let status = faucet.status();
+
+    println!("🚰 SpiraChain faucet on {}", network);
+    println!("   Funded account: {}", status.faucet_address);
+    println!("   Payout: {} QBT", options.amount);
+
+    FaucetServer::new(Arc::new(faucet), options.port)
+        .start()
+        .await
+        .map_err(|e| anyhow!("Faucet server error: {}", e))
+}
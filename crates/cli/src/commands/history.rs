@@ -0,0 +1,367 @@
+// Wallet transaction history
+// Every transaction the CLI signs for a wallet is recorded in
+// `<wallet>.history`, sealed like the address book. An entry keeps what the
+// node last said about it: `spira wallet history` asks again for every entry
+// that is not settled, looking for it in the mempool and then in the blocks
+// since it was submitted. One missing from both long after submission was
+// dropped. The history can be exported to CSV, with contact names.
+
+use super::contacts::{AddressBook, CONTACT_PREFIX};
+use super::wallet::WalletFileStore;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Address, Amount, Transaction};
+use spirachain_rpc::{BlockDto, RpcClient};
+use std::collections::HashMap;
+use std::fmt;
+
+const HISTORY_KEY_CONTEXT: &str = "SpiraChain wallet history v1";
+
+/// Blocks after submission before a transaction found nowhere counts as dropped
+const DROPPED_AFTER_BLOCKS: u64 = 100;
+
+/// Blocks searched per refresh, newest first
+const MAX_SCANNED_BLOCKS: u64 = 2_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TxStatus {
+    /// Signed but never accepted by a node
+    NotSubmitted,
+    Rejected {
+        reason: String,
+    },
+    /// Accepted by the node, not yet found again
+    Submitted,
+    Pending,
+    Confirmed {
+        height: u64,
+    },
+    Dropped,
+}
+
+impl TxStatus {
+    /// Whether asking the node again can change it
+    fn is_settled(&self) -> bool {
+        matches!(
+            self,
+            TxStatus::NotSubmitted | TxStatus::Rejected { .. } | TxStatus::Confirmed { .. }
+        )
+    }
+}
+
+impl fmt::Display for TxStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxStatus::NotSubmitted => f.write_str("not submitted"),
+            TxStatus::Rejected { reason } => write!(f, "rejected ({})", reason),
+            TxStatus::Submitted => f.write_str("submitted"),
+            TxStatus::Pending => f.write_str("pending"),
+            TxStatus::Confirmed { height } => write!(f, "confirmed in {}", height),
+            TxStatus::Dropped => f.write_str("dropped"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub hash: String,
+    /// Milliseconds, as signed
+    pub timestamp: u64,
+    pub to: Address,
+    pub amount: Amount,
+    pub fee: Amount,
+    pub purpose: String,
+    #[serde(flatten)]
+    pub status: TxStatus,
+    /// Chain height when the node accepted it; blocks above are searched
+    pub submitted_at: Option<u64>,
+}
+
+pub struct TxHistory {
+    store: WalletFileStore,
+    entries: Vec<HistoryEntry>,
+}
+
+impl TxHistory {
+    pub fn open(wallet_path: &str) -> Result<Self> {
+        let store = WalletFileStore::open(wallet_path, "history", HISTORY_KEY_CONTEXT)?;
+        Ok(Self {
+            entries: store.load()?,
+            store,
+        })
+    }
+
+    /// Record `tx`, replacing an earlier entry for the same hash
+    pub fn record(&mut self, tx: &Transaction, status: TxStatus, submitted_at: Option<u64>) {
+        let hash = tx.tx_hash.to_string();
+        self.entries.retain(|entry| entry.hash != hash);
+        self.entries.push(HistoryEntry {
+            hash,
+            timestamp: tx.timestamp,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            purpose: tx.purpose.clone(),
+            status,
+            submitted_at,
+        });
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.store.save(&self.entries)
+    }
+
+    /// Ask the node about every unsettled entry; returns how many changed
+    pub async fn refresh(&mut self, client: &RpcClient) -> Result<usize> {
+        let open: Vec<usize> = (0..self.entries.len())
+            .filter(|index| !self.entries[*index].status.is_settled())
+            .collect();
+        if open.is_empty() {
+            return Ok(0);
+        }
+
+        let tip = client.get_status().await?.chain_height;
+        let pending = mempool_hashes(client).await?;
+        let from = open
+            .iter()
+            .filter_map(|index| self.entries[*index].submitted_at)
+            .min()
+            .unwrap_or(0)
+            .max(tip.saturating_sub(MAX_SCANNED_BLOCKS));
+        let mut wanted: HashMap<String, Option<u64>> = open
+            .iter()
+            .map(|index| (self.entries[*index].hash.clone(), None))
+            .collect();
+        for height in (from..=tip).rev() {
+            let block: BlockDto = serde_json::from_value(client.get_block(height).await?.block)?;
+            for tx in &block.transactions {
+                if let Some(found) = wanted.get_mut(&tx.hash) {
+                    *found = Some(height);
+                }
+            }
+        }
+
+        let mut changed = 0;
+        for index in open {
+            let entry = &mut self.entries[index];
+            let in_mempool = pending.contains(&entry.hash);
+            let Some(status) = observed_status(entry, wanted[&entry.hash], in_mempool, tip) else {
+                continue;
+            };
+            if entry.status != status {
+                entry.status = status;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Status of an unsettled `entry` given where the node has it: in the block at
+/// `found_at`, in its mempool, or nowhere with the chain at `tip`. `None` when
+/// that says nothing new yet.
+fn observed_status(
+    entry: &HistoryEntry,
+    found_at: Option<u64>,
+    in_mempool: bool,
+    tip: u64,
+) -> Option<TxStatus> {
+    match found_at {
+        Some(height) => Some(TxStatus::Confirmed { height }),
+        None if in_mempool => Some(TxStatus::Pending),
+        None if tip > entry.submitted_at.unwrap_or(tip) + DROPPED_AFTER_BLOCKS => {
+            Some(TxStatus::Dropped)
+        }
+        None => None,
+    }
+}
+
+async fn mempool_hashes(client: &RpcClient) -> Result<Vec<String>> {
+    let mut hashes = Vec::new();
+    let mut page = 0;
+    loop {
+        let content = client.get_mempool_content(page).await?;
+        hashes.extend(content.transactions.into_iter().map(|tx| tx.hash));
+        page += 1;
+        if page * content.page_size >= content.total || content.page_size == 0 {
+            return Ok(hashes);
+        }
+    }
+}
+
+/// Record a transaction signed for the wallet at `wallet_path`. History is a
+/// convenience: failing to write it never fails the command.
+pub fn record(wallet_path: &str, tx: &Transaction, status: TxStatus, submitted_at: Option<u64>) {
+    let result = TxHistory::open(wallet_path).and_then(|mut history| {
+        history.record(tx, status, submitted_at);
+        history.save()
+    });
+    if let Err(e) = result {
+        eprintln!("⚠️  Could not update the wallet history: {}", e);
+    }
+}
+
+/// `to` as shown in the history, with its contact name when there is one
+fn recipient(contacts: &AddressBook, to: &Address) -> String {
+    match contacts.name_of(to) {
+        Some(name) => format!("{}{}", CONTACT_PREFIX, name),
+        None => to.to_string(),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One line of the CSV export, `contact` being the name saved for `entry.to`
+fn csv_row(entry: &HistoryEntry, contact: Option<&str>) -> String {
+    let height = match entry.status {
+        TxStatus::Confirmed { height } => height.to_string(),
+        _ => String::new(),
+    };
+    let row = [
+        entry.hash.clone(),
+        entry.timestamp.to_string(),
+        entry.to.to_string(),
+        contact.unwrap_or_default().to_string(),
+        entry.amount.to_qbt_string(),
+        entry.fee.to_qbt_string(),
+        entry.purpose.clone(),
+        entry.status.to_string(),
+        height,
+    ];
+    let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+    row.join(",")
+}
+
+pub async fn handle_history(
+    wallet: String,
+    rpc_host: String,
+    rpc_port: u16,
+    offline: bool,
+    csv: Option<String>,
+) -> Result<()> {
+    let mut history = TxHistory::open(&wallet)?;
+    let contacts = AddressBook::open(&wallet)?;
+
+    if !offline {
+        let client = RpcClient::new(&rpc_host, rpc_port);
+        match history.refresh(&client).await {
+            Ok(0) => {}
+            Ok(changed) => {
+                history.save()?;
+                println!("🔄 {} transaction(s) changed status", changed);
+            }
+            Err(e) => println!("⚠️  Showing the last known status, node unreachable: {}", e),
+        }
+    }
+
+    if let Some(path) = csv {
+        let mut out = String::from(
+            "hash,timestamp_ms,to,contact,amount_qbt,fee_qbt,purpose,status,block_height\n",
+        );
+        for entry in &history.entries {
+            out.push_str(&csv_row(entry, contacts.name_of(&entry.to)));
+            out.push('\n');
+        }
+        std::fs::write(&path, out)?;
+        println!(
+            "✅ {} transaction(s) exported to {}",
+            history.entries.len(),
+            path
+        );
+        return Ok(());
+    }
+
+    if history.entries.is_empty() {
+        println!("No transactions sent from this wallet yet");
+        return Ok(());
+    }
+    for entry in history.entries.iter().rev() {
+        println!("{}  {}", entry.hash, entry.status);
+        println!(
+            "    {} QBT to {} (fee {} QBT)",
+            entry.amount.to_qbt_string(),
+            recipient(&contacts, &entry.to),
+            entry.fee.to_qbt_string()
+        );
+        if !entry.purpose.is_empty() {
+            println!("    Purpose: {}", entry.purpose);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(status: TxStatus, submitted_at: Option<u64>) -> HistoryEntry {
+        HistoryEntry {
+            hash: "ab12".to_string(),
+            timestamp: 1_700_000_000_000,
+            to: Address::new([7; 32]),
+            amount: Amount::from_millis(12_500),
+            fee: Amount::from_millis(1),
+            purpose: "rent, \"March\"".to_string(),
+            status,
+            submitted_at,
+        }
+    }
+
+    #[test]
+    fn test_observed_status() {
+        let submitted = entry(TxStatus::Submitted, Some(1_000));
+
+        assert_eq!(
+            observed_status(&submitted, Some(1_004), true, 1_010),
+            Some(TxStatus::Confirmed { height: 1_004 })
+        );
+        assert_eq!(
+            observed_status(&submitted, None, true, 5_000),
+            Some(TxStatus::Pending)
+        );
+        // Missing from both, but not for long enough to give up on it
+        assert_eq!(
+            observed_status(&submitted, None, false, 1_000 + DROPPED_AFTER_BLOCKS),
+            None
+        );
+        assert_eq!(
+            observed_status(&submitted, None, false, 1_001 + DROPPED_AFTER_BLOCKS),
+            Some(TxStatus::Dropped)
+        );
+        // Without a submission height there is nothing to count from
+        assert_eq!(
+            observed_status(&entry(TxStatus::Submitted, None), None, false, 5_000),
+            None
+        );
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let to = Address::new([7; 32]);
+
+        assert_eq!(
+            csv_row(&entry(TxStatus::Confirmed { height: 42 }, Some(40)), Some("alice")),
+            format!(
+                "ab12,1700000000000,{},alice,12.5,0.001,\"rent, \"\"March\"\"\",confirmed in 42,42",
+                to
+            )
+        );
+        assert_eq!(
+            csv_row(
+                &entry(TxStatus::Rejected { reason: "fee too low".to_string() }, None),
+                None
+            ),
+            format!(
+                "ab12,1700000000000,{},,12.5,0.001,\"rent, \"\"March\"\"\",rejected (fee too low),",
+                to
+            )
+        );
+    }
+}
@@ -0,0 +1,430 @@
+// Validator key migration
+// Moving a validator to a new key (and usually a new machine) without ever
+// having both keys sign the same slot:
+//   1. export  - on the stopped old node: retire the old key in its signing
+//                protection file and write a handover with its last signed block
+//   2. import  - on the new node: start the new key's watermark above that block
+//   3. rotate  - move the stake to the new key on chain, proven by both keys
+//   4. sweep   - once the rotation executed, move balance and rewards over
+//   5. verify  - check on chain that nothing is left behind
+
+use super::tx::{account_nonce, chain_fork_id, load_keypair, parse_qbt, submit_to_local_node};
+use anyhow::{anyhow, Result};
+use spirachain_core::{
+    key_rotation_message, signed_message_preimage, Address, Amount, Hash, Transaction,
+    TransactionPayload,
+};
+use spirachain_node::{SigningHandover, SigningProtection, SIGNING_PROTECTION_FILE};
+use spirachain_rpc::RpcClient;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+pub async fn handle_export(wallet: String, data_dir: String, output: String) -> Result<()> {
+    let keypair = load_keypair(&wallet)?;
+    let validator = keypair.to_address();
+
+    // A running node keeps its watermarks in memory and would write the
+    // retirement away with its next block
+    let rpc_client = RpcClient::new("127.0.0.1", 9933);
+    if let Ok(true) = rpc_client.health_check().await {
+        return Err(anyhow!(
+            "A node is running on port 9933; stop it before exporting, or it keeps signing with the old key"
+        ));
+    }
+
+    let protection =
+        SigningProtection::load(Path::new(&data_dir).join(SIGNING_PROTECTION_FILE), false)?;
+    let last_signed = protection.retire(&validator)?;
+    let handover = SigningHandover {
+        validator,
+        public_key: hex::encode(keypair.public_key().as_bytes()),
+        last_signed,
+        exported_at: unix_secs(),
+    };
+    fs::write(&output, serde_json::to_string_pretty(&handover)?)?;
+
+    println!("✅ Validator {} retired in {}", validator, data_dir);
+    match last_signed {
+        Some(last) => println!(
+            "   Last signed: height {}, slot {} ({})",
+            last.height, last.slot, last.block_hash
+        ),
+        None => println!("   Last signed: nothing"),
+    }
+    println!("   Handover written to {}", output);
+    println!(
+        "   Next, on the new node: spira validator migrate import --handover {} --wallet <new wallet>",
+        output
+    );
+
+    Ok(())
+}
+
+pub fn handle_import(handover: String, wallet: String, data_dir: String) -> Result<()> {
+    let handover: SigningHandover = serde_json::from_str(&fs::read_to_string(&handover)?)?;
+    let new_key = load_keypair(&wallet)?.to_address();
+    if new_key == handover.validator {
+        return Err(anyhow!(
+            "{} is the key being retired; import the handover with the new wallet",
+            new_key
+        ));
+    }
+
+    let protection =
+        SigningProtection::load(Path::new(&data_dir).join(SIGNING_PROTECTION_FILE), false)?;
+    // Starting this node with the old wallet by mistake must not sign either
+    protection.retire(&handover.validator)?;
+    if let Some(last) = &handover.last_signed {
+        protection.inherit(&new_key, last)?;
+    }
+
+    println!(
+        "✅ Handover from {} imported into {}",
+        handover.validator, data_dir
+    );
+    match handover.last_signed {
+        Some(last) => println!(
+            "   {} signs above height {}, slot {}",
+            new_key, last.height, last.slot
+        ),
+        None => println!("   The old key never signed a block"),
+    }
+    println!(
+        "   Next: spira validator migrate rotate --wallet <old wallet> --new-wallet {}",
+        wallet
+    );
+
+    Ok(())
+}
+
+pub async fn handle_rotate(wallet: String, new_wallet: String, fee: Option<String>) -> Result<()> {
+    info!("📤 Creating validator key rotation");
+
+    let keypair = load_keypair(&wallet)?;
+    let new_keypair = load_keypair(&new_wallet)?;
+    let validator = keypair.to_address();
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    let rpc_client = RpcClient::new("127.0.0.1", 9933);
+    if let Ok(info) = rpc_client.get_validator_info(&validator.to_string()).await {
+        if Amount::new(info.stake.parse()?).is_zero() {
+            return Err(anyhow!("{} has no stake to rotate", validator));
+        }
+    }
+
+    let proof = new_keypair.sign(&signed_message_preimage(&key_rotation_message(&validator)));
+    let mut tx = Transaction::new_rotate_validator_key(
+        validator,
+        *new_keypair.public_key().as_bytes(),
+        proof,
+        fee,
+    );
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Key rotation created:");
+    println!("   Old key: {}", validator);
+    println!("   New key: {}", tx.to);
+    println!("   Fee: {}", fee);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&wallet, &tx).await
+}
+
+pub async fn handle_sweep(
+    wallet: String,
+    to: String,
+    fee: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    info!("📤 Sweeping the retired validator key");
+
+    let keypair = load_keypair(&wallet)?;
+    let old = keypair.to_address();
+    let to: Address = to.parse().map_err(|e| anyhow!("Invalid address: {}", e))?;
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    // Sweeping before the rotation executed would leave the stake behind, and
+    // anything still pending from the old key would race the sweep for its
+    // balance and nonce
+    let rpc_client = RpcClient::new("127.0.0.1", 9933);
+    let info = rpc_client.get_validator_info(&old.to_string()).await?;
+    if !Amount::new(info.stake.parse()?).is_zero() {
+        return Err(anyhow!(
+            "The key rotation has not executed yet; {} still holds stake",
+            old
+        ));
+    }
+    let pending = pending_from(&rpc_client, &old).await?;
+    if pending > 0 {
+        return Err(anyhow!(
+            "{} transaction(s) from {} are still pending; sweep once they are included",
+            pending,
+            old
+        ));
+    }
+
+    let rewards = Amount::new(
+        rpc_client
+            .get_rewards(&old.to_string())
+            .await?
+            .unclaimed_rewards
+            .parse()?,
+    );
+    let balance = Amount::new(
+        rpc_client
+            .get_balance(&old.to_string())
+            .await?
+            .balance
+            .parse()?,
+    );
+    let sweep = plan_sweep(
+        old,
+        to,
+        balance,
+        rewards,
+        fee,
+        chain_fork_id("127.0.0.1", 9933).await,
+        account_nonce("127.0.0.1", 9933, &old).await,
+    );
+
+    if sweep.is_empty() {
+        println!("✅ Nothing to sweep from {}", old);
+        return Ok(());
+    }
+    for mut tx in sweep.iter().cloned() {
+        if tx.payload == TransactionPayload::ClaimRewards {
+            println!("💰 Claiming {} of unclaimed rewards to {}", rewards, to);
+        } else {
+            println!("💸 Transferring {} to {}", tx.amount, to);
+        }
+        if !dry_run {
+            keypair.sign_transaction(&mut tx);
+            submit_to_local_node(&wallet, &tx).await?;
+        }
+    }
+
+    if dry_run {
+        println!("\n🔍 Dry run: nothing was signed or submitted");
+    } else {
+        println!(
+            "\n⏳ The sweep is complete once {}'s nonce reaches {}",
+            old,
+            info.nonce + sweep.len() as u64
+        );
+        println!(
+            "   Check with: spira validator migrate verify --old {} --new {}",
+            old, to
+        );
+    }
+
+    Ok(())
+}
+
+/// Unsigned transactions moving everything `old` holds to `to`: its rewards
+/// when they are worth the fee, then its balance, at consecutive nonces
+fn plan_sweep(
+    old: Address,
+    to: Address,
+    balance: Amount,
+    rewards: Amount,
+    fee: Amount,
+    fork_id: Hash,
+    nonce: Option<u64>,
+) -> Vec<Transaction> {
+    let mut sweep = Vec::new();
+    if rewards > fee {
+        sweep.push(Transaction::new_claim_rewards(old, to, fee));
+    }
+    if let Some(amount) = balance.checked_sub(fee).filter(|amount| !amount.is_zero()) {
+        sweep.push(Transaction::new(old, to, amount, fee));
+    }
+    for (sent, tx) in sweep.iter_mut().enumerate() {
+        tx.fork_id = fork_id;
+        tx.nonce = nonce.map(|nonce| nonce + sent as u64);
+        tx.compute_hash();
+    }
+    sweep
+}
+
+pub async fn handle_verify(old: String, new: String) -> Result<()> {
+    let old: Address = old.parse().map_err(|e| anyhow!("Invalid address: {}", e))?;
+    let new: Address = new.parse().map_err(|e| anyhow!("Invalid address: {}", e))?;
+    let rpc_client = RpcClient::new("127.0.0.1", 9933);
+    let amount = |value: &str| -> Result<Amount> { Ok(Amount::new(value.parse()?)) };
+
+    let old_info = rpc_client.get_validator_info(&old.to_string()).await?;
+    let new_info = rpc_client.get_validator_info(&new.to_string()).await?;
+    let old_balance = amount(&rpc_client.get_balance(&old.to_string()).await?.balance)?;
+    let old_rewards = amount(
+        &rpc_client
+            .get_rewards(&old.to_string())
+            .await?
+            .unclaimed_rewards,
+    )?;
+    let pending = pending_from(&rpc_client, &old).await?;
+
+    let checks = [
+        (
+            "Stake moved off the old key",
+            amount(&old_info.stake)?.is_zero(),
+        ),
+        (
+            "New key holds the stake",
+            !amount(&new_info.stake)?.is_zero(),
+        ),
+        ("Old key's rewards claimed", old_rewards.is_zero()),
+        (
+            "Old key's balance swept",
+            old_balance.value() < spirachain_core::MIN_TX_FEE,
+        ),
+        ("Nothing pending from the old key", pending == 0),
+    ];
+
+    println!("Validator migration {} → {}", old, new);
+    for (check, passed) in &checks {
+        println!("   {} {}", if *passed { "✅" } else { "❌" }, check);
+    }
+    println!(
+        "   New key stake: {} QBT",
+        amount(&new_info.stake)?.to_qbt_string()
+    );
+    if new_info.payout_address != new_info.address {
+        println!("   Rewards to: {}", new_info.payout_address);
+    }
+
+    if checks.iter().all(|(_, passed)| *passed) {
+        println!("✅ Migration complete");
+        Ok(())
+    } else {
+        Err(anyhow!("Migration not complete yet"))
+    }
+}
+
+/// Transactions from `address` still waiting in the node's mempool
+async fn pending_from(rpc_client: &RpcClient, address: &Address) -> Result<usize> {
+    let address = address.to_string();
+    let mut pending = 0;
+    let mut page = 0;
+    loop {
+        let content = rpc_client.get_mempool_content(page).await?;
+        pending += content
+            .transactions
+            .iter()
+            .filter(|tx| tx.from == address)
+            .count();
+        page += 1;
+        if content.transactions.is_empty() || page * content.page_size >= content.total {
+            return Ok(pending);
+        }
+    }
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_crypto::KeyPair;
+    use spirachain_node::SignedBlockRecord;
+
+    fn wallet(dir: &Path, name: &str, secret: u8) -> (String, Address) {
+        let path = dir.join(name);
+        fs::write(
+            &path,
+            serde_json::json!({ "secret_key": hex::encode([secret; 32]) }).to_string(),
+        )
+        .unwrap();
+        let address = KeyPair::from_secret([secret; 32]).unwrap().to_address();
+        (path.to_string_lossy().into_owned(), address)
+    }
+
+    #[test]
+    fn test_dry_run_sweep_claims_then_transfers_unsigned() {
+        let (old, to) = (Address::new([1; 32]), Address::new([2; 32]));
+        let fee = Amount::from_millis(1);
+        let fork_id = Hash::new([9; 32]);
+
+        let sweep = plan_sweep(old, to, Amount::qbt(10), Amount::qbt(2), fee, fork_id, Some(4));
+
+        assert_eq!(sweep.len(), 2);
+        assert_eq!(sweep[0].payload, TransactionPayload::ClaimRewards);
+        assert_eq!(sweep[1].amount, Amount::qbt(10).checked_sub(fee).unwrap());
+        assert_eq!(sweep[0].nonce, Some(4));
+        assert_eq!(sweep[1].nonce, Some(5));
+        for tx in &sweep {
+            assert_eq!((tx.from, tx.to, tx.fork_id), (old, to, fork_id));
+            assert!(tx.signature.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_already_swept_key_has_nothing_to_sweep() {
+        let fee = Amount::from_millis(1);
+        let sweep = plan_sweep(
+            Address::new([1; 32]),
+            Address::new([2; 32]),
+            fee,
+            fee,
+            fee,
+            Hash::zero(),
+            Some(7),
+        );
+        assert!(sweep.is_empty());
+    }
+
+    #[test]
+    fn test_import_into_an_already_migrated_data_dir() {
+        let dir = std::env::temp_dir().join(format!("spirachain-cli-migrate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (_, old) = wallet(&dir, "old.json", 1);
+        let (new_wallet, new) = wallet(&dir, "new.json", 2);
+        let handover = dir.join("handover.json");
+        let last = SignedBlockRecord {
+            height: 10,
+            slot: 40,
+            block_hash: Hash::new([7; 32]),
+            signed_at: 1_700_000_000,
+        };
+        fs::write(
+            &handover,
+            serde_json::to_string(&SigningHandover {
+                validator: old,
+                public_key: String::new(),
+                last_signed: Some(last),
+                exported_at: 1_700_000_000,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        let data_dir = dir.join("node").to_string_lossy().into_owned();
+        let import = || {
+            handle_import(
+                handover.to_string_lossy().into_owned(),
+                new_wallet.clone(),
+                data_dir.clone(),
+            )
+        };
+
+        import().unwrap();
+        // Importing again changes nothing: the new key stays right above the old
+        import().unwrap();
+        let protection =
+            SigningProtection::load(Path::new(&data_dir).join(SIGNING_PROTECTION_FILE), false)
+                .unwrap();
+        assert_eq!(protection.last_signed(&new), Some(last));
+        assert!(protection.check(&new, 10, 41).is_err());
+        assert!(protection.check(&new, 11, 41).is_ok());
+        assert!(protection.check(&old, 11, 41).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,8 +1,18 @@
+pub mod admin;
 pub mod calculate;
+pub mod ceremony;
+pub mod contacts;
+pub mod db;
+pub mod faucet;
 pub mod genesis;
+pub mod history;
 pub mod init;
+pub mod migrate;
+pub mod net;
 pub mod node;
 pub mod query;
+pub mod service;
+pub mod spec;
 pub mod tx;
 pub mod validator;
 pub mod wallet;
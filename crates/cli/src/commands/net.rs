@@ -0,0 +1,137 @@
+use anyhow::{anyhow, Result};
+use spirachain_network::{
+    load_identity, node_key_path, rotate_identity, write_private_file, PeerId,
+    SignedTopologySnapshot,
+};
+use std::fs;
+use std::path::Path;
+
+/// Fetch the node's signed topology snapshot, for attaching to fork/partition bug reports
+pub async fn handle_net_graph(rpc_url: String, output: Option<String>) -> Result<()> {
+    let signed = fetch_topology(&rpc_url).await?;
+
+    let json = serde_json::to_string_pretty(&signed)?;
+    match output {
+        Some(path) => {
+            fs::write(&path, json)?;
+
+            let snapshot = &signed.snapshot;
+            println!("✅ Signed topology snapshot written to {}", path);
+            println!("   Network: {}", snapshot.network);
+            println!("   Peer ID: {}", snapshot.local_peer_id);
+            println!("   Height: {}", snapshot.local_height);
+            println!("   Peers: {}", snapshot.peers.len());
+            println!("   Signed by: {}", signed.signer);
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// List the node's peers with their ping round-trip times
+pub async fn handle_net_peers(rpc_url: String) -> Result<()> {
+    let snapshot = fetch_topology(&rpc_url).await?.snapshot;
+    let millis = |ms: Option<u64>| {
+        ms.map(|ms| format!("{} ms", ms))
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    println!(
+        "{} peers of {} at height {}",
+        snapshot.peers.len(),
+        snapshot.local_peer_id,
+        snapshot.local_height
+    );
+    println!(
+        "{:<54} {:<10} {:>8} {:>10} {:>10} {:>7}",
+        "PEER", "ROLE", "HEIGHT", "RTT AVG", "RTT LAST", "MISSED"
+    );
+    for peer in &snapshot.peers {
+        println!(
+            "{:<54} {:<10} {:>8} {:>10} {:>10} {:>7}",
+            peer.peer_id,
+            peer.role.as_deref().unwrap_or("-"),
+            peer.height
+                .map(|h| h.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            millis(peer.latency_ms),
+            millis(peer.last_latency_ms),
+            peer.missed_pings
+        );
+    }
+
+    Ok(())
+}
+
+async fn fetch_topology(rpc_url: &str) -> Result<SignedTopologySnapshot> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()?;
+
+    let url = format!("{}/admin/network_graph", rpc_url.trim_end_matches('/'));
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| anyhow!("Could not connect to node at {}: {}", rpc_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        return Err(anyhow!(
+            "Node returned {}: {}",
+            status,
+            body["message"].as_str().unwrap_or("unknown error")
+        ));
+    }
+
+    let signed: SignedTopologySnapshot = response.json().await?;
+    if !signed.verify() {
+        return Err(anyhow!("Topology snapshot signature is invalid"));
+    }
+    Ok(signed)
+}
+
+/// Write the node key and its PeerId to `output`, e.g. to move a node to a new host
+pub fn handle_export_identity(data_dir: String, output: String) -> Result<()> {
+    let path = node_key_path(Path::new(&data_dir));
+    if !path.exists() {
+        return Err(anyhow!(
+            "No node identity at {} (it is created on first start)",
+            path.display()
+        ));
+    }
+
+    let keypair = load_identity(&path)?;
+    let peer_id = PeerId::from(keypair.public());
+    let encoded = keypair
+        .to_protobuf_encoding()
+        .map_err(|e| anyhow!("Could not encode node key: {}", e))?;
+
+    let json = serde_json::to_string_pretty(&serde_json::json!({
+        "peer_id": peer_id.to_string(),
+        "key_type": "ed25519",
+        "private_key": hex::encode(encoded),
+    }))?;
+    write_private_file(Path::new(&output), json.as_bytes())?;
+
+    println!("✅ Node identity exported to {}", output);
+    println!("   Peer ID: {}", peer_id);
+    println!("   ⚠️  Anyone with this file can impersonate the node");
+
+    Ok(())
+}
+
+pub fn handle_rotate_identity(data_dir: String) -> Result<()> {
+    let (old, new) = rotate_identity(Path::new(&data_dir))?;
+
+    println!("✅ Node identity rotated");
+    if let Some(old) = old {
+        println!("   Old Peer ID: {}", old);
+    }
+    println!("   New Peer ID: {}", new);
+    println!("   Restart the node to use it; update bootstrap lists that pin the old ID");
+
+    Ok(())
+}
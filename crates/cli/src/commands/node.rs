@@ -2,16 +2,21 @@ use anyhow::Result;
 use spirachain_consensus::Validator;
 use spirachain_core::Amount;
 use spirachain_crypto::KeyPair;
-use spirachain_node::{NodeConfig, ValidatorNode};
+use spirachain_node::{NodeConfig, NodeType, ValidatorNode};
 use std::fs;
 use tracing::info;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_node_start(
     validator_mode: bool,
     wallet_path: Option<String>,
     data_dir: Option<String>,
     port: u16,
     network: Option<String>,
+    allow_double_sign: bool,
+    dry_run: bool,
+    encrypt_at_rest: bool,
+    privacy_mode: bool,
 ) -> Result<()> {
     let _ = tracing_subscriber::fmt::try_init();
 
@@ -20,7 +25,9 @@ pub async fn handle_node_start(
     info!("🚀 Starting SpiraChain Node");
     info!(
         "   Mode: {}",
-        if validator_mode {
+        if dry_run {
+            "Validator (dry run)"
+        } else if validator_mode {
             "Validator"
         } else {
             "Full Node"
@@ -34,9 +41,14 @@ pub async fn handle_node_start(
     }
     config.network_addr = format!("0.0.0.0:{}", port);
     config.network = network_type;
+    config.allow_double_sign = allow_double_sign;
+    config.dry_run = dry_run;
+    config.encrypt_at_rest = encrypt_at_rest;
+    config.privacy_mode = privacy_mode;
     info!("   P2P Port: {}", port);
 
     if validator_mode {
+        config.node_type = NodeType::Validator;
         let wallet_file = wallet_path.as_deref().unwrap_or("validator_wallet.json");
 
         if !std::path::Path::new(wallet_file).exists() {
@@ -73,6 +85,7 @@ pub async fn handle_node_start(
         info!("   Address: {}", validator.address);
 
         let mut node = ValidatorNode::new(config, keypair)?;
+        node.set_log_level_setter(std::sync::Arc::new(crate::logging::set_log_level));
 
         info!("🎬 Starting validator node...");
         node.start().await?;
@@ -21,3 +21,26 @@ pub async fn handle_semantic_query(query: String, limit: usize) -> Result<()> {
 
     Ok(())
 }
+
+pub async fn handle_version_query() -> Result<()> {
+    let local = spirachain_node::version_info("-");
+    println!("CLI:  v{} ({})", local.version, local.git_commit);
+    println!("      Features: {}", local.features.join(", "));
+
+    let rpc_client = spirachain_rpc::RpcClient::new("127.0.0.1", 9933);
+    match rpc_client.get_version().await {
+        Ok(node) => {
+            println!("Node: v{} ({})", node.version, node.git_commit);
+            println!("      Built at: {}", node.build_timestamp);
+            println!("      Features: {}", node.features.join(", "));
+            println!("      Protocol: v{}", node.protocol_version);
+            println!("      Network: {}", node.network);
+            if node.git_commit != local.git_commit {
+                println!("\n⚠️  The node runs a different build than this CLI");
+            }
+        }
+        Err(e) => println!("\n(Node unreachable: {})", e),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,491 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Seconds the service manager waits for a graceful stop before killing the node
+const STOP_TIMEOUT_SECS: u64 = 30;
+
+pub struct ServiceOptions {
+    pub name: String,
+    pub wallet: String,
+    pub data_dir: String,
+    pub port: u16,
+    pub network: String,
+    pub log_file: Option<String>,
+    pub output: Option<String>,
+}
+
+pub async fn handle_install_service(options: ServiceOptions) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let wallet = absolute(&options.wallet)?;
+    let data_dir = absolute(&options.data_dir)?;
+    let log_file = match &options.log_file {
+        Some(path) => absolute(path)?,
+        None => data_dir.join("logs").join("node.log"),
+    };
+
+    if !wallet.exists() {
+        return Err(anyhow!("Wallet file not found: {}", wallet.display()));
+    }
+    fs::create_dir_all(&data_dir)?;
+
+    let args = vec![
+        "node".to_string(),
+        "--validator".to_string(),
+        "--wallet".to_string(),
+        wallet.display().to_string(),
+        "--data-dir".to_string(),
+        data_dir.display().to_string(),
+        "--port".to_string(),
+        options.port.to_string(),
+        "--network".to_string(),
+        options.network.clone(),
+        "--log-file".to_string(),
+        log_file.display().to_string(),
+    ];
+
+    println!("🛠️  Installing SpiraChain node service '{}'", options.name);
+    println!("   Binary: {}", exe.display());
+    println!("   Data dir: {}", data_dir.display());
+    println!("   Log file: {} (rotated)", log_file.display());
+
+    if cfg!(target_os = "linux") {
+        install_systemd(&options, &exe, &data_dir, &args)
+    } else if cfg!(target_os = "macos") {
+        install_launchd(&options, &exe, &data_dir, &args)
+    } else if cfg!(target_os = "windows") {
+        install_windows(&options, &exe, &data_dir, &log_file, &args)
+    } else {
+        Err(anyhow!(
+            "Service installation is not supported on this platform"
+        ))
+    }
+}
+
+fn install_systemd(
+    options: &ServiceOptions,
+    exe: &Path,
+    data_dir: &Path,
+    args: &[String],
+) -> Result<()> {
+    let unit = systemd_unit(&options.network, exe, data_dir, args);
+
+    let file_name = format!("{}.service", options.name);
+    match &options.output {
+        Some(dir) => {
+            let path = write_service_file(dir, &file_name, &unit)?;
+            println!("✅ Wrote systemd unit: {}", path.display());
+            println!(
+                "   Install with: sudo cp {} /etc/systemd/system/ && sudo systemctl daemon-reload && sudo systemctl enable --now {}",
+                path.display(),
+                options.name
+            );
+        }
+        None => {
+            let path = write_service_file("/etc/systemd/system", &file_name, &unit)?;
+            println!("✅ Wrote systemd unit: {}", path.display());
+
+            run("systemctl", &["daemon-reload"])?;
+            run("systemctl", &["enable", &options.name])?;
+
+            println!("✅ Service registered");
+            println!("   Start:  sudo systemctl start {}", options.name);
+            println!("   Stop:   sudo systemctl stop {}", options.name);
+            println!("   Status: systemctl status {}", options.name);
+        }
+    }
+
+    Ok(())
+}
+
+fn install_launchd(
+    options: &ServiceOptions,
+    exe: &Path,
+    data_dir: &Path,
+    args: &[String],
+) -> Result<()> {
+    let label = format!("com.spirachain.{}", options.name);
+    let plist = launchd_plist(&label, exe, data_dir, args);
+
+    let file_name = format!("{}.plist", label);
+    match &options.output {
+        Some(dir) => {
+            let path = write_service_file(dir, &file_name, &plist)?;
+            println!("✅ Wrote launchd plist: {}", path.display());
+            println!("   Load with: launchctl load -w {}", path.display());
+        }
+        None => {
+            let home = std::env::var("HOME")
+                .map_err(|_| anyhow!("HOME is not set; use --output to choose a directory"))?;
+            let agents_dir = PathBuf::from(home).join("Library").join("LaunchAgents");
+            fs::create_dir_all(data_dir.join("logs"))?;
+            let path = write_service_file(&agents_dir, &file_name, &plist)?;
+            println!("✅ Wrote launchd plist: {}", path.display());
+
+            run("launchctl", &["load", "-w", &path.display().to_string()])?;
+
+            println!("✅ Service registered and started");
+            println!("   Stop:  launchctl unload {}", path.display());
+            println!("   Start: launchctl load -w {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Windows services must talk to the Service Control Manager, so the node is run
+/// through WinSW (https://github.com/winsw/winsw), which sends Ctrl-C on stop.
+/// WinSW is not bundled: without its executable next to the config, only the
+/// config is written and the command prints how to finish the install.
+fn install_windows(
+    options: &ServiceOptions,
+    exe: &Path,
+    data_dir: &Path,
+    log_file: &Path,
+    args: &[String],
+) -> Result<()> {
+    let config = winsw_config(&options.name, &options.network, exe, data_dir, log_file, args);
+
+    let dir = options
+        .output
+        .as_deref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| data_dir.to_path_buf());
+    let path = write_service_file(&dir, &format!("{}.xml", options.name), &config)?;
+    println!("✅ Wrote service wrapper config: {}", path.display());
+
+    let wrapper = dir.join(format!("{}.exe", options.name));
+    if options.output.is_none() && wrapper.exists() {
+        run(&wrapper.display().to_string(), &["install"])?;
+        println!("✅ Service registered");
+        println!("   Start: sc start {}", options.name);
+        println!("   Stop:  sc stop {}", options.name);
+    } else {
+        println!("   Download WinSW and save it as {}", wrapper.display());
+        println!(
+            "   Then run: {} install && sc start {}",
+            wrapper.display(),
+            options.name
+        );
+    }
+
+    Ok(())
+}
+
+/// systemd unit running the node, stopped with SIGTERM
+fn systemd_unit(network: &str, exe: &Path, data_dir: &Path, args: &[String]) -> String {
+    format!(
+        "[Unit]\n\
+         Description=SpiraChain validator node ({network})\n\
+         After=network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         ExecStart={exe} {args}\n\
+         WorkingDirectory={data_dir}\n\
+         Restart=on-failure\n\
+         RestartSec=10\n\
+         KillSignal=SIGTERM\n\
+         TimeoutStopSec={timeout}\n\
+         LimitNOFILE=65535\n\
+         \n\
+         [Install]\n\
+         WantedBy=multi-user.target\n",
+        network = network,
+        exe = quote_systemd(&exe.display().to_string()),
+        args = args
+            .iter()
+            .map(|arg| quote_systemd(arg))
+            .collect::<Vec<_>>()
+            .join(" "),
+        data_dir = data_dir.display(),
+        timeout = STOP_TIMEOUT_SECS,
+    )
+}
+
+/// launchd agent keeping the node alive unless it exits cleanly
+fn launchd_plist(label: &str, exe: &Path, data_dir: &Path, args: &[String]) -> String {
+    let program_args = std::iter::once(exe.display().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| format!("        <string>{}</string>\n", escape_xml(&arg)))
+        .collect::<String>();
+    let console_log = data_dir.join("logs").join("launchd.log");
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{label}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n\
+         {program_args}\
+         \x20   </array>\n\
+         \x20   <key>WorkingDirectory</key>\n\
+         \x20   <string>{data_dir}</string>\n\
+         \x20   <key>RunAtLoad</key>\n\
+         \x20   <true/>\n\
+         \x20   <key>KeepAlive</key>\n\
+         \x20   <dict>\n\
+         \x20       <key>SuccessfulExit</key>\n\
+         \x20       <false/>\n\
+         \x20   </dict>\n\
+         \x20   <key>ExitTimeOut</key>\n\
+         \x20   <integer>{timeout}</integer>\n\
+         \x20   <key>StandardOutPath</key>\n\
+         \x20   <string>{console_log}</string>\n\
+         \x20   <key>StandardErrorPath</key>\n\
+         \x20   <string>{console_log}</string>\n\
+         </dict>\n\
+         </plist>\n",
+        label = escape_xml(label),
+        program_args = program_args,
+        data_dir = escape_xml(&data_dir.display().to_string()),
+        timeout = STOP_TIMEOUT_SECS,
+        console_log = escape_xml(&console_log.display().to_string()),
+    )
+}
+
+/// WinSW service definition; WinSW rolls its own wrapper logs, the node
+/// rotates `log_file` itself
+fn winsw_config(
+    name: &str,
+    network: &str,
+    exe: &Path,
+    data_dir: &Path,
+    log_file: &Path,
+    args: &[String],
+) -> String {
+    let arguments = args
+        .iter()
+        .map(|arg| {
+            if arg.contains(' ') {
+                format!("\"{}\"", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let wrapper_log_dir = log_file
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| data_dir.to_path_buf());
+
+    format!(
+        "<service>\n\
+         \x20 <id>{name}</id>\n\
+         \x20 <name>SpiraChain Node ({name})</name>\n\
+         \x20 <description>SpiraChain validator node ({network})</description>\n\
+         \x20 <executable>{exe}</executable>\n\
+         \x20 <arguments>{arguments}</arguments>\n\
+         \x20 <workingdirectory>{data_dir}</workingdirectory>\n\
+         \x20 <startmode>Automatic</startmode>\n\
+         \x20 <stopparentprocessfirst>true</stopparentprocessfirst>\n\
+         \x20 <stoptimeout>{timeout} sec</stoptimeout>\n\
+         \x20 <onfailure action=\"restart\" delay=\"10 sec\"/>\n\
+         \x20 <logpath>{log_dir}</logpath>\n\
+         \x20 <log mode=\"roll-by-size\">\n\
+         \x20   <sizeThreshold>10240</sizeThreshold>\n\
+         \x20   <keepFiles>5</keepFiles>\n\
+         \x20 </log>\n\
+         </service>\n",
+        name = escape_xml(name),
+        network = escape_xml(network),
+        exe = escape_xml(&exe.display().to_string()),
+        arguments = escape_xml(&arguments),
+        data_dir = escape_xml(&data_dir.display().to_string()),
+        timeout = STOP_TIMEOUT_SECS,
+        log_dir = escape_xml(&wrapper_log_dir.display().to_string()),
+    )
+}
+
+fn absolute(path: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+fn write_service_file(dir: impl AsRef<Path>, file_name: &str, contents: &str) -> Result<PathBuf> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+
+    let path = dir.join(file_name);
+    fs::write(&path, contents).map_err(|e| {
+        anyhow!(
+            "Failed to write {}: {} (try sudo, or --output <dir>)",
+            path.display(),
+            e
+        )
+    })?;
+
+    Ok(path)
+}
+
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| anyhow!("Failed to run {}: {}", program, e))?;
+
+    if !status.success() {
+        return Err(anyhow!(
+            "{} {} exited with {}",
+            program,
+            args.join(" "),
+            status
+        ));
+    }
+
+    Ok(())
+}
+
+/// systemd expands `%` specifiers, so they are doubled
+fn quote_systemd(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Vec<String> {
+        ["node", "--data-dir", "/srv/spira chain", "--network", "testnet"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_systemd_unit() {
+        let unit = systemd_unit(
+            "testnet",
+            Path::new("/usr/local/bin/spira"),
+            Path::new("/srv/spira chain"),
+            &args(),
+        );
+        assert_eq!(
+            unit,
+            "[Unit]\n\
+             Description=SpiraChain validator node (testnet)\n\
+             After=network-online.target\n\
+             Wants=network-online.target\n\
+             \n\
+             [Service]\n\
+             Type=simple\n\
+             ExecStart=/usr/local/bin/spira node --data-dir \"/srv/spira chain\" --network testnet\n\
+             WorkingDirectory=/srv/spira chain\n\
+             Restart=on-failure\n\
+             RestartSec=10\n\
+             KillSignal=SIGTERM\n\
+             TimeoutStopSec=30\n\
+             LimitNOFILE=65535\n\
+             \n\
+             [Install]\n\
+             WantedBy=multi-user.target\n"
+        );
+    }
+
+    #[test]
+    fn test_launchd_plist() {
+        let plist = launchd_plist(
+            "com.spirachain.node",
+            Path::new("/usr/local/bin/spira"),
+            Path::new("/srv/spira chain"),
+            &args(),
+        );
+        assert_eq!(
+            plist,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n\
+             <dict>\n\
+             \x20   <key>Label</key>\n\
+             \x20   <string>com.spirachain.node</string>\n\
+             \x20   <key>ProgramArguments</key>\n\
+             \x20   <array>\n\
+             \x20       <string>/usr/local/bin/spira</string>\n\
+             \x20       <string>node</string>\n\
+             \x20       <string>--data-dir</string>\n\
+             \x20       <string>/srv/spira chain</string>\n\
+             \x20       <string>--network</string>\n\
+             \x20       <string>testnet</string>\n\
+             \x20   </array>\n\
+             \x20   <key>WorkingDirectory</key>\n\
+             \x20   <string>/srv/spira chain</string>\n\
+             \x20   <key>RunAtLoad</key>\n\
+             \x20   <true/>\n\
+             \x20   <key>KeepAlive</key>\n\
+             \x20   <dict>\n\
+             \x20       <key>SuccessfulExit</key>\n\
+             \x20       <false/>\n\
+             \x20   </dict>\n\
+             \x20   <key>ExitTimeOut</key>\n\
+             \x20   <integer>30</integer>\n\
+             \x20   <key>StandardOutPath</key>\n\
+             \x20   <string>/srv/spira chain/logs/launchd.log</string>\n\
+             \x20   <key>StandardErrorPath</key>\n\
+             \x20   <string>/srv/spira chain/logs/launchd.log</string>\n\
+             </dict>\n\
+             </plist>\n"
+        );
+    }
+
+    #[test]
+    fn test_winsw_config() {
+        let config = winsw_config(
+            "spira-node",
+            "testnet",
+            Path::new("/opt/spira/spira.exe"),
+            Path::new("/srv/spira chain"),
+            Path::new("/srv/spira chain/logs/node.log"),
+            &args(),
+        );
+        assert_eq!(
+            config,
+            "<service>\n\
+             \x20 <id>spira-node</id>\n\
+             \x20 <name>SpiraChain Node (spira-node)</name>\n\
+             \x20 <description>SpiraChain validator node (testnet)</description>\n\
+             \x20 <executable>/opt/spira/spira.exe</executable>\n\
+             \x20 <arguments>node --data-dir &quot;/srv/spira chain&quot; --network testnet</arguments>\n\
+             \x20 <workingdirectory>/srv/spira chain</workingdirectory>\n\
+             \x20 <startmode>Automatic</startmode>\n\
+             \x20 <stopparentprocessfirst>true</stopparentprocessfirst>\n\
+             \x20 <stoptimeout>30 sec</stoptimeout>\n\
+             \x20 <onfailure action=\"restart\" delay=\"10 sec\"/>\n\
+             \x20 <logpath>/srv/spira chain/logs</logpath>\n\
+             \x20 <log mode=\"roll-by-size\">\n\
+             \x20   <sizeThreshold>10240</sizeThreshold>\n\
+             \x20   <keepFiles>5</keepFiles>\n\
+             \x20 </log>\n\
+             </service>\n"
+        );
+    }
+
+    #[test]
+    fn test_quoting_escapes_specifiers_and_markup() {
+        assert_eq!(quote_systemd("50%"), "50%%");
+        assert_eq!(quote_systemd("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(escape_xml("a<b>&\"c\""), "a&lt;b&gt;&amp;&quot;c&quot;");
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use serde::Serialize;
+use spirachain_consensus::{SlotConsensus, EQUIVOCATION_HORIZON, SLOTS_PER_EPOCH};
+use spirachain_core::ChainSpec;
+use spirachain_network::NetworkSpec;
+use std::fs;
+
+/// The core document plus the consensus and p2p sections from their crates
+#[derive(Serialize)]
+struct FullChainSpec {
+    #[serde(flatten)]
+    chain: ChainSpec,
+    consensus: ConsensusSpec,
+    p2p: NetworkSpec,
+}
+
+#[derive(Serialize)]
+struct ConsensusSpec {
+    slot_duration_secs: u64,
+    slots_per_epoch: u64,
+    equivocation_horizon_blocks: u64,
+}
+
+pub fn handle_spec_export(network: String, output: Option<String>) -> Result<()> {
+    let spec = FullChainSpec {
+        chain: ChainSpec::for_network(&network),
+        consensus: ConsensusSpec {
+            slot_duration_secs: SlotConsensus::new(&network).slot_duration(),
+            slots_per_epoch: SLOTS_PER_EPOCH,
+            equivocation_horizon_blocks: EQUIVOCATION_HORIZON,
+        },
+        p2p: NetworkSpec::for_network(&network),
+    };
+
+    // Straight to text: genesis stakes and amounts overflow a serde_json::Value
+    let json = serde_json::to_string_pretty(&spec)?;
+    match output {
+        Some(path) => {
+            fs::write(&path, json)?;
+            println!("✅ {} chain spec written to {}", network, path);
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
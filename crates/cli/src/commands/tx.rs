@@ -1,5 +1,9 @@
+use super::history::{record, TxStatus};
 use anyhow::Result;
-use spirachain_core::{Address, Amount, Transaction};
+use spirachain_core::{
+    derive_contract_address, is_name, normalize_name, Address, Amount, CustomSpiralDefinition,
+    Hash, SpiralFormula, Transaction, NAME_REGISTRATION_FEE, NAME_SUFFIX, SPIRAL_PARAM_SCALE,
+};
 use spirachain_crypto::KeyPair;
 use std::fs;
 use tracing::info;
@@ -10,10 +14,366 @@ pub async fn handle_send(
     amount: String,
     fee: Option<String>,
     purpose: Option<String>,
+    execute_at: Option<u64>,
 ) -> Result<()> {
     info!("📤 Creating transaction");
 
-    let wallet_data = fs::read_to_string(&from_wallet)?;
+    let keypair = load_keypair(&from_wallet)?;
+    let contacts = super::contacts::AddressBook::open(&from_wallet)?;
+    let to_address = resolve_recipient("127.0.0.1", 9933, &contacts.resolve(&to)?).await?;
+
+    let amount = parse_qbt(&amount)?;
+
+    let mut tx = Transaction::new(keypair.to_address(), to_address, amount, Amount::zero());
+
+    if let Some(p) = purpose {
+        tx = tx.with_purpose(p);
+    }
+    tx.execute_at = execute_at;
+
+    // Default fee is the node's estimate, never below what the purpose costs
+    tx.fee = match fee.as_deref() {
+        Some(fee) => parse_qbt(fee)?,
+        None => recommended_fee("127.0.0.1", 9933, &tx).await,
+    };
+
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+
+    keypair.sign_transaction(&mut tx);
+
+    let tx_json = serde_json::to_string_pretty(&serde_json::json!({
+        "from": keypair.to_address().to_string(),
+        "to": to_address.to_string(),
+        "contact": contacts.name_of(&to_address),
+        "amount": amount.to_qbt_string(),
+        "fee": tx.fee.to_qbt_string(),
+        "purpose": tx.purpose,
+        "hash": tx.tx_hash.to_string(),
+        "timestamp": tx.timestamp,
+        "execute_at": tx.execute_at,
+    }))?;
+
+    println!("✅ Transaction created:");
+    println!("{}", tx_json);
+    println!("\n📝 Transaction hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_deploy(
+    from_wallet: String,
+    code_path: String,
+    args: Option<String>,
+    nonce: u64,
+    value: Option<String>,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating contract deployment");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let code = fs::read(&code_path)?;
+    let constructor_args = decode_hex_arg(args.as_deref())?;
+    let value = parse_qbt(value.as_deref().unwrap_or("0"))?;
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    let mut tx = Transaction::new_contract_deploy(
+        keypair.to_address(),
+        code,
+        constructor_args,
+        nonce,
+        value,
+        fee,
+    );
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = Some(nonce);
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    let contract_address = derive_contract_address(&keypair.to_address(), nonce);
+
+    println!("✅ Deployment created:");
+    println!("   From: {}", keypair.to_address());
+    println!("   Contract address: {}", contract_address);
+    println!("   Nonce: {}", nonce);
+    println!("   Value: {}", value);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_call(
+    from_wallet: String,
+    contract: String,
+    input: Option<String>,
+    value: Option<String>,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating contract call");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let contract_address = parse_address(&contract)?;
+    let input = decode_hex_arg(input.as_deref())?;
+    let value = parse_qbt(value.as_deref().unwrap_or("0"))?;
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    let mut tx =
+        Transaction::new_contract_call(keypair.to_address(), contract_address, input, value, fee);
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Contract call created:");
+    println!("   From: {}", keypair.to_address());
+    println!("   Contract: {}", contract_address);
+    println!("   Value: {}", value);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_claim_rewards(
+    from_wallet: String,
+    payout: Option<String>,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating rewards claim");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let payout = match payout.as_deref() {
+        Some(address) => parse_address(address)?,
+        None => keypair.to_address(),
+    };
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    let rpc_client = spirachain_rpc::RpcClient::new("127.0.0.1", 9933);
+    if let Ok(rewards) = rpc_client
+        .get_rewards(&keypair.to_address().to_string())
+        .await
+    {
+        let unclaimed = Amount::new(rewards.unclaimed_rewards.parse()?);
+        println!("💰 Unclaimed rewards: {}", unclaimed);
+        if unclaimed <= fee {
+            return Err(anyhow::anyhow!(
+                "Unclaimed rewards do not cover the {} fee",
+                fee
+            ));
+        }
+    }
+
+    let mut tx = Transaction::new_claim_rewards(keypair.to_address(), payout, fee);
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Rewards claim created:");
+    println!("   Validator: {}", keypair.to_address());
+    println!("   Payout to: {}", payout);
+    println!("   Fee: {}", fee);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_set_payout(
+    from_wallet: String,
+    payout: String,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating payout address change");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let payout = parse_address(&payout)?;
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    let mut tx = Transaction::new_set_payout_address(keypair.to_address(), payout, fee);
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Payout address change created:");
+    println!("   Validator: {}", keypair.to_address());
+    if payout == keypair.to_address() {
+        println!("   Rewards to: the validator (payout address reset)");
+    } else {
+        println!("   Rewards to: {}", payout);
+    }
+    println!("   Fee: {}", fee);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_register_name(
+    from_wallet: String,
+    name: String,
+    target: Option<String>,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating name registration");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let name = normalize_name(&name)?;
+    let target = match target.as_deref() {
+        Some(address) => parse_address(address)?,
+        None => keypair.to_address(),
+    };
+    let fee = match fee.as_deref() {
+        Some(fee) => parse_qbt(fee)?,
+        None => Amount::new(NAME_REGISTRATION_FEE),
+    };
+
+    let rpc_client = spirachain_rpc::RpcClient::new("127.0.0.1", 9933);
+    if let Ok(Some(record)) = rpc_client.resolve_name(&name).await {
+        println!(
+            "📛 {} is registered to {} until block {}",
+            record.name, record.owner, record.expires_at
+        );
+    }
+
+    let mut tx = Transaction::new_register_name(keypair.to_address(), name.clone(), target, fee);
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Name registration created:");
+    println!("   Name: {}{}", name, NAME_SUFFIX);
+    println!("   Owner: {}", keypair.to_address());
+    println!("   Resolves to: {}", target);
+    println!("   Fee: {}", fee);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_transfer_name(
+    from_wallet: String,
+    name: String,
+    to: String,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating name transfer");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let name = normalize_name(&name)?;
+    let new_owner = parse_address(&to)?;
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    let mut tx = Transaction::new_transfer_name(keypair.to_address(), name.clone(), new_owner, fee);
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Name transfer created:");
+    println!("   Name: {}{}", name, NAME_SUFFIX);
+    println!("   New owner: {}", new_owner);
+    println!("   Fee: {}", fee);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_pause_vote(
+    from_wallet: String,
+    blocks: u64,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating emergency pause vote");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let fee = parse_qbt(fee.as_deref().unwrap_or("0.001"))?;
+
+    let rpc_client = spirachain_rpc::RpcClient::new("127.0.0.1", 9933);
+    if let Ok(status) = rpc_client.get_pause_status().await {
+        match status.paused_until {
+            Some(until) => println!("⏸️  Chain paused until block {}", until),
+            None => println!("▶️  Chain not paused"),
+        }
+        println!("   Pending guardian votes: {}", status.votes.len());
+    }
+
+    let mut tx = Transaction::new_pause_vote(keypair.to_address(), blocks, fee);
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Pause vote created:");
+    println!("   Guardian: {}", keypair.to_address());
+    if blocks == 0 {
+        println!("   Vote: lift the pause");
+    } else {
+        println!("   Vote: pause for {} blocks", blocks);
+    }
+    println!("   Fee: {}", fee);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+pub async fn handle_register_spiral(
+    from_wallet: String,
+    name: String,
+    formula: String,
+    params: String,
+    turns: u32,
+    fee: Option<String>,
+) -> Result<()> {
+    info!("📤 Creating custom spiral registration");
+
+    let keypair = load_keypair(&from_wallet)?;
+    let fee = parse_qbt(fee.as_deref().unwrap_or("1"))?;
+    let definition =
+        CustomSpiralDefinition::new(name, parse_spiral_formula(&formula, &params)?, turns);
+    definition.validate()?;
+
+    let mut tx = Transaction::new_register_spiral(keypair.to_address(), definition.clone(), fee);
+    tx.fork_id = chain_fork_id("127.0.0.1", 9933).await;
+    tx.nonce = account_nonce("127.0.0.1", 9933, &tx.from).await;
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+
+    println!("✅ Spiral registration created:");
+    println!("   Name: {}", definition.name);
+    println!("   Spiral ID: {}", definition.id());
+    println!("   Formula: {:?}", definition.formula);
+    println!("   Fee: {}", fee);
+    println!("   Hash: {}", tx.tx_hash);
+
+    submit_to_local_node(&from_wallet, &tx).await
+}
+
+/// Formula from its family name and decimal parameters
+fn parse_spiral_formula(formula: &str, params: &str) -> Result<SpiralFormula> {
+    let params = params
+        .split(',')
+        .map(|param| {
+            let value: f64 = param.trim().parse()?;
+            Ok((value * SPIRAL_PARAM_SCALE as f64).round() as i64)
+        })
+        .collect::<Result<Vec<i64>>>()?;
+
+    match (formula, params.as_slice()) {
+        ("archimedean", [a, b]) => Ok(SpiralFormula::Archimedean { a: *a, b: *b }),
+        ("logarithmic", [a, k]) => Ok(SpiralFormula::Logarithmic { a: *a, k: *k }),
+        ("fermat", [a]) => Ok(SpiralFormula::Fermat { a: *a }),
+        ("polynomial", _) => Ok(SpiralFormula::Polynomial {
+            coefficients: params,
+        }),
+        _ => Err(anyhow::anyhow!(
+            "Expected archimedean a,b | logarithmic a,k | fermat a | polynomial c0,c1,..."
+        )),
+    }
+}
+
+pub fn load_keypair(wallet_path: &str) -> Result<KeyPair> {
+    let wallet_data = fs::read_to_string(wallet_path)?;
     let wallet: serde_json::Value = serde_json::from_str(&wallet_data)?;
 
     let secret_key_hex = wallet["secret_key"]
@@ -24,79 +384,134 @@ pub async fn handle_send(
     let mut secret_key = [0u8; 32];
     secret_key.copy_from_slice(&secret_key_bytes);
 
-    let keypair = KeyPair::from_secret(secret_key)?;
+    Ok(KeyPair::from_secret(secret_key)?)
+}
 
-    let to_address_hex = to.trim_start_matches("0x");
+fn parse_address(address: &str) -> Result<Address> {
+    let address_hex = address.trim_start_matches("0x");
 
     // Ensure the address is exactly 64 characters (32 bytes)
-    if to_address_hex.len() != 64 {
+    if address_hex.len() != 64 {
         return Err(anyhow::anyhow!(
             "Address must be exactly 64 hex characters (32 bytes)"
         ));
     }
 
-    let to_bytes = hex::decode(to_address_hex)?;
-    let mut to_address_array = [0u8; 32];
-    to_address_array.copy_from_slice(&to_bytes[..32]);
-    let to_address = Address::new(to_address_array);
+    let bytes = hex::decode(address_hex)?;
+    let mut address_array = [0u8; 32];
+    address_array.copy_from_slice(&bytes[..32]);
+    Ok(Address::new(address_array))
+}
 
-    let amount_f64: f64 = amount.parse()?;
-    let amount_units = (amount_f64 * 1e18) as u128;
+/// Address for `recipient`, looking up `.spira` names on the node
+pub async fn resolve_recipient(host: &str, port: u16, recipient: &str) -> Result<Address> {
+    if !is_name(recipient) {
+        return parse_address(recipient);
+    }
 
-    let fee_f64: f64 = fee.as_deref().unwrap_or("0.001").parse()?;
-    let fee_units = (fee_f64 * 1e18) as u128;
+    let rpc_client = spirachain_rpc::RpcClient::new(host, port);
+    let record = rpc_client
+        .resolve_name(recipient)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("{} is not registered", recipient))?;
+    println!("🔎 {} resolves to {}", record.name, record.address);
+    parse_address(&record.address)
+}
 
-    let mut tx = Transaction::new(
-        keypair.to_address(),
-        to_address,
-        Amount::new(amount_units),
-        Amount::new(fee_units),
-    );
+pub fn parse_qbt(amount: &str) -> Result<Amount> {
+    Ok(amount.parse::<Amount>()?)
+}
 
-    if let Some(p) = purpose {
-        tx = tx.with_purpose(p);
+fn decode_hex_arg(data: Option<&str>) -> Result<Vec<u8>> {
+    match data {
+        Some(hex_str) => Ok(hex::decode(hex_str.trim_start_matches("0x"))?),
+        None => Ok(Vec::new()),
     }
+}
 
-    tx.compute_hash();
+/// Fork id of the node's chain, so the signature is only valid on that side of a fork.
+/// Without a reachable node, fall back to the latest testnet fork.
+pub async fn chain_fork_id(host: &str, port: u16) -> Hash {
+    let rpc_client = spirachain_rpc::RpcClient::new(host, port);
 
-    let signature = keypair.sign(&tx.serialize());
-    tx.signature = signature;
+    rpc_client
+        .get_status()
+        .await
+        .ok()
+        .and_then(|status| hex::decode(status.fork_id.trim_start_matches("0x")).ok())
+        .and_then(|bytes| Hash::from_slice(&bytes).ok())
+        .unwrap_or_else(|| spirachain_core::fork_id("testnet", u64::MAX))
+}
 
-    let tx_json = serde_json::to_string_pretty(&serde_json::json!({
-        "from": keypair.to_address().to_string(),
-        "to": to_address.to_string(),
-        "amount": amount_f64,
-        "fee": fee_f64,
-        "purpose": tx.purpose,
-        "hash": tx.tx_hash.to_string(),
-        "timestamp": tx.timestamp,
-    }))?;
+/// Account nonce of `address` on the node, which orders the next transaction
+/// it signs among its others in a block; none without a node
+pub async fn account_nonce(host: &str, port: u16, address: &Address) -> Option<u64> {
+    let rpc_client = spirachain_rpc::RpcClient::new(host, port);
 
-    println!("✅ Transaction created:");
-    println!("{}", tx_json);
-    println!("\n📝 Transaction hash: {}", tx.tx_hash);
+    rpc_client
+        .get_validator_info(&address.to_string())
+        .await
+        .ok()
+        .map(|info| info.nonce)
+}
+
+/// Inclusion target of the default fee, in blocks
+pub const DEFAULT_FEE_TARGET_BLOCKS: u64 = 3;
 
+/// The node's fee estimate for inclusion within [`DEFAULT_FEE_TARGET_BLOCKS`]
+/// for transactions with the same intent, raised to the transaction's own
+/// minimum; just the minimum without a node
+pub async fn recommended_fee(host: &str, port: u16, tx: &Transaction) -> Amount {
+    let rpc_client = spirachain_rpc::RpcClient::new(host, port);
+
+    let estimate = rpc_client
+        .estimate_fee(
+            DEFAULT_FEE_TARGET_BLOCKS,
+            tx.intent.as_ref().map(|intent| intent.intent_type),
+        )
+        .await
+        .ok()
+        .and_then(|estimate| estimate.fee.parse().ok())
+        .map(Amount::new)
+        .unwrap_or_default();
+    estimate.max(tx.min_fee())
+}
+
+/// Submit `tx` and record it in the history of the wallet that signed it
+pub async fn submit_to_local_node(wallet_path: &str, tx: &Transaction) -> Result<()> {
     // Try to submit to local RPC server
     println!("\n🔄 Attempting to submit to local node...");
 
     let rpc_client = spirachain_rpc::RpcClient::new("127.0.0.1", 9933);
 
-    match rpc_client.health_check().await {
+    let (status, submitted_at) = match rpc_client.health_check().await {
         Ok(true) => {
             info!("✅ Connected to local node");
 
-            match rpc_client.submit_transaction(&tx).await {
+            match rpc_client.submit_transaction(tx).await {
                 Ok(response) => {
                     if response.success {
                         println!("✅ Transaction submitted to network!");
                         println!("   Status: {}", response.message);
                         println!("   Hash: {}", response.tx_hash);
+                        let height = rpc_client.get_status().await.ok();
+                        (
+                            TxStatus::Submitted,
+                            height.map(|status| status.chain_height),
+                        )
                     } else {
                         println!("❌ Transaction rejected: {}", response.message);
+                        (
+                            TxStatus::Rejected {
+                                reason: response.message,
+                            },
+                            None,
+                        )
                     }
                 }
                 Err(e) => {
                     println!("⚠️  Failed to submit transaction: {}", e);
+                    (TxStatus::NotSubmitted, None)
                 }
             }
         }
@@ -104,8 +519,10 @@ pub async fn handle_send(
             println!("⚠️  No local node running on port 9933");
             println!("   Start a node with: spira node --validator --wallet <wallet.json>");
             println!("   Transaction created but not broadcasted");
+            (TxStatus::NotSubmitted, None)
         }
-    }
+    };
+    record(wallet_path, tx, status, submitted_at);
 
     Ok(())
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use spirachain_core::Amount;
 
 pub async fn handle_register(stake: u64, wallet: String) -> Result<()> {
     println!("Registering validator with stake: {} QBT", stake);
@@ -22,8 +23,65 @@ pub async fn handle_list() -> Result<()> {
 }
 
 pub async fn handle_info(address: String) -> Result<()> {
-    println!("Validator Info: {}", address);
-    println!("\n(Note: Connect to a running node to get actual info)");
+    let rpc_client = spirachain_rpc::RpcClient::new("127.0.0.1", 9933);
+    let info = rpc_client.get_validator_info(&address).await?;
+    let qbt = |value: &str| -> Result<String> { Ok(Amount::new(value.parse()?).to_qbt_string()) };
+
+    println!("Validator Info: {}", info.address);
+    println!("   Stake: {} QBT", qbt(&info.stake)?);
+    if info.payout_address == info.address {
+        println!("   Payout address: the validator");
+    } else {
+        println!("   Payout address: {}", info.payout_address);
+    }
+    println!(
+        "   Unclaimed rewards: {} QBT",
+        qbt(&info.unclaimed_rewards)?
+    );
+
+    match next_slot(&rpc_client, &info.address).await {
+        Ok(Some((slot, 0))) => println!("   Next slot: {} (now)", slot),
+        Ok(Some((slot, wait))) => {
+            println!("   Next slot: {} in {}m {:02}s", slot, wait / 60, wait % 60)
+        }
+        Ok(None) => println!("   Next slot: none, not in the active set"),
+        Err(e) => println!("   Next slot: unknown ({})", e),
+    }
 
     Ok(())
 }
+
+/// Next slot `address` leads, from the current one on, and the seconds until it starts
+async fn next_slot(
+    rpc_client: &spirachain_rpc::RpcClient,
+    address: &str,
+) -> Result<Option<(u64, u64)>> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let schedule = rpc_client.get_slot_schedule(None).await?;
+    let mut slot = first_slot_from(&schedule, address, schedule.current_slot);
+    if slot.is_none() {
+        // Past the validator's last slot of the epoch
+        let next = rpc_client
+            .get_slot_schedule(Some(schedule.epoch + 1))
+            .await?;
+        slot = first_slot_from(&next, address, next.first_slot);
+    }
+    Ok(slot.map(|slot| {
+        let start = slot * schedule.slot_duration_secs;
+        (slot, start.saturating_sub(now))
+    }))
+}
+
+fn first_slot_from(
+    schedule: &spirachain_rpc::SlotScheduleResponse,
+    address: &str,
+    from: u64,
+) -> Option<u64> {
+    schedule
+        .leaders
+        .iter()
+        .find(|leader| leader.address == address)
+        .and_then(|leader| leader.slots.iter().copied().find(|slot| *slot >= from))
+}
@@ -1,7 +1,13 @@
+use super::history::TxStatus;
 use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use spirachain_crypto::KeyPair;
+use spirachain_core::{Address, Amount, SignedMessage, Transaction};
+use spirachain_crypto::{ExportedKey, KeyFormat, KeyKind, KeyPair};
+use spirachain_network::write_private_file;
+use spirachain_node::AtRestCipher;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Serialize, Deserialize)]
 struct WalletFile {
@@ -42,6 +48,150 @@ pub async fn handle_new_wallet(output: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// A file kept next to the wallet, sealed under a key derived from its
+/// secret key for `context`, so only the wallet opens it
+pub struct WalletFileStore {
+    path: PathBuf,
+    cipher: AtRestCipher,
+}
+
+impl WalletFileStore {
+    pub fn open(wallet_path: &str, extension: &str, context: &str) -> Result<Self> {
+        let keypair = super::tx::load_keypair(wallet_path)?;
+        Ok(Self {
+            path: PathBuf::from(wallet_path).with_extension(extension),
+            cipher: AtRestCipher::new(blake3::derive_key(context, keypair.secret_key().as_bytes())),
+        })
+    }
+
+    /// Stored value, or the default before anything was saved
+    pub fn load<T: DeserializeOwned + Default>(&self) -> Result<T> {
+        if !self.path.exists() {
+            return Ok(T::default());
+        }
+        let plaintext = self
+            .cipher
+            .open(&fs::read(&self.path)?)
+            .map_err(|_| anyhow!("{} does not open with this wallet", self.path.display()))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    pub fn save<T: Serialize>(&self, value: &T) -> Result<()> {
+        let sealed = self.cipher.seal(&serde_json::to_vec(value)?)?;
+        write_private_file(&self.path, &sealed)?;
+        Ok(())
+    }
+}
+
+/// Print `encoded`, or write it to `output`; secret keys get a private file
+fn write_key(encoded: &str, secret: bool, output: Option<String>) -> Result<()> {
+    match output {
+        Some(path) if secret => write_private_file(Path::new(&path), encoded.as_bytes())?,
+        Some(path) => fs::write(&path, encoded)?,
+        None => {
+            println!("{}", encoded.trim_end());
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_export_key(
+    wallet_path: String,
+    format: String,
+    secret: bool,
+    output: Option<String>,
+) -> Result<()> {
+    let format: KeyFormat = format.parse()?;
+    let keypair = super::tx::load_keypair(&wallet_path)?;
+    let key = if secret {
+        ExportedKey::ed25519_secret(&keypair)
+    } else {
+        ExportedKey::ed25519_public(keypair.public_key())
+    };
+
+    write_key(&key.encode(format), secret, output.clone())?;
+    if let Some(path) = output {
+        eprintln!("✅ {} key exported to {}", key.kind(), path);
+    }
+    Ok(())
+}
+
+pub fn handle_import_key(input: String, format: String, output: Option<String>) -> Result<()> {
+    let format: KeyFormat = format.parse()?;
+    let key = ExportedKey::decode(
+        &fs::read_to_string(&input)?,
+        format,
+        Some(KeyKind::Ed25519Secret),
+    )?;
+    let keypair = key.to_keypair()?;
+
+    let wallet = WalletFile {
+        address: keypair.to_address().to_string(),
+        public_key: hex::encode(keypair.public_key().as_bytes()),
+        secret_key: hex::encode(keypair.secret_key().as_bytes()),
+    };
+    let json = serde_json::to_string_pretty(&wallet)?;
+    write_key(&json, true, output.clone())?;
+
+    if let Some(path) = output {
+        println!("✅ Wallet saved to: {}", path);
+    }
+    println!("\n🔑 Address: {}", wallet.address);
+    Ok(())
+}
+
+pub fn handle_convert_key(
+    input: String,
+    from: String,
+    to: String,
+    kind: Option<String>,
+    output: Option<String>,
+) -> Result<()> {
+    let kind = kind.map(|kind| kind.parse::<KeyKind>()).transpose()?;
+    let key = ExportedKey::decode(&fs::read_to_string(&input)?, from.parse()?, kind)?;
+
+    write_key(
+        &key.encode(to.parse()?),
+        key.kind().is_secret(),
+        output.clone(),
+    )?;
+    if let Some(path) = output {
+        eprintln!("✅ {} key written to {}", key.kind(), path);
+    }
+    Ok(())
+}
+
+pub fn handle_sign_message(
+    wallet_path: String,
+    message: String,
+    output: Option<String>,
+) -> Result<()> {
+    let keypair = super::tx::load_keypair(&wallet_path)?;
+    let json = serde_json::to_string_pretty(&keypair.sign_message(&message))?;
+
+    if let Some(path) = output {
+        fs::write(&path, &json)?;
+        println!("✅ Signed message saved to: {}", path);
+    } else {
+        println!("{}", json);
+    }
+    Ok(())
+}
+
+pub fn handle_verify_message(input: String) -> Result<()> {
+    let signed: SignedMessage = serde_json::from_str(&fs::read_to_string(&input)?)?;
+
+    match signed.verify() {
+        Ok(address) => {
+            println!("✅ Valid signature by {}", address);
+            println!("   Message: {}", signed.message);
+            Ok(())
+        }
+        Err(e) => Err(anyhow!("Signature does not verify: {}", e)),
+    }
+}
+
 pub async fn handle_wallet_address(wallet_path: String) -> Result<()> {
     let content = fs::read_to_string(wallet_path)?;
     let wallet: WalletFile = serde_json::from_str(&content)?;
@@ -71,16 +221,15 @@ pub async fn handle_wallet_balance(address: String) -> Result<()> {
             if response.status().is_success() {
                 match response.json::<BalanceResponse>().await {
                     Ok(balance_data) => {
-                        // Parse balance string to u128
-                        let balance_wei: u128 = balance_data.balance.parse().unwrap_or(0);
-
-                        // Convert to QBT (divide by 1e18)
-                        let balance_qbt = balance_wei as f64 / 1e18;
+                        // The node reports base units
+                        let balance = Amount::new(balance_data.balance.parse().map_err(|e| {
+                            anyhow!("Invalid balance {:?}: {}", balance_data.balance, e)
+                        })?);
 
-                        println!("Balance: {:.18} QBT", balance_qbt);
+                        println!("Balance: {}", balance);
 
-                        if balance_qbt > 0.0 {
-                            println!("\n💰 You have {} QBT!", balance_qbt);
+                        if !balance.is_zero() {
+                            println!("\n💰 You have {} QBT!", balance.to_qbt_string());
                         } else {
                             println!("\n💡 No balance yet. Start earning by validating blocks!");
                         }
@@ -110,21 +259,22 @@ pub async fn handle_wallet_balance(address: String) -> Result<()> {
 pub async fn handle_wallet_send(
     wallet_path: String,
     to_address: String,
-    amount: f64,
+    amount: String,
+    fee: Option<String>,
 ) -> Result<()> {
-    println!("📤 Sending {} QBT to {}...", amount, to_address);
+    let amount = super::tx::parse_qbt(&amount)?;
+    println!(
+        "📤 Sending {} QBT to {}...",
+        amount.to_qbt_string(),
+        to_address
+    );
 
     // Load wallet
     let content = fs::read_to_string(&wallet_path)?;
     let wallet: WalletFile = serde_json::from_str(&content)?;
 
-    // Convert amount to wei (QBT has 18 decimals)
-    let amount_wei = (amount * 1e18) as u128;
-    let fee_wei = 1_000_000_000_000_000u128; // 0.001 QBT fee
-
     println!("   From: {}", wallet.address);
-    println!("   Amount: {} QBT", amount);
-    println!("   Fee: 0.001 QBT");
+    println!("   Amount: {}", amount);
 
     // Parse secret key
     let secret_bytes = hex::decode(&wallet.secret_key)?;
@@ -135,24 +285,30 @@ pub async fn handle_wallet_send(
     let keypair = KeyPair::from_secret(secret_array)?;
 
     // Create transaction
-    use spirachain_core::{Address, Amount, Transaction};
-
     let from_bytes = hex::decode(wallet.address.trim_start_matches("0x"))?;
-    let to_bytes = hex::decode(to_address.trim_start_matches("0x"))?;
-
-    if from_bytes.len() != 32 || to_bytes.len() != 32 {
+    if from_bytes.len() != 32 {
         return Err(anyhow!("Invalid address length"));
     }
 
     let from = Address::new(from_bytes.try_into().unwrap());
-    let to = Address::new(to_bytes.try_into().unwrap());
+    let contacts = super::contacts::AddressBook::open(&wallet_path)?;
+    let recipient = contacts.resolve(&to_address)?;
+    let to = super::tx::resolve_recipient("127.0.0.1", 9933, &recipient).await?;
+    if let Some(name) = contacts.name_of(&to) {
+        println!("   To: {} (@{})", to, name);
+    }
 
-    let mut tx = Transaction::new(from, to, Amount::new(amount_wei), Amount::new(fee_wei));
+    let mut tx = Transaction::new(from, to, amount, Amount::zero());
+    tx.fee = match fee {
+        Some(fee) => super::tx::parse_qbt(&fee)?,
+        None => super::tx::recommended_fee("localhost", 8545, &tx).await,
+    };
+    println!("   Fee: {}", tx.fee);
 
-    // Compute hash and sign transaction
+    // Compute hash and sign transaction for the local node's fork
+    tx.fork_id = super::tx::chain_fork_id("localhost", 8545).await;
     tx.compute_hash();
-    let signature_bytes = keypair.sign(tx.tx_hash.as_bytes());
-    tx.signature = signature_bytes;
+    keypair.sign_transaction(&mut tx);
 
     println!("   Transaction hash: {}", tx.tx_hash);
 
@@ -176,17 +332,28 @@ pub async fn handle_wallet_send(
     match client.post(rpc_url).json(&request).send().await {
         Ok(response) => {
             if response.status().is_success() {
+                let height = spirachain_rpc::RpcClient::new("localhost", 8545)
+                    .get_status()
+                    .await
+                    .ok()
+                    .map(|status| status.chain_height);
+                super::history::record(&wallet_path, &tx, TxStatus::Submitted, height);
                 println!("\n✅ Transaction submitted successfully!");
                 println!("   It will be included in the next block (~60 seconds)");
                 println!("\n💡 Check balances:");
                 println!("   spira wallet balance {}", wallet.address);
-                println!("   spira wallet balance {}", to_address);
+                println!("   spira wallet balance {}", to);
             } else {
                 let error_text = response.text().await.unwrap_or_default();
+                let status = TxStatus::Rejected {
+                    reason: error_text.clone(),
+                };
+                super::history::record(&wallet_path, &tx, status, None);
                 return Err(anyhow!("RPC error: {}", error_text));
             }
         }
         Err(e) => {
+            super::history::record(&wallet_path, &tx, TxStatus::NotSubmitted, None);
             return Err(anyhow!("Failed to connect to local node: {}", e));
         }
     }
@@ -0,0 +1,187 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, Registry};
+
+/// Rotate the node log once it grows past this size
+const LOG_MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Rotated files kept next to the live log (node.log.1 .. node.log.N)
+const LOG_MAX_FILES: usize = 5;
+
+static LOG_LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+/// Size-based rotating log file: node.log -> node.log.1 -> ... -> node.log.N
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFileWriter {
+    pub fn open(path: impl AsRef<Path>, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+            self.size = 0;
+            return Ok(());
+        }
+
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Install the global subscriber: stdout, or a rotating file when `log_file` is set.
+/// The level starts at INFO and can be changed later with [`set_log_level`].
+pub fn init(log_file: Option<&str>) -> anyhow::Result<()> {
+    let (level, handle) = reload::Layer::new(LevelFilter::INFO);
+    let registry = tracing_subscriber::registry().with(level);
+
+    match log_file {
+        Some(path) => {
+            let writer = RotatingFileWriter::open(path, LOG_MAX_BYTES, LOG_MAX_FILES)?;
+            registry
+                .with(
+                    fmt::layer()
+                        .with_writer(Mutex::new(writer))
+                        .with_ansi(false),
+                )
+                .init();
+        }
+        None => registry.with(fmt::layer()).init(),
+    }
+
+    let _ = LOG_LEVEL.set(handle);
+    Ok(())
+}
+
+/// Change the log level at runtime ("trace", "debug", "info", "warn", "error", "off")
+pub fn set_log_level(level: &str) -> Result<(), String> {
+    let filter: LevelFilter = level
+        .parse()
+        .map_err(|_| format!("Unknown log level '{}'", level))?;
+
+    let handle = LOG_LEVEL
+        .get()
+        .ok_or_else(|| "Logging not initialized".to_string())?;
+    handle
+        .modify(|current| *current = filter)
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!("📝 Log level set to {}", filter);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!("spirachain-log-rotation-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("node.log");
+
+        let mut writer = RotatingFileWriter::open(&path, 10, 2).unwrap();
+        for line in ["first-123\n", "second-12\n", "third-123\n", "fourth-12\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // Each line fills a file; the oldest falls off past node.log.2
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("node.log"), "fourth-12\n");
+        assert_eq!(read("node.log.1"), "third-123\n");
+        assert_eq!(read("node.log.2"), "second-12\n");
+        assert!(!dir.join("node.log.3").exists());
+
+        // Reopening appends to the live file and rotates on the next overflow
+        drop(writer);
+        let mut writer = RotatingFileWriter::open(&path, 10, 2).unwrap();
+        writer.write_all(b"fifth\n").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(read("node.log"), "fifth\n");
+        assert_eq!(read("node.log.1"), "fourth-12\n");
+        assert_eq!(read("node.log.2"), "third-123\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_zero_retention_truncates_in_place() {
+        let dir = std::env::temp_dir().join(format!("spirachain-log-truncate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("node.log");
+
+        let mut writer = RotatingFileWriter::open(&path, 8, 0).unwrap();
+        writer.write_all(b"1234567\n").unwrap();
+        writer.write_all(b"abc\n").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "abc\n");
+        assert!(!dir.join("node.log.1").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use clap::{Parser, Subcommand};
 
 mod commands;
+mod logging;
 
 use commands::*;
 
@@ -45,12 +46,84 @@ enum Commands {
         tx_cmd: TxCommands,
     },
 
+    #[command(about = "Inspect the P2P network")]
+    Net {
+        #[command(subcommand)]
+        net_cmd: NetCommands,
+    },
+
+    #[command(about = "Node operator maintenance")]
+    Admin {
+        #[command(subcommand)]
+        admin_cmd: AdminCommands,
+    },
+
+    #[command(about = "Back up and restore the node database")]
+    Db {
+        #[command(subcommand)]
+        db_cmd: DbCommands,
+    },
+
+    #[command(about = "Run a testnet faucet paying out of a wallet")]
+    Faucet {
+        #[arg(long, help = "Wallet file of the funded faucet account")]
+        wallet: String,
+
+        #[arg(long, default_value = "8090")]
+        port: u16,
+
+        #[arg(long, default_value = "127.0.0.1")]
+        rpc_host: String,
+
+        #[arg(long, default_value = "9933")]
+        rpc_port: u16,
+
+        #[arg(long, default_value = "10", help = "QBT sent per request")]
+        amount: String,
+
+        #[arg(
+            long,
+            default_value = "24",
+            help = "Hours between payouts to one address"
+        )]
+        address_cooldown_hours: u64,
+
+        #[arg(long, default_value = "60", help = "Minutes between payouts to one IP")]
+        ip_cooldown_minutes: u64,
+    },
+
+    #[command(about = "Machine-readable chain specification")]
+    Spec {
+        #[command(subcommand)]
+        spec_cmd: SpecCommands,
+    },
+
     #[command(about = "Generate genesis block")]
     Genesis {
         #[arg(short, long)]
         output: Option<String>,
     },
 
+    #[command(
+        about = "Replay a stopped node's chain from genesis with full validation and report the first divergence"
+    )]
+    VerifyChain {
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+
+        #[arg(long, default_value = "testnet")]
+        network: String,
+
+        #[arg(long, help = "Stop after this height instead of the tip")]
+        to: Option<u64>,
+    },
+
+    #[command(about = "Multi-party genesis ceremony: register, assemble, co-sign and verify")]
+    Ceremony {
+        #[command(subcommand)]
+        ceremony_cmd: CeremonyCommands,
+    },
+
     #[command(about = "Calculate π, e, or φ to specified precision")]
     Calculate {
         #[arg(value_name = "CONSTANT")]
@@ -60,8 +133,14 @@ enum Commands {
         precision: usize,
     },
 
-    #[command(about = "Start SpiraChain node")]
+    #[command(
+        about = "Start SpiraChain node",
+        args_conflicts_with_subcommands = true
+    )]
     Node {
+        #[command(subcommand)]
+        node_cmd: Option<NodeCommands>,
+
         #[arg(long)]
         validator: bool,
 
@@ -76,6 +155,234 @@ enum Commands {
 
         #[arg(long, help = "Network type: testnet or mainnet (default: testnet)")]
         network: Option<String>,
+
+        #[arg(
+            long,
+            help = "Write logs to this file (rotated by size) instead of stdout"
+        )]
+        log_file: Option<String>,
+
+        #[arg(
+            long = "i-know-what-im-doing",
+            help = "Sign blocks even if the double-sign protection file says this key already did"
+        )]
+        allow_double_sign: bool,
+
+        #[arg(
+            long,
+            requires = "validator",
+            help = "Simulate validator duties without signing or broadcasting and write a readiness report"
+        )]
+        dry_run: bool,
+
+        #[arg(
+            long,
+            help = "Encrypt the saved mempool and semantic stores with a node-local key"
+        )]
+        encrypt_at_rest: bool,
+
+        #[arg(
+            long,
+            help = "Never write purposes, semantic vectors or entity rankings to disk"
+        )]
+        privacy_mode: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NodeCommands {
+    #[command(
+        about = "Install the validator node as a systemd, launchd or Windows service",
+        long_about = "Install the validator node as a systemd unit (Linux), a launchd agent \
+                      (macOS) or a Windows service.\n\n\
+                      Windows: the node cannot talk to the Service Control Manager itself, \
+                      so it runs under WinSW (https://github.com/winsw/winsw), which is not \
+                      bundled. The command writes <name>.xml and registers the service only \
+                      when WinSW is saved as <name>.exe next to it; otherwise it prints the \
+                      remaining steps. No native sc.exe definition is generated."
+    )]
+    InstallService {
+        #[arg(long, default_value = "spirachain-node", help = "Service name")]
+        name: String,
+
+        #[arg(long, default_value = "validator_wallet.json")]
+        wallet: String,
+
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+
+        #[arg(long, default_value = "9000")]
+        port: u16,
+
+        #[arg(long, default_value = "testnet")]
+        network: String,
+
+        #[arg(long, help = "Node log file (default: <data-dir>/logs/node.log)")]
+        log_file: Option<String>,
+
+        #[arg(
+            long,
+            help = "Only write the service files to this directory, do not register"
+        )]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum NetCommands {
+    #[command(about = "Export a signed snapshot of the node's peer and gossip mesh view")]
+    Graph {
+        #[arg(long, default_value = "http://127.0.0.1:8545", help = "Node RPC URL")]
+        rpc: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Write the snapshot to this file instead of stdout"
+        )]
+        output: Option<String>,
+    },
+
+    #[command(about = "List the node's peers with their ping round-trip times")]
+    Peers {
+        #[arg(long, default_value = "http://127.0.0.1:8545", help = "Node RPC URL")]
+        rpc: String,
+    },
+
+    #[command(about = "Export the node's libp2p identity key")]
+    ExportIdentity {
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+
+        #[arg(short, long, help = "File to write the key to (created with mode 600)")]
+        output: String,
+    },
+
+    #[command(about = "Replace the node's libp2p identity; the new PeerId is used after restart")]
+    RotateIdentity {
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AdminCommands {
+    #[command(
+        about = "Archive the node's chain data and install a new testnet genesis (never on mainnet)"
+    )]
+    Reset {
+        #[arg(long, help = "Network to reset; mainnet is refused")]
+        network: String,
+
+        #[arg(
+            long,
+            help = "Genesis config JSON every node of the reset network installs"
+        )]
+        genesis: String,
+
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+
+        #[arg(
+            long,
+            help = "Also archive the libp2p identity; the node gets a new PeerId"
+        )]
+        reset_identity: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CeremonyCommands {
+    #[command(about = "Sign this validator's registration for the genesis")]
+    Register {
+        #[arg(short, long, help = "Validator wallet that signs the registration")]
+        wallet: String,
+
+        #[arg(long)]
+        name: String,
+
+        #[arg(long)]
+        region: String,
+
+        #[arg(long, help = "Stake in base units")]
+        stake: u128,
+
+        #[arg(short, long, default_value = "registration.json")]
+        output: String,
+    },
+
+    #[command(about = "Assemble the unsigned genesis from every registration")]
+    Assemble {
+        #[arg(required = true, help = "Registration files")]
+        registrations: Vec<String>,
+
+        #[arg(long, help = "Genesis config supplying everything but the validators")]
+        template: Option<String>,
+
+        #[arg(long, help = "Genesis timestamp in milliseconds")]
+        timestamp: Option<u64>,
+
+        #[arg(short, long, default_value = "genesis.signed.json")]
+        output: String,
+    },
+
+    #[command(about = "Co-sign the assembled genesis")]
+    Sign {
+        #[arg(long, help = "Assembled genesis; the signature is added in place")]
+        genesis: String,
+
+        #[arg(short, long, help = "Genesis validator wallet")]
+        wallet: String,
+
+        #[arg(help = "Registration files to re-assemble and compare before signing")]
+        registrations: Vec<String>,
+
+        #[arg(long, help = "Template the coordinator assembled from")]
+        template: Option<String>,
+    },
+
+    #[command(about = "Check the genesis hashes and that every validator signed")]
+    Verify {
+        #[arg(long)]
+        genesis: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SpecCommands {
+    #[command(
+        about = "Export consensus parameters, genesis, protocol versions, gossip topics and wire formats as JSON"
+    )]
+    Export {
+        #[arg(long, default_value = "testnet")]
+        network: String,
+
+        #[arg(short, long, help = "Write the spec to this file instead of stdout")]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommands {
+    #[command(about = "Have the running node write a consistent copy of its database")]
+    Backup {
+        #[arg(long, default_value = "http://127.0.0.1:8545", help = "Node RPC URL")]
+        rpc: String,
+
+        #[arg(short, long, help = "New directory to write the backup to")]
+        output: String,
+    },
+
+    #[command(about = "Replace a stopped node's database with a backup")]
+    Restore {
+        #[arg(short, long, help = "Backup directory written by db backup")]
+        input: String,
+
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+
+        #[arg(long, default_value = "testnet")]
+        network: String,
     },
 }
 
@@ -104,11 +411,134 @@ enum WalletCommands {
         #[arg(long, help = "Path to sender wallet file")]
         from: String,
 
-        #[arg(long, help = "Recipient address")]
+        #[arg(long, help = "Recipient address, .spira name or @contact")]
+        to: String,
+
+        #[arg(long, help = "Amount in QBT, e.g. 12.5")]
+        amount: String,
+
+        #[arg(long, help = "Fee in QBT; defaults to the node's estimate")]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Export the wallet key as hex, PEM or JWK")]
+    ExportKey {
+        #[arg(short, long)]
+        wallet: String,
+
+        #[arg(long, default_value = "pem", help = "hex, pem or jwk")]
+        format: String,
+
+        #[arg(long, help = "Export the secret key instead of the public key")]
+        secret: bool,
+
+        #[arg(short, long, help = "Write to this file instead of stdout")]
+        output: Option<String>,
+    },
+
+    #[command(about = "Create a wallet from an Ed25519 secret key in hex, PEM or JWK")]
+    ImportKey {
+        #[arg(short, long, help = "Key file")]
+        input: String,
+
+        #[arg(long, default_value = "pem", help = "hex, pem or jwk")]
+        format: String,
+
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    #[command(about = "Convert a key file between hex, PEM and JWK")]
+    ConvertKey {
+        #[arg(short, long, help = "Key file")]
+        input: String,
+
+        #[arg(long, help = "hex, pem or jwk")]
+        from: String,
+
+        #[arg(long, help = "hex, pem or jwk")]
         to: String,
 
-        #[arg(long, help = "Amount in QBT")]
-        amount: f64,
+        #[arg(
+            long,
+            help = "Key kind of hex input: ed25519-public, ed25519-secret, xmss-public, kyber-public or kyber-secret"
+        )]
+        kind: Option<String>,
+
+        #[arg(short, long, help = "Write to this file instead of stdout")]
+        output: Option<String>,
+    },
+
+    #[command(about = "Sign a message off chain to prove ownership of the wallet address")]
+    SignMessage {
+        #[arg(short, long)]
+        wallet: String,
+
+        #[arg(short, long)]
+        message: String,
+
+        #[arg(short, long, help = "Write to this file instead of stdout")]
+        output: Option<String>,
+    },
+
+    #[command(about = "Verify a signed message produced by sign-message")]
+    VerifyMessage {
+        #[arg(short, long, help = "Signed message JSON file")]
+        input: String,
+    },
+
+    #[command(about = "Show transactions sent from the wallet and their status")]
+    History {
+        #[arg(short, long)]
+        wallet: String,
+
+        #[arg(long, default_value = "127.0.0.1")]
+        rpc_host: String,
+
+        #[arg(long, default_value = "9933")]
+        rpc_port: u16,
+
+        #[arg(long, help = "Show the last known status without asking the node")]
+        offline: bool,
+
+        #[arg(long, help = "Export to this CSV file instead of printing")]
+        csv: Option<String>,
+    },
+
+    #[command(about = "Manage named addresses, kept encrypted next to the wallet")]
+    Contacts {
+        #[command(subcommand)]
+        contacts_cmd: ContactsCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContactsCommands {
+    #[command(about = "Save an address under a name, usable as --to @name")]
+    Add {
+        #[arg(short, long)]
+        wallet: String,
+
+        #[arg(short, long)]
+        name: String,
+
+        #[arg(short, long)]
+        address: String,
+    },
+
+    #[command(about = "Forget a contact")]
+    Remove {
+        #[arg(short, long)]
+        wallet: String,
+
+        #[arg(short, long)]
+        name: String,
+    },
+
+    #[command(about = "List saved contacts")]
+    List {
+        #[arg(short, long)]
+        wallet: String,
     },
 }
 
@@ -131,6 +561,75 @@ enum ValidatorCommands {
         #[arg(value_name = "ADDRESS")]
         address: String,
     },
+
+    #[command(about = "Move a validator to a new key without double signing")]
+    Migrate {
+        #[command(subcommand)]
+        migrate_cmd: MigrateCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateCommands {
+    #[command(about = "Retire the old key on its stopped node and write a signing handover")]
+    Export {
+        #[arg(short, long, help = "Wallet of the key being retired")]
+        wallet: String,
+
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+
+        #[arg(short, long, help = "File to write the handover to")]
+        output: String,
+    },
+
+    #[command(about = "Start the new key's double-sign watermark above the old key's")]
+    Import {
+        #[arg(long, help = "Handover written by migrate export")]
+        handover: String,
+
+        #[arg(short, long, help = "Wallet of the new key")]
+        wallet: String,
+
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+    },
+
+    #[command(about = "Move the stake to the new key on chain")]
+    Rotate {
+        #[arg(short, long, help = "Wallet of the key being retired")]
+        wallet: String,
+
+        #[arg(long, help = "Wallet of the new key, which signs its consent")]
+        new_wallet: String,
+
+        #[arg(short, long)]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Move the old key's balance and rewards once the rotation executed")]
+    Sweep {
+        #[arg(short, long, help = "Wallet of the retired key")]
+        wallet: String,
+
+        #[arg(short, long, help = "Address to sweep to, usually the new key")]
+        to: String,
+
+        #[arg(short, long)]
+        fee: Option<String>,
+
+        #[arg(long, help = "Show what would be swept without signing or submitting")]
+        dry_run: bool,
+    },
+
+    #[command(about = "Check on chain that the migration is complete")]
+    Verify {
+        #[arg(long)]
+        old: String,
+
+        #[arg(long)]
+        new: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -155,6 +654,9 @@ enum QueryCommands {
         #[arg(short, long, default_value = "10")]
         limit: usize,
     },
+
+    #[command(about = "Show build provenance of this binary and the local node")]
+    Version,
 }
 
 #[derive(Subcommand)]
@@ -164,23 +666,162 @@ enum TxCommands {
         #[arg(short, long)]
         from: String,
 
-        #[arg(short, long)]
+        #[arg(short, long, help = "Recipient address, .spira name or @contact")]
         to: String,
 
         #[arg(short, long)]
         amount: String,
 
+        #[arg(long, help = "Fee in QBT; defaults to the node's estimate")]
+        fee: Option<String>,
+
         #[arg(short, long)]
         purpose: Option<String>,
+
+        #[arg(long, help = "Hold the transaction until this block height")]
+        execute_at: Option<u64>,
+    },
+
+    #[command(about = "Deploy a contract")]
+    Deploy {
+        #[arg(long, help = "Path to sender wallet file")]
+        from: String,
+
+        #[arg(long, help = "Path to contract bytecode")]
+        code: String,
+
+        #[arg(long, help = "Hex-encoded constructor arguments")]
+        args: Option<String>,
+
+        #[arg(long, help = "Sender account nonce (determines the contract address)")]
+        nonce: u64,
+
+        #[arg(long, help = "QBT sent to the contract")]
+        value: Option<String>,
+
+        #[arg(long, help = "Fee in QBT")]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Call a deployed contract")]
+    Call {
+        #[arg(long, help = "Path to sender wallet file")]
+        from: String,
+
+        #[arg(long, help = "Contract address")]
+        contract: String,
+
+        #[arg(long, help = "Hex-encoded call input")]
+        input: Option<String>,
+
+        #[arg(long, help = "QBT sent with the call")]
+        value: Option<String>,
+
+        #[arg(long, help = "Fee in QBT")]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Claim unclaimed validator rewards")]
+    ClaimRewards {
+        #[arg(long, help = "Path to validator wallet file")]
+        from: String,
+
+        #[arg(long, help = "Payout address (defaults to the validator)")]
+        payout: Option<String>,
+
+        #[arg(long, help = "Fee in QBT, paid out of the rewards")]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Send future block rewards to a separate (cold) address")]
+    SetPayout {
+        #[arg(long, help = "Path to validator wallet file")]
+        from: String,
+
+        #[arg(long, help = "Payout address (the validator itself to reset)")]
+        payout: String,
+
+        #[arg(long, help = "Fee in QBT")]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Register, renew or re-point a .spira name")]
+    RegisterName {
+        #[arg(long, help = "Path to owner wallet file")]
+        from: String,
+
+        #[arg(long, help = "Name, e.g. alice or alice.spira")]
+        name: String,
+
+        #[arg(long, help = "Address the name resolves to (defaults to the owner)")]
+        target: Option<String>,
+
+        #[arg(long, help = "Fee in QBT (at least 0.1)")]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Transfer a .spira name to a new owner")]
+    TransferName {
+        #[arg(long, help = "Path to owner wallet file")]
+        from: String,
+
+        #[arg(long, help = "Name, e.g. alice or alice.spira")]
+        name: String,
+
+        #[arg(long, help = "New owner address")]
+        to: String,
+
+        #[arg(long, help = "Fee in QBT")]
+        fee: Option<String>,
+    },
+
+    #[command(about = "Vote, as a guardian, to pause the chain during an exploit")]
+    Pause {
+        #[arg(long, help = "Path to guardian wallet file")]
+        from: String,
+
+        #[arg(long, help = "Blocks to pause for, 0 to lift the pause")]
+        blocks: u64,
+
+        #[arg(long, help = "Fee in QBT")]
+        fee: Option<String>,
+    },
+    /// Register a custom spiral generator
+    RegisterSpiral {
+        #[arg(long, help = "Path to wallet file")]
+        from: String,
+
+        #[arg(long, help = "Spiral name, [A-Za-z0-9_-]")]
+        name: String,
+
+        #[arg(long, help = "archimedean, logarithmic, fermat or polynomial")]
+        formula: String,
+
+        #[arg(long, help = "Comma-separated parameters, e.g. 1.0,0.5")]
+        params: String,
+
+        #[arg(long, default_value = "5")]
+        turns: u32,
+
+        #[arg(long, help = "Fee in QBT (at least 1)")]
+        fee: Option<String>,
     },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
-
     let cli = Cli::parse();
 
+    let log_file = match &cli.command {
+        Commands::Node {
+            node_cmd: None,
+            log_file,
+            ..
+        } => log_file.as_deref(),
+        _ => None,
+    };
+    logging::init(log_file)?;
+
     match cli.command {
         Commands::Init { data_dir } => {
             init::handle_init(data_dir).await?;
@@ -196,9 +837,72 @@ async fn main() -> anyhow::Result<()> {
             WalletCommands::Balance { address } => {
                 wallet::handle_wallet_balance(address).await?;
             }
-            WalletCommands::Send { from, to, amount } => {
-                wallet::handle_wallet_send(from, to, amount).await?;
+            WalletCommands::Send {
+                from,
+                to,
+                amount,
+                fee,
+            } => {
+                wallet::handle_wallet_send(from, to, amount, fee).await?;
+            }
+            WalletCommands::ExportKey {
+                wallet,
+                format,
+                secret,
+                output,
+            } => {
+                wallet::handle_export_key(wallet, format, secret, output)?;
+            }
+            WalletCommands::ImportKey {
+                input,
+                format,
+                output,
+            } => {
+                wallet::handle_import_key(input, format, output)?;
+            }
+            WalletCommands::ConvertKey {
+                input,
+                from,
+                to,
+                kind,
+                output,
+            } => {
+                wallet::handle_convert_key(input, from, to, kind, output)?;
+            }
+            WalletCommands::SignMessage {
+                wallet,
+                message,
+                output,
+            } => {
+                wallet::handle_sign_message(wallet, message, output)?;
+            }
+            WalletCommands::VerifyMessage { input } => {
+                wallet::handle_verify_message(input)?;
+            }
+            WalletCommands::History {
+                wallet,
+                rpc_host,
+                rpc_port,
+                offline,
+                csv,
+            } => {
+                history::handle_history(wallet, rpc_host, rpc_port, offline, csv).await?;
             }
+            WalletCommands::Contacts { contacts_cmd } => match contacts_cmd {
+                ContactsCommands::Add {
+                    wallet,
+                    name,
+                    address,
+                } => {
+                    contacts::handle_add(wallet, name, address)?;
+                }
+                ContactsCommands::Remove { wallet, name } => {
+                    contacts::handle_remove(wallet, name)?;
+                }
+                ContactsCommands::List { wallet } => {
+                    contacts::handle_list(wallet)?;
+                }
+            },
         },
 
         Commands::Validator { validator_cmd } => match validator_cmd {
@@ -211,6 +915,40 @@ async fn main() -> anyhow::Result<()> {
             ValidatorCommands::Info { address } => {
                 validator::handle_info(address).await?;
             }
+            ValidatorCommands::Migrate { migrate_cmd } => match migrate_cmd {
+                MigrateCommands::Export {
+                    wallet,
+                    data_dir,
+                    output,
+                } => {
+                    migrate::handle_export(wallet, data_dir, output).await?;
+                }
+                MigrateCommands::Import {
+                    handover,
+                    wallet,
+                    data_dir,
+                } => {
+                    migrate::handle_import(handover, wallet, data_dir)?;
+                }
+                MigrateCommands::Rotate {
+                    wallet,
+                    new_wallet,
+                    fee,
+                } => {
+                    migrate::handle_rotate(wallet, new_wallet, fee).await?;
+                }
+                MigrateCommands::Sweep {
+                    wallet,
+                    to,
+                    fee,
+                    dry_run,
+                } => {
+                    migrate::handle_sweep(wallet, to, fee, dry_run).await?;
+                }
+                MigrateCommands::Verify { old, new } => {
+                    migrate::handle_verify(old, new).await?;
+                }
+            },
         },
 
         Commands::Query { query_cmd } => match query_cmd {
@@ -223,6 +961,9 @@ async fn main() -> anyhow::Result<()> {
             QueryCommands::Semantic { query, limit } => {
                 query::handle_semantic_query(query, limit).await?;
             }
+            QueryCommands::Version => {
+                query::handle_version_query().await?;
+            }
         },
 
         Commands::Tx { tx_cmd } => match tx_cmd {
@@ -230,9 +971,131 @@ async fn main() -> anyhow::Result<()> {
                 from,
                 to,
                 amount,
+                fee,
                 purpose,
+                execute_at,
+            } => {
+                tx::handle_send(from, to, amount, fee, purpose, execute_at).await?;
+            }
+            TxCommands::Deploy {
+                from,
+                code,
+                args,
+                nonce,
+                value,
+                fee,
+            } => {
+                tx::handle_deploy(from, code, args, nonce, value, fee).await?;
+            }
+            TxCommands::Call {
+                from,
+                contract,
+                input,
+                value,
+                fee,
+            } => {
+                tx::handle_call(from, contract, input, value, fee).await?;
+            }
+            TxCommands::ClaimRewards { from, payout, fee } => {
+                tx::handle_claim_rewards(from, payout, fee).await?;
+            }
+            TxCommands::SetPayout { from, payout, fee } => {
+                tx::handle_set_payout(from, payout, fee).await?;
+            }
+            TxCommands::RegisterName {
+                from,
+                name,
+                target,
+                fee,
+            } => {
+                tx::handle_register_name(from, name, target, fee).await?;
+            }
+            TxCommands::TransferName {
+                from,
+                name,
+                to,
+                fee,
+            } => {
+                tx::handle_transfer_name(from, name, to, fee).await?;
+            }
+            TxCommands::Pause { from, blocks, fee } => {
+                tx::handle_pause_vote(from, blocks, fee).await?;
+            }
+            TxCommands::RegisterSpiral {
+                from,
+                name,
+                formula,
+                params,
+                turns,
+                fee,
+            } => {
+                tx::handle_register_spiral(from, name, formula, params, turns, fee).await?;
+            }
+        },
+
+        Commands::Net { net_cmd } => match net_cmd {
+            NetCommands::Graph { rpc, output } => {
+                net::handle_net_graph(rpc, output).await?;
+            }
+            NetCommands::Peers { rpc } => {
+                net::handle_net_peers(rpc).await?;
+            }
+            NetCommands::ExportIdentity { data_dir, output } => {
+                net::handle_export_identity(data_dir, output)?;
+            }
+            NetCommands::RotateIdentity { data_dir } => {
+                net::handle_rotate_identity(data_dir)?;
+            }
+        },
+
+        Commands::Admin { admin_cmd } => match admin_cmd {
+            AdminCommands::Reset {
+                network,
+                genesis,
+                data_dir,
+                reset_identity,
             } => {
-                tx::handle_send(from, to, amount, None, purpose).await?;
+                admin::handle_admin_reset(network, genesis, data_dir, reset_identity)?;
+            }
+        },
+
+        Commands::Db { db_cmd } => match db_cmd {
+            DbCommands::Backup { rpc, output } => {
+                db::handle_db_backup(rpc, output).await?;
+            }
+            DbCommands::Restore {
+                input,
+                data_dir,
+                network,
+            } => {
+                db::handle_db_restore(input, data_dir, network)?;
+            }
+        },
+
+        Commands::Faucet {
+            wallet,
+            port,
+            rpc_host,
+            rpc_port,
+            amount,
+            address_cooldown_hours,
+            ip_cooldown_minutes,
+        } => {
+            faucet::handle_faucet(faucet::FaucetOptions {
+                wallet,
+                port,
+                rpc_host,
+                rpc_port,
+                amount,
+                address_cooldown_hours,
+                ip_cooldown_minutes,
+            })
+            .await?;
+        }
+
+        Commands::Spec { spec_cmd } => match spec_cmd {
+            SpecCommands::Export { network, output } => {
+                spec::handle_spec_export(network, output)?;
             }
         },
 
@@ -240,6 +1103,45 @@ async fn main() -> anyhow::Result<()> {
             genesis::handle_genesis(output).await?;
         }
 
+        Commands::VerifyChain {
+            data_dir,
+            network,
+            to,
+        } => {
+            db::handle_verify_chain(data_dir, network, to)?;
+        }
+
+        Commands::Ceremony { ceremony_cmd } => match ceremony_cmd {
+            CeremonyCommands::Register {
+                wallet,
+                name,
+                region,
+                stake,
+                output,
+            } => {
+                ceremony::handle_register(wallet, name, region, stake, output)?;
+            }
+            CeremonyCommands::Assemble {
+                registrations,
+                template,
+                timestamp,
+                output,
+            } => {
+                ceremony::handle_assemble(registrations, template, timestamp, output)?;
+            }
+            CeremonyCommands::Sign {
+                genesis,
+                wallet,
+                registrations,
+                template,
+            } => {
+                ceremony::handle_sign(genesis, wallet, registrations, template)?;
+            }
+            CeremonyCommands::Verify { genesis } => {
+                ceremony::handle_verify(genesis)?;
+            }
+        },
+
         Commands::Calculate {
             constant: _,
             precision,
@@ -249,13 +1151,56 @@ async fn main() -> anyhow::Result<()> {
         }
 
         Commands::Node {
+            node_cmd: Some(node_cmd),
+            ..
+        } => match node_cmd {
+            NodeCommands::InstallService {
+                name,
+                wallet,
+                data_dir,
+                port,
+                network,
+                log_file,
+                output,
+            } => {
+                service::handle_install_service(service::ServiceOptions {
+                    name,
+                    wallet,
+                    data_dir,
+                    port,
+                    network,
+                    log_file,
+                    output,
+                })
+                .await?;
+            }
+        },
+
+        Commands::Node {
+            node_cmd: None,
             validator,
             wallet,
             data_dir,
             port,
             network,
+            log_file: _,
+            allow_double_sign,
+            dry_run,
+            encrypt_at_rest,
+            privacy_mode,
         } => {
-            node::handle_node_start(validator, wallet, data_dir, port, network).await?;
+            node::handle_node_start(
+                validator,
+                wallet,
+                data_dir,
+                port,
+                network,
+                allow_double_sign,
+                dry_run,
+                encrypt_at_rest,
+                privacy_mode,
+            )
+            .await?;
         }
     }
 
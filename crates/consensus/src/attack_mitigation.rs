@@ -1,17 +1,16 @@
-use spirachain_core::{Address, Block, Hash, Result, SpiraChainError, Transaction};
+use crate::{Checkpoint, CheckpointSet, CHECKPOINT_INTERVAL};
+use spirachain_core::{Address, Amount, Block, Hash, Result, SpiraChainError, Transaction};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
 pub const DOUBLE_SPEND_WINDOW: Duration = Duration::from_secs(300);
-pub const CHECKPOINT_INTERVAL: u64 = 100;
 pub const SLASHING_AMOUNT_PERCENT: f64 = 30.0;
 
 pub struct AttackMitigationSystem {
-    checkpoints: HashMap<u64, Hash>,
+    checkpoints: CheckpointSet,
     double_spend_detector: DoubleSpendDetector,
     validator_monitor: ValidatorMonitor,
-    last_checkpoint_height: u64,
 }
 
 struct DoubleSpendDetector {
@@ -102,7 +101,7 @@ impl AttackMitigationSystem {
         info!("   Slashing rate: {}%", SLASHING_AMOUNT_PERCENT);
 
         Self {
-            checkpoints: HashMap::new(),
+            checkpoints: CheckpointSet::new(),
             double_spend_detector: DoubleSpendDetector {
                 recent_transactions: HashMap::new(),
                 addresses_monitored: HashMap::new(),
@@ -111,7 +110,6 @@ impl AttackMitigationSystem {
                 suspicious_validators: HashMap::new(),
                 blocks_per_validator: HashMap::new(),
             },
-            last_checkpoint_height: 0,
         }
     }
 
@@ -244,11 +242,23 @@ impl AttackMitigationSystem {
         }
     }
 
+    /// Start from checkpoints loaded from storage
+    pub fn with_checkpoints(mut self, checkpoints: CheckpointSet) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    pub fn checkpoints(&self) -> &CheckpointSet {
+        &self.checkpoints
+    }
+
     pub fn create_checkpoint(&mut self, block: &Block) {
         let block_hash = block.hash();
-        self.checkpoints
-            .insert(block.header.block_height, block_hash);
-        self.last_checkpoint_height = block.header.block_height;
+        let checkpoint = Checkpoint::new(block.header.block_height, block_hash);
+        if let Err(e) = self.checkpoints.insert(checkpoint) {
+            error!("🚨 {}", e);
+            return;
+        }
 
         info!(
             "📍 Checkpoint created at height {}",
@@ -259,12 +269,12 @@ impl AttackMitigationSystem {
     }
 
     pub fn is_finalized(&self, block_height: u64) -> bool {
-        block_height <= self.last_checkpoint_height
+        self.checkpoints.is_finalized(block_height)
     }
 
     pub fn get_checkpoint(&self, height: u64) -> Option<Hash> {
         let checkpoint_height = (height / CHECKPOINT_INTERVAL) * CHECKPOINT_INTERVAL;
-        self.checkpoints.get(&checkpoint_height).copied()
+        self.checkpoints.get(checkpoint_height)
     }
 
     pub fn slash_validator(&mut self, validator: Address, reason: &str) -> u128 {
@@ -273,7 +283,10 @@ impl AttackMitigationSystem {
         error!("⚔️  SLASHING VALIDATOR!");
         error!("   Validator: {}", validator);
         error!("   Reason: {}", reason);
-        error!("   Amount: {} QBT", slashing_amount as f64 / 1e18);
+        error!(
+            "   Amount: {} QBT",
+            Amount::new(slashing_amount).to_qbt_string()
+        );
 
         self.validator_monitor
             .suspicious_validators
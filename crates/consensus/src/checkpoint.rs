@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Block, Hash, Result, SpiraChainError, FINALITY_BLOCKS};
+use std::collections::BTreeMap;
+
+pub const CHECKPOINT_INTERVAL: u64 = 100;
+
+/// A finalized block: the chain never reorganizes below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub height: u64,
+    pub block_hash: Hash,
+}
+
+impl Checkpoint {
+    pub fn new(height: u64, block_hash: Hash) -> Self {
+        Self { height, block_hash }
+    }
+
+    /// Height to checkpoint once the chain reaches `tip_height`, if any. Blocks
+    /// are checkpointed every [`CHECKPOINT_INTERVAL`] once [`FINALITY_BLOCKS`] deep.
+    pub fn due_at(tip_height: u64) -> Option<u64> {
+        tip_height
+            .checked_sub(FINALITY_BLOCKS)
+            .filter(|height| *height > 0 && height.is_multiple_of(CHECKPOINT_INTERVAL))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointSet {
+    checkpoints: BTreeMap<u64, Hash>,
+}
+
+impl CheckpointSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_checkpoints(checkpoints: impl IntoIterator<Item = Checkpoint>) -> Self {
+        Self {
+            checkpoints: checkpoints
+                .into_iter()
+                .map(|checkpoint| (checkpoint.height, checkpoint.block_hash))
+                .collect(),
+        }
+    }
+
+    /// Add a checkpoint. Returns whether it was new; a different hash at a
+    /// checkpointed height is an error.
+    pub fn insert(&mut self, checkpoint: Checkpoint) -> Result<bool> {
+        match self.checkpoints.get(&checkpoint.height) {
+            Some(hash) if *hash == checkpoint.block_hash => Ok(false),
+            Some(hash) => Err(SpiraChainError::ConsensusError(format!(
+                "Conflicting checkpoint at height {}: have {}, got {}",
+                checkpoint.height, hash, checkpoint.block_hash
+            ))),
+            None => {
+                self.checkpoints
+                    .insert(checkpoint.height, checkpoint.block_hash);
+                Ok(true)
+            }
+        }
+    }
+
+    pub fn get(&self, height: u64) -> Option<Hash> {
+        self.checkpoints.get(&height).copied()
+    }
+
+    pub fn latest(&self) -> Option<Checkpoint> {
+        self.checkpoints
+            .last_key_value()
+            .map(|(height, hash)| Checkpoint::new(*height, *hash))
+    }
+
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Checkpoint> + '_ {
+        self.checkpoints
+            .iter()
+            .map(|(height, hash)| Checkpoint::new(*height, *hash))
+    }
+
+    pub fn is_finalized(&self, height: u64) -> bool {
+        self.latest()
+            .is_some_and(|checkpoint| height <= checkpoint.height)
+    }
+
+    /// Refuse a reorg that would replace blocks from `first_replaced_height` up
+    /// if a checkpoint lies in that range
+    pub fn check_reorg(&self, first_replaced_height: u64) -> Result<()> {
+        match self.latest() {
+            Some(checkpoint) if checkpoint.height >= first_replaced_height => {
+                Err(SpiraChainError::ConsensusError(format!(
+                    "Reorg from height {} crosses checkpoint {} at height {}",
+                    first_replaced_height, checkpoint.block_hash, checkpoint.height
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// A block at a checkpointed height must be the checkpointed block
+    pub fn verify_block(&self, block: &Block) -> Result<()> {
+        let height = block.header.block_height;
+        match self.get(height) {
+            Some(expected) if expected != block.hash() => {
+                Err(SpiraChainError::InvalidBlock(format!(
+                    "Block {} at height {} does not match checkpoint {}",
+                    block.hash(),
+                    height,
+                    expected
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_schedule() {
+        assert_eq!(Checkpoint::due_at(FINALITY_BLOCKS), None);
+        assert_eq!(
+            Checkpoint::due_at(CHECKPOINT_INTERVAL + FINALITY_BLOCKS),
+            Some(CHECKPOINT_INTERVAL)
+        );
+        assert_eq!(
+            Checkpoint::due_at(CHECKPOINT_INTERVAL + FINALITY_BLOCKS + 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reorgs_stop_at_latest_checkpoint() {
+        let mut checkpoints = CheckpointSet::new();
+        assert!(checkpoints.check_reorg(1).is_ok());
+
+        assert!(checkpoints
+            .insert(Checkpoint::new(100, Hash::new([1u8; 32])))
+            .unwrap());
+        assert!(!checkpoints
+            .insert(Checkpoint::new(100, Hash::new([1u8; 32])))
+            .unwrap());
+        assert!(checkpoints
+            .insert(Checkpoint::new(100, Hash::new([2u8; 32])))
+            .is_err());
+
+        assert!(checkpoints.is_finalized(100));
+        assert!(!checkpoints.is_finalized(101));
+        assert!(checkpoints.check_reorg(100).is_err());
+        assert!(checkpoints.check_reorg(101).is_ok());
+
+        let restored = CheckpointSet::from_checkpoints(checkpoints.iter());
+        assert_eq!(restored.latest(), checkpoints.latest());
+    }
+}
@@ -0,0 +1,167 @@
+// Spiral continuity cache
+// Proofs of continuity that passed are kept by (block hash, parent hash), so
+// validating the same pair again - the producer's self-check, a re-org back
+// onto a known branch, an archive replay - re-runs the rule on the recorded
+// values. A store behind the cache keeps the proofs across restarts.
+
+use spirachain_core::{Block, ContinuityProof, Hash, Result, SpiraChainError};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Proofs kept in memory; older ones are still found in the store
+pub const CONTINUITY_CACHE_SIZE: usize = 4096;
+
+/// Where validated continuity proofs are persisted
+pub trait ContinuityStore: Send + Sync {
+    fn load_continuity(&self, block_hash: &Hash, parent_hash: &Hash) -> Option<ContinuityProof>;
+    fn store_continuity(&self, proof: &ContinuityProof);
+}
+
+#[derive(Default)]
+struct Entries {
+    proofs: HashMap<(Hash, Hash), ContinuityProof>,
+    order: VecDeque<(Hash, Hash)>,
+}
+
+#[derive(Default)]
+pub struct ContinuityCache {
+    entries: Mutex<Entries>,
+    store: Option<Arc<dyn ContinuityStore>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ContinuityCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn ContinuityStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Proof already validated for this pair, from memory or the store
+    pub fn get(&self, block_hash: &Hash, parent_hash: &Hash) -> Option<ContinuityProof> {
+        let key = (*block_hash, *parent_hash);
+        if let Some(proof) = self.lock().proofs.get(&key) {
+            return Some(proof.clone());
+        }
+        let proof = self
+            .store
+            .as_ref()?
+            .load_continuity(block_hash, parent_hash)
+            .filter(|proof| proof.covers(block_hash, parent_hash))?;
+        self.remember(proof.clone());
+        Some(proof)
+    }
+
+    /// Continuity of `block` on `parent`, recorded once it passes
+    pub fn verify(
+        &self,
+        parent: &Block,
+        block: &Block,
+        max_spiral_jump: f64,
+    ) -> Result<ContinuityProof> {
+        let invalid =
+            |e: spirachain_core::LightError| SpiraChainError::InvalidSpiral(e.to_string());
+
+        if let Some(proof) = self.get(&block.hash(), &parent.hash()) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            // The rule may be stricter than when the proof was recorded
+            proof.check(max_spiral_jump).map_err(invalid)?;
+            return Ok(proof);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let proof = ContinuityProof::compute(parent, block);
+        proof.check(max_spiral_jump).map_err(invalid)?;
+        if let Some(store) = &self.store {
+            store.store_continuity(&proof);
+        }
+        self.remember(proof.clone());
+        Ok(proof)
+    }
+
+    /// Lookups answered from a recorded proof, and those that were not
+    pub fn stats(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    fn remember(&self, proof: ContinuityProof) {
+        let mut entries = self.lock();
+        let key = (proof.block_hash, proof.parent_hash);
+        if entries.proofs.insert(key, proof).is_none() {
+            entries.order.push_back(key);
+        }
+        while entries.order.len() > CONTINUITY_CACHE_SIZE {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.proofs.remove(&oldest);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::PiCoordinate;
+
+    #[derive(Default)]
+    struct MemoryStore(Mutex<Vec<ContinuityProof>>);
+
+    impl ContinuityStore for MemoryStore {
+        fn load_continuity(
+            &self,
+            block_hash: &Hash,
+            parent_hash: &Hash,
+        ) -> Option<ContinuityProof> {
+            let proofs = self.0.lock().unwrap();
+            proofs
+                .iter()
+                .find(|proof| proof.covers(block_hash, parent_hash))
+                .cloned()
+        }
+
+        fn store_continuity(&self, proof: &ContinuityProof) {
+            self.0.lock().unwrap().push(proof.clone());
+        }
+    }
+
+    #[test]
+    fn test_cached_and_persisted_proofs() {
+        let parent = Block::new(Hash::zero(), 0);
+        let mut block = Block::new(parent.hash(), 1);
+        block.header.spiral.complexity = parent.header.spiral.complexity;
+        block.header.pi_coordinates = PiCoordinate::new(3.0, 4.0, 0.0, 0.0);
+
+        let store = Arc::new(MemoryStore::default());
+        let cache = ContinuityCache::new().with_store(store.clone());
+        cache.verify(&parent, &block, 10.0).unwrap();
+        cache.verify(&parent, &block, 10.0).unwrap();
+        assert_eq!(cache.stats(), (1, 1));
+        assert_eq!(store.0.lock().unwrap().len(), 1);
+
+        // A stricter rule still rejects a recorded pair
+        assert!(cache.verify(&parent, &block, 1.0).is_err());
+
+        // A fresh cache finds the proof in the store
+        let restarted = ContinuityCache::new().with_store(store.clone());
+        restarted.verify(&parent, &block, 10.0).unwrap();
+        assert_eq!(restarted.stats(), (1, 0));
+
+        // Failures are never recorded
+        let mut far = block.clone();
+        far.header.pi_coordinates = PiCoordinate::new(100.0, 0.0, 0.0, 0.0);
+        assert!(cache.verify(&parent, &far, 10.0).is_err());
+        assert!(cache.get(&far.hash(), &parent.hash()).is_none());
+    }
+}
@@ -79,18 +79,16 @@ impl Default for DifficultyAdjuster {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use spirachain_core::Hash;
+    use spirachain_core::ChainBuilder;
 
     #[test]
     fn test_difficulty_adjustment() {
         let adjuster = DifficultyAdjuster::new();
 
-        let mut blocks = Vec::new();
-        for i in 0..2016 {
-            let mut block = Block::new(Hash::zero(), i);
-            block.header.timestamp = i * 30 * 1000;
-            blocks.push(block);
-        }
+        let blocks = ChainBuilder::new("testnet")
+            .with_block_interval(30 * 1000)
+            .build(2015)
+            .blocks;
 
         let (complexity, difficulty) = adjuster.adjust_difficulty(&blocks);
 
@@ -102,12 +100,10 @@ mod tests {
     fn test_fast_blocks_increase_difficulty() {
         let adjuster = DifficultyAdjuster::new();
 
-        let mut blocks = Vec::new();
-        for i in 0..2016 {
-            let mut block = Block::new(Hash::zero(), i);
-            block.header.timestamp = i * 20 * 1000;
-            blocks.push(block);
-        }
+        let blocks = ChainBuilder::new("testnet")
+            .with_block_interval(20 * 1000)
+            .build(2015)
+            .blocks;
 
         let (complexity, _) = adjuster.adjust_difficulty(&blocks);
 
@@ -0,0 +1,126 @@
+// Equivocation detection
+// A producer signing two different blocks for the same height is trying to
+// split the network. Headers of received blocks are remembered per producer
+// and height for a while; a second, different header signed by the same
+// producer is proof of the offence. The evidence carries both headers, so any
+// node can check it without trusting whoever reported it.
+
+use spirachain_core::{Address, BlockHeader, Result, SpiraChainError};
+use std::collections::{HashMap, HashSet};
+
+/// Headers older than this many blocks below the newest seen are forgotten
+pub const EQUIVOCATION_HORIZON: u64 = 256;
+
+/// Two headers signed by one producer for the same height
+#[derive(Debug, Clone)]
+pub struct EquivocationEvidence {
+    pub first: BlockHeader,
+    pub second: BlockHeader,
+}
+
+impl EquivocationEvidence {
+    pub fn height(&self) -> u64 {
+        self.first.block_height
+    }
+
+    /// Check the evidence and return the offending producer
+    pub fn verify(&self) -> Result<Address> {
+        let producer = self
+            .first
+            .producer_address()
+            .ok_or(SpiraChainError::InvalidSignature)?;
+        if self.second.producer_address() != Some(producer) {
+            return Err(SpiraChainError::ConsensusError(
+                "Evidence headers have different producers".to_string(),
+            ));
+        }
+        if self.first.block_height != self.second.block_height {
+            return Err(SpiraChainError::ConsensusError(
+                "Evidence headers are for different heights".to_string(),
+            ));
+        }
+        if self.first.hash() == self.second.hash() {
+            return Err(SpiraChainError::ConsensusError(
+                "Evidence headers are the same block".to_string(),
+            ));
+        }
+        self.first.verify_signature()?;
+        self.second.verify_signature()?;
+        Ok(producer)
+    }
+}
+
+#[derive(Default)]
+pub struct EquivocationDetector {
+    seen: HashMap<(Address, u64), BlockHeader>,
+    /// Offences already reported, so a producer is only punished once per height
+    reported: HashSet<(Address, u64)>,
+}
+
+impl EquivocationDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `header`; evidence when its producer already signed another
+    /// block for its height. Headers with an invalid signature are ignored.
+    pub fn observe(&mut self, header: &BlockHeader) -> Option<EquivocationEvidence> {
+        let producer = header.producer_address()?;
+        let key = (producer, header.block_height);
+        match self.seen.get(&key) {
+            Some(first) if first.hash() == header.hash() => None,
+            Some(first) => {
+                let evidence = EquivocationEvidence {
+                    first: first.clone(),
+                    second: header.clone(),
+                };
+                if evidence.verify().is_err() || !self.reported.insert(key) {
+                    return None;
+                }
+                Some(evidence)
+            }
+            None => {
+                if header.verify_signature().is_ok() {
+                    self.seen.insert(key, header.clone());
+                    self.prune(header.block_height);
+                }
+                None
+            }
+        }
+    }
+
+    fn prune(&mut self, newest: u64) {
+        let oldest = newest.saturating_sub(EQUIVOCATION_HORIZON);
+        self.seen.retain(|(_, height), _| *height >= oldest);
+        self.reported.retain(|(_, height)| *height >= oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::ChainBuilder;
+
+    #[test]
+    fn test_equivocation_is_detected_once() {
+        let chain = ChainBuilder::new("testnet").build(2);
+        let honest = chain.block(2).header.clone();
+        let mut twin = honest.clone();
+        twin.extra_data = b"twin".to_vec();
+        let mut twin_block = chain.block(2).clone();
+        twin_block.header = twin;
+        chain.resign(&mut twin_block);
+
+        let mut detector = EquivocationDetector::new();
+        assert!(detector.observe(&honest).is_none());
+        assert!(detector.observe(&honest).is_none());
+        let evidence = detector.observe(&twin_block.header).unwrap();
+        assert_eq!(evidence.verify().unwrap(), chain.producer_of(2).address());
+        assert!(detector.observe(&twin_block.header).is_none());
+
+        // A forged header is not evidence against the producer
+        let mut forged = twin_block.header.clone();
+        forged.extra_data = b"forged".to_vec();
+        assert!(detector.observe(&forged).is_none());
+    }
+}
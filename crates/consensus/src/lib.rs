@@ -1,6 +1,9 @@
 pub mod attack_mitigation;
 pub mod bft;
+pub mod checkpoint;
+pub mod continuity_cache;
 pub mod difficulty;
+pub mod equivocation;
 pub mod proof_of_spiral;
 pub mod rewards;
 pub mod slot_consensus;
@@ -8,7 +11,10 @@ pub mod validator;
 
 pub use attack_mitigation::*;
 pub use bft::*;
+pub use checkpoint::*;
+pub use continuity_cache::*;
 pub use difficulty::*;
+pub use equivocation::*;
 pub use proof_of_spiral::*;
 pub use rewards::*;
 pub use slot_consensus::*;
@@ -1,16 +1,32 @@
-use crate::{Validator, ValidatorSet};
+use crate::{
+    ContinuityCache, ContinuityStore, RewardCalculator, SlashingReason, Validator, ValidatorSet,
+};
 use spirachain_core::{
-    Amount, Block, PiCoordinate, Result, SpiraChainError, Spiral, SpiralMetadata, SpiralType,
-    Transaction,
+    diversity_epoch, is_epoch_start, sort_canonical, spiral_kind, Address, Amount, Block,
+    BlockLimits, ChainParams, ContinuityProof, EpochSummary, PiCoordinate, Result,
+    SemanticScoring, SpiraChainError, Spiral, SpiralDiversity, SpiralMetadata, SpiralType,
+    Transaction, TransactionLane,
+    TESTNET_PARAMS,
 };
 use spirachain_crypto::KeyPair;
 use spirapi_bridge;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Block bytes kept free of mempool transactions for the header, the coinbase
+/// and the signature; the finished block is still checked against the limit
+const BLOCK_OVERHEAD_RESERVE: usize = 4096;
 
 pub struct ProofOfSpiral {
     min_complexity: f64,
     max_spiral_jump: f64,
     validator_set: ValidatorSet,
-    recent_spiral_types: Vec<SpiralType>,
+    diversity: SpiralDiversity,
+    epoch_summary: Option<EpochSummary>,
+    semantic_scoring: SemanticScoring,
+    continuity: ContinuityCache,
+    chain_params: &'static ChainParams,
 }
 
 impl ProofOfSpiral {
@@ -19,10 +35,37 @@ impl ProofOfSpiral {
             min_complexity,
             max_spiral_jump,
             validator_set: ValidatorSet::new(),
-            recent_spiral_types: Vec::new(),
+            diversity: SpiralDiversity::new(),
+            epoch_summary: None,
+            semantic_scoring: SemanticScoring::Model,
+            continuity: ContinuityCache::new(),
+            chain_params: &TESTNET_PARAMS,
         }
     }
 
+    /// Hard-fork schedule used to version produced blocks and check received ones
+    pub fn with_chain_params(mut self, chain_params: &'static ChainParams) -> Self {
+        self.chain_params = chain_params;
+        self
+    }
+
+    /// Persist validated continuity proofs in `store` and look them up there
+    pub fn with_continuity_store(mut self, store: Arc<dyn ContinuityStore>) -> Self {
+        self.continuity = std::mem::take(&mut self.continuity).with_store(store);
+        self
+    }
+
+    /// Spiral continuity of `block` on `previous_block`, from the cache when
+    /// this pair was validated before
+    pub fn continuity_proof(
+        &self,
+        block: &Block,
+        previous_block: &Block,
+    ) -> Result<ContinuityProof> {
+        self.continuity
+            .verify(previous_block, block, self.max_spiral_jump)
+    }
+
     pub fn generate_block_candidate(
         &self,
         validator: &Validator,
@@ -30,35 +73,93 @@ impl ProofOfSpiral {
         pending_txs: Vec<Transaction>,
         previous_block: &Block,
     ) -> Result<Block> {
-        let selected_txs = self.semantic_clustering(pending_txs)?;
+        let mut block = self.build_block_candidate(validator, pending_txs, previous_block)?;
+
+        let signature_bytes = keypair.sign(block.hash().as_bytes());
+        block.header.signature = signature_bytes;
+
+        block.validate_limits(&self.chain_params.block_limits)?;
+        Ok(block)
+    }
+
+    /// Everything [`Self::generate_block_candidate`] does short of signing
+    pub fn build_block_candidate(
+        &self,
+        validator: &Validator,
+        pending_txs: Vec<Transaction>,
+        previous_block: &Block,
+    ) -> Result<Block> {
+        let height = previous_block.header.block_height + 1;
+
+        // The first block of an epoch summarizes the last one, in room taken
+        // from mempool transactions
+        let summary = self
+            .epoch_summary
+            .clone()
+            .filter(|summary| {
+                is_epoch_start(height) && summary.epoch + 1 == diversity_epoch(height)
+            })
+            .map(|summary| {
+                let mut tx = Transaction::new_epoch_summary(summary);
+                tx.compute_hash();
+                tx
+            });
+        let mut limits = self.chain_params.block_limits;
+        if let Some(summary) = &summary {
+            limits.max_tx_per_block = limits.max_tx_per_block.saturating_sub(1);
+            limits.max_block_size = limits
+                .max_block_size
+                .saturating_sub(summary.serialize().len());
+        }
+        let mut selected_txs = self.semantic_clustering(pending_txs, &limits)?;
+        sort_canonical(&mut selected_txs);
 
         let spiral = self.create_spiral(&selected_txs, &previous_block.header.spiral)?;
 
+        // Coinbase carries the scheduled reward, less the repeat penalty when we
+        // already produced this spiral kind in the epoch, plus the unburned
+        // share of every fee in the block
+        let (fees, _) = RewardCalculator::distribute_fees(selected_txs.iter().fold(
+            Amount::zero(),
+            |sum, tx| sum.checked_add(tx.fee).unwrap_or(sum),
+        ));
+        let novel =
+            self.diversity
+                .is_novel(&validator.address, &spiral_kind(&spiral.metadata), height);
+        let coinbase_amount = RewardCalculator::diversity_reward_at_height(height, novel)
+            .checked_add(fees)
+            .ok_or_else(|| SpiraChainError::ConsensusError("Coinbase overflow".to_string()))?;
+        let mut coinbase = Transaction::new_coinbase(validator.address, coinbase_amount, height);
+        coinbase.compute_hash();
+        selected_txs.insert(0, coinbase);
+        if let Some(summary) = summary {
+            selected_txs.insert(1, summary);
+        }
+
         let pi_coords = self.generate_block_coordinates(previous_block, &spiral)?;
 
-        let mut block = Block::new(
-            previous_block.hash(),
-            previous_block.header.block_height + 1,
-        )
-        .with_transactions(selected_txs)
-        .with_spiral(spiral.metadata.clone())
-        .with_pi_coordinates(pi_coords)
-        .with_validator(validator.pubkey.clone());
+        let mut block = Block::new(previous_block.hash(), height)
+            .with_transactions(selected_txs)
+            .with_spiral(spiral.metadata.clone())
+            .with_pi_coordinates(pi_coords)
+            .with_validator(validator.pubkey.clone());
+        block.header.version = self.chain_params.protocol_version_at(height) as u64;
 
         block.compute_merkle_root();
+        block.compute_event_bloom();
         block.compute_spiral_root();
 
         let nonce = self.find_nonce(&block)?;
         block.header.nonce = nonce;
 
-        let signature_bytes = keypair.sign(block.hash().as_bytes());
-        block.header.signature = signature_bytes;
-
         Ok(block)
     }
 
     pub fn validate_block(&self, block: &Block, previous_block: &Block) -> Result<()> {
-        block.validate()?;
+        block.validate(self.chain_params)?;
+        block.validate_limits(&self.chain_params.block_limits)?;
+        self.chain_params
+            .check_block_version(block.header.block_height, block.header.version)?;
 
         if block.header.spiral.complexity < self.min_complexity {
             return Err(SpiraChainError::SpiralComplexityTooLow(
@@ -76,6 +177,10 @@ impl ProofOfSpiral {
 
         self.verify_spiral_continuity(block, previous_block)?;
 
+        RewardCalculator::verify_coinbase(block, self.chain_params)?;
+
+        self.verify_pi_identifiers(block)?;
+
         let validator = self
             .validator_set
             .get_validator(&self.extract_validator_address(&block.header.validator_pubkey)?)
@@ -97,24 +202,82 @@ impl ProofOfSpiral {
         Ok(())
     }
 
-    fn semantic_clustering(&self, mut transactions: Vec<Transaction>) -> Result<Vec<Transaction>> {
-        if transactions.len() <= spirachain_core::MAX_TX_PER_BLOCK {
+    /// Recompute every attached π identifier and reject duplicates within the block
+    fn verify_pi_identifiers(&self, block: &Block) -> Result<()> {
+        let mut seen = HashSet::new();
+        for tx in &block.transactions {
+            if let Some(id) = spirapi_bridge::verify_transaction_pi_identifier(tx)? {
+                if !seen.insert(id.identifier) {
+                    return Err(SpiraChainError::InvalidBlock(format!(
+                        "Duplicate π identifier in transaction {}",
+                        tx.tx_hash
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pick the transactions to include: all of them if they fit, otherwise the
+    /// best scored ones that fit the count and size limits
+    fn semantic_clustering(
+        &self,
+        mut transactions: Vec<Transaction>,
+        limits: &BlockLimits,
+    ) -> Result<Vec<Transaction>> {
+        // One slot is reserved for the coinbase
+        let max_txs = limits.max_tx_per_block.saturating_sub(1);
+        let max_bytes = limits.max_block_size.saturating_sub(BLOCK_OVERHEAD_RESERVE);
+        let sizes: Vec<usize> = transactions.iter().map(|tx| tx.serialize().len()).collect();
+
+        if transactions.len() <= max_txs && sizes.iter().sum::<usize>() <= max_bytes {
             return Ok(transactions);
         }
 
-        transactions.sort_by(|a, b| {
-            let score_a = self.transaction_score(a);
-            let score_b = self.transaction_score(b);
-            score_b.partial_cmp(&score_a).unwrap()
-        });
+        let (mut priority, mut scored): (Vec<_>, Vec<_>) = transactions
+            .drain(..)
+            .zip(sizes)
+            .map(|(tx, size)| (self.transaction_score(&tx), size, tx))
+            .partition(|(_, _, tx)| TransactionLane::of(tx).limits().is_some());
+
+        // Priority lanes fill their reserved slots first, highest fee first; the
+        // rest of their transactions compete with everyone else
+        let mut used = 0;
+        let mut selected = Vec::new();
+        let mut reserved_used: HashMap<TransactionLane, usize> = HashMap::new();
+        priority.sort_by_key(|(_, _, tx)| Reverse(tx.fee));
+        for (score, size, tx) in priority {
+            let lane = TransactionLane::of(&tx);
+            let taken = reserved_used.entry(lane).or_default();
+            let reserved = lane.limits().map_or(0, |limits| limits.reserved_txs);
+            if *taken < reserved && selected.len() < max_txs && used + size <= max_bytes {
+                *taken += 1;
+                used += size;
+                selected.push(tx);
+            } else {
+                scored.push((score, size, tx));
+            }
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
 
-        transactions.truncate(spirachain_core::MAX_TX_PER_BLOCK);
-        Ok(transactions)
+        // Greedy: a large transaction that does not fit leaves room for smaller ones
+        for (_, size, tx) in scored {
+            if selected.len() == max_txs {
+                break;
+            }
+            if used + size <= max_bytes {
+                used += size;
+                selected.push(tx);
+            }
+        }
+
+        Ok(selected)
     }
 
     fn transaction_score(&self, tx: &Transaction) -> f64 {
         let fee_score = tx.fee.value() as f64 / 1e18;
-        let coherence_score = tx.semantic_coherence();
+        let coherence_score = self.semantic_scoring.coherence(tx.semantic_coherence());
 
         fee_score * 0.5 + coherence_score * 0.5
     }
@@ -174,7 +337,10 @@ impl ProofOfSpiral {
             return 0.0;
         }
 
-        let sum: f64 = transactions.iter().map(|tx| tx.semantic_coherence()).sum();
+        let sum: f64 = transactions
+            .iter()
+            .map(|tx| self.semantic_scoring.coherence(tx.semantic_coherence()))
+            .sum();
 
         sum / (transactions.len() as f64)
     }
@@ -193,33 +359,25 @@ impl ProofOfSpiral {
             .unwrap()
             .as_secs();
 
-        let coords = spirapi_bridge::generate_pi_coordinate(&block_data, timestamp, 0)?;
+        // A stalled engine must not stall block production
+        let entity_hash = block_data.clone();
+        let coords = spirapi_bridge::bridge_pool()
+            .run_blocking("generate_pi_coordinate", move || {
+                spirapi_bridge::generate_pi_coordinate(&entity_hash, timestamp, 0)
+            })
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "⚠️ SpiraPi coordinates unavailable ({}), deriving locally",
+                    e
+                );
+                PiCoordinate::from_hash_timestamp(&block_data, timestamp, 0)
+            });
 
         Ok(coords)
     }
 
     fn verify_spiral_continuity(&self, block: &Block, previous_block: &Block) -> Result<()> {
-        let current_spiral = &block.header.spiral;
-        let prev_spiral = &previous_block.header.spiral;
-
-        if current_spiral.complexity < prev_spiral.complexity * 0.8 {
-            return Err(SpiraChainError::InvalidSpiral(
-                "Spiral complexity decreased too much".to_string(),
-            ));
-        }
-
-        let coord_distance = block
-            .header
-            .pi_coordinates
-            .distance(&previous_block.header.pi_coordinates);
-        if coord_distance.is_finite() && coord_distance > self.max_spiral_jump {
-            return Err(SpiraChainError::InvalidSpiral(format!(
-                "Spiral jump too large: {} > {}",
-                coord_distance, self.max_spiral_jump
-            )));
-        }
-
-        Ok(())
+        self.continuity_proof(block, previous_block).map(|_| ())
     }
 
     fn find_nonce(&self, block: &Block) -> Result<u64> {
@@ -251,21 +409,7 @@ impl ProofOfSpiral {
     }
 
     fn verify_proof_of_work(&self, block: &Block) -> bool {
-        let hash_input = [
-            &block.header.spiral_root.as_bytes()[..],
-            &block.header.nonce.to_be_bytes(),
-        ]
-        .concat();
-
-        let hash = blake3::hash(&hash_input);
-        let hash_value = u32::from_be_bytes([
-            hash.as_bytes()[0],
-            hash.as_bytes()[1],
-            hash.as_bytes()[2],
-            hash.as_bytes()[3],
-        ]);
-
-        hash_value < block.header.difficulty_target
+        block.header.view().verify_work().is_ok()
     }
 
     fn extract_validator_address(&self, pubkey: &[u8]) -> Result<spirachain_core::Address> {
@@ -305,13 +449,17 @@ impl ProofOfSpiral {
             + 0.2 * spiral.information_density
             + 0.3 * spiral.semantic_coherence;
 
-        if !self.recent_spiral_types.contains(&spiral.spiral_type) {
-            score *= 1.1;
-        }
-
         if let Ok(validator_address) =
             self.extract_validator_address(&block.header.validator_pubkey)
         {
+            if self.diversity.is_novel(
+                &validator_address,
+                &spiral_kind(spiral),
+                block.header.block_height,
+            ) {
+                score *= 1.1;
+            }
+
             if let Some(validator) = self.validator_set.get_validator(&validator_address) {
                 if validator.blocks_proposed > 100 {
                     score *= 0.9;
@@ -326,11 +474,39 @@ impl ProofOfSpiral {
         self.validator_set.add_validator(validator)
     }
 
-    pub fn update_recent_spiral_types(&mut self, spiral_type: SpiralType) {
-        self.recent_spiral_types.push(spiral_type);
-        if self.recent_spiral_types.len() > 100 {
-            self.recent_spiral_types.remove(0);
-        }
+    pub fn get_validator(&self, address: &Address) -> Option<&Validator> {
+        self.validator_set.get_validator(address)
+    }
+
+    /// Slash a validator of the set; `None` when it is not in it
+    pub fn slash_validator(
+        &mut self,
+        address: &Address,
+        reason: SlashingReason,
+        block_height: u64,
+        timestamp: u64,
+    ) -> Option<Amount> {
+        self.validator_set
+            .get_validator_mut(address)
+            .map(|validator| validator.slash(reason, block_height, timestamp))
+    }
+
+    /// Spiral history from the world state, which decides novelty bonuses and
+    /// the repeat penalty on our own coinbase
+    pub fn set_spiral_diversity(&mut self, diversity: SpiralDiversity) {
+        self.diversity = diversity;
+    }
+
+    /// Summary of the epoch that just ended, included when the next block
+    /// opens the following epoch
+    pub fn set_epoch_summary(&mut self, summary: Option<EpochSummary>) {
+        self.epoch_summary = summary;
+    }
+
+    /// Whether produced blocks are scored with the local embedding model,
+    /// see [`SemanticScoring::for_models`]
+    pub fn set_semantic_scoring(&mut self, scoring: SemanticScoring) {
+        self.semantic_scoring = scoring;
     }
 }
 
@@ -363,7 +539,221 @@ mod tests {
             transactions.push(tx);
         }
 
-        let selected = pos.semantic_clustering(transactions).unwrap();
+        let limits = BlockLimits::default();
+        let selected = pos
+            .semantic_clustering(transactions.clone(), &limits)
+            .unwrap();
         assert_eq!(selected.len(), 10);
+
+        // Room for the coinbase plus 4 transactions
+        let by_count = BlockLimits {
+            max_tx_per_block: 5,
+            ..limits
+        };
+        assert_eq!(
+            pos.semantic_clustering(transactions.clone(), &by_count)
+                .unwrap()
+                .len(),
+            4
+        );
+
+        // Room for exactly 3 transactions next to the overhead reserve
+        let tx_size = transactions[0].serialize().len();
+        let by_size = BlockLimits {
+            max_block_size: BLOCK_OVERHEAD_RESERVE + 3 * tx_size,
+            ..limits
+        };
+        assert_eq!(
+            pos.semantic_clustering(transactions.clone(), &by_size)
+                .unwrap()
+                .len(),
+            3
+        );
+        let one_byte_short = BlockLimits {
+            max_block_size: by_size.max_block_size - 1,
+            ..limits
+        };
+        assert_eq!(
+            pos.semantic_clustering(transactions.clone(), &one_byte_short)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // A guardian vote paying the lowest fee still gets its reserved slot
+        let mut vote = Transaction::new(
+            Address::new([42; 32]),
+            Address::new([42; 32]),
+            Amount::zero(),
+            Amount::zero(),
+        )
+        .with_payload(spirachain_core::TransactionPayload::EmergencyPause { blocks: 10 });
+        vote.compute_hash();
+        let mut with_vote = transactions;
+        with_vote.push(vote.clone());
+        let selected = pos.semantic_clustering(with_vote, &by_count).unwrap();
+        assert_eq!(selected.len(), 4);
+        assert!(selected.iter().any(|tx| tx.tx_hash == vote.tx_hash));
+    }
+
+    #[test]
+    fn test_neutral_scoring_ignores_local_vectors() {
+        let mut pos = ProofOfSpiral::new(
+            spirachain_core::MIN_SPIRAL_COMPLEXITY,
+            spirachain_core::MAX_SPIRAL_JUMP,
+        );
+        let tx = Transaction::new(
+            Address::new([1; 32]),
+            Address::new([2; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        )
+        .with_semantic_vector(vec![0.1; 4]);
+        let txs = vec![tx.clone()];
+        assert!((pos.calculate_semantic_coherence(&txs) - 0.2).abs() < 1e-6);
+
+        pos.set_semantic_scoring(SemanticScoring::Neutral);
+        assert_eq!(
+            pos.calculate_semantic_coherence(&txs),
+            spirachain_core::NEUTRAL_SEMANTIC_COHERENCE
+        );
+        let fee_score = tx.fee.value() as f64 / 1e18 * 0.5;
+        let neutral_score = spirachain_core::NEUTRAL_SEMANTIC_COHERENCE * 0.5;
+        assert!((pos.transaction_score(&tx) - fee_score - neutral_score).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_semantic_block_validates_on_a_fresh_node() {
+        let keypair = KeyPair::generate();
+        let pubkey = keypair.public_key().0.to_vec();
+        let validator = Validator::new(
+            keypair.to_address(),
+            pubkey,
+            Amount::new(spirachain_core::MIN_VALIDATOR_STAKE),
+            0,
+        )
+        .unwrap();
+        let new_node = || {
+            let mut pos = ProofOfSpiral::new(
+                spirachain_core::MIN_SPIRAL_COMPLEXITY,
+                spirachain_core::MAX_SPIRAL_JUMP,
+            );
+            pos.add_validator(validator.clone()).unwrap();
+            pos
+        };
+
+        let mut tx = Transaction::new(
+            Address::new([1; 32]),
+            Address::new([2; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        )
+        .with_semantic_vector(vec![0.3; 4]);
+        tx.signature = vec![0u8; 64];
+        tx.compute_hash();
+        let previous = Block::new(spirachain_core::Hash::zero(), 0);
+        let block = new_node()
+            .generate_block_candidate(&validator, &keypair, vec![tx], &previous)
+            .unwrap();
+
+        // Vectors stay behind; the score travels with the block
+        let decoded = Block::deserialize(&block.serialize()).unwrap();
+        assert!(decoded.transactions[1].semantic_vector.is_empty());
+        assert!((decoded.avg_semantic_coherence() - block.avg_semantic_coherence()).abs() < 1e-12);
+
+        let fresh = new_node();
+        assert_eq!(
+            fresh.calculate_semantic_coherence(&decoded.transactions[1..]),
+            decoded.header.spiral.semantic_coherence
+        );
+        assert!(decoded.header.spiral.semantic_coherence > 0.5);
+        fresh.validate_block(&decoded, &previous).unwrap();
+        assert_eq!(
+            fresh.continuity_proof(&decoded, &previous).unwrap(),
+            new_node().continuity_proof(&block, &previous).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tagged_hashes_switch_on_at_their_fork() {
+        static SCHEDULED: spirachain_core::ChainParams = spirachain_core::ChainParams {
+            hard_forks: &[spirachain_core::HardFork {
+                name: "tagged hashes",
+                version: spirachain_core::TAGGED_HASH_VERSION as u32,
+                height: 1,
+            }],
+            ..spirachain_core::TESTNET_PARAMS
+        };
+        let keypair = KeyPair::generate();
+        let validator = Validator::new(
+            keypair.to_address(),
+            keypair.public_key().0.to_vec(),
+            Amount::new(spirachain_core::MIN_VALIDATOR_STAKE),
+            0,
+        )
+        .unwrap();
+        let new_node = |params: &'static spirachain_core::ChainParams| {
+            let mut pos = ProofOfSpiral::new(
+                spirachain_core::MIN_SPIRAL_COMPLEXITY,
+                spirachain_core::MAX_SPIRAL_JUMP,
+            )
+            .with_chain_params(params);
+            pos.add_validator(validator.clone()).unwrap();
+            pos
+        };
+
+        let mut tx = Transaction::new(
+            Address::new([1; 32]),
+            Address::new([2; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        );
+        tx.signature = vec![0u8; 64];
+        tx.compute_hash();
+        let previous = Block::new(spirachain_core::Hash::zero(), 0);
+        let block = new_node(&SCHEDULED)
+            .generate_block_candidate(&validator, &keypair, vec![tx.clone()], &previous)
+            .unwrap();
+
+        assert_eq!(block.header.version, spirachain_core::TAGGED_HASH_VERSION);
+        assert_eq!(block.hash().as_bytes(), &block.header.view().tagged_hash());
+        let proof = block.merkle_proof(1).unwrap();
+        assert!(proof.verify(&tx.tx_hash, &block.header.merkle_root, block.header.version));
+        assert!(!proof.verify(&tx.tx_hash, &block.header.merkle_root, 1));
+
+        new_node(&SCHEDULED).validate_block(&block, &previous).unwrap();
+        // Nodes without the fork keep plain hashes and refuse the block
+        assert!(new_node(&spirachain_core::TESTNET_PARAMS)
+            .validate_block(&block, &previous)
+            .is_err());
+    }
+
+    #[test]
+    fn test_pi_identifier_verification() {
+        let pos = ProofOfSpiral::new(
+            spirachain_core::MIN_SPIRAL_COMPLEXITY,
+            spirachain_core::MAX_SPIRAL_JUMP,
+        );
+
+        let tx = Transaction::new(
+            Address::new([1; 32]),
+            Address::new([2; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        );
+        let ctx = spirapi_bridge::PiIdentifierContext::for_transaction(&tx);
+        let id = spirapi_bridge::generate_pi_identifier(&ctx, 20, true).unwrap();
+        let tx = tx.with_extra_data(
+            spirapi_bridge::PI_IDENTIFIER_KEY,
+            serde_json::to_vec(&id).unwrap(),
+        );
+
+        let block =
+            Block::new(spirachain_core::Hash::zero(), 1).with_transactions(vec![tx.clone()]);
+        assert!(pos.verify_pi_identifiers(&block).is_ok());
+
+        let duplicated =
+            Block::new(spirachain_core::Hash::zero(), 1).with_transactions(vec![tx.clone(), tx]);
+        assert!(pos.verify_pi_identifiers(&duplicated).is_err());
     }
 }
@@ -1,4 +1,4 @@
-use spirachain_core::{Amount, Block};
+use spirachain_core::{Amount, Block, ChainParams, Result, SpiraChainError, COINBASE_VERSION};
 
 pub struct RewardCalculator;
 
@@ -33,11 +33,97 @@ impl RewardCalculator {
         Amount::new(reward_value)
     }
 
+    /// Scheduled coinbase reward at `height`: halving base reward, truncated at MAX_SUPPLY.
+    pub fn block_reward_at_height(height: u64) -> Amount {
+        if height == 0 {
+            return Amount::zero();
+        }
+
+        let issued = Self::cumulative_emission(height - 1).value();
+        let remaining = spirachain_core::MAX_SUPPLY.saturating_sub(issued);
+
+        Amount::new(Self::base_reward_at_height(height).value().min(remaining))
+    }
+
+    /// Reward a producer may claim at `height`: the scheduled reward, less
+    /// REPEAT_SPIRAL_PENALTY_BPS when its spiral kind is not new for it this epoch
+    pub fn diversity_reward_at_height(height: u64, novel_spiral: bool) -> Amount {
+        let reward = Self::block_reward_at_height(height);
+        if novel_spiral {
+            return reward;
+        }
+        let withheld = reward.value() * spirachain_core::REPEAT_SPIRAL_PENALTY_BPS / 10_000;
+        Amount::new(reward.value() - withheld)
+    }
+
+    /// Total QBT minted by block rewards in blocks 1..=height
+    pub fn cumulative_emission(height: u64) -> Amount {
+        let mut total: u128 = 0;
+        let mut era_start: u64 = 1;
+        let mut halvings = 0u32;
+
+        while era_start <= height && halvings < spirachain_core::MAX_HALVINGS {
+            let era_end = ((halvings as u64 + 1) * spirachain_core::HALVING_BLOCKS - 1).min(height);
+            let blocks = (era_end - era_start + 1) as u128;
+            total =
+                total.saturating_add(blocks * (spirachain_core::INITIAL_BLOCK_REWARD >> halvings));
+
+            if total >= spirachain_core::MAX_SUPPLY {
+                return Amount::new(spirachain_core::MAX_SUPPLY);
+            }
+
+            era_start = era_end + 1;
+            halvings += 1;
+        }
+
+        Amount::new(total)
+    }
+
+    /// From [`COINBASE_VERSION`] on, every non-genesis block must open with a
+    /// coinbase paying at most the scheduled reward plus the producer's share
+    /// of the fees in the block. This is an upper bound; applying the block
+    /// limits the coinbase to the fees its transactions actually paid. Before
+    /// the fork, blocks without a coinbase follow the legacy reward rules.
+    pub fn verify_coinbase(block: &Block, params: &ChainParams) -> Result<()> {
+        let height = block.header.block_height;
+        if height == 0 {
+            return Ok(());
+        }
+
+        let Some(coinbase) = block.coinbase() else {
+            if params.is_active(COINBASE_VERSION, height) {
+                return Err(SpiraChainError::InvalidBlock(
+                    "Missing coinbase transaction".to_string(),
+                ));
+            }
+            return Ok(());
+        };
+
+        let reward = Self::block_reward_at_height(height);
+        let (fees, _) = Self::distribute_fees(block.total_fees());
+        let allowed = reward.checked_add(fees).ok_or_else(|| {
+            SpiraChainError::InvalidBlock("Coinbase allowance overflow".to_string())
+        })?;
+
+        if coinbase.amount > allowed {
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "Coinbase {} exceeds reward {} plus producer fees {} at height {}",
+                coinbase.amount, reward, fees, height
+            )));
+        }
+
+        Ok(())
+    }
+
     fn base_reward_at_height(height: u64) -> Amount {
         let halvings = height / spirachain_core::HALVING_BLOCKS;
         let base = spirachain_core::INITIAL_BLOCK_REWARD;
 
-        let reward = if halvings < 64 { base >> halvings } else { 0 };
+        let reward = if halvings < spirachain_core::MAX_HALVINGS as u64 {
+            base >> halvings
+        } else {
+            0
+        };
 
         Amount::new(reward)
     }
@@ -66,19 +152,18 @@ impl RewardCalculator {
         Amount::new(total_fee.max(spirachain_core::MIN_TX_FEE))
     }
 
-    pub fn distribute_fees(total_fees: Amount) -> (Amount, Amount, Amount) {
-        let validator_share = Amount::new((total_fees.value() as f64 * 0.5) as u128);
-        let burn_share = Amount::new((total_fees.value() as f64 * 0.3) as u128);
-        let treasury_share = Amount::new((total_fees.value() as f64 * 0.2) as u128);
-
-        (validator_share, burn_share, treasury_share)
+    /// Split collected fees into the producer's share, paid through the
+    /// coinbase, and the FEE_BURN_BPS share that is never minted back
+    pub fn distribute_fees(total_fees: Amount) -> (Amount, Amount) {
+        let burned = total_fees.basis_points(spirachain_core::FEE_BURN_BPS);
+        (Amount::new(total_fees.value() - burned.value()), burned)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use spirachain_core::Hash;
+    use spirachain_core::{HardFork, Hash, TESTNET_PARAMS};
 
     #[test]
     fn test_block_reward_calculation() {
@@ -92,8 +177,8 @@ mod tests {
         let amount = spirachain_core::Amount::qbt(100);
         let fee = spirachain_core::Amount::from_millis(1);
 
-        let mut tx = spirachain_core::Transaction::new(from, to, amount, fee);
-        tx.semantic_vector = vec![0.5; 100]; // Set semantic vector for coherence
+        let mut tx = spirachain_core::Transaction::new(from, to, amount, fee)
+            .with_semantic_vector(vec![0.5; 100]); // Set semantic vector for coherence
         tx.compute_hash();
 
         block = block.with_transactions(vec![tx]);
@@ -125,6 +210,93 @@ mod tests {
         assert_eq!(reward_halving.value(), reward_0.value() / 2);
     }
 
+    #[test]
+    fn test_reward_schedule_starts_at_initial_reward() {
+        assert_eq!(RewardCalculator::block_reward_at_height(0), Amount::zero());
+        assert_eq!(
+            RewardCalculator::block_reward_at_height(1).value(),
+            spirachain_core::INITIAL_BLOCK_REWARD
+        );
+        assert_eq!(
+            RewardCalculator::cumulative_emission(1000).value(),
+            1000 * spirachain_core::INITIAL_BLOCK_REWARD
+        );
+    }
+
+    #[test]
+    fn test_reward_halves_at_halving_boundary() {
+        let halving = spirachain_core::HALVING_BLOCKS;
+
+        let last_full = RewardCalculator::block_reward_at_height(halving - 1);
+        let first_halved = RewardCalculator::block_reward_at_height(halving);
+        assert_eq!(last_full.value(), spirachain_core::INITIAL_BLOCK_REWARD);
+        assert_eq!(first_halved.value(), last_full.value() / 2);
+
+        let second = RewardCalculator::block_reward_at_height(2 * halving);
+        assert_eq!(second.value(), first_halved.value() / 2);
+    }
+
+    #[test]
+    fn test_emission_stops_after_last_halving() {
+        let stop = spirachain_core::MAX_HALVINGS as u64 * spirachain_core::HALVING_BLOCKS;
+
+        assert!(RewardCalculator::block_reward_at_height(stop - 1) > Amount::zero());
+        assert_eq!(RewardCalculator::block_reward_at_height(stop), Amount::zero());
+        assert_eq!(
+            RewardCalculator::block_reward_at_height(stop + spirachain_core::HALVING_BLOCKS),
+            Amount::zero()
+        );
+
+        // The schedule runs out before the cap is reached, so no block is truncated
+        let total = RewardCalculator::cumulative_emission(stop - 1);
+        assert!(total.value() < spirachain_core::MAX_SUPPLY);
+        assert_eq!(RewardCalculator::cumulative_emission(stop), total);
+        assert_eq!(RewardCalculator::cumulative_emission(u64::MAX / 2), total);
+    }
+
+    #[test]
+    fn test_verify_coinbase() {
+        let producer = spirachain_core::Address::new([3u8; 32]);
+        let reward = RewardCalculator::block_reward_at_height(5);
+        let fee = Amount::from_millis(1);
+        let transfer = spirachain_core::Transaction::new(
+            spirachain_core::Address::new([1u8; 32]),
+            producer,
+            Amount::qbt(1),
+            fee,
+        );
+
+        let with_coinbase = |amount: Amount| {
+            Block::new(Hash::zero(), 5).with_transactions(vec![
+                spirachain_core::Transaction::new_coinbase(producer, amount, 5),
+                transfer.clone(),
+            ])
+        };
+
+        let (producer_fee, _) = RewardCalculator::distribute_fees(fee);
+        let exact = with_coinbase(reward.checked_add(producer_fee).unwrap());
+        assert!(RewardCalculator::verify_coinbase(&exact, &TESTNET_PARAMS).is_ok());
+
+        // The burned share of the fee cannot be paid out
+        let greedy = with_coinbase(reward.checked_add(fee).unwrap());
+        assert!(RewardCalculator::verify_coinbase(&greedy, &TESTNET_PARAMS).is_err());
+
+        // Legacy blocks lack one until the coinbase fork
+        let missing = Block::new(Hash::zero(), 5).with_transactions(vec![transfer.clone()]);
+        assert!(RewardCalculator::verify_coinbase(&missing, &TESTNET_PARAMS).is_ok());
+        let forked = ChainParams {
+            hard_forks: &[HardFork {
+                name: "coinbase",
+                version: COINBASE_VERSION,
+                height: 5,
+            }],
+            ..TESTNET_PARAMS
+        };
+        assert!(RewardCalculator::verify_coinbase(&missing, &forked).is_err());
+        let before_fork = Block::new(Hash::zero(), 4).with_transactions(vec![transfer.clone()]);
+        assert!(RewardCalculator::verify_coinbase(&before_fork, &forked).is_ok());
+    }
+
     #[test]
     fn test_fee_calculation() {
         let fee = RewardCalculator::calculate_tx_fee(1000, 100, 0.9);
@@ -134,9 +306,15 @@ mod tests {
     #[test]
     fn test_fee_distribution() {
         let total = Amount::qbt(100);
-        let (validator, burn, treasury) = RewardCalculator::distribute_fees(total);
+        let (producer, burned) = RewardCalculator::distribute_fees(total);
+
+        assert_eq!(producer, Amount::qbt(70));
+        assert_eq!(burned, Amount::qbt(30));
 
-        let sum = validator.value() + burn.value() + treasury.value();
-        assert!(sum <= total.value());
+        // The burn rounds down; nothing is lost or created
+        let odd = Amount::new(10_001);
+        let (producer, burned) = RewardCalculator::distribute_fees(odd);
+        assert_eq!(producer.value() + burned.value(), odd.value());
+        assert_eq!(burned.value(), 3_000);
     }
 }
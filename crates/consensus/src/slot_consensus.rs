@@ -8,6 +8,9 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub const SLOT_DURATION_TESTNET: u64 = 30;
 pub const SLOT_DURATION_MAINNET: u64 = 60;
 
+/// Slots per leader schedule epoch (testnet: 1 hour, mainnet: 2 hours)
+pub const SLOTS_PER_EPOCH: u64 = 120;
+
 /// Slot-based consensus manager
 #[derive(Debug, Clone)]
 pub struct SlotConsensus {
@@ -36,18 +39,22 @@ impl SlotConsensus {
         }
     }
 
-    /// Add a validator to the active set
-    pub fn add_validator(&mut self, address: Address) {
-        if !self.validators.contains(&address) {
-            self.validators.push(address);
-            // Sort for determinism (everyone must have the same order)
-            self.validators.sort_by_key(|a| *a.as_bytes());
+    /// Add a validator to the active set; true if it was not in it yet
+    pub fn add_validator(&mut self, address: Address) -> bool {
+        if self.validators.contains(&address) {
+            return false;
         }
+        self.validators.push(address);
+        // Sort for determinism (everyone must have the same order)
+        self.validators.sort_by_key(|a| *a.as_bytes());
+        true
     }
 
-    /// Remove a validator from the active set
-    pub fn remove_validator(&mut self, address: &Address) {
+    /// Remove a validator from the active set; true if it was in it
+    pub fn remove_validator(&mut self, address: &Address) -> bool {
+        let before = self.validators.len();
         self.validators.retain(|v| v != address);
+        self.validators.len() < before
     }
 
     /// Get the current slot number based on timestamp
@@ -61,6 +68,40 @@ impl SlotConsensus {
         now / self.slot_duration
     }
 
+    /// Slot a block with this timestamp (Unix milliseconds) was produced in
+    pub fn slot_at(&self, timestamp_ms: u64) -> u64 {
+        timestamp_ms / 1000 / self.slot_duration
+    }
+
+    /// Slot duration in seconds
+    pub fn slot_duration(&self) -> u64 {
+        self.slot_duration
+    }
+
+    /// Leader schedule epoch a slot belongs to
+    pub fn epoch_of(slot: u64) -> u64 {
+        slot / SLOTS_PER_EPOCH
+    }
+
+    /// Slots of `epoch` led by each active validator, in leader order. Later
+    /// epochs assume the active set stays as it is.
+    pub fn leader_schedule(&self, epoch: u64) -> Vec<(Address, Vec<u64>)> {
+        let mut schedule: Vec<(Address, Vec<u64>)> = self
+            .validators
+            .iter()
+            .map(|validator| (*validator, Vec::new()))
+            .collect();
+        if schedule.is_empty() {
+            return schedule;
+        }
+        let first_slot = epoch * SLOTS_PER_EPOCH;
+        for slot in first_slot..first_slot + SLOTS_PER_EPOCH {
+            let index = (slot as usize) % schedule.len();
+            schedule[index].1.push(slot);
+        }
+        schedule
+    }
+
     /// Get the validator that should produce the block for a given slot
     pub fn get_slot_leader(&self, slot: u64) -> Option<Address> {
         if self.validators.is_empty() {
@@ -72,6 +113,14 @@ impl SlotConsensus {
         Some(self.validators[index])
     }
 
+    /// Leaders of the slots strictly between a block's parent and the block,
+    /// which produced nothing the block builds on
+    pub fn skipped_leaders(&self, parent_slot: u64, block_slot: u64) -> Vec<(u64, Address)> {
+        (parent_slot.saturating_add(1)..block_slot)
+            .filter_map(|slot| self.get_slot_leader(slot).map(|leader| (slot, leader)))
+            .collect()
+    }
+
     /// Check if the given validator is the leader for the current slot
     pub fn is_slot_leader(&self, validator: &Address) -> bool {
         let current_slot = self.get_current_slot();
@@ -126,15 +175,19 @@ mod tests {
 
         // Should return a reasonable slot number
         assert!(slot > 0);
+
+        let start_ms = slot * SLOT_DURATION_TESTNET * 1000;
+        assert_eq!(consensus.slot_at(start_ms), slot);
+        assert_eq!(consensus.slot_at(start_ms - 1), slot - 1);
     }
 
     #[test]
     fn test_round_robin() {
         let mut consensus = SlotConsensus::new("testnet");
 
-        let addr1 = Address([1u8; 32]);
-        let addr2 = Address([2u8; 32]);
-        let addr3 = Address([3u8; 32]);
+        let addr1 = Address::new([1u8; 32]);
+        let addr2 = Address::new([2u8; 32]);
+        let addr3 = Address::new([3u8; 32]);
 
         consensus.add_validator(addr1);
         consensus.add_validator(addr2);
@@ -148,6 +201,14 @@ mod tests {
         assert_eq!(consensus.get_slot_leader(2), Some(addr3));
         // Slot 3 should wrap around to validator 0
         assert_eq!(consensus.get_slot_leader(3), Some(addr1));
+
+        let schedule = consensus.leader_schedule(2);
+        assert_eq!(schedule.len(), 3);
+        assert_eq!(schedule[0].1.len() as u64, SLOTS_PER_EPOCH / 3);
+        for (leader, slots) in &schedule {
+            assert!(slots.iter().all(|slot| SlotConsensus::epoch_of(*slot) == 2
+                && consensus.get_slot_leader(*slot) == Some(*leader)));
+        }
     }
 
     #[test]
@@ -155,8 +216,8 @@ mod tests {
         let mut consensus1 = SlotConsensus::new("testnet");
         let mut consensus2 = SlotConsensus::new("testnet");
 
-        let addr1 = Address([1u8; 32]);
-        let addr2 = Address([2u8; 32]);
+        let addr1 = Address::new([1u8; 32]);
+        let addr2 = Address::new([2u8; 32]);
 
         // Add in different order
         consensus1.add_validator(addr1);
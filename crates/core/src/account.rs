@@ -0,0 +1,173 @@
+use crate::{Address, Amount, Hash};
+use serde::{Deserialize, Serialize};
+
+/// Part of an account balance released over time. Nothing is spendable before
+/// `cliff_height`; the rest unlocks linearly until `end_height`. A plain time
+/// lock is a schedule with `cliff_height == end_height`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VestingSchedule {
+    pub amount: Amount,
+    pub cliff_height: u64,
+    pub end_height: u64,
+}
+
+impl VestingSchedule {
+    pub fn new(amount: Amount, cliff_height: u64, end_height: u64) -> Self {
+        Self {
+            amount,
+            cliff_height,
+            end_height: end_height.max(cliff_height),
+        }
+    }
+
+    /// Amount still locked at `height`
+    pub fn locked_at(&self, height: u64) -> Amount {
+        if height < self.cliff_height {
+            return self.amount;
+        }
+        if height >= self.end_height {
+            return Amount::zero();
+        }
+
+        let remaining = (self.end_height - height) as u128;
+        let duration = (self.end_height - self.cliff_height) as u128;
+        Amount::new(
+            self.amount.value() / duration * remaining
+                + self.amount.value() % duration * remaining / duration,
+        )
+    }
+}
+
+/// Everything the chain knows about one address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub balance: Amount,
+    pub nonce: u64,
+    pub stake: Amount,
+    /// Hash of deployed code, set only for contract accounts
+    pub code_hash: Option<Hash>,
+    pub storage_root: Hash,
+    /// Portion of `balance` that cannot be spent yet
+    pub vesting: Option<VestingSchedule>,
+    /// Block rewards earned as a producer, held outside `balance` until claimed
+    pub unclaimed_rewards: Amount,
+    /// Where block rewards produced by this address are credited instead, so a
+    /// validator's hot identity key never holds them. Set by the identity key.
+    pub payout_address: Option<Address>,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self {
+            balance: Amount::zero(),
+            nonce: 0,
+            stake: Amount::zero(),
+            code_hash: None,
+            storage_root: Hash::zero(),
+            vesting: None,
+            unclaimed_rewards: Amount::zero(),
+            payout_address: None,
+        }
+    }
+}
+
+impl Account {
+    pub fn with_balance(balance: Amount) -> Self {
+        Self {
+            balance,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_contract(&self) -> bool {
+        self.code_hash.is_some()
+    }
+
+    pub fn locked_at(&self, height: u64) -> Amount {
+        self.vesting
+            .map(|vesting| vesting.locked_at(height))
+            .unwrap_or(Amount::zero())
+    }
+
+    /// Balance minus whatever vesting still holds at `height`
+    pub fn spendable_at(&self, height: u64) -> Amount {
+        self.balance
+            .checked_sub(self.locked_at(height))
+            .unwrap_or(Amount::zero())
+    }
+
+    /// Deterministic entry committed to by the state root. Accounts without code,
+    /// vesting, unclaimed rewards or a payout address keep the original
+    /// `address:balance:nonce` layout.
+    pub fn state_entry(&self, address: &Address) -> String {
+        let mut entry = format!("{}:{}:{}", address, self.balance.value(), self.nonce);
+        if let Some(code_hash) = self.code_hash {
+            entry.push_str(&format!(":{}:{}", code_hash, self.storage_root));
+        }
+        if let Some(vesting) = self.vesting {
+            entry.push_str(&format!(
+                ":vesting:{}:{}:{}",
+                vesting.amount.value(),
+                vesting.cliff_height,
+                vesting.end_height
+            ));
+        }
+        if !self.unclaimed_rewards.is_zero() {
+            entry.push_str(&format!(":rewards:{}", self.unclaimed_rewards.value()));
+        }
+        if let Some(payout) = self.payout_address {
+            entry.push_str(&format!(":payout:{}", payout));
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vesting_releases_linearly_after_cliff() {
+        let vesting = VestingSchedule::new(Amount::new(1_000), 100, 200);
+        assert_eq!(vesting.locked_at(0), Amount::new(1_000));
+        assert_eq!(vesting.locked_at(99), Amount::new(1_000));
+        assert_eq!(vesting.locked_at(100), Amount::new(1_000));
+        assert_eq!(vesting.locked_at(150), Amount::new(500));
+        assert_eq!(vesting.locked_at(200), Amount::zero());
+
+        let lock = VestingSchedule::new(Amount::new(7), 50, 50);
+        assert_eq!(lock.locked_at(49), Amount::new(7));
+        assert_eq!(lock.locked_at(50), Amount::zero());
+    }
+
+    #[test]
+    fn test_spendable_balance_excludes_locked_funds() {
+        let mut account = Account::with_balance(Amount::new(1_500));
+        account.vesting = Some(VestingSchedule::new(Amount::new(1_000), 100, 200));
+
+        assert_eq!(account.spendable_at(10), Amount::new(500));
+        assert_eq!(account.spendable_at(150), Amount::new(1_000));
+        assert_eq!(account.spendable_at(300), Amount::new(1_500));
+
+        account.balance = Amount::new(200);
+        assert_eq!(account.spendable_at(10), Amount::zero());
+    }
+
+    #[test]
+    fn test_state_entry_layout() {
+        let address = Address::new([1u8; 32]);
+        let account = Account::with_balance(Amount::new(5));
+        assert_eq!(account.state_entry(&address), format!("{}:5:0", address));
+
+        let mut vested = account.clone();
+        vested.vesting = Some(VestingSchedule::new(Amount::new(5), 1, 2));
+        assert!(vested.state_entry(&address).ends_with(":vesting:5:1:2"));
+
+        let mut with_payout = account.clone();
+        with_payout.payout_address = Some(Address::new([2u8; 32]));
+        assert_eq!(
+            with_payout.state_entry(&address),
+            format!("{}:5:0:payout:{}", address, Address::new([2u8; 32]))
+        );
+    }
+}
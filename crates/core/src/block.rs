@@ -1,4 +1,7 @@
-use crate::{Hash, PiCoordinate, Result, SpiraChainError, SpiralMetadata, Transaction};
+use crate::{
+    Address, ChainParams, EventBloom, Hash, HeaderView, PiCoordinate, Result, SpiraChainError, SpiralMetadata,
+    Transaction, TransactionPayload,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +20,59 @@ pub struct BlockHeader {
     pub difficulty_target: u32,
     pub tx_count: u32,
     pub block_height: u64,
+    /// Free-form producer data, bounded by the rules of the header version
+    #[serde(default)]
+    pub extra_data: Vec<u8>,
+    /// [`crate::EventBloom`] of the block's transactions, empty in blocks produced
+    /// before blooms existed
+    #[serde(default)]
+    pub event_bloom: Vec<u8>,
+}
+
+/// Header rules for one protocol version. A consensus change adds an entry
+/// here and a hard fork in `ChainParams` that switches blocks to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderRules {
+    pub version: u64,
+    pub max_extra_data: usize,
+}
+
+pub const HEADER_RULES: &[HeaderRules] = &[
+    HeaderRules {
+        version: 1,
+        max_extra_data: crate::MAX_HEADER_EXTRA_DATA_SIZE,
+    },
+    // Tagged block, transaction and merkle hashes, see [`crate::TAGGED_HASH_VERSION`]
+    HeaderRules {
+        version: 2,
+        max_extra_data: crate::MAX_HEADER_EXTRA_DATA_SIZE,
+    },
+];
+
+/// Size limits on a whole block, enforced when producing and validating
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockLimits {
+    /// Serialized size in bytes, header and coinbase included
+    pub max_block_size: usize,
+    /// Transaction count, coinbase included
+    pub max_tx_per_block: usize,
+}
+
+pub const DEFAULT_BLOCK_LIMITS: BlockLimits = BlockLimits {
+    max_block_size: crate::MAX_BLOCK_SIZE,
+    max_tx_per_block: crate::MAX_TX_PER_BLOCK,
+};
+
+impl Default for BlockLimits {
+    fn default() -> Self {
+        DEFAULT_BLOCK_LIMITS
+    }
+}
+
+impl HeaderRules {
+    pub fn for_version(version: u64) -> Option<&'static HeaderRules> {
+        HEADER_RULES.iter().find(|rules| rules.version == version)
+    }
 }
 
 impl BlockHeader {
@@ -39,25 +95,89 @@ impl BlockHeader {
             difficulty_target: u32::MAX / 1000, // Testnet: facile à miner
             tx_count: 0,
             block_height,
+            extra_data: Vec::new(),
+            event_bloom: Vec::new(),
         }
     }
 
+    /// Apply the rules of this header's version
+    pub fn validate_version_rules(&self) -> Result<()> {
+        let rules = HeaderRules::for_version(self.version).ok_or_else(|| {
+            SpiraChainError::UnsupportedProtocol(format!(
+                "no rules for header version {}",
+                self.version
+            ))
+        })?;
+
+        if self.extra_data.len() > rules.max_extra_data {
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "Header extra data too large: {} > {} bytes",
+                self.extra_data.len(),
+                rules.max_extra_data
+            )));
+        }
+
+        Ok(())
+    }
+
     pub fn hash(&self) -> Hash {
-        let mut hasher = blake3::Hasher::new();
-        hasher.update(&self.version.to_be_bytes());
-        hasher.update(self.previous_block_hash.as_bytes());
-        hasher.update(self.merkle_root.as_bytes());
-        hasher.update(self.spiral_root.as_bytes());
-        hasher.update(self.state_root.as_bytes()); // Include state root in block hash
-        hasher.update(&self.timestamp.to_be_bytes());
-        hasher.update(&self.pi_coordinates.x.to_be_bytes());
-        hasher.update(&self.pi_coordinates.y.to_be_bytes());
-        hasher.update(&self.pi_coordinates.z.to_be_bytes());
-        hasher.update(&self.pi_coordinates.t.to_be_bytes());
-        hasher.update(&self.nonce.to_be_bytes());
-        hasher.update(&self.difficulty_target.to_be_bytes());
-        hasher.update(&self.block_height.to_be_bytes());
-        hasher.finalize().into()
+        Hash::new(self.view().hash())
+    }
+
+    /// Fields checked by [`crate::light`] verification
+    pub fn view(&self) -> HeaderView<'_> {
+        HeaderView {
+            version: self.version,
+            previous_block_hash: self.previous_block_hash.as_bytes(),
+            merkle_root: self.merkle_root.as_bytes(),
+            spiral_root: self.spiral_root.as_bytes(),
+            state_root: self.state_root.as_bytes(),
+            timestamp: self.timestamp,
+            pi_coordinates: [
+                self.pi_coordinates.x,
+                self.pi_coordinates.y,
+                self.pi_coordinates.z,
+                self.pi_coordinates.t,
+            ],
+            nonce: self.nonce,
+            difficulty_target: self.difficulty_target,
+            block_height: self.block_height,
+            extra_data: &self.extra_data,
+            event_bloom: &self.event_bloom,
+            validator_pubkey: &self.validator_pubkey,
+            signature: &self.signature,
+            spiral_complexity: self.spiral.complexity,
+        }
+    }
+
+    /// `None` for blocks without a bloom: they have to be scanned
+    pub fn event_bloom(&self) -> Option<EventBloom> {
+        EventBloom::from_bytes(&self.event_bloom).ok()
+    }
+
+    /// Producer signature over [`Self::hash`], checked through [`crate::signature_cache`]
+    pub fn verify_signature(&self) -> Result<()> {
+        let public_key: &[u8; 32] = self
+            .validator_pubkey
+            .as_slice()
+            .try_into()
+            .map_err(|_| SpiraChainError::InvalidSignature)?;
+        let hash = self.hash();
+        if crate::signature_cache().verify(&hash, public_key, hash.as_bytes(), &self.signature) {
+            Ok(())
+        } else {
+            Err(SpiraChainError::InvalidSignature)
+        }
+    }
+
+    /// Address of the producer's key, `None` for unsigned blocks
+    pub fn producer_address(&self) -> Option<Address> {
+        if self.validator_pubkey.is_empty() {
+            return None;
+        }
+        Some(Address::new(
+            *blake3::hash(&self.validator_pubkey).as_bytes(),
+        ))
     }
 
     pub fn serialize(&self) -> Vec<u8> {
@@ -69,6 +189,29 @@ impl BlockHeader {
     }
 }
 
+/// Path from a transaction hash to a block's merkle root, for light clients that
+/// only hold headers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub index: u32,
+    /// Sibling hashes from the leaf level up
+    pub siblings: Vec<Hash>,
+}
+
+impl MerkleProof {
+    /// Whether `tx_hash` at `self.index` hashes up to `merkle_root` of a block
+    /// of header `version`
+    pub fn verify(&self, tx_hash: &Hash, merkle_root: &Hash, version: u64) -> bool {
+        crate::light::verify_merkle_path(
+            tx_hash.as_bytes(),
+            self.index,
+            self.siblings.iter().map(Hash::as_bytes),
+            merkle_root.as_bytes(),
+            version,
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     pub header: BlockHeader,
@@ -104,32 +247,71 @@ impl Block {
         self
     }
 
+    pub fn with_extra_data(mut self, extra_data: Vec<u8>) -> Self {
+        self.header.extra_data = extra_data;
+        self
+    }
+
     pub fn compute_merkle_root(&mut self) {
-        if self.transactions.is_empty() {
+        let mut hashes = self.merkle_leaves();
+        if hashes.is_empty() {
             self.header.merkle_root = Hash::zero();
             return;
         }
 
-        let mut hashes: Vec<Hash> = self.transactions.iter().map(|tx| tx.tx_hash).collect();
-
+        let version = self.header.version;
         while hashes.len() > 1 {
-            let mut next_level = Vec::new();
-
-            for chunk in hashes.chunks(2) {
-                let mut hasher = blake3::Hasher::new();
-                hasher.update(chunk[0].as_bytes());
-                if chunk.len() > 1 {
-                    hasher.update(chunk[1].as_bytes());
-                } else {
-                    hasher.update(chunk[0].as_bytes());
-                }
-                next_level.push(hasher.finalize().into());
-            }
+            hashes = hashes
+                .chunks(2)
+                .map(|chunk| {
+                    crate::light::merkle_node(&chunk[0], chunk.get(1).unwrap_or(&chunk[0]), version)
+                })
+                .collect();
+        }
 
-            hashes = next_level;
+        self.header.merkle_root = Hash::new(hashes[0]);
+    }
+
+    /// Proof for the transaction at `index`, mirroring [`Self::compute_merkle_root`]
+    /// (an odd node out is paired with itself)
+    pub fn merkle_proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.transactions.len() {
+            return None;
         }
 
-        self.header.merkle_root = hashes[0];
+        let version = self.header.version;
+        let mut hashes = self.merkle_leaves();
+        let mut position = index;
+        let mut siblings = Vec::new();
+
+        while hashes.len() > 1 {
+            let sibling = position ^ 1;
+            siblings.push(Hash::new(*hashes.get(sibling).unwrap_or(&hashes[position])));
+
+            hashes = hashes
+                .chunks(2)
+                .map(|chunk| {
+                    crate::light::merkle_node(&chunk[0], chunk.get(1).unwrap_or(&chunk[0]), version)
+                })
+                .collect();
+            position /= 2;
+        }
+
+        Some(MerkleProof {
+            index: index as u32,
+            siblings,
+        })
+    }
+
+    fn merkle_leaves(&self) -> Vec<crate::light::Digest> {
+        self.transactions
+            .iter()
+            .map(|tx| crate::light::merkle_leaf(tx.tx_hash.as_bytes(), self.header.version))
+            .collect()
+    }
+
+    pub fn compute_event_bloom(&mut self) {
+        self.header.event_bloom = EventBloom::for_block(self).as_bytes().to_vec();
     }
 
     pub fn compute_spiral_root(&mut self) {
@@ -141,6 +323,11 @@ impl Block {
         self.header.hash()
     }
 
+    /// Producer signature over [`Self::hash`], checked through [`crate::signature_cache`]
+    pub fn verify_signature(&self) -> Result<()> {
+        self.header.verify_signature()
+    }
+
     pub fn serialize(&self) -> Vec<u8> {
         bincode::serialize(self).unwrap_or_default()
     }
@@ -149,10 +336,18 @@ impl Block {
         bincode::deserialize(data).map_err(|e| SpiraChainError::SerializationError(e.to_string()))
     }
 
-    pub fn validate(&self) -> Result<()> {
-        if self.header.version == 0 {
-            return Err(SpiraChainError::InvalidBlock("Invalid version".to_string()));
+    /// Structural checks; rules introduced by a hard fork apply from its
+    /// activation height in `params`
+    pub fn validate(&self, params: &ChainParams) -> Result<()> {
+        if self.header.signature.is_empty() {
+            return Err(SpiraChainError::InvalidSignature);
         }
+        self.validate_unsigned(params)
+    }
+
+    /// [`Self::validate`] for a block whose producer has not signed it yet
+    pub fn validate_unsigned(&self, params: &ChainParams) -> Result<()> {
+        self.header.validate_version_rules()?;
 
         if self.header.previous_block_hash == Hash::zero() && self.header.block_height != 0 {
             return Err(SpiraChainError::InvalidBlock(
@@ -160,13 +355,7 @@ impl Block {
             ));
         }
 
-        if self.transactions.len() > crate::MAX_TX_PER_BLOCK {
-            return Err(SpiraChainError::InvalidBlock(format!(
-                "Too many transactions: {} > {}",
-                self.transactions.len(),
-                crate::MAX_TX_PER_BLOCK
-            )));
-        }
+        self.validate_limits(&DEFAULT_BLOCK_LIMITS)?;
 
         if self.header.spiral.complexity < crate::MIN_SPIRAL_COMPLEXITY {
             return Err(SpiraChainError::SpiralComplexityTooLow(
@@ -175,12 +364,28 @@ impl Block {
             ));
         }
 
-        if self.header.signature.is_empty() {
-            return Err(SpiraChainError::InvalidSignature);
+        for (index, tx) in self.transactions.iter().enumerate() {
+            if tx.is_coinbase() {
+                if index != 0 {
+                    return Err(SpiraChainError::InvalidBlock(
+                        "Coinbase must be the first transaction".to_string(),
+                    ));
+                }
+                tx.validate_coinbase(self.header.block_height)?;
+            } else if tx.is_epoch_summary() {
+                if index != 1 {
+                    return Err(SpiraChainError::InvalidBlock(
+                        "Epoch summary must follow the coinbase".to_string(),
+                    ));
+                }
+                tx.validate_epoch_summary(self.header.block_height)?;
+            } else {
+                tx.validate()?;
+            }
         }
-
-        for tx in &self.transactions {
-            tx.validate()?;
+        // Blocks of earlier releases list transactions in mempool order
+        if params.is_active(crate::CANONICAL_ORDER_VERSION, self.header.block_height) {
+            crate::validate_canonical_order(&self.transactions)?;
         }
 
         let mut block_clone = self.clone();
@@ -191,6 +396,15 @@ impl Block {
             ));
         }
 
+        // Optional, but a bloom that hides transactions would mislead indexers
+        if !self.header.event_bloom.is_empty()
+            && self.header.event_bloom() != Some(EventBloom::for_block(self))
+        {
+            return Err(SpiraChainError::InvalidBlock(
+                "Invalid event bloom".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
@@ -208,10 +422,52 @@ impl Block {
         sum / (self.transactions.len() as f64)
     }
 
+    /// Fees paid by the non-coinbase transactions, collected by the coinbase
+    pub fn total_fees(&self) -> crate::Amount {
+        self.transactions
+            .iter()
+            .filter(|tx| !tx.is_coinbase())
+            .fold(crate::Amount::zero(), |sum, tx| {
+                sum.checked_add(tx.fee).unwrap_or(sum)
+            })
+    }
+
+    pub fn coinbase(&self) -> Option<&Transaction> {
+        self.transactions.first().filter(|tx| tx.is_coinbase())
+    }
+
+    pub fn epoch_summary(&self) -> Option<&crate::EpochSummary> {
+        match self.transactions.get(1).map(|tx| &tx.payload) {
+            Some(TransactionPayload::EpochSummary { summary }) => Some(summary),
+            _ => None,
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.serialize().len()
     }
 
+    /// Check transaction count and serialized size against `limits`
+    pub fn validate_limits(&self, limits: &BlockLimits) -> Result<()> {
+        if self.transactions.len() > limits.max_tx_per_block {
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "Too many transactions: {} > {}",
+                self.transactions.len(),
+                limits.max_tx_per_block
+            )));
+        }
+
+        let size = self.size();
+        if size > limits.max_block_size {
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "Block too large: {} > {} bytes",
+                size, limits.max_block_size
+            )));
+        }
+
+        Ok(())
+    }
+
     pub fn is_genesis(&self) -> bool {
         self.header.block_height == 0
     }
@@ -252,6 +508,165 @@ mod tests {
         assert_ne!(block.header.merkle_root, Hash::zero());
     }
 
+    #[test]
+    fn test_merkle_proofs_verify_against_root() {
+        let transactions = (1..=5u8)
+            .map(|i| {
+                let mut tx = Transaction::new(
+                    Address::new([i; 32]),
+                    Address::new([9u8; 32]),
+                    Amount::qbt(i as u64),
+                    Amount::zero(),
+                );
+                tx.compute_hash();
+                tx
+            })
+            .collect();
+        let mut block = Block::new(Hash::zero(), 1).with_transactions(transactions);
+
+        for version in [1, crate::TAGGED_HASH_VERSION] {
+            block.header.version = version;
+            block.compute_merkle_root();
+            let root = block.header.merkle_root;
+
+            for (index, tx) in block.transactions.iter().enumerate() {
+                let proof = block.merkle_proof(index).unwrap();
+                assert!(proof.verify(&tx.tx_hash, &root, version));
+                assert!(!proof.verify(&Hash::zero(), &root, version));
+                // Tagged and untagged trees never share a root
+                assert!(!proof.verify(&tx.tx_hash, &root, 3 - version));
+            }
+        }
+        assert!(block.merkle_proof(5).is_none());
+    }
+
+    #[test]
+    fn test_coinbase_must_be_first() {
+        let producer = Address::new([9u8; 32]);
+        let mut coinbase = Transaction::new_coinbase(producer, Amount::qbt(10), 1);
+        coinbase.compute_hash();
+
+        let mut transfer = Transaction::new(
+            Address::new([1u8; 32]),
+            producer,
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        );
+        transfer.signature = vec![0u8; 64];
+        transfer.compute_hash();
+
+        let build = |txs: Vec<Transaction>| {
+            let mut block = Block::new(Hash::new([1u8; 32]), 1).with_transactions(txs);
+            block.header.spiral.complexity = crate::MIN_SPIRAL_COMPLEXITY;
+            block.header.signature = vec![0u8; 64];
+            block.compute_merkle_root();
+            block
+        };
+
+        let params = &crate::TESTNET_PARAMS;
+        assert!(build(vec![coinbase.clone(), transfer.clone()])
+            .validate(params)
+            .is_ok());
+        assert!(build(vec![transfer.clone(), coinbase])
+            .validate(params)
+            .is_err());
+        // Whether one is required at all depends on the fork schedule
+        assert!(build(vec![transfer]).validate(params).is_ok());
+    }
+
+    #[test]
+    fn test_canonical_order_enforced_from_fork() {
+        static FORKED: ChainParams = ChainParams {
+            hard_forks: &[crate::HardFork {
+                name: "canonical-order",
+                version: crate::CANONICAL_ORDER_VERSION,
+                height: 2,
+            }],
+            ..crate::TESTNET_PARAMS
+        };
+        let transfer = |nonce| {
+            let mut tx = Transaction::new_at(
+                Address::new([1u8; 32]),
+                Address::new([2u8; 32]),
+                Amount::qbt(1),
+                Amount::from_millis(1),
+                1_000,
+            );
+            tx.nonce = Some(nonce);
+            tx.signature = vec![0u8; 64];
+            tx.compute_hash();
+            tx
+        };
+        let build = |height| {
+            let mut block = Block::new(Hash::new([1u8; 32]), height)
+                .with_transactions(vec![transfer(1), transfer(0)]);
+            block.header.spiral.complexity = crate::MIN_SPIRAL_COMPLEXITY;
+            block.header.signature = vec![0u8; 64];
+            block.compute_merkle_root();
+            block
+        };
+
+        assert!(build(1).validate(&FORKED).is_ok());
+        assert!(build(2).validate(&FORKED).is_err());
+        assert!(build(2).validate(&crate::TESTNET_PARAMS).is_ok());
+    }
+
+    #[test]
+    fn test_header_version_rules() {
+        let mut header = BlockHeader::new(Hash::new([1u8; 32]), 1);
+        let plain_hash = header.hash();
+        assert!(header.validate_version_rules().is_ok());
+
+        header.extra_data = b"spirachain/0.1.0".to_vec();
+        assert!(header.validate_version_rules().is_ok());
+        assert_ne!(header.hash(), plain_hash);
+
+        header.extra_data = vec![0u8; crate::MAX_HEADER_EXTRA_DATA_SIZE + 1];
+        assert!(header.validate_version_rules().is_err());
+
+        header.extra_data.clear();
+        assert_eq!(header.hash(), plain_hash);
+
+        header.version = 0;
+        assert!(matches!(
+            header.validate_version_rules(),
+            Err(SpiraChainError::UnsupportedProtocol(_))
+        ));
+
+        // Every version this binary can produce has rules
+        for version in 1..=crate::PROTOCOL_VERSION as u64 {
+            assert!(HeaderRules::for_version(version).is_some());
+        }
+    }
+
+    #[test]
+    fn test_block_limits_boundary() {
+        let transfer = Transaction::new(
+            Address::new([1u8; 32]),
+            Address::new([2u8; 32]),
+            Amount::new(1),
+            Amount::new(1),
+        );
+        let block = Block::new(Hash::new([1u8; 32]), 1).with_transactions(vec![transfer; 3]);
+        let exact = BlockLimits {
+            max_block_size: block.size(),
+            max_tx_per_block: 3,
+        };
+        assert!(block.validate_limits(&exact).is_ok());
+
+        let one_byte_short = BlockLimits {
+            max_block_size: exact.max_block_size - 1,
+            ..exact
+        };
+        assert!(block.validate_limits(&one_byte_short).is_err());
+
+        let one_tx_short = BlockLimits {
+            max_tx_per_block: 2,
+            ..exact
+        };
+        assert!(block.validate_limits(&one_tx_short).is_err());
+    }
+
     #[test]
     fn test_genesis_block() {
         let prev_hash = Hash::zero();
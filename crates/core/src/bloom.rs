@@ -0,0 +1,151 @@
+use crate::{Address, Block, Hash, Result, SpiraChainError, Transaction, TransactionPayload};
+use serde::{Deserialize, Serialize};
+
+/// Bloom size: 2048 bits
+pub const EVENT_BLOOM_BYTES: usize = 256;
+
+/// Bits set per inserted item
+const BLOOM_HASHES: usize = 3;
+
+/// Event topic for `name`. Until contracts emit logs, a transaction's events are
+/// its payload kind and its intent, see [`transaction_topics`].
+pub fn event_topic(name: &str) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"spirachain-event");
+    hasher.update(name.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Topics a transaction emits: `payload:<kind>` and, when declared, `intent:<type>`
+pub fn transaction_topics(tx: &Transaction) -> Vec<Hash> {
+    let kind = match tx.payload {
+        TransactionPayload::Transfer => "transfer",
+        TransactionPayload::ContractDeploy { .. } => "contract_deploy",
+        TransactionPayload::ContractCall { .. } => "contract_call",
+        TransactionPayload::Coinbase { .. } => "coinbase",
+        TransactionPayload::ClaimRewards => "claim_rewards",
+        TransactionPayload::EmergencyPause { .. } => "emergency_pause",
+        TransactionPayload::RegisterSpiral { .. } => "register_spiral",
+        TransactionPayload::SetPayoutAddress => "set_payout_address",
+        TransactionPayload::RegisterName { .. } => "register_name",
+        TransactionPayload::TransferName { .. } => "transfer_name",
+        TransactionPayload::EpochSummary { .. } => "epoch_summary",
+        TransactionPayload::RotateValidatorKey { .. } => "rotate_validator_key",
+        TransactionPayload::ApproveSemanticModel { .. } => "approve_semantic_model",
+    };
+
+    let mut topics = vec![event_topic(&format!("payload:{}", kind))];
+    if let Some(intent) = &tx.intent {
+        topics.push(event_topic(&format!("intent:{:?}", intent.intent_type)));
+    }
+    topics
+}
+
+/// Bloom filter over the addresses and event topics of a block's transactions.
+/// A miss means the block is irrelevant; a hit still needs the block (or a
+/// [`crate::MerkleProof`] of the matching transaction) to confirm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventBloom(Vec<u8>);
+
+impl Default for EventBloom {
+    fn default() -> Self {
+        Self(vec![0u8; EVENT_BLOOM_BYTES])
+    }
+}
+
+impl EventBloom {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != EVENT_BLOOM_BYTES {
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "Event bloom must be {} bytes, got {}",
+                EVENT_BLOOM_BYTES,
+                bytes.len()
+            )));
+        }
+        Ok(Self(bytes.to_vec()))
+    }
+
+    pub fn for_block(block: &Block) -> Self {
+        let mut bloom = Self::new();
+        for tx in &block.transactions {
+            bloom.accrue_transaction(tx);
+        }
+        bloom
+    }
+
+    pub fn accrue_transaction(&mut self, tx: &Transaction) {
+        // The coinbase has no sender
+        if tx.from != Address::zero() {
+            self.accrue(tx.from.as_bytes());
+        }
+        self.accrue(tx.to.as_bytes());
+        for topic in transaction_topics(tx) {
+            self.accrue(topic.as_bytes());
+        }
+    }
+
+    pub fn accrue(&mut self, item: &[u8]) {
+        for (byte, mask) in Self::bits(item) {
+            self.0[byte] |= mask;
+        }
+    }
+
+    pub fn may_contain(&self, item: &[u8]) -> bool {
+        Self::bits(item).all(|(byte, mask)| self.0[byte] & mask != 0)
+    }
+
+    pub fn may_contain_address(&self, address: &Address) -> bool {
+        self.may_contain(address.as_bytes())
+    }
+
+    pub fn may_contain_topic(&self, topic: &Hash) -> bool {
+        self.may_contain(topic.as_bytes())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Byte index and bit mask of each bit `item` sets
+    fn bits(item: &[u8]) -> impl Iterator<Item = (usize, u8)> {
+        let digest = blake3::hash(item);
+        let digest = *digest.as_bytes();
+        (0..BLOOM_HASHES).map(move |i| {
+            let bit = u16::from_be_bytes([digest[2 * i], digest[2 * i + 1]]) as usize
+                % (EVENT_BLOOM_BYTES * 8);
+            (bit / 8, 1u8 << (bit % 8))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Amount;
+
+    #[test]
+    fn test_block_bloom_matches_its_transactions() {
+        let from = Address::new([1u8; 32]);
+        let to = Address::new([2u8; 32]);
+        let block = Block::new(Hash::zero(), 1).with_transactions(vec![Transaction::new(
+            from,
+            to,
+            Amount::new(1),
+            Amount::zero(),
+        )]);
+
+        let bloom = EventBloom::for_block(&block);
+        assert!(bloom.may_contain_address(&from));
+        assert!(bloom.may_contain_address(&to));
+        assert!(bloom.may_contain_topic(&event_topic("payload:transfer")));
+        assert!(!bloom.may_contain_address(&Address::new([3u8; 32])));
+        assert!(!bloom.may_contain_topic(&event_topic("payload:contract_call")));
+
+        assert_eq!(EventBloom::from_bytes(bloom.as_bytes()).unwrap(), bloom);
+        assert!(EventBloom::from_bytes(&[0u8; 8]).is_err());
+    }
+}
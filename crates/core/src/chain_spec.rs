@@ -0,0 +1,333 @@
+// Chain specification
+// A machine-readable description of a network for third-party implementations
+// and auditors. Everything is read from what the node itself runs on: the
+// network's ChainParams, the protocol constants, the genesis config, and the
+// types' own serde definitions for field order. Test vectors are produced by
+// the real encoders, so an implementation can check its encoding, hashing and
+// signing preimages byte for byte. `spira spec export` adds the consensus and
+// network sections from their crates.
+
+use crate::{
+    Address, Amount, BlockHeader, ChainParams, GenesisConfig, Hash, Transaction,
+    GENESIS_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+/// Bumped whenever the layout of the spec document changes
+pub const CHAIN_SPEC_VERSION: u32 = 1;
+
+/// Hashes and fork ids are `0x` hex
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainSpec {
+    pub spec_version: u32,
+    pub network: String,
+    pub network_id: String,
+    pub chain_id: u64,
+    pub token: TokenSpec,
+    pub genesis: GenesisSpec,
+    pub protocol: ProtocolSpec,
+    pub limits: LimitsSpec,
+    pub economics: EconomicsSpec,
+    pub wire: WireSpec,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenSpec {
+    pub name: &'static str,
+    pub symbol: &'static str,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GenesisSpec {
+    pub hash: &'static str,
+    pub config: GenesisConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolSpec {
+    /// Highest version the exporting binary validates
+    pub binary_version: u32,
+    pub genesis_version: u32,
+    /// Fork id transactions are signed for until the first hard fork
+    pub genesis_fork_id: String,
+    pub hard_forks: Vec<HardForkSpec>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HardForkSpec {
+    pub name: &'static str,
+    pub version: u32,
+    pub height: u64,
+    pub fork_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitsSpec {
+    pub max_block_size: usize,
+    pub max_tx_per_block: usize,
+    pub max_header_extra_data: usize,
+    pub max_contract_code_size: usize,
+    pub max_contract_input_size: usize,
+    pub max_purpose_size: usize,
+    pub max_semantic_fields_size: usize,
+    pub scheduled_tx_window: u64,
+    pub max_schedule_lead: u64,
+    pub pause_guardians: Vec<&'static str>,
+    pub pause_threshold: usize,
+}
+
+/// Amounts in the smallest unit, as decimal strings
+#[derive(Debug, Clone, Serialize)]
+pub struct EconomicsSpec {
+    pub block_time_target_secs: u64,
+    pub finality_blocks: u64,
+    pub initial_block_reward: String,
+    pub halving_blocks: u64,
+    pub max_supply: String,
+    pub min_validator_stake: String,
+    pub max_validators: usize,
+    pub lock_period_blocks: u64,
+    pub min_tx_fee: String,
+    pub semantic_free_bytes: usize,
+    pub semantic_fee_per_byte: String,
+    pub slashing: SlashingSpec,
+}
+
+/// Share of stake slashed per offence
+#[derive(Debug, Clone, Serialize)]
+pub struct SlashingSpec {
+    pub invalid_spiral: f64,
+    pub double_signing: f64,
+    pub semantic_manipulation: f64,
+    pub downtime: f64,
+    pub censorship: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WireSpec {
+    pub encoding: &'static str,
+    pub hash: &'static str,
+    pub signature: &'static str,
+    pub address: &'static str,
+    /// Field order of the binary encoding, per type
+    pub types: Vec<TypeSpec>,
+    pub test_vectors: Vec<TestVector>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeSpec {
+    pub name: &'static str,
+    pub fields: Vec<String>,
+}
+
+/// An object with its encodings, binary ones in hex
+#[derive(Debug, Clone, Serialize)]
+pub struct TestVector {
+    pub name: &'static str,
+    /// As a string: amounts are u128, which JSON readers tend to round
+    pub json: String,
+    pub bincode: String,
+    pub hash: String,
+    /// Bytes the signer signs
+    pub signing_preimage: String,
+}
+
+impl ChainSpec {
+    pub fn for_network(network: &str) -> Self {
+        let params = ChainParams::for_network(network);
+        let genesis = GenesisConfig::default();
+        let amount = |value: u128| value.to_string();
+
+        let (tx, header) = sample_objects(params, genesis.timestamp);
+        let test_vectors = vec![
+            TestVector {
+                name: "transfer",
+                json: serde_json::to_string(&tx).unwrap_or_default(),
+                bincode: hex::encode(tx.serialize()),
+                hash: tx.tx_hash.to_string(),
+                signing_preimage: hex::encode(tx.signing_message()),
+            },
+            TestVector {
+                name: "block_header",
+                json: serde_json::to_string(&header).unwrap_or_default(),
+                bincode: hex::encode(header.serialize()),
+                hash: header.hash().to_string(),
+                signing_preimage: hex::encode(header.hash().as_bytes()),
+            },
+        ];
+
+        Self {
+            spec_version: CHAIN_SPEC_VERSION,
+            network: params.network.to_string(),
+            network_id: params.network_id(),
+            chain_id: crate::CHAIN_ID,
+            token: TokenSpec {
+                name: crate::TOKEN_NAME,
+                symbol: crate::TOKEN_SYMBOL,
+                decimals: crate::TOKEN_DECIMALS,
+            },
+            genesis: GenesisSpec {
+                hash: params.genesis_hash,
+                config: genesis,
+            },
+            protocol: ProtocolSpec {
+                binary_version: PROTOCOL_VERSION,
+                genesis_version: GENESIS_PROTOCOL_VERSION,
+                genesis_fork_id: params.fork_id(0).to_string(),
+                hard_forks: params
+                    .hard_forks
+                    .iter()
+                    .map(|fork| HardForkSpec {
+                        name: fork.name,
+                        version: fork.version,
+                        height: fork.height,
+                        fork_id: params.fork_id(fork.height).to_string(),
+                    })
+                    .collect(),
+            },
+            limits: LimitsSpec {
+                max_block_size: params.block_limits.max_block_size,
+                max_tx_per_block: params.block_limits.max_tx_per_block,
+                max_header_extra_data: crate::MAX_HEADER_EXTRA_DATA_SIZE,
+                max_contract_code_size: crate::MAX_CONTRACT_CODE_SIZE,
+                max_contract_input_size: crate::MAX_CONTRACT_INPUT_SIZE,
+                max_purpose_size: crate::MAX_PURPOSE_SIZE,
+                max_semantic_fields_size: crate::MAX_SEMANTIC_FIELDS_SIZE,
+                scheduled_tx_window: crate::SCHEDULED_TX_WINDOW,
+                max_schedule_lead: crate::MAX_SCHEDULE_LEAD,
+                pause_guardians: params.pause_multisig.guardians.to_vec(),
+                pause_threshold: params.pause_multisig.threshold,
+            },
+            economics: EconomicsSpec {
+                block_time_target_secs: crate::BLOCK_TIME_TARGET,
+                finality_blocks: crate::FINALITY_BLOCKS,
+                initial_block_reward: amount(crate::INITIAL_BLOCK_REWARD),
+                halving_blocks: crate::HALVING_BLOCKS,
+                max_supply: amount(crate::MAX_SUPPLY),
+                min_validator_stake: amount(crate::MIN_VALIDATOR_STAKE),
+                max_validators: crate::MAX_VALIDATORS,
+                lock_period_blocks: crate::LOCK_PERIOD_BLOCKS,
+                min_tx_fee: amount(crate::MIN_TX_FEE),
+                semantic_free_bytes: crate::SEMANTIC_FREE_BYTES,
+                semantic_fee_per_byte: amount(crate::SEMANTIC_FEE_PER_BYTE),
+                slashing: SlashingSpec {
+                    invalid_spiral: crate::SLASHING_INVALID_SPIRAL,
+                    double_signing: crate::SLASHING_DOUBLE_SIGNING,
+                    semantic_manipulation: crate::SLASHING_SEMANTIC_MANIPULATION,
+                    downtime: crate::SLASHING_DOWNTIME,
+                    censorship: crate::SLASHING_CENSORSHIP,
+                },
+            },
+            wire: WireSpec {
+                encoding: "bincode 1.x default options: little-endian fixed-width integers, u64 length prefixes, u32 enum tags, fields in declaration order",
+                hash: "blake3-256",
+                signature: "ed25519 (strict verification)",
+                address: "blake3-256 of the ed25519 public key",
+                types: vec![
+                    TypeSpec {
+                        name: "Transaction",
+                        fields: field_order(&tx),
+                    },
+                    TypeSpec {
+                        name: "BlockHeader",
+                        fields: field_order(&header),
+                    },
+                ],
+                test_vectors,
+            },
+        }
+    }
+}
+
+/// A transfer and a header with fixed contents, so every export is identical
+fn sample_objects(params: &ChainParams, timestamp: u64) -> (Transaction, BlockHeader) {
+    let mut tx = Transaction::new_at(
+        Address::new([1u8; 32]),
+        Address::new([2u8; 32]),
+        Amount::qbt(1),
+        Amount::new(crate::MIN_TX_FEE),
+        timestamp,
+    );
+    tx.fork_id = params.fork_id(0);
+    tx.compute_hash();
+
+    let mut header = BlockHeader::new(Hash::new([1u8; 32]), 1);
+    header.timestamp = timestamp;
+    (tx, header)
+}
+
+/// Names of `value`'s fields in the order serde, and so bincode, writes them
+fn field_order<T: Serialize>(value: &T) -> Vec<String> {
+    serde_json::to_string(value)
+        .ok()
+        .and_then(|json| serde_json::from_str::<FieldNames>(&json).ok())
+        .map(|names| names.0)
+        .unwrap_or_default()
+}
+
+/// Keys of a JSON object in document order
+struct FieldNames(Vec<String>);
+
+impl<'de> Deserialize<'de> for FieldNames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = FieldNames;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<FieldNames, A::Error> {
+                let mut names = Vec::new();
+                while let Some(name) = map.next_key::<String>()? {
+                    map.next_value::<IgnoredAny>()?;
+                    names.push(name);
+                }
+                Ok(FieldNames(names))
+            }
+        }
+
+        deserializer.deserialize_map(KeysVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_matches_the_running_code() {
+        let spec = ChainSpec::for_network("testnet");
+        assert_eq!(spec.genesis.hash, crate::TESTNET_GENESIS_HASH);
+        assert_eq!(
+            spec.protocol.genesis_fork_id,
+            crate::fork_id("testnet", 0).to_string()
+        );
+        assert_eq!(ChainSpec::for_network("mainnet").network, "mainnet");
+
+        let tx_fields = &spec.wire.types[0].fields;
+        assert_eq!(tx_fields.len(), 23);
+        assert_eq!(&tx_fields[..3], ["version", "tx_hash", "pi_id"]);
+        assert_eq!(tx_fields.last().unwrap(), "nonce");
+
+        // The vectors decode with the real decoders and hash to what they claim
+        let transfer = &spec.wire.test_vectors[0];
+        let tx = Transaction::deserialize(&hex::decode(&transfer.bincode).unwrap()).unwrap();
+        assert_eq!(tx.computed_hash().to_string(), transfer.hash);
+        assert_eq!(hex::encode(tx.signing_message()), transfer.signing_preimage);
+        let from_json: Transaction = serde_json::from_str(&transfer.json).unwrap();
+        assert_eq!(from_json.serialize(), tx.serialize());
+        let header = &spec.wire.test_vectors[1];
+        let decoded = BlockHeader::deserialize(&hex::decode(&header.bincode).unwrap()).unwrap();
+        assert_eq!(decoded.hash().to_string(), header.hash);
+
+        // Identical on every export
+        let again = serde_json::to_string(&ChainSpec::for_network("testnet")).unwrap();
+        assert_eq!(serde_json::to_string(&spec).unwrap(), again);
+    }
+}
@@ -8,14 +8,25 @@ pub const BLOCK_TIME_TARGET: u64 = 30;
 pub const FINALITY_BLOCKS: u64 = 12;
 pub const MAX_BLOCK_SIZE: usize = 1_048_576;
 pub const MAX_TX_PER_BLOCK: usize = 1000;
+/// Cap on BlockHeader::extra_data under the v1 header rules
+pub const MAX_HEADER_EXTRA_DATA_SIZE: usize = 64;
 
 pub const MIN_VALIDATOR_STAKE: u128 = 10_000 * 10u128.pow(TOKEN_DECIMALS as u32);
 pub const MAX_VALIDATORS: usize = 1000;
 pub const LOCK_PERIOD_BLOCKS: u64 = 100_000;
 
+/// Allocated by the default genesis config; not counted against MAX_SUPPLY
 pub const INITIAL_SUPPLY: u128 = 21_000_000 * 10u128.pow(TOKEN_DECIMALS as u32);
 pub const INITIAL_BLOCK_REWARD: u128 = 10 * 10u128.pow(TOKEN_DECIMALS as u32);
-pub const HALVING_BLOCKS: u64 = 2_102_400;
+/// About one year of 30 second blocks. Emission is bounded by
+/// 2 * HALVING_BLOCKS * INITIAL_BLOCK_REWARD, which stays under MAX_SUPPLY
+pub const HALVING_BLOCKS: u64 = 1_050_000;
+/// Halvings after which the block reward is zero; the last reward is paid at
+/// height MAX_HALVINGS * HALVING_BLOCKS - 1
+pub const MAX_HALVINGS: u32 = 64;
+/// Hard cap on QBT ever minted through block rewards (emission only, genesis
+/// allocations come on top)
+pub const MAX_SUPPLY: u128 = 21_000_000 * 10u128.pow(TOKEN_DECIMALS as u32);
 
 pub const PI_PRECISION: usize = 1000;
 pub const E_PRECISION: usize = 1000;
@@ -23,14 +34,36 @@ pub const PHI_PRECISION: usize = 1000;
 
 pub const SEMANTIC_VECTOR_DIM: usize = 1536;
 pub const MIN_SEMANTIC_COHERENCE: f64 = 0.0; // Testnet: accepter blocs vides
+/// [`crate::Transaction::semantic_score`] of a fully coherent vector
+pub const SEMANTIC_SCORE_MAX: u16 = 10_000;
 
 pub const MIN_SPIRAL_COMPLEXITY: f64 = 50.0;
 pub const MAX_SPIRAL_COMPLEXITY: f64 = 250.0; // Cap to keep Raspberry Pi validators viable
 pub const MAX_SPIRAL_JUMP: f64 = 4.0; // Testnet: augmenté pour accommoder sauts basés sur π (3.14159...)
 
-pub const FEE_BURN_RATE: f64 = 0.3;
+/// Blocks after its `execute_at` height a scheduled transaction may still be
+/// included; it expires after that
+pub const SCHEDULED_TX_WINDOW: u64 = 1_000;
+/// Furthest ahead of the chain a scheduled transaction is accepted, one week
+pub const MAX_SCHEDULE_LEAD: u64 = 20_160;
+
+/// Share of every transaction fee burned rather than paid to the block
+/// producer, in basis points
+pub const FEE_BURN_BPS: u128 = 3_000;
 pub const MIN_TX_FEE: u128 = 1_000_000_000_000_000;
 
+pub const MAX_CONTRACT_CODE_SIZE: usize = 65_536;
+pub const MAX_CONTRACT_INPUT_SIZE: usize = 16_384;
+
+pub const MAX_PURPOSE_SIZE: usize = 512;
+pub const MAX_ENTITIES: usize = 32;
+pub const MAX_ENTITY_NAME_SIZE: usize = 128;
+/// Cap on the serialized purpose + semantic_vector + entities + intent
+pub const MAX_SEMANTIC_FIELDS_SIZE: usize = 8_192;
+/// Semantic bytes already paid for by MIN_TX_FEE
+pub const SEMANTIC_FREE_BYTES: usize = 256;
+pub const SEMANTIC_FEE_PER_BYTE: u128 = 1_000_000_000_000; // 0.000001 QBT
+
 pub const SLASHING_INVALID_SPIRAL: f64 = 0.05;
 pub const SLASHING_DOUBLE_SIGNING: f64 = 0.50;
 pub const SLASHING_SEMANTIC_MANIPULATION: f64 = 0.10;
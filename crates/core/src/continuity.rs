@@ -0,0 +1,83 @@
+// Spiral continuity proofs
+// Continuity between a block and its parent depends on two complexities and
+// one π distance. A proof records those values once, keyed by the block and
+// parent hashes, so archive re-validation and light checks re-run the rule on
+// 104 bytes instead of re-deriving them from both blocks.
+
+use crate::{check_continuity, squared_jump, Block, Hash, LightError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContinuityProof {
+    pub block_hash: Hash,
+    pub parent_hash: Hash,
+    pub height: u64,
+    pub parent_complexity: f64,
+    pub child_complexity: f64,
+    /// Squared distance between the parent's and the block's π coordinates
+    pub squared_jump: f64,
+    /// Average semantic coherence of the block's transactions
+    pub coherence: f64,
+}
+
+impl ContinuityProof {
+    /// Values of `block` against `parent`; says nothing about whether they pass
+    pub fn compute(parent: &Block, block: &Block) -> Self {
+        let parent_view = parent.header.view();
+        let view = block.header.view();
+        Self {
+            block_hash: block.hash(),
+            parent_hash: parent.hash(),
+            height: block.header.block_height,
+            parent_complexity: parent_view.spiral_complexity,
+            child_complexity: view.spiral_complexity,
+            squared_jump: squared_jump(&parent_view, &view),
+            coherence: block.avg_semantic_coherence(),
+        }
+    }
+
+    /// Whether this proof is about `block_hash` on top of `parent_hash`
+    pub fn covers(&self, block_hash: &Hash, parent_hash: &Hash) -> bool {
+        self.block_hash == *block_hash && self.parent_hash == *parent_hash
+    }
+
+    /// The continuity rule on the recorded values
+    pub fn check(&self, max_spiral_jump: f64) -> Result<(), LightError> {
+        check_continuity(
+            self.height,
+            self.parent_complexity,
+            self.child_complexity,
+            self.squared_jump,
+            max_spiral_jump,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PiCoordinate;
+
+    #[test]
+    fn test_proof_matches_header_rule() {
+        let parent = Block::new(Hash::zero(), 0);
+        let mut block = Block::new(parent.hash(), 1);
+        block.header.spiral.complexity = parent.header.spiral.complexity;
+        block.header.pi_coordinates = PiCoordinate::new(3.0, 4.0, 0.0, 0.0);
+
+        let proof = ContinuityProof::compute(&parent, &block);
+        assert!(proof.covers(&block.hash(), &parent.hash()));
+        assert_eq!(
+            proof.check(10.0).is_ok(),
+            crate::verify_continuity(&parent.header.view(), &block.header.view(), 10.0).is_ok()
+        );
+        assert!(proof.check(1.0).is_err());
+
+        let bytes = bincode::serialize(&proof).unwrap();
+        assert_eq!(bytes.len(), 32 + 32 + 8 + 4 * 8);
+        assert_eq!(
+            bincode::deserialize::<ContinuityProof>(&bytes).unwrap(),
+            proof
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{Address, SpiralMetadata, SpiralType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Blocks per diversity epoch; a producer's spiral history resets at each boundary
+pub const DIVERSITY_EPOCH_BLOCKS: u64 = 1_000;
+
+/// Completed epochs kept for the explorer besides the current one
+pub const DIVERSITY_HISTORY_EPOCHS: u64 = 4;
+
+/// Share of the scheduled reward, in basis points, withheld when a producer
+/// repeats a spiral kind it already used in the current epoch
+pub const REPEAT_SPIRAL_PENALTY_BPS: u128 = 1_000;
+
+pub fn diversity_epoch(height: u64) -> u64 {
+    height / DIVERSITY_EPOCH_BLOCKS
+}
+
+/// What counts as a distinct spiral: its type, or the registry id for custom spirals
+pub fn spiral_kind(spiral: &SpiralMetadata) -> String {
+    match (spiral.spiral_type, spiral.custom_spiral_id()) {
+        (SpiralType::Custom, Some(id)) => format!("Custom:{}", id),
+        (spiral_type, _) => spiral_type.to_string(),
+    }
+}
+
+/// Spiral kinds one validator produced during one epoch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorDiversity {
+    pub blocks: u64,
+    pub kinds: BTreeMap<String, u64>,
+}
+
+impl ValidatorDiversity {
+    pub fn distinct_kinds(&self) -> usize {
+        self.kinds.len()
+    }
+
+    /// Distinct kinds per block produced, 1.0 when every block was novel
+    pub fn score(&self) -> f64 {
+        if self.blocks == 0 {
+            return 0.0;
+        }
+        self.kinds.len() as f64 / self.blocks as f64
+    }
+}
+
+/// On-chain spiral history per validator and epoch. Rewards depend on it, so
+/// every node derives it from the blocks it applies.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiralDiversity {
+    epochs: BTreeMap<u64, BTreeMap<Address, ValidatorDiversity>>,
+}
+
+impl SpiralDiversity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `kind` would be new for `validator` in the epoch of `height`
+    pub fn is_novel(&self, validator: &Address, kind: &str, height: u64) -> bool {
+        self.validator_epoch(validator, diversity_epoch(height))
+            .is_none_or(|stats| !stats.kinds.contains_key(kind))
+    }
+
+    pub fn validator_epoch(&self, validator: &Address, epoch: u64) -> Option<&ValidatorDiversity> {
+        self.epochs.get(&epoch)?.get(validator)
+    }
+
+    /// Every validator's stats for `epoch`
+    pub fn epoch(&self, epoch: u64) -> impl Iterator<Item = (&Address, &ValidatorDiversity)> {
+        self.epochs.get(&epoch).into_iter().flatten()
+    }
+
+    /// Epochs with recorded blocks, oldest first
+    pub fn epochs(&self) -> impl Iterator<Item = u64> + '_ {
+        self.epochs.keys().copied()
+    }
+
+    pub fn record(&mut self, validator: Address, kind: String, height: u64) {
+        let epoch = diversity_epoch(height);
+        let stats = self
+            .epochs
+            .entry(epoch)
+            .or_default()
+            .entry(validator)
+            .or_default();
+        stats.blocks += 1;
+        *stats.kinds.entry(kind).or_default() += 1;
+
+        let oldest = epoch.saturating_sub(DIVERSITY_HISTORY_EPOCHS);
+        self.epochs.retain(|kept, _| *kept >= oldest);
+    }
+
+    /// Stable text for the state root; empty until the first block is recorded
+    pub fn state_entry(&self) -> String {
+        let mut entry = String::new();
+        for (epoch, validators) in &self.epochs {
+            for (validator, stats) in validators {
+                entry.push_str(&format!(
+                    "diversity:{}:{}:{}",
+                    epoch, validator, stats.blocks
+                ));
+                for (kind, count) in &stats.kinds {
+                    entry.push_str(&format!(":{}={}", kind, count));
+                }
+            }
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_novelty_resets_each_epoch() {
+        let mut diversity = SpiralDiversity::new();
+        let validator = Address::new([1u8; 32]);
+        let fibonacci = spiral_kind(&SpiralMetadata::new(SpiralType::Fibonacci));
+
+        assert!(diversity.is_novel(&validator, &fibonacci, 10));
+        diversity.record(validator, fibonacci.clone(), 10);
+        assert!(!diversity.is_novel(&validator, &fibonacci, 11));
+        assert!(diversity.is_novel(&Address::new([2u8; 32]), &fibonacci, 11));
+        assert!(diversity.is_novel(&validator, &fibonacci, DIVERSITY_EPOCH_BLOCKS));
+
+        diversity.record(validator, fibonacci.clone(), 11);
+        let stats = diversity.validator_epoch(&validator, 0).unwrap();
+        assert_eq!(stats.blocks, 2);
+        assert_eq!(stats.distinct_kinds(), 1);
+        assert_eq!(stats.score(), 0.5);
+
+        let far = (DIVERSITY_HISTORY_EPOCHS + 1) * DIVERSITY_EPOCH_BLOCKS;
+        diversity.record(validator, fibonacci, far);
+        assert!(diversity.validator_epoch(&validator, 0).is_none());
+    }
+}
@@ -0,0 +1,178 @@
+// Epoch summaries
+// The first block of each diversity epoch carries, right after its coinbase,
+// a compact summary of the epoch before: which validators produced blocks,
+// the fees collected and a merkle root over the rewards each coinbase
+// recipient received. Every field is derived from the chain, so nodes
+// recompute and check it, and external systems can verify validator
+// performance from one transaction (with its merkle proof against the block
+// header) instead of replaying the epoch.
+
+use crate::{diversity_epoch, Address, Amount, Block, Hash, DIVERSITY_EPOCH_BLOCKS};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochSummary {
+    pub epoch: u64,
+    /// Participants of the previous summary plus this epoch's producers,
+    /// sorted; a validator that stops producing shows up once as absent
+    pub validators: Vec<Address>,
+    /// Bit `i % 8` of byte `i / 8` is set when `validators[i]` produced a block
+    pub participation: Vec<u8>,
+    pub blocks: u64,
+    pub total_fees: Amount,
+    /// Merkle root over the [`reward_leaf`] of every rewarded address, sorted
+    pub reward_root: Hash,
+}
+
+impl EpochSummary {
+    /// Summary of `epoch` from its blocks; `previous` is the summary of the epoch before
+    pub fn compute(epoch: u64, previous: Option<&EpochSummary>, blocks: &[Block]) -> Self {
+        let mut producers = BTreeSet::new();
+        let mut rewards: BTreeMap<Address, Amount> = BTreeMap::new();
+        let mut total_fees = Amount::zero();
+        for block in blocks {
+            producers.extend(block.header.producer_address());
+            total_fees = total_fees
+                .checked_add(block.total_fees())
+                .unwrap_or(total_fees);
+            if let Some(coinbase) = block.coinbase() {
+                let reward = rewards.entry(coinbase.to).or_insert_with(Amount::zero);
+                *reward = reward.checked_add(coinbase.amount).unwrap_or(*reward);
+            }
+        }
+
+        let validators: Vec<Address> = previous
+            .into_iter()
+            .flat_map(|summary| summary.participants())
+            .chain(producers.iter().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut participation = vec![0u8; validators.len().div_ceil(8)];
+        for (index, validator) in validators.iter().enumerate() {
+            if producers.contains(validator) {
+                participation[index / 8] |= 1 << (index % 8);
+            }
+        }
+
+        Self {
+            epoch,
+            validators,
+            participation,
+            blocks: blocks.len() as u64,
+            total_fees,
+            reward_root: merkle_root(
+                rewards
+                    .iter()
+                    .map(|(address, reward)| reward_leaf(address, *reward))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn participated(&self, index: usize) -> bool {
+        self.participation
+            .get(index / 8)
+            .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+    }
+
+    pub fn participants(&self) -> impl Iterator<Item = Address> + '_ {
+        self.validators
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.participated(*index))
+            .map(|(_, validator)| *validator)
+    }
+
+    /// Checks that need nothing but the summary and the height of its block
+    pub fn validate_at(&self, height: u64) -> crate::Result<()> {
+        if !is_epoch_start(height) || self.epoch + 1 != diversity_epoch(height) {
+            return Err(crate::SpiraChainError::InvalidTransaction(format!(
+                "Summary of epoch {} does not belong in block {}",
+                self.epoch, height
+            )));
+        }
+        if self.participation.len() != self.validators.len().div_ceil(8) {
+            return Err(crate::SpiraChainError::InvalidTransaction(
+                "Participation bitmap does not match the validator list".to_string(),
+            ));
+        }
+        if !self.validators.windows(2).all(|pair| pair[0] < pair[1]) {
+            return Err(crate::SpiraChainError::InvalidTransaction(
+                "Summary validators must be sorted and unique".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Whether the block at `height` opens an epoch and so carries the summary of the last one
+pub fn is_epoch_start(height: u64) -> bool {
+    height > 0 && height.is_multiple_of(DIVERSITY_EPOCH_BLOCKS)
+}
+
+/// Leaf of the reward tree: `address ‖ reward` (big-endian), hashed
+pub fn reward_leaf(address: &Address, reward: Amount) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(address.as_bytes());
+    hasher.update(&reward.value().to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Pairs like [`Block::compute_merkle_root`]: an odd node out is paired with itself
+fn merkle_root(mut hashes: Vec<Hash>) -> Hash {
+    if hashes.is_empty() {
+        return Hash::zero();
+    }
+    while hashes.len() > 1 {
+        hashes = hashes
+            .chunks(2)
+            .map(|chunk| {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(chunk[0].as_bytes());
+                hasher.update(chunk.get(1).unwrap_or(&chunk[0]).as_bytes());
+                hasher.finalize().into()
+            })
+            .collect();
+    }
+    hashes[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Transaction;
+
+    fn block_by(producer: u8, height: u64, reward: u128) -> Block {
+        let mut block = Block::new(Hash::zero(), height).with_validator(vec![producer; 32]);
+        let address = block.header.producer_address().unwrap();
+        block = block.with_transactions(vec![Transaction::new_coinbase(
+            address,
+            Amount::new(reward),
+            height,
+        )]);
+        block
+    }
+
+    #[test]
+    fn test_summary_participation_and_rewards() {
+        let first = EpochSummary::compute(0, None, &[block_by(1, 1, 10), block_by(2, 2, 10)]);
+        assert_eq!(first.validators.len(), 2);
+        assert_eq!(first.participants().count(), 2);
+        assert_eq!(first.blocks, 2);
+
+        // Validator 2 went silent: listed once more, as absent
+        let blocks = [block_by(1, 1001, 10), block_by(1, 1002, 5)];
+        let second = EpochSummary::compute(1, Some(&first), &blocks);
+        let producer = blocks[0].header.producer_address().unwrap();
+        assert_eq!(second.validators.len(), 2);
+        assert_eq!(second.participants().collect::<Vec<_>>(), vec![producer]);
+        assert_eq!(second.reward_root, reward_leaf(&producer, Amount::new(15)));
+        assert_eq!(EpochSummary::compute(1, Some(&first), &blocks), second);
+
+        assert!(second.validate_at(2 * DIVERSITY_EPOCH_BLOCKS).is_ok());
+        assert!(second.validate_at(2 * DIVERSITY_EPOCH_BLOCKS + 1).is_err());
+        assert!(second.validate_at(DIVERSITY_EPOCH_BLOCKS).is_err());
+    }
+}
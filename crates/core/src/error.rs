@@ -1,5 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use thiserror::Error;
 
+/// Subsystem an error comes from; decides how APIs report it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorCategory {
+    /// Malformed or unacceptable input: transactions, amounts, payloads
+    Validation,
+    Consensus,
+    Storage,
+    Network,
+    Crypto,
+    Semantic,
+    /// A quota, lane or mempool limit; retrying later may succeed
+    Resource,
+    Config,
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Whether the details may be shown to API clients. Storage and internal
+    /// errors describe the node, not the request, and are only logged.
+    pub fn is_public(&self) -> bool {
+        !matches!(self, ErrorCategory::Storage | ErrorCategory::Internal)
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorCategory::Validation => "validation",
+            ErrorCategory::Consensus => "consensus",
+            ErrorCategory::Storage => "storage",
+            ErrorCategory::Network => "network",
+            ErrorCategory::Crypto => "crypto",
+            ErrorCategory::Semantic => "semantic",
+            ErrorCategory::Resource => "resource",
+            ErrorCategory::Config => "config",
+            ErrorCategory::Internal => "internal",
+        };
+        f.write_str(name)
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum SpiraChainError {
     #[error("Invalid block: {0}")]
@@ -26,6 +70,12 @@ pub enum SpiraChainError {
     #[error("Insufficient balance")]
     InsufficientBalance,
 
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Balance overflow for {0}")]
+    BalanceOverflow(String),
+
     #[error("Block not found: {0}")]
     BlockNotFound(String),
 
@@ -56,6 +106,35 @@ pub enum SpiraChainError {
     #[error("Insufficient evidence")]
     InsufficientEvidence,
 
+    #[error("Near-duplicate of pending transaction {0}")]
+    NearDuplicate(String),
+
+    #[error("Priority lane limit: {0}")]
+    LaneLimit(String),
+
+    #[error("Resource quota exceeded: {0}")]
+    ResourceExhausted(String),
+
+    #[error("Upgrade required: {0}")]
+    UnsupportedProtocol(String),
+
+    #[error("Mempool full")]
+    MempoolFull,
+
+    #[error("Semantic engine error: {0}")]
+    SemanticEngine(String),
+
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
+    /// File access that failed; the OS error stays reachable through `source()`
+    #[error("I/O error: {context}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+
     #[error("Internal error: {0}")]
     Internal(String),
 
@@ -63,4 +142,120 @@ pub enum SpiraChainError {
     Other(#[from] anyhow::Error),
 }
 
+impl SpiraChainError {
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        SpiraChainError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Stable numeric code, grouped by category in blocks of a thousand.
+    /// Codes are part of the API: never renumber or reuse one.
+    pub fn code(&self) -> u32 {
+        match self {
+            SpiraChainError::InvalidTransaction(_) => 1001,
+            SpiraChainError::InvalidAmount(_) => 1002,
+            SpiraChainError::InsufficientBalance => 1003,
+            SpiraChainError::BalanceOverflow(_) => 1004,
+            SpiraChainError::NearDuplicate(_) => 1005,
+            SpiraChainError::TransactionNotFound(_) => 1006,
+            SpiraChainError::SerializationError(_) => 1007,
+            SpiraChainError::InvalidBlock(_) => 2001,
+            SpiraChainError::ConsensusError(_) => 2002,
+            SpiraChainError::InsufficientStake(_, _) => 2003,
+            SpiraChainError::ValidatorNotFound(_) => 2004,
+            SpiraChainError::InsufficientEvidence => 2005,
+            SpiraChainError::UnsupportedProtocol(_) => 2006,
+            SpiraChainError::VmError(_) => 2007,
+            SpiraChainError::StorageError(_) => 3001,
+            SpiraChainError::BlockNotFound(_) => 3002,
+            SpiraChainError::Io { .. } => 3003,
+            SpiraChainError::NetworkError(_) => 4001,
+            SpiraChainError::InvalidSignature => 5001,
+            SpiraChainError::CryptoError(_) => 5002,
+            SpiraChainError::InvalidSpiral(_) => 6001,
+            SpiraChainError::SpiralComplexityTooLow(_, _) => 6002,
+            SpiraChainError::SemanticCoherenceTooLow(_, _) => 6003,
+            SpiraChainError::SemanticEngine(_) => 6004,
+            SpiraChainError::ResourceExhausted(_) => 7001,
+            SpiraChainError::LaneLimit(_) => 7002,
+            SpiraChainError::MempoolFull => 7003,
+            SpiraChainError::Config(_) => 8001,
+            SpiraChainError::Internal(_) => 9001,
+            SpiraChainError::Other(_) => 9002,
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self.code() / 1000 {
+            1 => ErrorCategory::Validation,
+            2 => ErrorCategory::Consensus,
+            // Lookups of missing blocks are about the request, not the node
+            3 if matches!(self, SpiraChainError::BlockNotFound(_)) => ErrorCategory::Validation,
+            3 => ErrorCategory::Storage,
+            4 => ErrorCategory::Network,
+            5 => ErrorCategory::Crypto,
+            6 => ErrorCategory::Semantic,
+            7 => ErrorCategory::Resource,
+            8 => ErrorCategory::Config,
+            _ => ErrorCategory::Internal,
+        }
+    }
+
+    /// What an API client may see: the message itself, or only the category
+    /// for errors that would leak node internals (paths, database state)
+    pub fn public_message(&self) -> String {
+        if self.category().is_public() {
+            self.to_string()
+        } else {
+            format!("{} error (code {})", self.category(), self.code())
+        }
+    }
+
+    /// The message followed by every underlying cause, for logs
+    pub fn chain(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        message
+    }
+}
+
 pub type Result<T> = std::result::Result<T, SpiraChainError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_categories_and_sources() {
+        let invalid = SpiraChainError::InvalidTransaction("zero fee".to_string());
+        assert_eq!(invalid.code(), 1001);
+        assert_eq!(invalid.category(), ErrorCategory::Validation);
+        assert_eq!(invalid.public_message(), "Invalid transaction: zero fee");
+
+        let storage = SpiraChainError::StorageError("/var/lib/spira/db locked".to_string());
+        assert_eq!(storage.category(), ErrorCategory::Storage);
+        assert_eq!(storage.public_message(), "storage error (code 3001)");
+        assert_eq!(
+            SpiraChainError::BlockNotFound("7".to_string()).category(),
+            ErrorCategory::Validation
+        );
+
+        let io = SpiraChainError::io(
+            "reading runtime.json",
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no such file"),
+        );
+        assert_eq!(io.to_string(), "I/O error: reading runtime.json");
+        assert_eq!(io.chain(), "I/O error: reading runtime.json: no such file");
+        assert_eq!(
+            serde_json::to_string(&io.category()).unwrap(),
+            "\"storage\""
+        );
+    }
+}
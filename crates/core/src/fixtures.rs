@@ -0,0 +1,319 @@
+// Test chain fixtures
+// Builds deterministic chains for tests: the same builder settings always
+// give the same blocks, down to their hashes. Validator and account keys are
+// derived from a seed, validators produce in turn one block per interval, and
+// every block passes `Block::validate`: coinbase first, transfers signed for
+// the chain's fork id in canonical order, merkle root and producer signature
+// set. Competing branches can be grown off any height, to replay fork
+// incidents. Balances are not tracked; fund `accounts()` where state matters.
+// Enabled in other crates' tests by the `test-fixtures` feature.
+
+use crate::{
+    fork_id, sort_canonical, Address, Amount, Block, GenesisConfig, Hash, PiCoordinate,
+    Transaction, FEE_BURN_BPS, MIN_SPIRAL_COMPLEXITY, MIN_TX_FEE,
+};
+use ed25519_dalek::{Signer, SigningKey};
+
+const FIXTURE_KEY_CONTEXT: &str = "SpiraChain test fixtures v1";
+
+/// Transactions put in each block after the coinbase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxPattern {
+    Empty,
+    /// Transfers between the fixture accounts, each sender in turn
+    Transfers(usize),
+}
+
+/// Deterministic key for fixture validators and accounts
+#[derive(Clone)]
+pub struct TestKey {
+    key: SigningKey,
+}
+
+impl TestKey {
+    pub fn derive(seed: u64, role: &str, index: usize) -> Self {
+        let mut material = Vec::new();
+        material.extend_from_slice(&seed.to_be_bytes());
+        material.extend_from_slice(role.as_bytes());
+        material.extend_from_slice(&(index as u64).to_be_bytes());
+        Self {
+            key: SigningKey::from_bytes(&blake3::derive_key(FIXTURE_KEY_CONTEXT, &material)),
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    pub fn address(&self) -> Address {
+        Address::new(*blake3::hash(&self.public_key()).as_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.key.sign(message).to_bytes().to_vec()
+    }
+
+    /// Sign `tx` and attach the key, as wallets do
+    pub fn sign_transaction(&self, tx: &mut Transaction) {
+        tx.signature = self.sign(&tx.signing_message());
+        tx.extra_data.insert(
+            crate::SENDER_PUBLIC_KEY.to_string(),
+            self.public_key().to_vec(),
+        );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainBuilder {
+    network: String,
+    seed: u64,
+    validators: usize,
+    accounts: usize,
+    tx_pattern: TxPattern,
+    block_interval_ms: u64,
+    block_reward: Amount,
+}
+
+impl ChainBuilder {
+    pub fn new(network: &str) -> Self {
+        Self {
+            network: network.to_string(),
+            seed: 0,
+            validators: 3,
+            accounts: 4,
+            tx_pattern: TxPattern::Empty,
+            block_interval_ms: crate::BLOCK_TIME_TARGET * 1000,
+            block_reward: Amount::zero(),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_validators(mut self, validators: usize) -> Self {
+        self.validators = validators.max(1);
+        self
+    }
+
+    pub fn with_accounts(mut self, accounts: usize) -> Self {
+        self.accounts = accounts.max(2);
+        self
+    }
+
+    pub fn with_tx_pattern(mut self, tx_pattern: TxPattern) -> Self {
+        self.tx_pattern = tx_pattern;
+        self
+    }
+
+    pub fn with_block_interval(mut self, block_interval_ms: u64) -> Self {
+        self.block_interval_ms = block_interval_ms;
+        self
+    }
+
+    /// Minted by each coinbase on top of the fees; zero by default
+    pub fn with_block_reward(mut self, block_reward: Amount) -> Self {
+        self.block_reward = block_reward;
+        self
+    }
+
+    /// Genesis and `length` blocks on top of it
+    pub fn build(self, length: u64) -> TestChain {
+        let validators = (0..self.validators)
+            .map(|index| TestKey::derive(self.seed, "validator", index))
+            .collect();
+        let accounts = (0..self.accounts)
+            .map(|index| TestKey::derive(self.seed, "account", index))
+            .collect();
+        let mut chain = TestChain {
+            blocks: vec![GenesisConfig::default().create_genesis_block()],
+            validators,
+            accounts,
+            builder: self,
+        };
+        chain.extend(length);
+        chain
+    }
+}
+
+pub struct TestChain {
+    pub blocks: Vec<Block>,
+    validators: Vec<TestKey>,
+    accounts: Vec<TestKey>,
+    builder: ChainBuilder,
+}
+
+impl TestChain {
+    pub fn genesis(&self) -> &Block {
+        &self.blocks[0]
+    }
+
+    pub fn tip(&self) -> &Block {
+        self.blocks.last().expect("a chain holds its genesis")
+    }
+
+    pub fn block(&self, height: u64) -> &Block {
+        &self.blocks[height as usize]
+    }
+
+    pub fn validators(&self) -> &[TestKey] {
+        &self.validators
+    }
+
+    pub fn accounts(&self) -> &[TestKey] {
+        &self.accounts
+    }
+
+    /// Producer of the block at `height` on the main chain
+    pub fn producer_of(&self, height: u64) -> &TestKey {
+        &self.validators[height as usize % self.validators.len()]
+    }
+
+    /// Add `count` blocks on the tip
+    pub fn extend(&mut self, count: u64) {
+        for _ in 0..count {
+            let block = self.make_block(self.tip(), 0);
+            self.blocks.push(block);
+        }
+    }
+
+    /// A competing branch of `length` blocks on the block at `height`. Each
+    /// `branch` number gives other blocks, produced by the validators shifted
+    /// by that many places.
+    pub fn fork_at(&self, height: u64, length: u64, branch: usize) -> Vec<Block> {
+        let mut parent = self.block(height).clone();
+        let mut blocks = Vec::new();
+        for _ in 0..length {
+            let block = self.make_block(&parent, branch);
+            parent = block.clone();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    /// Sign `block` again with its producer's key, after a test changed its header
+    pub fn resign(&self, block: &mut Block) {
+        let signer = self
+            .validators
+            .iter()
+            .find(|key| block.header.validator_pubkey == key.public_key())
+            .expect("block produced by a fixture validator");
+        block.header.signature = signer.sign(block.hash().as_bytes());
+    }
+
+    fn make_block(&self, parent: &Block, branch: usize) -> Block {
+        let height = parent.header.block_height + 1;
+        let timestamp = parent.header.timestamp + self.builder.block_interval_ms;
+        let producer = &self.validators[(height as usize + branch) % self.validators.len()];
+
+        let mut transfers = self.transfers(height, timestamp, branch);
+        sort_canonical(&mut transfers);
+        let fees = transfers.iter().fold(Amount::zero(), |sum, tx| {
+            sum.checked_add(tx.fee).unwrap_or(sum)
+        });
+        // The producer keeps what is not burned
+        let fees = Amount::new(fees.value() - fees.basis_points(FEE_BURN_BPS).value());
+        let reward = self.builder.block_reward.checked_add(fees).unwrap_or(fees);
+        let mut coinbase = Transaction::new_coinbase(producer.address(), reward, height);
+        coinbase.timestamp = timestamp;
+        coinbase.compute_hash();
+
+        let mut transactions = vec![coinbase];
+        transactions.extend(transfers);
+        let mut block = Block::new(parent.hash(), height)
+            .with_transactions(transactions)
+            .with_pi_coordinates(PiCoordinate::new(height as f64, branch as f64, 0.0, 0.0))
+            .with_validator(producer.public_key().to_vec());
+        block.header.timestamp = timestamp;
+        block.header.spiral.complexity = MIN_SPIRAL_COMPLEXITY;
+        block.compute_merkle_root();
+        block.compute_spiral_root();
+        block.header.signature = producer.sign(block.hash().as_bytes());
+        block
+    }
+
+    fn transfers(&self, height: u64, timestamp: u64, branch: usize) -> Vec<Transaction> {
+        let TxPattern::Transfers(count) = self.builder.tx_pattern else {
+            return Vec::new();
+        };
+        let fork = fork_id(&self.builder.network, height);
+        (0..count)
+            .map(|index| {
+                let turn = height as usize * count + index + branch;
+                let sender = &self.accounts[turn % self.accounts.len()];
+                let recipient = &self.accounts[(turn + 1) % self.accounts.len()];
+                let mut tx = Transaction::new_at(
+                    sender.address(),
+                    recipient.address(),
+                    Amount::qbt(index as u64 + 1),
+                    Amount::new(MIN_TX_FEE),
+                    timestamp - index as u64 - 1,
+                )
+                .with_fork_id(fork);
+                tx.compute_hash();
+                sender.sign_transaction(&mut tx);
+                tx
+            })
+            .collect()
+    }
+}
+
+/// Hash of each block, for comparing chains in assertions
+pub fn block_hashes(blocks: &[Block]) -> Vec<Hash> {
+    blocks.iter().map(Block::hash).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chains_are_deterministic_and_valid() {
+        let build = || {
+            ChainBuilder::new("testnet")
+                .with_validators(2)
+                .with_tx_pattern(TxPattern::Transfers(3))
+                .build(4)
+        };
+        let chain = build();
+        assert_eq!(block_hashes(&chain.blocks), block_hashes(&build().blocks));
+        assert_eq!(chain.tip().header.block_height, 4);
+
+        for block in &chain.blocks[1..] {
+            block.validate(&crate::TESTNET_PARAMS).unwrap();
+            block.verify_signature().unwrap();
+            assert_eq!(block.transactions.len(), 4);
+            let sender = chain
+                .accounts()
+                .iter()
+                .find(|key| key.address() == block.transactions[1].from)
+                .unwrap();
+            block.transactions[1]
+                .verify_signature(&sender.public_key())
+                .unwrap();
+            block.transactions[1].verify_sender_signature().unwrap();
+        }
+        assert_ne!(
+            chain.block(1).header.producer_address(),
+            chain.block(2).header.producer_address()
+        );
+
+        let fork = chain.fork_at(2, 3, 1);
+        assert_eq!(fork[0].header.previous_block_hash, chain.block(2).hash());
+        assert_ne!(fork[0].hash(), chain.block(3).hash());
+        assert_ne!(
+            fork[0].header.producer_address(),
+            chain.block(3).header.producer_address()
+        );
+        fork[2].validate(&crate::TESTNET_PARAMS).unwrap();
+
+        let other = ChainBuilder::new("testnet").with_seed(1).build(1);
+        assert_ne!(other.tip().hash(), build().block(1).hash());
+
+        let mut tweaked = chain.tip().clone();
+        tweaked.header.state_root = Hash::new([1u8; 32]);
+        chain.resign(&mut tweaked);
+        tweaked.verify_signature().unwrap();
+    }
+}
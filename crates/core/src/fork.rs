@@ -0,0 +1,267 @@
+use crate::{
+    BlockLimits, Hash, PauseMultisig, Result, SpiraChainError, DEFAULT_BLOCK_LIMITS,
+    MAINNET_GENESIS_HASH, NO_PAUSE_MULTISIG, TESTNET_GENESIS_HASH,
+};
+
+/// Highest protocol version this binary can validate. Blocks above it are
+/// rejected with an upgrade notice instead of being followed blindly.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Protocol version of blocks before the first hard fork
+pub const GENESIS_PROTOCOL_VERSION: u32 = 1;
+
+/// Protocol version from which every block opens with a coinbase. Until it
+/// activates, blocks without one follow the rules of earlier releases: fees
+/// stay with their senders and the producer is credited the reward directly.
+pub const COINBASE_VERSION: u32 = 2;
+
+/// Protocol version from which the state root is the bucketed tree of account
+/// hashes. Earlier blocks commit to one flat hash over the sorted state entries.
+pub const STATE_TREE_VERSION: u32 = 2;
+
+/// Protocol version from which a block's transactions must be in canonical
+/// order, see [`crate::canonical_key`]
+pub const CANONICAL_ORDER_VERSION: u32 = 2;
+
+/// Consensus rule change activating at a fixed height
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HardFork {
+    pub name: &'static str,
+    /// Header version of every block from `height` on
+    pub version: u32,
+    pub height: u64,
+}
+
+/// Per-network consensus parameters. Rules that change in a hard fork are gated
+/// with [`ChainParams::is_active`] so every node switches at the same block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainParams {
+    pub network: &'static str,
+    pub genesis_hash: &'static str,
+    /// Ordered by height, versions strictly increasing
+    pub hard_forks: &'static [HardFork],
+    /// Block size and transaction count, from the genesis constants
+    pub block_limits: BlockLimits,
+    /// Guardians who can pause the chain during an exploit response
+    pub pause_multisig: PauseMultisig,
+}
+
+pub const TESTNET_PARAMS: ChainParams = ChainParams {
+    network: "testnet",
+    genesis_hash: TESTNET_GENESIS_HASH,
+    hard_forks: &[],
+    block_limits: DEFAULT_BLOCK_LIMITS,
+    pause_multisig: NO_PAUSE_MULTISIG,
+};
+
+pub const MAINNET_PARAMS: ChainParams = ChainParams {
+    network: "mainnet",
+    genesis_hash: MAINNET_GENESIS_HASH,
+    hard_forks: &[],
+    block_limits: DEFAULT_BLOCK_LIMITS,
+    pause_multisig: NO_PAUSE_MULTISIG,
+};
+
+impl ChainParams {
+    pub fn for_network(network: &str) -> &'static ChainParams {
+        match network {
+            "mainnet" => &MAINNET_PARAMS,
+            _ => &TESTNET_PARAMS,
+        }
+    }
+
+    /// Short id naming this chain on the wire: the network name plus the start of
+    /// its genesis hash, so two chains never share gossip topics or protocols
+    pub fn network_id(&self) -> String {
+        let genesis = self.genesis_hash.trim_start_matches("0x");
+        format!("{}-{}", self.network, &genesis[..genesis.len().min(8)])
+    }
+
+    /// Latest hard fork active at `height`
+    pub fn active_fork(&self, height: u64) -> Option<&HardFork> {
+        self.hard_forks
+            .iter()
+            .filter(|fork| fork.height <= height)
+            .max_by_key(|fork| fork.height)
+    }
+
+    pub fn protocol_version_at(&self, height: u64) -> u32 {
+        self.active_fork(height)
+            .map(|fork| fork.version)
+            .unwrap_or(GENESIS_PROTOCOL_VERSION)
+    }
+
+    /// Whether rules introduced in protocol `version` apply at `height`
+    pub fn is_active(&self, version: u32, height: u64) -> bool {
+        self.protocol_version_at(height) >= version
+    }
+
+    /// Height of the latest hard fork active at `height`, 0 if the chain never forked
+    pub fn last_hard_fork_height(&self, height: u64) -> u64 {
+        self.active_fork(height)
+            .map(|fork| fork.height)
+            .unwrap_or(0)
+    }
+
+    /// Next scheduled hard fork after `height`
+    pub fn next_fork(&self, height: u64) -> Option<&HardFork> {
+        self.hard_forks
+            .iter()
+            .filter(|fork| fork.height > height)
+            .min_by_key(|fork| fork.height)
+    }
+
+    /// Fork id that transactions included at `height` must be signed for
+    pub fn fork_id(&self, height: u64) -> Hash {
+        let genesis_hash = hex::decode(self.genesis_hash.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| Hash::from_slice(&bytes).ok())
+            .unwrap_or_else(Hash::zero);
+
+        compute_fork_id(&genesis_hash, self.last_hard_fork_height(height))
+    }
+
+    /// Check a block header version against the schedule
+    pub fn check_block_version(&self, height: u64, version: u64) -> Result<()> {
+        if version > PROTOCOL_VERSION as u64 {
+            return Err(SpiraChainError::UnsupportedProtocol(format!(
+                "block {} uses protocol v{}, this node supports up to v{}",
+                height, version, PROTOCOL_VERSION
+            )));
+        }
+
+        let expected = self.protocol_version_at(height);
+        if version != expected as u64 {
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "Block {} has version {}, expected {} at this height",
+                height, version, expected
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Schedule sanity: heights and versions strictly increasing, all known to this binary
+    pub fn validate(&self) -> Result<()> {
+        let mut previous = (0, GENESIS_PROTOCOL_VERSION);
+        for fork in self.hard_forks {
+            if fork.height <= previous.0 || fork.version <= previous.1 {
+                return Err(SpiraChainError::Config(format!(
+                    "Hard fork {} is out of order in the {} schedule",
+                    fork.name, self.network
+                )));
+            }
+            if fork.version > PROTOCOL_VERSION {
+                return Err(SpiraChainError::Config(format!(
+                    "Hard fork {} requires protocol v{}, binary supports v{}",
+                    fork.name, fork.version, PROTOCOL_VERSION
+                )));
+            }
+            previous = (fork.height, fork.version);
+        }
+
+        Ok(())
+    }
+}
+
+pub fn compute_fork_id(genesis_hash: &Hash, last_hard_fork_height: u64) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"spirachain-fork-id");
+    hasher.update(genesis_hash.as_bytes());
+    hasher.update(&last_hard_fork_height.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Latest hard fork active at `height` on `network`, 0 if the chain never forked
+pub fn last_hard_fork_height(network: &str, height: u64) -> u64 {
+    ChainParams::for_network(network).last_hard_fork_height(height)
+}
+
+/// Fork id that transactions included at `height` on `network` must be signed for
+pub fn fork_id(network: &str, height: u64) -> Hash {
+    ChainParams::for_network(network).fork_id(height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORKS: &[HardFork] = &[
+        HardFork {
+            name: "first",
+            version: 2,
+            height: 100,
+        },
+        HardFork {
+            name: "second",
+            version: 3,
+            height: 200,
+        },
+    ];
+
+    fn scheduled() -> ChainParams {
+        ChainParams {
+            hard_forks: FORKS,
+            ..TESTNET_PARAMS
+        }
+    }
+
+    #[test]
+    fn test_fork_id_separates_networks_and_forks() {
+        let genesis = Hash::new([1u8; 32]);
+        assert_eq!(compute_fork_id(&genesis, 0), compute_fork_id(&genesis, 0));
+        assert_ne!(compute_fork_id(&genesis, 0), compute_fork_id(&genesis, 100));
+        assert_ne!(
+            compute_fork_id(&genesis, 0),
+            compute_fork_id(&Hash::new([2u8; 32]), 0)
+        );
+
+        assert_ne!(fork_id("testnet", 0), fork_id("mainnet", 0));
+        assert_eq!(last_hard_fork_height("testnet", u64::MAX), 0);
+
+        let params = scheduled();
+        assert_eq!(params.fork_id(99), params.fork_id(0));
+        assert_ne!(params.fork_id(100), params.fork_id(99));
+    }
+
+    #[test]
+    fn test_rules_switch_at_activation_height() {
+        let params = scheduled();
+        assert_eq!(params.protocol_version_at(99), 1);
+        assert_eq!(params.protocol_version_at(100), 2);
+        assert_eq!(params.protocol_version_at(250), 3);
+        assert!(!params.is_active(2, 99));
+        assert!(params.is_active(2, 100));
+        assert_eq!(params.last_hard_fork_height(199), 100);
+        assert_eq!(params.next_fork(100).map(|fork| fork.name), Some("second"));
+
+        assert!(TESTNET_PARAMS.validate().is_ok());
+        assert!(MAINNET_PARAMS.validate().is_ok());
+    }
+
+    #[test]
+    fn test_network_id_separates_networks() {
+        assert_eq!(TESTNET_PARAMS.network_id(), "testnet-6d0e132a");
+        assert_ne!(TESTNET_PARAMS.network_id(), MAINNET_PARAMS.network_id());
+    }
+
+    #[test]
+    fn test_block_version_check() {
+        let params = TESTNET_PARAMS;
+        assert!(params.check_block_version(10, 1).is_ok());
+        assert!(matches!(
+            params.check_block_version(10, PROTOCOL_VERSION as u64 + 1),
+            Err(SpiraChainError::UnsupportedProtocol(_))
+        ));
+
+        let unordered = ChainParams {
+            hard_forks: &[HardFork {
+                name: "broken",
+                version: 1,
+                height: 10,
+            }],
+            ..TESTNET_PARAMS
+        };
+        assert!(unordered.validate().is_err());
+    }
+}
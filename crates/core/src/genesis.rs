@@ -45,11 +45,26 @@ pub struct GenesisConstants {
     pub phi_precision: usize,
     pub block_time_target: u64,
     pub max_block_size: usize,
+    #[serde(default = "default_max_tx_per_block")]
+    pub max_tx_per_block: usize,
     pub semantic_dimensions: usize,
     pub min_validator_stake: u128,
     pub qubitum_decimals: u8,
 }
 
+fn default_max_tx_per_block() -> usize {
+    crate::MAX_TX_PER_BLOCK
+}
+
+impl GenesisConstants {
+    pub fn block_limits(&self) -> crate::BlockLimits {
+        crate::BlockLimits {
+            max_block_size: self.max_block_size,
+            max_tx_per_block: self.max_tx_per_block,
+        }
+    }
+}
+
 impl Default for GenesisConfig {
     fn default() -> Self {
         Self {
@@ -71,6 +86,7 @@ impl Default for GenesisConfig {
                 phi_precision: crate::PHI_PRECISION,
                 block_time_target: crate::BLOCK_TIME_TARGET,
                 max_block_size: crate::MAX_BLOCK_SIZE,
+                max_tx_per_block: crate::MAX_TX_PER_BLOCK,
                 semantic_dimensions: crate::SEMANTIC_VECTOR_DIM,
                 min_validator_stake: crate::MIN_VALIDATOR_STAKE,
                 qubitum_decimals: crate::TOKEN_DECIMALS,
@@ -188,6 +204,8 @@ impl GenesisConfig {
             difficulty_target: u32::MAX / 1000,
             tx_count: 0,
             block_height: 0,
+            extra_data: Vec::new(),
+            event_bloom: Vec::new(),
         };
         
         let mut genesis_block = Block {
@@ -266,6 +284,11 @@ mod tests {
         assert_eq!(config.version, 1);
         assert_eq!(config.initial_validators.len(), 5);
         assert_eq!(config.genesis_transactions.len(), 6);
+
+        // Consensus enforces what genesis declares
+        for params in [crate::TESTNET_PARAMS, crate::MAINNET_PARAMS] {
+            assert_eq!(params.block_limits, config.constants.block_limits());
+        }
     }
 
     #[test]
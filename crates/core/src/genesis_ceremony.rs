@@ -0,0 +1,388 @@
+// Genesis ceremony
+// A multi-party launch builds its genesis from validator registrations: each
+// party signs its name, key, region and stake with its validator key and hands
+// the registration to the coordinator. Assembly sorts registrations by key, so
+// anyone holding the same set and template gets a byte-identical genesis, and
+// the resulting SignedGenesis commits to both the config hash and the genesis
+// block hash. Each party checks those against its own assembly and co-signs;
+// nodes only run a signed genesis once every genesis validator has signed it.
+
+use crate::{GenesisConfig, GenesisValidator, Hash, Result, SpiraChainError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+const REGISTRATION_DOMAIN: &[u8] = b"spirachain-genesis-registration";
+const CONFIG_HASH_DOMAIN: &[u8] = b"spirachain-genesis-config";
+const SIGNATURE_DOMAIN: &[u8] = b"spirachain-genesis-signature";
+
+pub const MAX_VALIDATOR_NAME_SIZE: usize = 64;
+pub const MAX_REGION_SIZE: usize = 64;
+
+/// A party's request to join the genesis validator set, signed with its validator key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorRegistration {
+    pub name: String,
+    /// Hex ed25519 public key
+    pub pubkey: String,
+    pub geographic_region: String,
+    pub stake: u128,
+    /// Hex signature over [`ValidatorRegistration::signing_message`]
+    pub signature: String,
+}
+
+fn decode_hex<const N: usize>(field: &str, value: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| SpiraChainError::CryptoError(format!("invalid {}: {}", field, e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| SpiraChainError::CryptoError(format!("{} must be {} bytes", field, N)))
+}
+
+fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &str) -> Result<()> {
+    let signature: [u8; 64] = decode_hex("signature", signature)?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(public_key)
+        .map_err(|_| SpiraChainError::InvalidSignature)?;
+    key.verify_strict(message, &ed25519_dalek::Signature::from_bytes(&signature))
+        .map_err(|_| SpiraChainError::InvalidSignature)
+}
+
+impl ValidatorRegistration {
+    /// Bytes the registering validator signs
+    pub fn signing_message(&self) -> Result<Vec<u8>> {
+        let pubkey: [u8; 32] = decode_hex("pubkey", &self.pubkey)?;
+        let mut message = REGISTRATION_DOMAIN.to_vec();
+        message.extend_from_slice(&(self.name.len() as u64).to_be_bytes());
+        message.extend_from_slice(self.name.as_bytes());
+        message.extend_from_slice(&pubkey);
+        message.extend_from_slice(&(self.geographic_region.len() as u64).to_be_bytes());
+        message.extend_from_slice(self.geographic_region.as_bytes());
+        message.extend_from_slice(&self.stake.to_be_bytes());
+        Ok(message)
+    }
+
+    /// Check the fields and signature; returns the validator it registers
+    pub fn verify(&self, min_stake: u128) -> Result<GenesisValidator> {
+        let invalid = |reason: String| {
+            SpiraChainError::Config(format!("Registration of {:?}: {}", self.name, reason))
+        };
+        if self.name.is_empty() || self.name.len() > MAX_VALIDATOR_NAME_SIZE {
+            return Err(invalid(format!(
+                "name must be 1-{} bytes",
+                MAX_VALIDATOR_NAME_SIZE
+            )));
+        }
+        if self.geographic_region.is_empty() || self.geographic_region.len() > MAX_REGION_SIZE {
+            return Err(invalid(format!(
+                "region must be 1-{} bytes",
+                MAX_REGION_SIZE
+            )));
+        }
+        if self.stake < min_stake {
+            return Err(invalid(format!(
+                "stake {} is below the minimum {}",
+                self.stake, min_stake
+            )));
+        }
+        let pubkey: [u8; 32] = decode_hex("pubkey", &self.pubkey)?;
+        verify_ed25519(&pubkey, &self.signing_message()?, &self.signature)
+            .map_err(|e| invalid(e.to_string()))?;
+
+        Ok(GenesisValidator {
+            name: self.name.clone(),
+            pubkey: pubkey.to_vec(),
+            geographic_region: self.geographic_region.clone(),
+            stake: self.stake,
+        })
+    }
+}
+
+/// `template` with its validator set replaced by `registrations`, ordered by
+/// key. Rejects invalid registrations and duplicate keys or names.
+pub fn assemble_genesis(
+    template: &GenesisConfig,
+    registrations: &[ValidatorRegistration],
+) -> Result<GenesisConfig> {
+    if registrations.is_empty() {
+        return Err(SpiraChainError::Config(
+            "A genesis needs at least one validator registration".to_string(),
+        ));
+    }
+
+    let mut validators = Vec::with_capacity(registrations.len());
+    let mut keys = BTreeSet::new();
+    let mut names = BTreeSet::new();
+    for registration in registrations {
+        let validator = registration.verify(template.constants.min_validator_stake)?;
+        if !keys.insert(validator.pubkey.clone()) {
+            return Err(SpiraChainError::Config(format!(
+                "Key {} registered twice",
+                registration.pubkey
+            )));
+        }
+        if !names.insert(validator.name.clone()) {
+            return Err(SpiraChainError::Config(format!(
+                "Validator name {:?} registered twice",
+                validator.name
+            )));
+        }
+        validators.push(validator);
+    }
+    validators.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+
+    let mut genesis = template.clone();
+    genesis.initial_validators = validators;
+    Ok(genesis)
+}
+
+impl GenesisConfig {
+    /// blake3 over the canonical JSON encoding: covers the validator set,
+    /// which the genesis block hash does not
+    pub fn config_hash(&self) -> Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(CONFIG_HASH_DOMAIN);
+        hasher.update(&serde_json::to_vec(self).unwrap_or_default());
+        Hash::from(hasher.finalize())
+    }
+}
+
+/// A genesis validator's approval of the assembled genesis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisSignature {
+    /// Hex ed25519 public key, one of the genesis validators
+    pub pubkey: String,
+    pub signature: String,
+}
+
+/// Assembled genesis with the hashes parties compare and their co-signatures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedGenesis {
+    pub genesis: GenesisConfig,
+    pub config_hash: Hash,
+    pub genesis_hash: Hash,
+    #[serde(default)]
+    pub signatures: Vec<GenesisSignature>,
+}
+
+impl SignedGenesis {
+    pub fn new(genesis: GenesisConfig) -> Self {
+        Self {
+            config_hash: genesis.config_hash(),
+            genesis_hash: genesis.create_genesis_block().hash(),
+            genesis,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Bytes every party signs, from hashes recomputed over the genesis
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut message = SIGNATURE_DOMAIN.to_vec();
+        message.extend_from_slice(self.genesis.config_hash().as_bytes());
+        message.extend_from_slice(self.genesis.create_genesis_block().hash().as_bytes());
+        message
+    }
+
+    /// Check that the recorded hashes match the genesis they describe
+    pub fn verify_hashes(&self) -> Result<()> {
+        if self.genesis.config_hash() != self.config_hash {
+            return Err(SpiraChainError::Config(format!(
+                "Genesis config hashes to {}, not the recorded {}",
+                self.genesis.config_hash(),
+                self.config_hash
+            )));
+        }
+        let genesis_hash = self.genesis.create_genesis_block().hash();
+        if genesis_hash != self.genesis_hash {
+            return Err(SpiraChainError::Config(format!(
+                "Genesis block hashes to {}, not the recorded {}",
+                genesis_hash, self.genesis_hash
+            )));
+        }
+        Ok(())
+    }
+
+    /// Add or replace the signature of genesis validator `pubkey`
+    pub fn add_signature(&mut self, pubkey: &[u8; 32], signature: &[u8]) -> Result<()> {
+        self.verify_hashes()?;
+        let signature = GenesisSignature {
+            pubkey: hex::encode(pubkey),
+            signature: hex::encode(signature),
+        };
+        self.verify_signature(&signature)?;
+        self.signatures
+            .retain(|existing| existing.pubkey.trim_start_matches("0x") != signature.pubkey);
+        self.signatures.push(signature);
+        self.signatures.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+        Ok(())
+    }
+
+    fn verify_signature(&self, signature: &GenesisSignature) -> Result<[u8; 32]> {
+        let pubkey: [u8; 32] = decode_hex("pubkey", &signature.pubkey)?;
+        if !self
+            .genesis
+            .initial_validators
+            .iter()
+            .any(|validator| validator.pubkey == pubkey)
+        {
+            return Err(SpiraChainError::Config(format!(
+                "{} is not a genesis validator",
+                signature.pubkey
+            )));
+        }
+        verify_ed25519(&pubkey, &self.signing_message(), &signature.signature)?;
+        Ok(pubkey)
+    }
+
+    /// Genesis validators that have not signed yet
+    pub fn missing_signers(&self) -> Vec<&GenesisValidator> {
+        let signed: BTreeSet<[u8; 32]> = self
+            .signatures
+            .iter()
+            .filter_map(|signature| self.verify_signature(signature).ok())
+            .collect();
+        self.genesis
+            .initial_validators
+            .iter()
+            .filter(|validator| {
+                <[u8; 32]>::try_from(validator.pubkey.as_slice())
+                    .map_or(true, |pubkey| !signed.contains(&pubkey))
+            })
+            .collect()
+    }
+
+    /// Hashes match and every genesis validator signed, each exactly once
+    pub fn verify(&self) -> Result<()> {
+        self.verify_hashes()?;
+        let mut signers = BTreeSet::new();
+        for signature in &self.signatures {
+            let pubkey = self.verify_signature(signature)?;
+            if !signers.insert(pubkey) {
+                return Err(SpiraChainError::Config(format!(
+                    "{} signed the genesis twice",
+                    signature.pubkey
+                )));
+            }
+        }
+        let missing = self.missing_signers();
+        if !missing.is_empty() {
+            return Err(SpiraChainError::Config(format!(
+                "Genesis is missing signatures from {}",
+                missing
+                    .iter()
+                    .map(|validator| validator.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn from_json(json: &str) -> std::result::Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn register(key: &SigningKey, name: &str, stake: u128) -> ValidatorRegistration {
+        let mut registration = ValidatorRegistration {
+            name: name.to_string(),
+            pubkey: hex::encode(key.verifying_key().to_bytes()),
+            geographic_region: "Europe".to_string(),
+            stake,
+            signature: String::new(),
+        };
+        registration.signature = hex::encode(
+            key.sign(&registration.signing_message().unwrap())
+                .to_bytes(),
+        );
+        registration
+    }
+
+    #[test]
+    fn test_assembly_is_deterministic() {
+        let template = GenesisConfig::default();
+        let stake = template.constants.min_validator_stake;
+        let keys = [
+            SigningKey::from_bytes(&[1; 32]),
+            SigningKey::from_bytes(&[2; 32]),
+        ];
+        let a = register(&keys[0], "a", stake);
+        let b = register(&keys[1], "b", stake);
+
+        let genesis = assemble_genesis(&template, &[a.clone(), b.clone()]).unwrap();
+        let reordered = assemble_genesis(&template, &[b.clone(), a.clone()]).unwrap();
+        assert_eq!(genesis.config_hash(), reordered.config_hash());
+        assert_eq!(genesis.initial_validators.len(), 2);
+        assert_ne!(genesis.config_hash(), template.config_hash());
+
+        let mut tampered = a.clone();
+        tampered.stake += 1;
+        assert!(assemble_genesis(&template, &[tampered]).is_err());
+        assert!(assemble_genesis(&template, &[a.clone(), a]).is_err());
+        assert!(assemble_genesis(&template, &[register(&keys[0], "low", stake - 1)]).is_err());
+    }
+
+    #[test]
+    fn test_every_validator_must_sign() {
+        let template = GenesisConfig::default();
+        let stake = template.constants.min_validator_stake;
+        let keys = [
+            SigningKey::from_bytes(&[1; 32]),
+            SigningKey::from_bytes(&[2; 32]),
+        ];
+        let genesis = assemble_genesis(
+            &template,
+            &[
+                register(&keys[0], "a", stake),
+                register(&keys[1], "b", stake),
+            ],
+        )
+        .unwrap();
+
+        let mut signed = SignedGenesis::new(genesis);
+        assert_eq!(signed.missing_signers().len(), 2);
+        assert!(signed.verify().is_err());
+
+        let outsider = SigningKey::from_bytes(&[9; 32]);
+        let message = signed.signing_message();
+        assert!(signed
+            .add_signature(
+                &outsider.verifying_key().to_bytes(),
+                &outsider.sign(&message).to_bytes()
+            )
+            .is_err());
+        assert!(signed
+            .add_signature(
+                &keys[0].verifying_key().to_bytes(),
+                &keys[1].sign(&message).to_bytes()
+            )
+            .is_err());
+
+        for key in &keys {
+            signed
+                .add_signature(
+                    &key.verifying_key().to_bytes(),
+                    &key.sign(&message).to_bytes(),
+                )
+                .unwrap();
+        }
+        signed.verify().unwrap();
+        let parsed = SignedGenesis::from_json(&signed.to_json()).unwrap();
+        parsed.verify().unwrap();
+
+        // Any change to the genesis voids the signatures
+        let mut altered = parsed;
+        altered.genesis.timestamp += 1;
+        assert!(altered.verify().is_err());
+        altered.config_hash = altered.genesis.config_hash();
+        altered.genesis_hash = altered.genesis.create_genesis_block().hash();
+        assert!(altered.verify().is_err());
+    }
+}
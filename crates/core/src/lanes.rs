@@ -0,0 +1,134 @@
+// Transaction priority lanes
+// Time-critical transactions must not be starved by fee competition. Each
+// priority lane gets block space reserved for it and its own mempool quota on
+// top of the normal mempool size; per-sender caps keep the lane from being used
+// to jump the queue. Guardian votes (pauses and semantic model approvals) are
+// the priority payloads today; misbehaviour evidence and other governance
+// payloads belong here as they land.
+
+use crate::{Result, SpiraChainError, Transaction, TransactionPayload};
+use serde::{Deserialize, Serialize};
+
+/// Lanes in packing order: every priority lane comes before `Standard`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionLane {
+    Governance,
+    Standard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaneLimits {
+    /// Block slots kept for the lane; slots it leaves unused go to standard transactions
+    pub reserved_txs: usize,
+    /// Lane transactions the mempool holds beyond its normal size
+    pub max_pending: usize,
+    pub max_pending_per_sender: usize,
+}
+
+pub const GOVERNANCE_LANE: LaneLimits = LaneLimits {
+    reserved_txs: 16,
+    max_pending: 128,
+    max_pending_per_sender: 2,
+};
+
+impl TransactionLane {
+    pub fn of(tx: &Transaction) -> Self {
+        match tx.payload {
+            TransactionPayload::EmergencyPause { .. }
+            | TransactionPayload::ApproveSemanticModel { .. } => TransactionLane::Governance,
+            _ => TransactionLane::Standard,
+        }
+    }
+
+    /// `None` for the standard lane, which competes on fees alone
+    pub fn limits(self) -> Option<&'static LaneLimits> {
+        match self {
+            TransactionLane::Governance => Some(&GOVERNANCE_LANE),
+            TransactionLane::Standard => None,
+        }
+    }
+}
+
+/// Whether the mempool may take `tx` beyond its normal size because its lane
+/// has room. Fails once the lane, or the sender's share of it, is full.
+pub fn lane_admission<'a>(
+    pending: impl IntoIterator<Item = &'a Transaction>,
+    tx: &Transaction,
+) -> Result<bool> {
+    let lane = TransactionLane::of(tx);
+    let Some(limits) = lane.limits() else {
+        return Ok(false);
+    };
+
+    let (in_lane, from_sender) = pending
+        .into_iter()
+        .filter(|pending| TransactionLane::of(pending) == lane)
+        .fold((0, 0), |(in_lane, from_sender), pending| {
+            (
+                in_lane + 1,
+                from_sender + usize::from(pending.from == tx.from),
+            )
+        });
+
+    if from_sender >= limits.max_pending_per_sender {
+        return Err(SpiraChainError::LaneLimit(format!(
+            "{} already has {} pending {:?} transactions",
+            tx.from, from_sender, lane
+        )));
+    }
+    if in_lane >= limits.max_pending {
+        return Err(SpiraChainError::LaneLimit(format!(
+            "{:?} lane is full",
+            lane
+        )));
+    }
+    Ok(true)
+}
+
+/// Move priority lane transactions to the front, otherwise keeping the order,
+/// so a cap on how many transactions a producer considers never cuts them
+pub fn prioritize_lanes(transactions: &mut [Transaction]) {
+    transactions.sort_by_key(TransactionLane::of);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Address, Amount};
+
+    fn vote(from: u8) -> Transaction {
+        let mut tx = Transaction::new(
+            Address::new([from; 32]),
+            Address::new([from; 32]),
+            Amount::zero(),
+            Amount::from_millis(1),
+        )
+        .with_payload(TransactionPayload::EmergencyPause { blocks: 10 });
+        tx.compute_hash();
+        tx
+    }
+
+    #[test]
+    fn test_lane_admission_and_ordering() {
+        let transfer = Transaction::new(
+            Address::new([1; 32]),
+            Address::new([2; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        );
+        assert!(!lane_admission(&[], &transfer).unwrap());
+        assert!(lane_admission(&[], &vote(1)).unwrap());
+
+        let pending = vec![vote(1), vote(1)];
+        assert!(matches!(
+            lane_admission(&pending, &vote(1)),
+            Err(SpiraChainError::LaneLimit(_))
+        ));
+        assert!(lane_admission(&pending, &vote(2)).unwrap());
+
+        let mut txs = vec![transfer.clone(), vote(3), transfer];
+        prioritize_lanes(&mut txs);
+        assert_eq!(TransactionLane::of(&txs[0]), TransactionLane::Governance);
+    }
+}
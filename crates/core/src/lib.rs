@@ -1,15 +1,55 @@
+pub mod account;
 pub mod block;
+pub mod bloom;
+pub mod chain_spec;
 pub mod constants;
+pub mod continuity;
+pub mod diversity;
+pub mod epoch_summary;
 pub mod error;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod fixtures;
+pub mod fork;
 pub mod genesis;
+pub mod genesis_ceremony;
+pub mod lanes;
+pub mod light;
+pub mod message;
+pub mod names;
+pub mod ordering;
+pub mod pause;
+pub mod semantic_model;
+pub mod signature_cache;
 pub mod spiral;
+pub mod spiral_registry;
 pub mod transaction;
 pub mod types;
+pub mod validator_events;
 
+pub use account::*;
 pub use block::*;
+pub use bloom::*;
+pub use chain_spec::*;
 pub use constants::*;
+pub use continuity::*;
+pub use diversity::*;
+pub use epoch_summary::*;
 pub use error::*;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub use fixtures::*;
+pub use fork::*;
 pub use genesis::*;
+pub use genesis_ceremony::*;
+pub use lanes::*;
+pub use light::*;
+pub use message::*;
+pub use names::*;
+pub use ordering::*;
+pub use pause::*;
+pub use semantic_model::*;
+pub use signature_cache::*;
 pub use spiral::*;
+pub use spiral_registry::*;
 pub use transaction::*;
 pub use types::*;
+pub use validator_events::*;
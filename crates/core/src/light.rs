@@ -0,0 +1,332 @@
+// Header verification for light clients
+// Only `core`, blake3 and ed25519-dalek are used here, no std and no allocation,
+// so embedded and wasm clients can lift this file out and verify header chains
+// and merkle proofs without the rest of the node. BlockHeader and MerkleProof
+// delegate to it, keeping one implementation of the rules.
+
+use core::fmt;
+
+pub type Digest = [u8; 32];
+
+/// Header version from which block hashes, transaction hashes and the
+/// transaction merkle tree are hashed in blake3's derive-key mode, each under
+/// its own context. Blocks switch to it at the hard fork that schedules it.
+pub const TAGGED_HASH_VERSION: u64 = 2;
+
+/// Derive-key contexts of the tagged hashes; never change one once it is used
+pub const BLOCK_HEADER_CONTEXT: &str = "spirachain 2025-01 block header";
+pub const TRANSACTION_CONTEXT: &str = "spirachain 2025-01 transaction";
+pub const MERKLE_LEAF_CONTEXT: &str = "spirachain 2025-01 merkle leaf";
+pub const MERKLE_NODE_CONTEXT: &str = "spirachain 2025-01 merkle node";
+
+/// Plain blake3 before `version` reaches [`TAGGED_HASH_VERSION`], derive-key
+/// mode under `context` from then on
+pub fn versioned_hasher(version: u64, context: &str) -> blake3::Hasher {
+    if version >= TAGGED_HASH_VERSION {
+        blake3::Hasher::new_derive_key(context)
+    } else {
+        blake3::Hasher::new()
+    }
+}
+
+/// Merkle leaf of a transaction: the transaction hash itself in untagged blocks
+pub fn merkle_leaf(tx_hash: &Digest, version: u64) -> Digest {
+    if version >= TAGGED_HASH_VERSION {
+        blake3::derive_key(MERKLE_LEAF_CONTEXT, tx_hash)
+    } else {
+        *tx_hash
+    }
+}
+
+/// Parent of two merkle nodes
+pub fn merkle_node(left: &Digest, right: &Digest, version: u64) -> Digest {
+    let mut hasher = versioned_hasher(version, MERKLE_NODE_CONTEXT);
+    hasher.update(left);
+    hasher.update(right);
+    *hasher.finalize().as_bytes()
+}
+
+/// Borrowed view of the header fields light verification needs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeaderView<'a> {
+    pub version: u64,
+    pub previous_block_hash: &'a Digest,
+    pub merkle_root: &'a Digest,
+    pub spiral_root: &'a Digest,
+    pub state_root: &'a Digest,
+    pub timestamp: u64,
+    /// π coordinates as (x, y, z, t)
+    pub pi_coordinates: [f64; 4],
+    pub nonce: u64,
+    pub difficulty_target: u32,
+    pub block_height: u64,
+    pub extra_data: &'a [u8],
+    pub event_bloom: &'a [u8],
+    pub validator_pubkey: &'a [u8],
+    pub signature: &'a [u8],
+    pub spiral_complexity: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightError {
+    /// Header does not point at the hash of the header before it
+    BrokenLink {
+        height: u64,
+    },
+    /// Heights are not consecutive
+    HeightGap {
+        expected: u64,
+        got: u64,
+    },
+    BadSignature {
+        height: u64,
+    },
+    InsufficientWork {
+        height: u64,
+    },
+    /// Spiral complexity fell below 80% of the parent's
+    ComplexityDrop {
+        height: u64,
+    },
+    /// π coordinates moved further than the allowed jump
+    SpiralJump {
+        height: u64,
+    },
+}
+
+impl fmt::Display for LightError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LightError::BrokenLink { height } => {
+                write!(f, "Header {} does not extend its parent", height)
+            }
+            LightError::HeightGap { expected, got } => {
+                write!(f, "Expected header {}, got {}", expected, got)
+            }
+            LightError::BadSignature { height } => {
+                write!(f, "Header {} has an invalid producer signature", height)
+            }
+            LightError::InsufficientWork { height } => {
+                write!(f, "Header {} does not meet its difficulty target", height)
+            }
+            LightError::ComplexityDrop { height } => {
+                write!(f, "Spiral complexity decreased too much at {}", height)
+            }
+            LightError::SpiralJump { height } => write!(f, "Spiral jump too large at {}", height),
+        }
+    }
+}
+
+impl HeaderView<'_> {
+    /// Block hash, the message producers sign
+    pub fn hash(&self) -> Digest {
+        self.hash_with(versioned_hasher(self.version, BLOCK_HEADER_CONTEXT))
+    }
+
+    /// Header hash under [`BLOCK_HEADER_CONTEXT`] whatever the version; the block
+    /// hash from [`TAGGED_HASH_VERSION`] on
+    pub fn tagged_hash(&self) -> Digest {
+        self.hash_with(blake3::Hasher::new_derive_key(BLOCK_HEADER_CONTEXT))
+    }
+
+    fn hash_with(&self, mut hasher: blake3::Hasher) -> Digest {
+        hasher.update(&self.version.to_be_bytes());
+        hasher.update(self.previous_block_hash);
+        hasher.update(self.merkle_root);
+        hasher.update(self.spiral_root);
+        hasher.update(self.state_root);
+        hasher.update(&self.timestamp.to_be_bytes());
+        for coordinate in self.pi_coordinates {
+            hasher.update(&coordinate.to_be_bytes());
+        }
+        hasher.update(&self.nonce.to_be_bytes());
+        hasher.update(&self.difficulty_target.to_be_bytes());
+        hasher.update(&self.block_height.to_be_bytes());
+        // Only committed when present so blocks without extra data keep their hash
+        if !self.extra_data.is_empty() {
+            hasher.update(&(self.extra_data.len() as u64).to_be_bytes());
+            hasher.update(self.extra_data);
+        }
+        if !self.event_bloom.is_empty() {
+            hasher.update(self.event_bloom);
+        }
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Ed25519 signature of the producer over [`Self::hash`]
+    pub fn verify_signature(&self) -> Result<(), LightError> {
+        let bad = LightError::BadSignature {
+            height: self.block_height,
+        };
+        let pubkey: &[u8; 32] = self.validator_pubkey.try_into().map_err(|_| bad)?;
+        let signature: &[u8; 64] = self.signature.try_into().map_err(|_| bad)?;
+
+        let key = ed25519_dalek::VerifyingKey::from_bytes(pubkey).map_err(|_| bad)?;
+        let signature = ed25519_dalek::Signature::from_bytes(signature);
+        key.verify_strict(&self.hash(), &signature).map_err(|_| bad)
+    }
+
+    /// blake3(spiral_root ‖ nonce) must start below the difficulty target
+    pub fn verify_work(&self) -> Result<(), LightError> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.spiral_root);
+        hasher.update(&self.nonce.to_be_bytes());
+        let hash = hasher.finalize();
+        let bytes = hash.as_bytes();
+
+        if u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) < self.difficulty_target {
+            Ok(())
+        } else {
+            Err(LightError::InsufficientWork {
+                height: self.block_height,
+            })
+        }
+    }
+}
+
+/// `child` extends `parent`: hash link, consecutive height and spiral continuity
+pub fn verify_link(
+    parent: &HeaderView,
+    child: &HeaderView,
+    max_spiral_jump: f64,
+) -> Result<(), LightError> {
+    let height = child.block_height;
+    if height != parent.block_height.wrapping_add(1) {
+        return Err(LightError::HeightGap {
+            expected: parent.block_height.wrapping_add(1),
+            got: height,
+        });
+    }
+    if *child.previous_block_hash != parent.hash() {
+        return Err(LightError::BrokenLink { height });
+    }
+    verify_continuity(parent, child, max_spiral_jump)
+}
+
+/// Spiral complexity may drop at most 20% and π coordinates may move at most
+/// `max_spiral_jump` between consecutive blocks. Distances are compared squared,
+/// so no square root is needed.
+pub fn verify_continuity(
+    parent: &HeaderView,
+    child: &HeaderView,
+    max_spiral_jump: f64,
+) -> Result<(), LightError> {
+    check_continuity(
+        child.block_height,
+        parent.spiral_complexity,
+        child.spiral_complexity,
+        squared_jump(parent, child),
+        max_spiral_jump,
+    )
+}
+
+/// Squared distance between the π coordinates of two headers
+pub fn squared_jump(parent: &HeaderView, child: &HeaderView) -> f64 {
+    child
+        .pi_coordinates
+        .iter()
+        .zip(parent.pi_coordinates.iter())
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum()
+}
+
+/// The rule of [`verify_continuity`] on values already extracted from the
+/// headers, as carried by a continuity proof
+pub fn check_continuity(
+    height: u64,
+    parent_complexity: f64,
+    child_complexity: f64,
+    squared_jump: f64,
+    max_spiral_jump: f64,
+) -> Result<(), LightError> {
+    if child_complexity < parent_complexity * 0.8 {
+        return Err(LightError::ComplexityDrop { height });
+    }
+    // Coordinates that overflow are not comparable and pass, as in full validation
+    if squared_jump.is_finite() && squared_jump > max_spiral_jump * max_spiral_jump {
+        return Err(LightError::SpiralJump { height });
+    }
+    Ok(())
+}
+
+/// Check `headers` in order, each signed, linked to the previous and continuous
+pub fn verify_header_chain(headers: &[HeaderView], max_spiral_jump: f64) -> Result<(), LightError> {
+    for (index, header) in headers.iter().enumerate() {
+        header.verify_signature()?;
+        if let Some(parent) = index.checked_sub(1).map(|parent| &headers[parent]) {
+            verify_link(parent, header, max_spiral_jump)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the transaction `tx_hash` at `index` hashes up to `root` through
+/// `siblings`, leaf level first, in a block of header `version`
+pub fn verify_merkle_path<'a>(
+    tx_hash: &Digest,
+    index: u32,
+    siblings: impl IntoIterator<Item = &'a Digest>,
+    root: &Digest,
+    version: u64,
+) -> bool {
+    let mut index = index;
+    let mut current = merkle_leaf(tx_hash, version);
+    for sibling in siblings {
+        current = if index.is_multiple_of(2) {
+            merkle_node(&current, sibling, version)
+        } else {
+            merkle_node(sibling, &current, version)
+        };
+        index /= 2;
+    }
+    current == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockHeader, Hash, PiCoordinate};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn signed_chain(length: u64) -> Vec<BlockHeader> {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut previous = Hash::zero();
+        (0..length)
+            .map(|height| {
+                let mut header = BlockHeader::new(previous, height);
+                header.pi_coordinates = PiCoordinate::new(height as f64, 0.0, 0.0, 0.0);
+                header.validator_pubkey = key.verifying_key().to_bytes().to_vec();
+                header.signature = key.sign(header.hash().as_bytes()).to_bytes().to_vec();
+                previous = header.hash();
+                header
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_header_chain_verification() {
+        let mut headers = signed_chain(4);
+        let views: Vec<_> = headers.iter().map(BlockHeader::view).collect();
+        assert_eq!(verify_header_chain(&views, 10.0), Ok(()));
+        assert_eq!(
+            verify_header_chain(&views, 0.5),
+            Err(LightError::SpiralJump { height: 1 })
+        );
+
+        headers[2].signature[0] ^= 1;
+        let views: Vec<_> = headers.iter().map(BlockHeader::view).collect();
+        assert_eq!(
+            verify_header_chain(&views, 10.0),
+            Err(LightError::BadSignature { height: 2 })
+        );
+
+        let views = [headers[0].view(), headers[3].view()];
+        assert_eq!(
+            verify_link(&views[0], &views[1], 10.0),
+            Err(LightError::HeightGap {
+                expected: 1,
+                got: 3
+            })
+        );
+    }
+}
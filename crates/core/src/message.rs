@@ -0,0 +1,121 @@
+// Signed messages
+// Wallets prove they own an address off chain (logins, ownership proofs) by
+// signing SIGNED_MESSAGE_PREFIX ‖ decimal byte length ‖ message with Ed25519.
+// Transactions sign `spirachain-tx ‖ …` and blocks a bare 32-byte hash, so a
+// signed message can never be replayed as either.
+
+use crate::{Address, Result, SpiraChainError};
+use serde::{Deserialize, Serialize};
+
+pub const SIGNED_MESSAGE_PREFIX: &[u8] = b"\x19SpiraChain Signed Message:\n";
+
+/// Bytes actually signed for `message`
+pub fn signed_message_preimage(message: &[u8]) -> Vec<u8> {
+    let length = message.len().to_string();
+    let mut preimage =
+        Vec::with_capacity(SIGNED_MESSAGE_PREFIX.len() + length.len() + message.len());
+    preimage.extend_from_slice(SIGNED_MESSAGE_PREFIX);
+    preimage.extend_from_slice(length.as_bytes());
+    preimage.extend_from_slice(message);
+    preimage
+}
+
+/// Ed25519 signatures do not reveal the key, so the public key travels with the
+/// signature and must hash to `address`
+pub fn verify_signed_message(
+    address: &Address,
+    message: &[u8],
+    public_key: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    let public_key: &[u8; 32] = public_key
+        .try_into()
+        .map_err(|_| SpiraChainError::CryptoError("public key must be 32 bytes".to_string()))?;
+    if Address::new(*blake3::hash(public_key).as_bytes()) != *address {
+        return Err(SpiraChainError::CryptoError(format!(
+            "public key does not belong to {}",
+            address
+        )));
+    }
+
+    let signature: &[u8; 64] = signature
+        .try_into()
+        .map_err(|_| SpiraChainError::InvalidSignature)?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(public_key)
+        .map_err(|_| SpiraChainError::InvalidSignature)?;
+    key.verify_strict(
+        &signed_message_preimage(message),
+        &ed25519_dalek::Signature::from_bytes(signature),
+    )
+    .map_err(|_| SpiraChainError::InvalidSignature)
+}
+
+/// JSON form produced by `spira wallet sign-message` and accepted by
+/// `/message/verify`; keys and signature are hex
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedMessage {
+    pub address: String,
+    pub public_key: String,
+    pub message: String,
+    pub signature: String,
+}
+
+impl SignedMessage {
+    pub fn verify(&self) -> Result<Address> {
+        let address: Address = self
+            .address
+            .parse()
+            .map_err(|e| SpiraChainError::CryptoError(format!("invalid address: {}", e)))?;
+        let decode = |field: &str, value: &str| {
+            hex::decode(value.trim_start_matches("0x"))
+                .map_err(|e| SpiraChainError::CryptoError(format!("invalid {}: {}", field, e)))
+        };
+
+        verify_signed_message(
+            &address,
+            self.message.as_bytes(),
+            &decode("public key", &self.public_key)?,
+            &decode("signature", &self.signature)?,
+        )?;
+        Ok(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn test_signed_message_roundtrip() {
+        assert_eq!(
+            signed_message_preimage(b"hi"),
+            b"\x19SpiraChain Signed Message:\n2hi".to_vec()
+        );
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        let signed = SignedMessage {
+            address: Address::new(*blake3::hash(&public_key).as_bytes()).to_string(),
+            public_key: hex::encode(public_key),
+            message: "login:42".to_string(),
+            signature: hex::encode(key.sign(&signed_message_preimage(b"login:42")).to_bytes()),
+        };
+        assert!(signed.verify().is_ok());
+
+        let raw = SignedMessage {
+            signature: hex::encode(key.sign(b"login:42").to_bytes()),
+            ..signed.clone()
+        };
+        assert!(matches!(
+            raw.verify(),
+            Err(SpiraChainError::InvalidSignature)
+        ));
+
+        let other = SignedMessage {
+            address: Address::new([1; 32]).to_string(),
+            ..signed
+        };
+        assert!(other.verify().is_err());
+    }
+}
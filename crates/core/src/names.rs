@@ -0,0 +1,205 @@
+// Name registry
+// Human-readable `<name>.spira` names resolving to addresses, bought with a
+// registration fee for NAME_REGISTRATION_PERIOD blocks. The owner renews or
+// re-points a name by registering it again and may transfer it; once a
+// registration lapses anyone can register the name.
+
+use crate::{Address, Result, SpiraChainError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Suffix that marks a name wherever an address is expected
+pub const NAME_SUFFIX: &str = ".spira";
+
+pub const MIN_NAME_SIZE: usize = 3;
+pub const MAX_NAME_SIZE: usize = 32;
+
+/// Blocks a registration or renewal lasts (~1 year at the 30s block target)
+pub const NAME_REGISTRATION_PERIOD: u64 = 1_051_200;
+
+/// Minimum fee of a registration or renewal (0.1 QBT)
+pub const NAME_REGISTRATION_FEE: u128 = 100_000_000_000_000_000;
+
+/// Names as stored on chain: lowercase `[a-z0-9-]`, no leading or trailing
+/// hyphen, without the `.spira` suffix
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = (MIN_NAME_SIZE..=MAX_NAME_SIZE).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !name.starts_with('-')
+        && !name.ends_with('-');
+    if !valid {
+        return Err(SpiraChainError::InvalidTransaction(format!(
+            "Invalid name {:?}: {}-{} characters of a-z, 0-9 and inner hyphens",
+            name, MIN_NAME_SIZE, MAX_NAME_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// Registry key for user input such as `Alice.spira`
+pub fn normalize_name(input: &str) -> Result<String> {
+    let name = input.trim().to_ascii_lowercase();
+    let name = name.strip_suffix(NAME_SUFFIX).unwrap_or(&name).to_string();
+    validate_name(&name)?;
+    Ok(name)
+}
+
+/// Whether `input` names an account rather than spelling out an address
+pub fn is_name(input: &str) -> bool {
+    input.trim().to_ascii_lowercase().ends_with(NAME_SUFFIX)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NameRecord {
+    pub owner: Address,
+    /// Address the name resolves to
+    pub target: Address,
+    pub registered_at: u64,
+    /// First height at which the name no longer resolves
+    pub expires_at: u64,
+}
+
+impl NameRecord {
+    pub fn is_active(&self, height: u64) -> bool {
+        height < self.expires_at
+    }
+}
+
+/// Names registered through `RegisterName` transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NameRegistry {
+    names: BTreeMap<String, NameRecord>,
+}
+
+impl NameRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `name` for `owner`, or renew and re-point it when `owner`
+    /// already holds it. Returns the new expiry height.
+    pub fn register(
+        &mut self,
+        name: &str,
+        owner: Address,
+        target: Address,
+        height: u64,
+    ) -> Result<u64> {
+        validate_name(name)?;
+        let record = match self.names.get(name) {
+            Some(record) if record.is_active(height) && record.owner != owner => {
+                return Err(SpiraChainError::InvalidTransaction(format!(
+                    "Name {}{} is registered to {} until block {}",
+                    name, NAME_SUFFIX, record.owner, record.expires_at
+                )));
+            }
+            Some(record) if record.is_active(height) => NameRecord {
+                target,
+                expires_at: record.expires_at.saturating_add(NAME_REGISTRATION_PERIOD),
+                ..record.clone()
+            },
+            _ => NameRecord {
+                owner,
+                target,
+                registered_at: height,
+                expires_at: height.saturating_add(NAME_REGISTRATION_PERIOD),
+            },
+        };
+
+        let expires_at = record.expires_at;
+        self.names.insert(name.to_string(), record);
+        Ok(expires_at)
+    }
+
+    /// Hand `name` to `new_owner`, who also becomes its target
+    pub fn transfer(
+        &mut self,
+        name: &str,
+        owner: Address,
+        new_owner: Address,
+        height: u64,
+    ) -> Result<()> {
+        match self.names.get_mut(name) {
+            Some(record) if record.is_active(height) && record.owner == owner => {
+                record.owner = new_owner;
+                record.target = new_owner;
+                Ok(())
+            }
+            _ => Err(SpiraChainError::InvalidTransaction(format!(
+                "{} does not own {}{}",
+                owner, name, NAME_SUFFIX
+            ))),
+        }
+    }
+
+    /// Record of `name` while its registration is active
+    pub fn resolve(&self, name: &str, height: u64) -> Option<&NameRecord> {
+        self.names
+            .get(name)
+            .filter(|record| record.is_active(height))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &NameRecord)> {
+        self.names.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Stable text for the state root; empty while nothing is registered
+    pub fn state_entry(&self) -> String {
+        self.names
+            .iter()
+            .map(|(name, record)| {
+                format!(
+                    "name:{}:{}:{}:{}",
+                    name, record.owner, record.target, record.expires_at
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_renew_transfer_and_expiry() {
+        assert_eq!(normalize_name(" Alice.spira").unwrap(), "alice");
+        assert!(normalize_name("-alice").is_err());
+        assert!(normalize_name("al").is_err());
+        assert!(validate_name("Alice").is_err());
+        assert!(is_name("alice.SPIRA"));
+
+        let alice = Address::new([1u8; 32]);
+        let bob = Address::new([2u8; 32]);
+        let mut names = NameRegistry::new();
+
+        let expires = names.register("alice", alice, alice, 10).unwrap();
+        assert_eq!(expires, 10 + NAME_REGISTRATION_PERIOD);
+        assert!(names.register("alice", bob, bob, 11).is_err());
+
+        // Renewal extends the current registration and may re-point it
+        let renewed = names.register("alice", alice, bob, 12).unwrap();
+        assert_eq!(renewed, expires + NAME_REGISTRATION_PERIOD);
+        assert_eq!(names.resolve("alice", 12).unwrap().target, bob);
+
+        assert!(names.transfer("alice", bob, bob, 13).is_err());
+        names.transfer("alice", alice, bob, 13).unwrap();
+        assert_eq!(names.resolve("alice", 13).unwrap().owner, bob);
+
+        // Lapsed names resolve to nothing and are up for grabs
+        assert!(names.resolve("alice", renewed).is_none());
+        names.register("alice", alice, alice, renewed).unwrap();
+        assert_eq!(names.resolve("alice", renewed).unwrap().owner, alice);
+    }
+}
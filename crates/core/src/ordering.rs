@@ -0,0 +1,88 @@
+// Canonical transaction order
+// Transactions are applied in block order, so the order decides the state
+// each one sees and with it the state root. It is a consensus rule rather
+// than whatever the producer's mempool yielded: the coinbase, then the epoch
+// summary when there is one, then every other transaction by sender, the
+// sender's nonce and finally the transaction hash. Keeping a sender's
+// transactions in nonce order applies them in the order they were signed,
+// which contract deployments depend on; unlike timestamps the nonce is not
+// the sender's clock. Legacy transactions carry no nonce and sort first.
+// Blocks are checked against this order from `CANONICAL_ORDER_VERSION` on.
+
+use crate::{Address, Hash, Result, SpiraChainError, Transaction};
+
+/// Position of a non-protocol transaction among the others in its block
+pub fn canonical_key(tx: &Transaction) -> (Address, Option<u64>, Hash) {
+    (tx.from, tx.nonce, tx.tx_hash)
+}
+
+/// Put non-protocol transactions in canonical order
+pub fn sort_canonical(transactions: &mut [Transaction]) {
+    transactions.sort_by_key(canonical_key);
+}
+
+/// Check the transactions after the protocol ones are in canonical order;
+/// equal keys only occur for duplicates, which are rejected elsewhere
+pub fn validate_canonical_order(transactions: &[Transaction]) -> Result<()> {
+    let ordinary = transactions
+        .iter()
+        .skip_while(|tx| tx.is_protocol())
+        .collect::<Vec<_>>();
+    match ordinary
+        .windows(2)
+        .position(|pair| canonical_key(pair[0]) > canonical_key(pair[1]))
+    {
+        Some(index) => Err(SpiraChainError::InvalidBlock(format!(
+            "Transaction {} is out of canonical order",
+            ordinary[index + 1].tx_hash
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Amount;
+
+    fn transfer(from: u8, nonce: u64, timestamp: u64) -> Transaction {
+        let mut tx = Transaction::new_at(
+            Address::new([from; 32]),
+            Address::new([9u8; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+            timestamp,
+        );
+        tx.nonce = Some(nonce);
+        tx.compute_hash();
+        tx
+    }
+
+    #[test]
+    fn test_canonical_order() {
+        let mut coinbase = Transaction::new_coinbase(Address::new([7u8; 32]), Amount::qbt(10), 1);
+        coinbase.compute_hash();
+
+        // The sender's clock does not decide its order
+        let mut ordinary = vec![transfer(2, 0, 5), transfer(1, 1, 3), transfer(1, 0, 9)];
+        let mut block = vec![coinbase.clone()];
+        block.extend(ordinary.iter().cloned());
+        assert!(validate_canonical_order(&block).is_err());
+
+        sort_canonical(&mut ordinary);
+        assert_eq!(
+            ordinary
+                .iter()
+                .map(|tx| (tx.from, tx.nonce))
+                .collect::<Vec<_>>(),
+            vec![
+                (Address::new([1u8; 32]), Some(0)),
+                (Address::new([1u8; 32]), Some(1)),
+                (Address::new([2u8; 32]), Some(0)),
+            ]
+        );
+        let mut block = vec![coinbase];
+        block.extend(ordinary);
+        assert!(validate_canonical_order(&block).is_ok());
+    }
+}
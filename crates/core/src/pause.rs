@@ -0,0 +1,199 @@
+use crate::{Address, Result, SpiraChainError, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest pause a single vote can ask for (~24h at the 30s block target), so
+/// a compromised multisig cannot halt the chain for good
+pub const MAX_PAUSE_BLOCKS: u64 = 2_880;
+
+/// Guardian votes older than this many blocks no longer count toward a pause
+pub const PAUSE_VOTE_WINDOW: u64 = 120;
+
+/// Guardians allowed to pause the chain during an exploit response, as hex
+/// addresses. `threshold` matching votes activate or lift a pause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PauseMultisig {
+    pub guardians: &'static [&'static str],
+    pub threshold: usize,
+}
+
+/// No guardians: the chain cannot be paused
+pub const NO_PAUSE_MULTISIG: PauseMultisig = PauseMultisig {
+    guardians: &[],
+    threshold: 0,
+};
+
+impl PauseMultisig {
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > 0 && self.guardians.len() >= self.threshold
+    }
+
+    pub fn is_guardian(&self, address: &Address) -> bool {
+        self.guardians
+            .iter()
+            .filter_map(|guardian| guardian.parse::<Address>().ok())
+            .any(|guardian| guardian == *address)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PauseVote {
+    /// Requested pause length, 0 to lift the current pause
+    pub blocks: u64,
+    pub height: u64,
+}
+
+/// On-chain pause: guardian votes collected so far and the block the current
+/// pause expires after, if any
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PauseState {
+    votes: BTreeMap<Address, PauseVote>,
+    paused_until: Option<u64>,
+}
+
+impl PauseState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Last paused block, while the pause has not expired at `height`
+    pub fn paused_until(&self, height: u64) -> Option<u64> {
+        self.paused_until.filter(|until| height <= *until)
+    }
+
+    pub fn is_paused(&self, height: u64) -> bool {
+        self.paused_until(height).is_some()
+    }
+
+    /// Votes still inside the window at `height`
+    pub fn votes(&self, height: u64) -> impl Iterator<Item = (&Address, &PauseVote)> {
+        self.votes
+            .iter()
+            .filter(move |(_, vote)| height.saturating_sub(vote.height) < PAUSE_VOTE_WINDOW)
+    }
+
+    /// Whether `tx` may execute at `height`. Only the coinbase and guardian votes
+    /// go through while the chain is paused.
+    pub fn check_transaction(&self, tx: &Transaction, height: u64) -> Result<()> {
+        match self.paused_until(height) {
+            Some(until) if !tx.payload.runs_while_paused() => {
+                Err(SpiraChainError::InvalidTransaction(format!(
+                    "Chain is paused until block {}",
+                    until
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Record `guardian`'s vote at `height`. Once `threshold` guardians agree on
+    /// the same length the pause starts (or is lifted) and the votes are cleared.
+    /// Returns whether the pause changed.
+    pub fn vote(
+        &mut self,
+        multisig: &PauseMultisig,
+        guardian: Address,
+        blocks: u64,
+        height: u64,
+    ) -> Result<bool> {
+        if !multisig.is_enabled() || !multisig.is_guardian(&guardian) {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "{} is not a pause guardian",
+                guardian
+            )));
+        }
+        if blocks > MAX_PAUSE_BLOCKS {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Pause too long: {} > {} blocks",
+                blocks, MAX_PAUSE_BLOCKS
+            )));
+        }
+
+        self.votes
+            .retain(|_, vote| height.saturating_sub(vote.height) < PAUSE_VOTE_WINDOW);
+        self.votes.insert(guardian, PauseVote { blocks, height });
+
+        let agreeing = self
+            .votes
+            .values()
+            .filter(|vote| vote.blocks == blocks)
+            .count();
+        if agreeing < multisig.threshold {
+            return Ok(false);
+        }
+
+        self.paused_until = (blocks > 0).then(|| height + blocks);
+        self.votes.clear();
+        Ok(true)
+    }
+
+    /// Stable text for the state root; empty while there is nothing to commit to
+    pub fn state_entry(&self, height: u64) -> String {
+        let mut entry = String::new();
+        if let Some(until) = self.paused_until(height) {
+            entry.push_str(&format!("pause:{}", until));
+        }
+        for (guardian, vote) in self.votes(height) {
+            entry.push_str(&format!(
+                ":vote:{}:{}:{}",
+                guardian, vote.blocks, vote.height
+            ));
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUARDIANS: PauseMultisig = PauseMultisig {
+        guardians: &[
+            "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "0x0202020202020202020202020202020202020202020202020202020202020202",
+            "0x0303030303030303030303030303030303030303030303030303030303030303",
+        ],
+        threshold: 2,
+    };
+
+    fn guardian(index: u8) -> Address {
+        Address::new([index; 32])
+    }
+
+    #[test]
+    fn test_pause_needs_threshold_and_expires() {
+        let mut pause = PauseState::new();
+        assert!(pause.vote(&GUARDIANS, guardian(9), 10, 100).is_err());
+        assert!(pause
+            .vote(&GUARDIANS, guardian(1), MAX_PAUSE_BLOCKS + 1, 100)
+            .is_err());
+
+        assert!(!pause.vote(&GUARDIANS, guardian(1), 10, 100).unwrap());
+        assert!(!pause.vote(&GUARDIANS, guardian(2), 20, 101).unwrap());
+        assert!(!pause.is_paused(102));
+        assert!(pause.vote(&GUARDIANS, guardian(3), 10, 102).unwrap());
+
+        assert!(pause.is_paused(103));
+        assert_eq!(pause.paused_until(103), Some(112));
+        assert!(!pause.is_paused(113));
+
+        let transfer = Transaction::new(
+            guardian(1),
+            guardian(2),
+            crate::Amount::new(1),
+            crate::Amount::zero(),
+        );
+        assert!(pause.check_transaction(&transfer, 105).is_err());
+        assert!(pause.check_transaction(&transfer, 113).is_ok());
+    }
+
+    #[test]
+    fn test_stale_votes_do_not_count() {
+        let mut pause = PauseState::new();
+        pause.vote(&GUARDIANS, guardian(1), 10, 100).unwrap();
+        assert!(!pause
+            .vote(&GUARDIANS, guardian(2), 10, 100 + PAUSE_VOTE_WINDOW)
+            .unwrap());
+        assert!(!pause.is_paused(100 + PAUSE_VOTE_WINDOW));
+    }
+}
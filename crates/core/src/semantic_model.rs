@@ -0,0 +1,301 @@
+// Semantic model commitments
+// Coherence scores are only comparable between validators that embed purposes
+// with the same model. Pause guardians approve the embedding model on chain:
+// once `threshold` of them vote for the same name, version and weights hash,
+// that model applies from the next diversity epoch on. Validators compare it
+// with the model they run before scoring transactions and fall back to a
+// neutral score when the two differ, so a node with the wrong model does not
+// skew block contents. Before the first approval every node scores with its
+// own model, as it always did.
+
+use crate::{diversity_epoch, Address, Hash, PauseMultisig, Result, SpiraChainError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const MAX_MODEL_NAME_SIZE: usize = 64;
+pub const MAX_MODEL_VERSION_SIZE: usize = 32;
+
+/// Guardian votes older than this many blocks no longer count toward an approval
+pub const MODEL_VOTE_WINDOW: u64 = 1_000;
+
+/// Coherence given to every transaction while the local model is not the approved one
+pub const NEUTRAL_SEMANTIC_COHERENCE: f64 = 0.5;
+
+/// An embedding model, identified by its weights
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SemanticModel {
+    pub name: String,
+    pub version: String,
+    /// blake3 of the model weights
+    pub weights_hash: Hash,
+}
+
+impl SemanticModel {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() || self.name.len() > MAX_MODEL_NAME_SIZE {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Model name must be 1-{} bytes",
+                MAX_MODEL_NAME_SIZE
+            )));
+        }
+        if self.version.is_empty() || self.version.len() > MAX_MODEL_VERSION_SIZE {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Model version must be 1-{} bytes",
+                MAX_MODEL_VERSION_SIZE
+            )));
+        }
+        if self.weights_hash.is_zero() {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Model weights hash is missing".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// What validators compare: blake3 over name, version and weights hash
+    pub fn commitment(&self) -> Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"spirachain-semantic-model");
+        hasher.update(&(self.name.len() as u64).to_be_bytes());
+        hasher.update(self.name.as_bytes());
+        hasher.update(&(self.version.len() as u64).to_be_bytes());
+        hasher.update(self.version.as_bytes());
+        hasher.update(self.weights_hash.as_bytes());
+        Hash::from(hasher.finalize())
+    }
+}
+
+/// How a validator scores semantic coherence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticScoring {
+    /// With its local model: it is the approved one, or none is approved yet
+    Model,
+    /// [`NEUTRAL_SEMANTIC_COHERENCE`] for every transaction
+    Neutral,
+}
+
+impl SemanticScoring {
+    /// Scoring for a node running `local` (`None` if unknown) while
+    /// `approved` is the epoch's model
+    pub fn for_models(approved: Option<&SemanticModel>, local: Option<&SemanticModel>) -> Self {
+        match (approved, local) {
+            (None, _) => SemanticScoring::Model,
+            (Some(approved), Some(local)) if approved.commitment() == local.commitment() => {
+                SemanticScoring::Model
+            }
+            _ => SemanticScoring::Neutral,
+        }
+    }
+
+    pub fn coherence(&self, model_coherence: f64) -> f64 {
+        match self {
+            SemanticScoring::Model => model_coherence,
+            SemanticScoring::Neutral => NEUTRAL_SEMANTIC_COHERENCE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelVote {
+    pub model: SemanticModel,
+    pub height: u64,
+}
+
+/// On-chain model approvals by the epoch they take effect in, and the
+/// guardian votes collected toward the next one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SemanticModelState {
+    approved: BTreeMap<u64, SemanticModel>,
+    votes: BTreeMap<Address, ModelVote>,
+}
+
+impl SemanticModelState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Model in force at `height`, `None` before the first approval
+    pub fn approved_at(&self, height: u64) -> Option<&SemanticModel> {
+        self.approved
+            .range(..=diversity_epoch(height))
+            .next_back()
+            .map(|(_, model)| model)
+    }
+
+    /// Approvals by the epoch they take effect in
+    pub fn approvals(&self) -> impl Iterator<Item = (u64, &SemanticModel)> {
+        self.approved.iter().map(|(epoch, model)| (*epoch, model))
+    }
+
+    /// Record `guardian`'s vote for `model` at `height`. Once `threshold`
+    /// guardians agree the model applies from the next epoch and the votes
+    /// are cleared. Returns whether a model was approved.
+    pub fn vote(
+        &mut self,
+        multisig: &PauseMultisig,
+        guardian: Address,
+        model: SemanticModel,
+        height: u64,
+    ) -> Result<bool> {
+        if !multisig.is_enabled() || !multisig.is_guardian(&guardian) {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "{} is not a guardian",
+                guardian
+            )));
+        }
+        model.validate()?;
+
+        self.votes
+            .retain(|_, vote| height.saturating_sub(vote.height) < MODEL_VOTE_WINDOW);
+        let commitment = model.commitment();
+        self.votes.insert(guardian, ModelVote { model, height });
+
+        let agreeing: Vec<&ModelVote> = self
+            .votes
+            .values()
+            .filter(|vote| vote.model.commitment() == commitment)
+            .collect();
+        if agreeing.len() < multisig.threshold {
+            return Ok(false);
+        }
+
+        let model = agreeing[0].model.clone();
+        self.approved.insert(diversity_epoch(height) + 1, model);
+        self.votes.clear();
+        Ok(true)
+    }
+
+    /// Stable text for the state root; empty while there is nothing to commit to
+    pub fn state_entry(&self, height: u64) -> String {
+        let mut entry = String::new();
+        for (epoch, model) in &self.approved {
+            entry.push_str(&format!(":model:{}:{}", epoch, model.commitment()));
+        }
+        for (guardian, vote) in self
+            .votes
+            .iter()
+            .filter(|(_, vote)| height.saturating_sub(vote.height) < MODEL_VOTE_WINDOW)
+        {
+            entry.push_str(&format!(
+                ":model_vote:{}:{}:{}",
+                guardian,
+                vote.model.commitment(),
+                vote.height
+            ));
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DIVERSITY_EPOCH_BLOCKS;
+
+    const GUARDIANS: PauseMultisig = PauseMultisig {
+        guardians: &[
+            "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "0x0202020202020202020202020202020202020202020202020202020202020202",
+            "0x0303030303030303030303030303030303030303030303030303030303030303",
+        ],
+        threshold: 2,
+    };
+
+    fn guardian(index: u8) -> Address {
+        Address::new([index; 32])
+    }
+
+    fn model(version: &str) -> SemanticModel {
+        SemanticModel {
+            name: "spirapi-embed".to_string(),
+            version: version.to_string(),
+            weights_hash: Hash::from(blake3::hash(version.as_bytes())),
+        }
+    }
+
+    #[test]
+    fn test_approved_model_applies_from_the_next_epoch() {
+        let mut state = SemanticModelState::new();
+        let height = DIVERSITY_EPOCH_BLOCKS + 10;
+        assert!(state
+            .vote(&GUARDIANS, guardian(9), model("1"), height)
+            .is_err());
+        assert!(state
+            .vote(
+                &GUARDIANS,
+                guardian(1),
+                SemanticModel {
+                    name: String::new(),
+                    ..model("1")
+                },
+                height
+            )
+            .is_err());
+
+        assert!(!state
+            .vote(&GUARDIANS, guardian(1), model("1"), height)
+            .unwrap());
+        assert!(!state
+            .vote(&GUARDIANS, guardian(2), model("2"), height)
+            .unwrap());
+        assert!(state.state_entry(height).contains(":model_vote:"));
+        assert!(state
+            .vote(&GUARDIANS, guardian(3), model("1"), height + 1)
+            .unwrap());
+
+        assert_eq!(state.approved_at(height + 1), None);
+        assert_eq!(
+            state.approved_at(2 * DIVERSITY_EPOCH_BLOCKS),
+            Some(&model("1"))
+        );
+        assert!(!state.state_entry(height).contains(":model_vote:"));
+
+        // A later approval replaces it from its own epoch on
+        state
+            .vote(
+                &GUARDIANS,
+                guardian(1),
+                model("2"),
+                3 * DIVERSITY_EPOCH_BLOCKS,
+            )
+            .unwrap();
+        state
+            .vote(
+                &GUARDIANS,
+                guardian(2),
+                model("2"),
+                3 * DIVERSITY_EPOCH_BLOCKS,
+            )
+            .unwrap();
+        assert_eq!(
+            state.approved_at(3 * DIVERSITY_EPOCH_BLOCKS),
+            Some(&model("1"))
+        );
+        assert_eq!(
+            state.approved_at(4 * DIVERSITY_EPOCH_BLOCKS),
+            Some(&model("2"))
+        );
+        assert_eq!(state.approvals().count(), 2);
+    }
+
+    #[test]
+    fn test_mismatched_model_scores_neutral() {
+        let approved = model("1");
+        assert_eq!(
+            SemanticScoring::for_models(None, None),
+            SemanticScoring::Model
+        );
+        assert_eq!(
+            SemanticScoring::for_models(Some(&approved), Some(&model("1"))),
+            SemanticScoring::Model
+        );
+        let stale = SemanticScoring::for_models(Some(&approved), Some(&model("2")));
+        assert_eq!(stale, SemanticScoring::Neutral);
+        assert_eq!(
+            SemanticScoring::for_models(Some(&approved), None),
+            SemanticScoring::Neutral
+        );
+        assert_eq!(stale.coherence(0.93), NEUTRAL_SEMANTIC_COHERENCE);
+        assert_eq!(SemanticScoring::Model.coherence(0.93), 0.93);
+    }
+}
@@ -0,0 +1,208 @@
+// Signature verification cache
+// The same ed25519 signature gets checked on several paths: a header during
+// light sync and again for every proof checked against it, a transaction each
+// time it is checked against its sender's key. Successful verifications are kept by
+// (signed hash, public key) with a digest of the signature bytes, so another
+// signature over the same hash is still checked. Entries are spread over
+// shards so validation workers rarely contend on a lock; each shard drops its
+// oldest entries first. Failures are never cached. Switching to another
+// chain empties the cache, and the hit rate is exported with the node metrics.
+
+use crate::Hash;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// Verifications kept by [`signature_cache`]
+pub const SIGNATURE_CACHE_SIZE: usize = 65_536;
+
+const SHARDS: usize = 16;
+
+type Key = (Hash, [u8; 32]);
+
+#[derive(Default)]
+struct Shard {
+    /// Digest of the signature that verified
+    entries: HashMap<Key, [u8; 32]>,
+    order: VecDeque<Key>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignatureCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub entries: usize,
+}
+
+pub struct SignatureCache {
+    shards: Vec<Mutex<Shard>>,
+    per_shard: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl SignatureCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::default()).collect(),
+            per_shard: capacity.div_ceil(SHARDS).max(1),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether `signature` by `public_key` over `message` verifies. `hash`
+    /// must commit to everything in `message`: it is the cache key.
+    pub fn verify(
+        &self,
+        hash: &Hash,
+        public_key: &[u8; 32],
+        message: &[u8],
+        signature: &[u8],
+    ) -> bool {
+        let key = (*hash, *public_key);
+        let digest = *blake3::hash(signature).as_bytes();
+        if self.shard(&key).entries.get(&key) == Some(&digest) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return true;
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        if !verify_ed25519(public_key, message, signature) {
+            return false;
+        }
+
+        let mut shard = self.shard(&key);
+        if shard.entries.insert(key, digest).is_none() {
+            shard.order.push_back(key);
+        }
+        while shard.order.len() > self.per_shard {
+            if let Some(oldest) = shard.order.pop_front() {
+                shard.entries.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Forget every verification, after a switch to another chain
+    pub fn invalidate(&self) {
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap_or_else(|e| e.into_inner());
+            shard.entries.clear();
+            shard.order.clear();
+        }
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> SignatureCacheStats {
+        SignatureCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            entries: self
+                .shards
+                .iter()
+                .map(|shard| shard.lock().unwrap_or_else(|e| e.into_inner()).order.len())
+                .sum(),
+        }
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        let stats = self.stats();
+        let lookups = stats.hits + stats.misses;
+        let hit_rate = if lookups == 0 {
+            0.0
+        } else {
+            stats.hits as f64 / lookups as f64
+        };
+
+        format!(
+            "# HELP spirachain_signature_cache_hits_total Signature checks answered from the cache\n\
+             # TYPE spirachain_signature_cache_hits_total counter\n\
+             spirachain_signature_cache_hits_total {}\n\
+             # HELP spirachain_signature_cache_misses_total Signature checks that ran ed25519\n\
+             # TYPE spirachain_signature_cache_misses_total counter\n\
+             spirachain_signature_cache_misses_total {}\n\
+             # HELP spirachain_signature_cache_hit_rate Share of signature checks answered from the cache\n\
+             # TYPE spirachain_signature_cache_hit_rate gauge\n\
+             spirachain_signature_cache_hit_rate {:.4}\n\
+             # HELP spirachain_signature_cache_entries Verifications held\n\
+             # TYPE spirachain_signature_cache_entries gauge\n\
+             spirachain_signature_cache_entries {}\n\
+             # HELP spirachain_signature_cache_invalidations_total Times the cache was emptied after a chain switch\n\
+             # TYPE spirachain_signature_cache_invalidations_total counter\n\
+             spirachain_signature_cache_invalidations_total {}\n",
+            stats.hits, stats.misses, hit_rate, stats.entries, stats.invalidations
+        )
+    }
+
+    fn shard(&self, key: &Key) -> MutexGuard<'_, Shard> {
+        let index = key.0.as_bytes()[0] as usize % SHARDS;
+        self.shards[index].lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for SignatureCache {
+    fn default() -> Self {
+        Self::new(SIGNATURE_CACHE_SIZE)
+    }
+}
+
+/// Process-wide cache shared by every verification path
+pub fn signature_cache() -> &'static SignatureCache {
+    static CACHE: OnceLock<SignatureCache> = OnceLock::new();
+    CACHE.get_or_init(SignatureCache::default)
+}
+
+fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
+    let Ok(signature) = <&[u8; 64]>::try_from(signature) else {
+        return false;
+    };
+    let Ok(key) = ed25519_dalek::VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    key.verify_strict(message, &ed25519_dalek::Signature::from_bytes(signature))
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn test_cached_verifications() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        let hash = Hash::new([1u8; 32]);
+        let signature = key.sign(hash.as_bytes()).to_bytes();
+
+        let cache = SignatureCache::new(SHARDS);
+        assert!(cache.verify(&hash, &public_key, hash.as_bytes(), &signature));
+        assert!(cache.verify(&hash, &public_key, hash.as_bytes(), &signature));
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+
+        // Other signature bytes over a cached hash are checked, and fail
+        let mut forged = signature;
+        forged[0] ^= 1;
+        assert!(!cache.verify(&hash, &public_key, hash.as_bytes(), &forged));
+        assert_eq!(cache.stats().entries, 1);
+
+        // Bounded: one entry per shard here
+        for i in 0..64u8 {
+            let hash = Hash::new([i; 32]);
+            let signature = key.sign(hash.as_bytes()).to_bytes();
+            assert!(cache.verify(&hash, &public_key, hash.as_bytes(), &signature));
+        }
+        assert!(cache.stats().entries <= SHARDS);
+
+        cache.invalidate();
+        assert_eq!(cache.stats().entries, 0);
+        assert!(cache
+            .export_prometheus()
+            .contains("spirachain_signature_cache_invalidations_total 1\n"));
+    }
+}
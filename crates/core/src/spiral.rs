@@ -34,6 +34,14 @@ impl SpiralMetadata {
     pub fn is_valid(&self, min_complexity: f64) -> bool {
         self.complexity >= min_complexity && self.overall_score() >= min_complexity
     }
+
+    /// Registry id of a custom spiral, carried in `geometry_data`
+    pub fn custom_spiral_id(&self) -> Option<Hash> {
+        if self.spiral_type != SpiralType::Custom {
+            return None;
+        }
+        Hash::from_slice(&self.geometry_data).ok()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,288 @@
+use crate::{Address, Hash, Result, SpiraChainError, Spiral, SpiralMetadata, SpiralType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::f64::consts::{E, PI};
+
+/// Formula parameters are fixed point with six decimals, so every node validates
+/// and hashes the exact same definition
+pub const SPIRAL_PARAM_SCALE: i64 = 1_000_000;
+
+/// Largest magnitude of a radius or polynomial coefficient (1000.0)
+pub const MAX_SPIRAL_PARAM: i64 = 1_000 * SPIRAL_PARAM_SCALE;
+
+/// Largest magnitude of a logarithmic growth rate (1.0)
+pub const MAX_SPIRAL_GROWTH: i64 = SPIRAL_PARAM_SCALE;
+
+pub const MAX_POLYNOMIAL_DEGREE: usize = 4;
+pub const MAX_CUSTOM_SPIRAL_TURNS: u32 = 16;
+pub const MAX_SPIRAL_NAME_SIZE: usize = 32;
+
+/// Minimum fee of a registration, which stays in the state forever (1 QBT)
+pub const SPIRAL_REGISTRATION_FEE: u128 = 1_000_000_000_000_000_000;
+
+const POINTS_PER_TURN: u32 = 100;
+
+/// Polar formula r(θ) of a custom spiral, parameters scaled by [`SPIRAL_PARAM_SCALE`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpiralFormula {
+    /// r = a + bθ
+    Archimedean { a: i64, b: i64 },
+    /// r = a·e^(kθ)
+    Logarithmic { a: i64, k: i64 },
+    /// r = a·√θ
+    Fermat { a: i64 },
+    /// r = c₀ + c₁θ + … + cₙθⁿ
+    Polynomial { coefficients: Vec<i64> },
+}
+
+impl SpiralFormula {
+    fn validate(&self) -> Result<()> {
+        let in_bounds = |value: i64, bound: i64| (-bound..=bound).contains(&value);
+        let valid = match self {
+            SpiralFormula::Archimedean { a, b } => {
+                in_bounds(*a, MAX_SPIRAL_PARAM) && in_bounds(*b, MAX_SPIRAL_PARAM)
+            }
+            SpiralFormula::Logarithmic { a, k } => {
+                in_bounds(*a, MAX_SPIRAL_PARAM) && in_bounds(*k, MAX_SPIRAL_GROWTH) && *k != 0
+            }
+            SpiralFormula::Fermat { a } => in_bounds(*a, MAX_SPIRAL_PARAM),
+            SpiralFormula::Polynomial { coefficients } => {
+                !coefficients.is_empty()
+                    && coefficients.len() <= MAX_POLYNOMIAL_DEGREE + 1
+                    && coefficients.iter().all(|c| in_bounds(*c, MAX_SPIRAL_PARAM))
+            }
+        };
+
+        if !valid {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Spiral formula parameters out of bounds: {:?}",
+                self
+            )));
+        }
+        Ok(())
+    }
+
+    fn radius(&self, theta: f64) -> f64 {
+        let param = |value: i64| value as f64 / SPIRAL_PARAM_SCALE as f64;
+        match self {
+            SpiralFormula::Archimedean { a, b } => param(*a) + param(*b) * theta,
+            SpiralFormula::Logarithmic { a, k } => param(*a) * E.powf(param(*k) * theta),
+            SpiralFormula::Fermat { a } => param(*a) * theta.sqrt(),
+            SpiralFormula::Polynomial { coefficients } => coefficients
+                .iter()
+                .rev()
+                .fold(0.0, |acc, c| acc * theta + param(*c)),
+        }
+    }
+}
+
+/// Custom spiral generator anyone can register on-chain, see [`SpiralRegistry`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomSpiralDefinition {
+    pub name: String,
+    pub formula: SpiralFormula,
+    pub turns: u32,
+}
+
+impl CustomSpiralDefinition {
+    pub fn new(name: impl Into<String>, formula: SpiralFormula, turns: u32) -> Self {
+        Self {
+            name: name.into(),
+            formula,
+            turns,
+        }
+    }
+
+    /// Structural checks every node runs before accepting a registration
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty()
+            || self.name.len() > MAX_SPIRAL_NAME_SIZE
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Spiral name must be 1-{} characters of [A-Za-z0-9_-]",
+                MAX_SPIRAL_NAME_SIZE
+            )));
+        }
+        if self.turns == 0 || self.turns > MAX_CUSTOM_SPIRAL_TURNS {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Spiral turns must be 1-{}, got {}",
+                MAX_CUSTOM_SPIRAL_TURNS, self.turns
+            )));
+        }
+        self.formula.validate()
+    }
+
+    /// Registry id: blake3 over the canonical encoding of the definition
+    pub fn id(&self) -> Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"spirachain-custom-spiral");
+        hasher.update(&bincode::serialize(self).unwrap_or_default());
+        hasher.finalize().into()
+    }
+
+    /// Points of the spiral; the metadata references this definition by id
+    pub fn generate(&self) -> Spiral {
+        let mut spiral = Spiral::new(SpiralType::Custom);
+        for i in 0..self.turns * POINTS_PER_TURN {
+            let theta = (i as f64) * 2.0 * PI / (POINTS_PER_TURN as f64);
+            spiral.points.push((self.formula.radius(theta), theta));
+        }
+        spiral.metadata.geometry_data = self.id().as_bytes().to_vec();
+        spiral.compute_metrics();
+        spiral
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisteredSpiral {
+    pub definition: CustomSpiralDefinition,
+    pub registrant: Address,
+    pub height: u64,
+}
+
+/// Custom spirals registered through `RegisterSpiral` transactions. Blocks of
+/// type [`SpiralType::Custom`] must reference one of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiralRegistry {
+    spirals: BTreeMap<Hash, RegisteredSpiral>,
+}
+
+impl SpiralRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(
+        &mut self,
+        definition: CustomSpiralDefinition,
+        registrant: Address,
+        height: u64,
+    ) -> Result<Hash> {
+        definition.validate()?;
+        let id = definition.id();
+        if self.spirals.contains_key(&id) {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Spiral {} is already registered",
+                id
+            )));
+        }
+
+        self.spirals.insert(
+            id,
+            RegisteredSpiral {
+                definition,
+                registrant,
+                height,
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn get(&self, id: &Hash) -> Option<&RegisteredSpiral> {
+        self.spirals.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Hash, &RegisteredSpiral)> {
+        self.spirals.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.spirals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spirals.is_empty()
+    }
+
+    /// A custom block spiral must name a registered definition
+    pub fn check_block_spiral(&self, spiral: &SpiralMetadata) -> Result<()> {
+        if spiral.spiral_type != SpiralType::Custom {
+            return Ok(());
+        }
+        match spiral.custom_spiral_id() {
+            Some(id) if self.spirals.contains_key(&id) => Ok(()),
+            Some(id) => Err(SpiraChainError::InvalidBlock(format!(
+                "Block references unregistered custom spiral {}",
+                id
+            ))),
+            None => Err(SpiraChainError::InvalidBlock(
+                "Custom spiral block does not reference a registered spiral".to_string(),
+            )),
+        }
+    }
+
+    /// Stable text for the state root; empty while nothing is registered
+    pub fn state_entry(&self) -> String {
+        self.spirals
+            .iter()
+            .map(|(id, spiral)| format!("spiral:{}:{}:{}", id, spiral.registrant, spiral.height))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn golden() -> CustomSpiralDefinition {
+        CustomSpiralDefinition::new(
+            "golden",
+            SpiralFormula::Logarithmic {
+                a: SPIRAL_PARAM_SCALE,
+                k: 306_349,
+            },
+            4,
+        )
+    }
+
+    #[test]
+    fn test_definition_bounds() {
+        assert!(golden().validate().is_ok());
+
+        let mut bad = golden();
+        bad.turns = MAX_CUSTOM_SPIRAL_TURNS + 1;
+        assert!(bad.validate().is_err());
+
+        bad = golden();
+        bad.name = "no spaces".to_string();
+        assert!(bad.validate().is_err());
+
+        bad.name = "cubic".to_string();
+        bad.formula = SpiralFormula::Polynomial {
+            coefficients: vec![0; MAX_POLYNOMIAL_DEGREE + 2],
+        };
+        assert!(bad.validate().is_err());
+
+        bad.formula = SpiralFormula::Archimedean {
+            a: MAX_SPIRAL_PARAM + 1,
+            b: 0,
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_registry_and_block_references() {
+        let mut registry = SpiralRegistry::new();
+        let registrant = Address::new([1u8; 32]);
+        let id = registry.register(golden(), registrant, 10).unwrap();
+        assert_eq!(id, golden().id());
+        assert!(registry.register(golden(), registrant, 11).is_err());
+
+        let spiral = golden().generate();
+        assert_eq!(spiral.metadata.custom_spiral_id(), Some(id));
+        assert!(registry.check_block_spiral(&spiral.metadata).is_ok());
+
+        let mut unknown = SpiralMetadata::new(SpiralType::Custom);
+        assert!(registry.check_block_spiral(&unknown).is_err());
+        unknown.geometry_data = vec![7u8; 32];
+        assert!(registry.check_block_spiral(&unknown).is_err());
+        assert!(registry
+            .check_block_spiral(&SpiralMetadata::new(SpiralType::Fermat))
+            .is_ok());
+    }
+}
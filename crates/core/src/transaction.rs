@@ -5,6 +5,10 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// extra_data key carrying the sender's ed25519 public key. Not hashed: it
+/// authenticates itself by hashing to the sender address.
+pub const SENDER_PUBLIC_KEY: &str = "sender_public_key";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
     pub name: String,
@@ -18,6 +22,192 @@ pub struct Intent {
     pub confidence: f64,
 }
 
+/// What a transaction does beyond moving `amount` from `from` to `to`.
+///
+/// For contract calls the target is `to` and the attached value is `amount`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionPayload {
+    #[default]
+    Transfer,
+    ContractDeploy {
+        code: Vec<u8>,
+        constructor_args: Vec<u8>,
+        /// Sender nonce the contract address is derived from
+        nonce: u64,
+    },
+    ContractCall {
+        input: Vec<u8>,
+    },
+    /// Block reward minted to the producer; only valid as the first transaction of a block
+    Coinbase {
+        height: u64,
+    },
+    /// Pay the sender's unclaimed block rewards, less the fee, to `to`
+    ClaimRewards,
+    /// Guardian vote to pause the chain for `blocks` blocks, 0 to lift a pause.
+    /// See [`crate::PauseState`].
+    EmergencyPause {
+        blocks: u64,
+    },
+    /// Add a custom spiral generator to the [`crate::SpiralRegistry`]
+    RegisterSpiral {
+        definition: crate::CustomSpiralDefinition,
+    },
+    /// Send the sender's future block rewards to `to`, or back to the sender
+    /// when `to` is the sender. See [`crate::Account::payout_address`].
+    SetPayoutAddress,
+    /// Register, renew or re-point `name` in the [`crate::NameRegistry`] so it
+    /// resolves to `to`
+    RegisterName {
+        name: String,
+    },
+    /// Hand the sender's `name` over to `to`
+    TransferName {
+        name: String,
+    },
+    /// Summary of the previous epoch; only valid as the second transaction of
+    /// an epoch's first block, see [`crate::EpochSummary`]
+    EpochSummary {
+        summary: crate::EpochSummary,
+    },
+    /// Move the sender's stake and payout address to `to`, the validator's new
+    /// key. `proof` is `public_key`'s signed message over
+    /// [`key_rotation_message`], so stake cannot be rotated to a key nobody holds.
+    RotateValidatorKey {
+        public_key: Vec<u8>,
+        proof: Vec<u8>,
+    },
+    /// Guardian vote approving the embedding model validators score with
+    /// from the next epoch. See [`crate::SemanticModelState`].
+    ApproveSemanticModel {
+        model: crate::SemanticModel,
+    },
+}
+
+impl TransactionPayload {
+    pub fn is_contract(&self) -> bool {
+        matches!(
+            self,
+            TransactionPayload::ContractDeploy { .. } | TransactionPayload::ContractCall { .. }
+        )
+    }
+
+    /// Whether the transaction still executes while the chain is paused
+    pub fn runs_while_paused(&self) -> bool {
+        matches!(
+            self,
+            TransactionPayload::Coinbase { .. }
+                | TransactionPayload::EpochSummary { .. }
+                | TransactionPayload::EmergencyPause { .. }
+        )
+    }
+
+    /// Whether a transaction with this payload may carry a zero amount
+    pub fn allows_zero_amount(&self) -> bool {
+        match self {
+            TransactionPayload::Transfer | TransactionPayload::Coinbase { .. } => false,
+            TransactionPayload::ContractDeploy { .. }
+            | TransactionPayload::ContractCall { .. }
+            | TransactionPayload::ClaimRewards
+            | TransactionPayload::EmergencyPause { .. }
+            | TransactionPayload::RegisterSpiral { .. }
+            | TransactionPayload::SetPayoutAddress
+            | TransactionPayload::RegisterName { .. }
+            | TransactionPayload::TransferName { .. }
+            | TransactionPayload::EpochSummary { .. }
+            | TransactionPayload::RotateValidatorKey { .. }
+            | TransactionPayload::ApproveSemanticModel { .. } => true,
+        }
+    }
+
+    /// Why a transaction with this payload must carry a zero amount, if it must
+    pub fn zero_amount_reason(&self) -> Option<&'static str> {
+        match self {
+            TransactionPayload::Transfer
+            | TransactionPayload::ContractDeploy { .. }
+            | TransactionPayload::ContractCall { .. }
+            | TransactionPayload::Coinbase { .. }
+            | TransactionPayload::EpochSummary { .. } => None,
+            TransactionPayload::ClaimRewards => Some(
+                "A rewards claim pays out the whole unclaimed balance; amount must be zero",
+            ),
+            TransactionPayload::EmergencyPause { .. }
+            | TransactionPayload::ApproveSemanticModel { .. } => {
+                Some("A guardian vote cannot carry value")
+            }
+            TransactionPayload::RegisterSpiral { .. } => {
+                Some("A spiral registration cannot carry value")
+            }
+            TransactionPayload::SetPayoutAddress => {
+                Some("A payout address change cannot carry value")
+            }
+            TransactionPayload::RegisterName { .. } | TransactionPayload::TransferName { .. } => {
+                Some("A name registration or transfer cannot carry value")
+            }
+            TransactionPayload::RotateValidatorKey { .. } => {
+                Some("A key rotation moves the stake, not the balance; amount must be zero")
+            }
+        }
+    }
+}
+
+/// Message the new key signs to accept `validator`'s stake
+pub fn key_rotation_message(validator: &Address) -> Vec<u8> {
+    format!("Rotate validator {} to this key", validator).into_bytes()
+}
+
+/// Commitment to a semantic vector: blake3 over the big-endian bit pattern of
+/// every component, so it does not depend on how the vector was encoded
+pub fn semantic_commitment(vector: &[f32]) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"spirachain-semantic-vector");
+    for value in vector {
+        hasher.update(&value.to_bits().to_be_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Coherence of a semantic vector in units of 1/[`crate::SEMANTIC_SCORE_MAX`]:
+/// its magnitude capped at 1, or zero below 0.01
+pub fn semantic_score(vector: &[f32]) -> u16 {
+    let magnitude = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if magnitude.is_nan() || magnitude < 0.01 {
+        return 0;
+    }
+    (f64::from(magnitude.min(1.0)) * f64::from(crate::SEMANTIC_SCORE_MAX)).round() as u16
+}
+
+/// Semantic vectors travel in JSON (RPC submissions, side store exports) but are
+/// left out of the binary encoding used for blocks, gossip and storage
+mod off_chain_vector {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(vector: &Vec<f32>, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            vector.serialize(serializer)
+        } else {
+            ().serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+        if deserializer.is_human_readable() {
+            Vec::deserialize(deserializer)
+        } else {
+            <()>::deserialize(deserializer).map(|_| Vec::new())
+        }
+    }
+}
+
+/// Deterministic contract address: blake3(domain || sender || nonce)
+pub fn derive_contract_address(sender: &Address, nonce: u64) -> Address {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"spirachain-contract");
+    hasher.update(sender.as_bytes());
+    hasher.update(&nonce.to_be_bytes());
+    Address::new(*hasher.finalize().as_bytes())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     pub version: u64,
@@ -32,7 +222,17 @@ pub struct Transaction {
     pub signature: Vec<u8>,
 
     pub purpose: String,
+    /// Embedding of `purpose`. Not part of blocks or the transaction hash; nodes
+    /// keep it in a side store keyed by tx hash.
+    #[serde(default, with = "off_chain_vector")]
     pub semantic_vector: Vec<f32>,
+    /// [`semantic_commitment`] of the vector, zero if the sender attached none
+    #[serde(default = "Hash::zero")]
+    pub semantic_commitment: Hash,
+    /// [`semantic_score`] of the vector. Unlike the vector it is on-chain and
+    /// hashed, so every node scores blocks alike; zero for legacy transactions.
+    #[serde(default)]
+    pub semantic_score: u16,
     pub entities: Vec<Entity>,
     pub intent: Option<Intent>,
     pub related_txs: Vec<Hash>,
@@ -41,10 +241,49 @@ pub struct Transaction {
     pub thread_id: Option<Hash>,
 
     pub extra_data: HashMap<String, Vec<u8>>,
+
+    #[serde(default)]
+    pub payload: TransactionPayload,
+
+    /// Chain side this transaction is valid on, see [`crate::fork_id`]. Zero for
+    /// legacy transactions, which are only accepted until the first hard fork.
+    #[serde(default = "Hash::zero")]
+    pub fork_id: Hash,
+
+    /// Earliest block height that may include the transaction. Held in the
+    /// mempool until then, it expires [`crate::SCHEDULED_TX_WINDOW`] blocks later.
+    #[serde(default)]
+    pub execute_at: Option<u64>,
+
+    /// Sender's account nonce when the transaction was signed. Orders a
+    /// sender's transactions within a block, see [`crate::canonical_key`];
+    /// `None` for legacy transactions.
+    #[serde(default)]
+    pub nonce: Option<u64>,
+}
+
+/// Where a transaction stands against its `execute_at` height
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleStatus {
+    Due,
+    /// Not includable before this height
+    Waiting(u64),
+    /// Missed its inclusion window, which ended at this height
+    Expired(u64),
 }
 
 impl Transaction {
     pub fn new(from: Address, to: Address, amount: Amount, fee: Amount) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        Self::new_at(from, to, amount, fee, timestamp)
+    }
+
+    /// [`Self::new`] with a caller-supplied timestamp in milliseconds, for targets
+    /// without a system clock such as wasm
+    pub fn new_at(from: Address, to: Address, amount: Amount, fee: Amount, timestamp: u64) -> Self {
         Self {
             version: 1,
             tx_hash: Hash::zero(),
@@ -53,22 +292,55 @@ impl Transaction {
             to,
             amount,
             fee,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
+            timestamp,
             signature: Vec::new(),
             purpose: String::new(),
             semantic_vector: Vec::new(),
+            semantic_commitment: Hash::zero(),
+            semantic_score: 0,
             entities: Vec::new(),
             intent: None,
             related_txs: Vec::new(),
             spiral_position: None,
             thread_id: None,
             extra_data: HashMap::new(),
+            payload: TransactionPayload::Transfer,
+            fork_id: Hash::zero(),
+            execute_at: None,
+            nonce: None,
         }
     }
 
+    /// Deploy `code` from `from`. The target address is derived from the sender and nonce.
+    pub fn new_contract_deploy(
+        from: Address,
+        code: Vec<u8>,
+        constructor_args: Vec<u8>,
+        nonce: u64,
+        value: Amount,
+        fee: Amount,
+    ) -> Self {
+        let contract_address = derive_contract_address(&from, nonce);
+        Self::new(from, contract_address, value, fee).with_payload(
+            TransactionPayload::ContractDeploy {
+                code,
+                constructor_args,
+                nonce,
+            },
+        )
+    }
+
+    pub fn new_contract_call(
+        from: Address,
+        contract: Address,
+        input: Vec<u8>,
+        value: Amount,
+        fee: Amount,
+    ) -> Self {
+        Self::new(from, contract, value, fee)
+            .with_payload(TransactionPayload::ContractCall { input })
+    }
+
     pub fn with_purpose(mut self, purpose: impl Into<String>) -> Self {
         self.purpose = purpose.into();
         self
@@ -85,10 +357,19 @@ impl Transaction {
     }
 
     pub fn with_semantic_vector(mut self, vector: Vec<f32>) -> Self {
+        self.semantic_commitment = semantic_commitment(&vector);
+        self.semantic_score = semantic_score(&vector);
         self.semantic_vector = vector;
         self
     }
 
+    /// Whether `vector` is the one this transaction committed to and scored
+    pub fn verify_semantic_vector(&self, vector: &[f32]) -> bool {
+        !self.semantic_commitment.is_zero()
+            && self.semantic_commitment == semantic_commitment(vector)
+            && self.semantic_score == semantic_score(vector)
+    }
+
     pub fn with_intent(mut self, intent: Intent) -> Self {
         self.intent = Some(intent);
         self
@@ -109,8 +390,155 @@ impl Transaction {
         self
     }
 
+    /// Attach the sender's ed25519 key, see [`SENDER_PUBLIC_KEY`]
+    pub fn with_sender_public_key(self, public_key: [u8; 32]) -> Self {
+        self.with_extra_data(SENDER_PUBLIC_KEY, public_key.to_vec())
+    }
+
+    pub fn sender_public_key(&self) -> Option<[u8; 32]> {
+        self.extra_data
+            .get(SENDER_PUBLIC_KEY)
+            .and_then(|key| key.as_slice().try_into().ok())
+    }
+
+    /// Reward transaction minting `reward` to `producer` at `height`
+    pub fn new_coinbase(producer: Address, reward: Amount, height: u64) -> Self {
+        Self::new(Address::zero(), producer, reward, Amount::zero())
+            .with_payload(TransactionPayload::Coinbase { height })
+    }
+
+    /// Claim `from`'s unclaimed rewards, paid out to `payout` (which may be `from`)
+    pub fn new_claim_rewards(from: Address, payout: Address, fee: Amount) -> Self {
+        Self::new(from, payout, Amount::zero(), fee).with_payload(TransactionPayload::ClaimRewards)
+    }
+
+    /// Guardian vote pausing the chain for `blocks` blocks (0 lifts the pause)
+    pub fn new_pause_vote(guardian: Address, blocks: u64, fee: Amount) -> Self {
+        Self::new(guardian, guardian, Amount::zero(), fee)
+            .with_payload(TransactionPayload::EmergencyPause { blocks })
+    }
+
+    /// Guardian vote approving `model` as the epoch's embedding model
+    pub fn new_model_vote(guardian: Address, model: crate::SemanticModel, fee: Amount) -> Self {
+        Self::new(guardian, guardian, Amount::zero(), fee)
+            .with_payload(TransactionPayload::ApproveSemanticModel { model })
+    }
+
+    /// Register `definition` so blocks can reference it as a custom spiral
+    pub fn new_register_spiral(
+        from: Address,
+        definition: crate::CustomSpiralDefinition,
+        fee: Amount,
+    ) -> Self {
+        Self::new(from, from, Amount::zero(), fee)
+            .with_payload(TransactionPayload::RegisterSpiral { definition })
+    }
+
+    /// Have `validator`'s block rewards credited to `payout` (`validator` itself resets it)
+    pub fn new_set_payout_address(validator: Address, payout: Address, fee: Amount) -> Self {
+        Self::new(validator, payout, Amount::zero(), fee)
+            .with_payload(TransactionPayload::SetPayoutAddress)
+    }
+
+    /// Register `name` (without the `.spira` suffix) resolving to `target`, or
+    /// renew it when `from` already owns it
+    pub fn new_register_name(from: Address, name: String, target: Address, fee: Amount) -> Self {
+        Self::new(from, target, Amount::zero(), fee)
+            .with_payload(TransactionPayload::RegisterName { name })
+    }
+
+    pub fn new_transfer_name(from: Address, name: String, new_owner: Address, fee: Amount) -> Self {
+        Self::new(from, new_owner, Amount::zero(), fee)
+            .with_payload(TransactionPayload::TransferName { name })
+    }
+
+    /// Hand `validator`'s stake over to the key `public_key`, which signed
+    /// [`key_rotation_message`] as `proof`
+    pub fn new_rotate_validator_key(
+        validator: Address,
+        public_key: [u8; 32],
+        proof: Vec<u8>,
+        fee: Amount,
+    ) -> Self {
+        let new_key = Address::new(*blake3::hash(&public_key).as_bytes());
+        Self::new(validator, new_key, Amount::zero(), fee).with_payload(
+            TransactionPayload::RotateValidatorKey {
+                public_key: public_key.to_vec(),
+                proof,
+            },
+        )
+    }
+
+    /// Summary of the epoch before `summary.epoch + 1`, added by the producer
+    /// of that epoch's first block
+    pub fn new_epoch_summary(summary: crate::EpochSummary) -> Self {
+        Self::new(
+            Address::zero(),
+            Address::zero(),
+            Amount::zero(),
+            Amount::zero(),
+        )
+        .with_payload(TransactionPayload::EpochSummary { summary })
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        matches!(self.payload, TransactionPayload::Coinbase { .. })
+    }
+
+    pub fn is_epoch_summary(&self) -> bool {
+        matches!(self.payload, TransactionPayload::EpochSummary { .. })
+    }
+
+    /// Written by the block producer rather than sent by an account: never
+    /// signed, gossiped or held in a mempool
+    pub fn is_protocol(&self) -> bool {
+        self.is_coinbase() || self.is_epoch_summary()
+    }
+
+    pub fn with_payload(mut self, payload: TransactionPayload) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    pub fn with_fork_id(mut self, fork_id: Hash) -> Self {
+        self.fork_id = fork_id;
+        self
+    }
+
+    pub fn with_execute_at(mut self, height: u64) -> Self {
+        self.execute_at = Some(height);
+        self
+    }
+
+    /// Address of the contract created by this transaction, if it is a deployment
+    pub fn contract_address(&self) -> Option<Address> {
+        match &self.payload {
+            TransactionPayload::ContractDeploy { nonce, .. } => {
+                Some(derive_contract_address(&self.from, *nonce))
+            }
+            _ => None,
+        }
+    }
+
     pub fn compute_hash(&mut self) {
-        let mut hasher = blake3::Hasher::new();
+        self.tx_hash = self.computed_hash();
+    }
+
+    /// Hash of the current contents, whatever `tx_hash` holds
+    pub fn computed_hash(&self) -> Hash {
+        self.hash_with(crate::versioned_hasher(
+            self.version,
+            crate::TRANSACTION_CONTEXT,
+        ))
+    }
+
+    /// Hash under [`crate::TRANSACTION_CONTEXT`] whatever the version; the
+    /// transaction hash from [`crate::TAGGED_HASH_VERSION`] on
+    pub fn tagged_hash(&self) -> Hash {
+        self.hash_with(blake3::Hasher::new_derive_key(crate::TRANSACTION_CONTEXT))
+    }
+
+    fn hash_with(&self, mut hasher: blake3::Hasher) -> Hash {
         hasher.update(&self.version.to_be_bytes());
         hasher.update(self.from.as_bytes());
         hasher.update(self.to.as_bytes());
@@ -123,7 +551,167 @@ impl Transaction {
             hasher.update(&coord.to_be_bytes());
         }
 
-        self.tx_hash = hasher.finalize().into();
+        // Plain transfers keep their historical preimage
+        if self.payload != TransactionPayload::Transfer {
+            hasher.update(&bincode::serialize(&self.payload).unwrap_or_default());
+        }
+
+        if !self.fork_id.is_zero() {
+            hasher.update(self.fork_id.as_bytes());
+        }
+
+        if !self.semantic_commitment.is_zero() {
+            hasher.update(self.semantic_commitment.as_bytes());
+        }
+
+        if self.semantic_score != 0 {
+            hasher.update(b"semantic_score");
+            hasher.update(&self.semantic_score.to_be_bytes());
+        }
+
+        if let Some(height) = self.execute_at {
+            hasher.update(b"execute_at");
+            hasher.update(&height.to_be_bytes());
+        }
+
+        if let Some(nonce) = self.nonce {
+            hasher.update(b"nonce");
+            hasher.update(&nonce.to_be_bytes());
+        }
+
+        hasher.finalize().into()
+    }
+
+    /// Signature by the sender's `public_key`, checked through
+    /// [`crate::signature_cache`]. Transactions do not carry the key, so the
+    /// caller supplies it; it must hash to the sender address.
+    pub fn verify_signature(&self, public_key: &[u8; 32]) -> Result<()> {
+        if Address::new(*blake3::hash(public_key).as_bytes()) != self.from {
+            return Err(SpiraChainError::CryptoError(format!(
+                "public key does not belong to {}",
+                self.from
+            )));
+        }
+        // The cache is keyed by the hash, so it must match the contents
+        if self.tx_hash != self.computed_hash() {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Transaction hash does not match its contents".to_string(),
+            ));
+        }
+        if crate::signature_cache().verify(
+            &self.tx_hash,
+            public_key,
+            &self.signing_message(),
+            &self.signature,
+        ) {
+            Ok(())
+        } else {
+            Err(SpiraChainError::InvalidSignature)
+        }
+    }
+
+    /// [`Self::verify_signature`] against the key the transaction carries.
+    /// Protocol transactions have no sender to check.
+    pub fn verify_sender_signature(&self) -> Result<()> {
+        if self.is_protocol() {
+            return Ok(());
+        }
+        let public_key = self.sender_public_key().ok_or_else(|| {
+            SpiraChainError::InvalidTransaction(
+                "Transaction does not carry its sender's public key".to_string(),
+            )
+        })?;
+        self.verify_signature(&public_key)
+    }
+
+    /// [`Self::verify_sender_signature`] under `network`'s rules at `height`.
+    /// Transactions from before senders attached their key were never checked;
+    /// like legacy fork ids they stay valid until the first hard fork.
+    pub fn verify_sender_signature_at(&self, network: &str, height: u64) -> Result<()> {
+        let keyless_allowed =
+            crate::ChainParams::for_network(network).last_hard_fork_height(height) == 0;
+        if keyless_allowed && self.sender_public_key().is_none() {
+            return Ok(());
+        }
+        self.verify_sender_signature()
+    }
+
+    /// Bytes the sender signs: the fork id and the transaction hash
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(b"spirachain-tx".len() + 64);
+        message.extend_from_slice(b"spirachain-tx");
+        message.extend_from_slice(self.fork_id.as_bytes());
+        message.extend_from_slice(self.tx_hash.as_bytes());
+        message
+    }
+
+    /// Reject transactions signed for another fork of `network`. Legacy transactions
+    /// without a fork id are accepted only while no hard fork has activated.
+    pub fn validate_fork_id(&self, network: &str, height: u64) -> Result<()> {
+        // Tagged transaction hashes only count once blocks use them too
+        if self.version >= crate::TAGGED_HASH_VERSION
+            && !crate::ChainParams::for_network(network)
+                .is_active(crate::TAGGED_HASH_VERSION as u32, height)
+        {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Transaction version {} is not active at height {}",
+                self.version, height
+            )));
+        }
+
+        if self.is_protocol() || self.fork_id == crate::fork_id(network, height) {
+            return Ok(());
+        }
+
+        if self.fork_id.is_zero() && crate::last_hard_fork_height(network, height) == 0 {
+            return Ok(());
+        }
+
+        Err(SpiraChainError::InvalidTransaction(format!(
+            "Transaction fork id {} does not match this chain",
+            self.fork_id
+        )))
+    }
+
+    pub fn schedule_status(&self, height: u64) -> ScheduleStatus {
+        match self.execute_at {
+            Some(at) if height < at => ScheduleStatus::Waiting(at),
+            Some(at) if height > at.saturating_add(crate::SCHEDULED_TX_WINDOW) => {
+                ScheduleStatus::Expired(at.saturating_add(crate::SCHEDULED_TX_WINDOW))
+            }
+            _ => ScheduleStatus::Due,
+        }
+    }
+
+    /// A block at `height` may only include the transaction inside its window
+    pub fn validate_schedule(&self, height: u64) -> Result<()> {
+        match self.schedule_status(height) {
+            ScheduleStatus::Due => Ok(()),
+            ScheduleStatus::Waiting(at) => Err(SpiraChainError::InvalidTransaction(format!(
+                "Scheduled for block {}",
+                at
+            ))),
+            ScheduleStatus::Expired(end) => Err(SpiraChainError::InvalidTransaction(format!(
+                "Scheduled transaction expired at block {}",
+                end
+            ))),
+        }
+    }
+
+    /// Mempool admission when `next_height` is the next block: scheduled
+    /// transactions are held, unless already expired or too far ahead
+    pub fn validate_schedule_admission(&self, next_height: u64) -> Result<()> {
+        match self.schedule_status(next_height) {
+            ScheduleStatus::Waiting(at) if at - next_height > crate::MAX_SCHEDULE_LEAD => {
+                Err(SpiraChainError::InvalidTransaction(format!(
+                    "Scheduled {} blocks ahead, at most {} allowed",
+                    at - next_height,
+                    crate::MAX_SCHEDULE_LEAD
+                )))
+            }
+            ScheduleStatus::Waiting(_) => Ok(()),
+            _ => self.validate_schedule(next_height),
+        }
     }
 
     pub fn serialize(&self) -> Vec<u8> {
@@ -135,17 +723,30 @@ impl Transaction {
     }
 
     pub fn validate(&self) -> Result<()> {
-        if self.amount.value() == 0 {
+        if self.is_protocol() {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Coinbase and epoch summary transactions are only valid inside blocks".to_string(),
+            ));
+        }
+
+        if self.amount.is_zero() && !self.payload.allows_zero_amount() {
             return Err(SpiraChainError::InvalidTransaction(
                 "Amount cannot be zero".to_string(),
             ));
         }
+        if let Some(reason) = self.payload.zero_amount_reason() {
+            if !self.amount.is_zero() {
+                return Err(SpiraChainError::InvalidTransaction(reason.to_string()));
+            }
+        }
+
+        self.validate_semantic_fields()?;
 
-        if self.fee.value() < crate::MIN_TX_FEE {
+        let min_fee = self.min_fee();
+        if self.fee < min_fee {
             return Err(SpiraChainError::InvalidTransaction(format!(
                 "Fee too low: {} < {}",
-                self.fee,
-                Amount::new(crate::MIN_TX_FEE)
+                self.fee, min_fee
             )));
         }
 
@@ -159,25 +760,247 @@ impl Transaction {
             ));
         }
 
+        match &self.payload {
+            TransactionPayload::Transfer => {}
+            TransactionPayload::ContractDeploy {
+                code,
+                constructor_args,
+                nonce,
+            } => {
+                if code.is_empty() {
+                    return Err(SpiraChainError::InvalidTransaction(
+                        "Contract code cannot be empty".to_string(),
+                    ));
+                }
+                if code.len() > crate::MAX_CONTRACT_CODE_SIZE {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Contract code too large: {} > {} bytes",
+                        code.len(),
+                        crate::MAX_CONTRACT_CODE_SIZE
+                    )));
+                }
+                if constructor_args.len() > crate::MAX_CONTRACT_INPUT_SIZE {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Constructor args too large: {} > {} bytes",
+                        constructor_args.len(),
+                        crate::MAX_CONTRACT_INPUT_SIZE
+                    )));
+                }
+                if self.to != derive_contract_address(&self.from, *nonce) {
+                    return Err(SpiraChainError::InvalidTransaction(
+                        "Deployment target does not match derived contract address".to_string(),
+                    ));
+                }
+            }
+            TransactionPayload::Coinbase { .. }
+            | TransactionPayload::EpochSummary { .. }
+            | TransactionPayload::ClaimRewards
+            | TransactionPayload::SetPayoutAddress => {}
+            TransactionPayload::EmergencyPause { blocks } => {
+                if *blocks > crate::MAX_PAUSE_BLOCKS {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Pause too long: {} > {} blocks",
+                        blocks,
+                        crate::MAX_PAUSE_BLOCKS
+                    )));
+                }
+            }
+            TransactionPayload::ApproveSemanticModel { model } => model.validate()?,
+            TransactionPayload::RegisterSpiral { definition } => {
+                definition.validate()?;
+                if self.fee.value() < crate::SPIRAL_REGISTRATION_FEE {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Spiral registration fee too low: {} < {}",
+                        self.fee,
+                        Amount::new(crate::SPIRAL_REGISTRATION_FEE)
+                    )));
+                }
+            }
+            TransactionPayload::RegisterName { name } => {
+                crate::validate_name(name)?;
+                if self.fee.value() < crate::NAME_REGISTRATION_FEE {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Name registration fee too low: {} < {}",
+                        self.fee,
+                        Amount::new(crate::NAME_REGISTRATION_FEE)
+                    )));
+                }
+            }
+            TransactionPayload::TransferName { name } => crate::validate_name(name)?,
+            TransactionPayload::RotateValidatorKey { public_key, proof } => {
+                if self.to == self.from {
+                    return Err(SpiraChainError::InvalidTransaction(
+                        "A key rotation needs a new key".to_string(),
+                    ));
+                }
+                crate::verify_signed_message(
+                    &self.to,
+                    &key_rotation_message(&self.from),
+                    public_key,
+                    proof,
+                )?;
+            }
+            TransactionPayload::ContractCall { input } => {
+                if input.len() > crate::MAX_CONTRACT_INPUT_SIZE {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Call input too large: {} > {} bytes",
+                        input.len(),
+                        crate::MAX_CONTRACT_INPUT_SIZE
+                    )));
+                }
+            }
+        }
+
         Ok(())
     }
 
-    pub fn semantic_coherence(&self) -> f64 {
-        if self.semantic_vector.is_empty() {
-            return 0.0;
+    /// Serialized size of purpose, semantic commitment, entities and intent. The
+    /// vector itself is off-chain and not billed.
+    pub fn semantic_size(&self) -> usize {
+        bincode::serialized_size(&(
+            &self.purpose,
+            &self.semantic_commitment,
+            &self.entities,
+            &self.intent,
+        ))
+        .map(|size| size as usize)
+        .unwrap_or(usize::MAX)
+    }
+
+    /// MIN_TX_FEE plus SEMANTIC_FEE_PER_BYTE for every semantic byte beyond the free allowance
+    pub fn min_fee(&self) -> Amount {
+        let billable = self
+            .semantic_size()
+            .saturating_sub(crate::SEMANTIC_FREE_BYTES) as u128;
+        Amount::new(
+            crate::MIN_TX_FEE.saturating_add(billable.saturating_mul(crate::SEMANTIC_FEE_PER_BYTE)),
+        )
+    }
+
+    /// Byte-size limits on the semantic fields
+    pub fn validate_semantic_fields(&self) -> Result<()> {
+        if self.purpose.len() > crate::MAX_PURPOSE_SIZE {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Purpose too large: {} > {} bytes",
+                self.purpose.len(),
+                crate::MAX_PURPOSE_SIZE
+            )));
+        }
+
+        if self.semantic_vector.len() > crate::SEMANTIC_VECTOR_DIM {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Semantic vector too large: {} > {} dimensions",
+                self.semantic_vector.len(),
+                crate::SEMANTIC_VECTOR_DIM
+            )));
         }
 
-        let magnitude: f32 = self
-            .semantic_vector
+        if !self.semantic_vector.is_empty() && !self.verify_semantic_vector(&self.semantic_vector) {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Semantic vector does not match its commitment".to_string(),
+            ));
+        }
+
+        // Without a commitment nothing backs the score
+        if self.semantic_score > crate::SEMANTIC_SCORE_MAX
+            || (self.semantic_score != 0 && self.semantic_commitment.is_zero())
+        {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Invalid semantic score {}",
+                self.semantic_score
+            )));
+        }
+
+        if self.entities.len() > crate::MAX_ENTITIES {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Too many entities: {} > {}",
+                self.entities.len(),
+                crate::MAX_ENTITIES
+            )));
+        }
+
+        if let Some(entity) = self
+            .entities
             .iter()
-            .map(|x| x * x)
-            .sum::<f32>()
-            .sqrt();
-        if magnitude < 0.01 {
-            return 0.0;
+            .find(|entity| entity.name.len() > crate::MAX_ENTITY_NAME_SIZE)
+        {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Entity name too large: {} > {} bytes",
+                entity.name.len(),
+                crate::MAX_ENTITY_NAME_SIZE
+            )));
         }
 
-        magnitude.min(1.0) as f64
+        let size = self.semantic_size();
+        if size > crate::MAX_SEMANTIC_FIELDS_SIZE {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Semantic fields too large: {} > {} bytes",
+                size,
+                crate::MAX_SEMANTIC_FIELDS_SIZE
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Structural checks for the coinbase of the block at `height`
+    pub fn validate_coinbase(&self, height: u64) -> Result<()> {
+        match self.payload {
+            TransactionPayload::Coinbase {
+                height: coinbase_height,
+            } if coinbase_height == height => {}
+            TransactionPayload::Coinbase { .. } => {
+                return Err(SpiraChainError::InvalidTransaction(
+                    "Coinbase height does not match block height".to_string(),
+                ));
+            }
+            _ => {
+                return Err(SpiraChainError::InvalidTransaction(
+                    "Not a coinbase transaction".to_string(),
+                ));
+            }
+        }
+
+        if self.from != Address::zero() || self.to == Address::zero() {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Invalid coinbase address".to_string(),
+            ));
+        }
+
+        if !self.fee.is_zero() {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Coinbase cannot pay a fee".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Structural checks for the epoch summary of the block at `height`
+    pub fn validate_epoch_summary(&self, height: u64) -> Result<()> {
+        let TransactionPayload::EpochSummary { summary } = &self.payload else {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Not an epoch summary transaction".to_string(),
+            ));
+        };
+
+        if self.from != Address::zero()
+            || self.to != Address::zero()
+            || !self.amount.is_zero()
+            || !self.fee.is_zero()
+        {
+            return Err(SpiraChainError::InvalidTransaction(
+                "Epoch summary cannot move value".to_string(),
+            ));
+        }
+
+        summary.validate_at(height)
+    }
+
+    /// Coherence in [0, 1] from the on-chain score, whether or not the
+    /// vector is at hand
+    pub fn semantic_coherence(&self) -> f64 {
+        f64::from(self.semantic_score) / f64::from(crate::SEMANTIC_SCORE_MAX)
     }
 
     pub fn hash(&self) -> Hash {
@@ -186,6 +1009,56 @@ impl Transaction {
     }
 }
 
+/// Transaction encoding of releases before payloads. bincode ignores
+/// `#[serde(default)]`, so their bytes only decode with this layout. Accepted
+/// until the first hard fork, like zero fork ids.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyTransaction {
+    pub version: u64,
+    pub tx_hash: Hash,
+    pub pi_id: PiCoordinate,
+    pub from: Address,
+    pub to: Address,
+    pub amount: Amount,
+    pub fee: Amount,
+    pub timestamp: u64,
+    pub signature: Vec<u8>,
+    pub purpose: String,
+    pub semantic_vector: Vec<f32>,
+    pub entities: Vec<Entity>,
+    pub intent: Option<Intent>,
+    pub related_txs: Vec<Hash>,
+    pub spiral_position: Option<SpiralPosition>,
+    pub thread_id: Option<Hash>,
+    pub extra_data: HashMap<String, Vec<u8>>,
+}
+
+impl LegacyTransaction {
+    pub fn deserialize(data: &[u8]) -> Result<Self> {
+        bincode::deserialize(data).map_err(|e| SpiraChainError::SerializationError(e.to_string()))
+    }
+}
+
+/// Every legacy transaction is a plain transfer; its hash is unchanged
+impl From<LegacyTransaction> for Transaction {
+    fn from(tx: LegacyTransaction) -> Self {
+        let mut upgraded = Transaction::new_at(tx.from, tx.to, tx.amount, tx.fee, tx.timestamp);
+        upgraded.version = tx.version;
+        upgraded.tx_hash = tx.tx_hash;
+        upgraded.pi_id = tx.pi_id;
+        upgraded.signature = tx.signature;
+        upgraded.purpose = tx.purpose;
+        upgraded.semantic_vector = tx.semantic_vector;
+        upgraded.entities = tx.entities;
+        upgraded.intent = tx.intent;
+        upgraded.related_txs = tx.related_txs;
+        upgraded.spiral_position = tx.spiral_position;
+        upgraded.thread_id = tx.thread_id;
+        upgraded.extra_data = tx.extra_data;
+        upgraded
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,6 +1093,28 @@ mod tests {
         assert!(tx.validate().is_ok());
     }
 
+    #[test]
+    fn test_zero_amount_rules() {
+        let from = Address::new([1u8; 32]);
+        let to = Address::new([2u8; 32]);
+        let fee = Amount::from_millis(1);
+
+        let mut tx = Transaction::new(from, to, Amount::zero(), fee);
+        tx.signature = vec![0u8; 64];
+        assert!(tx.validate().is_err());
+
+        tx.payload = TransactionPayload::SetPayoutAddress;
+        assert!(tx.validate().is_ok());
+        tx.amount = Amount::qbt(1);
+        let error = tx.validate().unwrap_err().to_string();
+        assert!(error.contains("payout address change cannot carry value"), "{}", error);
+
+        tx.payload = TransactionPayload::ContractCall { input: Vec::new() };
+        assert!(tx.validate().is_ok());
+        tx.amount = Amount::zero();
+        assert!(tx.validate().is_ok());
+    }
+
     #[test]
     fn test_invalid_transaction_no_signature() {
         let from = Address::new([1u8; 32]);
@@ -231,4 +1126,273 @@ mod tests {
 
         assert!(tx.validate().is_err());
     }
+
+    #[test]
+    fn test_contract_address_derivation() {
+        let sender = Address::new([1u8; 32]);
+
+        assert_eq!(
+            derive_contract_address(&sender, 0),
+            derive_contract_address(&sender, 0)
+        );
+        assert_ne!(
+            derive_contract_address(&sender, 0),
+            derive_contract_address(&sender, 1)
+        );
+        assert_ne!(
+            derive_contract_address(&sender, 0),
+            derive_contract_address(&Address::new([2u8; 32]), 0)
+        );
+    }
+
+    #[test]
+    fn test_contract_deploy_validation() {
+        let from = Address::new([1u8; 32]);
+        let fee = Amount::from_millis(1);
+
+        let mut tx = Transaction::new_contract_deploy(
+            from,
+            vec![0x00, 0x61, 0x73, 0x6d],
+            vec![],
+            3,
+            Amount::zero(),
+            fee,
+        );
+        tx.signature = vec![0u8; 64];
+
+        assert_eq!(
+            tx.contract_address(),
+            Some(derive_contract_address(&from, 3))
+        );
+        assert_eq!(tx.to, derive_contract_address(&from, 3));
+        assert!(tx.validate().is_ok());
+
+        tx.to = Address::new([2u8; 32]);
+        assert!(tx.validate().is_err());
+
+        let mut empty =
+            Transaction::new_contract_deploy(from, vec![], vec![], 0, Amount::zero(), fee);
+        empty.signature = vec![0u8; 64];
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_payload_changes_hash() {
+        let from = Address::new([1u8; 32]);
+        let contract = Address::new([2u8; 32]);
+        let fee = Amount::from_millis(1);
+
+        let mut call_a =
+            Transaction::new_contract_call(from, contract, vec![1], Amount::zero(), fee);
+        let mut call_b = call_a
+            .clone()
+            .with_payload(TransactionPayload::ContractCall { input: vec![2] });
+        call_a.compute_hash();
+        call_b.compute_hash();
+
+        assert_ne!(call_a.tx_hash, call_b.tx_hash);
+    }
+
+    #[test]
+    fn test_coinbase_validation() {
+        let producer = Address::new([7u8; 32]);
+        let coinbase = Transaction::new_coinbase(producer, Amount::qbt(10), 42);
+
+        assert!(coinbase.is_coinbase());
+        assert!(coinbase.validate_coinbase(42).is_ok());
+        assert!(coinbase.validate_coinbase(43).is_err());
+        // Never accepted as a standalone transaction
+        assert!(coinbase.validate().is_err());
+
+        let mut with_fee = coinbase.clone();
+        with_fee.fee = Amount::from_millis(1);
+        assert!(with_fee.validate_coinbase(42).is_err());
+    }
+
+    #[test]
+    fn test_fork_id_binds_hash_and_signing_domain() {
+        let from = Address::new([1u8; 32]);
+        let to = Address::new([2u8; 32]);
+
+        let mut legacy = Transaction::new(from, to, Amount::qbt(1), Amount::from_millis(1));
+        legacy.timestamp = 1;
+        legacy.compute_hash();
+
+        let mut forked = legacy.clone().with_fork_id(crate::fork_id("testnet", 0));
+        forked.compute_hash();
+        assert_ne!(legacy.tx_hash, forked.tx_hash);
+        assert_ne!(legacy.signing_message(), forked.signing_message());
+
+        assert!(legacy.validate_fork_id("testnet", 10).is_ok());
+        assert!(forked.validate_fork_id("testnet", 10).is_ok());
+        assert!(forked.validate_fork_id("mainnet", 10).is_err());
+
+        let other_side = legacy
+            .clone()
+            .with_fork_id(crate::compute_fork_id(&Hash::new([9u8; 32]), 0));
+        assert!(other_side.validate_fork_id("testnet", 10).is_err());
+    }
+
+    #[test]
+    fn test_tagged_hash_waits_for_its_fork() {
+        let mut legacy = Transaction::new(
+            Address::new([1u8; 32]),
+            Address::new([2u8; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        );
+        legacy.timestamp = 1;
+        legacy.compute_hash();
+
+        let mut tagged = legacy.clone();
+        tagged.version = crate::TAGGED_HASH_VERSION;
+        tagged.compute_hash();
+        assert_ne!(legacy.tx_hash, tagged.tx_hash);
+
+        // Neither network schedules the fork yet
+        assert!(tagged.validate_fork_id("testnet", u64::MAX).is_err());
+        assert!(tagged.validate_fork_id("mainnet", u64::MAX).is_err());
+        assert!(legacy.validate_fork_id("testnet", u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_semantic_limits_and_fee_scaling() {
+        let from = Address::new([1u8; 32]);
+        let to = Address::new([2u8; 32]);
+
+        let mut tx = Transaction::new(from, to, Amount::qbt(1), Amount::from_millis(1))
+            .with_purpose("Monthly rent");
+        tx.signature = vec![0u8; 64];
+        assert_eq!(tx.min_fee(), Amount::new(crate::MIN_TX_FEE));
+        assert!(tx.validate().is_ok());
+
+        // A full embedding is off-chain: only its commitment is billed
+        tx = tx.with_semantic_vector(vec![0.1; crate::SEMANTIC_VECTOR_DIM]);
+        assert_eq!(tx.min_fee(), Amount::new(crate::MIN_TX_FEE));
+        assert!(tx.validate().is_ok());
+
+        // Long purposes are billed by size
+        tx.purpose = "x".repeat(crate::SEMANTIC_FREE_BYTES);
+        assert!(tx.min_fee() > Amount::new(crate::MIN_TX_FEE));
+        assert!(tx.validate().is_err());
+        tx.fee = tx.min_fee();
+        assert!(tx.validate().is_ok());
+
+        let mut oversized = tx.clone();
+        oversized.semantic_vector.push(0.1);
+        assert!(oversized.validate_semantic_fields().is_err());
+
+        let mut tampered = tx.clone();
+        tampered.semantic_vector[0] = 0.2;
+        assert!(tampered.validate_semantic_fields().is_err());
+
+        let mut long_purpose = tx.clone();
+        long_purpose.purpose = "x".repeat(crate::MAX_PURPOSE_SIZE + 1);
+        assert!(long_purpose.validate_semantic_fields().is_err());
+
+        let mut many_entities = tx;
+        many_entities.entities = vec![
+            Entity {
+                name: "Alice".to_string(),
+                entity_type: crate::EntityType::Person,
+                confidence: 0.9,
+            };
+            crate::MAX_ENTITIES + 1
+        ];
+        assert!(many_entities.validate_semantic_fields().is_err());
+    }
+    #[test]
+    fn test_semantic_vector_stays_off_chain() {
+        let from = Address::new([1u8; 32]);
+        let to = Address::new([2u8; 32]);
+        let mut tx = Transaction::new(from, to, Amount::qbt(1), Amount::from_millis(1))
+            .with_semantic_vector(vec![0.25; 8]);
+        tx.compute_hash();
+
+        let decoded = Transaction::deserialize(&tx.serialize()).unwrap();
+        assert!(decoded.semantic_vector.is_empty());
+        assert_eq!(decoded.semantic_commitment, tx.semantic_commitment);
+        // The score, which consensus reads, survives the binary encoding
+        assert_eq!(decoded.semantic_score, 7_071);
+        assert_eq!(decoded.semantic_coherence(), tx.semantic_coherence());
+        assert!(decoded.verify_semantic_vector(&tx.semantic_vector));
+        assert!(!decoded.verify_semantic_vector(&[0.5; 8]));
+
+        let json: Transaction = serde_json::from_slice(&serde_json::to_vec(&tx).unwrap()).unwrap();
+        assert_eq!(json.semantic_vector, tx.semantic_vector);
+
+        let mut without = decoded.clone();
+        without.semantic_commitment = Hash::zero();
+        without.compute_hash();
+        assert_ne!(without.tx_hash, tx.tx_hash);
+    }
+
+    #[test]
+    fn test_execute_at_window() {
+        let from = Address::new([1u8; 32]);
+        let mut plain = Transaction::new(from, from, Amount::qbt(1), Amount::from_millis(1));
+        plain.compute_hash();
+        let mut scheduled = plain.clone().with_execute_at(100);
+        scheduled.compute_hash();
+        assert_ne!(scheduled.tx_hash, plain.tx_hash);
+
+        assert_eq!(scheduled.schedule_status(99), ScheduleStatus::Waiting(100));
+        assert!(scheduled.validate_schedule(99).is_err());
+        assert!(scheduled.validate_schedule_admission(99).is_ok());
+        assert!(scheduled.validate_schedule(100).is_ok());
+        assert!(scheduled
+            .validate_schedule(100 + crate::SCHEDULED_TX_WINDOW)
+            .is_ok());
+        assert_eq!(
+            scheduled.schedule_status(101 + crate::SCHEDULED_TX_WINDOW),
+            ScheduleStatus::Expired(100 + crate::SCHEDULED_TX_WINDOW)
+        );
+        assert!(scheduled
+            .validate_schedule_admission(101 + crate::SCHEDULED_TX_WINDOW)
+            .is_err());
+        let far = plain.clone().with_execute_at(crate::MAX_SCHEDULE_LEAD + 200);
+        assert!(far.validate_schedule_admission(100).is_err());
+        assert!(plain.validate_schedule(0).is_ok());
+    }
+
+    #[test]
+    fn test_key_rotation_needs_proof_from_new_key() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let validator = Address::new([1u8; 32]);
+        let new_key = SigningKey::from_bytes(&[9u8; 32]);
+        let public_key = new_key.verifying_key().to_bytes();
+        let prove = |key: &SigningKey, validator: &Address| {
+            let preimage = crate::signed_message_preimage(&key_rotation_message(validator));
+            key.sign(&preimage).to_bytes().to_vec()
+        };
+
+        let mut tx = Transaction::new_rotate_validator_key(
+            validator,
+            public_key,
+            prove(&new_key, &validator),
+            Amount::from_millis(1),
+        );
+        tx.signature = vec![0u8; 64];
+        assert_eq!(tx.to, Address::new(*blake3::hash(&public_key).as_bytes()));
+        assert!(tx.validate().is_ok());
+
+        // A proof made for another validator, or by another key, is refused
+        let mut replayed = tx.clone();
+        replayed.payload = TransactionPayload::RotateValidatorKey {
+            public_key: public_key.to_vec(),
+            proof: prove(&new_key, &Address::new([2u8; 32])),
+        };
+        assert!(replayed.validate().is_err());
+        let mut wrong_key = tx.clone();
+        wrong_key.payload = TransactionPayload::RotateValidatorKey {
+            public_key: public_key.to_vec(),
+            proof: prove(&SigningKey::from_bytes(&[8u8; 32]), &validator),
+        };
+        assert!(wrong_key.validate().is_err());
+
+        let mut with_value = tx;
+        with_value.amount = Amount::qbt(1);
+        assert!(with_value.validate().is_err());
+    }
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Hash([u8; 32]);
 
 impl Hash {
@@ -140,7 +140,9 @@ impl PiCoordinate {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Serialized as 32 bytes. Human-readable formats also accept a hex string,
+/// checked against its checksum, see [`Address::from_checksummed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct Address([u8; 32]);
 
 impl Address {
@@ -168,6 +170,95 @@ impl Address {
     pub fn to_vec(&self) -> Vec<u8> {
         self.0.to_vec()
     }
+
+    /// "0x" and the hex digits, letters upper-cased where the matching
+    /// nibble of blake3(lowercase hex) is 8 or more
+    pub fn to_checksummed(&self) -> String {
+        let lower = hex::encode(self.0);
+        let digest = blake3::hash(lower.as_bytes());
+        let mut checksummed = String::with_capacity(2 + lower.len());
+        checksummed.push_str("0x");
+        for (i, c) in lower.chars().enumerate() {
+            if checksum_nibble(digest.as_bytes(), i) >= 8 {
+                checksummed.push(c.to_ascii_uppercase());
+            } else {
+                checksummed.push(c);
+            }
+        }
+        checksummed
+    }
+
+    /// Parse a hex address. Mixed case must match [`Address::to_checksummed`];
+    /// all-lowercase and all-uppercase addresses carry no checksum.
+    pub fn from_checksummed(s: &str) -> Result<Self, String> {
+        let hex_str = s.strip_prefix("0x").unwrap_or(s);
+        if hex_str.len() != 64 {
+            return Err(format!(
+                "expected 64 hex digits, got {}",
+                hex_str.chars().count()
+            ));
+        }
+        let address: Address = hex_str.parse()?;
+
+        let has_lower = hex_str.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = hex_str.chars().any(|c| c.is_ascii_uppercase());
+        if has_lower && has_upper && address.to_checksummed()[2..] != *hex_str {
+            return Err(format!(
+                "checksum mismatch, expected {}",
+                address.to_checksummed()
+            ));
+        }
+        Ok(address)
+    }
+}
+
+fn checksum_nibble(digest: &[u8; 32], index: usize) -> u8 {
+    let byte = digest[index / 2];
+    if index.is_multiple_of(2) {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AddressVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for AddressVisitor {
+            type Value = Address;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("32 bytes or a hex address")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Address, E> {
+                Address::from_checksummed(s).map_err(E::custom)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Address, A::Error> {
+                let mut bytes = [0u8; 32];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                }
+                if seq.next_element::<serde::de::IgnoredAny>()?.is_some() {
+                    return Err(serde::de::Error::invalid_length(33, &self));
+                }
+                Ok(Address(bytes))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(AddressVisitor)
+        } else {
+            <[u8; 32]>::deserialize(deserializer).map(Address)
+        }
+    }
 }
 
 impl fmt::Display for Address {
@@ -232,6 +323,78 @@ impl Amount {
     pub fn checked_mul(&self, factor: u64) -> Option<Amount> {
         self.0.checked_mul(factor as u128).map(Amount)
     }
+
+    /// `bps` basis points of the amount, rounded down; `bps` is at most 10_000
+    pub fn basis_points(&self, bps: u128) -> Amount {
+        Amount(self.0 / 10_000 * bps + self.0 % 10_000 * bps / 10_000)
+    }
+
+    /// Exact QBT value without unit or trailing zeros, e.g. `12.5` or `3`
+    pub fn to_qbt_string(&self) -> String {
+        let unit = 10u128.pow(crate::TOKEN_DECIMALS as u32);
+        let fraction = format!(
+            "{:0width$}",
+            self.0 % unit,
+            width = crate::TOKEN_DECIMALS as usize
+        );
+        let fraction = fraction.trim_end_matches('0');
+
+        if fraction.is_empty() {
+            (self.0 / unit).to_string()
+        } else {
+            format!("{}.{}", self.0 / unit, fraction)
+        }
+    }
+}
+
+/// Parses a decimal QBT value such as `12.5`, `0.001 QBT` or `3qbt`, exactly.
+/// More fractional digits than the token has, signs and exponents are rejected.
+impl std::str::FromStr for Amount {
+    type Err = crate::SpiraChainError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let invalid =
+            |reason: &str| crate::SpiraChainError::InvalidAmount(format!("{:?}: {}", s, reason));
+
+        let mut number = s.trim();
+        if number.len() >= 3 && number[number.len() - 3..].eq_ignore_ascii_case("qbt") {
+            number = number[..number.len() - 3].trim_end();
+        }
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid("no digits"));
+        }
+        if !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid("expected a decimal number"));
+        }
+        if fraction.len() > crate::TOKEN_DECIMALS as usize {
+            return Err(invalid("too many decimal places"));
+        }
+
+        let unit = 10u128.pow(crate::TOKEN_DECIMALS as u32);
+        let whole: u128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| invalid("too large"))?
+        };
+        let fraction: u128 = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<u128>().map_err(|_| invalid("too large"))?
+                * 10u128.pow((crate::TOKEN_DECIMALS as usize - fraction.len()) as u32)
+        };
+
+        whole
+            .checked_mul(unit)
+            .and_then(|units| units.checked_add(fraction))
+            .map(Amount)
+            .ok_or_else(|| invalid("too large"))
+    }
 }
 
 impl fmt::Display for Amount {
@@ -271,7 +434,7 @@ impl fmt::Display for SpiralType {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EntityType {
     Person = 0,
     Organization = 1,
@@ -288,3 +451,95 @@ pub enum IntentType {
     Governance = 3,
     Social = 4,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_checksum() {
+        let address = Address::new(*blake3::hash(b"alice").as_bytes());
+        let checksummed = address.to_checksummed();
+        assert_ne!(checksummed, checksummed.to_lowercase());
+        assert_eq!(Address::from_checksummed(&checksummed), Ok(address));
+        assert_eq!(Address::from_checksummed(&address.to_string()), Ok(address));
+        assert_eq!(
+            Address::from_checksummed(&checksummed[2..].to_uppercase()),
+            Ok(address)
+        );
+
+        // One letter with the wrong case
+        let i = checksummed.rfind(|c: char| c.is_ascii_uppercase()).unwrap();
+        let mut typo = checksummed.clone();
+        typo.replace_range(i..=i, &checksummed[i..=i].to_lowercase());
+        assert!(Address::from_checksummed(&typo)
+            .unwrap_err()
+            .contains("checksum mismatch"));
+        assert!(Address::from_checksummed("0x1234").is_err());
+
+        // JSON takes the byte array or a hex string, bincode is unchanged
+        let from_bytes: Address =
+            serde_json::from_str(&serde_json::to_string(&address).unwrap()).unwrap();
+        let from_hex: Address = serde_json::from_str(&format!("\"{}\"", checksummed)).unwrap();
+        assert_eq!(from_bytes, address);
+        assert_eq!(from_hex, address);
+        assert!(serde_json::from_str::<Address>(&format!("\"{}\"", typo)).is_err());
+        assert!(serde_json::from_str::<Address>("[1, 2, 3]").is_err());
+        let encoded = bincode::serialize(&address).unwrap();
+        assert_eq!(encoded.len(), 32);
+        assert_eq!(bincode::deserialize::<Address>(&encoded).unwrap(), address);
+    }
+
+    #[test]
+    fn test_amount_parses_exactly() {
+        assert_eq!(
+            "12.5 QBT".parse::<Amount>().unwrap(),
+            Amount::from_millis(12_500)
+        );
+        assert_eq!("0.001".parse::<Amount>().unwrap(), Amount::from_millis(1));
+        assert_eq!("3qbt".parse::<Amount>().unwrap(), Amount::qbt(3));
+        assert_eq!(".5".parse::<Amount>().unwrap(), Amount::from_millis(500));
+        assert_eq!(
+            "0.000000000000000001".parse::<Amount>().unwrap(),
+            Amount::new(1)
+        );
+        // Beyond f64 precision
+        assert_eq!(
+            "123456789.123456789123456789".parse::<Amount>().unwrap(),
+            Amount::new(123_456_789_123_456_789_123_456_789)
+        );
+    }
+
+    #[test]
+    fn test_amount_rejects_malformed_input() {
+        for input in [
+            "",
+            ".",
+            "QBT",
+            "-1",
+            "+1",
+            "1e18",
+            "1.2.3",
+            "0.0000000000000000001",
+            "340282366920938463464",
+        ] {
+            assert!(input.parse::<Amount>().is_err(), "{:?} parsed", input);
+        }
+    }
+
+    #[test]
+    fn test_amount_qbt_string_roundtrip() {
+        for amount in [
+            Amount::zero(),
+            Amount::new(1),
+            Amount::from_millis(12_500),
+            Amount::qbt(21_000_000),
+            Amount::new(u128::MAX),
+        ] {
+            let text = amount.to_qbt_string();
+            assert_eq!(text.parse::<Amount>().unwrap(), amount);
+        }
+        assert_eq!(Amount::from_millis(12_500).to_qbt_string(), "12.5");
+        assert_eq!(Amount::qbt(3).to_qbt_string(), "3");
+    }
+}
@@ -0,0 +1,90 @@
+// Validator set changes
+// Joins, departures, slashings and key rotations as the node observes them,
+// grouped by diversity epoch so wallets and staking dashboards can page
+// through the history and subscribe to new changes.
+
+use crate::{diversity_epoch, Address, Amount};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidatorChange {
+    /// Entered the slot rotation
+    Joined,
+    /// Left the slot rotation
+    Left,
+    Slashed {
+        reason: String,
+        amount: Amount,
+    },
+    /// Signs with a new key from now on; keys are raw public key bytes
+    KeyRotated {
+        old_key: Vec<u8>,
+        new_key: Vec<u8>,
+    },
+}
+
+impl ValidatorChange {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValidatorChange::Joined => "joined",
+            ValidatorChange::Left => "left",
+            ValidatorChange::Slashed { .. } => "slashed",
+            ValidatorChange::KeyRotated { .. } => "key_rotated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSetChange {
+    pub validator: Address,
+    pub change: ValidatorChange,
+    /// Chain height when the node observed the change
+    pub height: u64,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
+
+impl ValidatorSetChange {
+    /// A change observed now, at chain height `height`
+    pub fn new(validator: Address, change: ValidatorChange, height: u64) -> Self {
+        Self {
+            validator,
+            change,
+            height,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        diversity_epoch(self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DIVERSITY_EPOCH_BLOCKS;
+
+    #[test]
+    fn test_change_epoch_and_encoding() {
+        let change = ValidatorSetChange::new(
+            Address::new([7u8; 32]),
+            ValidatorChange::Slashed {
+                reason: "DoubleSigning".to_string(),
+                amount: Amount::new(5),
+            },
+            DIVERSITY_EPOCH_BLOCKS * 3 + 1,
+        );
+        assert_eq!(change.epoch(), 3);
+        assert_eq!(change.change.name(), "slashed");
+
+        let bytes = bincode::serialize(&change).unwrap();
+        assert_eq!(
+            bincode::deserialize::<ValidatorSetChange>(&bytes).unwrap(),
+            change
+        );
+    }
+}
@@ -0,0 +1,55 @@
+// XMSS signing latency
+// Signing walks the tree with treehash instead of rebuilding it, so the cost
+// of a signature grows with the height while keygen grows with 2^height.
+// Every signature of a key is timed, including those that cross into a new
+// subtree, so the average covers the worst case of a block producer's run.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use spirachain_crypto::XmssKeyPair;
+use std::time::{Duration, Instant};
+
+const HEIGHTS: [usize; 3] = [8, 10, 12];
+
+fn bench_xmss_sign(c: &mut Criterion) {
+    let mut group = c.benchmark_group("xmss_sign");
+    for height in HEIGHTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(height),
+            &height,
+            |b, &height| {
+                let mut keypair = XmssKeyPair::generate_with_height(height).unwrap();
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        if keypair.remaining_signatures() == 0 {
+                            keypair = XmssKeyPair::generate_with_height(height).unwrap();
+                        }
+                        let start = Instant::now();
+                        black_box(keypair.sign(b"block header hash").unwrap());
+                        elapsed += start.elapsed();
+                    }
+                    elapsed
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_xmss_keygen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("xmss_keygen");
+    group.sample_size(10);
+    for height in HEIGHTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(height),
+            &height,
+            |b, &height| {
+                b.iter(|| black_box(XmssKeyPair::generate_with_height(height).unwrap()));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_xmss_sign, bench_xmss_keygen);
+criterion_main!(benches);
@@ -1,3 +1,4 @@
+use crate::HashDomain;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use spirachain_core::{Address, Result, SpiraChainError};
@@ -158,7 +159,7 @@ impl FractalKeyRotation {
         let pi_index = (self.rotation_counter as usize) % self.pi_digits_cache.len();
         let pi_digit = self.pi_digits_cache[pi_index];
 
-        let mut hasher = blake3::Hasher::new();
+        let mut hasher = HashDomain::KeyRotation.hasher();
         hasher.update(previous_key);
         hasher.update(&(self.rotation_counter * pi_digit as u64).to_be_bytes());
         hasher.update(&[pi_digit]);
@@ -182,7 +183,7 @@ impl FractalKeyRotation {
         let pi_index = (index as usize) % self.pi_digits_cache.len();
         let pi_digit = self.pi_digits_cache[pi_index];
 
-        let mut hasher = blake3::Hasher::new();
+        let mut hasher = HashDomain::KeyRotation.hasher();
         hasher.update(base_key);
         hasher.update(&(index * pi_digit as u64).to_be_bytes());
         hasher.update(&[pi_digit]);
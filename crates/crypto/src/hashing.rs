@@ -0,0 +1,150 @@
+// Domain-separated hashing
+// Every tagged domain hashes in blake3's derive-key mode under its own context
+// string, so preimages from two domains can never collide, and none of them can
+// collide with a plain blake3 hash either: the mode flag enters every
+// compression. Block headers, transactions and the transaction merkle tree
+// were committed in plain mode up to protocol v2 (`TAGGED_HASH_VERSION`); the
+// helpers here always hash them tagged, matching consensus from that fork on.
+
+use spirachain_core::{BlockHeader, Hash, Transaction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashDomain {
+    BlockHeader,
+    Transaction,
+    MerkleLeaf,
+    MerkleNode,
+    /// XMSS one-time key derived from the PRF seed
+    XmssKey,
+    /// One step of a Winternitz chain
+    XmssChain,
+    XmssMessage,
+    KeyRotation,
+    McElieceKey,
+}
+
+impl HashDomain {
+    /// Context string fed to blake3; never change one once it has been used
+    pub fn context(self) -> &'static str {
+        match self {
+            HashDomain::BlockHeader => spirachain_core::BLOCK_HEADER_CONTEXT,
+            HashDomain::Transaction => spirachain_core::TRANSACTION_CONTEXT,
+            HashDomain::MerkleLeaf => spirachain_core::MERKLE_LEAF_CONTEXT,
+            HashDomain::MerkleNode => spirachain_core::MERKLE_NODE_CONTEXT,
+            HashDomain::XmssKey => "spirachain 2025-01 xmss wots key",
+            HashDomain::XmssChain => "spirachain 2025-01 xmss wots chain",
+            HashDomain::XmssMessage => "spirachain 2025-01 xmss message",
+            HashDomain::KeyRotation => "spirachain 2025-01 pi key rotation",
+            HashDomain::McElieceKey => "spirachain 2025-01 mceliece key stream",
+        }
+    }
+
+    pub fn hasher(self) -> blake3::Hasher {
+        blake3::Hasher::new_derive_key(self.context())
+    }
+}
+
+pub fn hash_with_domain(domain: HashDomain, data: &[u8]) -> Hash {
+    domain.hasher().update(data).finalize().into()
+}
+
+/// Header hash in the [`HashDomain::BlockHeader`] domain, the block hash of
+/// tagged headers
+pub fn hash_block_header(header: &BlockHeader) -> Hash {
+    Hash::new(header.view().tagged_hash())
+}
+
+/// Transaction hash in the [`HashDomain::Transaction`] domain, the hash
+/// committed for tagged transactions
+pub fn hash_tx(tx: &Transaction) -> Hash {
+    tx.tagged_hash()
+}
+
+pub fn hash_leaf(data: &[u8]) -> Hash {
+    hash_with_domain(HashDomain::MerkleLeaf, data)
+}
+
+pub fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    HashDomain::MerkleNode
+        .hasher()
+        .update(left.as_bytes())
+        .update(right.as_bytes())
+        .finalize()
+        .into()
+}
+
+/// Root over already hashed leaves, an odd node out paired with itself
+pub fn merkle_root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return Hash::zero();
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| hash_node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+    }
+    level[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Address, Amount, Block, TAGGED_HASH_VERSION};
+
+    #[test]
+    fn test_domains_never_share_outputs() {
+        let left = Hash::from([1u8; 32]);
+        let right = Hash::from([2u8; 32]);
+        let mut concatenated = left.to_vec();
+        concatenated.extend_from_slice(right.as_bytes());
+
+        let node = hash_node(&left, &right);
+        assert_ne!(node, hash_leaf(&concatenated));
+        assert_ne!(node, crate::blake3_hash(&concatenated));
+        assert_ne!(
+            hash_with_domain(HashDomain::XmssChain, b"x"),
+            hash_with_domain(HashDomain::XmssMessage, b"x")
+        );
+        assert_eq!(merkle_root(&[left, right]), node);
+        assert_eq!(merkle_root(&[left]), left);
+
+        let mut tx = Transaction::new(
+            Address::new([1; 32]),
+            Address::new([2; 32]),
+            Amount::qbt(1),
+            Amount::from_millis(1),
+        );
+        tx.compute_hash();
+        assert_ne!(hash_tx(&tx), tx.tx_hash);
+        tx.version = TAGGED_HASH_VERSION;
+        tx.compute_hash();
+        assert_eq!(hash_tx(&tx), tx.tx_hash);
+    }
+
+    #[test]
+    fn test_tagged_consensus_hashes_match_domains() {
+        let mut header = Block::new(Hash::zero(), 1).header;
+        assert_ne!(hash_block_header(&header), header.hash());
+        header.version = TAGGED_HASH_VERSION;
+        assert_eq!(hash_block_header(&header), header.hash());
+
+        let leaves = [Hash::from([1u8; 32]), Hash::from([2u8; 32])];
+        let leaf_hashes: Vec<Hash> = leaves
+            .iter()
+            .map(|leaf| hash_leaf(leaf.as_bytes()))
+            .collect();
+        let left = spirachain_core::merkle_leaf(leaves[0].as_bytes(), TAGGED_HASH_VERSION);
+        let right = spirachain_core::merkle_leaf(leaves[1].as_bytes(), TAGGED_HASH_VERSION);
+        assert_eq!(
+            merkle_root(&leaf_hashes),
+            Hash::new(spirachain_core::merkle_node(
+                &left,
+                &right,
+                TAGGED_HASH_VERSION
+            ))
+        );
+    }
+}
@@ -0,0 +1,439 @@
+// Key import/export in formats external tools understand
+// Ed25519 keys use the standard SPKI/PKCS#8 PEM and RFC 8037 JWK encodings;
+// post-quantum keys have no settled standard yet, so they use SpiraChain PEM
+// labels and "AKP" JWKs carrying the raw key bytes
+
+use crate::{
+    KeyPair, KyberPublicKey, PublicKey, XmssPublicKey, KYBER_PUBLIC_KEY_SIZE, KYBER_SECRET_KEY_SIZE,
+};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use std::fmt;
+use std::str::FromStr;
+
+/// DER prefix of an Ed25519 SubjectPublicKeyInfo, followed by the 32 key bytes
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+/// DER prefix of an Ed25519 PKCS#8 PrivateKeyInfo, followed by the 32 seed bytes
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+const PEM_LINE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    Ed25519Public,
+    Ed25519Secret,
+    XmssPublic,
+    KyberPublic,
+    KyberSecret,
+}
+
+impl KeyKind {
+    pub const ALL: [KeyKind; 5] = [
+        KeyKind::Ed25519Public,
+        KeyKind::Ed25519Secret,
+        KeyKind::XmssPublic,
+        KeyKind::KyberPublic,
+        KeyKind::KyberSecret,
+    ];
+
+    pub fn is_secret(&self) -> bool {
+        matches!(self, KeyKind::Ed25519Secret | KeyKind::KyberSecret)
+    }
+
+    fn key_size(&self) -> usize {
+        match self {
+            KeyKind::Ed25519Public | KeyKind::Ed25519Secret => 32,
+            KeyKind::XmssPublic => 64,
+            KeyKind::KyberPublic => KYBER_PUBLIC_KEY_SIZE,
+            KeyKind::KyberSecret => KYBER_SECRET_KEY_SIZE,
+        }
+    }
+
+    fn pem_label(&self) -> &'static str {
+        match self {
+            KeyKind::Ed25519Public => "PUBLIC KEY",
+            KeyKind::Ed25519Secret => "PRIVATE KEY",
+            KeyKind::XmssPublic => "SPIRACHAIN XMSS PUBLIC KEY",
+            KeyKind::KyberPublic => "SPIRACHAIN KYBER1024 PUBLIC KEY",
+            KeyKind::KyberSecret => "SPIRACHAIN KYBER1024 PRIVATE KEY",
+        }
+    }
+
+    /// JWK `alg` of the post-quantum kinds
+    fn jwk_alg(&self) -> Option<&'static str> {
+        match self {
+            KeyKind::XmssPublic => Some("SPIRA-XMSS"),
+            KeyKind::KyberPublic | KeyKind::KyberSecret => Some("KYBER1024"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KeyKind::Ed25519Public => "ed25519-public",
+            KeyKind::Ed25519Secret => "ed25519-secret",
+            KeyKind::XmssPublic => "xmss-public",
+            KeyKind::KyberPublic => "kyber-public",
+            KeyKind::KyberSecret => "kyber-secret",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for KeyKind {
+    type Err = SpiraChainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        KeyKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| SpiraChainError::CryptoError(format!("Unknown key kind: {}", s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    Hex,
+    Pem,
+    Jwk,
+}
+
+impl FromStr for KeyFormat {
+    type Err = SpiraChainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "hex" => Ok(KeyFormat::Hex),
+            "pem" => Ok(KeyFormat::Pem),
+            "jwk" => Ok(KeyFormat::Jwk),
+            other => Err(SpiraChainError::CryptoError(format!(
+                "Unknown key format: {} (expected hex, pem or jwk)",
+                other
+            ))),
+        }
+    }
+}
+
+/// JSON Web Key; `x`/`d` for Ed25519 (RFC 8037), `pub`/`priv` for post-quantum keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+    #[serde(default, rename = "pub", skip_serializing_if = "Option::is_none")]
+    pub public: Option<String>,
+    #[serde(default, rename = "priv", skip_serializing_if = "Option::is_none")]
+    pub private: Option<String>,
+}
+
+/// Raw key material tagged with its kind, convertible between formats
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExportedKey {
+    kind: KeyKind,
+    bytes: Vec<u8>,
+}
+
+impl ExportedKey {
+    pub fn new(kind: KeyKind, bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() != kind.key_size() {
+            return Err(SpiraChainError::CryptoError(format!(
+                "Invalid {} key size: {} (expected {})",
+                kind,
+                bytes.len(),
+                kind.key_size()
+            )));
+        }
+        Ok(Self { kind, bytes })
+    }
+
+    pub fn ed25519_public(key: &PublicKey) -> Self {
+        Self {
+            kind: KeyKind::Ed25519Public,
+            bytes: key.to_vec(),
+        }
+    }
+
+    pub fn ed25519_secret(keypair: &KeyPair) -> Self {
+        Self {
+            kind: KeyKind::Ed25519Secret,
+            bytes: keypair.secret_key().as_bytes().to_vec(),
+        }
+    }
+
+    pub fn xmss_public(key: &XmssPublicKey) -> Self {
+        Self {
+            kind: KeyKind::XmssPublic,
+            bytes: key.to_vec(),
+        }
+    }
+
+    pub fn kyber_public(key: &KyberPublicKey) -> Self {
+        Self {
+            kind: KeyKind::KyberPublic,
+            bytes: key.to_vec(),
+        }
+    }
+
+    pub fn kind(&self) -> KeyKind {
+        self.kind
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Ed25519 keypair of an imported secret key
+    pub fn to_keypair(&self) -> Result<KeyPair> {
+        match self.kind {
+            KeyKind::Ed25519Secret => {
+                let mut secret = [0u8; 32];
+                secret.copy_from_slice(&self.bytes);
+                KeyPair::from_secret(secret)
+            }
+            kind => Err(SpiraChainError::CryptoError(format!(
+                "A {} key is not an Ed25519 secret key",
+                kind
+            ))),
+        }
+    }
+
+    pub fn encode(&self, format: KeyFormat) -> String {
+        match format {
+            KeyFormat::Hex => hex::encode(&self.bytes),
+            KeyFormat::Pem => self.to_pem(),
+            KeyFormat::Jwk => serde_json::to_string_pretty(&self.to_jwk()).unwrap_or_default(),
+        }
+    }
+
+    /// Parse `input`; hex carries no kind, so it needs `kind`
+    pub fn decode(input: &str, format: KeyFormat, kind: Option<KeyKind>) -> Result<Self> {
+        match format {
+            KeyFormat::Hex => {
+                let kind = kind.ok_or_else(|| {
+                    SpiraChainError::CryptoError("Hex keys need an explicit key kind".to_string())
+                })?;
+                let bytes = hex::decode(input.trim().trim_start_matches("0x"))
+                    .map_err(|e| SpiraChainError::CryptoError(format!("Invalid hex: {}", e)))?;
+                Self::new(kind, bytes)
+            }
+            KeyFormat::Pem => Self::from_pem(input),
+            KeyFormat::Jwk => {
+                let jwk: Jwk = serde_json::from_str(input)
+                    .map_err(|e| SpiraChainError::CryptoError(format!("Invalid JWK: {}", e)))?;
+                Self::from_jwk(&jwk)
+            }
+        }
+    }
+
+    pub fn to_pem(&self) -> String {
+        let der = match self.kind {
+            KeyKind::Ed25519Public => [&ED25519_SPKI_PREFIX[..], &self.bytes].concat(),
+            KeyKind::Ed25519Secret => [&ED25519_PKCS8_PREFIX[..], &self.bytes].concat(),
+            _ => self.bytes.clone(),
+        };
+        let body = STANDARD.encode(der);
+        let label = self.kind.pem_label();
+
+        let mut pem = format!("-----BEGIN {}-----\n", label);
+        for line in body.as_bytes().chunks(PEM_LINE) {
+            pem.push_str(&String::from_utf8_lossy(line));
+            pem.push('\n');
+        }
+        pem.push_str(&format!("-----END {}-----\n", label));
+        pem
+    }
+
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let invalid =
+            |reason: &str| SpiraChainError::CryptoError(format!("Invalid PEM: {}", reason));
+
+        let pem = pem.trim();
+        let label = pem
+            .strip_prefix("-----BEGIN ")
+            .and_then(|rest| rest.split_once("-----"))
+            .map(|(label, _)| label)
+            .ok_or_else(|| invalid("missing BEGIN line"))?;
+        let kind = KeyKind::ALL
+            .into_iter()
+            .find(|kind| kind.pem_label() == label)
+            .ok_or_else(|| invalid(&format!("unsupported label {}", label)))?;
+
+        let body: String = pem
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.starts_with("-----END"))
+            .collect();
+        let der = STANDARD
+            .decode(body.trim())
+            .map_err(|e| invalid(&e.to_string()))?;
+
+        let prefix: &[u8] = match kind {
+            KeyKind::Ed25519Public => &ED25519_SPKI_PREFIX,
+            KeyKind::Ed25519Secret => &ED25519_PKCS8_PREFIX,
+            _ => &[],
+        };
+        let bytes = der
+            .strip_prefix(prefix)
+            .ok_or_else(|| invalid("not an Ed25519 key"))?;
+        Self::new(kind, bytes.to_vec())
+    }
+
+    pub fn to_jwk(&self) -> Jwk {
+        let value = Some(URL_SAFE_NO_PAD.encode(&self.bytes));
+        let mut jwk = Jwk {
+            kty: "AKP".to_string(),
+            crv: None,
+            alg: self.kind.jwk_alg().map(str::to_string),
+            x: None,
+            d: None,
+            public: None,
+            private: None,
+        };
+
+        match self.kind {
+            KeyKind::Ed25519Public | KeyKind::Ed25519Secret => {
+                jwk.kty = "OKP".to_string();
+                jwk.crv = Some("Ed25519".to_string());
+                if self.kind == KeyKind::Ed25519Secret {
+                    // RFC 8037 private JWKs carry the public key too
+                    jwk.x = self
+                        .to_keypair()
+                        .ok()
+                        .map(|keypair| URL_SAFE_NO_PAD.encode(keypair.public_key().as_bytes()));
+                    jwk.d = value;
+                } else {
+                    jwk.x = value;
+                }
+            }
+            KeyKind::KyberSecret => jwk.private = value,
+            KeyKind::XmssPublic | KeyKind::KyberPublic => jwk.public = value,
+        }
+        jwk
+    }
+
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self> {
+        let decode = |value: &str| {
+            URL_SAFE_NO_PAD
+                .decode(value)
+                .map_err(|e| SpiraChainError::CryptoError(format!("Invalid JWK value: {}", e)))
+        };
+
+        let (kind, value) = match (jwk.kty.as_str(), jwk.crv.as_deref(), jwk.alg.as_deref()) {
+            ("OKP", Some("Ed25519"), _) => match (&jwk.d, &jwk.x) {
+                (Some(d), _) => (KeyKind::Ed25519Secret, d),
+                (None, Some(x)) => (KeyKind::Ed25519Public, x),
+                (None, None) => {
+                    return Err(SpiraChainError::CryptoError(
+                        "Ed25519 JWK has neither x nor d".to_string(),
+                    ))
+                }
+            },
+            ("AKP", _, Some(alg)) => {
+                let kind = match (alg, &jwk.private) {
+                    ("SPIRA-XMSS", _) => KeyKind::XmssPublic,
+                    ("KYBER1024", Some(_)) => KeyKind::KyberSecret,
+                    ("KYBER1024", None) => KeyKind::KyberPublic,
+                    (other, _) => {
+                        return Err(SpiraChainError::CryptoError(format!(
+                            "Unsupported JWK alg: {}",
+                            other
+                        )))
+                    }
+                };
+                let value = match kind {
+                    KeyKind::KyberSecret => jwk.private.as_ref(),
+                    _ => jwk.public.as_ref(),
+                };
+                let value = value.ok_or_else(|| {
+                    SpiraChainError::CryptoError(format!("{} JWK has no key value", alg))
+                })?;
+                (kind, value)
+            }
+            (kty, _, _) => {
+                return Err(SpiraChainError::CryptoError(format!(
+                    "Unsupported JWK key type: {}",
+                    kty
+                )))
+            }
+        };
+
+        Self::new(kind, decode(value)?)
+    }
+}
+
+impl fmt::Debug for ExportedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ExportedKey");
+        debug.field("kind", &self.kind);
+        if self.kind.is_secret() {
+            debug.field("bytes", &"[REDACTED]");
+        } else {
+            debug.field("bytes", &hex::encode(&self.bytes));
+        }
+        debug.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_roundtrips_through_every_format() {
+        let keypair = KeyPair::generate();
+        let secret = ExportedKey::ed25519_secret(&keypair);
+        let public = ExportedKey::ed25519_public(keypair.public_key());
+
+        for key in [&secret, &public] {
+            for format in [KeyFormat::Pem, KeyFormat::Jwk] {
+                let encoded = key.encode(format);
+                assert_eq!(&ExportedKey::decode(&encoded, format, None).unwrap(), key);
+            }
+            let hex = key.encode(KeyFormat::Hex);
+            assert!(ExportedKey::decode(&hex, KeyFormat::Hex, None).is_err());
+            assert_eq!(
+                &ExportedKey::decode(&hex, KeyFormat::Hex, Some(key.kind())).unwrap(),
+                key
+            );
+        }
+
+        assert!(public
+            .to_pem()
+            .starts_with("-----BEGIN PUBLIC KEY-----\nMCowBQYDK2VwAyEA"));
+        assert_eq!(
+            secret.to_jwk().x,
+            public.to_jwk().x,
+            "private JWK must carry the public key"
+        );
+        assert_eq!(
+            secret.to_keypair().unwrap().to_address(),
+            keypair.to_address()
+        );
+    }
+
+    #[test]
+    fn test_post_quantum_public_keys() {
+        let xmss = ExportedKey::new(KeyKind::XmssPublic, vec![7u8; 64]).unwrap();
+        let jwk = xmss.to_jwk();
+        assert_eq!(jwk.kty, "AKP");
+        assert_eq!(ExportedKey::from_jwk(&jwk).unwrap(), xmss);
+        assert_eq!(ExportedKey::from_pem(&xmss.to_pem()).unwrap(), xmss);
+        assert!(XmssPublicKey::from_bytes(xmss.as_bytes()).is_ok());
+
+        assert!(ExportedKey::new(KeyKind::XmssPublic, vec![7u8; 63]).is_err());
+    }
+}
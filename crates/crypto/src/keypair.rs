@@ -2,7 +2,7 @@ use anyhow::anyhow;
 use ed25519_dalek::{Signer, Verifier};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
-use spirachain_core::{Address, Result};
+use spirachain_core::{signed_message_preimage, Address, Result, SignedMessage, Transaction};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct KeyPair {
@@ -58,6 +58,26 @@ impl KeyPair {
         PublicKey::verify(&self.public_key, message, signature)
     }
 
+    /// Sign `tx`, whose hash is already computed, and attach the public key
+    /// nodes check the signature against
+    pub fn sign_transaction(&self, tx: &mut Transaction) {
+        tx.signature = self.sign(&tx.signing_message());
+        tx.extra_data.insert(
+            spirachain_core::SENDER_PUBLIC_KEY.to_string(),
+            self.public_key.0.to_vec(),
+        );
+    }
+
+    /// Off-chain signature over the prefixed message, see [`signed_message_preimage`]
+    pub fn sign_message(&self, message: &str) -> SignedMessage {
+        SignedMessage {
+            address: self.to_address().to_string(),
+            public_key: hex::encode(self.public_key.0),
+            message: message.to_string(),
+            signature: hex::encode(self.sign(&signed_message_preimage(message.as_bytes()))),
+        }
+    }
+
     pub fn to_address(&self) -> Address {
         let hash = blake3::hash(&self.public_key.0);
         Address::new(*hash.as_bytes())
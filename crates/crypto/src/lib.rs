@@ -1,5 +1,7 @@
 pub mod dkg;
 pub mod hash;
+pub mod hashing;
+pub mod key_format;
 pub mod keypair;
 pub mod kyber;
 pub mod mceliece;
@@ -8,6 +10,8 @@ pub mod xmss;
 
 pub use dkg::*;
 pub use hash::*;
+pub use hashing::*;
+pub use key_format::*;
 pub use keypair::*;
 pub use kyber::*;
 pub use mceliece::*;
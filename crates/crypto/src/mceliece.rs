@@ -1,3 +1,4 @@
+use crate::{hash_with_domain, HashDomain};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use spirachain_core::{Result, SpiraChainError};
@@ -67,7 +68,7 @@ impl McElieceKeyPair {
         }
 
         // Derive encryption key from secret key
-        let key_hash = blake3::hash(&self.secret_key.bytes);
+        let key_hash = hash_with_domain(HashDomain::McElieceKey, &self.secret_key.bytes);
 
         // XOR plaintext part with key stream (simplified code-based encryption)
         for (i, item) in ciphertext
@@ -90,7 +91,7 @@ impl McElieceKeyPair {
         }
 
         // Derive the same encryption key from secret key
-        let key_hash = blake3::hash(&self.secret_key.bytes);
+        let key_hash = hash_with_domain(HashDomain::McElieceKey, &self.secret_key.bytes);
 
         // XOR to decrypt (XOR is its own inverse)
         let mut plaintext = vec![0u8; MCELIECE_PLAINTEXT_SIZE];
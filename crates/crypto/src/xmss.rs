@@ -1,16 +1,67 @@
+use crate::{hash_leaf, hash_node, hash_with_domain, HashDomain};
 use rand::Rng;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
-use spirachain_core::{Result, SpiraChainError};
+use spirachain_core::{Hash, Result, SpiraChainError};
 
 // Production: 20 (1M signatures), Tests: 10 (1024 signatures)
 pub const XMSS_TREE_HEIGHT: usize = if cfg!(test) { 10 } else { 20 };
 pub const XMSS_SIGNATURE_SIZE: usize = 2500;
+pub const XMSS_MAX_TREE_HEIGHT: usize = 32;
+/// Key format written by this version: WOTS keys, chains and messages hashed
+/// in their own [`HashDomain`]s. Keys stored without a format predate the
+/// domains and their public root no longer matches what they would sign.
+pub const XMSS_KEY_FORMAT: u32 = 1;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct XmssKeyPair {
     public_key: XmssPublicKey,
     secret_key: XmssSecretKey,
+    #[serde(default = "default_tree_height")]
+    height: usize,
+    /// Stored with the key; a key stored without it rebuilds it on its first
+    /// signature
+    #[serde(default)]
+    tree: Option<XmssTree>,
+    #[serde(default)]
+    format: u32,
+}
+
+fn default_tree_height() -> usize {
+    XMSS_TREE_HEIGHT
+}
+
+/// Merkle tree state. The upper half of the levels is kept (2^11 nodes at
+/// height 20). The lower half of the auth path is maintained by one treehash
+/// instance per level (Szydlo's log traversal), so a signature computes at
+/// most `height - 1` leaves instead of the whole tree.
+#[derive(Clone, Serialize, Deserialize)]
+struct XmssTree {
+    height: usize,
+    /// From level `height / 2` up, the root last
+    upper: Vec<Vec<[u8; 32]>>,
+    /// Rebuilt from the upper levels when missing or behind the key's index
+    #[serde(default)]
+    traversal: Option<Traversal>,
+}
+
+/// Lower half of the auth path of `leaf`, and the nodes it needs next
+#[derive(Clone, Serialize, Deserialize)]
+struct Traversal {
+    leaf: u64,
+    auth: Vec<[u8; 32]>,
+    treehash: Vec<Treehash>,
+}
+
+/// Computes one node of `level` a leaf at a time, keeping at most one node
+/// per level below it
+#[derive(Clone, Serialize, Deserialize)]
+struct Treehash {
+    level: usize,
+    next_leaf: u64,
+    end: u64,
+    stack: Vec<(usize, [u8; 32])>,
+    node: Option<[u8; 32]>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +88,18 @@ pub struct XmssSignature {
 
 impl XmssKeyPair {
     pub fn generate() -> Result<Self> {
+        Self::generate_with_height(XMSS_TREE_HEIGHT)
+    }
+
+    /// A key good for 2^`height` signatures
+    pub fn generate_with_height(height: usize) -> Result<Self> {
+        if !(2..=XMSS_MAX_TREE_HEIGHT).contains(&height) {
+            return Err(SpiraChainError::CryptoError(format!(
+                "XMSS tree height must be between 2 and {}",
+                XMSS_MAX_TREE_HEIGHT
+            )));
+        }
+
         let mut rng = rand::thread_rng();
 
         let mut seed = [0u8; 32];
@@ -47,8 +110,9 @@ impl XmssKeyPair {
         rng.fill(&mut prf_seed);
         rng.fill(&mut pub_seed);
 
-        let leaf_nodes = Self::generate_leaf_nodes(&prf_seed, &pub_seed);
-        let root = Self::merkle_root(&leaf_nodes);
+        let mut tree = XmssTree::build(&prf_seed, &pub_seed, height);
+        tree.traversal = Some(Traversal::at(&prf_seed, &pub_seed, height, 0));
+        let root = tree.root();
 
         let secret_key = XmssSecretKey {
             index: 0,
@@ -63,11 +127,28 @@ impl XmssKeyPair {
         Ok(Self {
             public_key,
             secret_key,
+            height,
+            tree: Some(tree),
+            format: XMSS_KEY_FORMAT,
         })
     }
 
+    /// Refuse keys of another format, which would sign under a public key
+    /// nobody can verify against
+    pub fn check_format(&self) -> Result<()> {
+        if self.format != XMSS_KEY_FORMAT {
+            return Err(SpiraChainError::CryptoError(format!(
+                "XMSS key format {} is not supported (expected {}); generate a new key \
+                 and rotate to it",
+                self.format, XMSS_KEY_FORMAT
+            )));
+        }
+        Ok(())
+    }
+
     pub fn sign(&mut self, message: &[u8]) -> Result<XmssSignature> {
-        if self.secret_key.index >= (1u64 << XMSS_TREE_HEIGHT) {
+        self.check_format()?;
+        if self.secret_key.index >= (1u64 << self.height) {
             return Err(SpiraChainError::CryptoError(
                 "XMSS key exhausted - no more signatures available".to_string(),
             ));
@@ -78,9 +159,15 @@ impl XmssKeyPair {
         let wots_key = self.generate_wots_key(index);
         let wots_signature = self.wots_sign(&wots_key, message);
 
-        let leaf_nodes =
-            Self::generate_leaf_nodes(&self.secret_key.prf_seed, &self.secret_key.pub_seed);
-        let auth_path = self.generate_auth_path(&leaf_nodes, index as usize);
+        let (prf_seed, pub_seed, height) = (
+            self.secret_key.prf_seed,
+            self.secret_key.pub_seed,
+            self.height,
+        );
+        let auth_path = self
+            .tree
+            .get_or_insert_with(|| XmssTree::build(&prf_seed, &pub_seed, height))
+            .auth_path(&prf_seed, &pub_seed, index);
 
         self.secret_key.index += 1;
 
@@ -92,7 +179,7 @@ impl XmssKeyPair {
     }
 
     pub fn verify(&self, message: &[u8], signature: &XmssSignature) -> bool {
-        if signature.index >= (1u64 << XMSS_TREE_HEIGHT) {
+        if signature.index >= (1u64 << self.height) {
             return false;
         }
 
@@ -114,114 +201,79 @@ impl XmssKeyPair {
     }
 
     pub fn remaining_signatures(&self) -> u64 {
-        (1u64 << XMSS_TREE_HEIGHT) - self.secret_key.index
-    }
-
-    fn generate_leaf_nodes(prf_seed: &[u8; 32], pub_seed: &[u8; 32]) -> Vec<[u8; 32]> {
-        let num_leaves = 1 << XMSS_TREE_HEIGHT;
-        let mut leaves = Vec::with_capacity(num_leaves);
-
-        for i in 0..num_leaves {
-            // Generate WOTS public key for this leaf
-            // Start with the PRF seed + index to get deterministic key
-            let mut key_hasher = Sha256::new();
-            key_hasher.update(prf_seed);
-            key_hasher.update((i as u64).to_be_bytes());
-            let wots_key = key_hasher.finalize();
-
-            // Generate the WOTS public key parts
-            let mut public_key_parts = Vec::new();
-
-            for j in 0..32 {
-                // Start with seed derived from key and position
-                let mut chain_hasher = Sha256::new();
-                chain_hasher.update(wots_key);
-                chain_hasher.update((j as u32).to_be_bytes());
-                let mut chain_value = chain_hasher.finalize();
-
-                // Chain hash 255 times (maximum for w=256 Winternitz)
-                for _ in 0..255 {
-                    let mut next_hasher = Sha256::new();
-                    next_hasher.update(chain_value);
-                    chain_value = next_hasher.finalize();
-                }
+        (1u64 << self.height) - self.secret_key.index
+    }
 
-                public_key_parts.extend_from_slice(&chain_value);
-            }
+    /// Leaf `index`: the hash of its WOTS public key
+    fn leaf_node(prf_seed: &[u8; 32], pub_seed: &[u8; 32], index: u64) -> [u8; 32] {
+        // Start with the PRF seed + index to get deterministic key
+        let wots_key = Self::wots_key(prf_seed, index);
 
-            // Hash the complete public key to get the leaf
-            let mut leaf_hasher = Sha256::new();
-            leaf_hasher.update(&public_key_parts);
-            leaf_hasher.update(pub_seed); // Add pub_seed for additional entropy
-            let leaf_hash = leaf_hasher.finalize();
+        // Generate the WOTS public key parts
+        let mut public_key_parts = Vec::new();
 
-            let mut leaf = [0u8; 32];
-            leaf.copy_from_slice(&leaf_hash);
-            leaves.push(leaf);
-        }
+        for j in 0..32 {
+            // Start with seed derived from key and position
+            let mut chain_value = Self::chain_start(&wots_key, j);
 
-        leaves
-    }
+            // Chain hash 255 times (maximum for w=256 Winternitz)
+            for _ in 0..255 {
+                chain_value = Self::chain_step(&chain_value);
+            }
 
-    fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
-        if leaves.is_empty() {
-            return [0u8; 32];
+            public_key_parts.extend_from_slice(&chain_value);
         }
 
-        let mut current_level = leaves.to_vec();
+        Self::leaf(&public_key_parts, pub_seed)
+    }
 
-        while current_level.len() > 1 {
-            let mut next_level = Vec::new();
+    fn wots_key(prf_seed: &[u8; 32], index: u64) -> [u8; 32] {
+        let mut hasher = HashDomain::XmssKey.hasher();
+        hasher.update(prf_seed);
+        hasher.update(&index.to_be_bytes());
+        *hasher.finalize().as_bytes()
+    }
 
-            for chunk in current_level.chunks(2) {
-                let mut hasher = Sha256::new();
-                hasher.update(chunk[0]);
-                if chunk.len() > 1 {
-                    hasher.update(chunk[1]);
-                } else {
-                    hasher.update(chunk[0]);
-                }
-                let hash = hasher.finalize();
+    fn chain_start(key: &[u8], position: u32) -> [u8; 32] {
+        let mut hasher = HashDomain::XmssChain.hasher();
+        hasher.update(key);
+        hasher.update(&position.to_be_bytes());
+        *hasher.finalize().as_bytes()
+    }
 
-                let mut node = [0u8; 32];
-                node.copy_from_slice(&hash);
-                next_level.push(node);
-            }
+    fn chain_step(value: &[u8; 32]) -> [u8; 32] {
+        *hash_with_domain(HashDomain::XmssChain, value).as_bytes()
+    }
 
-            current_level = next_level;
-        }
+    /// Leaf committing to a WOTS public key and the public seed
+    fn leaf(public_key_parts: &[u8], pub_seed: &[u8; 32]) -> [u8; 32] {
+        let mut data = public_key_parts.to_vec();
+        data.extend_from_slice(pub_seed);
+        *hash_leaf(&data).as_bytes()
+    }
 
-        current_level[0]
+    fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        *hash_node(&Hash::from(*left), &Hash::from(*right)).as_bytes()
     }
 
     fn generate_wots_key(&self, index: u64) -> Vec<u8> {
-        let mut hasher = Sha256::new();
-        hasher.update(self.secret_key.prf_seed);
-        hasher.update(index.to_be_bytes());
-        hasher.finalize().to_vec()
+        Self::wots_key(&self.secret_key.prf_seed, index).to_vec()
     }
 
     fn wots_sign(&self, key: &[u8], message: &[u8]) -> Vec<u8> {
         // Hash the message to get 32 bytes
-        let mut hasher = Sha256::new();
-        hasher.update(message);
-        let msg_hash = hasher.finalize();
+        let msg_hash = hash_with_domain(HashDomain::XmssMessage, message);
 
         // WOTS signature: for each byte of the hash, chain-hash the key
         let mut signature = Vec::new();
 
-        for (i, &byte) in msg_hash.iter().enumerate() {
+        for (i, &byte) in msg_hash.as_bytes().iter().enumerate() {
             // Start with a deterministic seed derived from the key and position
-            let mut chain_hasher = Sha256::new();
-            chain_hasher.update(key);
-            chain_hasher.update((i as u32).to_be_bytes());
-            let mut chain_value = chain_hasher.finalize();
+            let mut chain_value = Self::chain_start(key, i as u32);
 
             // Chain hash 'byte' times (Winternitz parameter)
             for _ in 0..byte {
-                let mut next_hasher = Sha256::new();
-                next_hasher.update(chain_value);
-                chain_value = next_hasher.finalize();
+                chain_value = Self::chain_step(&chain_value);
             }
 
             signature.extend_from_slice(&chain_value);
@@ -232,14 +284,12 @@ impl XmssKeyPair {
 
     fn wots_verify(&self, signature: &[u8], message: &[u8]) -> [u8; 32] {
         // Hash the message
-        let mut hasher = Sha256::new();
-        hasher.update(message);
-        let msg_hash = hasher.finalize();
+        let msg_hash = hash_with_domain(HashDomain::XmssMessage, message);
 
         // Reconstruct the WOTS public key from the signature
         let mut public_key_parts = Vec::new();
 
-        for (i, &byte) in msg_hash.iter().enumerate() {
+        for (i, &byte) in msg_hash.as_bytes().iter().enumerate() {
             // Extract the signature chunk for this position (32 bytes each)
             let sig_start = i * 32;
             let sig_end = sig_start + 32;
@@ -255,10 +305,7 @@ impl XmssKeyPair {
             // Continue chain hashing from 'byte' to 255 to get the public key
             let remaining_iterations = 255 - byte;
             for _ in 0..remaining_iterations {
-                let mut next_hasher = Sha256::new();
-                next_hasher.update(chain_value);
-                let hash_result = next_hasher.finalize();
-                chain_value.copy_from_slice(&hash_result);
+                chain_value = Self::chain_step(&chain_value);
             }
 
             // This should now be the public key part for position i
@@ -266,77 +313,211 @@ impl XmssKeyPair {
         }
 
         // Hash all public key parts + pub_seed to get the leaf (must match generate_leaf_nodes)
-        let mut final_hasher = Sha256::new();
-        final_hasher.update(&public_key_parts);
-        final_hasher.update(self.public_key.pub_seed);
-        let result = final_hasher.finalize();
-
-        let mut leaf = [0u8; 32];
-        leaf.copy_from_slice(&result);
-        leaf
+        Self::leaf(&public_key_parts, &self.public_key.pub_seed)
     }
 
-    fn generate_auth_path(&self, leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
-        let mut auth_path = Vec::new();
+    fn verify_auth_path(&self, leaf: &[u8; 32], auth_path: &[[u8; 32]], index: usize) -> [u8; 32] {
+        let mut current_node = *leaf;
         let mut current_index = index;
-        let mut current_level = leaves.to_vec();
 
-        for _ in 0..XMSS_TREE_HEIGHT {
-            let sibling_index = if current_index.is_multiple_of(2) {
-                current_index + 1
+        for sibling in auth_path {
+            current_node = if current_index.is_multiple_of(2) {
+                Self::node(&current_node, sibling)
             } else {
-                current_index - 1
+                Self::node(sibling, &current_node)
             };
+            current_index /= 2;
+        }
 
-            if sibling_index < current_level.len() {
-                auth_path.push(current_level[sibling_index]);
-            } else {
-                auth_path.push(current_level[current_index]);
-            }
+        current_node
+    }
+}
 
-            let mut next_level = Vec::new();
-            for chunk in current_level.chunks(2) {
-                let mut hasher = Sha256::new();
-                hasher.update(chunk[0]);
-                if chunk.len() > 1 {
-                    hasher.update(chunk[1]);
-                } else {
-                    hasher.update(chunk[0]);
-                }
-                let hash = hasher.finalize();
+impl XmssTree {
+    fn build(prf_seed: &[u8; 32], pub_seed: &[u8; 32], height: usize) -> Self {
+        let split = height / 2;
+        let roots = (0..1u64 << (height - split))
+            .into_par_iter()
+            .map(|index| Self::node_at(prf_seed, pub_seed, split, index))
+            .collect();
+
+        Self {
+            height,
+            upper: Self::levels(roots),
+            traversal: None,
+        }
+    }
 
-                let mut node = [0u8; 32];
-                node.copy_from_slice(&hash);
-                next_level.push(node);
-            }
+    fn root(&self) -> [u8; 32] {
+        self.upper.last().map(|level| level[0]).unwrap_or_default()
+    }
 
-            current_level = next_level;
-            current_index /= 2;
+    /// Node `index` of `level`, its leaves generated in parallel
+    fn node_at(prf_seed: &[u8; 32], pub_seed: &[u8; 32], level: usize, index: u64) -> [u8; 32] {
+        Self::subtree_levels(prf_seed, pub_seed, level, index)[level][0]
+    }
+
+    /// Levels of the subtree under node `index` of `level`, leaves first
+    fn subtree_levels(
+        prf_seed: &[u8; 32],
+        pub_seed: &[u8; 32],
+        level: usize,
+        index: u64,
+    ) -> Vec<Vec<[u8; 32]>> {
+        let first = index << level;
+        let leaves = (first..first + (1 << level))
+            .into_par_iter()
+            .map(|leaf| XmssKeyPair::leaf_node(prf_seed, pub_seed, leaf))
+            .collect();
+        Self::levels(leaves)
+    }
+
+    /// `nodes` and every level above them, up to a single node
+    fn levels(nodes: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+        let mut levels = vec![nodes];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level
+                .chunks(2)
+                .map(|pair| XmssKeyPair::node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            levels.push(parents);
         }
+        levels
+    }
 
+    /// Auth path of `leaf`, then the traversal moves on to the next leaf
+    fn auth_path(&mut self, prf_seed: &[u8; 32], pub_seed: &[u8; 32], leaf: u64) -> Vec<[u8; 32]> {
+        let height = self.height;
+        let traversal = match &mut self.traversal {
+            Some(traversal) if traversal.leaf == leaf => traversal,
+            slot => slot.insert(Traversal::at(prf_seed, pub_seed, height, leaf)),
+        };
+
+        let mut auth_path = traversal.auth.clone();
+        let split = auth_path.len();
+        for (above, level) in self.upper[..self.upper.len() - 1].iter().enumerate() {
+            let index = (leaf >> (split + above)) ^ 1;
+            auth_path.push(level[index as usize]);
+        }
+
+        traversal.advance(prf_seed, pub_seed, height);
         auth_path
     }
+}
 
-    fn verify_auth_path(&self, leaf: &[u8; 32], auth_path: &[[u8; 32]], index: usize) -> [u8; 32] {
-        let mut current_node = *leaf;
-        let mut current_index = index;
+impl Traversal {
+    /// State for signing `leaf` next: its auth nodes, and for every level the
+    /// node that replaces them when `leaf` crosses into the next node
+    fn at(prf_seed: &[u8; 32], pub_seed: &[u8; 32], height: usize, leaf: u64) -> Self {
+        let split = height / 2;
+        let subtree = XmssTree::subtree_levels(prf_seed, pub_seed, split, leaf >> split);
+        let auth = (0..split)
+            .map(|level| {
+                let index = ((leaf >> level) ^ 1) & ((1 << (split - level)) - 1);
+                subtree[level][index as usize]
+            })
+            .collect();
+        let treehash = (0..split)
+            .map(|level| {
+                let change = ((leaf >> level) + 1) << level;
+                let mut treehash = Treehash::idle(level);
+                if change < 1 << height {
+                    let index = (change >> level) ^ 1;
+                    treehash.node = Some(XmssTree::node_at(prf_seed, pub_seed, level, index));
+                }
+                treehash
+            })
+            .collect();
+
+        Self {
+            leaf,
+            auth,
+            treehash,
+        }
+    }
 
-        for sibling in auth_path {
-            let mut hasher = Sha256::new();
-            if current_index.is_multiple_of(2) {
-                hasher.update(current_node);
-                hasher.update(sibling);
-            } else {
-                hasher.update(sibling);
-                hasher.update(current_node);
+    fn advance(&mut self, prf_seed: &[u8; 32], pub_seed: &[u8; 32], height: usize) {
+        let next = self.leaf + 1;
+        self.leaf = next;
+        if next >= 1 << height {
+            return;
+        }
+
+        for (level, treehash) in self.treehash.iter_mut().enumerate() {
+            if !next.is_multiple_of(1 << level) {
+                continue;
+            }
+            // Never short of budget, but a missing node would mean a bad
+            // signature: finish it here rather than trusting the schedule
+            while treehash.node.is_none() && treehash.low().is_some() {
+                treehash.update(prf_seed, pub_seed);
+            }
+            if let Some(node) = treehash.node.take() {
+                self.auth[level] = node;
             }
-            let hash = hasher.finalize();
+            let start = (next + (1 << level)) ^ (1 << level);
+            if start < 1 << height {
+                treehash.start(start);
+            }
+        }
 
-            current_node.copy_from_slice(&hash);
-            current_index /= 2;
+        // Leaves go to the instance whose lowest node is lowest, so the nodes
+        // needed soonest finish first
+        for _ in 0..(2 * self.treehash.len()).saturating_sub(1) {
+            let lowest = self
+                .treehash
+                .iter()
+                .enumerate()
+                .filter_map(|(level, treehash)| treehash.low().map(|low| (low, level)))
+                .min();
+            match lowest {
+                Some((_, level)) => self.treehash[level].update(prf_seed, pub_seed),
+                None => break,
+            }
         }
+    }
+}
 
-        current_node
+impl Treehash {
+    fn idle(level: usize) -> Self {
+        Self {
+            level,
+            next_leaf: 0,
+            end: 0,
+            stack: Vec::new(),
+            node: None,
+        }
+    }
+
+    fn start(&mut self, first_leaf: u64) {
+        self.next_leaf = first_leaf;
+        self.end = first_leaf + (1 << self.level);
+        self.stack.clear();
+        self.node = None;
+    }
+
+    /// Level of the lowest node on the stack, `None` with nothing to compute
+    fn low(&self) -> Option<usize> {
+        (self.next_leaf < self.end)
+            .then(|| self.stack.last().map_or(self.level, |(level, _)| *level))
+    }
+
+    fn update(&mut self, prf_seed: &[u8; 32], pub_seed: &[u8; 32]) {
+        let mut node = (
+            0,
+            XmssKeyPair::leaf_node(prf_seed, pub_seed, self.next_leaf),
+        );
+        self.next_leaf += 1;
+        while let Some(&(level, left)) = self.stack.last().filter(|(level, _)| *level == node.0) {
+            self.stack.pop();
+            node = (level + 1, XmssKeyPair::node(&left, &node.1));
+        }
+
+        if self.next_leaf == self.end {
+            self.node = Some(node.1);
+        } else {
+            self.stack.push(node);
+        }
     }
 }
 
@@ -409,6 +590,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_xmss_traversal_matches_full_tree() {
+        let height = 6;
+        let mut keypair = XmssKeyPair::generate_with_height(height).unwrap();
+        let secret = keypair.secret_key.clone();
+        let leaves: Vec<[u8; 32]> = (0..1u64 << height)
+            .map(|i| XmssKeyPair::leaf_node(&secret.prf_seed, &secret.pub_seed, i))
+            .collect();
+        let full_tree = XmssTree::levels(leaves);
+        assert_eq!(full_tree[height][0], keypair.public_key().root);
+
+        for leaf in 0..1u64 << height {
+            // The state is stored with the key and survives a reload
+            if leaf == 21 {
+                let stored = serde_json::to_string(&keypair).unwrap();
+                keypair = serde_json::from_str(&stored).unwrap();
+            }
+            // A key stored without it rebuilds it
+            if leaf == 40 {
+                keypair.tree = None;
+            }
+
+            let signature = keypair.sign(b"block").unwrap();
+            let expected: Vec<[u8; 32]> = (0..height)
+                .map(|level| full_tree[level][((leaf >> level) ^ 1) as usize])
+                .collect();
+            assert_eq!(signature.auth_path, expected, "leaf {}", leaf);
+            assert!(keypair.verify(b"block", &signature));
+
+            // O(h^2) state: at most one node per level below each instance
+            let traversal = keypair.tree.as_ref().unwrap().traversal.as_ref().unwrap();
+            let stacked: usize = traversal.treehash.iter().map(|t| t.stack.len()).sum();
+            assert!(stacked <= height * height);
+        }
+        assert!(keypair.sign(b"block").is_err());
+    }
+
+    #[test]
+    fn test_xmss_refuses_keys_without_format() {
+        let mut keypair = XmssKeyPair::generate_with_height(4).unwrap();
+        let mut stored = serde_json::to_value(&keypair).unwrap();
+        stored.as_object_mut().unwrap().remove("format");
+
+        let mut legacy: XmssKeyPair = serde_json::from_value(stored).unwrap();
+        assert!(legacy.check_format().is_err());
+        assert!(legacy.sign(b"block").is_err());
+        assert!(keypair.sign(b"block").is_ok());
+    }
+
     #[test]
     fn test_xmss_wrong_message() {
         let mut keypair = XmssKeyPair::generate().unwrap();
@@ -209,9 +209,14 @@ mod tests {
 
     #[test]
     fn test_bootstrap_config_with_static_peer() {
+        let fallback = BootstrapConfig::new().static_peers.len();
         let config = BootstrapConfig::new().with_static_peer("/ip4/127.0.0.1/tcp/9000".to_string());
 
-        assert_eq!(config.static_peers.len(), 1);
+        assert_eq!(config.static_peers.len(), fallback + 1);
+        assert_eq!(
+            config.static_peers.last().map(String::as_str),
+            Some("/ip4/127.0.0.1/tcp/9000")
+        );
     }
 
     #[test]
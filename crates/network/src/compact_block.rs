@@ -0,0 +1,229 @@
+// Compact block relay
+// Blocks are announced as header + short transaction IDs; peers rebuild them
+// from their mempool and only fetch the transactions they are missing.
+
+use crate::propagation::AnnouncementMeta;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Block, BlockHeader, Hash, Transaction};
+use std::collections::HashMap;
+
+/// First 8 bytes of blake3(block_hash || tx_hash). Salting with the block hash
+/// keeps collisions from being precomputed across blocks.
+pub type ShortTxId = [u8; 8];
+
+pub fn short_tx_id(block_hash: &Hash, tx_hash: &Hash) -> ShortTxId {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(block_hash.as_bytes());
+    hasher.update(tx_hash.as_bytes());
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&hasher.finalize().as_bytes()[..8]);
+    id
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactBlock {
+    pub header: BlockHeader,
+    /// One short ID per transaction, in block order
+    pub short_ids: Vec<ShortTxId>,
+    /// Transactions peers cannot have in their mempool (coinbase, epoch summary)
+    pub prefilled: Vec<(u16, Transaction)>,
+    /// When the producer announced the block, for propagation latency
+    pub announcement: AnnouncementMeta,
+}
+
+impl CompactBlock {
+    pub fn from_block(block: &Block) -> Self {
+        let block_hash = block.hash();
+
+        Self {
+            header: block.header.clone(),
+            short_ids: block
+                .transactions
+                .iter()
+                .map(|tx| short_tx_id(&block_hash, &tx.tx_hash))
+                .collect(),
+            prefilled: block
+                .transactions
+                .iter()
+                .enumerate()
+                .filter(|(_, tx)| tx.is_protocol())
+                .map(|(index, tx)| (index as u16, tx.clone()))
+                .collect(),
+            announcement: AnnouncementMeta::now(),
+        }
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.header.hash()
+    }
+
+    /// Fill in every transaction found in `mempool`
+    pub fn reconstruct(&self, mempool: &[Transaction]) -> PartialBlock {
+        let block_hash = self.hash();
+        let by_short_id: HashMap<ShortTxId, &Transaction> = mempool
+            .iter()
+            .map(|tx| (short_tx_id(&block_hash, &tx.tx_hash), tx))
+            .collect();
+
+        let mut txs: Vec<Option<Transaction>> = self
+            .short_ids
+            .iter()
+            .map(|id| by_short_id.get(id).map(|tx| (*tx).clone()))
+            .collect();
+
+        for (index, tx) in &self.prefilled {
+            if let Some(slot) = txs.get_mut(*index as usize) {
+                *slot = Some(tx.clone());
+            }
+        }
+
+        PartialBlock {
+            header: self.header.clone(),
+            short_ids: self.short_ids.clone(),
+            txs,
+        }
+    }
+}
+
+/// A compact block being reconstructed
+#[derive(Debug, Clone)]
+pub struct PartialBlock {
+    header: BlockHeader,
+    short_ids: Vec<ShortTxId>,
+    txs: Vec<Option<Transaction>>,
+}
+
+impl PartialBlock {
+    pub fn hash(&self) -> Hash {
+        self.header.hash()
+    }
+
+    pub fn height(&self) -> u64 {
+        self.header.block_height
+    }
+
+    /// Indexes of transactions still to be fetched
+    pub fn missing(&self) -> Vec<u16> {
+        self.txs
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| tx.is_none())
+            .map(|(index, _)| index as u16)
+            .collect()
+    }
+
+    /// Add fetched transactions; ones that don't match their short ID are dropped
+    pub fn fill(&mut self, txs: Vec<(u16, Transaction)>) {
+        let block_hash = self.hash();
+        for (index, tx) in txs {
+            let index = index as usize;
+            if index < self.txs.len()
+                && short_tx_id(&block_hash, &tx.tx_hash) == self.short_ids[index]
+            {
+                self.txs[index] = Some(tx);
+            }
+        }
+    }
+
+    /// The full block, if every transaction is present and the merkle root checks out.
+    /// `None` means the caller should fall back to fetching the full block.
+    pub fn into_block(self) -> Option<Block> {
+        let transactions: Option<Vec<Transaction>> = self.txs.into_iter().collect();
+        let mut block = Block {
+            header: self.header,
+            transactions: transactions?,
+        };
+
+        let expected_root = block.header.merkle_root;
+        block.compute_merkle_root();
+        if block.header.merkle_root != expected_root {
+            return None;
+        }
+
+        Some(block)
+    }
+}
+
+/// Answer to a missing-transactions request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockTransactions {
+    pub block_hash: Hash,
+    pub transactions: Vec<(u16, Transaction)>,
+}
+
+impl BlockTransactions {
+    pub fn from_block(block: &Block, indexes: &[u16]) -> Self {
+        Self {
+            block_hash: block.hash(),
+            transactions: indexes
+                .iter()
+                .filter_map(|index| {
+                    block
+                        .transactions
+                        .get(*index as usize)
+                        .map(|tx| (*index, tx.clone()))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{ChainBuilder, TxPattern};
+
+    fn sample_block() -> Block {
+        ChainBuilder::new("testnet")
+            .with_tx_pattern(TxPattern::Transfers(3))
+            .build(1)
+            .tip()
+            .clone()
+    }
+
+    #[test]
+    fn test_reconstruct_from_mempool() {
+        let block = sample_block();
+        let compact = CompactBlock::from_block(&block);
+        assert_eq!(compact.prefilled.len(), 1);
+
+        let mempool = block.transactions[1..].to_vec();
+        let partial = compact.reconstruct(&mempool);
+        assert!(partial.missing().is_empty());
+
+        let rebuilt = partial.into_block().expect("complete block");
+        assert_eq!(rebuilt.hash(), block.hash());
+        assert_eq!(rebuilt.transactions.len(), block.transactions.len());
+    }
+
+    #[test]
+    fn test_fetch_missing_transactions() {
+        let block = sample_block();
+        let compact = CompactBlock::from_block(&block);
+
+        let mut partial = compact.reconstruct(&block.transactions[1..2]);
+        assert_eq!(partial.missing(), vec![2, 3]);
+
+        let response = BlockTransactions::from_block(&block, &partial.missing());
+        assert_eq!(response.block_hash, block.hash());
+
+        // A transaction under the wrong index is rejected
+        partial.fill(vec![(2, block.transactions[3].clone())]);
+        assert_eq!(partial.missing(), vec![2, 3]);
+
+        partial.fill(response.transactions);
+        assert!(partial.missing().is_empty());
+        assert!(partial.into_block().is_some());
+    }
+
+    #[test]
+    fn test_merkle_mismatch_falls_back() {
+        let mut block = sample_block();
+        block.header.merkle_root = Hash::new([0xAA; 32]);
+
+        let compact = CompactBlock::from_block(&block);
+        let partial = compact.reconstruct(&block.transactions);
+        assert!(partial.missing().is_empty());
+        assert!(partial.into_block().is_none());
+    }
+}
@@ -0,0 +1,270 @@
+// Gossip payload compression
+// Nodes advertise the codecs they read as identify capabilities (`snappy/1`,
+// `zstd/1`). Gossip reaches every connected peer at once, so a large payload
+// is compressed with the configured codec only when all of them advertise it,
+// else with the next cheaper codec they all read, else sent as is. A node
+// relaying a compressed payload to a peer that cannot read it re-publishes it
+// uncompressed, so nodes from before compression keep receiving everything.
+// Wire: compressed magic ‖ codec tag ‖ compressed bytes
+
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Reads Snappy-compressed payloads
+pub const CAP_SNAPPY: &str = "snappy/1";
+/// Reads zstd-compressed payloads
+pub const CAP_ZSTD: &str = "zstd/1";
+
+/// Payloads smaller than this are sent as is; text sync messages and most
+/// transactions never pay the CPU cost
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Largest payload a compressed message may expand to, so a tiny message
+/// cannot make us allocate without bound
+pub const MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+/// Ratio-oriented but still fast enough for blocks on every hop
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    /// Cheap on CPU, moderate savings
+    Snappy,
+    /// Best savings, more CPU per message
+    Zstd,
+}
+
+impl Compression {
+    /// Byte after the compressed magic naming the codec
+    pub fn tag(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Snappy => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Snappy),
+            2 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Capability a peer advertises to receive this codec; every peer reads `None`
+    pub fn capability(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Snappy => Some(CAP_SNAPPY),
+            Compression::Zstd => Some(CAP_ZSTD),
+        }
+    }
+
+    /// This codec followed by the cheaper ones to fall back to
+    pub fn fallbacks(&self) -> &'static [Compression] {
+        match self {
+            Compression::Zstd => &[Compression::Zstd, Compression::Snappy, Compression::None],
+            Compression::Snappy => &[Compression::Snappy, Compression::None],
+            Compression::None => &[Compression::None],
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Snappy => snap::raw::Encoder::new()
+                .compress_vec(data)
+                .map_err(|e| SpiraChainError::NetworkError(format!("Snappy compress: {}", e))),
+            Compression::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL)
+                .map_err(|e| SpiraChainError::NetworkError(format!("Zstd compress: {}", e))),
+        }
+    }
+
+    /// Inverse of [`Self::compress`], refusing output over [`MAX_DECOMPRESSED_SIZE`]
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let too_large = |size: usize| {
+            SpiraChainError::NetworkError(format!(
+                "Compressed payload expands to {} bytes (limit {})",
+                size, MAX_DECOMPRESSED_SIZE
+            ))
+        };
+        match self {
+            Compression::None if data.len() > MAX_DECOMPRESSED_SIZE => Err(too_large(data.len())),
+            Compression::None => Ok(data.to_vec()),
+            Compression::Snappy => {
+                let size = snap::raw::decompress_len(data)
+                    .map_err(|e| SpiraChainError::NetworkError(format!("Snappy header: {}", e)))?;
+                if size > MAX_DECOMPRESSED_SIZE {
+                    return Err(too_large(size));
+                }
+                snap::raw::Decoder::new()
+                    .decompress_vec(data)
+                    .map_err(|e| SpiraChainError::NetworkError(format!("Snappy decompress: {}", e)))
+            }
+            Compression::Zstd => zstd::bulk::decompress(data, MAX_DECOMPRESSED_SIZE)
+                .map_err(|e| SpiraChainError::NetworkError(format!("Zstd decompress: {}", e))),
+        }
+    }
+
+    /// First codec of our preference list that `readable` accepts
+    pub fn negotiate(&self, readable: impl Fn(Compression) -> bool) -> Compression {
+        self.fallbacks()
+            .iter()
+            .copied()
+            .find(|codec| readable(*codec))
+            .unwrap_or(Compression::None)
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Compression::None => "none",
+            Compression::Snappy => "snappy",
+            Compression::Zstd => "zstd",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "snappy" => Ok(Compression::Snappy),
+            "zstd" => Ok(Compression::Zstd),
+            other => Err(format!(
+                "Unknown compression {} (expected none, snappy or zstd)",
+                other
+            )),
+        }
+    }
+}
+
+/// CPU spent and bytes saved by one codec since the node started
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodecStats {
+    pub messages_sent: u64,
+    /// Payload bytes before compression
+    pub raw_bytes: u64,
+    /// Payload bytes on the wire
+    pub wire_bytes: u64,
+    pub compress_micros: u64,
+    pub messages_received: u64,
+    pub decompress_micros: u64,
+}
+
+impl CodecStats {
+    /// Wire bytes per raw byte sent; 1.0 until something was sent
+    pub fn ratio(&self) -> f64 {
+        if self.raw_bytes == 0 {
+            1.0
+        } else {
+            self.wire_bytes as f64 / self.raw_bytes as f64
+        }
+    }
+}
+
+/// Per-codec counters plus how often a peer forced a cheaper codec
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompressionStats {
+    pub codecs: BTreeMap<Compression, CodecStats>,
+    /// Large payloads sent with a cheaper codec than configured because a
+    /// connected peer could not read it
+    pub fallbacks: u64,
+    /// Compressed payloads re-published uncompressed for such peers
+    pub relayed_uncompressed: u64,
+}
+
+impl CompressionStats {
+    pub fn record_sent(&mut self, codec: Compression, raw: usize, wire: usize, took: Duration) {
+        let stats = self.codecs.entry(codec).or_default();
+        stats.messages_sent += 1;
+        stats.raw_bytes += raw as u64;
+        stats.wire_bytes += wire as u64;
+        stats.compress_micros += took.as_micros() as u64;
+    }
+
+    pub fn record_received(&mut self, codec: Compression, took: Duration) {
+        let stats = self.codecs.entry(codec).or_default();
+        stats.messages_received += 1;
+        stats.decompress_micros += took.as_micros() as u64;
+    }
+}
+
+/// Compress `data` for the wire behind `magic`, timing the codec
+pub fn encode_compressed(
+    magic: &[u8],
+    codec: Compression,
+    data: &[u8],
+) -> Result<(Vec<u8>, Duration)> {
+    let started = Instant::now();
+    let compressed = codec.compress(data)?;
+    let took = started.elapsed();
+
+    let mut payload = Vec::with_capacity(magic.len() + 1 + compressed.len());
+    payload.extend_from_slice(magic);
+    payload.push(codec.tag());
+    payload.extend_from_slice(&compressed);
+    Ok((payload, took))
+}
+
+/// Codec and decompressed bytes of a payload whose compressed magic is already stripped
+pub fn decode_compressed(data: &[u8]) -> Result<(Compression, Vec<u8>, Duration)> {
+    let (tag, body) = data
+        .split_first()
+        .ok_or_else(|| SpiraChainError::NetworkError("Empty compressed payload".to_string()))?;
+    let codec = Compression::from_tag(*tag)
+        .ok_or_else(|| SpiraChainError::NetworkError(format!("Unknown compression tag {}", tag)))?;
+
+    let started = Instant::now();
+    let decompressed = codec.decompress(body)?;
+    Ok((codec, decompressed, started.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_roundtrip_and_negotiation() {
+        let data = b"spirachain block ".repeat(200);
+        for codec in [Compression::None, Compression::Snappy, Compression::Zstd] {
+            let (payload, _) = encode_compressed(b"MAGC", codec, &data).unwrap();
+            let (decoded_codec, decoded, _) =
+                decode_compressed(payload.strip_prefix(b"MAGC").unwrap()).unwrap();
+            assert_eq!(decoded_codec, codec);
+            assert_eq!(decoded, data);
+        }
+        assert!(decode_compressed(&[9, 1, 2]).is_err());
+
+        assert_eq!("zstd".parse::<Compression>(), Ok(Compression::Zstd));
+        assert!("gzip".parse::<Compression>().is_err());
+
+        // A snappy-only peer pulls zstd down to snappy; a legacy peer to none
+        let snappy_only = |codec: Compression| codec != Compression::Zstd;
+        assert_eq!(
+            Compression::Zstd.negotiate(snappy_only),
+            Compression::Snappy
+        );
+        assert_eq!(
+            Compression::Snappy.negotiate(|codec| codec == Compression::None),
+            Compression::None
+        );
+        assert_eq!(Compression::None.negotiate(|_| true), Compression::None);
+
+        let mut stats = CompressionStats::default();
+        stats.record_sent(Compression::Snappy, 1000, 250, Duration::from_micros(40));
+        assert_eq!(stats.codecs[&Compression::Snappy].ratio(), 0.25);
+    }
+}
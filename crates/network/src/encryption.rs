@@ -79,7 +79,7 @@ impl P2PEncryption {
 
         let peer_state = peer_keys
             .get_mut(peer_id)
-            .ok_or_else(|| SpiraChainError::Internal(format!("Unknown peer: {}", peer_id)))?;
+            .ok_or_else(|| SpiraChainError::CryptoError(format!("Unknown peer: {}", peer_id)))?;
 
         let (ciphertext, shared_secret) = peer_state.public_key.encapsulate()?;
 
@@ -105,10 +105,10 @@ impl P2PEncryption {
 
         let peer_state = peer_keys
             .get_mut(peer_id)
-            .ok_or_else(|| SpiraChainError::Internal(format!("Unknown peer: {}", peer_id)))?;
+            .ok_or_else(|| SpiraChainError::CryptoError(format!("Unknown peer: {}", peer_id)))?;
 
         let shared_secret = peer_state.shared_secret.as_ref().ok_or_else(|| {
-            SpiraChainError::Internal(format!("No shared secret with {}", peer_id))
+            SpiraChainError::CryptoError(format!("No shared secret with {}", peer_id))
         })?;
 
         let key = shared_secret.derive_key(b"spirachain-p2p-v1");
@@ -129,10 +129,10 @@ impl P2PEncryption {
 
         let peer_state = peer_keys
             .get(peer_id)
-            .ok_or_else(|| SpiraChainError::Internal(format!("Unknown peer: {}", peer_id)))?;
+            .ok_or_else(|| SpiraChainError::CryptoError(format!("Unknown peer: {}", peer_id)))?;
 
         let shared_secret = peer_state.shared_secret.as_ref().ok_or_else(|| {
-            SpiraChainError::Internal(format!("No shared secret with {}", peer_id))
+            SpiraChainError::CryptoError(format!("No shared secret with {}", peer_id))
         })?;
 
         let key = shared_secret.derive_key(b"spirachain-p2p-v1");
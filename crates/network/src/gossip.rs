@@ -0,0 +1,183 @@
+// Gossipsub mesh parameters
+// D (mesh_n) and its bounds, the heartbeat and the message history window
+// are configurable. The libp2p defaults assume a large network: on a testnet
+// of a handful of nodes a 10s heartbeat leaves new peers out of the mesh for
+// seconds, and the outbound quota keeps grafting peers that do not exist. So
+// unless told the network is bigger, non-mainnet nodes tighten the mesh to
+// the expected size, beat every second and gossip their whole history.
+// Gossipsub cannot be reconfigured once running; changes apply on restart.
+
+use libp2p::gossipsub;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use std::time::Duration;
+
+/// Networks up to this many peers get the small-network tuning
+pub const SMALL_NETWORK_PEERS: usize = 5;
+
+/// Slowest heartbeat the small-network tuning allows
+pub const SMALL_NETWORK_HEARTBEAT_MS: u64 = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GossipParams {
+    pub heartbeat_ms: u64,
+    /// Target mesh degree (D)
+    pub mesh_n: usize,
+    /// Below this many mesh peers the heartbeat grafts more (D_lo)
+    pub mesh_n_low: usize,
+    /// Above this many mesh peers the heartbeat prunes (D_hi)
+    pub mesh_n_high: usize,
+    /// Mesh peers that must be outbound connections
+    pub mesh_outbound_min: usize,
+    /// Heartbeats a message stays in the cache for IWANT requests
+    pub history_length: usize,
+    /// Heartbeats of history advertised in IHAVE gossip
+    pub history_gossip: usize,
+    /// Apply the small-network tuning when the network looks small
+    pub adaptive: bool,
+    /// Expected number of peers, 0 = unknown: small on test networks,
+    /// large on mainnet
+    pub expected_peers: usize,
+}
+
+impl Default for GossipParams {
+    fn default() -> Self {
+        Self {
+            heartbeat_ms: 10_000,
+            mesh_n: 6,
+            mesh_n_low: 5,
+            mesh_n_high: 12,
+            mesh_outbound_min: 2,
+            history_length: 5,
+            history_gossip: 3,
+            adaptive: true,
+            expected_peers: 0,
+        }
+    }
+}
+
+impl GossipParams {
+    /// The constraints gossipsub enforces, reported with our field names
+    pub fn validate(&self) -> Result<()> {
+        if self.heartbeat_ms == 0 {
+            return Err(SpiraChainError::Config(
+                "gossip heartbeat_ms must be greater than zero".to_string(),
+            ));
+        }
+        if !(self.mesh_n_low <= self.mesh_n && self.mesh_n <= self.mesh_n_high) {
+            return Err(SpiraChainError::Config(format!(
+                "gossip mesh must satisfy mesh_n_low <= mesh_n <= mesh_n_high (got {} / {} / {})",
+                self.mesh_n_low, self.mesh_n, self.mesh_n_high
+            )));
+        }
+        if self.mesh_outbound_min > self.mesh_n_low || self.mesh_outbound_min * 2 > self.mesh_n {
+            return Err(SpiraChainError::Config(format!(
+                "gossip mesh_outbound_min {} must be at most mesh_n_low and half of mesh_n",
+                self.mesh_outbound_min
+            )));
+        }
+        if self.history_gossip > self.history_length {
+            return Err(SpiraChainError::Config(format!(
+                "gossip history_gossip {} exceeds history_length {}",
+                self.history_gossip, self.history_length
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether the small-network tuning applies on `network`
+    pub fn is_small_network(&self, network: &str) -> bool {
+        self.adaptive
+            && match self.expected_peers {
+                0 => network != "mainnet",
+                peers => peers <= SMALL_NETWORK_PEERS,
+            }
+    }
+
+    /// Parameters to run with on `network`: these, or the small-network
+    /// tuning of them
+    pub fn effective(&self, network: &str) -> Self {
+        if !self.is_small_network(network) {
+            return self.clone();
+        }
+
+        let peers = match self.expected_peers {
+            0 => SMALL_NETWORK_PEERS,
+            peers => peers,
+        };
+        // Every peer in the mesh, and never below one while anyone is connected
+        let mesh_n = self.mesh_n.min(peers).max(1);
+        Self {
+            heartbeat_ms: self.heartbeat_ms.min(SMALL_NETWORK_HEARTBEAT_MS),
+            mesh_n,
+            mesh_n_low: 1,
+            mesh_n_high: self.mesh_n_high.max(mesh_n),
+            mesh_outbound_min: self.mesh_outbound_min.min(mesh_n / 2).min(1),
+            history_length: self.history_length,
+            history_gossip: self.history_length,
+            adaptive: self.adaptive,
+            expected_peers: self.expected_peers,
+        }
+    }
+
+    pub fn apply(&self, builder: &mut gossipsub::ConfigBuilder) {
+        builder
+            .heartbeat_interval(Duration::from_millis(self.heartbeat_ms))
+            .mesh_n(self.mesh_n)
+            .mesh_n_low(self.mesh_n_low)
+            .mesh_n_high(self.mesh_n_high)
+            .mesh_outbound_min(self.mesh_outbound_min)
+            .history_length(self.history_length)
+            .history_gossip(self.history_gossip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_network_tuning() {
+        let params = GossipParams::default();
+        assert!(params.validate().is_ok());
+        assert_eq!(params.effective("mainnet"), params);
+
+        let testnet = params.effective("testnet");
+        assert_eq!(testnet.heartbeat_ms, SMALL_NETWORK_HEARTBEAT_MS);
+        assert_eq!(testnet.mesh_n_low, 1);
+        assert!(testnet.validate().is_ok());
+
+        let three = GossipParams {
+            expected_peers: 3,
+            ..GossipParams::default()
+        }
+        .effective("mainnet");
+        assert_eq!((three.mesh_n, three.mesh_outbound_min), (3, 1));
+        assert!(three.validate().is_ok());
+
+        let one = GossipParams {
+            expected_peers: 1,
+            ..GossipParams::default()
+        }
+        .effective("testnet");
+        assert_eq!((one.mesh_n, one.mesh_outbound_min), (1, 0));
+        assert!(one.validate().is_ok());
+
+        let large = GossipParams {
+            expected_peers: 50,
+            ..GossipParams::default()
+        };
+        assert_eq!(large.effective("testnet"), large);
+
+        let mut builder = gossipsub::ConfigBuilder::default();
+        testnet.apply(&mut builder);
+        assert!(builder.build().is_ok());
+
+        let invalid = GossipParams {
+            mesh_n_low: 7,
+            ..GossipParams::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
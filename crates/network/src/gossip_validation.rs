@@ -0,0 +1,388 @@
+// Gossip message validation
+// Gossipsub forwards a message only once we vouch for it, so every message is
+// checked against its topic before it reaches the mesh: it must carry our
+// network magic, stay within the topic's size limit and decode to what the
+// topic carries, with the cheap stateless checks passing. Transactions in the
+// layout from before payloads are upgraded until the first hard fork. Rejected
+// messages are not relayed and count against the sender's peer score;
+// transactions for a fork we are not on yet are only ignored, as a peer ahead
+// of us is not at fault. The decoded payload is handed on, so nothing is
+// decoded twice.
+
+use crate::compact_block::{BlockTransactions, CompactBlock};
+use libp2p::gossipsub::{self, MessageAcceptance, PeerScoreParams, TopicScoreParams};
+use parking_lot::Mutex;
+use spirachain_core::{Block, ChainParams, LegacyTransaction, Transaction};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Largest gossiped transaction: a contract deployment with a full semantic payload
+pub const MAX_GOSSIP_TX_SIZE: usize = 128 * 1024;
+/// Largest sync topic message; they are all short text
+pub const MAX_SYNC_MESSAGE_SIZE: usize = 1024;
+
+/// Messages the sync topic carries, by prefix
+pub const SYNC_PREFIXES: [&str; 8] = [
+    "HEIGHT:",
+    "VALIDATOR:",
+    "CHECKPOINT:",
+    "REVOKE_BLOCK:",
+    "GET_STATE_ROOT:",
+    "STATE_ROOT:",
+    "GET_BLOCK_TXS:",
+    "GET_BLOCKS:",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GossipTopic {
+    Blocks,
+    Transactions,
+    Sync,
+    CompactBlocks,
+    BlockTransactions,
+}
+
+impl GossipTopic {
+    pub const ALL: [GossipTopic; 5] = [
+        GossipTopic::Blocks,
+        GossipTopic::Transactions,
+        GossipTopic::Sync,
+        GossipTopic::CompactBlocks,
+        GossipTopic::BlockTransactions,
+    ];
+
+    /// Topic name after the network id, see `topic_name`
+    pub fn name(&self) -> &'static str {
+        match self {
+            GossipTopic::Blocks => "blocks",
+            GossipTopic::Transactions => "transactions",
+            GossipTopic::Sync => "sync",
+            GossipTopic::CompactBlocks => "compact-blocks",
+            GossipTopic::BlockTransactions => "block-txs",
+        }
+    }
+
+    /// What a message on this topic decodes to
+    pub fn payload(&self) -> &'static str {
+        match self {
+            GossipTopic::Blocks => "Block",
+            GossipTopic::Transactions => "Transaction",
+            GossipTopic::Sync => "UTF-8 text starting with one of SYNC_PREFIXES",
+            GossipTopic::CompactBlocks => "CompactBlock",
+            GossipTopic::BlockTransactions => "BlockTransactions",
+        }
+    }
+
+    /// Largest payload accepted on this topic, after decompression
+    pub fn max_size(&self, params: &ChainParams) -> usize {
+        match self {
+            GossipTopic::Blocks | GossipTopic::CompactBlocks | GossipTopic::BlockTransactions => {
+                params.block_limits.max_block_size
+            }
+            GossipTopic::Transactions => MAX_GOSSIP_TX_SIZE,
+            GossipTopic::Sync => MAX_SYNC_MESSAGE_SIZE,
+        }
+    }
+}
+
+/// Why a gossip message was not relayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GossipRejection {
+    /// Without our network magic
+    WrongNetwork,
+    Oversized,
+    /// Not what the topic carries
+    Malformed,
+    /// Decodes, but fails the stateless checks
+    Invalid,
+    /// A transaction for another fork than the one we are on
+    OtherFork,
+}
+
+impl GossipRejection {
+    pub const ALL: [GossipRejection; 5] = [
+        GossipRejection::WrongNetwork,
+        GossipRejection::Oversized,
+        GossipRejection::Malformed,
+        GossipRejection::Invalid,
+        GossipRejection::OtherFork,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GossipRejection::WrongNetwork => "wrong_network",
+            GossipRejection::Oversized => "oversized",
+            GossipRejection::Malformed => "malformed",
+            GossipRejection::Invalid => "invalid",
+            GossipRejection::OtherFork => "other_fork",
+        }
+    }
+
+    /// What gossipsub is told: the sender is only penalized for its own fault
+    pub fn acceptance(&self) -> MessageAcceptance {
+        match self {
+            GossipRejection::OtherFork => MessageAcceptance::Ignore,
+            _ => MessageAcceptance::Reject,
+        }
+    }
+}
+
+impl fmt::Display for GossipRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A message that passed validation, decoded
+#[derive(Debug)]
+pub enum GossipPayload {
+    Block(Box<Block>),
+    Transaction(Box<Transaction>),
+    Sync(String),
+    CompactBlock(Box<CompactBlock>),
+    BlockTransactions(BlockTransactions),
+}
+
+/// Check `data`, stripped of the network magic and decompressed, against
+/// `topic` on `network` while our chain is at `local_height`
+pub fn validate_gossip(
+    topic: GossipTopic,
+    network: &str,
+    local_height: u64,
+    data: &[u8],
+) -> Result<GossipPayload, GossipRejection> {
+    let params = ChainParams::for_network(network);
+    if data.len() > topic.max_size(params) {
+        return Err(GossipRejection::Oversized);
+    }
+    let max_txs = params.block_limits.max_tx_per_block;
+
+    match topic {
+        GossipTopic::Blocks => {
+            let block: Block = decode(data)?;
+            params
+                .check_block_version(block.header.block_height, block.header.version)
+                .map_err(|_| GossipRejection::Invalid)?;
+            if block.transactions.len() > max_txs {
+                return Err(GossipRejection::Invalid);
+            }
+            Ok(GossipPayload::Block(Box::new(block)))
+        }
+        GossipTopic::CompactBlocks => {
+            let block: CompactBlock = decode(data)?;
+            params
+                .check_block_version(block.header.block_height, block.header.version)
+                .map_err(|_| GossipRejection::Invalid)?;
+            if block.short_ids.len() > max_txs || block.prefilled.len() > block.short_ids.len() {
+                return Err(GossipRejection::Invalid);
+            }
+            Ok(GossipPayload::CompactBlock(Box::new(block)))
+        }
+        GossipTopic::BlockTransactions => {
+            let response: BlockTransactions = decode(data)?;
+            if response.transactions.len() > max_txs {
+                return Err(GossipRejection::Invalid);
+            }
+            Ok(GossipPayload::BlockTransactions(response))
+        }
+        GossipTopic::Transactions => {
+            let tx = decode::<Transaction>(data).or_else(|rejection| {
+                // Peers from before payloads gossip the legacy layout
+                if params.last_hard_fork_height(local_height + 1) == 0 {
+                    decode::<LegacyTransaction>(data).map(Transaction::from)
+                } else {
+                    Err(rejection)
+                }
+            })?;
+            tx.validate().map_err(|_| GossipRejection::Invalid)?;
+            tx.validate_fork_id(network, local_height + 1)
+                .map_err(|_| GossipRejection::OtherFork)?;
+            Ok(GossipPayload::Transaction(Box::new(tx)))
+        }
+        GossipTopic::Sync => {
+            let msg = String::from_utf8(data.to_vec()).map_err(|_| GossipRejection::Malformed)?;
+            if !SYNC_PREFIXES.iter().any(|prefix| msg.starts_with(prefix)) {
+                return Err(GossipRejection::Malformed);
+            }
+            Ok(GossipPayload::Sync(msg))
+        }
+    }
+}
+
+fn decode<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, GossipRejection> {
+    bincode::deserialize(data).map_err(|_| GossipRejection::Malformed)
+}
+
+/// Peer scoring driven by message validity alone: every rejected message
+/// costs the peer that sent it, and a handful of them graylists it. Mesh
+/// delivery rates are not scored, as small networks go quiet between blocks,
+/// and neither is IP colocation, as test networks run many nodes per host.
+pub fn peer_score_params(topics: &[gossipsub::TopicHash]) -> PeerScoreParams {
+    let topic_params = TopicScoreParams {
+        topic_weight: 1.0,
+        time_in_mesh_weight: 0.0,
+        first_message_deliveries_weight: 0.0,
+        mesh_message_deliveries_weight: 0.0,
+        mesh_failure_penalty_weight: 0.0,
+        invalid_message_deliveries_weight: -10.0,
+        invalid_message_deliveries_decay: 0.5,
+        ..TopicScoreParams::default()
+    };
+    PeerScoreParams {
+        topics: topics
+            .iter()
+            .map(|topic| (topic.clone(), topic_params.clone()))
+            .collect(),
+        ip_colocation_factor_weight: 0.0,
+        ..PeerScoreParams::default()
+    }
+}
+
+/// Gossip messages accepted and rejected, per topic
+#[derive(Default)]
+pub struct GossipValidationStats {
+    accepted: Mutex<BTreeMap<GossipTopic, u64>>,
+    rejected: Mutex<BTreeMap<(GossipTopic, GossipRejection), u64>>,
+}
+
+impl GossipValidationStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, topic: GossipTopic, outcome: Result<(), GossipRejection>) {
+        match outcome {
+            Ok(()) => *self.accepted.lock().entry(topic).or_default() += 1,
+            Err(rejection) => *self.rejected.lock().entry((topic, rejection)).or_default() += 1,
+        }
+    }
+
+    pub fn rejected(&self, topic: GossipTopic, rejection: GossipRejection) -> u64 {
+        self.rejected
+            .lock()
+            .get(&(topic, rejection))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        let mut out = String::from(
+            "# HELP spirachain_gossip_messages_total Gossip messages received, by topic and validation result\n\
+             # TYPE spirachain_gossip_messages_total counter\n",
+        );
+        let accepted = self.accepted.lock();
+        for topic in GossipTopic::ALL {
+            out.push_str(&format!(
+                "spirachain_gossip_messages_total{{topic=\"{}\",result=\"accepted\"}} {}\n",
+                topic.name(),
+                accepted.get(&topic).copied().unwrap_or(0)
+            ));
+            for rejection in GossipRejection::ALL {
+                out.push_str(&format!(
+                    "spirachain_gossip_messages_total{{topic=\"{}\",result=\"{}\"}} {}\n",
+                    topic.name(),
+                    rejection,
+                    self.rejected(topic, rejection)
+                ));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{fork_id, Amount, ChainBuilder, Hash, TransactionPayload, TxPattern};
+
+    #[test]
+    fn test_messages_are_checked_against_their_topic() {
+        let chain = ChainBuilder::new("testnet")
+            .with_tx_pattern(TxPattern::Transfers(2))
+            .build(2);
+        let block = chain.tip();
+        let encoded = bincode::serialize(block).unwrap();
+
+        assert!(matches!(
+            validate_gossip(GossipTopic::Blocks, "testnet", 1, &encoded),
+            Ok(GossipPayload::Block(_))
+        ));
+        // A block is not a transaction, nor a compact block
+        assert_eq!(
+            validate_gossip(GossipTopic::Transactions, "testnet", 1, &encoded).unwrap_err(),
+            GossipRejection::Malformed
+        );
+        assert!(validate_gossip(GossipTopic::CompactBlocks, "testnet", 1, &encoded).is_err());
+        let oversized = vec![0u8; MAX_GOSSIP_TX_SIZE + 1];
+        assert_eq!(
+            validate_gossip(GossipTopic::Transactions, "testnet", 1, &oversized).unwrap_err(),
+            GossipRejection::Oversized
+        );
+
+        let tx = block.transactions[1].clone();
+        let encoded_tx = bincode::serialize(&tx).unwrap();
+        assert!(validate_gossip(GossipTopic::Transactions, "testnet", 1, &encoded_tx).is_ok());
+        let coinbase = bincode::serialize(&block.transactions[0]).unwrap();
+        assert_eq!(
+            validate_gossip(GossipTopic::Transactions, "testnet", 1, &coinbase).unwrap_err(),
+            GossipRejection::Invalid
+        );
+        let mut other_fork = tx.clone().with_fork_id(Hash::new([9u8; 32]));
+        other_fork.amount = Amount::qbt(5);
+        let rejection = validate_gossip(
+            GossipTopic::Transactions,
+            "testnet",
+            1,
+            &bincode::serialize(&other_fork).unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(rejection, GossipRejection::OtherFork);
+        assert!(matches!(rejection.acceptance(), MessageAcceptance::Ignore));
+        assert_eq!(tx.fork_id, fork_id("testnet", 2));
+
+        // A transfer from a peer on the release before payloads
+        let legacy = LegacyTransaction {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature.clone(),
+            purpose: tx.purpose.clone(),
+            semantic_vector: Vec::new(),
+            entities: Vec::new(),
+            intent: None,
+            related_txs: Vec::new(),
+            spiral_position: None,
+            thread_id: None,
+            extra_data: tx.extra_data.clone(),
+        };
+        let encoded_legacy = bincode::serialize(&legacy).unwrap();
+        assert!(bincode::deserialize::<Transaction>(&encoded_legacy).is_err());
+        match validate_gossip(GossipTopic::Transactions, "testnet", 1, &encoded_legacy) {
+            Ok(GossipPayload::Transaction(upgraded)) => {
+                assert_eq!(upgraded.payload, TransactionPayload::Transfer);
+                assert_eq!((upgraded.tx_hash, upgraded.amount), (tx.tx_hash, tx.amount));
+            }
+            other => panic!("legacy transfer not accepted: {:?}", other),
+        }
+
+        assert!(validate_gossip(GossipTopic::Sync, "testnet", 1, b"HEIGHT:12").is_ok());
+        assert_eq!(
+            validate_gossip(GossipTopic::Sync, "testnet", 1, b"HELLO").unwrap_err(),
+            GossipRejection::Malformed
+        );
+
+        let stats = GossipValidationStats::new();
+        stats.record(GossipTopic::Sync, Ok(()));
+        stats.record(GossipTopic::Sync, Err(GossipRejection::Malformed));
+        let metrics = stats.export_prometheus();
+        assert!(metrics
+            .contains("spirachain_gossip_messages_total{topic=\"sync\",result=\"accepted\"} 1"));
+        assert!(metrics
+            .contains("spirachain_gossip_messages_total{topic=\"sync\",result=\"malformed\"} 1"));
+    }
+}
@@ -0,0 +1,149 @@
+// Chain handshake
+// Identify tells us which network and protocol version a peer speaks, and
+// the agent string adds the genesis hash and fork id of the chain it follows.
+// A peer on another network, below the protocol the chain requires, built on
+// another genesis or still on a fork we left (or not yet on ours) cannot sync
+// with us, so it is disconnected as soon as it identifies. Peers that do not
+// advertise a chain are kept. Rejections are counted per reason.
+
+use crate::AgentInfo;
+use spirachain_core::{fork_id, Hash};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Why a peer was disconnected during the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainMismatch {
+    Network,
+    Protocol,
+    Genesis,
+    ForkId,
+}
+
+impl ChainMismatch {
+    pub const ALL: [ChainMismatch; 4] = [
+        ChainMismatch::Network,
+        ChainMismatch::Protocol,
+        ChainMismatch::Genesis,
+        ChainMismatch::ForkId,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChainMismatch::Network => "network",
+            ChainMismatch::Protocol => "protocol",
+            ChainMismatch::Genesis => "genesis",
+            ChainMismatch::ForkId => "fork_id",
+        }
+    }
+}
+
+impl fmt::Display for ChainMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Compare the chain a peer advertises with ours. The fork id is checked at
+/// the height the peer advertised, so a peer behind a hard fork is not
+/// mistaken for one on another chain.
+pub fn check_peer_chain(
+    network: &str,
+    local_genesis: Option<&Hash>,
+    peer: &AgentInfo,
+) -> Result<(), (ChainMismatch, String)> {
+    if let (Some(ours), Some(theirs)) = (local_genesis, &peer.genesis) {
+        if ours != theirs {
+            return Err((
+                ChainMismatch::Genesis,
+                format!("peer genesis {} differs from ours {}", theirs, ours),
+            ));
+        }
+    }
+    if let Some(theirs) = &peer.fork_id {
+        let expected = fork_id(network, peer.height);
+        if *theirs != expected {
+            return Err((
+                ChainMismatch::ForkId,
+                format!(
+                    "peer fork id {} at height {} differs from ours {}",
+                    theirs, peer.height, expected
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Peers disconnected during the handshake, per reason
+#[derive(Default)]
+pub struct HandshakeStats {
+    rejections: [AtomicU64; 4],
+}
+
+impl HandshakeStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, reason: ChainMismatch) {
+        self.rejections[reason as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rejections(&self, reason: ChainMismatch) -> u64 {
+        self.rejections[reason as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        let mut out = String::from(
+            "# HELP spirachain_peer_handshake_rejections_total Peers disconnected for following another chain\n\
+             # TYPE spirachain_peer_handshake_rejections_total counter\n",
+        );
+        for reason in ChainMismatch::ALL {
+            out.push_str(&format!(
+                "spirachain_peer_handshake_rejections_total{{reason=\"{}\"}} {}\n",
+                reason,
+                self.rejections(reason)
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NodeRole;
+
+    #[test]
+    fn test_peer_chain_checks() {
+        let genesis = Hash::new([1u8; 32]);
+        let peer = AgentInfo::local(NodeRole::Full, 10).with_chain(genesis, fork_id("testnet", 10));
+        let parsed = AgentInfo::parse(&peer.to_string()).unwrap();
+        assert_eq!(parsed, peer);
+        assert!(check_peer_chain("testnet", Some(&genesis), &parsed).is_ok());
+
+        // Nodes that do not advertise a chain are kept
+        let legacy = AgentInfo::local(NodeRole::Full, 10);
+        assert!(check_peer_chain("testnet", Some(&genesis), &legacy).is_ok());
+
+        let other = Hash::new([2u8; 32]);
+        assert_eq!(
+            check_peer_chain("testnet", Some(&other), &parsed)
+                .unwrap_err()
+                .0,
+            ChainMismatch::Genesis
+        );
+        let forked = AgentInfo::local(NodeRole::Full, 10).with_chain(genesis, other);
+        assert_eq!(
+            check_peer_chain("testnet", None, &forked).unwrap_err().0,
+            ChainMismatch::ForkId
+        );
+
+        let stats = HandshakeStats::new();
+        stats.record(ChainMismatch::Genesis);
+        assert!(stats
+            .export_prometheus()
+            .contains("spirachain_peer_handshake_rejections_total{reason=\"genesis\"} 1\n"));
+    }
+}
@@ -0,0 +1,167 @@
+// Persistent libp2p node identity
+// The ed25519 key behind the PeerId lives in the data dir so the PeerId, and
+// everything peers remember about it (scores, bans, bootstrap lists), survives
+// restarts.
+
+use libp2p::{identity::Keypair, PeerId};
+use spirachain_core::{Result, SpiraChainError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Identity key file, kept in the node data directory
+pub const NODE_KEY_FILE: &str = "node_key";
+
+pub fn node_key_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(NODE_KEY_FILE)
+}
+
+fn storage_error(path: &Path, e: impl std::fmt::Display) -> SpiraChainError {
+    SpiraChainError::StorageError(format!("{:?}: {}", path, e))
+}
+
+/// The key must only be readable by the node's user
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = std::fs::metadata(path)
+        .map_err(|e| storage_error(path, e))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        return Err(SpiraChainError::CryptoError(format!(
+            "{:?} is accessible by other users (mode {:o}); run `chmod 600` on it",
+            path,
+            mode & 0o777
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Write `bytes` readable by the owner only, replacing any existing file
+pub fn write_private_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| storage_error(parent, e))?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&tmp).map_err(|e| storage_error(&tmp, e))?;
+    file.write_all(bytes).map_err(|e| storage_error(&tmp, e))?;
+    file.sync_all().map_err(|e| storage_error(&tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| storage_error(path, e))
+}
+
+pub fn load_identity(path: &Path) -> Result<Keypair> {
+    check_permissions(path)?;
+    let bytes = std::fs::read(path).map_err(|e| storage_error(path, e))?;
+    Keypair::from_protobuf_encoding(&bytes)
+        .map_err(|e| SpiraChainError::CryptoError(format!("Invalid node key {:?}: {}", path, e)))
+}
+
+pub fn save_identity(path: &Path, keypair: &Keypair) -> Result<()> {
+    let bytes = keypair
+        .to_protobuf_encoding()
+        .map_err(|e| SpiraChainError::CryptoError(e.to_string()))?;
+    write_private_file(path, &bytes)
+}
+
+/// Load the node key from `data_dir`, creating it on first start
+pub fn load_or_create_identity(data_dir: &Path) -> Result<Keypair> {
+    let path = node_key_path(data_dir);
+    if path.exists() {
+        let keypair = load_identity(&path)?;
+        info!("🔑 Loaded node identity from {}", path.display());
+        return Ok(keypair);
+    }
+
+    let keypair = Keypair::generate_ed25519();
+    save_identity(&path, &keypair)?;
+    info!("🔑 Generated new node identity at {}", path.display());
+    Ok(keypair)
+}
+
+/// Replace the node key with a fresh one. The old key is kept next to it as
+/// `node_key.old`; the new PeerId is used from the next start.
+pub fn rotate_identity(data_dir: &Path) -> Result<(Option<PeerId>, PeerId)> {
+    let path = node_key_path(data_dir);
+    let old = if path.exists() {
+        let old_key = load_identity(&path)?;
+        let backup = path.with_extension("old");
+        save_identity(&backup, &old_key)?;
+        Some(PeerId::from(old_key.public()))
+    } else {
+        None
+    };
+
+    let keypair = Keypair::generate_ed25519();
+    save_identity(&path, &keypair)?;
+    warn!(
+        "🔄 Node identity rotated; peers will see a new PeerId after restart ({} was kept as {})",
+        path.display(),
+        path.with_extension("old").display()
+    );
+
+    Ok((old, PeerId::from(keypair.public())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "spirachain-identity-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_identity_is_stable_across_loads() {
+        let dir = scratch_dir("stable");
+        let first = load_or_create_identity(&dir).unwrap();
+        let second = load_or_create_identity(&dir).unwrap();
+        assert_eq!(PeerId::from(first.public()), PeerId::from(second.public()));
+
+        let (old, new) = rotate_identity(&dir).unwrap();
+        assert_eq!(old, Some(PeerId::from(first.public())));
+        assert_ne!(Some(new), old);
+        assert_eq!(
+            PeerId::from(load_or_create_identity(&dir).unwrap().public()),
+            new
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_world_readable_key_is_rejected() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = scratch_dir("perms");
+        load_or_create_identity(&dir).unwrap();
+        let path = node_key_path(&dir);
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(load_identity(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
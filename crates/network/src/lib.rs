@@ -1,18 +1,47 @@
 pub mod bootstrap;
+pub mod compact_block;
+pub mod compression;
 pub mod encryption;
+pub mod gossip;
+pub mod gossip_validation;
+pub mod handshake;
+pub mod identity;
 pub mod libp2p_sync;
 pub mod libp2p_v53;
+pub mod network_spec;
+pub mod outbound_queue;
 pub mod p2p;
+pub mod peer_diversity;
+pub mod peer_latency;
+pub mod peer_manager;
+pub mod propagation;
 pub mod protocol;
+pub mod providers;
 pub mod sync;
+pub mod topology;
 
 pub use bootstrap::*;
+pub use compact_block::*;
+pub use compression::*;
 pub use encryption::*;
+pub use gossip::*;
+pub use gossip_validation::*;
+pub use handshake::*;
+pub use identity::*;
+pub use libp2p::PeerId;
 pub use libp2p_sync::{LibP2PNetworkWithSync, NetworkEvent};
 pub use libp2p_v53::LibP2PNetwork;
+pub use network_spec::*;
+pub use outbound_queue::*;
 pub use p2p::*;
+pub use peer_diversity::*;
+pub use peer_latency::{LivenessStats, PeerLatencyTracker, PeerRtt};
+pub use peer_manager::*;
+pub use propagation::*;
 pub use protocol::*;
+pub use providers::*;
 pub use sync::*;
+pub use topology::*;
 
 use spirachain_core::{Block, Result, Transaction};
 
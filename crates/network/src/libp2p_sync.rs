@@ -2,34 +2,170 @@
 // SIMPLE implementation: Gossipsub for broadcast + manual block requests
 
 use libp2p::{
-    gossipsub,
+    gossipsub, identify,
     identity::Keypair,
-    noise,
+    kad, mdns, noise, request_response,
     swarm::{Swarm, SwarmEvent},
-    tcp, yamux, Multiaddr, PeerId,
+    tcp, yamux, Multiaddr, PeerId, StreamProtocol,
 };
-use spirachain_core::{Block, Result, SpiraChainError, Transaction};
-use std::collections::{HashMap, HashSet};
+use spirachain_core::{
+    fork_id, Address, Block, ChainParams, Hash, Result, SpiraChainError, Transaction,
+    PROTOCOL_VERSION,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
 use crate::bootstrap::{discover_bootstrap_peers, BootstrapConfig};
+use behaviour::{SyncBehaviour, SyncBehaviourEvent};
+use crate::compact_block::{BlockTransactions, CompactBlock};
+use crate::compression::{
+    decode_compressed, encode_compressed, Compression, CompressionStats, COMPRESSION_THRESHOLD,
+};
+use crate::gossip::GossipParams;
+use crate::gossip_validation::{
+    peer_score_params, validate_gossip, GossipPayload, GossipRejection, GossipTopic,
+    GossipValidationStats,
+};
+use crate::handshake::{check_peer_chain, ChainMismatch, HandshakeStats};
+use crate::outbound_queue::{OutboundKind, OutboundQueue, OutboundQueueStats};
+use crate::peer_diversity::{PeerDiversity, PeerDiversityParams, PeerDiversityStats, PeerSource};
+use crate::peer_latency::{LatencyProbe, LivenessStats, PeerLatencyTracker};
+use crate::peer_manager::{AgentInfo, NodeRole, PeerManager, CAP_SYNC};
+use crate::propagation::{PropagationStats, PropagationTracker};
+use crate::providers::{ProviderDirectory, ProviderService};
+use crate::topology::{PeerView, TopologySnapshot};
+
+/// How often every connected peer is probed for latency
+const PROBE_INTERVAL: Duration = Duration::from_secs(15);
+/// Unanswered probes older than this count as failures
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Probes a peer may miss in a row before it is disconnected
+const MAX_MISSED_PINGS: u32 = 4;
+/// A block request with no answer after this long fails over to the next peer
+const BLOCK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often outbound connections are topped up and rotated
+const PEER_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(10);
+/// Blocks requested per GET_BLOCKS message
+const SYNC_BATCH_SIZE: u64 = 50;
+/// Root of every protocol id and gossip topic; the network id follows it
+const PROTOCOL_PREFIX: &str = "/spirachain/";
+/// Bytes of network magic in front of every gossip payload
+const NETWORK_MAGIC_LEN: usize = 4;
+/// Gossipsub protocol id, after the network id
+pub const GOSSIPSUB_PROTOCOL: &str = "meshsub";
+/// Kademlia protocol name, after the network id
+pub const KADEMLIA_PROTOCOL: &str = "kad/1.0.0";
+/// Latency probe protocol name, after the network id
+pub const PROBE_PROTOCOL: &str = "probe/1.0.0";
+
+/// Gossip topic `kind` (blocks, sync, ...) of the chain named `network_id`
+pub fn topic_name(network_id: &str, kind: &str) -> String {
+    format!("{}{}/{}", PROTOCOL_PREFIX, network_id, kind)
+}
+
+/// Protocol id `suffix` (gossipsub, kademlia, the identify version) of the
+/// chain named `network_id`
+pub fn protocol_name(network_id: &str, suffix: &str) -> String {
+    format!("{}{}/{}", PROTOCOL_PREFIX, network_id, suffix)
+}
+
+/// Tag that lets a handler reject payloads from another chain even if a
+/// misconfigured peer publishes them on our topic
+pub fn network_magic(network_id: &str) -> [u8; NETWORK_MAGIC_LEN] {
+    let hash = blake3::hash(network_id.as_bytes());
+    let mut magic = [0u8; NETWORK_MAGIC_LEN];
+    magic.copy_from_slice(&hash.as_bytes()[..NETWORK_MAGIC_LEN]);
+    magic
+}
+
+/// Magic of compressed gossip; peers without compression drop it as foreign
+pub fn compressed_network_magic(network_id: &str) -> [u8; NETWORK_MAGIC_LEN] {
+    network_magic(&format!("{}/compressed", network_id))
+}
+
+fn strip_network_magic(magic: [u8; NETWORK_MAGIC_LEN], data: &[u8]) -> Option<&[u8]> {
+    data.strip_prefix(&magic[..])
+}
+
+/// `{height}:0x{hash}` from a `CHECKPOINT:`, `REVOKE_BLOCK:` or `STATE_ROOT:` sync message
+fn parse_checkpoint(msg: &str) -> Option<(u64, Hash)> {
+    let (height, hash) = msg.split_once(':')?;
+    let bytes = hex::decode(hash.strip_prefix("0x").unwrap_or(hash)).ok()?;
+    Some((height.parse().ok()?, Hash::from_slice(&bytes).ok()?))
+}
+
+mod behaviour {
+    use crate::peer_latency::LatencyProbe;
+    use libp2p::swarm::{behaviour::toggle::Toggle, NetworkBehaviour};
+    use libp2p::{gossipsub, identify, kad, mdns, request_response};
+
+    /// Gossip for blocks, transactions and sync, identify for peer agent
+    /// versions, Kademlia for capability provider records and peer
+    /// addresses, latency probes addressed to a single peer, and mDNS for
+    /// peers on the local network when enabled
+    #[derive(NetworkBehaviour)]
+    pub(super) struct SyncBehaviour {
+        pub(super) gossipsub: gossipsub::Behaviour,
+        pub(super) identify: identify::Behaviour,
+        pub(super) kademlia: kad::Behaviour<kad::store::MemoryStore>,
+        pub(super) probe: request_response::cbor::Behaviour<LatencyProbe, LatencyProbe>,
+        pub(super) mdns: Toggle<mdns::tokio::Behaviour>,
+    }
+}
+
+/// Outstanding GET_BLOCKS request addressed to a single peer
+struct PendingBlockRequest {
+    peer: PeerId,
+    start: u64,
+    end: u64,
+    sent_at: Instant,
+    answered: bool,
+    /// Gossip bytes of the blocks received so far
+    bytes: usize,
+}
 
 pub struct LibP2PNetworkWithSync {
-    swarm: Swarm<gossipsub::Behaviour>,
-    #[allow(dead_code)]
+    swarm: Swarm<SyncBehaviour>,
     local_peer_id: PeerId,
     connected_peers: HashSet<PeerId>,
     block_topic: gossipsub::IdentTopic,
     tx_topic: gossipsub::IdentTopic,
     sync_topic: gossipsub::IdentTopic, // For height announcements
+    compact_block_topic: gossipsub::IdentTopic, // Header + short tx IDs for new blocks
+    block_txs_topic: gossipsub::IdentTopic, // Missing transactions for compact blocks
     is_listening: bool,
     listen_port: u16,
     network: String,
+    network_id: String, // Network name + genesis hash prefix, namespaces topics and protocols
+    network_magic: [u8; NETWORK_MAGIC_LEN],
+    compressed_magic: [u8; NETWORK_MAGIC_LEN],
+    compression: Compression, // Preferred codec for large payloads
+    compression_stats: CompressionStats,
     local_height: u64,
     last_height_announcement: std::time::Instant,
     bootstrap_addrs: Vec<Multiaddr>, // Store bootstrap addresses for reconnection
     last_reconnect_attempt: std::time::Instant,
     peer_heights: HashMap<PeerId, u64>, // Track peer heights
+    latency: PeerLatencyTracker,        // Rolling response times for sync peer selection
+    liveness_stats: Arc<LivenessStats>,
+    propagation: PropagationTracker,    // How long compact block announcements take to reach us
+    last_probe_round: Instant,
+    pending_block_request: Option<PendingBlockRequest>,
+    banned_peers: HashSet<PeerId>,
+    peers: PeerManager, // identify agent versions and capabilities
+    genesis_hash: Option<Hash>, // Chain we follow, checked against peers' during the handshake
+    handshake_stats: Arc<HandshakeStats>,
+    gossip_stats: Arc<GossipValidationStats>,
+    outbound: OutboundQueue, // Blocks and transactions published while no peer was subscribed
+    peer_protocol_versions: HashMap<PeerId, u32>, // Highest protocol version each peer supports
+    peer_validators: HashMap<PeerId, Address>, // Validator addresses announced by peers
+    role: NodeRole,
+    providers: ProviderDirectory, // Archive and snapshot providers found through the DHT
+    provided_snapshot: Option<ProviderService>, // Snapshot bucket we currently announce
+    diversity: PeerDiversity, // Outbound minimum, subnet caps, discovery sources and rotation
+    last_peer_maintenance: Instant,
 }
 
 // Network events
@@ -39,9 +175,43 @@ pub enum NetworkEvent {
     PeerDisconnected(PeerId),
     PeerHeight { peer: PeerId, height: u64 },
     NewBlock(Block),
+    NewCompactBlock {
+        peer: PeerId,
+        block: CompactBlock,
+    },
+    BlockTransactionsRequested {
+        peer: PeerId,
+        block_hash: Hash,
+        indexes: Vec<u16>,
+    },
+    BlockTransactions(BlockTransactions),
     NewTransaction(Transaction),
     BlockRequested(u64), // A peer requested a specific block height
     ValidatorAnnouncement(spirachain_core::Address), // A peer announced itself as a validator
+    /// A peer finalized the block at `height`
+    Checkpoint {
+        height: u64,
+        block_hash: Hash,
+    },
+    /// A peer relayed this block before validating it, and it failed
+    BlockRevoked {
+        peer: PeerId,
+        height: u64,
+        block_hash: Hash,
+    },
+    /// A peer asked for our tip and the state root it left us with
+    StateRootRequested {
+        peer: PeerId,
+        nonce: u64,
+    },
+    /// A peer's answer to our state root request `nonce`
+    StateRoot {
+        peer: PeerId,
+        nonce: u64,
+        height: u64,
+        block_hash: Hash,
+        state_root: Hash,
+    },
 }
 
 impl LibP2PNetworkWithSync {
@@ -49,29 +219,158 @@ impl LibP2PNetworkWithSync {
         Self::new_with_network(port, "testnet", local_height).await
     }
 
+    /// Start with a throwaway identity; the PeerId changes on every start
     pub async fn new_with_network(port: u16, network: &str, local_height: u64) -> Result<Self> {
+        Self::new_with_identity(
+            port,
+            network,
+            local_height,
+            Keypair::generate_ed25519(),
+            NodeRole::Full,
+            &GossipParams::default(),
+            None,
+        )
+        .await
+    }
+
+    /// Start with a persisted identity (see `load_or_create_identity`),
+    /// advertising `role` and its capabilities to peers and meshing with
+    /// `gossip`, tuned to `network` (see [`GossipParams::effective`]). With
+    /// `genesis_hash` the chain is advertised too, and peers following
+    /// another one are disconnected.
+    pub async fn new_with_identity(
+        port: u16,
+        network: &str,
+        local_height: u64,
+        local_key: Keypair,
+        role: NodeRole,
+        gossip: &GossipParams,
+        genesis_hash: Option<Hash>,
+    ) -> Result<Self> {
         info!("🌐 Initializing LibP2P Network with block sync");
         info!("   Network: {}", network.to_uppercase());
         info!("   Local Height: {}", local_height);
 
-        // Generate keypair
-        let local_key = Keypair::generate_ed25519();
         let local_peer_id = PeerId::from(local_key.public());
+        let network_id = ChainParams::for_network(network).network_id();
 
         info!("   Local PeerID: {}", local_peer_id);
+        info!("   Network ID: {}", network_id);
+
+        let mut agent = AgentInfo::local(role, local_height);
+        if let Some(genesis_hash) = genesis_hash {
+            agent = agent.with_chain(genesis_hash, fork_id(network, local_height));
+        }
+        let agent = agent.to_string();
+        info!("   Agent: {}", agent);
 
-        // Create Gossipsub
-        let gossipsub_config = gossipsub::ConfigBuilder::default()
-            .heartbeat_interval(std::time::Duration::from_secs(10))
+        // Create Gossipsub; peers of another chain fail protocol negotiation
+        gossip.validate()?;
+        let mesh = gossip.effective(network);
+        info!(
+            "   Gossip mesh: D={} ({}..{}), heartbeat {}ms, history {}/{}{}",
+            mesh.mesh_n,
+            mesh.mesh_n_low,
+            mesh.mesh_n_high,
+            mesh.heartbeat_ms,
+            mesh.history_gossip,
+            mesh.history_length,
+            if gossip.is_small_network(network) {
+                " (small network)"
+            } else {
+                ""
+            }
+        );
+        let mut gossipsub_builder = gossipsub::ConfigBuilder::default();
+        gossipsub_builder
+            .protocol_id_prefix(protocol_name(&network_id, GOSSIPSUB_PROTOCOL))
             .validation_mode(gossipsub::ValidationMode::Strict)
+            // Nothing is relayed until `handle_gossipsub_event` validated it
+            .validate_messages();
+        mesh.apply(&mut gossipsub_builder);
+        let gossipsub_config = gossipsub_builder
             .build()
             .map_err(|e| SpiraChainError::NetworkError(format!("Gossipsub config: {}", e)))?;
 
-        let behaviour = gossipsub::Behaviour::new(
+        let block_topic = gossipsub::IdentTopic::new(topic_name(&network_id, "blocks"));
+        let tx_topic = gossipsub::IdentTopic::new(topic_name(&network_id, "transactions"));
+        let sync_topic = gossipsub::IdentTopic::new(topic_name(&network_id, "sync"));
+        let compact_block_topic =
+            gossipsub::IdentTopic::new(topic_name(&network_id, "compact-blocks"));
+        let block_txs_topic = gossipsub::IdentTopic::new(topic_name(&network_id, "block-txs"));
+
+        let mut gossipsub = gossipsub::Behaviour::new(
             gossipsub::MessageAuthenticity::Signed(local_key.clone()),
             gossipsub_config,
         )
         .map_err(|e| SpiraChainError::NetworkError(format!("Gossipsub init: {}", e)))?;
+        let scored_topics = [
+            &block_topic,
+            &tx_topic,
+            &sync_topic,
+            &compact_block_topic,
+            &block_txs_topic,
+        ]
+        .map(|topic| topic.hash());
+        gossipsub
+            .with_peer_score(
+                peer_score_params(&scored_topics),
+                gossipsub::PeerScoreThresholds::default(),
+            )
+            .map_err(|e| SpiraChainError::NetworkError(format!("Gossipsub scoring: {}", e)))?;
+
+        let identify = identify::Behaviour::new(
+            identify::Config::new(
+                protocol_name(&network_id, &PROTOCOL_VERSION.to_string()),
+                local_key.public(),
+            )
+            .with_agent_version(agent),
+        );
+        // Own protocol name per chain, so DHTs of different networks never merge
+        let kad_protocol =
+            StreamProtocol::try_from_owned(protocol_name(&network_id, KADEMLIA_PROTOCOL))
+                .map_err(|e| SpiraChainError::NetworkError(format!("Kademlia protocol: {}", e)))?;
+        let mut kad_config = kad::Config::default();
+        kad_config.set_protocol_names(vec![kad_protocol]);
+        let mut kademlia = kad::Behaviour::with_config(
+            local_peer_id,
+            kad::store::MemoryStore::new(local_peer_id),
+            kad_config,
+        );
+        // Light nodes serve nothing, so they only query
+        kademlia.set_mode(Some(if role == NodeRole::Light {
+            kad::Mode::Client
+        } else {
+            kad::Mode::Server
+        }));
+
+        let probe_protocol =
+            StreamProtocol::try_from_owned(protocol_name(&network_id, PROBE_PROTOCOL))
+                .map_err(|e| SpiraChainError::NetworkError(format!("Probe protocol: {}", e)))?;
+        let probe = request_response::cbor::Behaviour::new(
+            [(probe_protocol, request_response::ProtocolSupport::Full)],
+            request_response::Config::default().with_request_timeout(PROBE_TIMEOUT),
+        );
+
+        let mdns = if BootstrapConfig::for_network(network).enable_mdns {
+            match mdns::tokio::Behaviour::new(mdns::Config::default(), local_peer_id) {
+                Ok(mdns) => Some(mdns),
+                Err(e) => {
+                    warn!("⚠️  mDNS discovery unavailable: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let behaviour = SyncBehaviour {
+            gossipsub,
+            identify,
+            kademlia,
+            probe,
+            mdns: mdns.into(),
+        };
 
         // Create Swarm
         let swarm = libp2p::SwarmBuilder::with_existing_identity(local_key)
@@ -89,9 +388,8 @@ impl LibP2PNetworkWithSync {
             })
             .build();
 
-        let block_topic = gossipsub::IdentTopic::new("spirachain-blocks");
-        let tx_topic = gossipsub::IdentTopic::new("spirachain-transactions");
-        let sync_topic = gossipsub::IdentTopic::new("spirachain-sync");
+        let network_magic = network_magic(&network_id);
+        let compressed_magic = compressed_network_magic(&network_id);
 
         info!("✅ P2P network initialized with Gossipsub");
 
@@ -102,14 +400,39 @@ impl LibP2PNetworkWithSync {
             block_topic,
             tx_topic,
             sync_topic,
+            compact_block_topic,
+            block_txs_topic,
             is_listening: false,
             listen_port: port,
             network: network.to_string(),
+            network_id,
+            network_magic,
+            compressed_magic,
+            compression: Compression::Snappy,
+            compression_stats: CompressionStats::default(),
             local_height,
             last_height_announcement: std::time::Instant::now(),
             bootstrap_addrs: Vec::new(),
             last_reconnect_attempt: std::time::Instant::now(),
             peer_heights: HashMap::new(),
+            latency: PeerLatencyTracker::new(),
+            liveness_stats: Arc::new(LivenessStats::new()),
+            propagation: PropagationTracker::new(),
+            last_probe_round: Instant::now(),
+            pending_block_request: None,
+            banned_peers: HashSet::new(),
+            peers: PeerManager::new(),
+            genesis_hash,
+            handshake_stats: Arc::new(HandshakeStats::new()),
+            gossip_stats: Arc::new(GossipValidationStats::new()),
+            outbound: OutboundQueue::new(),
+            peer_protocol_versions: HashMap::new(),
+            peer_validators: HashMap::new(),
+            role,
+            providers: ProviderDirectory::new(),
+            provided_snapshot: None,
+            diversity: PeerDiversity::new(PeerDiversityParams::default(), Instant::now()),
+            last_peer_maintenance: Instant::now(),
         })
     }
 
@@ -141,18 +464,33 @@ impl LibP2PNetworkWithSync {
         // Subscribe to topics
         self.swarm
             .behaviour_mut()
+            .gossipsub
             .subscribe(&self.block_topic)
             .map_err(|e| SpiraChainError::NetworkError(format!("Subscribe blocks: {}", e)))?;
         self.swarm
             .behaviour_mut()
+            .gossipsub
             .subscribe(&self.tx_topic)
             .map_err(|e| SpiraChainError::NetworkError(format!("Subscribe tx: {}", e)))?;
         self.swarm
             .behaviour_mut()
+            .gossipsub
             .subscribe(&self.sync_topic)
             .map_err(|e| SpiraChainError::NetworkError(format!("Subscribe sync: {}", e)))?;
+        self.swarm
+            .behaviour_mut()
+            .gossipsub
+            .subscribe(&self.compact_block_topic)
+            .map_err(|e| {
+                SpiraChainError::NetworkError(format!("Subscribe compact blocks: {}", e))
+            })?;
+        self.swarm
+            .behaviour_mut()
+            .gossipsub
+            .subscribe(&self.block_txs_topic)
+            .map_err(|e| SpiraChainError::NetworkError(format!("Subscribe block txs: {}", e)))?;
 
-        info!("✅ Subscribed to topics: blocks, transactions, sync");
+        info!("✅ Subscribed to topics: blocks, compact blocks, transactions, sync");
 
         // Discover bootstrap peers
         info!("🔍 Discovering bootstrap peers...");
@@ -169,6 +507,12 @@ impl LibP2PNetworkWithSync {
                         if let Ok(addr) = addr_str.parse::<Multiaddr>() {
                             // Store bootstrap addresses for reconnection
                             self.bootstrap_addrs.push(addr.clone());
+                            let source = if config.static_peers.contains(addr_str) {
+                                PeerSource::Static
+                            } else {
+                                PeerSource::DnsSeed
+                            };
+                            self.diversity.record_dial(addr.clone(), source, Instant::now());
                             
                             // Try to dial - if it fails with "Broken pipe", it's probably ourselves
                             // LibP2P will automatically prevent self-dial
@@ -206,6 +550,11 @@ impl LibP2PNetworkWithSync {
         // Announce our height
         self.announce_height();
 
+        if self.role == NodeRole::Archive {
+            self.start_providing(ProviderService::Archive);
+        }
+        self.update_snapshot_provider();
+
         Ok(())
     }
 
@@ -213,6 +562,9 @@ impl LibP2PNetworkWithSync {
     pub fn set_local_height(&mut self, height: u64) {
         let height_changed = height != self.local_height;
         self.local_height = height;
+        if height_changed {
+            self.update_snapshot_provider();
+        }
 
         // Announce height every 10 seconds (keep-alive) OR if changed
         let elapsed = self.last_height_announcement.elapsed();
@@ -222,15 +574,279 @@ impl LibP2PNetworkWithSync {
         }
     }
 
+    fn start_providing(&mut self, service: ProviderService) {
+        let key = service.key(&self.network_id);
+        match self.swarm.behaviour_mut().kademlia.start_providing(key) {
+            Ok(_) => info!("📇 Providing {} to the DHT", service),
+            Err(e) => warn!("Failed to publish {} provider record: {}", service, e),
+        }
+    }
+
+    /// Announce the snapshot bucket we reached, withdrawing the previous one.
+    /// Light nodes hold no blocks and never announce.
+    fn update_snapshot_provider(&mut self) {
+        if !self.is_listening || self.role == NodeRole::Light {
+            return;
+        }
+        let service = ProviderService::snapshot_at(self.local_height);
+        if service == ProviderService::snapshot_at(0) || self.provided_snapshot == Some(service) {
+            return;
+        }
+
+        if let Some(previous) = self.provided_snapshot.replace(service) {
+            let key = previous.key(&self.network_id);
+            self.swarm.behaviour_mut().kademlia.stop_providing(&key);
+        }
+        self.start_providing(service);
+    }
+
+    /// Ask the DHT for peers able to serve blocks from `start` on, at most once
+    /// per [`crate::PROVIDER_LOOKUP_INTERVAL`] for each service
+    fn lookup_providers(&mut self, start: u64) {
+        let best_height = self.peer_heights.values().copied().max().unwrap_or(0);
+        let mut services = vec![ProviderService::Archive];
+        let snapshot = ProviderService::snapshot_at(best_height);
+        if snapshot.serves(start) {
+            services.push(snapshot);
+        }
+
+        for service in services {
+            if self.providers.start_lookup(service) {
+                debug!("🔎 Looking up {} providers", service);
+                let key = service.key(&self.network_id);
+                self.swarm.behaviour_mut().kademlia.get_providers(key);
+            }
+        }
+    }
+
+    /// Answer latency probes with their own nonce and time the answers to ours.
+    /// Unanswered probes expire in `maintain_sync` and count as missed.
+    fn handle_probe_event(&mut self, event: request_response::Event<LatencyProbe, LatencyProbe>) {
+        let request_response::Event::Message { peer, message } = event else {
+            return;
+        };
+        match message {
+            request_response::Message::Request { request, channel, .. } => {
+                if self
+                    .swarm
+                    .behaviour_mut()
+                    .probe
+                    .send_response(channel, request)
+                    .is_err()
+                {
+                    debug!("Failed to answer latency probe from {}", peer);
+                }
+            }
+            request_response::Message::Response { response, .. } => {
+                if let Some(rtt) = self.latency.complete_probe(response.nonce, &peer) {
+                    debug!("⏱️  Peer {} latency: {:?}", peer, rtt);
+                }
+            }
+        }
+    }
+
+    fn handle_kademlia_event(&mut self, event: kad::Event) {
+        if let kad::Event::RoutingUpdated {
+            peer, addresses, ..
+        } = &event
+        {
+            if *peer != self.local_peer_id {
+                self.diversity.add_candidate(
+                    addresses.first().clone(),
+                    Some(*peer),
+                    PeerSource::Kademlia,
+                );
+            }
+            return;
+        }
+
+        let kad::Event::OutboundQueryProgressed {
+            result:
+                kad::QueryResult::GetProviders(Ok(kad::GetProvidersOk::FoundProviders {
+                    key,
+                    providers,
+                })),
+            ..
+        } = event
+        else {
+            return;
+        };
+        let Some(service) = ProviderService::from_key(&self.network_id, &key) else {
+            return;
+        };
+
+        let new: Vec<PeerId> = providers
+            .into_iter()
+            .filter(|peer| *peer != self.local_peer_id && !self.banned_peers.contains(peer))
+            .collect();
+        for peer in &new {
+            if !self.connected_peers.contains(peer) {
+                // Kademlia supplies the addresses it learned with the record
+                match self.swarm.dial(*peer) {
+                    Ok(()) => debug!("📞 Dialing {} provider {}", service, peer),
+                    Err(e) => debug!("⊘ Cannot dial {} provider {}: {}", service, peer, e),
+                }
+            }
+        }
+        if !new.is_empty() {
+            info!("📇 Found {} {} provider(s)", new.len(), service);
+        }
+        self.providers.record(service, new);
+    }
+
+    /// Codec for payloads of at least [`COMPRESSION_THRESHOLD`] bytes
+    pub fn set_compression(&mut self, compression: Compression) {
+        if compression != self.compression {
+            info!("🗜️  P2P compression: {}", compression);
+            self.compression = compression;
+        }
+    }
+
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.compression_stats.clone()
+    }
+
+    /// Whether every connected peer advertised it reads `codec`. Peers that
+    /// have not identified yet might not, so they count as unable.
+    fn peers_read(&self, codec: Compression) -> bool {
+        let Some(capability) = codec.capability() else {
+            return true;
+        };
+        self.connected_peers.iter().all(|peer| {
+            self.peers
+                .info(peer)
+                .is_some_and(|info| info.supports(capability))
+        })
+    }
+
+    /// Publish on one of our topics, tagged with the network magic. Large
+    /// payloads go out compressed with the best codec all peers read.
+    fn publish(
+        &mut self,
+        topic: gossipsub::IdentTopic,
+        data: &[u8],
+    ) -> std::result::Result<gossipsub::MessageId, gossipsub::PublishError> {
+        if data.len() >= COMPRESSION_THRESHOLD {
+            let codec = self.compression.negotiate(|codec| self.peers_read(codec));
+            if codec != self.compression {
+                self.compression_stats.fallbacks += 1;
+            }
+            if codec != Compression::None {
+                match encode_compressed(&self.compressed_magic, codec, data) {
+                    Ok((payload, took)) => {
+                        self.compression_stats
+                            .record_sent(codec, data.len(), payload.len(), took);
+                        return self.swarm.behaviour_mut().gossipsub.publish(topic, payload);
+                    }
+                    Err(e) => warn!("Sending uncompressed: {}", e),
+                }
+            }
+        }
+        self.publish_uncompressed(topic, data)
+    }
+
+    /// Publish a block or transaction, or queue it until a peer subscribes
+    /// when none is there to take it. Queued behind anything already waiting,
+    /// so peers still get blocks in order.
+    fn publish_or_queue(
+        &mut self,
+        kind: OutboundKind,
+        data: Vec<u8>,
+    ) -> std::result::Result<(), gossipsub::PublishError> {
+        self.flush_outbound();
+        if !self.outbound.is_empty() {
+            self.outbound.push(kind, data, Instant::now());
+            return Ok(());
+        }
+
+        match self.publish(self.outbound_topic(kind), &data) {
+            Ok(_) => Ok(()),
+            Err(gossipsub::PublishError::InsufficientPeers) => {
+                info!("📭 No peers to publish {} to, queued for retry", kind.as_str());
+                self.outbound.push(kind, data, Instant::now());
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn outbound_topic(&self, kind: OutboundKind) -> gossipsub::IdentTopic {
+        match kind {
+            OutboundKind::Block => self.compact_block_topic.clone(),
+            OutboundKind::Transaction => self.tx_topic.clone(),
+        }
+    }
+
+    /// Publish what waits in the outbound queue, as far as peers take it
+    fn flush_outbound(&mut self) {
+        if self.outbound.is_empty() {
+            return;
+        }
+        let mut outbound = std::mem::take(&mut self.outbound);
+        let before = outbound.len();
+        outbound.flush(Instant::now(), |kind, data| {
+            self.publish(self.outbound_topic(kind), data).map(|_| ())
+        });
+        if outbound.len() < before {
+            info!(
+                "📬 Published {} queued message(s), {} still waiting",
+                before - outbound.len(),
+                outbound.len()
+            );
+        }
+        self.outbound = outbound;
+    }
+
+    /// Depth and outcomes of the outbound queue
+    pub fn outbound_queue_stats(&self) -> Arc<OutboundQueueStats> {
+        self.outbound.stats()
+    }
+
+    fn publish_uncompressed(
+        &mut self,
+        topic: gossipsub::IdentTopic,
+        data: &[u8],
+    ) -> std::result::Result<gossipsub::MessageId, gossipsub::PublishError> {
+        let mut payload = Vec::with_capacity(NETWORK_MAGIC_LEN + data.len());
+        payload.extend_from_slice(&self.network_magic);
+        payload.extend_from_slice(data);
+        self.swarm.behaviour_mut().gossipsub.publish(topic, payload)
+    }
+
+    /// Gossip relays payloads verbatim, so peers that cannot read `codec` get
+    /// our own uncompressed copy instead
+    fn relay_uncompressed(
+        &mut self,
+        topic: &gossipsub::TopicHash,
+        codec: Compression,
+        data: &[u8],
+    ) {
+        if self.peers_read(codec) {
+            return;
+        }
+        let Some(topic) = [
+            &self.block_topic,
+            &self.tx_topic,
+            &self.sync_topic,
+            &self.compact_block_topic,
+            &self.block_txs_topic,
+        ]
+        .into_iter()
+        .find(|ours| ours.hash() == *topic)
+        .cloned() else {
+            return;
+        };
+
+        match self.publish_uncompressed(topic, data) {
+            Ok(_) => self.compression_stats.relayed_uncompressed += 1,
+            Err(e) => debug!("Failed to relay uncompressed payload: {}", e),
+        }
+    }
+
     /// Announce our blockchain height to peers
     fn announce_height(&mut self) {
         let msg = format!("HEIGHT:{}", self.local_height);
-        let data = msg.as_bytes().to_vec();
-        if let Err(e) = self
-            .swarm
-            .behaviour_mut()
-            .publish(self.sync_topic.clone(), data)
-        {
+        if let Err(e) = self.publish(self.sync_topic.clone(), msg.as_bytes()) {
             debug!("Failed to announce height: {}", e);
         } else {
             debug!("📢 Announced height: {}", self.local_height);
@@ -240,18 +856,59 @@ impl LibP2PNetworkWithSync {
     /// Announce that we are a validator (call this once at startup)
     pub fn announce_validator(&mut self, validator_address: &spirachain_core::Address) {
         let msg = format!("VALIDATOR:{}", validator_address);
-        let data = msg.as_bytes().to_vec();
-        if let Err(e) = self
-            .swarm
-            .behaviour_mut()
-            .publish(self.sync_topic.clone(), data)
-        {
+        if let Err(e) = self.publish(self.sync_topic.clone(), msg.as_bytes()) {
             warn!("Failed to announce validator address: {}", e);
         } else {
             info!("📣 Announced validator address: {}", validator_address);
         }
     }
 
+    /// Gossip a newly finalized checkpoint
+    pub fn broadcast_checkpoint(&mut self, height: u64, block_hash: &Hash) {
+        let msg = format!("CHECKPOINT:{}:{}", height, block_hash);
+        if let Err(e) = self.publish(self.sync_topic.clone(), msg.as_bytes()) {
+            debug!("Failed to broadcast checkpoint: {}", e);
+        } else {
+            info!("📍 Broadcast checkpoint at height {}", height);
+        }
+    }
+
+    /// Withdraw a block we relayed before validating it
+    pub fn revoke_block(&mut self, height: u64, block_hash: &Hash) {
+        let msg = format!("REVOKE_BLOCK:{}:{}", height, block_hash);
+        if let Err(e) = self.publish(self.sync_topic.clone(), msg.as_bytes()) {
+            warn!("Failed to revoke block {}: {}", height, e);
+        } else {
+            warn!("↩️  Revoked relayed block {} ({})", height, block_hash);
+        }
+    }
+
+    /// Ask `peer` for its tip and state root, to cross-check ours
+    pub fn request_state_root(&mut self, peer: PeerId, nonce: u64) {
+        let request = format!("GET_STATE_ROOT:{}@{}", nonce, peer);
+        if let Err(e) = self.publish(self.sync_topic.clone(), request.as_bytes()) {
+            debug!("Failed to request state root from {}: {}", peer, e);
+        }
+    }
+
+    /// Answer a state root request from `peer`
+    pub fn send_state_root(
+        &mut self,
+        peer: PeerId,
+        nonce: u64,
+        height: u64,
+        block_hash: &Hash,
+        state_root: &Hash,
+    ) {
+        let answer = format!(
+            "STATE_ROOT:{}:{}:{}:{}:{}",
+            peer, nonce, height, block_hash, state_root
+        );
+        if let Err(e) = self.publish(self.sync_topic.clone(), answer.as_bytes()) {
+            debug!("Failed to answer state root request: {}", e);
+        }
+    }
+
     /// Poll for network events (non-blocking)
     pub async fn poll_events(&mut self) -> Option<NetworkEvent> {
         // Use poll_next instead of select_next_some to avoid blocking
@@ -264,8 +921,34 @@ impl LibP2PNetworkWithSync {
                 None
             }
             SwarmEvent::ConnectionEstablished {
-                peer_id, endpoint, ..
+                peer_id,
+                endpoint,
+                num_established,
+                ..
             } => {
+                if self.banned_peers.contains(&peer_id) {
+                    debug!("⛔ Dropping connection from banned peer {}", peer_id);
+                    let _ = self.swarm.disconnect_peer_id(peer_id);
+                    return None;
+                }
+                if num_established.get() == 1 {
+                    if let Err(rejection) = self.diversity.admit(
+                        peer_id,
+                        endpoint.get_remote_address(),
+                        endpoint.is_dialer(),
+                        Instant::now(),
+                    ) {
+                        debug!(
+                            "⛔ Dropping connection with {} at {}: {}",
+                            peer_id,
+                            endpoint.get_remote_address(),
+                            rejection
+                        );
+                        let _ = self.swarm.disconnect_peer_id(peer_id);
+                        return None;
+                    }
+                }
+
                 info!(
                     "🤝 Connected to peer: {} at {}",
                     peer_id,
@@ -278,17 +961,78 @@ impl LibP2PNetworkWithSync {
 
                 Some(NetworkEvent::PeerConnected(peer_id))
             }
-            SwarmEvent::ConnectionClosed { peer_id, .. } => {
+            SwarmEvent::ConnectionClosed {
+                peer_id,
+                num_established,
+                ..
+            } => {
                 info!("👋 Disconnected from peer: {}", peer_id);
+                if num_established == 0 {
+                    self.diversity.remove(&peer_id);
+                }
                 self.connected_peers.remove(&peer_id);
                 self.peer_heights.remove(&peer_id);
+                self.latency.remove_peer(&peer_id);
+                self.peers.remove_peer(&peer_id);
+                self.peer_protocol_versions.remove(&peer_id);
+                self.peer_validators.remove(&peer_id);
+
+                // Fail over immediately if our sync peer went away
+                if self
+                    .pending_block_request
+                    .as_ref()
+                    .is_some_and(|req| req.peer == peer_id && !req.answered)
+                {
+                    self.pending_block_request = None;
+                    self.request_missing_blocks(Some(peer_id));
+                }
                 
                 // Schedule reconnection attempt
                 self.last_reconnect_attempt = std::time::Instant::now();
                 
                 Some(NetworkEvent::PeerDisconnected(peer_id))
             }
-            SwarmEvent::Behaviour(gossip_event) => self.handle_gossipsub_event(gossip_event),
+            SwarmEvent::Behaviour(SyncBehaviourEvent::Gossipsub(gossip_event)) => {
+                self.handle_gossipsub_event(gossip_event)
+            }
+            SwarmEvent::Behaviour(SyncBehaviourEvent::Kademlia(kad_event)) => {
+                self.handle_kademlia_event(kad_event);
+                None
+            }
+            SwarmEvent::Behaviour(SyncBehaviourEvent::Probe(probe_event)) => {
+                self.handle_probe_event(probe_event);
+                None
+            }
+            SwarmEvent::Behaviour(SyncBehaviourEvent::Mdns(mdns::Event::Discovered(peers))) => {
+                for (peer_id, addr) in peers {
+                    self.diversity
+                        .add_candidate(addr, Some(peer_id), PeerSource::Mdns);
+                }
+                None
+            }
+            SwarmEvent::Behaviour(SyncBehaviourEvent::Mdns(mdns::Event::Expired(peers))) => {
+                for (_, addr) in peers {
+                    self.diversity.remove_candidate(&addr);
+                }
+                None
+            }
+            SwarmEvent::Behaviour(SyncBehaviourEvent::Identify(identify::Event::Received {
+                peer_id,
+                info,
+            })) => {
+                debug!("🪪 Peer {} runs {}", peer_id, info.agent_version);
+                for addr in info.listen_addrs {
+                    self.swarm
+                        .behaviour_mut()
+                        .kademlia
+                        .add_address(&peer_id, addr);
+                }
+                self.peers.record_agent(peer_id, info.agent_version);
+                if self.check_peer_protocol(peer_id, &info.protocol_version) {
+                    self.check_peer_chain(peer_id);
+                }
+                None
+            }
             _ => None,
             }
             std::task::Poll::Ready(None) => None,
@@ -296,157 +1040,629 @@ impl LibP2PNetworkWithSync {
         }
     }
 
+    /// Drop peers that cannot validate the chain at our height; false when
+    /// the peer was disconnected
+    fn check_peer_protocol(&mut self, peer_id: PeerId, protocol: &str) -> bool {
+        let Some(peer_network) = protocol.strip_prefix(PROTOCOL_PREFIX) else {
+            debug!("Peer {} sent unknown protocol {}", peer_id, protocol);
+            return true;
+        };
+        let Some(version) = peer_network
+            .strip_prefix(self.network_id.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+        else {
+            warn!(
+                "⛔ Disconnecting {}: peer is on another network ({})",
+                peer_id, protocol
+            );
+            self.handshake_stats.record(ChainMismatch::Network);
+            let _ = self.swarm.disconnect_peer_id(peer_id);
+            return false;
+        };
+        let Ok(version) = version.parse::<u32>() else {
+            debug!("Peer {} sent unknown protocol {}", peer_id, protocol);
+            return true;
+        };
+        self.peer_protocol_versions.insert(peer_id, version);
+
+        let required =
+            ChainParams::for_network(&self.network).protocol_version_at(self.local_height);
+        if version < required {
+            warn!(
+                "⛔ Disconnecting {}: supports protocol v{}, chain requires v{}",
+                peer_id, version, required
+            );
+            self.handshake_stats.record(ChainMismatch::Protocol);
+            let _ = self.swarm.disconnect_peer_id(peer_id);
+            return false;
+        } else if version > PROTOCOL_VERSION {
+            info!(
+                "⬆️  Peer {} supports protocol v{} (this node: v{}), an upgrade may be available",
+                peer_id, version, PROTOCOL_VERSION
+            );
+        }
+        true
+    }
+
+    /// Drop peers built on another genesis or on another side of a hard fork
+    fn check_peer_chain(&mut self, peer_id: PeerId) {
+        let Some(info) = self.peers.info(&peer_id) else {
+            return;
+        };
+        if let Err((reason, detail)) =
+            check_peer_chain(&self.network, self.genesis_hash.as_ref(), info)
+        {
+            warn!("⛔ Disconnecting {}: {}", peer_id, detail);
+            self.handshake_stats.record(reason);
+            let _ = self.swarm.disconnect_peer_id(peer_id);
+        }
+    }
+
+    /// Connection mix and diversity rule outcomes, shared for metrics
+    pub fn peer_diversity_stats(&self) -> Arc<PeerDiversityStats> {
+        self.diversity.stats()
+    }
+
+    /// Apply new outbound, inbound, subnet and rotation limits; existing
+    /// connections are only affected by later rotations
+    pub fn set_peer_diversity(&mut self, params: PeerDiversityParams) {
+        self.diversity.set_params(params);
+    }
+
+    /// Rotate out one outbound peer when due and dial known addresses while
+    /// below the outbound minimum, looking for more through the DHT. Call
+    /// this regularly from the node loop.
+    pub fn maintain_peers(&mut self) {
+        if !self.is_listening || self.last_peer_maintenance.elapsed() < PEER_MAINTENANCE_INTERVAL {
+            return;
+        }
+        let now = Instant::now();
+        self.last_peer_maintenance = now;
+
+        let mut excluded = self.banned_peers.clone();
+        excluded.insert(self.local_peer_id);
+        if let Some(peer) = self.diversity.rotation_due(&excluded, now) {
+            info!(
+                "🔄 Rotating out outbound peer {} ({})",
+                peer,
+                self.diversity
+                    .source(&peer)
+                    .map(|source| source.as_str())
+                    .unwrap_or("unknown")
+            );
+            let _ = self.swarm.disconnect_peer_id(peer);
+        }
+
+        let dials = self.diversity.dials_needed(&excluded, now);
+        if self.diversity.outbound() + dials.len() < self.diversity.params().min_outbound {
+            // Random walk: the routing updates it causes bring new candidates
+            self.swarm
+                .behaviour_mut()
+                .kademlia
+                .get_closest_peers(PeerId::random());
+        }
+        for (addr, source) in dials {
+            match self.swarm.dial(addr.clone()) {
+                Ok(()) => debug!("📞 Dialing {} peer {}", source, addr),
+                Err(e) => debug!("⊘ Cannot dial {} peer {}: {}", source, addr, e),
+            }
+        }
+    }
+
+    /// Peer round-trip times and liveness disconnects, shared for metrics
+    pub fn liveness_stats(&self) -> Arc<LivenessStats> {
+        self.liveness_stats.clone()
+    }
+
+    /// Peers disconnected during the handshake, shared for metrics
+    pub fn handshake_stats(&self) -> Arc<HandshakeStats> {
+        self.handshake_stats.clone()
+    }
+
+    /// Gossip messages accepted and rejected per topic, shared for metrics
+    pub fn gossip_validation_stats(&self) -> Arc<GossipValidationStats> {
+        self.gossip_stats.clone()
+    }
+
+    fn gossip_topic(&self, topic: &gossipsub::TopicHash) -> Option<GossipTopic> {
+        [
+            (&self.block_topic, GossipTopic::Blocks),
+            (&self.tx_topic, GossipTopic::Transactions),
+            (&self.sync_topic, GossipTopic::Sync),
+            (&self.compact_block_topic, GossipTopic::CompactBlocks),
+            (&self.block_txs_topic, GossipTopic::BlockTransactions),
+        ]
+        .into_iter()
+        .find(|(ours, _)| ours.hash() == *topic)
+        .map(|(_, kind)| kind)
+    }
+
     fn handle_gossipsub_event(&mut self, event: gossipsub::Event) -> Option<NetworkEvent> {
-        match event {
-            gossipsub::Event::Message { message, .. } => {
-                if message.topic == self.block_topic.hash() {
-                    // Received a new block
-                    match bincode::deserialize::<Block>(&message.data) {
-                        Ok(block) => {
-                            info!(
-                                "📦 Received new block {} via gossip",
-                                block.header.block_height
-                            );
-                            Some(NetworkEvent::NewBlock(block))
-                        }
-                        Err(e) => {
-                            warn!("Failed to deserialize block: {}", e);
-                            None
-                        }
-                    }
-                } else if message.topic == self.tx_topic.hash() {
-                    // Received a new transaction
-                    match bincode::deserialize::<Transaction>(&message.data) {
-                        Ok(tx) => {
-                            debug!("📨 Received new transaction via gossip");
-                            Some(NetworkEvent::NewTransaction(tx))
-                        }
-                        Err(e) => {
-                            warn!("Failed to deserialize transaction: {}", e);
-                            None
-                        }
-                    }
-                } else if message.topic == self.sync_topic.hash() {
-                    // Received sync message (height announcement, validator announcement, or block request)
-                    if let Ok(msg) = String::from_utf8(message.data.clone()) {
-                        if let Some(validator_addr_str) = msg.strip_prefix("VALIDATOR:") {
-                            // Parse validator address announcement
-                            if let Ok(validator_addr) = validator_addr_str.parse::<spirachain_core::Address>() {
-                                info!("📝 Discovered new validator: {}", validator_addr);
-                                return Some(NetworkEvent::ValidatorAnnouncement(validator_addr));
-                            } else {
-                                warn!("Failed to parse validator address: {}", validator_addr_str);
-                                return None;
-                            }
-                        } else if let Some(height_str) = msg.strip_prefix("HEIGHT:") {
-                            if let Ok(peer_height) = height_str.parse::<u64>() {
-                                // Track peer height
-                                if let Some(propagation_source) = message.source {
-                                    self.peer_heights.insert(propagation_source, peer_height);
-                                    info!("📊 Peer {} at height: {}", propagation_source, peer_height);
-                                }
+        if let gossipsub::Event::Subscribed { .. } = event {
+            // A peer to publish to, perhaps the first one
+            self.flush_outbound();
+            return None;
+        }
+        let gossipsub::Event::Message {
+            propagation_source,
+            message_id,
+            message,
+        } = event
+        else {
+            return None;
+        };
+        let topic = self.gossip_topic(&message.topic)?;
 
-                                // If peer is ahead, we're behind and need to catch up
-                                if peer_height > self.local_height {
-                                    let blocks_behind = peer_height - self.local_height;
-                                    info!(
-                                        "🔄 We are {} blocks behind (peer at {}, us at {})",
-                                        blocks_behind, peer_height, self.local_height
-                                    );
-
-                                    // Request missing blocks in batches of 50
-                                    let batch_size = 50;
-                                    let start = self.local_height + 1;
-                                    let end = std::cmp::min(start + batch_size - 1, peer_height);
-
-                                    let request_msg = format!("GET_BLOCKS:{}-{}", start, end);
-                                    info!(
-                                        "📥 Requesting blocks {} to {} (batch of {})",
-                                        start,
-                                        end,
-                                        end - start + 1
-                                    );
-
-                                    if let Err(e) = self.swarm.behaviour_mut().publish(
-                                        self.sync_topic.clone(),
-                                        request_msg.as_bytes().to_vec(),
-                                    ) {
-                                        warn!("Failed to request blocks: {}", e);
-                                    }
-                                }
-                                None
-                            } else {
-                                None
-                            }
-                        } else if msg.starts_with("GET_BLOCKS:") {
-                            // Someone is requesting a range of blocks
-                            // Format: GET_BLOCKS:start-end
-                            if let Some(range_str) = msg.strip_prefix("GET_BLOCKS:") {
-                                if let Some((start_str, _end_str)) = range_str.split_once('-') {
-                                    if let Ok(start) = start_str.parse::<u64>() {
-                                        info!("📤 Peer requested blocks starting at {}", start);
-                                        // ValidatorNode will handle sending the range
-                                        return Some(NetworkEvent::BlockRequested(start));
-                                    }
-                                }
-                            }
-                            None
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                } else {
+        // Every handler below only ever sees payloads for our network
+        let mut decompressed = Vec::new();
+        let mut codec = None;
+        let data = if let Some(data) = strip_network_magic(self.compressed_magic, &message.data) {
+            match decode_compressed(data) {
+                Ok((used, bytes, took)) => {
+                    self.compression_stats.record_received(used, took);
+                    codec = Some(used);
+                    decompressed = bytes;
+                    Ok(&decompressed[..])
+                }
+                Err(e) => {
+                    warn!("Dropping compressed gossip on {}: {}", message.topic, e);
+                    Err(GossipRejection::Malformed)
+                }
+            }
+        } else if let Some(data) = strip_network_magic(self.network_magic, &message.data) {
+            Ok(data)
+        } else {
+            debug!(
+                "Dropping gossip without our network magic on {}",
+                message.topic
+            );
+            Err(GossipRejection::WrongNetwork)
+        };
+        let payload =
+            data.and_then(|data| validate_gossip(topic, &self.network, self.local_height, data));
+
+        let acceptance = match &payload {
+            Ok(_) => gossipsub::MessageAcceptance::Accept,
+            Err(rejection) => rejection.acceptance(),
+        };
+        self.gossip_stats
+            .record(topic, payload.as_ref().map(|_| ()).map_err(|r| *r));
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .gossipsub
+            .report_message_validation_result(&message_id, &propagation_source, acceptance)
+        {
+            debug!("Failed to report gossip validation result: {}", e);
+        }
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(rejection) => {
+                debug!(
+                    "🚫 Rejected {} gossip from {}: {}",
+                    topic.name(),
+                    propagation_source,
+                    rejection
+                );
+                return None;
+            }
+        };
+        if let Some(codec) = codec {
+            self.relay_uncompressed(&message.topic, codec, &decompressed);
+        }
+
+        match payload {
+            GossipPayload::Block(block) => {
+                info!(
+                    "📦 Received new block {} via gossip",
+                    block.header.block_height
+                );
+                if let Some(source) = message.source {
+                    self.record_block_response(
+                        &source,
+                        block.header.block_height,
+                        message.data.len(),
+                    );
+                }
+                Some(NetworkEvent::NewBlock(*block))
+            }
+            GossipPayload::CompactBlock(block) => {
+                let peer = message.source?;
+                let latency = self
+                    .propagation
+                    .record(&block.announcement, propagation_source != peer);
+                info!(
+                    "📦 Received compact block {} ({} txs) via gossip after {}ms",
+                    block.header.block_height,
+                    block.short_ids.len(),
+                    latency
+                );
+                Some(NetworkEvent::NewCompactBlock {
+                    peer,
+                    block: *block,
+                })
+            }
+            GossipPayload::BlockTransactions(response) => {
+                Some(NetworkEvent::BlockTransactions(response))
+            }
+            GossipPayload::Transaction(tx) => {
+                debug!("📨 Received new transaction via gossip");
+                Some(NetworkEvent::NewTransaction(*tx))
+            }
+            GossipPayload::Sync(msg) => self.handle_sync_message(&msg, message.source),
+        }
+    }
+
+    /// Height and validator announcements, checkpoints, probes and requests
+    fn handle_sync_message(&mut self, msg: &str, source: Option<PeerId>) -> Option<NetworkEvent> {
+        if let Some(validator_addr_str) = msg.strip_prefix("VALIDATOR:") {
+            // Parse validator address announcement
+            if let Ok(validator_addr) = validator_addr_str.parse::<spirachain_core::Address>() {
+                info!("📝 Discovered new validator: {}", validator_addr);
+                if let Some(source) = source {
+                    self.peer_validators.insert(source, validator_addr);
+                }
+                Some(NetworkEvent::ValidatorAnnouncement(validator_addr))
+            } else {
+                warn!("Failed to parse validator address: {}", validator_addr_str);
+                None
+            }
+        } else if let Some(checkpoint) = msg.strip_prefix("CHECKPOINT:") {
+            match parse_checkpoint(checkpoint) {
+                Some((height, block_hash)) => {
+                    debug!("📍 Peer checkpoint at height {}", height);
+                    Some(NetworkEvent::Checkpoint { height, block_hash })
+                }
+                None => {
+                    warn!("Invalid checkpoint announcement: {}", checkpoint);
                     None
                 }
             }
-            _ => None,
+        } else if let Some(revocation) = msg.strip_prefix("REVOKE_BLOCK:") {
+            let (height, block_hash) = parse_checkpoint(revocation)?;
+            Some(NetworkEvent::BlockRevoked {
+                peer: source?,
+                height,
+                block_hash,
+            })
+        } else if let Some(height_str) = msg.strip_prefix("HEIGHT:") {
+            if let Ok(peer_height) = height_str.parse::<u64>() {
+                // Track peer height
+                if let Some(propagation_source) = source {
+                    self.peer_heights.insert(propagation_source, peer_height);
+                    info!("📊 Peer {} at height: {}", propagation_source, peer_height);
+                }
+
+                // If peer is ahead, we're behind and need to catch up
+                if peer_height > self.local_height {
+                    let blocks_behind = peer_height - self.local_height;
+                    info!(
+                        "🔄 We are {} blocks behind (peer at {}, us at {})",
+                        blocks_behind, peer_height, self.local_height
+                    );
+
+                    self.request_missing_blocks(None);
+                }
+                None
+            } else {
+                None
+            }
+        } else if let Some(request) = msg.strip_prefix("GET_STATE_ROOT:") {
+            // State root cross-check: GET_STATE_ROOT:nonce@peer
+            let (nonce, target) = request.split_once('@')?;
+            if target != self.local_peer_id.to_string() {
+                return None;
+            }
+            Some(NetworkEvent::StateRootRequested {
+                peer: source?,
+                nonce: nonce.parse().ok()?,
+            })
+        } else if let Some(answer) = msg.strip_prefix("STATE_ROOT:") {
+            // Answer: STATE_ROOT:origin:nonce:height:0xblock_hash:0xstate_root
+            let (origin, rest) = answer.split_once(':')?;
+            if origin != self.local_peer_id.to_string() {
+                return None;
+            }
+            let (nonce, rest) = rest.split_once(':')?;
+            let (tip, state_root) = rest.rsplit_once(':')?;
+            let (height, block_hash) = parse_checkpoint(tip)?;
+            let state_root = hex::decode(state_root.strip_prefix("0x")?).ok()?;
+            Some(NetworkEvent::StateRoot {
+                peer: source?,
+                nonce: nonce.parse().ok()?,
+                height,
+                block_hash,
+                state_root: Hash::from_slice(&state_root).ok()?,
+            })
+        } else if let Some(request) = msg.strip_prefix("GET_BLOCK_TXS:") {
+            // Missing compact block transactions: GET_BLOCK_TXS:hash:i,j,k@peer
+            let (body, target) = request.split_once('@')?;
+            if target != self.local_peer_id.to_string() {
+                return None;
+            }
+            let (hash_hex, indexes_str) = body.split_once(':')?;
+            let hash_bytes: [u8; 32] = hex::decode(hash_hex).ok()?.try_into().ok()?;
+            let indexes: Vec<u16> = indexes_str
+                .split(',')
+                .filter_map(|index| index.parse().ok())
+                .collect();
+            Some(NetworkEvent::BlockTransactionsRequested {
+                peer: source?,
+                block_hash: Hash::new(hash_bytes),
+                indexes,
+            })
+        } else if msg.starts_with("GET_BLOCKS:") {
+            // Someone is requesting a range of blocks
+            // Format: GET_BLOCKS:start-end[@peer]; targeted requests are
+            // only served by the named peer
+            if let Some(request) = msg.strip_prefix("GET_BLOCKS:") {
+                let (range_str, target) = match request.split_once('@') {
+                    Some((range, target)) => (range, Some(target)),
+                    None => (request, None),
+                };
+                if target.is_some_and(|t| t != self.local_peer_id.to_string()) {
+                    return None;
+                }
+                if let Some((start_str, _end_str)) = range_str.split_once('-') {
+                    if let Ok(start) = start_str.parse::<u64>() {
+                        info!("📤 Peer requested blocks starting at {}", start);
+                        // ValidatorNode will handle sending the range
+                        return Some(NetworkEvent::BlockRequested(start));
+                    }
+                }
+            }
+            None
+        } else {
+            None
         }
     }
 
-    /// Broadcast a block via Gossipsub
+    /// Ask the fastest peer that is ahead of us for the next batch of blocks.
+    /// `exclude` skips a peer that just failed, so the request fails over.
+    fn request_missing_blocks(&mut self, exclude: Option<PeerId>) {
+        if self.pending_block_request.is_some() {
+            return;
+        }
+
+        let start = self.local_height + 1;
+        let mut candidates: Vec<PeerId> = self
+            .peer_heights
+            .iter()
+            .filter(|(peer, height)| {
+                **height >= start && Some(**peer) != exclude && self.peers.supports(peer, CAP_SYNC)
+            })
+            .map(|(peer, _)| *peer)
+            .collect();
+
+        // Providers announced for these heights take precedence over whoever
+        // else happens to be ahead; keep looking them up while none is connected
+        let providers = self.providers.providers_for(start);
+        if candidates.iter().any(|peer| providers.contains(peer)) {
+            candidates.retain(|peer| providers.contains(peer));
+        } else {
+            self.lookup_providers(start);
+        }
+
+        let Some(peer) = self.latency.best_peer(&candidates) else {
+            debug!("No peer available to serve blocks from {}", start);
+            return;
+        };
+        let peer_height = self.peer_heights[&peer];
+        let end = std::cmp::min(start + SYNC_BATCH_SIZE - 1, peer_height);
+
+        let request_msg = format!("GET_BLOCKS:{}-{}@{}", start, end, peer);
+        info!(
+            "📥 Requesting blocks {} to {} (batch of {}) from {}",
+            start,
+            end,
+            end - start + 1,
+            peer
+        );
+
+        if let Err(e) = self.publish(self.sync_topic.clone(), request_msg.as_bytes()) {
+            warn!("Failed to request blocks: {}", e);
+            return;
+        }
+
+        self.pending_block_request = Some(PendingBlockRequest {
+            peer,
+            start,
+            end,
+            sent_at: Instant::now(),
+            answered: false,
+            bytes: 0,
+        });
+    }
+
+    /// Time the first block answering our outstanding request, and the
+    /// transfer rate once its last block arrived
+    fn record_block_response(&mut self, source: &PeerId, height: u64, bytes: usize) {
+        if let Some(req) = self.pending_block_request.as_mut() {
+            if req.peer == *source && (req.start..=req.end).contains(&height) {
+                if !req.answered {
+                    req.answered = true;
+                    self.latency.record_response(req.peer, req.sent_at.elapsed());
+                }
+                req.bytes += bytes;
+                if height == req.end {
+                    self.latency
+                        .record_transfer(req.peer, req.bytes, req.sent_at.elapsed());
+                    self.pending_block_request = None;
+                }
+            }
+        }
+    }
+
+    /// Send latency probes, drop peers that stopped answering them and fail
+    /// over stalled block requests. Call this regularly from the node loop.
+    pub fn maintain_sync(&mut self) {
+        if self.outbound.retry_due(Instant::now()) {
+            self.flush_outbound();
+        }
+
+        if self.last_probe_round.elapsed() >= PROBE_INTERVAL {
+            self.latency.expire_probes(PROBE_TIMEOUT);
+            for peer in self.latency.unresponsive_peers(MAX_MISSED_PINGS) {
+                warn!(
+                    "⛔ Disconnecting {}: missed {} pings in a row",
+                    peer,
+                    self.latency.missed_probes(&peer)
+                );
+                self.latency.remove_peer(&peer);
+                self.liveness_stats.record_disconnect();
+                let _ = self.swarm.disconnect_peer_id(peer);
+            }
+            self.liveness_stats
+                .set_peers(self.latency.rtts(&self.connected_peers));
+
+            // Each probe goes to its peer alone, not through the gossip mesh
+            let peers: Vec<PeerId> = self.connected_peers.iter().copied().collect();
+            for peer in peers {
+                let nonce = self.latency.start_probe(peer);
+                self.swarm
+                    .behaviour_mut()
+                    .probe
+                    .send_request(&peer, LatencyProbe { nonce });
+            }
+            self.last_probe_round = Instant::now();
+        }
+
+        let Some(req) = self.pending_block_request.as_ref() else {
+            return;
+        };
+
+        if self.local_height >= req.end {
+            self.pending_block_request = None;
+        } else if !req.answered && req.sent_at.elapsed() >= BLOCK_REQUEST_TIMEOUT {
+            let failed = req.peer;
+            warn!(
+                "⏱️  Peer {} did not answer block request {}-{}, failing over",
+                failed, req.start, req.end
+            );
+            self.latency.record_failure(failed);
+            self.pending_block_request = None;
+            self.request_missing_blocks(Some(failed));
+        } else if req.sent_at.elapsed() >= BLOCK_REQUEST_TIMEOUT * 3 {
+            // Peer answered but stalled mid-batch; re-request from where we are
+            self.pending_block_request = None;
+            self.request_missing_blocks(None);
+        }
+    }
+
+    /// Announce a new block as a compact block (header + short tx IDs)
     pub async fn broadcast_block(&mut self, block: &Block) -> Result<()> {
-        let data = bincode::serialize(block)
+        let compact = CompactBlock::from_block(block);
+        let data = bincode::serialize(&compact)
             .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
 
-        self.swarm
-            .behaviour_mut()
-            .publish(self.block_topic.clone(), data)
+        self.publish_or_queue(OutboundKind::Block, data)
             .map_err(|e| SpiraChainError::NetworkError(format!("Broadcast block: {}", e)))?;
 
-        debug!("📡 Broadcasted block {}", block.header.block_height);
+        debug!(
+            "📡 Broadcasted compact block {} ({} txs)",
+            block.header.block_height,
+            compact.short_ids.len()
+        );
         Ok(())
     }
 
+    /// Pass on a compact block as received, keeping the producer's
+    /// announcement time
+    pub fn relay_compact_block(&mut self, block: &CompactBlock) -> Result<()> {
+        let data = bincode::serialize(block)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+
+        self.publish(self.compact_block_topic.clone(), &data)
+            .map_err(|e| SpiraChainError::NetworkError(format!("Relay block: {}", e)))?;
+
+        debug!("⚡ Relayed compact block {}", block.header.block_height);
+        Ok(())
+    }
+
+    /// Ask `peer` for the compact block transactions we could not find locally
+    pub fn request_block_transactions(&mut self, peer: PeerId, block_hash: &Hash, indexes: &[u16]) {
+        let indexes = indexes
+            .iter()
+            .map(|index| index.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let request = format!(
+            "GET_BLOCK_TXS:{}:{}@{}",
+            hex::encode(block_hash.as_bytes()),
+            indexes,
+            peer
+        );
+
+        if let Err(e) = self.publish(self.sync_topic.clone(), request.as_bytes()) {
+            warn!("Failed to request block transactions: {}", e);
+        }
+    }
+
+    pub async fn send_block_transactions(&mut self, response: &BlockTransactions) -> Result<()> {
+        let data = bincode::serialize(response)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+
+        self.publish(self.block_txs_topic.clone(), &data)
+            .map_err(|e| SpiraChainError::NetworkError(format!("Send block txs: {}", e)))?;
+
+        debug!(
+            "📤 Sent {} transactions for block {}",
+            response.transactions.len(),
+            response.block_hash
+        );
+        Ok(())
+    }
+
+    /// Fallback when a compact block cannot be reconstructed
+    pub fn request_full_block(&mut self, peer: PeerId, height: u64) {
+        let request = format!("GET_BLOCKS:{}-{}@{}", height, height, peer);
+        info!("📥 Requesting full block {} from {}", height, peer);
+
+        if let Err(e) = self.publish(self.sync_topic.clone(), request.as_bytes()) {
+            warn!("Failed to request full block: {}", e);
+        }
+    }
+
     /// Send a specific block (in response to GET_BLOCK request)
     pub async fn send_block(&mut self, block: &Block) -> Result<()> {
         let data = bincode::serialize(block)
             .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
 
-        self.swarm
-            .behaviour_mut()
-            .publish(self.block_topic.clone(), data)
+        self.publish(self.block_topic.clone(), &data)
             .map_err(|e| SpiraChainError::NetworkError(format!("Send block: {}", e)))?;
 
         info!("📤 Sent block {} to peers", block.header.block_height);
         Ok(())
     }
 
+    /// Send a block in the encoding it was stored with, which is the one
+    /// `send_block` produces; serving sync batches skips the round trip
+    /// through `Block`, and the bytes are only copied into the gossip frame
+    pub async fn send_block_bytes(&mut self, height: u64, data: &[u8]) -> Result<()> {
+        self.publish(self.block_topic.clone(), data)
+            .map_err(|e| SpiraChainError::NetworkError(format!("Send block: {}", e)))?;
+
+        debug!("📤 Sent stored block {} to peers", height);
+        Ok(())
+    }
+
     /// Broadcast a transaction via Gossipsub
     pub async fn broadcast_transaction(&mut self, tx: &Transaction) -> Result<()> {
         let data = bincode::serialize(tx)
             .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
 
-        self.swarm
-            .behaviour_mut()
-            .publish(self.tx_topic.clone(), data)
+        self.publish_or_queue(OutboundKind::Transaction, data)
             .map_err(|e| SpiraChainError::NetworkError(format!("Broadcast tx: {}", e)))?;
 
         debug!("📨 Broadcasted transaction");
         Ok(())
     }
 
+    /// Latency percentiles of recent compact block announcements
+    pub fn propagation_stats(&self) -> PropagationStats {
+        self.propagation.stats()
+    }
+
     /// Get connected peer count
     pub fn peer_count(&self) -> usize {
         self.connected_peers.len()
@@ -457,11 +1673,106 @@ impl LibP2PNetworkWithSync {
         self.connected_peers.len()
     }
     
+    pub fn connected_peer_ids(&self) -> Vec<PeerId> {
+        self.connected_peers.iter().copied().collect()
+    }
+
     /// Get peer heights map (for sync checking)
     pub fn get_peer_heights(&self) -> &HashMap<PeerId, u64> {
         &self.peer_heights
     }
 
+    /// Replace the ban list: banned peers are disconnected and their gossip ignored
+    pub fn set_banned_peers(&mut self, peers: &[String]) {
+        let mut banned = HashSet::new();
+        for peer in peers {
+            match peer.parse::<PeerId>() {
+                Ok(peer_id) => {
+                    banned.insert(peer_id);
+                }
+                Err(e) => warn!("Ignoring invalid banned peer {}: {}", peer, e),
+            }
+        }
+
+        for peer_id in self.banned_peers.difference(&banned) {
+            self.swarm.behaviour_mut().gossipsub.remove_blacklisted_peer(peer_id);
+            info!("✅ Unbanned peer {}", peer_id);
+        }
+
+        for peer_id in banned.difference(&self.banned_peers) {
+            self.swarm.behaviour_mut().gossipsub.blacklist_peer(peer_id);
+            let _ = self.swarm.disconnect_peer_id(*peer_id);
+            self.peer_heights.remove(peer_id);
+            self.latency.remove_peer(peer_id);
+            self.providers.remove_peer(peer_id);
+            info!("⛔ Banned peer {}", peer_id);
+        }
+
+        self.banned_peers = banned;
+    }
+
+    /// Average response time of every peer we have measured
+    pub fn get_peer_latencies(&self) -> HashMap<PeerId, Duration> {
+        self.latency.latencies()
+    }
+
+    /// Our current view of peers and the gossip mesh, for `spira net graph`
+    pub fn topology_snapshot(&self) -> TopologySnapshot {
+        let latencies = self.latency.latencies();
+
+        let mut peers: Vec<PeerView> = self
+            .connected_peers
+            .iter()
+            .map(|peer_id| PeerView {
+                peer_id: peer_id.to_string(),
+                height: self.peer_heights.get(peer_id).copied(),
+                latency_ms: latencies
+                    .get(peer_id)
+                    .map(|latency| latency.as_millis() as u64),
+                last_latency_ms: self
+                    .latency
+                    .last_latency(peer_id)
+                    .map(|latency| latency.as_millis() as u64),
+                missed_pings: self.latency.missed_probes(peer_id),
+                agent: self.peers.agent(peer_id).map(str::to_string),
+                role: self.peers.info(peer_id).map(|info| info.role.to_string()),
+                capabilities: self
+                    .peers
+                    .info(peer_id)
+                    .map(|info| info.capabilities.clone())
+                    .unwrap_or_default(),
+                protocol_version: self.peer_protocol_versions.get(peer_id).copied(),
+                validator: self.peer_validators.get(peer_id).copied(),
+            })
+            .collect();
+        peers.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+
+        let gossipsub = &self.swarm.behaviour().gossipsub;
+        let mesh: BTreeMap<String, Vec<String>> = gossipsub
+            .topics()
+            .map(|topic| {
+                let mut mesh_peers: Vec<String> = gossipsub
+                    .mesh_peers(topic)
+                    .map(|peer_id| peer_id.to_string())
+                    .collect();
+                mesh_peers.sort();
+                (topic.to_string(), mesh_peers)
+            })
+            .collect();
+
+        TopologySnapshot {
+            taken_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+            network: self.network.clone(),
+            local_peer_id: self.local_peer_id.to_string(),
+            local_height: self.local_height,
+            peers,
+            mesh,
+        }
+    }
+
     /// Get sync statistics (simplified)
     pub fn get_sync_stats(&self) -> String {
         format!(
@@ -0,0 +1,119 @@
+// Network section of the chain spec
+// Protocol ids, gossip topics and framing of a network, from the same
+// functions and constants the swarm is built with. See
+// `spirachain_core::ChainSpec` for the rest of the document.
+
+use crate::compression::{
+    Compression, CAP_SNAPPY, CAP_ZSTD, COMPRESSION_THRESHOLD, MAX_DECOMPRESSED_SIZE,
+};
+use crate::gossip_validation::{GossipTopic, SYNC_PREFIXES};
+use crate::libp2p_sync::{
+    compressed_network_magic, network_magic, protocol_name, topic_name, GOSSIPSUB_PROTOCOL,
+    KADEMLIA_PROTOCOL,
+};
+use serde::Serialize;
+use spirachain_core::{ChainParams, PROTOCOL_VERSION};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkSpec {
+    pub network_id: String,
+    pub gossipsub_protocol: String,
+    pub kademlia_protocol: String,
+    /// Identify protocol version; peers on another network id are disconnected
+    pub identify_protocol: String,
+    /// Hex bytes in front of every uncompressed gossip payload
+    pub network_magic: String,
+    pub framing: FramingSpec,
+    pub topics: Vec<TopicSpec>,
+    pub sync_messages: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FramingSpec {
+    pub uncompressed: &'static str,
+    pub compressed: &'static str,
+    /// Hex magic of compressed payloads
+    pub compressed_magic: String,
+    pub codecs: Vec<CodecSpec>,
+    pub compression_threshold: usize,
+    pub max_decompressed_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CodecSpec {
+    pub codec: Compression,
+    pub tag: u8,
+    /// Identify capability a peer advertises when it reads the codec
+    pub capability: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicSpec {
+    pub name: String,
+    pub payload: &'static str,
+    pub max_size: usize,
+}
+
+impl NetworkSpec {
+    pub fn for_network(network: &str) -> Self {
+        let params = ChainParams::for_network(network);
+        let network_id = params.network_id();
+        let codec = |codec: Compression, capability| CodecSpec {
+            codec,
+            tag: codec.tag(),
+            capability,
+        };
+
+        Self {
+            gossipsub_protocol: protocol_name(&network_id, GOSSIPSUB_PROTOCOL),
+            kademlia_protocol: protocol_name(&network_id, KADEMLIA_PROTOCOL),
+            identify_protocol: protocol_name(&network_id, &PROTOCOL_VERSION.to_string()),
+            network_magic: hex::encode(network_magic(&network_id)),
+            framing: FramingSpec {
+                uncompressed: "network magic ‖ bincode payload (sync: UTF-8 text)",
+                compressed: "compressed magic ‖ codec tag ‖ compressed payload",
+                compressed_magic: hex::encode(compressed_network_magic(&network_id)),
+                codecs: vec![
+                    codec(Compression::None, None),
+                    codec(Compression::Snappy, Some(CAP_SNAPPY)),
+                    codec(Compression::Zstd, Some(CAP_ZSTD)),
+                ],
+                compression_threshold: COMPRESSION_THRESHOLD,
+                max_decompressed_size: MAX_DECOMPRESSED_SIZE,
+            },
+            topics: GossipTopic::ALL
+                .iter()
+                .map(|topic| TopicSpec {
+                    name: topic_name(&network_id, topic.name()),
+                    payload: topic.payload(),
+                    max_size: topic.max_size(params),
+                })
+                .collect(),
+            sync_messages: SYNC_PREFIXES.to_vec(),
+            network_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_spec_names_what_the_swarm_uses() {
+        let spec = NetworkSpec::for_network("testnet");
+        let network_id = ChainParams::for_network("testnet").network_id();
+        assert_eq!(
+            spec.gossipsub_protocol,
+            format!("/spirachain/{}/meshsub", network_id)
+        );
+        assert_eq!(spec.topics.len(), GossipTopic::ALL.len());
+        assert_eq!(
+            spec.topics[0].name,
+            format!("/spirachain/{}/blocks", network_id)
+        );
+        assert_eq!(spec.network_magic.len(), 8);
+        assert_ne!(spec.network_magic, spec.framing.compressed_magic);
+        assert!(spec.sync_messages.contains(&"GET_BLOCKS:"));
+    }
+}
@@ -0,0 +1,261 @@
+// Outbound gossip queue
+// gossipsub refuses to publish on a topic no peer is subscribed to
+// (`InsufficientPeers`), which is where a node stands right after startup, after
+// losing its peers, or alone on a testnet. Blocks and transactions refused that
+// way wait here, oldest first, and go out again as soon as a peer subscribes or
+// on the next retry round. The queue is bounded in size and age: past either
+// the oldest message is dropped, since peers catch up on old blocks through sync
+// anyway. How many messages are stuck, and for how long, is on `/metrics`.
+
+use libp2p::gossipsub::PublishError;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Most messages waiting for peers; the oldest is dropped past it
+pub const OUTBOUND_QUEUE_CAPACITY: usize = 512;
+/// Messages older than this are dropped instead of published
+pub const OUTBOUND_MAX_AGE: Duration = Duration::from_secs(300);
+/// Time between retries while nothing tells us a peer subscribed
+pub const OUTBOUND_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboundKind {
+    Block,
+    Transaction,
+}
+
+impl OutboundKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboundKind::Block => "block",
+            OutboundKind::Transaction => "transaction",
+        }
+    }
+}
+
+struct QueuedMessage {
+    kind: OutboundKind,
+    data: Vec<u8>,
+    queued_at: Instant,
+}
+
+#[derive(Default)]
+pub struct OutboundQueue {
+    messages: VecDeque<QueuedMessage>,
+    last_retry: Option<Instant>,
+    stats: Arc<OutboundQueueStats>,
+}
+
+impl OutboundQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> Arc<OutboundQueueStats> {
+        self.stats.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Keep `data` until it can be published, dropping the oldest message if full
+    pub fn push(&mut self, kind: OutboundKind, data: Vec<u8>, now: Instant) {
+        if self.messages.len() >= OUTBOUND_QUEUE_CAPACITY {
+            self.messages.pop_front();
+            self.stats.dropped_full.fetch_add(1, Ordering::Relaxed);
+        }
+        self.messages.push_back(QueuedMessage {
+            kind,
+            data,
+            queued_at: now,
+        });
+        self.update_gauges(now);
+    }
+
+    /// Whether messages wait and the last retry is [`OUTBOUND_RETRY_INTERVAL`] old
+    pub fn retry_due(&self, now: Instant) -> bool {
+        !self.messages.is_empty()
+            && self
+                .last_retry
+                .is_none_or(|last| now.duration_since(last) >= OUTBOUND_RETRY_INTERVAL)
+    }
+
+    /// Publish waiting messages oldest first, stopping at the first one still
+    /// refused for lack of peers. Expired messages and those refused for any
+    /// other reason are dropped.
+    pub fn flush(
+        &mut self,
+        now: Instant,
+        mut publish: impl FnMut(OutboundKind, &[u8]) -> Result<(), PublishError>,
+    ) {
+        self.last_retry = Some(now);
+        while let Some(message) = self.messages.pop_front() {
+            if now.duration_since(message.queued_at) > OUTBOUND_MAX_AGE {
+                self.stats.dropped_expired.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            match publish(message.kind, &message.data) {
+                Ok(()) => {
+                    self.stats.sent.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(PublishError::InsufficientPeers) => {
+                    self.messages.push_front(message);
+                    break;
+                }
+                Err(e) => {
+                    debug!("Dropping queued {}: {}", message.kind.as_str(), e);
+                    self.stats.dropped_rejected.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        self.update_gauges(now);
+    }
+
+    fn update_gauges(&self, now: Instant) {
+        let count = |kind| self.messages.iter().filter(|m| m.kind == kind).count() as u64;
+        self.stats
+            .queued_blocks
+            .store(count(OutboundKind::Block), Ordering::Relaxed);
+        self.stats
+            .queued_transactions
+            .store(count(OutboundKind::Transaction), Ordering::Relaxed);
+        let oldest = self
+            .messages
+            .front()
+            .map(|m| now.duration_since(m.queued_at).as_millis() as u64)
+            .unwrap_or(0);
+        self.stats.oldest_age_ms.store(oldest, Ordering::Relaxed);
+    }
+}
+
+/// Outbound queue depth and outcomes, shared for metrics
+#[derive(Default)]
+pub struct OutboundQueueStats {
+    queued_blocks: AtomicU64,
+    queued_transactions: AtomicU64,
+    /// As of the last change to the queue
+    oldest_age_ms: AtomicU64,
+    sent: AtomicU64,
+    dropped_full: AtomicU64,
+    dropped_expired: AtomicU64,
+    dropped_rejected: AtomicU64,
+}
+
+impl OutboundQueueStats {
+    pub fn queued(&self, kind: OutboundKind) -> u64 {
+        match kind {
+            OutboundKind::Block => self.queued_blocks.load(Ordering::Relaxed),
+            OutboundKind::Transaction => self.queued_transactions.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        format!(
+            "# HELP spirachain_gossip_outbound_queued Messages waiting for peers to publish them to\n\
+             # TYPE spirachain_gossip_outbound_queued gauge\n\
+             spirachain_gossip_outbound_queued{{kind=\"block\"}} {}\n\
+             spirachain_gossip_outbound_queued{{kind=\"transaction\"}} {}\n\
+             # HELP spirachain_gossip_outbound_oldest_seconds Age of the oldest waiting message\n\
+             # TYPE spirachain_gossip_outbound_oldest_seconds gauge\n\
+             spirachain_gossip_outbound_oldest_seconds {:.3}\n\
+             # HELP spirachain_gossip_outbound_sent_total Queued messages published on a retry\n\
+             # TYPE spirachain_gossip_outbound_sent_total counter\n\
+             spirachain_gossip_outbound_sent_total {}\n\
+             # HELP spirachain_gossip_outbound_dropped_total Queued messages given up on, by reason\n\
+             # TYPE spirachain_gossip_outbound_dropped_total counter\n\
+             spirachain_gossip_outbound_dropped_total{{reason=\"full\"}} {}\n\
+             spirachain_gossip_outbound_dropped_total{{reason=\"expired\"}} {}\n\
+             spirachain_gossip_outbound_dropped_total{{reason=\"rejected\"}} {}\n",
+            self.queued(OutboundKind::Block),
+            self.queued(OutboundKind::Transaction),
+            self.oldest_age_ms.load(Ordering::Relaxed) as f64 / 1000.0,
+            self.sent(),
+            self.dropped_full.load(Ordering::Relaxed),
+            self.dropped_expired.load(Ordering::Relaxed),
+            self.dropped_rejected.load(Ordering::Relaxed)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queued_messages_wait_for_peers_in_order() {
+        let start = Instant::now();
+        let mut queue = OutboundQueue::new();
+        queue.push(OutboundKind::Block, vec![1], start);
+        queue.push(OutboundKind::Transaction, vec![2], start);
+        queue.push(OutboundKind::Block, vec![3], start);
+        assert!(queue.retry_due(start));
+        assert_eq!(queue.stats().queued(OutboundKind::Block), 2);
+
+        // Still no peers: nothing is lost and the next retry waits
+        queue.flush(start, |_, _| Err(PublishError::InsufficientPeers));
+        assert_eq!(queue.len(), 3);
+        assert!(!queue.retry_due(start + OUTBOUND_RETRY_INTERVAL / 2));
+
+        // A peer takes the first two, then goes away again
+        let mut published = Vec::new();
+        queue.flush(start + OUTBOUND_RETRY_INTERVAL, |_, data| {
+            if published.len() == 2 {
+                return Err(PublishError::InsufficientPeers);
+            }
+            published.push(data[0]);
+            Ok(())
+        });
+        assert_eq!(published, vec![1, 2]);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.stats().sent(), 2);
+        assert_eq!(queue.stats().queued(OutboundKind::Transaction), 0);
+
+        // Too old to be worth publishing
+        queue.flush(start + OUTBOUND_MAX_AGE * 2, |_, _| unreachable!());
+        assert!(queue.is_empty());
+        assert!(queue
+            .stats()
+            .export_prometheus()
+            .contains("spirachain_gossip_outbound_dropped_total{reason=\"expired\"} 1"));
+    }
+
+    #[test]
+    fn test_full_queue_drops_the_oldest() {
+        let now = Instant::now();
+        let mut queue = OutboundQueue::new();
+        for i in 0..OUTBOUND_QUEUE_CAPACITY + 2 {
+            queue.push(
+                OutboundKind::Transaction,
+                (i as u32).to_le_bytes().to_vec(),
+                now,
+            );
+        }
+        assert_eq!(queue.len(), OUTBOUND_QUEUE_CAPACITY);
+
+        let mut first = None;
+        queue.flush(now, |_, data| {
+            first.get_or_insert(data.to_vec());
+            Err(PublishError::Duplicate)
+        });
+        assert_eq!(first, Some(2u32.to_le_bytes().to_vec()));
+        assert!(queue.is_empty());
+        let metrics = queue.stats().export_prometheus();
+        assert!(metrics.contains("spirachain_gossip_outbound_dropped_total{reason=\"full\"} 2"));
+        assert!(metrics.contains(&format!(
+            "spirachain_gossip_outbound_dropped_total{{reason=\"rejected\"}} {}",
+            OUTBOUND_QUEUE_CAPACITY
+        )));
+    }
+}
@@ -95,14 +95,14 @@ impl P2PNetwork {
 
     pub fn broadcast_block(&self, block: Block) -> Result<()> {
         self.message_tx
-            .send(NetworkMessage::NewBlock(block))
-            .map_err(|e| SpiraChainError::Internal(format!("Channel send: {}", e)))
+            .send(NetworkMessage::NewBlock(Box::new(block)))
+            .map_err(|e| SpiraChainError::NetworkError(format!("Channel send: {}", e)))
     }
 
     pub fn broadcast_transaction(&self, tx: Transaction) -> Result<()> {
         self.message_tx
-            .send(NetworkMessage::NewTransaction(tx))
-            .map_err(|e| SpiraChainError::Internal(format!("Channel send: {}", e)))
+            .send(NetworkMessage::NewTransaction(Box::new(tx)))
+            .map_err(|e| SpiraChainError::NetworkError(format!("Channel send: {}", e)))
     }
 
     pub fn add_peer(&mut self, peer_id: String, address: String) {
@@ -0,0 +1,689 @@
+// Peer diversity
+// A node whose every connection is held by one attacker only sees the chain
+// that attacker shows it (an eclipse). Inbound connections are capped so they
+// can never crowd out the peers we picked ourselves, and at least
+// `min_outbound` outbound connections are kept, topped up from the addresses
+// we know. No more than `max_per_subnet` peers in each direction share a /16
+// (IPv4) or /32 (IPv6) prefix, so addresses rented in one range do not buy
+// more slots. Outbound peers should not all come from the same discovery
+// source (DNS seeds, static peers, mDNS, Kademlia): candidates of the least
+// represented source are dialed first. Every `rotation_interval_secs` one
+// outbound peer is replaced, so a connection set taken over does not last.
+// Loopback and private addresses are not grouped into subnets: local and LAN
+// test networks run many nodes on one host.
+
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A candidate dialed this recently is not dialed again
+pub const CANDIDATE_DIAL_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Addresses remembered for topping up outbound connections
+pub const MAX_PEER_CANDIDATES: usize = 1_024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeerDiversityParams {
+    /// Outbound connections kept open, dialing known addresses when short
+    pub min_outbound: usize,
+    /// Inbound connections accepted, 0 = none
+    pub max_inbound: usize,
+    /// Peers per /16 (IPv4) or /32 (IPv6) prefix, in each direction
+    pub max_per_subnet: usize,
+    /// Seconds between outbound peer rotations, 0 = never rotate
+    pub rotation_interval_secs: u64,
+}
+
+impl Default for PeerDiversityParams {
+    fn default() -> Self {
+        Self {
+            min_outbound: 8,
+            max_inbound: 32,
+            max_per_subnet: 2,
+            rotation_interval_secs: 1_800,
+        }
+    }
+}
+
+impl PeerDiversityParams {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_outbound == 0 {
+            return Err(SpiraChainError::Config(
+                "p2p_peers min_outbound must be greater than zero".to_string(),
+            ));
+        }
+        if self.max_per_subnet == 0 {
+            return Err(SpiraChainError::Config(
+                "p2p_peers max_per_subnet must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How we came to know a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PeerSource {
+    DnsSeed,
+    /// Configured or built-in fallback address
+    Static,
+    Mdns,
+    /// Learned from other peers through the DHT
+    Kademlia,
+    /// The peer connected to us
+    Inbound,
+}
+
+impl PeerSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PeerSource::DnsSeed => "dns_seed",
+            PeerSource::Static => "static",
+            PeerSource::Mdns => "mdns",
+            PeerSource::Kademlia => "kademlia",
+            PeerSource::Inbound => "inbound",
+        }
+    }
+}
+
+impl fmt::Display for PeerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Address range peers are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Subnet {
+    V4([u8; 2]),
+    V6([u8; 4]),
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Subnet::V4([a, b]) => write!(f, "{}.{}.0.0/16", a, b),
+            Subnet::V6([a, b, c, d]) => write!(f, "{:02x}{:02x}:{:02x}{:02x}::/32", a, b, c, d),
+        }
+    }
+}
+
+/// Subnet of a routable IP address; `None` for loopback, private and
+/// link-local addresses, and for addresses that are not IP (DNS names)
+pub fn subnet_of(addr: &Multiaddr) -> Option<Subnet> {
+    match addr.iter().next()? {
+        Protocol::Ip4(ip) => {
+            let local =
+                ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified();
+            let octets = ip.octets();
+            (!local).then_some(Subnet::V4([octets[0], octets[1]]))
+        }
+        Protocol::Ip6(ip) => {
+            let first = ip.segments()[0];
+            let local = ip.is_loopback()
+                || ip.is_unspecified()
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80;
+            let octets = ip.octets();
+            (!local).then_some(Subnet::V6([octets[0], octets[1], octets[2], octets[3]]))
+        }
+        _ => None,
+    }
+}
+
+/// Why a new connection was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiversityRejection {
+    InboundFull,
+    Subnet(Subnet),
+}
+
+impl fmt::Display for DiversityRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiversityRejection::InboundFull => write!(f, "no inbound slot left"),
+            DiversityRejection::Subnet(subnet) => write!(f, "too many peers in {}", subnet),
+        }
+    }
+}
+
+struct Connection {
+    source: PeerSource,
+    subnet: Option<Subnet>,
+    connected_at: Instant,
+}
+
+impl Connection {
+    fn is_outbound(&self) -> bool {
+        self.source != PeerSource::Inbound
+    }
+}
+
+struct Candidate {
+    peer: Option<PeerId>,
+    source: PeerSource,
+    last_dialed: Option<Instant>,
+}
+
+/// Connections by direction, source and subnet, and the addresses we can dial
+pub struct PeerDiversity {
+    params: PeerDiversityParams,
+    connections: HashMap<PeerId, Connection>,
+    candidates: HashMap<Multiaddr, Candidate>,
+    last_rotation: Instant,
+    stats: Arc<PeerDiversityStats>,
+}
+
+impl PeerDiversity {
+    pub fn new(params: PeerDiversityParams, now: Instant) -> Self {
+        Self {
+            params,
+            connections: HashMap::new(),
+            candidates: HashMap::new(),
+            last_rotation: now,
+            stats: Arc::new(PeerDiversityStats::default()),
+        }
+    }
+
+    pub fn stats(&self) -> Arc<PeerDiversityStats> {
+        Arc::clone(&self.stats)
+    }
+
+    pub fn params(&self) -> PeerDiversityParams {
+        self.params
+    }
+
+    pub fn set_params(&mut self, params: PeerDiversityParams) {
+        self.params = params;
+    }
+
+    /// Remember an address to dial when outbound connections run short.
+    /// The first source an address was learned from is kept.
+    pub fn add_candidate(&mut self, addr: Multiaddr, peer: Option<PeerId>, source: PeerSource) {
+        if self.candidates.len() >= MAX_PEER_CANDIDATES && !self.candidates.contains_key(&addr) {
+            return;
+        }
+        let candidate = self.candidates.entry(addr).or_insert(Candidate {
+            peer,
+            source,
+            last_dialed: None,
+        });
+        candidate.peer = candidate.peer.or(peer);
+    }
+
+    pub fn remove_candidate(&mut self, addr: &Multiaddr) {
+        self.candidates.remove(addr);
+    }
+
+    /// Record a dial we started ourselves, so the connection counts as
+    /// outbound from `source`
+    pub fn record_dial(&mut self, addr: Multiaddr, source: PeerSource, now: Instant) {
+        self.add_candidate(addr.clone(), None, source);
+        if let Some(candidate) = self.candidates.get_mut(&addr) {
+            candidate.last_dialed = Some(now);
+        }
+    }
+
+    /// Keep or refuse the first connection to `peer`, made to or from
+    /// `remote`. Outbound connections we did not dial ourselves were opened
+    /// by the DHT.
+    pub fn admit(
+        &mut self,
+        peer: PeerId,
+        remote: &Multiaddr,
+        dialer: bool,
+        now: Instant,
+    ) -> std::result::Result<PeerSource, DiversityRejection> {
+        let source = if dialer {
+            self.candidates
+                .get(remote)
+                .or_else(|| {
+                    self.candidates
+                        .values()
+                        .find(|candidate| candidate.peer == Some(peer))
+                })
+                .map(|candidate| candidate.source)
+                .unwrap_or(PeerSource::Kademlia)
+        } else {
+            PeerSource::Inbound
+        };
+        let outbound = source != PeerSource::Inbound;
+
+        let rejection = if !outbound && self.inbound() >= self.params.max_inbound {
+            Some(DiversityRejection::InboundFull)
+        } else {
+            subnet_of(remote)
+                .filter(|subnet| {
+                    self.connections
+                        .values()
+                        .filter(|c| c.subnet == Some(*subnet) && c.is_outbound() == outbound)
+                        .count()
+                        >= self.params.max_per_subnet
+                })
+                .map(DiversityRejection::Subnet)
+        };
+        if let Some(rejection) = rejection {
+            self.stats.record_rejection(rejection);
+            return Err(rejection);
+        }
+
+        if let Some(candidate) = self.candidates.get_mut(remote) {
+            candidate.peer = Some(peer);
+        }
+        self.connections.insert(
+            peer,
+            Connection {
+                source,
+                subnet: subnet_of(remote),
+                connected_at: now,
+            },
+        );
+        self.update_gauges();
+        Ok(source)
+    }
+
+    /// The last connection to `peer` closed
+    pub fn remove(&mut self, peer: &PeerId) {
+        if self.connections.remove(peer).is_some() {
+            self.update_gauges();
+        }
+    }
+
+    pub fn source(&self, peer: &PeerId) -> Option<PeerSource> {
+        self.connections.get(peer).map(|c| c.source)
+    }
+
+    pub fn outbound(&self) -> usize {
+        self.connections
+            .values()
+            .filter(|c| c.is_outbound())
+            .count()
+    }
+
+    pub fn inbound(&self) -> usize {
+        self.connections.len() - self.outbound()
+    }
+
+    /// Outbound connections per discovery source
+    pub fn outbound_sources(&self) -> BTreeMap<PeerSource, usize> {
+        let mut sources = BTreeMap::new();
+        for connection in self.connections.values().filter(|c| c.is_outbound()) {
+            *sources.entry(connection.source).or_insert(0) += 1;
+        }
+        sources
+    }
+
+    /// Addresses to dial to get back to `min_outbound`: never a connected or
+    /// `excluded` peer, nor one recently dialed, nor one in a subnet already
+    /// at its cap, least represented sources first. Returned dials are recorded.
+    pub fn dials_needed(
+        &mut self,
+        excluded: &HashSet<PeerId>,
+        now: Instant,
+    ) -> Vec<(Multiaddr, PeerSource)> {
+        let missing = self.params.min_outbound.saturating_sub(self.outbound());
+        if missing == 0 {
+            return Vec::new();
+        }
+
+        let mut per_source = self.outbound_sources();
+        let mut per_subnet: HashMap<Subnet, usize> = HashMap::new();
+        for subnet in self
+            .connections
+            .values()
+            .filter(|c| c.is_outbound())
+            .filter_map(|c| c.subnet)
+        {
+            *per_subnet.entry(subnet).or_insert(0) += 1;
+        }
+
+        let mut eligible: Vec<(&Multiaddr, &Candidate)> = self
+            .candidates
+            .iter()
+            .filter(|(_, candidate)| {
+                candidate.peer.is_none_or(|peer| {
+                    !self.connections.contains_key(&peer) && !excluded.contains(&peer)
+                })
+            })
+            .filter(|(_, candidate)| {
+                candidate
+                    .last_dialed
+                    .is_none_or(|dialed| now.duration_since(dialed) >= CANDIDATE_DIAL_BACKOFF)
+            })
+            .collect();
+        // Deterministic order within a source
+        eligible.sort_by_key(|(addr, candidate)| (candidate.source, addr.to_string()));
+
+        let mut dials = Vec::new();
+        while dials.len() < missing {
+            let next = eligible
+                .iter()
+                .enumerate()
+                .filter(|(_, (addr, _))| {
+                    subnet_of(addr).is_none_or(|subnet| {
+                        per_subnet.get(&subnet).copied().unwrap_or(0) < self.params.max_per_subnet
+                    })
+                })
+                .min_by_key(|(_, (_, candidate))| {
+                    per_source.get(&candidate.source).copied().unwrap_or(0)
+                })
+                .map(|(index, _)| index);
+            let Some(index) = next else {
+                break;
+            };
+            let (addr, candidate) = eligible.remove(index);
+            *per_source.entry(candidate.source).or_insert(0) += 1;
+            if let Some(subnet) = subnet_of(addr) {
+                *per_subnet.entry(subnet).or_insert(0) += 1;
+            }
+            dials.push((addr.clone(), candidate.source));
+        }
+
+        for (addr, _) in &dials {
+            if let Some(candidate) = self.candidates.get_mut(addr) {
+                candidate.last_dialed = Some(now);
+            }
+        }
+        self.stats
+            .dials
+            .fetch_add(dials.len() as u64, Ordering::Relaxed);
+        dials
+    }
+
+    /// Outbound peer to replace once per rotation interval: the longest
+    /// connected one of the most represented source. Only while we are at
+    /// `min_outbound` and a candidate is available to take its place.
+    pub fn rotation_due(&mut self, excluded: &HashSet<PeerId>, now: Instant) -> Option<PeerId> {
+        if self.params.rotation_interval_secs == 0
+            || now.duration_since(self.last_rotation)
+                < Duration::from_secs(self.params.rotation_interval_secs)
+        {
+            return None;
+        }
+        self.last_rotation = now;
+
+        let has_replacement = self.candidates.values().any(|candidate| {
+            candidate.peer.is_none_or(|peer| {
+                !self.connections.contains_key(&peer) && !excluded.contains(&peer)
+            })
+        });
+        if self.outbound() < self.params.min_outbound || !has_replacement {
+            return None;
+        }
+
+        let sources = self.outbound_sources();
+        let (peer, _) = self
+            .connections
+            .iter()
+            .filter(|(_, c)| c.is_outbound())
+            .max_by_key(|(peer, c)| {
+                (
+                    sources.get(&c.source).copied().unwrap_or(0),
+                    std::cmp::Reverse(c.connected_at),
+                    peer.to_bytes(),
+                )
+            })?;
+        let peer = *peer;
+        self.stats.rotations.fetch_add(1, Ordering::Relaxed);
+        Some(peer)
+    }
+
+    fn update_gauges(&self) {
+        let subnets: HashSet<Subnet> = self.connections.values().filter_map(|c| c.subnet).collect();
+        self.stats
+            .outbound
+            .store(self.outbound() as u64, Ordering::Relaxed);
+        self.stats
+            .inbound
+            .store(self.inbound() as u64, Ordering::Relaxed);
+        self.stats
+            .subnets
+            .store(subnets.len() as u64, Ordering::Relaxed);
+        self.stats
+            .outbound_sources
+            .store(self.outbound_sources().len() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Connection mix and what the diversity rules did about it, shared for metrics
+#[derive(Default)]
+pub struct PeerDiversityStats {
+    outbound: AtomicU64,
+    inbound: AtomicU64,
+    subnets: AtomicU64,
+    outbound_sources: AtomicU64,
+    rejected_inbound_full: AtomicU64,
+    rejected_subnet: AtomicU64,
+    dials: AtomicU64,
+    rotations: AtomicU64,
+}
+
+impl PeerDiversityStats {
+    pub fn rotations(&self) -> u64 {
+        self.rotations.load(Ordering::Relaxed)
+    }
+
+    pub fn rejected(&self, rejection: DiversityRejection) -> u64 {
+        match rejection {
+            DiversityRejection::InboundFull => self.rejected_inbound_full.load(Ordering::Relaxed),
+            DiversityRejection::Subnet(_) => self.rejected_subnet.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_rejection(&self, rejection: DiversityRejection) {
+        match rejection {
+            DiversityRejection::InboundFull => &self.rejected_inbound_full,
+            DiversityRejection::Subnet(_) => &self.rejected_subnet,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        format!(
+            "# HELP spirachain_p2p_connections Connected peers by direction\n\
+             # TYPE spirachain_p2p_connections gauge\n\
+             spirachain_p2p_connections{{direction=\"outbound\"}} {}\n\
+             spirachain_p2p_connections{{direction=\"inbound\"}} {}\n\
+             # HELP spirachain_p2p_subnets Distinct /16 (IPv4) or /32 (IPv6) subnets among connected peers\n\
+             # TYPE spirachain_p2p_subnets gauge\n\
+             spirachain_p2p_subnets {}\n\
+             # HELP spirachain_p2p_outbound_sources Distinct discovery sources among outbound peers\n\
+             # TYPE spirachain_p2p_outbound_sources gauge\n\
+             spirachain_p2p_outbound_sources {}\n\
+             # HELP spirachain_p2p_connections_rejected_total Connections dropped by the diversity rules, by reason\n\
+             # TYPE spirachain_p2p_connections_rejected_total counter\n\
+             spirachain_p2p_connections_rejected_total{{reason=\"inbound_full\"}} {}\n\
+             spirachain_p2p_connections_rejected_total{{reason=\"subnet\"}} {}\n\
+             # HELP spirachain_p2p_outbound_dials_total Dials made to top up outbound connections\n\
+             # TYPE spirachain_p2p_outbound_dials_total counter\n\
+             spirachain_p2p_outbound_dials_total {}\n\
+             # HELP spirachain_p2p_rotations_total Outbound peers rotated out\n\
+             # TYPE spirachain_p2p_rotations_total counter\n\
+             spirachain_p2p_rotations_total {}\n",
+            self.outbound.load(Ordering::Relaxed),
+            self.inbound.load(Ordering::Relaxed),
+            self.subnets.load(Ordering::Relaxed),
+            self.outbound_sources.load(Ordering::Relaxed),
+            self.rejected_inbound_full.load(Ordering::Relaxed),
+            self.rejected_subnet.load(Ordering::Relaxed),
+            self.dials.load(Ordering::Relaxed),
+            self.rotations()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(ip: &str) -> Multiaddr {
+        format!("/ip4/{}/tcp/30333", ip).parse().unwrap()
+    }
+
+    #[test]
+    fn test_subnets_group_routable_addresses() {
+        assert_eq!(
+            subnet_of(&addr("51.154.64.38")),
+            Some(Subnet::V4([51, 154]))
+        );
+        assert_eq!(
+            subnet_of(&addr("51.154.1.2")),
+            subnet_of(&addr("51.154.64.38"))
+        );
+        assert_eq!(subnet_of(&addr("127.0.0.1")), None);
+        assert_eq!(subnet_of(&addr("192.168.1.20")), None);
+        let v6: Multiaddr = "/ip6/2001:db8:1::5/tcp/30333".parse().unwrap();
+        assert_eq!(
+            subnet_of(&v6).unwrap().to_string(),
+            "2001:0db8::/32".to_string()
+        );
+        let dns: Multiaddr = "/dns4/seed1.spirachain.org/tcp/30333".parse().unwrap();
+        assert_eq!(subnet_of(&dns), None);
+    }
+
+    #[test]
+    fn test_inbound_and_subnet_caps() {
+        let now = Instant::now();
+        let mut diversity = PeerDiversity::new(
+            PeerDiversityParams {
+                max_inbound: 2,
+                ..PeerDiversityParams::default()
+            },
+            now,
+        );
+
+        // Two per subnet, inbound and outbound counted apart
+        assert!(diversity
+            .admit(PeerId::random(), &addr("8.8.1.1"), false, now)
+            .is_ok());
+        assert!(diversity
+            .admit(PeerId::random(), &addr("8.8.2.2"), false, now)
+            .is_ok());
+        assert_eq!(
+            diversity.admit(PeerId::random(), &addr("9.9.9.9"), false, now),
+            Err(DiversityRejection::InboundFull)
+        );
+        diversity.record_dial(addr("8.8.3.3"), PeerSource::DnsSeed, now);
+        assert_eq!(
+            diversity.admit(PeerId::random(), &addr("8.8.3.3"), true, now),
+            Ok(PeerSource::DnsSeed)
+        );
+        assert_eq!(
+            diversity.admit(PeerId::random(), &addr("8.8.4.4"), true, now),
+            Ok(PeerSource::Kademlia)
+        );
+        assert_eq!(
+            diversity.admit(PeerId::random(), &addr("8.8.5.5"), true, now),
+            Err(DiversityRejection::Subnet(Subnet::V4([8, 8])))
+        );
+        // Local test networks share one host
+        for _ in 0..4 {
+            assert!(diversity
+                .admit(PeerId::random(), &addr("127.0.0.1"), true, now)
+                .is_ok());
+        }
+        assert_eq!((diversity.outbound(), diversity.inbound()), (6, 2));
+        assert_eq!(
+            diversity
+                .stats()
+                .rejected(DiversityRejection::Subnet(Subnet::V4([0, 0]))),
+            1
+        );
+    }
+
+    #[test]
+    fn test_outbound_top_up_prefers_missing_sources() {
+        let now = Instant::now();
+        let mut diversity = PeerDiversity::new(
+            PeerDiversityParams {
+                min_outbound: 5,
+                ..PeerDiversityParams::default()
+            },
+            now,
+        );
+        diversity.record_dial(addr("1.1.1.1"), PeerSource::DnsSeed, now);
+        diversity
+            .admit(PeerId::random(), &addr("1.1.1.1"), true, now)
+            .unwrap();
+
+        diversity.add_candidate(addr("2.2.1.1"), None, PeerSource::DnsSeed);
+        for ip in ["3.3.1.1", "3.3.2.2", "3.3.3.3"] {
+            diversity.add_candidate(addr(ip), Some(PeerId::random()), PeerSource::Kademlia);
+        }
+        let banned = PeerId::random();
+        diversity.add_candidate(addr("4.4.1.1"), Some(banned), PeerSource::Mdns);
+
+        // Kademlia first, as no outbound peer comes from it; the third
+        // 3.3.0.0/16 address and the banned peer are left out
+        let excluded = HashSet::from([banned]);
+        assert_eq!(
+            diversity.dials_needed(&excluded, now),
+            vec![
+                (addr("3.3.1.1"), PeerSource::Kademlia),
+                (addr("2.2.1.1"), PeerSource::DnsSeed),
+                (addr("3.3.2.2"), PeerSource::Kademlia),
+            ]
+        );
+
+        // Dialed candidates back off
+        assert_eq!(
+            diversity.dials_needed(&excluded, now),
+            vec![(addr("3.3.3.3"), PeerSource::Kademlia)]
+        );
+        assert!(diversity.dials_needed(&excluded, now).is_empty());
+        assert_eq!(
+            diversity
+                .dials_needed(&excluded, now + CANDIDATE_DIAL_BACKOFF)
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_rotation_replaces_the_oldest_of_the_largest_source() {
+        let start = Instant::now();
+        let mut diversity = PeerDiversity::new(
+            PeerDiversityParams {
+                min_outbound: 3,
+                rotation_interval_secs: 60,
+                ..PeerDiversityParams::default()
+            },
+            start,
+        );
+        let oldest = PeerId::random();
+        diversity.record_dial(addr("1.1.1.1"), PeerSource::DnsSeed, start);
+        diversity
+            .admit(oldest, &addr("1.1.1.1"), true, start)
+            .unwrap();
+        let later = start + Duration::from_secs(1);
+        diversity.record_dial(addr("2.2.2.2"), PeerSource::DnsSeed, later);
+        diversity
+            .admit(PeerId::random(), &addr("2.2.2.2"), true, later)
+            .unwrap();
+        let mdns = PeerId::random();
+        diversity.record_dial(addr("3.3.3.3"), PeerSource::Mdns, start);
+        diversity
+            .admit(mdns, &addr("3.3.3.3"), true, start)
+            .unwrap();
+
+        let excluded = HashSet::new();
+        let due = start + Duration::from_secs(60);
+        // Nothing to replace it with yet
+        assert_eq!(diversity.rotation_due(&excluded, due), None);
+
+        diversity.add_candidate(addr("4.4.4.4"), None, PeerSource::Kademlia);
+        assert_eq!(diversity.rotation_due(&excluded, due), None);
+        assert_eq!(
+            diversity.rotation_due(&excluded, due + Duration::from_secs(60)),
+            Some(oldest)
+        );
+        assert_eq!(diversity.stats().rotations(), 1);
+    }
+}
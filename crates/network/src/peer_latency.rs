@@ -0,0 +1,454 @@
+// Peer latency tracking for sync peer selection
+// Rolling response times per peer, fed by direct latency probes and block request answers,
+// and rolling transfer rates fed by completed GET_BLOCKS batches. Sync peers are
+// ranked by the time a batch is expected to take, so a near peer with a thin pipe
+// loses to a farther one that delivers faster.
+// Probes double as a liveness check: a peer missing several in a row is dropped.
+
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of response times kept per peer
+const LATENCY_WINDOW: usize = 16;
+
+/// Consecutive failures after which a peer is only used when nobody else can serve
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// Size of a sync batch assumed when turning a transfer rate into a fetch time
+const EXPECTED_BATCH_BYTES: u64 = 1024 * 1024;
+
+/// Latency probe sent to a single peer over the probe protocol; the answer
+/// echoes it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyProbe {
+    pub nonce: u64,
+}
+
+#[derive(Debug, Default)]
+struct PeerStats {
+    samples: VecDeque<Duration>,
+    /// Bytes per second of recent block batches
+    throughput: VecDeque<u64>,
+    consecutive_failures: u32,
+    /// Probes expired since the peer last answered anything
+    missed_probes: u32,
+}
+
+impl PeerStats {
+    fn average(&self) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let total: Duration = self.samples.iter().sum();
+        Some(total / self.samples.len() as u32)
+    }
+
+    fn average_throughput(&self) -> Option<u64> {
+        if self.throughput.is_empty() {
+            return None;
+        }
+        let total: u128 = self.throughput.iter().map(|rate| *rate as u128).sum();
+        Some((total / self.throughput.len() as u128) as u64)
+    }
+
+    /// Round trip plus the time an average batch takes at the measured rate;
+    /// only the round trip until a batch completed
+    fn expected_fetch_time(&self) -> Option<Duration> {
+        let latency = self.average()?;
+        let transfer = match self.average_throughput() {
+            Some(rate) => Duration::from_secs_f64(EXPECTED_BATCH_BYTES as f64 / rate.max(1) as f64),
+            None => Duration::ZERO,
+        };
+        Some(latency.saturating_add(transfer))
+    }
+}
+
+pub struct PeerLatencyTracker {
+    peers: HashMap<PeerId, PeerStats>,
+    pending_probes: HashMap<u64, (PeerId, Instant)>,
+    next_nonce: u64,
+}
+
+impl PeerLatencyTracker {
+    pub fn new() -> Self {
+        Self {
+            peers: HashMap::new(),
+            pending_probes: HashMap::new(),
+            next_nonce: 0,
+        }
+    }
+
+    /// Register an outgoing probe and return its nonce
+    pub fn start_probe(&mut self, peer: PeerId) -> u64 {
+        let nonce = self.next_nonce;
+        self.next_nonce = self.next_nonce.wrapping_add(1);
+        self.pending_probes.insert(nonce, (peer, Instant::now()));
+        nonce
+    }
+
+    /// Match a probe answer; only the probed peer can complete it
+    pub fn complete_probe(&mut self, nonce: u64, responder: &PeerId) -> Option<Duration> {
+        match self.pending_probes.get(&nonce) {
+            Some((peer, _)) if peer == responder => {
+                let (peer, sent_at) = self.pending_probes.remove(&nonce)?;
+                let rtt = sent_at.elapsed();
+                self.record_response(peer, rtt);
+                Some(rtt)
+            }
+            _ => None,
+        }
+    }
+
+    /// Count unanswered probes older than `timeout` as failures
+    pub fn expire_probes(&mut self, timeout: Duration) {
+        let expired: Vec<u64> = self
+            .pending_probes
+            .iter()
+            .filter(|(_, (_, sent_at))| sent_at.elapsed() >= timeout)
+            .map(|(nonce, _)| *nonce)
+            .collect();
+
+        for nonce in expired {
+            if let Some((peer, _)) = self.pending_probes.remove(&nonce) {
+                self.record_failure(peer);
+                self.peers.entry(peer).or_default().missed_probes += 1;
+            }
+        }
+    }
+
+    pub fn record_response(&mut self, peer: PeerId, rtt: Duration) {
+        let stats = self.peers.entry(peer).or_default();
+        stats.samples.push_back(rtt);
+        if stats.samples.len() > LATENCY_WINDOW {
+            stats.samples.pop_front();
+        }
+        stats.consecutive_failures = 0;
+        stats.missed_probes = 0;
+    }
+
+    /// Record a block batch of `bytes` that took `elapsed` from request to last block
+    pub fn record_transfer(&mut self, peer: PeerId, bytes: usize, elapsed: Duration) {
+        let millis = elapsed.as_millis().max(1);
+        let rate = (bytes as u128 * 1000 / millis).min(u64::MAX as u128) as u64;
+        let stats = self.peers.entry(peer).or_default();
+        stats.throughput.push_back(rate);
+        if stats.throughput.len() > LATENCY_WINDOW {
+            stats.throughput.pop_front();
+        }
+    }
+
+    pub fn record_failure(&mut self, peer: PeerId) {
+        self.peers.entry(peer).or_default().consecutive_failures += 1;
+    }
+
+    pub fn remove_peer(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+        self.pending_probes.retain(|_, (p, _)| p != peer);
+    }
+
+    pub fn average_latency(&self, peer: &PeerId) -> Option<Duration> {
+        self.peers.get(peer).and_then(|stats| stats.average())
+    }
+
+    /// Average bytes per second of the peer's recent block batches
+    pub fn throughput(&self, peer: &PeerId) -> Option<u64> {
+        self.peers
+            .get(peer)
+            .and_then(|stats| stats.average_throughput())
+    }
+
+    pub fn consecutive_failures(&self, peer: &PeerId) -> u32 {
+        self.peers
+            .get(peer)
+            .map(|stats| stats.consecutive_failures)
+            .unwrap_or(0)
+    }
+
+    /// Most recent response time
+    pub fn last_latency(&self, peer: &PeerId) -> Option<Duration> {
+        self.peers
+            .get(peer)
+            .and_then(|stats| stats.samples.back().copied())
+    }
+
+    pub fn missed_probes(&self, peer: &PeerId) -> u32 {
+        self.peers
+            .get(peer)
+            .map(|stats| stats.missed_probes)
+            .unwrap_or(0)
+    }
+
+    /// Peers that missed at least `max_missed` probes in a row
+    pub fn unresponsive_peers(&self, max_missed: u32) -> Vec<PeerId> {
+        self.peers
+            .iter()
+            .filter(|(_, stats)| stats.missed_probes >= max_missed)
+            .map(|(peer, _)| *peer)
+            .collect()
+    }
+
+    /// Round-trip times of `peers`, for metrics
+    pub fn rtts<'a>(&self, peers: impl IntoIterator<Item = &'a PeerId>) -> Vec<PeerRtt> {
+        peers
+            .into_iter()
+            .map(|peer| PeerRtt {
+                peer: peer.to_string(),
+                average: self.average_latency(peer),
+                last: self.last_latency(peer),
+                missed_probes: self.missed_probes(peer),
+            })
+            .collect()
+    }
+
+    /// Average latency of every peer with at least one sample
+    pub fn latencies(&self) -> HashMap<PeerId, Duration> {
+        self.peers
+            .iter()
+            .filter_map(|(peer, stats)| stats.average().map(|avg| (*peer, avg)))
+            .collect()
+    }
+
+    /// Pick the healthy peer among `candidates` expected to deliver a batch
+    /// soonest. Measured peers beat unmeasured ones; failing peers are a last resort.
+    pub fn best_peer<'a>(
+        &self,
+        candidates: impl IntoIterator<Item = &'a PeerId>,
+    ) -> Option<PeerId> {
+        candidates
+            .into_iter()
+            .min_by_key(|peer| {
+                let failures = self.consecutive_failures(peer);
+                let unhealthy = failures >= MAX_CONSECUTIVE_FAILURES;
+                let fetch_time = self
+                    .peers
+                    .get(peer)
+                    .and_then(|stats| stats.expected_fetch_time())
+                    .unwrap_or(Duration::MAX);
+                (
+                    unhealthy,
+                    failures.min(MAX_CONSECUTIVE_FAILURES),
+                    fetch_time,
+                    peer.to_bytes(),
+                )
+            })
+            .copied()
+    }
+}
+
+impl Default for PeerLatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerRtt {
+    pub peer: String,
+    pub average: Option<Duration>,
+    pub last: Option<Duration>,
+    pub missed_probes: u32,
+}
+
+/// Peer round-trip times and liveness disconnects, shared for metrics
+#[derive(Default)]
+pub struct LivenessStats {
+    peers: Mutex<Vec<PeerRtt>>,
+    disconnects: AtomicU64,
+}
+
+impl LivenessStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the per-peer view after a probe round
+    pub fn set_peers(&self, mut peers: Vec<PeerRtt>) {
+        peers.sort_by(|a, b| a.peer.cmp(&b.peer));
+        *self.peers.lock().unwrap_or_else(|e| e.into_inner()) = peers;
+    }
+
+    pub fn record_disconnect(&self) {
+        self.disconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn disconnects(&self) -> u64 {
+        self.disconnects.load(Ordering::Relaxed)
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        let peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut out = String::from(
+            "# HELP spirachain_peer_rtt_seconds Average ping round-trip time per peer\n\
+             # TYPE spirachain_peer_rtt_seconds gauge\n",
+        );
+        for rtt in peers.iter() {
+            if let Some(average) = rtt.average {
+                out.push_str(&format!(
+                    "spirachain_peer_rtt_seconds{{peer=\"{}\"}} {:.6}\n",
+                    rtt.peer,
+                    average.as_secs_f64()
+                ));
+            }
+        }
+        out.push_str(
+            "# HELP spirachain_peer_missed_pings Pings the peer missed in a row\n\
+             # TYPE spirachain_peer_missed_pings gauge\n",
+        );
+        for rtt in peers.iter() {
+            out.push_str(&format!(
+                "spirachain_peer_missed_pings{{peer=\"{}\"}} {}\n",
+                rtt.peer, rtt.missed_probes
+            ));
+        }
+        out.push_str(&format!(
+            "# HELP spirachain_peer_liveness_disconnects_total Peers disconnected for missing pings\n\
+             # TYPE spirachain_peer_liveness_disconnects_total counter\n\
+             spirachain_peer_liveness_disconnects_total {}\n",
+            self.disconnects()
+        ));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_average() {
+        let mut tracker = PeerLatencyTracker::new();
+        let peer = PeerId::random();
+
+        tracker.record_response(peer, Duration::from_millis(100));
+        tracker.record_response(peer, Duration::from_millis(300));
+        assert_eq!(
+            tracker.average_latency(&peer),
+            Some(Duration::from_millis(200))
+        );
+
+        for _ in 0..LATENCY_WINDOW {
+            tracker.record_response(peer, Duration::from_millis(50));
+        }
+        assert_eq!(
+            tracker.average_latency(&peer),
+            Some(Duration::from_millis(50))
+        );
+    }
+
+    #[test]
+    fn test_best_peer_prefers_lowest_latency() {
+        let mut tracker = PeerLatencyTracker::new();
+        let fast = PeerId::random();
+        let slow = PeerId::random();
+        let unknown = PeerId::random();
+
+        tracker.record_response(fast, Duration::from_millis(20));
+        tracker.record_response(slow, Duration::from_millis(400));
+
+        let candidates = [slow, unknown, fast];
+        assert_eq!(tracker.best_peer(&candidates), Some(fast));
+        assert_eq!(tracker.best_peer(&[slow, unknown]), Some(slow));
+        assert_eq!(tracker.best_peer(&[]), None);
+    }
+
+    #[test]
+    fn test_best_peer_ranks_slow_transfers_below_fast_ones() {
+        let mut tracker = PeerLatencyTracker::new();
+        let near_but_slow = PeerId::random();
+        let far_but_fast = PeerId::random();
+
+        tracker.record_response(near_but_slow, Duration::from_millis(10));
+        tracker.record_response(far_but_fast, Duration::from_millis(150));
+        assert_eq!(
+            tracker.best_peer(&[near_but_slow, far_but_fast]),
+            Some(near_but_slow)
+        );
+
+        // 64 KiB/s against 8 MiB/s: a 1 MiB batch takes 16s against 125ms
+        tracker.record_transfer(near_but_slow, 64 * 1024, Duration::from_secs(1));
+        tracker.record_transfer(far_but_fast, 8 * 1024 * 1024, Duration::from_secs(1));
+        assert_eq!(tracker.throughput(&near_but_slow), Some(64 * 1024));
+        assert_eq!(
+            tracker.best_peer(&[near_but_slow, far_but_fast]),
+            Some(far_but_fast)
+        );
+    }
+
+    #[test]
+    fn test_failover_skips_failing_peer() {
+        let mut tracker = PeerLatencyTracker::new();
+        let fast = PeerId::random();
+        let backup = PeerId::random();
+
+        tracker.record_response(fast, Duration::from_millis(10));
+        tracker.record_response(backup, Duration::from_millis(500));
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            tracker.record_failure(fast);
+        }
+        assert_eq!(tracker.best_peer(&[fast, backup]), Some(backup));
+
+        // A failing peer is still used when it is the only option
+        assert_eq!(tracker.best_peer(&[fast]), Some(fast));
+
+        // One good answer restores it
+        tracker.record_response(fast, Duration::from_millis(10));
+        assert_eq!(tracker.best_peer(&[fast, backup]), Some(fast));
+    }
+
+    #[test]
+    fn test_probe_only_completed_by_target() {
+        let mut tracker = PeerLatencyTracker::new();
+        let target = PeerId::random();
+        let other = PeerId::random();
+
+        let nonce = tracker.start_probe(target);
+        assert!(tracker.complete_probe(nonce, &other).is_none());
+        assert!(tracker.complete_probe(nonce, &target).is_some());
+        assert!(tracker.average_latency(&target).is_some());
+
+        let expired = tracker.start_probe(other);
+        tracker.expire_probes(Duration::ZERO);
+        assert!(tracker.complete_probe(expired, &other).is_none());
+        assert_eq!(tracker.consecutive_failures(&other), 1);
+    }
+
+    #[test]
+    fn test_missed_pings_mark_peer_unresponsive() {
+        let mut tracker = PeerLatencyTracker::new();
+        let silent = PeerId::random();
+        let alive = PeerId::random();
+
+        for _ in 0..3 {
+            tracker.start_probe(silent);
+            let nonce = tracker.start_probe(alive);
+            tracker.complete_probe(nonce, &alive);
+            tracker.expire_probes(Duration::ZERO);
+        }
+        assert_eq!(tracker.unresponsive_peers(3), vec![silent]);
+        assert!(tracker.last_latency(&alive).is_some());
+
+        let stats = LivenessStats::new();
+        stats.set_peers(tracker.rtts(&[silent, alive]));
+        stats.record_disconnect();
+        let metrics = stats.export_prometheus();
+        assert!(metrics.contains(&format!(
+            "spirachain_peer_missed_pings{{peer=\"{}\"}} 3\n",
+            silent
+        )));
+        assert!(metrics.contains(&format!(
+            "spirachain_peer_rtt_seconds{{peer=\"{}\"}}",
+            alive
+        )));
+        assert!(metrics.contains("spirachain_peer_liveness_disconnects_total 1\n"));
+
+        // Any answer proves the peer is alive
+        tracker.record_response(silent, Duration::from_millis(30));
+        assert!(tracker.unresponsive_peers(3).is_empty());
+    }
+}
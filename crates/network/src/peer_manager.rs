@@ -0,0 +1,267 @@
+// Peer capabilities advertised through identify
+// Agent string: `spirachain/<version> (<role>; <cap>,<cap>; height=<n>[; genesis=<hex>; fork=<hex>])`
+
+use crate::compression::{CAP_SNAPPY, CAP_ZSTD};
+use libp2p::PeerId;
+use spirachain_core::Hash;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+const AGENT_PREFIX: &str = "spirachain/";
+
+/// Serves GET_BLOCKS batches
+pub const CAP_SYNC: &str = "sync/1";
+/// Relays compact blocks and answers missing transaction requests
+pub const CAP_COMPACT_BLOCKS: &str = "compactblocks/1";
+/// Validates semantic intents and spiral metadata
+pub const CAP_SEMANTIC: &str = "semantic/1";
+
+/// What a node runs as, from the network's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+    Validator,
+    Full,
+    Light,
+    Archive,
+}
+
+impl NodeRole {
+    /// Capabilities a node of this role advertises. Light nodes only follow
+    /// headers, so nobody should ask them for blocks; every role reads
+    /// compressed gossip.
+    pub fn capabilities(&self) -> &'static [&'static str] {
+        match self {
+            NodeRole::Light => &[CAP_SNAPPY, CAP_ZSTD],
+            _ => &[
+                CAP_SYNC,
+                CAP_COMPACT_BLOCKS,
+                CAP_SEMANTIC,
+                CAP_SNAPPY,
+                CAP_ZSTD,
+            ],
+        }
+    }
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NodeRole::Validator => "validator",
+            NodeRole::Full => "full",
+            NodeRole::Light => "light",
+            NodeRole::Archive => "archive",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for NodeRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "validator" => Ok(NodeRole::Validator),
+            "full" => Ok(NodeRole::Full),
+            "light" => Ok(NodeRole::Light),
+            "archive" => Ok(NodeRole::Archive),
+            other => Err(format!("Unknown node role: {}", other)),
+        }
+    }
+}
+
+/// Parsed identify agent version of a SpiraChain node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentInfo {
+    pub version: String,
+    pub role: NodeRole,
+    pub capabilities: Vec<String>,
+    /// Height when the peer started its network; live heights come from HEIGHT announcements
+    pub height: u64,
+    /// Chain the peer follows, when it advertises one
+    pub genesis: Option<Hash>,
+    /// Fork id at `height`
+    pub fork_id: Option<Hash>,
+}
+
+impl AgentInfo {
+    pub fn local(role: NodeRole, height: u64) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            role,
+            capabilities: role.capabilities().iter().map(|c| c.to_string()).collect(),
+            height,
+            genesis: None,
+            fork_id: None,
+        }
+    }
+
+    /// Advertise the chain followed, checked by peers during the handshake
+    pub fn with_chain(mut self, genesis: Hash, fork_id: Hash) -> Self {
+        self.genesis = Some(genesis);
+        self.fork_id = Some(fork_id);
+        self
+    }
+
+    /// Parse an agent string. Nodes from before capability advertisement send a
+    /// bare `spirachain/<version>` and are treated as full nodes serving sync.
+    pub fn parse(agent: &str) -> Option<Self> {
+        let rest = agent.strip_prefix(AGENT_PREFIX)?;
+        let Some((version, details)) = rest.split_once(' ') else {
+            return Some(Self {
+                version: rest.to_string(),
+                role: NodeRole::Full,
+                capabilities: vec![CAP_SYNC.to_string(), CAP_COMPACT_BLOCKS.to_string()],
+                height: 0,
+                genesis: None,
+                fork_id: None,
+            });
+        };
+
+        let details = details.strip_prefix('(')?.strip_suffix(')')?;
+        let mut fields = details.split(';').map(str::trim);
+        let role = fields.next()?.parse().ok()?;
+        let capabilities = fields
+            .next()?
+            .split(',')
+            .filter(|cap| !cap.is_empty())
+            .map(|cap| cap.trim().to_string())
+            .collect();
+        let height = fields.next()?.strip_prefix("height=")?.parse().ok()?;
+
+        let mut info = Self {
+            version: version.to_string(),
+            role,
+            capabilities,
+            height,
+            genesis: None,
+            fork_id: None,
+        };
+        // Optional fields, absent from nodes that do not advertise their chain
+        for field in fields {
+            let (key, value) = field.split_once('=')?;
+            let hash = hex::decode(value)
+                .ok()
+                .and_then(|bytes| Hash::from_slice(&bytes).ok());
+            match key {
+                "genesis" => info.genesis = Some(hash?),
+                "fork" => info.fork_id = Some(hash?),
+                _ => {}
+            }
+        }
+        Some(info)
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|cap| cap == capability)
+    }
+}
+
+impl fmt::Display for AgentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{} ({}; {}; height={}",
+            AGENT_PREFIX,
+            self.version,
+            self.role,
+            self.capabilities.join(","),
+            self.height
+        )?;
+        if let Some(genesis) = &self.genesis {
+            write!(f, "; genesis={}", hex::encode(genesis.as_bytes()))?;
+        }
+        if let Some(fork_id) = &self.fork_id {
+            write!(f, "; fork={}", hex::encode(fork_id.as_bytes()))?;
+        }
+        f.write_str(")")
+    }
+}
+
+struct PeerEntry {
+    agent: String,
+    info: Option<AgentInfo>,
+}
+
+/// Agent and capabilities of every peer that sent identify
+#[derive(Default)]
+pub struct PeerManager {
+    peers: HashMap<PeerId, PeerEntry>,
+}
+
+impl PeerManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_agent(&mut self, peer: PeerId, agent: String) {
+        let info = AgentInfo::parse(&agent);
+        self.peers.insert(peer, PeerEntry { agent, info });
+    }
+
+    pub fn remove_peer(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    pub fn agent(&self, peer: &PeerId) -> Option<&str> {
+        self.peers.get(peer).map(|entry| entry.agent.as_str())
+    }
+
+    pub fn info(&self, peer: &PeerId) -> Option<&AgentInfo> {
+        self.peers.get(peer).and_then(|entry| entry.info.as_ref())
+    }
+
+    /// Whether `peer` can serve `capability`. Peers that have not identified yet
+    /// get the benefit of the doubt; peers with a foreign agent do not.
+    pub fn supports(&self, peer: &PeerId, capability: &str) -> bool {
+        match self.peers.get(peer) {
+            None => true,
+            Some(entry) => entry
+                .info
+                .as_ref()
+                .is_some_and(|info| info.supports(capability)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_string_roundtrip() {
+        let local = AgentInfo::local(NodeRole::Validator, 42);
+        let agent = local.to_string();
+        assert!(agent.ends_with(
+            "(validator; sync/1,compactblocks/1,semantic/1,snappy/1,zstd/1; height=42)"
+        ));
+        assert_eq!(AgentInfo::parse(&agent), Some(local));
+
+        let light = AgentInfo::parse(&AgentInfo::local(NodeRole::Light, 7).to_string()).unwrap();
+        assert_eq!(light.role, NodeRole::Light);
+        assert!(!light.supports(CAP_SYNC));
+
+        let legacy = AgentInfo::parse("spirachain/0.1.0").unwrap();
+        assert!(legacy.supports(CAP_SYNC));
+        assert!(AgentInfo::parse("rust-libp2p/0.53").is_none());
+    }
+
+    #[test]
+    fn test_peer_manager_routes_by_capability() {
+        let mut peers = PeerManager::new();
+        let light = PeerId::random();
+        let full = PeerId::random();
+        let other = PeerId::random();
+        peers.record_agent(light, AgentInfo::local(NodeRole::Light, 0).to_string());
+        peers.record_agent(full, AgentInfo::local(NodeRole::Full, 0).to_string());
+        peers.record_agent(other, "go-ipfs/0.20".to_string());
+
+        assert!(!peers.supports(&light, CAP_SYNC));
+        assert!(peers.supports(&full, CAP_SYNC));
+        assert!(!peers.supports(&other, CAP_SYNC));
+        assert!(peers.supports(&PeerId::random(), CAP_SYNC));
+
+        peers.remove_peer(&full);
+        assert!(peers.agent(&full).is_none());
+    }
+}
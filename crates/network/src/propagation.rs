@@ -0,0 +1,142 @@
+// Block propagation latency
+// Compact block announcements carry the time the producer sent them, rounded to
+// ANNOUNCE_GRANULARITY_MS so the stamp cannot fingerprint a node's clock. The
+// receiver works out the latency and whether the announcement reached it
+// straight from the producer or through relays; no relay path is ever sent.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Resolution of announcement timestamps
+pub const ANNOUNCE_GRANULARITY_MS: u64 = 10;
+
+/// Announcements kept for the percentiles
+const PROPAGATION_WINDOW: usize = 256;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnouncementMeta {
+    /// Producer's clock when the announcement was first published
+    pub sent_at_ms: u64,
+}
+
+impl AnnouncementMeta {
+    pub fn now() -> Self {
+        Self::at(now_ms())
+    }
+
+    pub fn at(time_ms: u64) -> Self {
+        Self {
+            sent_at_ms: time_ms - time_ms % ANNOUNCE_GRANULARITY_MS,
+        }
+    }
+
+    /// Latency seen at `received_ms`; skew that puts the announcement in the
+    /// future reads as zero
+    pub fn latency_ms(&self, received_ms: u64) -> u64 {
+        received_ms.saturating_sub(self.sent_at_ms)
+    }
+}
+
+/// Latency percentiles over the most recent announcements
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PropagationStats {
+    pub samples: usize,
+    /// Announcements measured since startup
+    pub total: u64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+    /// Share of recent announcements that came through at least one relay
+    pub relayed_ratio: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct PropagationTracker {
+    samples: VecDeque<(u64, bool)>,
+    total: u64,
+}
+
+impl PropagationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an announcement received now; `relayed` when the peer that
+    /// delivered it is not the producer
+    pub fn record(&mut self, meta: &AnnouncementMeta, relayed: bool) -> u64 {
+        let latency = meta.latency_ms(now_ms());
+        self.record_latency(latency, relayed);
+        latency
+    }
+
+    pub fn record_latency(&mut self, latency_ms: u64, relayed: bool) {
+        self.samples.push_back((latency_ms, relayed));
+        while self.samples.len() > PROPAGATION_WINDOW {
+            self.samples.pop_front();
+        }
+        self.total += 1;
+    }
+
+    pub fn stats(&self) -> PropagationStats {
+        if self.samples.is_empty() {
+            return PropagationStats {
+                total: self.total,
+                ..PropagationStats::default()
+            };
+        }
+
+        let mut latencies: Vec<u64> = self.samples.iter().map(|(latency, _)| *latency).collect();
+        latencies.sort_unstable();
+        let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+        let relayed = self.samples.iter().filter(|(_, relayed)| *relayed).count();
+
+        PropagationStats {
+            samples: latencies.len(),
+            total: self.total,
+            p50_ms: percentile(50),
+            p90_ms: percentile(90),
+            p99_ms: percentile(99),
+            max_ms: latencies[latencies.len() - 1],
+            relayed_ratio: relayed as f64 / latencies.len() as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_propagation_percentiles() {
+        let meta = AnnouncementMeta::at(1_234_567);
+        assert_eq!(meta.sent_at_ms, 1_234_560);
+        assert_eq!(meta.latency_ms(1_234_800), 240);
+        assert_eq!(meta.latency_ms(1_000_000), 0);
+
+        let mut tracker = PropagationTracker::new();
+        assert_eq!(tracker.stats().samples, 0);
+        for latency in 1..=100 {
+            tracker.record_latency(latency, latency > 75);
+        }
+
+        let stats = tracker.stats();
+        assert_eq!((stats.p50_ms, stats.p90_ms, stats.p99_ms), (50, 90, 99));
+        assert_eq!(stats.max_ms, 100);
+        assert_eq!(stats.relayed_ratio, 0.25);
+
+        for _ in 0..PROPAGATION_WINDOW {
+            tracker.record_latency(5, false);
+        }
+        assert_eq!(tracker.stats().max_ms, 5);
+        assert_eq!(tracker.stats().total, 100 + PROPAGATION_WINDOW as u64);
+    }
+}
@@ -3,8 +3,8 @@ use spirachain_core::{Block, BlockHeader, Transaction};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NetworkMessage {
-    NewBlock(Block),
-    NewTransaction(Transaction),
+    NewBlock(Box<Block>),
+    NewTransaction(Box<Transaction>),
     BlockRequest {
         start_height: u64,
         count: u64,
@@ -44,11 +44,11 @@ pub enum NetworkMessage {
 
 impl NetworkMessage {
     pub fn new_block(block: Block) -> Self {
-        NetworkMessage::NewBlock(block)
+        NetworkMessage::NewBlock(Box::new(block))
     }
 
     pub fn new_transaction(tx: Transaction) -> Self {
-        NetworkMessage::NewTransaction(tx)
+        NetworkMessage::NewTransaction(Box::new(tx))
     }
 
     pub fn block_request(start_height: u64, count: u64) -> Self {
@@ -0,0 +1,153 @@
+// Capability provider records
+// Nodes publish Kademlia provider records for what they can serve, so a
+// syncing node asks the DHT who holds the blocks it is missing instead of
+// trying whichever peers gossip happened to connect it to.
+// Keys: /spirachain/<network id>/providers/archive
+//       /spirachain/<network id>/providers/snapshot/<height>
+
+use libp2p::{kad, PeerId};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Snapshot records are published per bucket of this many blocks, so a node
+/// re-announces a few times a week rather than every block
+pub const SNAPSHOT_PROVIDER_INTERVAL: u64 = 10_000;
+
+/// How long a provider lookup is trusted before asking the DHT again
+pub const PROVIDER_LOOKUP_INTERVAL: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderService {
+    /// Every block since genesis
+    Archive,
+    /// Blocks and state up to `height`, a multiple of [`SNAPSHOT_PROVIDER_INTERVAL`]
+    Snapshot { height: u64 },
+}
+
+impl ProviderService {
+    /// Snapshot bucket a node at `height` can serve
+    pub fn snapshot_at(height: u64) -> Self {
+        ProviderService::Snapshot {
+            height: height - height % SNAPSHOT_PROVIDER_INTERVAL,
+        }
+    }
+
+    /// DHT key of the service on the chain named `network_id`
+    pub fn key(&self, network_id: &str) -> kad::RecordKey {
+        kad::RecordKey::new(&format!("/spirachain/{}/providers/{}", network_id, self))
+    }
+
+    /// Inverse of [`Self::key`]; `None` for keys of other chains or services
+    pub fn from_key(network_id: &str, key: &kad::RecordKey) -> Option<Self> {
+        let key = std::str::from_utf8(key.as_ref()).ok()?;
+        let service = key
+            .strip_prefix("/spirachain/")?
+            .strip_prefix(network_id)?
+            .strip_prefix("/providers/")?;
+        match service.split_once('/') {
+            None if service == "archive" => Some(ProviderService::Archive),
+            Some(("snapshot", height)) => Some(ProviderService::Snapshot {
+                height: height.parse().ok()?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether a provider of this service can serve the block at `height`
+    pub fn serves(&self, height: u64) -> bool {
+        match self {
+            ProviderService::Archive => true,
+            ProviderService::Snapshot { height: up_to } => height <= *up_to,
+        }
+    }
+}
+
+impl fmt::Display for ProviderService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderService::Archive => f.write_str("archive"),
+            ProviderService::Snapshot { height } => write!(f, "snapshot/{}", height),
+        }
+    }
+}
+
+/// Providers found through the DHT, per service
+#[derive(Debug, Default)]
+pub struct ProviderDirectory {
+    providers: HashMap<ProviderService, HashSet<PeerId>>,
+    last_lookup: HashMap<ProviderService, Instant>,
+}
+
+impl ProviderDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, service: ProviderService, peers: impl IntoIterator<Item = PeerId>) {
+        self.providers.entry(service).or_default().extend(peers);
+    }
+
+    /// Whether `service` is due for another DHT lookup; marks it looked up
+    pub fn start_lookup(&mut self, service: ProviderService) -> bool {
+        let due = self
+            .last_lookup
+            .get(&service)
+            .is_none_or(|at| at.elapsed() >= PROVIDER_LOOKUP_INTERVAL);
+        if due {
+            self.last_lookup.insert(service, Instant::now());
+        }
+        due
+    }
+
+    /// Known providers that can serve the block at `height`
+    pub fn providers_for(&self, height: u64) -> HashSet<PeerId> {
+        self.providers
+            .iter()
+            .filter(|(service, _)| service.serves(height))
+            .flat_map(|(_, peers)| peers.iter().copied())
+            .collect()
+    }
+
+    pub fn remove_peer(&mut self, peer: &PeerId) {
+        for peers in self.providers.values_mut() {
+            peers.remove(peer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_keys_and_directory() {
+        let snapshot = ProviderService::snapshot_at(25_123);
+        assert_eq!(snapshot, ProviderService::Snapshot { height: 20_000 });
+        assert_eq!(
+            ProviderService::from_key("testnet-ab", &snapshot.key("testnet-ab")),
+            Some(snapshot)
+        );
+        assert_eq!(
+            ProviderService::from_key("testnet-ab", &ProviderService::Archive.key("testnet-ab")),
+            Some(ProviderService::Archive)
+        );
+        assert_eq!(
+            ProviderService::from_key("mainnet-cd", &snapshot.key("testnet-ab")),
+            None
+        );
+
+        let archive = PeerId::random();
+        let recent = PeerId::random();
+        let mut directory = ProviderDirectory::new();
+        directory.record(ProviderService::Archive, [archive]);
+        directory.record(snapshot, [recent]);
+        assert_eq!(directory.providers_for(20_000).len(), 2);
+        assert_eq!(directory.providers_for(20_001), HashSet::from([archive]));
+
+        assert!(directory.start_lookup(snapshot));
+        assert!(!directory.start_lookup(snapshot));
+        directory.remove_peer(&archive);
+        assert!(directory.providers_for(20_001).is_empty());
+    }
+}
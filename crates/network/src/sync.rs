@@ -1,7 +1,11 @@
 use spirachain_core::{Block, Result};
 use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 use tracing::{debug, info};
 
+/// Height samples the blocks/s rate is averaged over
+const SYNC_RATE_SAMPLES: usize = 12;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyncState {
     Idle,
@@ -161,3 +165,147 @@ impl Default for SyncManager {
         Self::new()
     }
 }
+
+/// Catch-up progress against the best peer: heights, rate and ETA, as logged
+/// during startup and served over RPC
+#[derive(Debug, Clone, Default)]
+pub struct SyncStats {
+    current_height: u64,
+    target_height: u64,
+    peers: usize,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl SyncStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record our height, the highest height a peer announced and the peer count
+    pub fn update(&mut self, current_height: u64, target_height: u64, peers: usize) {
+        self.update_at(Instant::now(), current_height, target_height, peers);
+    }
+
+    pub fn update_at(
+        &mut self,
+        now: Instant,
+        current_height: u64,
+        target_height: u64,
+        peers: usize,
+    ) {
+        // A reorg moves the height back; the old samples no longer describe a rate
+        if current_height < self.current_height {
+            self.samples.clear();
+        }
+
+        self.current_height = current_height;
+        self.target_height = target_height.max(current_height);
+        self.peers = peers;
+
+        self.samples.push_back((now, current_height));
+        while self.samples.len() > SYNC_RATE_SAMPLES {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn current_height(&self) -> u64 {
+        self.current_height
+    }
+
+    pub fn target_height(&self) -> u64 {
+        self.target_height
+    }
+
+    pub fn peers(&self) -> usize {
+        self.peers
+    }
+
+    pub fn is_syncing(&self) -> bool {
+        self.target_height > self.current_height
+    }
+
+    pub fn progress_percent(&self) -> f64 {
+        if self.target_height == 0 {
+            return 100.0;
+        }
+        self.current_height as f64 * 100.0 / self.target_height as f64
+    }
+
+    pub fn blocks_per_second(&self) -> f64 {
+        let (Some((first_at, first)), Some((last_at, last))) =
+            (self.samples.front(), self.samples.back())
+        else {
+            return 0.0;
+        };
+
+        let elapsed = last_at.duration_since(*first_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        (last - first) as f64 / elapsed
+    }
+
+    /// Time left at the current rate; `None` while no blocks are arriving
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.target_height - self.current_height;
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+
+        let rate = self.blocks_per_second();
+        (rate > 0.0).then(|| Duration::from_secs_f64(remaining as f64 / rate))
+    }
+
+    /// One-line summary for the startup log
+    pub fn status_line(&self) -> String {
+        let eta = self
+            .eta()
+            .map(format_eta)
+            .unwrap_or_else(|| "--".to_string());
+        format!(
+            "Syncing {}/{} ({:.1}%) | ETA {} | {} peers | {:.1} blocks/s",
+            self.current_height,
+            self.target_height,
+            self.progress_percent(),
+            eta,
+            self.peers,
+            self.blocks_per_second()
+        )
+    }
+}
+
+fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_stats_rate_and_eta() {
+        let start = Instant::now();
+        let mut stats = SyncStats::new();
+        stats.update_at(start, 100, 1100, 3);
+        assert_eq!(stats.eta(), None);
+
+        stats.update_at(start + Duration::from_secs(10), 200, 1100, 3);
+        assert!(stats.is_syncing());
+        assert_eq!(stats.blocks_per_second(), 10.0);
+        assert_eq!(stats.eta(), Some(Duration::from_secs(90)));
+        assert_eq!(
+            stats.status_line(),
+            "Syncing 200/1100 (18.2%) | ETA 1m30s | 3 peers | 10.0 blocks/s"
+        );
+
+        stats.update_at(start + Duration::from_secs(20), 1100, 1100, 3);
+        assert!(!stats.is_syncing());
+        assert_eq!(stats.progress_percent(), 100.0);
+        assert_eq!(stats.eta(), Some(Duration::ZERO));
+    }
+}
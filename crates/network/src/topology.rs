@@ -0,0 +1,146 @@
+// Network topology snapshots
+// A node's local view of its peers and gossip mesh, signed with the node key
+// so it can be attached to fork/partition bug reports.
+
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Address, Result, SpiraChainError};
+use spirachain_crypto::{KeyPair, PublicKey};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerView {
+    pub peer_id: String,
+    pub height: Option<u64>,
+    /// Average ping round-trip time
+    pub latency_ms: Option<u64>,
+    /// Most recent round-trip time
+    #[serde(default)]
+    pub last_latency_ms: Option<u64>,
+    /// Pings missed in a row
+    #[serde(default)]
+    pub missed_pings: u32,
+    /// identify agent version, once the peer has sent it
+    pub agent: Option<String>,
+    /// Node role from the agent string (validator, full, light, archive)
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Capabilities from the agent string, e.g. `sync/1`
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Highest protocol version the peer supports
+    pub protocol_version: Option<u32>,
+    /// Validator address the peer announced on the sync topic
+    pub validator: Option<Address>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopologySnapshot {
+    /// Unix seconds
+    pub taken_at: u64,
+    pub network: String,
+    pub local_peer_id: String,
+    pub local_height: u64,
+    pub peers: Vec<PeerView>,
+    /// Gossip topic -> peers in our mesh for that topic
+    pub mesh: BTreeMap<String, Vec<String>>,
+}
+
+impl TopologySnapshot {
+    fn signing_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| SpiraChainError::SerializationError(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTopologySnapshot {
+    pub snapshot: TopologySnapshot,
+    pub signer: Address,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl SignedTopologySnapshot {
+    pub fn sign(snapshot: TopologySnapshot, keypair: &KeyPair) -> Result<Self> {
+        let signature = keypair.sign(&snapshot.signing_bytes()?);
+
+        Ok(Self {
+            snapshot,
+            signer: keypair.to_address(),
+            public_key: hex::encode(keypair.public_key().as_bytes()),
+            signature: hex::encode(signature),
+        })
+    }
+
+    /// True if the signature is valid and the public key matches `signer`
+    pub fn verify(&self) -> bool {
+        let Some(public_key) = hex::decode(&self.public_key)
+            .ok()
+            .and_then(|bytes| PublicKey::from_bytes(&bytes).ok())
+        else {
+            return false;
+        };
+        let Ok(signature) = hex::decode(&self.signature) else {
+            return false;
+        };
+        let Ok(message) = self.snapshot.signing_bytes() else {
+            return false;
+        };
+
+        public_key.to_address() == self.signer
+            && PublicKey::verify(&public_key, &message, &signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_snapshot() -> TopologySnapshot {
+        let mut mesh = BTreeMap::new();
+        mesh.insert("spirachain-blocks".to_string(), vec!["peer-a".to_string()]);
+
+        TopologySnapshot {
+            taken_at: 1_700_000_000,
+            network: "testnet".to_string(),
+            local_peer_id: "local".to_string(),
+            local_height: 42,
+            peers: vec![PeerView {
+                peer_id: "peer-a".to_string(),
+                height: Some(41),
+                latency_ms: Some(120),
+                last_latency_ms: Some(110),
+                missed_pings: 0,
+                agent: Some("spirachain/0.1.0".to_string()),
+                role: Some("full".to_string()),
+                capabilities: vec!["sync/1".to_string()],
+                protocol_version: Some(1),
+                validator: Some(Address::new([3u8; 32])),
+            }],
+            mesh,
+        }
+    }
+
+    #[test]
+    fn test_signed_snapshot_roundtrip() {
+        let keypair = KeyPair::generate();
+        let signed = SignedTopologySnapshot::sign(sample_snapshot(), &keypair).unwrap();
+        assert!(signed.verify());
+
+        let json = serde_json::to_string(&signed).unwrap();
+        let decoded: SignedTopologySnapshot = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify());
+        assert_eq!(decoded.snapshot, signed.snapshot);
+    }
+
+    #[test]
+    fn test_tampered_snapshot_fails_verification() {
+        let keypair = KeyPair::generate();
+        let mut signed = SignedTopologySnapshot::sign(sample_snapshot(), &keypair).unwrap();
+        signed.snapshot.peers[0].height = Some(1_000);
+        assert!(!signed.verify());
+
+        let mut signed = SignedTopologySnapshot::sign(sample_snapshot(), &keypair).unwrap();
+        signed.signer = Address::new([7u8; 32]);
+        assert!(!signed.verify());
+    }
+}
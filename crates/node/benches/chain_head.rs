@@ -0,0 +1,59 @@
+// Chain head reads
+// A validator reads the tip several times per slot: the production path, the
+// competing block check and every received block. With the head cache those
+// reads no longer touch the database; the `database` rows read the same blocks
+// through the height index, the way every tip read went before the cache.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use spirachain_core::{ChainBuilder, TxPattern};
+use spirachain_node::BlockStorage;
+
+const CHAIN_LENGTH: u64 = 200;
+const TRANSFERS_PER_BLOCK: usize = 50;
+
+fn bench_chain_head(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("spirachain-bench-head-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let storage = BlockStorage::new(&dir).unwrap();
+    let chain = ChainBuilder::new("testnet")
+        .with_tx_pattern(TxPattern::Transfers(TRANSFERS_PER_BLOCK))
+        .build(CHAIN_LENGTH);
+    for block in &chain.blocks {
+        storage.store_block(block).unwrap();
+    }
+    let tip = chain.tip().header.block_height;
+
+    let mut group = c.benchmark_group("chain_head");
+    group.bench_function("latest_block/cached", |b| {
+        b.iter(|| black_box(storage.get_latest_block().unwrap()))
+    });
+    group.bench_function("latest_block/database", |b| {
+        b.iter(|| black_box(storage.get_block_by_height(tip).unwrap()))
+    });
+
+    // The reads of one slot: production, the competing block check, the
+    // height, and the parent of a received block
+    group.bench_function("validator_loop/cached", |b| {
+        b.iter(|| {
+            black_box(storage.get_latest_block().unwrap());
+            black_box(storage.get_latest_block().unwrap());
+            black_box(storage.get_chain_height().unwrap());
+            black_box(storage.get_header_by_height(tip - 1).unwrap());
+        })
+    });
+    group.bench_function("validator_loop/database", |b| {
+        b.iter(|| {
+            black_box(storage.get_block_by_height(tip).unwrap());
+            black_box(storage.get_block_by_height(tip).unwrap());
+            black_box(storage.get_block_by_height(tip).unwrap());
+            black_box(storage.get_block_by_height(tip - 1).unwrap());
+        })
+    });
+    group.finish();
+
+    drop(storage);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+criterion_group!(benches, bench_chain_head);
+criterion_main!(benches);
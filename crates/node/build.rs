@@ -0,0 +1,35 @@
+//! Embeds the git commit and build timestamp so a running binary can be traced
+//! back to its exact source. Both can be pinned from the environment, and the
+//! timestamp defaults to the commit time, so rebuilding a commit is reproducible.
+
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=SPIRACHAIN_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs/heads");
+
+    let commit = std::env::var("SPIRACHAIN_GIT_COMMIT")
+        .ok()
+        .or_else(|| git(&["rev-parse", "HEAD"]))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .or_else(|| git(&["log", "-1", "--format=%ct"]))
+        .and_then(|secs| secs.parse::<u64>().ok())
+        .unwrap_or(0);
+
+    println!("cargo:rustc-env=SPIRACHAIN_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=SPIRACHAIN_BUILD_TIMESTAMP={}", timestamp);
+}
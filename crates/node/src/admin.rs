@@ -0,0 +1,61 @@
+use crate::{backup_database, BlockStorage, RuntimeConfigManager};
+use parking_lot::RwLock;
+use spirachain_core::{Result, SpiraChainError};
+use spirachain_crypto::KeyPair;
+use spirachain_network::{SignedTopologySnapshot, TopologySnapshot};
+use spirachain_rpc::server::AdminHandler;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Latest topology view, refreshed from the network loop
+pub type SharedTopology = Arc<RwLock<Option<TopologySnapshot>>>;
+
+/// Backs the loopback-only `/admin/*` RPC endpoints
+pub struct NodeAdmin {
+    runtime: Arc<RuntimeConfigManager>,
+    topology: SharedTopology,
+    keypair: KeyPair,
+    storage: Arc<BlockStorage>,
+    network: String,
+}
+
+impl NodeAdmin {
+    pub fn new(
+        runtime: Arc<RuntimeConfigManager>,
+        topology: SharedTopology,
+        keypair: KeyPair,
+        storage: Arc<BlockStorage>,
+        network: String,
+    ) -> Self {
+        Self {
+            runtime,
+            topology,
+            keypair,
+            storage,
+            network,
+        }
+    }
+}
+
+impl AdminHandler for NodeAdmin {
+    fn reload_config(&self) -> Result<Vec<String>> {
+        self.runtime.reload()
+    }
+
+    fn network_graph(&self) -> Result<serde_json::Value> {
+        let snapshot =
+            self.topology.read().clone().ok_or_else(|| {
+                SpiraChainError::NetworkError("P2P network not running".to_string())
+            })?;
+        let signed = SignedTopologySnapshot::sign(snapshot, &self.keypair)?;
+
+        serde_json::to_value(&signed)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))
+    }
+
+    fn backup_database(&self, output: &Path) -> Result<serde_json::Value> {
+        let manifest = backup_database(&self.storage, &self.network, output)?;
+        serde_json::to_value(&manifest)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))
+    }
+}
@@ -0,0 +1,186 @@
+use crate::BlockStorage;
+use spirachain_core::{
+    diversity_epoch, semantic_score, Block, EntityType, Hash, Result, DIVERSITY_EPOCH_BLOCKS,
+    SEMANTIC_SCORE_MAX,
+};
+use spirachain_rpc::{EntityCountResponse, EpochSemanticsResponse};
+use std::collections::{BTreeMap, HashMap};
+
+/// Entities listed per epoch
+pub const TOP_ENTITIES: usize = 20;
+
+/// Running semantic aggregate of one epoch's transactions
+#[derive(Debug, Default)]
+pub struct EpochSemantics {
+    epoch: u64,
+    last_height: Option<u64>,
+    transactions: u64,
+    intents: BTreeMap<String, u64>,
+    unclassified: u64,
+    entities: HashMap<(String, EntityType), u64>,
+    coherence_sum: f64,
+    with_vector: u64,
+}
+
+impl EpochSemantics {
+    pub fn new(epoch: u64) -> Self {
+        Self {
+            epoch,
+            ..Self::default()
+        }
+    }
+
+    /// `vector` finds the semantic vectors blocks leave out, see
+    /// [`BlockStorage::get_semantic_vector`]
+    pub fn add_block(&mut self, block: &Block, vector: impl Fn(&Hash) -> Option<Vec<f32>>) {
+        self.last_height = Some(block.header.block_height);
+
+        for tx in block.transactions.iter().filter(|tx| !tx.is_protocol()) {
+            self.transactions += 1;
+
+            match &tx.intent {
+                Some(intent) => {
+                    *self
+                        .intents
+                        .entry(format!("{:?}", intent.intent_type))
+                        .or_default() += 1
+                }
+                None => self.unclassified += 1,
+            }
+
+            for entity in &tx.entities {
+                *self
+                    .entities
+                    .entry((entity.name.clone(), entity.entity_type))
+                    .or_default() += 1;
+            }
+
+            // Coherence is only meaningful for transactions that carried a
+            // vector. Legacy ones were never scored on-chain; their vector
+            // may still be in the side store.
+            let score = match tx.semantic_score {
+                0 => vector(&tx.tx_hash).map(|vector| semantic_score(&vector)),
+                score => Some(score),
+            };
+            if let Some(score) = score {
+                self.coherence_sum += f64::from(score) / f64::from(SEMANTIC_SCORE_MAX);
+                self.with_vector += 1;
+            }
+        }
+    }
+
+    pub fn finish(self, complete: bool) -> EpochSemanticsResponse {
+        let first_height = self.epoch * DIVERSITY_EPOCH_BLOCKS;
+
+        let mut top_entities: Vec<EntityCountResponse> = self
+            .entities
+            .into_iter()
+            .map(|((name, entity_type), count)| EntityCountResponse {
+                name,
+                entity_type,
+                count,
+            })
+            .collect();
+        top_entities.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        top_entities.truncate(TOP_ENTITIES);
+
+        EpochSemanticsResponse {
+            epoch: self.epoch,
+            first_height,
+            last_height: self.last_height.unwrap_or(first_height),
+            complete,
+            transactions: self.transactions,
+            intents: self.intents,
+            unclassified: self.unclassified,
+            top_entities,
+            average_coherence: if self.with_vector == 0 {
+                0.0
+            } else {
+                self.coherence_sum / self.with_vector as f64
+            },
+        }
+    }
+}
+
+/// Aggregate every epoch from the first one without a complete aggregate up to
+/// `height`, storing each. The current epoch is rebuilt on every run until it
+/// completes. Returns the number of epochs written.
+pub fn update_epoch_semantics(storage: &BlockStorage, height: u64) -> Result<usize> {
+    let mut epoch = match storage.latest_epoch_semantics()? {
+        Some(latest) if latest.complete => latest.epoch + 1,
+        Some(latest) => latest.epoch,
+        None => 0,
+    };
+
+    let mut written = 0;
+    while epoch <= diversity_epoch(height) {
+        let first = epoch * DIVERSITY_EPOCH_BLOCKS;
+        let epoch_last = first + DIVERSITY_EPOCH_BLOCKS - 1;
+
+        let mut semantics = EpochSemantics::new(epoch);
+        for block_height in first..=epoch_last.min(height) {
+            // Pruned blocks are skipped
+            if let Some(block) = storage.get_block_by_height(block_height)? {
+                semantics.add_block(&block, |tx_hash| {
+                    storage.get_semantic_vector(tx_hash).ok().flatten()
+                });
+            }
+        }
+
+        storage.store_epoch_semantics(&semantics.finish(height >= epoch_last))?;
+        written += 1;
+        epoch += 1;
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Address, Amount, Entity, Intent, IntentType, Transaction};
+
+    #[test]
+    fn test_epoch_semantics_aggregation() {
+        let mut block = Block::new(Hash::zero(), 5);
+        let tagged = |name: &str| {
+            let mut tx = Transaction::new(
+                Address::new([1u8; 32]),
+                Address::new([2u8; 32]),
+                Amount::new(1),
+                Amount::zero(),
+            )
+            .with_intent(Intent {
+                intent_type: IntentType::Social,
+                confidence: 0.9,
+            })
+            .with_entities(vec![Entity {
+                name: name.to_string(),
+                entity_type: EntityType::Organization,
+                confidence: 0.8,
+            }]);
+            tx.compute_hash();
+            tx
+        };
+        block.transactions = vec![
+            Transaction::new_coinbase(Address::zero(), Amount::new(1), 5),
+            tagged("SpiraPi"),
+            tagged("SpiraPi"),
+            tagged("Qbitum"),
+        ];
+        let first = block.transactions[1].tx_hash;
+
+        let mut semantics = EpochSemantics::new(0);
+        semantics.add_block(&block, |tx_hash| {
+            (*tx_hash == first).then(|| vec![0.6, 0.0])
+        });
+        let stats = semantics.finish(false);
+
+        assert_eq!(stats.transactions, 3);
+        assert_eq!(stats.intents.get("Social"), Some(&3));
+        assert_eq!(stats.last_height, 5);
+        assert_eq!(stats.top_entities[0].name, "SpiraPi");
+        assert_eq!(stats.top_entities[0].count, 2);
+        assert!((stats.average_coherence - 0.6).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,242 @@
+// At-rest encryption and privacy mode
+// With encryption on, the persisted mempool and the semantic side-stores
+// (vectors, epoch analytics) are sealed with AES-256-GCM under a key that
+// never leaves the data dir. Privacy mode goes further and never writes
+// semantic fields: no vectors, no entity rankings, and pending transactions
+// carrying a purpose or vector stay in memory only.
+// Sealed: SEALED_PREFIX ‖ nonce (12) ‖ ciphertext
+
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use spirachain_core::{Result, SpiraChainError, Transaction};
+use spirachain_network::write_private_file;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Node-local at-rest key, kept in the node data directory
+pub const AT_REST_KEY_FILE: &str = "at_rest.key";
+
+/// Pending transactions saved on shutdown and re-admitted on the next start
+pub const MEMPOOL_FILE: &str = "mempool.dat";
+
+/// Marks sealed values. Read as the length prefix of a bincode value it is
+/// far beyond anything stored, so plaintext written before encryption was
+/// switched on stays readable.
+const SEALED_PREFIX: &[u8] = b"SPSEALv1";
+
+const NONCE_SIZE: usize = 12;
+
+#[derive(Clone)]
+pub struct AtRestCipher {
+    key: [u8; 32],
+}
+
+impl fmt::Debug for AtRestCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AtRestCipher(..)")
+    }
+}
+
+impl AtRestCipher {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self { key }
+    }
+
+    /// Load the key from `data_dir`, creating it on first use. Losing it makes
+    /// sealed data unreadable; nothing sealed is consensus data, so the node
+    /// only loses its pending transactions and semantic side-stores.
+    pub fn load_or_create(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(AT_REST_KEY_FILE);
+        if path.exists() {
+            let bytes = std::fs::read(&path)
+                .map_err(|e| SpiraChainError::io(format!("reading {}", path.display()), e))?;
+            let key: [u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+                SpiraChainError::CryptoError(format!(
+                    "{} must hold 32 bytes, found {}",
+                    path.display(),
+                    bytes.len()
+                ))
+            })?;
+            return Ok(Self::new(key));
+        }
+
+        let key = rand::random::<[u8; 32]>();
+        write_private_file(&path, &key)?;
+        info!("🔐 Generated at-rest encryption key at {}", path.display());
+        Ok(Self::new(key))
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce_bytes = rand::random::<[u8; NONCE_SIZE]>();
+        let ciphertext = Aes256Gcm::new(&self.key.into())
+            .encrypt(&Nonce::from(nonce_bytes), plaintext)
+            .map_err(|e| SpiraChainError::CryptoError(format!("At-rest seal failed: {}", e)))?;
+
+        let mut sealed = Vec::with_capacity(SEALED_PREFIX.len() + NONCE_SIZE + ciphertext.len());
+        sealed.extend_from_slice(SEALED_PREFIX);
+        sealed.extend_from_slice(&nonce_bytes);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Inverse of [`Self::seal`]; fails on data sealed under another key or tampered with
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        let body = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .filter(|body| body.len() >= NONCE_SIZE)
+            .ok_or_else(|| SpiraChainError::CryptoError("Not a sealed value".to_string()))?;
+        let (nonce, ciphertext) = body.split_at(NONCE_SIZE);
+        let nonce: [u8; NONCE_SIZE] = nonce.try_into().expect("split at nonce size");
+
+        Aes256Gcm::new(&self.key.into())
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| {
+                SpiraChainError::CryptoError(
+                    "At-rest data does not open with this node's key".to_string(),
+                )
+            })
+    }
+}
+
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(SEALED_PREFIX)
+}
+
+/// What the node may write to disk besides the chain itself
+#[derive(Debug, Clone, Default)]
+pub struct AtRestPolicy {
+    /// Seals the mempool file and semantic side-stores when set
+    pub cipher: Option<AtRestCipher>,
+    /// Never persist semantic fields
+    pub privacy_mode: bool,
+}
+
+impl AtRestPolicy {
+    /// Policy for the node `config` describes, creating the key if needed
+    pub fn for_config(config: &crate::NodeConfig) -> Result<Self> {
+        let cipher = if config.encrypt_at_rest {
+            Some(AtRestCipher::load_or_create(&config.data_dir)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            cipher,
+            privacy_mode: config.privacy_mode,
+        })
+    }
+
+    /// `bytes` as they go to disk
+    pub fn seal(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal(&bytes),
+            None => Ok(bytes),
+        }
+    }
+
+    /// Bytes read from disk, opened if sealed; plaintext passes through
+    pub fn open(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        if !is_sealed(bytes) {
+            return Ok(bytes.to_vec());
+        }
+        match &self.cipher {
+            Some(cipher) => cipher.open(bytes),
+            None => Err(SpiraChainError::Config(
+                "Found data sealed at rest; start the node with --encrypt-at-rest".to_string(),
+            )),
+        }
+    }
+
+    /// `tx` as it may be written to disk, or `None` when privacy mode keeps
+    /// it in memory. Purpose and vector are covered by the hash and signature,
+    /// so a transaction carrying them cannot be stripped; entities and intent
+    /// are the node's own enrichment and are recomputed on re-admission.
+    pub fn persistable(&self, tx: &Transaction) -> Option<Transaction> {
+        if !self.privacy_mode {
+            return Some(tx.clone());
+        }
+        if !tx.purpose.is_empty() || !tx.semantic_vector.is_empty() {
+            return None;
+        }
+        let mut tx = tx.clone();
+        tx.entities.clear();
+        tx.intent = None;
+        Some(tx)
+    }
+}
+
+pub fn mempool_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(MEMPOOL_FILE)
+}
+
+/// Write the pending transactions `policy` allows; returns how many were written
+pub fn save_mempool(data_dir: &Path, policy: &AtRestPolicy, txs: &[Transaction]) -> Result<usize> {
+    let kept: Vec<Transaction> = txs.iter().filter_map(|tx| policy.persistable(tx)).collect();
+    let bytes = bincode::serialize(&kept).map_err(|e| {
+        SpiraChainError::SerializationError(format!("Failed to serialize mempool: {}", e))
+    })?;
+    write_private_file(&mempool_path(data_dir), &policy.seal(bytes)?)?;
+    Ok(kept.len())
+}
+
+/// Transactions saved by the last shutdown. The file is removed once read so
+/// a crash never re-admits an older mempool.
+pub fn load_mempool(data_dir: &Path, policy: &AtRestPolicy) -> Result<Vec<Transaction>> {
+    let path = mempool_path(data_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let bytes = std::fs::read(&path)
+        .map_err(|e| SpiraChainError::io(format!("reading {}", path.display()), e))?;
+    std::fs::remove_file(&path)
+        .map_err(|e| SpiraChainError::io(format!("removing {}", path.display()), e))?;
+    bincode::deserialize(&policy.open(&bytes)?).map_err(|e| {
+        SpiraChainError::SerializationError(format!("Failed to deserialize mempool: {}", e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Address, Amount};
+
+    #[test]
+    fn test_sealed_mempool_and_privacy_mode() {
+        let dir = std::env::temp_dir().join(format!("spirachain-at-rest-{}", std::process::id()));
+        let cipher = AtRestCipher::load_or_create(&dir).unwrap();
+        assert_eq!(AtRestCipher::load_or_create(&dir).unwrap().key, cipher.key);
+
+        let sealed = cipher.seal(b"pending purpose").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(cipher.open(&sealed).unwrap(), b"pending purpose");
+        assert!(AtRestCipher::new([9u8; 32]).open(&sealed).is_err());
+
+        let plain = Transaction::new(
+            Address::new([1u8; 32]),
+            Address::new([2u8; 32]),
+            Amount::new(10),
+            Amount::new(1),
+        );
+        let with_purpose = plain.clone().with_purpose("salary for alice");
+
+        let policy = AtRestPolicy {
+            cipher: Some(cipher),
+            privacy_mode: true,
+        };
+        assert_eq!(
+            save_mempool(&dir, &policy, &[plain.clone(), with_purpose]).unwrap(),
+            1
+        );
+        let on_disk = std::fs::read(mempool_path(&dir)).unwrap();
+        assert!(is_sealed(&on_disk));
+        assert!(AtRestPolicy::default().open(&on_disk).is_err());
+
+        let loaded = load_mempool(&dir, &policy).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].tx_hash, plain.tx_hash);
+        assert!(!mempool_path(&dir).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
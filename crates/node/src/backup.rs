@@ -0,0 +1,274 @@
+// Database backup and restore
+// sled keeps the database locked while the node runs, so `spira db backup`
+// asks the node, over the loopback admin RPC, to copy it. A block is stored
+// after the accounts it produced, and trees are copied one after the other,
+// so a copy taken during a commit can pair the new accounts with the old
+// tip. Every copy is therefore checked the way a restore checks it - the
+// stored accounts must hash to the tip's state root - and taken again when
+// it does not. `spira db restore` runs the same check on a backup before
+// replacing a stopped node's database with it, and keeps the old one aside.
+
+use crate::{BlockStorage, WorldState};
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Hash, Result, SpiraChainError};
+use std::path::{Path, PathBuf};
+
+/// Describes the backup, next to the copied database
+pub const BACKUP_MANIFEST_FILE: &str = "backup.json";
+
+const BACKUP_DB_DIR: &str = "db";
+
+/// Copies taken before giving up on one that matches its tip
+const BACKUP_ATTEMPTS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub network: String,
+    pub schema_version: u32,
+    pub height: u64,
+    pub block_hash: String,
+    pub state_root: String,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestoreReport {
+    pub manifest: BackupManifest,
+    /// Copy of the database the backup replaced
+    pub previous_dir: PathBuf,
+}
+
+/// Copy `storage` into the new directory `output` and write its manifest;
+/// nothing is left behind when no consistent copy could be taken
+pub fn backup_database(
+    storage: &BlockStorage,
+    network: &str,
+    output: &Path,
+) -> Result<BackupManifest> {
+    if output.exists() {
+        return Err(SpiraChainError::Config(format!(
+            "{} already exists; back up into a new directory",
+            output.display()
+        )));
+    }
+    std::fs::create_dir_all(output)
+        .map_err(|e| SpiraChainError::io(format!("creating {}", output.display()), e))?;
+
+    let db_dir = output.join(BACKUP_DB_DIR);
+    let mut last_error = None;
+    for attempt in 1..=BACKUP_ATTEMPTS {
+        if db_dir.exists() {
+            std::fs::remove_dir_all(&db_dir)
+                .map_err(|e| SpiraChainError::io(format!("removing {}", db_dir.display()), e))?;
+        }
+        storage.copy_to(&db_dir)?;
+
+        let copy = BlockStorage::new(&db_dir)?;
+        match verify_storage(&copy, network) {
+            Ok(manifest) => {
+                let json = serde_json::to_string_pretty(&manifest)
+                    .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+                std::fs::write(output.join(BACKUP_MANIFEST_FILE), json).map_err(|e| {
+                    SpiraChainError::io(format!("writing {}", BACKUP_MANIFEST_FILE), e)
+                })?;
+                tracing::info!(
+                    "💾 Database backed up to {} at height {}",
+                    output.display(),
+                    manifest.height
+                );
+                return Ok(manifest);
+            }
+            Err(e) => {
+                tracing::debug!("Backup copy {} is inconsistent: {}", attempt, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(output);
+    Err(SpiraChainError::StorageError(format!(
+        "No consistent copy after {} attempts: {}",
+        BACKUP_ATTEMPTS,
+        last_error.map(|e| e.to_string()).unwrap_or_default()
+    )))
+}
+
+/// Check the backup in `dir` is for `network` and still matches its manifest
+pub fn verify_backup(dir: &Path, network: &str) -> Result<BackupManifest> {
+    let path = dir.join(BACKUP_MANIFEST_FILE);
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| SpiraChainError::io(format!("reading {}", path.display()), e))?;
+    let manifest: BackupManifest = serde_json::from_str(&json)
+        .map_err(|e| SpiraChainError::Config(format!("Invalid {}: {}", path.display(), e)))?;
+    if manifest.network != network {
+        return Err(SpiraChainError::Config(format!(
+            "Backup is of {}, not {}",
+            manifest.network, network
+        )));
+    }
+
+    let copy = BlockStorage::new(dir.join(BACKUP_DB_DIR))?;
+    let found = verify_storage(&copy, network)?;
+    check_matches(&manifest, &found)?;
+    Ok(manifest)
+}
+
+/// Replace the database in `data_dir` with the backup in `backup_dir`. The
+/// node must be stopped; its database is first copied next to `data_dir`.
+pub fn restore_database(
+    backup_dir: &Path,
+    data_dir: &Path,
+    network: &str,
+) -> Result<RestoreReport> {
+    let manifest = verify_backup(backup_dir, network)?;
+    let backup = BlockStorage::new(backup_dir.join(BACKUP_DB_DIR))?;
+
+    let storage = BlockStorage::new(data_dir).map_err(|e| {
+        SpiraChainError::Config(format!(
+            "Cannot open {} ({}); stop the node before restoring",
+            data_dir.display(),
+            e
+        ))
+    })?;
+
+    let timestamp = unix_time();
+    let dir_name = data_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "data".to_string());
+    let previous_dir = data_dir.with_file_name(format!("{}.pre-restore-{}", dir_name, timestamp));
+    storage.copy_to(&previous_dir)?;
+
+    storage.replace_with(&backup)?;
+    check_matches(&manifest, &verify_storage(&storage, network)?)?;
+
+    Ok(RestoreReport {
+        manifest,
+        previous_dir,
+    })
+}
+
+/// Tip of `storage`, after checking its accounts hash to the tip's state root
+fn verify_storage(storage: &BlockStorage, network: &str) -> Result<BackupManifest> {
+    let tip = storage
+        .get_latest_block()?
+        .ok_or_else(|| SpiraChainError::StorageError("Database holds no blocks".to_string()))?;
+    let height = tip.header.block_height;
+    let indexed = storage
+        .get_block_by_height(height)?
+        .map(|block| block.hash());
+    if indexed != Some(tip.hash()) {
+        return Err(SpiraChainError::StorageError(format!(
+            "Block {} is missing from the height index",
+            height
+        )));
+    }
+
+    // Genesis carries no state root
+    let state_root = stored_state_root(storage, network, height)?;
+    if !tip.header.state_root.is_zero() && state_root != tip.header.state_root {
+        return Err(SpiraChainError::StorageError(format!(
+            "Stored accounts hash to {}, block {} commits to {}",
+            state_root, height, tip.header.state_root
+        )));
+    }
+
+    Ok(BackupManifest {
+        network: network.to_string(),
+        schema_version: storage.schema_version()?.unwrap_or_default(),
+        height,
+        block_hash: tip.hash().to_string(),
+        state_root: tip.header.state_root.to_string(),
+        created_at: unix_time(),
+    })
+}
+
+fn check_matches(manifest: &BackupManifest, found: &BackupManifest) -> Result<()> {
+    if (found.height, &found.block_hash, &found.state_root)
+        != (manifest.height, &manifest.block_hash, &manifest.state_root)
+    {
+        return Err(SpiraChainError::StorageError(format!(
+            "Backup holds block {} ({}), its manifest says block {} ({})",
+            found.height, found.block_hash, manifest.height, manifest.block_hash
+        )));
+    }
+    Ok(())
+}
+
+/// State root of the stored accounts and registries, as computed when the
+/// block at `height` was applied: before the state moved to its height
+pub(crate) fn stored_state_root(storage: &BlockStorage, network: &str, height: u64) -> Result<Hash> {
+    let mut state = WorldState::for_network(network);
+    for (address, account) in storage.get_all_accounts()? {
+        if let Some(code_hash) = account.code_hash {
+            if let Some(code) = storage.get_contract_code(&code_hash)? {
+                state.insert_code(code);
+            }
+        }
+        state.set_account(address, account);
+    }
+    state.set_pause_state(storage.get_pause_state()?);
+    state.set_semantic_models(storage.get_semantic_models()?);
+    state.set_spiral_registry(storage.get_spiral_registry()?);
+    state.set_name_registry(storage.get_name_registry()?);
+    state.set_spiral_diversity(storage.get_spiral_diversity()?);
+    state.set_height(height.saturating_sub(1));
+    Ok(state.calculate_merkle_root())
+}
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Account, Address, Amount, ChainBuilder};
+
+    #[test]
+    fn test_backup_and_restore() {
+        let root = std::env::temp_dir().join(format!("spirachain-backup-{}", std::process::id()));
+        let data_dir = root.join("data");
+        let storage = BlockStorage::new(&data_dir).unwrap();
+        let chain = ChainBuilder::new("testnet").build(1);
+        storage.store_block(chain.genesis()).unwrap();
+
+        let address = Address::new([3u8; 32]);
+        storage
+            .store_account(&address, &Account::with_balance(Amount::qbt(5)))
+            .unwrap();
+        let mut block = chain.tip().clone();
+        block.header.state_root = stored_state_root(&storage, "testnet", 1).unwrap();
+        chain.resign(&mut block);
+        storage.store_block(&block).unwrap();
+
+        let backup_dir = root.join("backup");
+        let manifest = backup_database(&storage, "testnet", &backup_dir).unwrap();
+        assert_eq!(
+            (manifest.height, manifest.block_hash.clone()),
+            (1, block.hash().to_string())
+        );
+        assert!(backup_database(&storage, "testnet", &backup_dir).is_err());
+        assert!(verify_backup(&backup_dir, "mainnet").is_err());
+
+        // Accounts ahead of the tip, as mid-commit, are not a consistent copy
+        storage
+            .store_account(&address, &Account::with_balance(Amount::qbt(6)))
+            .unwrap();
+        assert!(backup_database(&storage, "testnet", &root.join("torn")).is_err());
+        drop(storage);
+
+        let report = restore_database(&backup_dir, &data_dir, "testnet").unwrap();
+        assert_eq!(report.manifest, manifest);
+        let restored = BlockStorage::new(&data_dir).unwrap();
+        assert_eq!(restored.get_balance(&address).unwrap(), Amount::qbt(5));
+        drop(restored);
+        let previous = BlockStorage::new(&report.previous_dir).unwrap();
+        assert_eq!(previous.get_balance(&address).unwrap(), Amount::qbt(6));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,371 @@
+use parking_lot::Mutex as SyncMutex;
+use spirachain_consensus::RewardCalculator;
+use spirachain_core::{Block, ChainParams, Hash, Result, SpiraChainError};
+use spirachain_rpc::server::MetricsSource;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, warn};
+
+/// Blocks waiting for a validation worker; further blocks are dropped and
+/// fetched again through sync once the flood is over
+pub const VALIDATION_QUEUE_SIZE: usize = 64;
+
+/// Upper bound on validation workers, whatever the core count
+const MAX_VALIDATION_WORKERS: usize = 4;
+
+/// Outcome of validating one received block
+#[derive(Debug)]
+pub enum BlockVerdict {
+    Valid(Box<Block>),
+    Invalid {
+        height: u64,
+        block_hash: Hash,
+        reason: String,
+    },
+    /// The block is for a protocol this binary does not implement
+    UpgradeRequired {
+        height: u64,
+        reason: String,
+    },
+}
+
+/// Checks that need nothing but the block itself: header version, structure,
+/// producer and sender signatures, size limits, merkle root, coinbase, fork ids
+/// and schedules. State application stays with the caller.
+pub fn validate_block_stateless(block: &Block, network: &str) -> Result<()> {
+    validate_unsigned_block(block, network)?;
+    if block.header.signature.is_empty() {
+        return Err(SpiraChainError::InvalidSignature);
+    }
+    // Genesis carries no verifiable producer signature
+    if block.header.block_height > 0 {
+        block.verify_signature()?;
+    }
+    Ok(())
+}
+
+/// [`validate_block_stateless`] short of the producer signature, for a
+/// candidate the producer only signs once it is sure to publish it
+pub fn validate_unsigned_block(block: &Block, network: &str) -> Result<()> {
+    let height = block.header.block_height;
+
+    let params = ChainParams::for_network(network);
+    params.check_block_version(height, block.header.version)?;
+    block.validate_unsigned(params)?;
+    block.validate_limits(&params.block_limits)?;
+    RewardCalculator::verify_coinbase(block, params)?;
+
+    block
+        .transactions
+        .iter()
+        .try_for_each(|tx| tx.verify_sender_signature_at(network, height))
+        .map_err(|e| {
+            SpiraChainError::InvalidBlock(format!(
+                "contains a transaction with a bad sender signature: {}",
+                e
+            ))
+        })?;
+
+    block
+        .transactions
+        .iter()
+        .try_for_each(|tx| tx.validate_fork_id(network, height))
+        .map_err(|e| {
+            SpiraChainError::InvalidBlock(format!(
+                "contains a transaction from another fork: {}",
+                e
+            ))
+        })?;
+
+    block
+        .transactions
+        .iter()
+        .try_for_each(|tx| tx.validate_schedule(height))
+        .map_err(|e| {
+            SpiraChainError::InvalidBlock(format!(
+                "contains a transaction outside its schedule: {}",
+                e
+            ))
+        })
+}
+
+/// Run [`validate_block_stateless`] and classify the result
+pub fn validate_received_block(block: Block, network: &str) -> BlockVerdict {
+    let height = block.header.block_height;
+    let block_hash = block.hash();
+    match validate_block_stateless(&block, network) {
+        Ok(()) => BlockVerdict::Valid(Box::new(block)),
+        Err(SpiraChainError::UnsupportedProtocol(reason)) => {
+            BlockVerdict::UpgradeRequired { height, reason }
+        }
+        Err(e) => BlockVerdict::Invalid {
+            height,
+            block_hash,
+            reason: e.to_string(),
+        },
+    }
+}
+
+/// Check of the receiving pipeline a block failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationStage {
+    /// [`validate_block_stateless`]
+    Stateless,
+    Checkpoint,
+    EpochSummary,
+    /// Custom spiral registration
+    Spiral,
+    /// Complexity, coherence, continuity, π identifiers and validator stake
+    Consensus,
+}
+
+impl ValidationStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValidationStage::Stateless => "stateless",
+            ValidationStage::Checkpoint => "checkpoint",
+            ValidationStage::EpochSummary => "epoch_summary",
+            ValidationStage::Spiral => "spiral",
+            ValidationStage::Consensus => "consensus",
+        }
+    }
+}
+
+/// Our own block candidates run through the checks peers apply before we
+/// broadcast them, and how often they failed
+#[derive(Default)]
+pub struct SelfValidationMetrics {
+    checked: AtomicU64,
+    rejected: SyncMutex<BTreeMap<ValidationStage, u64>>,
+    abandoned: AtomicU64,
+    cancelled: AtomicU64,
+}
+
+impl SelfValidationMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_checked(&self) {
+        self.checked.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_rejected(&self, stage: ValidationStage) {
+        *self.rejected.lock().entry(stage).or_default() += 1;
+    }
+
+    /// Every retry failed too and the slot went without our block
+    pub fn record_abandoned(&self) {
+        self.abandoned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Another block for the slot was accepted before ours was signed
+    pub fn record_cancelled(&self) {
+        self.cancelled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rejected(&self) -> BTreeMap<ValidationStage, u64> {
+        self.rejected.lock().clone()
+    }
+}
+
+impl MetricsSource for SelfValidationMetrics {
+    fn export_prometheus(&self) -> String {
+        let mut out = format!(
+            "# HELP spirachain_candidate_checks_total Own block candidates validated before broadcast\n\
+             # TYPE spirachain_candidate_checks_total counter\n\
+             spirachain_candidate_checks_total {}\n\
+             # HELP spirachain_candidate_self_rejections_total Own block candidates that failed a receiver check\n\
+             # TYPE spirachain_candidate_self_rejections_total counter\n",
+            self.checked.load(Ordering::Relaxed)
+        );
+        for (stage, count) in self.rejected() {
+            let _ = writeln!(
+                out,
+                "spirachain_candidate_self_rejections_total{{stage=\"{}\"}} {}",
+                stage.name(),
+                count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP spirachain_candidate_abandoned_total Slots skipped because no candidate passed\n\
+             # TYPE spirachain_candidate_abandoned_total counter\n\
+             spirachain_candidate_abandoned_total {}",
+            self.abandoned.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "# HELP spirachain_candidate_cancelled_total Candidates dropped because another block for the slot was accepted first\n\
+             # TYPE spirachain_candidate_cancelled_total counter\n\
+             spirachain_candidate_cancelled_total {}",
+            self.cancelled.load(Ordering::Relaxed)
+        );
+        out
+    }
+}
+
+/// Validates received blocks off the node's event loop. Results come back on
+/// the receiver returned by [`BlockValidationPool::spawn`], possibly out of
+/// height order when several workers run.
+#[derive(Clone)]
+pub struct BlockValidationPool {
+    jobs: mpsc::Sender<Box<Block>>,
+}
+
+impl BlockValidationPool {
+    pub fn spawn(network: &str) -> (Self, mpsc::Receiver<BlockVerdict>) {
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(MAX_VALIDATION_WORKERS);
+        Self::spawn_with(network, workers, VALIDATION_QUEUE_SIZE)
+    }
+
+    pub fn spawn_with(
+        network: &str,
+        workers: usize,
+        queue_size: usize,
+    ) -> (Self, mpsc::Receiver<BlockVerdict>) {
+        let (jobs, job_rx) = mpsc::channel::<Box<Block>>(queue_size.max(1));
+        let (results, result_rx) = mpsc::channel(queue_size.max(1));
+        let job_rx = Arc::new(Mutex::new(job_rx));
+
+        for worker in 0..workers.max(1) {
+            let job_rx = Arc::clone(&job_rx);
+            let results = results.clone();
+            let network = network.to_string();
+
+            tokio::spawn(async move {
+                loop {
+                    let Some(block) = job_rx.lock().await.recv().await else {
+                        break;
+                    };
+
+                    let network = network.clone();
+                    let verdict = match tokio::task::spawn_blocking(move || {
+                        validate_received_block(*block, &network)
+                    })
+                    .await
+                    {
+                        Ok(verdict) => verdict,
+                        Err(e) => {
+                            warn!("Block validation worker {} panicked: {}", worker, e);
+                            continue;
+                        }
+                    };
+
+                    if results.send(verdict).await.is_err() {
+                        break;
+                    }
+                }
+                debug!("Block validation worker {} stopped", worker);
+            });
+        }
+
+        (Self { jobs }, result_rx)
+    }
+
+    /// Queue a block without waiting; a full queue hands the block back
+    pub fn submit(&self, block: Block) -> std::result::Result<(), Box<Block>> {
+        self.jobs.try_send(Box::new(block)).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(block) | mpsc::error::TrySendError::Closed(block) => {
+                block
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{signature_cache, ChainBuilder, TxPattern};
+
+    #[test]
+    fn test_self_validation_metrics_export() {
+        let metrics = SelfValidationMetrics::new();
+        metrics.record_checked();
+        metrics.record_checked();
+        metrics.record_rejected(ValidationStage::Consensus);
+        metrics.record_abandoned();
+        metrics.record_cancelled();
+
+        let text = metrics.export_prometheus();
+        assert!(text.contains("spirachain_candidate_checks_total 2\n"));
+        assert!(
+            text.contains("spirachain_candidate_self_rejections_total{stage=\"consensus\"} 1\n")
+        );
+        assert!(text.contains("spirachain_candidate_abandoned_total 1\n"));
+        assert!(text.contains("spirachain_candidate_cancelled_total 1\n"));
+    }
+
+    #[test]
+    fn test_sender_signatures_checked_at_admission_are_cache_hits() {
+        let chain = ChainBuilder::new("testnet")
+            .with_validators(1)
+            .with_tx_pattern(TxPattern::Transfers(3))
+            .build(1);
+        let block = chain.block(1);
+        let mempool = crate::Mempool::new(16);
+        for tx in &block.transactions[1..] {
+            mempool.add_transaction_sync(tx.clone()).unwrap();
+        }
+
+        // Other tests share the cache, but can only add hits
+        let before = signature_cache().stats();
+        validate_block_stateless(block, "testnet").unwrap();
+        assert!(signature_cache().stats().hits >= before.hits + 3);
+
+        let mut forged = block.clone();
+        forged.transactions[1].signature = forged.transactions[2].signature.clone();
+        assert!(validate_block_stateless(&forged, "testnet").is_err());
+        assert!(crate::Mempool::new(16)
+            .add_transaction_sync(forged.transactions[1].clone())
+            .is_err());
+    }
+
+    #[test]
+    fn test_unsigned_candidate_passes_all_but_the_signature_check() {
+        let chain = ChainBuilder::new("testnet")
+            .with_validators(1)
+            .with_tx_pattern(TxPattern::Transfers(2))
+            .build(1);
+        let mut candidate = chain.block(1).clone();
+        candidate.header.signature.clear();
+
+        validate_unsigned_block(&candidate, "testnet").unwrap();
+        assert!(validate_block_stateless(&candidate, "testnet").is_err());
+    }
+
+    #[test]
+    fn test_keyless_transactions_admitted_until_the_first_fork() {
+        let chain = ChainBuilder::new("testnet")
+            .with_validators(1)
+            .with_tx_pattern(TxPattern::Transfers(1))
+            .build(1);
+        let mut keyless = chain.block(1).transactions[1].clone();
+        keyless.extra_data.clear();
+        assert!(keyless.verify_sender_signature().is_err());
+
+        // Testnet has not forked, so admission follows block validation
+        keyless.verify_sender_signature_at("testnet", 2).unwrap();
+        crate::Mempool::new(16)
+            .add_transaction_sync(keyless.clone())
+            .unwrap();
+        let mut mempool = Vec::new();
+        crate::admit_network_transaction(
+            &mut mempool,
+            keyless,
+            "testnet",
+            2,
+            16,
+            &spirachain_rpc::ResourceGuard::new(spirachain_rpc::ResourceLimits::default()),
+            &spirachain_rpc::MempoolMonitor::new(),
+        )
+        .unwrap();
+        assert_eq!(mempool.len(), 1);
+    }
+}
@@ -0,0 +1,30 @@
+use spirachain_rpc::types::VersionResponse;
+
+/// Commit this binary was built from, "unknown" outside a git checkout
+pub const GIT_COMMIT: &str = env!("SPIRACHAIN_GIT_COMMIT");
+
+/// Unix seconds: `SOURCE_DATE_EPOCH` if set, else the commit time
+pub const BUILD_TIMESTAMP: &str = env!("SPIRACHAIN_BUILD_TIMESTAMP");
+
+/// Optional components compiled into this binary. The semantic layer is always
+/// built in; the transport is TCP-only, so `quic` is never listed yet.
+pub fn enabled_features() -> Vec<String> {
+    let mut features = vec!["semantic".to_string()];
+    if cfg!(feature = "python-bridge") {
+        features.push("python-bridge".to_string());
+    }
+    features
+}
+
+/// Provenance served on `/version`, so fork investigations can tell which
+/// exact binary a node runs
+pub fn version_info(network: &str) -> VersionResponse {
+    VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: GIT_COMMIT.to_string(),
+        build_timestamp: BUILD_TIMESTAMP.parse().unwrap_or(0),
+        features: enabled_features(),
+        protocol_version: spirachain_core::PROTOCOL_VERSION,
+        network: network.to_string(),
+    }
+}
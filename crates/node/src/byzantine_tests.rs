@@ -0,0 +1,342 @@
+// Byzantine behavior harness
+// Honest nodes built from the same pieces the validator node wires together
+// (stateless block checks, verified state roots, the misbehavior monitor,
+// slashing through the validator set and gossip admission) are fed by a
+// malicious validator of a fixture chain. Each behavior is configured on its
+// own and the tests assert the detection, rejection or slashing path it should
+// trip actually fires, and that the honest node's chain and state survive it.
+
+use crate::{admit_network_transaction, validate_block_stateless, MisbehaviorMonitor, WorldState};
+use spirachain_consensus::{ProofOfSpiral, SlashingReason, SlotConsensus, Validator};
+use spirachain_core::{
+    fork_id, Amount, Block, ChainBuilder, Hash, Result, SpiraChainError, TestChain, TestKey,
+    Transaction, MIN_SPIRAL_COMPLEXITY, MIN_TX_FEE, MIN_VALIDATOR_STAKE,
+};
+use spirachain_rpc::{DropReason, MempoolMonitor, ResourceGuard, ResourceLimits};
+
+const NETWORK: &str = "testnet";
+const MEMPOOL_SIZE: usize = 16;
+
+/// What the malicious validator does
+#[derive(Debug, Clone, Copy)]
+enum Behavior {
+    /// Signs a second block for a height it produced
+    Equivocate,
+    /// Produces a block committing to a state root it did not compute
+    InvalidStateRoot,
+    /// Sits on its block for this many slots before releasing it
+    WithholdBlocks(u64),
+    /// Floods peers with this many junk and duplicate transactions
+    GossipSpam(usize),
+}
+
+/// A node following the chain the way the validator node does
+struct HonestNode {
+    chain: Vec<Block>,
+    state: WorldState,
+    consensus: ProofOfSpiral,
+    slot_consensus: SlotConsensus,
+    misbehavior: MisbehaviorMonitor,
+    mempool: Vec<Transaction>,
+    mempool_monitor: MempoolMonitor,
+    resource_guard: ResourceGuard,
+}
+
+impl HonestNode {
+    fn new(fixture: &TestChain) -> Self {
+        let mut consensus = ProofOfSpiral::new(MIN_SPIRAL_COMPLEXITY, 10.0);
+        let mut slot_consensus = SlotConsensus::new(NETWORK);
+        for key in fixture.validators() {
+            let validator = Validator::new(
+                key.address(),
+                key.public_key().to_vec(),
+                Amount::new(MIN_VALIDATOR_STAKE),
+                0,
+            )
+            .unwrap();
+            consensus.add_validator(validator).unwrap();
+            slot_consensus.add_validator(key.address());
+        }
+
+        Self {
+            chain: vec![fixture.genesis().clone()],
+            state: WorldState::for_network(NETWORK),
+            consensus,
+            slot_consensus,
+            misbehavior: MisbehaviorMonitor::new(),
+            mempool: Vec::new(),
+            mempool_monitor: MempoolMonitor::new(),
+            resource_guard: ResourceGuard::new(ResourceLimits::default()),
+        }
+    }
+
+    fn tip(&self) -> &Block {
+        self.chain.last().unwrap()
+    }
+
+    fn stake_of(&self, key: &TestKey) -> Amount {
+        self.consensus.get_validator(&key.address()).unwrap().stake
+    }
+
+    /// Same order of checks as the validator node's block handler
+    fn receive_block(&mut self, block: &Block) -> Result<()> {
+        if let Some(evidence) = self.misbehavior.observe_header(&block.header) {
+            let producer = evidence.verify()?;
+            self.consensus.slash_validator(
+                &producer,
+                SlashingReason::DoubleSigning,
+                evidence.height(),
+                evidence.second.timestamp,
+            );
+        }
+        if block.header.block_height <= self.tip().header.block_height {
+            return Err(SpiraChainError::InvalidBlock(
+                "already have a block at this height".into(),
+            ));
+        }
+        if block.header.previous_block_hash != self.tip().hash() {
+            return Err(SpiraChainError::InvalidBlock(
+                "does not build on our tip".into(),
+            ));
+        }
+        validate_block_stateless(block, NETWORK)?;
+        if let Err(e) = self.state.apply_verified_block(block) {
+            self.misbehavior.record_state_root_rejection();
+            return Err(e);
+        }
+        self.misbehavior.record_missed_slots(
+            &self.slot_consensus,
+            &self.tip().header,
+            &block.header,
+        );
+        self.chain.push(block.clone());
+        Ok(())
+    }
+
+    fn receive_transaction(&mut self, tx: Transaction) -> Result<()> {
+        let next_height = self.tip().header.block_height + 1;
+        admit_network_transaction(
+            &mut self.mempool,
+            tx,
+            NETWORK,
+            next_height,
+            MEMPOOL_SIZE,
+            &self.resource_guard,
+            &self.mempool_monitor,
+        )
+    }
+
+    fn rejections(&self, reason: DropReason) -> u64 {
+        let (rejected, _) = self.mempool_monitor.counters();
+        rejected.get(&reason).copied().unwrap_or(0)
+    }
+}
+
+/// A validator of the fixture chain misbehaving in one way
+struct MaliciousNode<'a> {
+    chain: &'a TestChain,
+    behavior: Behavior,
+}
+
+impl<'a> MaliciousNode<'a> {
+    fn new(chain: &'a TestChain, behavior: Behavior) -> Self {
+        Self { chain, behavior }
+    }
+
+    /// Blocks it sends for `height`, the honest one first where it sends one
+    fn blocks_for(&self, height: u64) -> Vec<Block> {
+        let honest = self.chain.block(height).clone();
+        match self.behavior {
+            Behavior::Equivocate => {
+                let mut twin = honest.clone();
+                twin.header.extra_data = b"equivocation".to_vec();
+                self.chain.resign(&mut twin);
+                vec![honest, twin]
+            }
+            Behavior::InvalidStateRoot => {
+                let mut forged = honest;
+                forged.header.state_root = Hash::new([0xee; 32]);
+                self.chain.resign(&mut forged);
+                vec![forged]
+            }
+            Behavior::WithholdBlocks(slots) => {
+                let mut late = honest;
+                late.header.timestamp += slots * self.chain_slot_ms();
+                self.chain.resign(&mut late);
+                vec![late]
+            }
+            Behavior::GossipSpam(_) => vec![honest],
+        }
+    }
+
+    /// Transactions it floods peers with before a block at `next_height`
+    fn spam(&self, next_height: u64) -> Vec<Transaction> {
+        let Behavior::GossipSpam(count) = self.behavior else {
+            return Vec::new();
+        };
+        let accounts = self.chain.accounts();
+        let transfer = |index: usize, purpose: &str, fork: Hash| {
+            let sender = &accounts[index % accounts.len()];
+            let mut tx = Transaction::new_at(
+                sender.address(),
+                accounts[(index + 1) % accounts.len()].address(),
+                Amount::qbt(1 + index as u64),
+                Amount::new(MIN_TX_FEE),
+                1_700_000_000_000 + index as u64,
+            )
+            .with_purpose(purpose)
+            .with_fork_id(fork);
+            tx.compute_hash();
+            sender.sign_transaction(&mut tx);
+            tx
+        };
+        let fork = fork_id(NETWORK, next_height);
+
+        let mut spam = Vec::new();
+        // The same transfer over and over, then reworded
+        let original = transfer(0, "rent", fork);
+        spam.extend(std::iter::repeat_n(original.clone(), count));
+        let mut reworded = original.clone();
+        reworded.purpose = "  RENT ".to_string();
+        reworded.timestamp += 1;
+        reworded.compute_hash();
+        accounts[0].sign_transaction(&mut reworded);
+        spam.extend(std::iter::repeat_n(reworded, count));
+        // Unsigned and replayed from another fork
+        for index in 0..count {
+            let mut unsigned = transfer(index, "unsigned", fork);
+            unsigned.signature.clear();
+            spam.push(unsigned);
+            spam.push(transfer(index, "other fork", Hash::new([7u8; 32])));
+        }
+        // Distinct valid transfers, more than a mempool holds
+        spam.extend((0..count).map(|index| transfer(index, &format!("flood {}", index), fork)));
+        spam
+    }
+
+    fn chain_slot_ms(&self) -> u64 {
+        SlotConsensus::new(NETWORK).slot_duration() * 1000
+    }
+}
+
+fn fixture(length: u64) -> TestChain {
+    ChainBuilder::new(NETWORK).with_validators(4).build(length)
+}
+
+#[test]
+fn test_equivocating_producer_is_detected_and_slashed_once() {
+    let chain = fixture(3);
+    let attacker = MaliciousNode::new(&chain, Behavior::Equivocate);
+    let mut node = HonestNode::new(&chain);
+    node.receive_block(chain.block(1)).unwrap();
+
+    let offender = chain.producer_of(2);
+    let stake = node.stake_of(offender);
+    let [honest, twin] = <[Block; 2]>::try_from(attacker.blocks_for(2)).unwrap();
+    node.receive_block(&honest).unwrap();
+    assert!(node.receive_block(&twin).is_err());
+    // Relayed again by other peers: no second punishment
+    assert!(node.receive_block(&twin).is_err());
+
+    assert_eq!(node.misbehavior.stats().equivocations(), 1);
+    let slashed =
+        Amount::new((stake.value() as f64 * spirachain_core::SLASHING_DOUBLE_SIGNING) as u128);
+    assert_eq!(node.stake_of(offender), stake.checked_sub(slashed).unwrap());
+    assert_eq!(node.stake_of(chain.producer_of(1)), stake);
+    assert_eq!(node.tip().hash(), honest.hash());
+
+    // Nothing to see when blocks at a height come from different producers
+    let fork = chain.fork_at(1, 1, 1);
+    assert!(node.receive_block(&fork[0]).is_err());
+    node.receive_block(chain.block(3)).unwrap();
+    assert_eq!(node.misbehavior.stats().equivocations(), 1);
+}
+
+#[test]
+fn test_block_with_invalid_state_root_is_rejected_without_touching_state() {
+    let chain = fixture(2);
+    let attacker = MaliciousNode::new(&chain, Behavior::InvalidStateRoot);
+    let mut node = HonestNode::new(&chain);
+    node.receive_block(chain.block(1)).unwrap();
+    let root_before = node.state.calculate_merkle_root();
+
+    let forged = attacker.blocks_for(2).remove(0);
+    assert!(matches!(
+        node.receive_block(&forged),
+        Err(SpiraChainError::InvalidBlock(_))
+    ));
+    assert_eq!(node.misbehavior.stats().state_root_rejections(), 1);
+    assert_eq!(node.state.calculate_merkle_root(), root_before);
+    assert_eq!(node.tip().header.block_height, 1);
+
+    // The same block committing to the root it really produces goes through
+    let mut probe = node.state.clone();
+    probe.apply_block(chain.block(2));
+    let mut honest = chain.block(2).clone();
+    honest.header.state_root = probe.calculate_merkle_root();
+    chain.resign(&mut honest);
+    node.receive_block(&honest).unwrap();
+    assert_eq!(node.state.calculate_merkle_root(), honest.header.state_root);
+    assert_eq!(node.misbehavior.stats().state_root_rejections(), 1);
+}
+
+#[test]
+fn test_withheld_blocks_count_as_missed_slots() {
+    let chain = fixture(3);
+    let withheld = 5;
+    let attacker = MaliciousNode::new(&chain, Behavior::WithholdBlocks(withheld));
+    let mut node = HonestNode::new(&chain);
+    node.receive_block(chain.block(1)).unwrap();
+    node.receive_block(chain.block(2)).unwrap();
+    assert!(chain.validators().iter().all(|key| node
+        .misbehavior
+        .stats()
+        .missed_slots(&key.address())
+        == 0));
+
+    let late = attacker.blocks_for(3).remove(0);
+    node.receive_block(&late).unwrap();
+    let missed: u64 = chain
+        .validators()
+        .iter()
+        .map(|key| node.misbehavior.stats().missed_slots(&key.address()))
+        .sum();
+    assert_eq!(missed, withheld);
+    // Four validators and five skipped slots: one of them missed two
+    assert!(chain.validators().iter().any(|key| node
+        .misbehavior
+        .stats()
+        .missed_slots(&key.address())
+        == 2));
+}
+
+#[test]
+fn test_gossip_spam_is_rejected_and_mempool_stays_bounded() {
+    let chain = fixture(1);
+    let count = MEMPOOL_SIZE * 2;
+    let attacker = MaliciousNode::new(&chain, Behavior::GossipSpam(count));
+    let mut node = HonestNode::new(&chain);
+    node.receive_block(chain.block(1)).unwrap();
+
+    for tx in attacker.spam(2) {
+        let _ = node.receive_transaction(tx);
+    }
+
+    assert_eq!(node.mempool.len(), MEMPOOL_SIZE);
+    // Replays of a pending transaction are dropped silently, rewordings are not
+    assert_eq!(node.rejections(DropReason::NearDuplicate), count as u64);
+    assert_eq!(node.rejections(DropReason::Invalid), 2 * count as u64);
+    assert_eq!(
+        node.rejections(DropReason::Full) + node.rejections(DropReason::LaneLimit),
+        (count + 1 - MEMPOOL_SIZE) as u64
+    );
+    let mut hashes: Vec<_> = node.mempool.iter().map(|tx| tx.tx_hash).collect();
+    hashes.sort_by_key(|hash| *hash.as_bytes());
+    hashes.dedup();
+    assert_eq!(hashes.len(), MEMPOOL_SIZE);
+
+    // Honest blocks keep flowing meanwhile
+    let mut chain = chain;
+    chain.extend(1);
+    node.receive_block(chain.block(2)).unwrap();
+}
@@ -0,0 +1,268 @@
+// Chain replay checker
+// `spira verify-chain` re-executes a stopped node's chain from genesis on an
+// empty WorldState, ignoring the stored accounts and snapshots the node's
+// fast path starts from. Every block goes through the checks a block received
+// from a peer goes through (stateless validation, parent linkage, epoch
+// summary, spiral rules) plus the diversity-adjusted coinbase allowance and
+// the state root it commits to. The replay stops at the first divergence and
+// reports the block and the check that failed. A replay that reaches the tip
+// also compares its result with the accounts the node stored. Leader and
+// stake checks need the live validator set and stay with the node.
+
+use crate::{backup::stored_state_root, validate_block_stateless, BlockStorage, WorldState};
+use spirachain_core::{Block, GenesisConfig, Hash, Result, SpiraChainError};
+
+/// Check of the replay a block failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayCheck {
+    /// The height index has no block
+    Missing,
+    /// Block 0 is not the genesis the node runs
+    Genesis,
+    /// Height or previous hash does not follow the parent
+    Linkage,
+    /// [`validate_block_stateless`]
+    Stateless,
+    EpochSummary,
+    Spiral,
+    /// Coinbase above the diversity-adjusted reward plus fees
+    Reward,
+    StateRoot,
+}
+
+impl ReplayCheck {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReplayCheck::Missing => "missing",
+            ReplayCheck::Genesis => "genesis",
+            ReplayCheck::Linkage => "linkage",
+            ReplayCheck::Stateless => "stateless",
+            ReplayCheck::EpochSummary => "epoch_summary",
+            ReplayCheck::Spiral => "spiral",
+            ReplayCheck::Reward => "reward",
+            ReplayCheck::StateRoot => "state_root",
+        }
+    }
+}
+
+/// First block the replay could not accept
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    pub height: u64,
+    pub block_hash: Option<Hash>,
+    pub check: ReplayCheck,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainVerifyReport {
+    /// Highest block that passed every check
+    pub verified_height: Option<u64>,
+    pub target_height: u64,
+    pub transactions: u64,
+    /// Transactions the chain skipped, as every node does when applying them
+    pub skipped_transactions: u64,
+    /// State root after the last verified block
+    pub state_root: Option<Hash>,
+    /// Root of the node's stored accounts, when the replay reached the tip
+    pub stored_state_root: Option<Hash>,
+    pub divergence: Option<Divergence>,
+}
+
+impl ChainVerifyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.divergence.is_none()
+    }
+
+    /// Whether the node's stored accounts differ from the replayed state
+    pub fn stored_state_differs(&self) -> bool {
+        self.stored_state_root
+            .is_some_and(|stored| Some(stored) != self.state_root)
+    }
+}
+
+/// Replay `storage` from genesis up to `to` (the tip when `None`). `genesis`
+/// is the installed genesis, the official one when `None`; `progress` is
+/// called with each verified height.
+pub fn verify_chain(
+    storage: &BlockStorage,
+    network: &str,
+    genesis: Option<&GenesisConfig>,
+    to: Option<u64>,
+    mut progress: impl FnMut(u64),
+) -> Result<ChainVerifyReport> {
+    let tip = storage
+        .get_latest_block()?
+        .map(|block| block.header.block_height)
+        .ok_or_else(|| SpiraChainError::StorageError("Database holds no blocks".to_string()))?;
+    let target_height = to.map_or(tip, |to| to.min(tip));
+
+    let mut report = ChainVerifyReport {
+        verified_height: None,
+        target_height,
+        transactions: 0,
+        skipped_transactions: 0,
+        state_root: None,
+        stored_state_root: None,
+        divergence: None,
+    };
+    let mut state = WorldState::for_network(network);
+    let mut parent: Option<Block> = None;
+
+    for height in 0..=target_height {
+        let Some(block) = storage.get_block_by_height(height)? else {
+            report.divergence = Some(Divergence {
+                height,
+                block_hash: None,
+                check: ReplayCheck::Missing,
+                reason: format!("no block at height {} below the tip {}", height, tip),
+            });
+            break;
+        };
+
+        let checked = match &parent {
+            None => replay_genesis(&mut state, &block, network, genesis),
+            Some(parent) => replay_block(&mut state, storage, parent, &block, network),
+        };
+        match checked {
+            Ok(skipped) => {
+                report.transactions += block.transactions.len() as u64;
+                report.skipped_transactions += skipped;
+                report.state_root = Some(state.calculate_merkle_root());
+                report.verified_height = Some(height);
+                state.set_height(height);
+                progress(height);
+                parent = Some(block);
+            }
+            Err((check, reason)) => {
+                report.divergence = Some(Divergence {
+                    height,
+                    block_hash: Some(block.hash()),
+                    check,
+                    reason,
+                });
+                break;
+            }
+        }
+    }
+
+    if report.verified_height == Some(tip) {
+        report.stored_state_root = Some(stored_state_root(storage, network, tip)?);
+    }
+    Ok(report)
+}
+
+type Checked = std::result::Result<u64, (ReplayCheck, String)>;
+
+fn replay_genesis(
+    state: &mut WorldState,
+    block: &Block,
+    network: &str,
+    genesis: Option<&GenesisConfig>,
+) -> Checked {
+    let expected = match genesis {
+        Some(genesis) => genesis.create_genesis_block().hash().to_string(),
+        None => GenesisConfig::expected_genesis_hash(network).to_string(),
+    };
+    if block.hash().to_string() != expected {
+        return Err((
+            ReplayCheck::Genesis,
+            format!("genesis is {}, expected {}", block.hash(), expected),
+        ));
+    }
+
+    // Allocations are credited directly, as the node does
+    for tx in &block.transactions {
+        state
+            .credit_balance(&tx.to, tx.amount)
+            .map_err(|e| (ReplayCheck::Genesis, e.to_string()))?;
+    }
+    Ok(0)
+}
+
+fn replay_block(
+    state: &mut WorldState,
+    storage: &BlockStorage,
+    parent: &Block,
+    block: &Block,
+    network: &str,
+) -> Checked {
+    let height = block.header.block_height;
+    if height != parent.header.block_height + 1 || block.header.previous_block_hash != parent.hash()
+    {
+        return Err((
+            ReplayCheck::Linkage,
+            format!(
+                "block claims height {} on {}, parent is {} at height {}",
+                height,
+                block.header.previous_block_hash,
+                parent.hash(),
+                parent.header.block_height
+            ),
+        ));
+    }
+
+    validate_block_stateless(block, network)
+        .map_err(|e| (ReplayCheck::Stateless, e.to_string()))?;
+
+    if let Some(summary) = block.epoch_summary() {
+        let expected = storage
+            .compute_epoch_summary(summary.epoch)
+            .map_err(|e| (ReplayCheck::EpochSummary, e.to_string()))?;
+        if expected != *summary {
+            return Err((
+                ReplayCheck::EpochSummary,
+                format!("epoch {} summary does not match the chain", summary.epoch),
+            ));
+        }
+    }
+
+    state
+        .check_block_spiral(block)
+        .map_err(|e| (ReplayCheck::Spiral, e.to_string()))?;
+
+    let failures = state.apply_verified_block(block).map_err(|e| match e {
+        SpiraChainError::InvalidTransaction(_) => (ReplayCheck::Reward, e.to_string()),
+        _ => (ReplayCheck::StateRoot, e.to_string()),
+    })?;
+
+    Ok(failures.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_storage(name: &str) -> (std::path::PathBuf, BlockStorage) {
+        let dir =
+            std::env::temp_dir().join(format!("spirachain-verify-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = BlockStorage::new(&dir).unwrap();
+        (dir, storage)
+    }
+
+    #[test]
+    fn test_replay_reports_wrong_genesis() {
+        let (dir, storage) = temp_storage("genesis");
+        let official = GenesisConfig::default();
+        storage
+            .store_block(&official.create_genesis_block())
+            .unwrap();
+
+        let report = verify_chain(&storage, "testnet", None, None, |_| {}).unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(report.verified_height, Some(0));
+        assert!(report.state_root.is_some());
+
+        let mut other = GenesisConfig::default();
+        other.timestamp += 1;
+        let report = verify_chain(&storage, "testnet", Some(&other), None, |_| {}).unwrap();
+        let divergence = report.divergence.unwrap();
+        assert_eq!(divergence.height, 0);
+        assert_eq!(divergence.check, ReplayCheck::Genesis);
+        assert_eq!(report.verified_height, None);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
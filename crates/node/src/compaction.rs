@@ -0,0 +1,377 @@
+// Storage compaction scheduling
+// Pruning the semantic vector store removes entries by the thousand, sled
+// rewrites the log segments they leave mostly empty, and the flush that follows
+// can hold the database for seconds. Run at the wrong moment, that pushes our
+// own block past its slot. Heavy compaction work is therefore queued and only
+// started when none of our leader slots is within COMPACTION_GUARD_SLOTS, per
+// the slot calendar, then run in the background in batches whose disk IO is
+// rate limited, stopping before our next slot comes up. A validator that leads
+// so often that no window opens compacts anyway after MAX_COMPACTION_DEFERRAL.
+// Blocks we store while a compaction runs are counted as stalls.
+
+use crate::BlockStorage;
+use spirachain_consensus::SlotConsensus;
+use spirachain_core::{Address, Result};
+use spirachain_rpc::server::MetricsSource;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Slots before one of ours in which no compaction starts or continues
+pub const COMPACTION_GUARD_SLOTS: u64 = 2;
+
+/// Longest a pending compaction waits for a window before running anyway
+pub const MAX_COMPACTION_DEFERRAL: Duration = Duration::from_secs(30 * 60);
+
+/// Vector bytes pruned between two IO pauses
+pub const COMPACTION_BATCH_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Whether queued compaction work may start now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactionDecision {
+    /// Nothing queued, or a compaction is already running
+    Idle,
+    /// Start, and stop after `window_slots` whole slots past the current
+    /// one; `None` when no leader slot of ours is scheduled or the run is forced
+    Run { window_slots: Option<u64> },
+    /// Our slot `leader_slot` is too close
+    Defer { leader_slot: u64 },
+}
+
+/// Compactions run and deferred, and blocks stored during one
+#[derive(Default)]
+pub struct CompactionStats {
+    running: AtomicBool,
+    runs: AtomicU64,
+    interrupted: AtomicU64,
+    deferred: AtomicU64,
+    forced: AtomicU64,
+    pruned_bytes: AtomicU64,
+    stalls: AtomicU64,
+    stall_micros: AtomicU64,
+}
+
+impl CompactionStats {
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn runs(&self) -> u64 {
+        self.runs.load(Ordering::Relaxed)
+    }
+
+    pub fn deferred(&self) -> u64 {
+        self.deferred.load(Ordering::Relaxed)
+    }
+
+    pub fn stalls(&self) -> u64 {
+        self.stalls.load(Ordering::Relaxed)
+    }
+
+    /// Count a block write that took `elapsed` as a stall if it overlapped
+    /// a compaction
+    pub fn record_block_stored(&self, elapsed: Duration) {
+        if self.is_running() {
+            self.stalls.fetch_add(1, Ordering::Relaxed);
+            self.stall_micros
+                .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn try_start(&self) -> bool {
+        self.running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
+    fn finish(&self, pruned_bytes: u64, interrupted: bool) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        if interrupted {
+            self.interrupted.fetch_add(1, Ordering::Relaxed);
+        }
+        self.pruned_bytes.fetch_add(pruned_bytes, Ordering::Relaxed);
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+impl MetricsSource for CompactionStats {
+    fn export_prometheus(&self) -> String {
+        format!(
+            "# HELP spirachain_compaction_runs_total Background storage compactions run\n\
+             # TYPE spirachain_compaction_runs_total counter\n\
+             spirachain_compaction_runs_total {}\n\
+             # HELP spirachain_compaction_interrupted_total Compactions stopped before an upcoming leader slot\n\
+             # TYPE spirachain_compaction_interrupted_total counter\n\
+             spirachain_compaction_interrupted_total {}\n\
+             # HELP spirachain_compaction_deferred_total Compaction starts put off because one of our leader slots was near\n\
+             # TYPE spirachain_compaction_deferred_total counter\n\
+             spirachain_compaction_deferred_total {}\n\
+             # HELP spirachain_compaction_forced_total Compactions run without a window after waiting too long\n\
+             # TYPE spirachain_compaction_forced_total counter\n\
+             spirachain_compaction_forced_total {}\n\
+             # HELP spirachain_compaction_pruned_bytes_total Semantic vector bytes removed by compactions\n\
+             # TYPE spirachain_compaction_pruned_bytes_total counter\n\
+             spirachain_compaction_pruned_bytes_total {}\n\
+             # HELP spirachain_compaction_running Whether a compaction is running\n\
+             # TYPE spirachain_compaction_running gauge\n\
+             spirachain_compaction_running {}\n\
+             # HELP spirachain_compaction_stalls_total Own blocks stored while a compaction was running\n\
+             # TYPE spirachain_compaction_stalls_total counter\n\
+             spirachain_compaction_stalls_total {}\n\
+             # HELP spirachain_compaction_stall_seconds_total Time spent storing those blocks\n\
+             # TYPE spirachain_compaction_stall_seconds_total counter\n\
+             spirachain_compaction_stall_seconds_total {:.6}\n",
+            self.runs(),
+            self.interrupted.load(Ordering::Relaxed),
+            self.deferred(),
+            self.forced.load(Ordering::Relaxed),
+            self.pruned_bytes.load(Ordering::Relaxed),
+            self.is_running() as u8,
+            self.stalls(),
+            self.stall_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        )
+    }
+}
+
+/// Queued compaction work and when it has been waiting since
+#[derive(Default)]
+pub struct CompactionScheduler {
+    /// Vector store bytes over the quota, while pruning is queued
+    vector_excess: Option<u64>,
+    deferred_since: Option<Instant>,
+    stats: Arc<CompactionStats>,
+}
+
+impl CompactionScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> Arc<CompactionStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Queue pruning of `excess_bytes` from the vector store, replacing any
+    /// earlier measurement
+    pub fn request_vector_prune(&mut self, excess_bytes: u64) {
+        self.vector_excess = Some(excess_bytes).filter(|bytes| *bytes > 0);
+        if self.vector_excess.is_none() {
+            self.deferred_since = None;
+        }
+    }
+
+    /// Decide at `now`, in `current_slot`, given the slots we lead from
+    /// there on in ascending order
+    pub fn decide(
+        &mut self,
+        current_slot: u64,
+        leader_slots: &[u64],
+        now: Instant,
+    ) -> CompactionDecision {
+        if self.vector_excess.is_none() || self.stats.is_running() {
+            return CompactionDecision::Idle;
+        }
+
+        let next_leader_slot = leader_slots
+            .iter()
+            .copied()
+            .find(|slot| *slot >= current_slot);
+        let window_slots = match next_leader_slot {
+            None => None,
+            Some(slot) if slot > current_slot + COMPACTION_GUARD_SLOTS => {
+                Some(slot - COMPACTION_GUARD_SLOTS - current_slot - 1)
+            }
+            Some(slot) => {
+                let since = *self.deferred_since.get_or_insert(now);
+                if now.duration_since(since) < MAX_COMPACTION_DEFERRAL {
+                    self.stats.deferred.fetch_add(1, Ordering::Relaxed);
+                    return CompactionDecision::Defer { leader_slot: slot };
+                }
+                self.stats.forced.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        };
+        self.deferred_since = None;
+        CompactionDecision::Run { window_slots }
+    }
+
+    /// Claim the queued work for a run; `None` if a run is already going
+    pub fn start(&mut self) -> Option<CompactionJob> {
+        let excess_bytes = self.vector_excess?;
+        if !self.stats.try_start() {
+            return None;
+        }
+        self.vector_excess = None;
+        Some(CompactionJob {
+            excess_bytes,
+            stats: self.stats(),
+        })
+    }
+}
+
+/// One claimed compaction, run on a blocking thread
+pub struct CompactionJob {
+    excess_bytes: u64,
+    stats: Arc<CompactionStats>,
+}
+
+impl CompactionJob {
+    /// Prune the vector store in batches, pausing after each so IO stays under
+    /// `io_bytes_per_sec` (0 = unlimited), then flush. Stops at `deadline`;
+    /// what is left is measured and queued again by the next resource check.
+    pub fn run(self, storage: &BlockStorage, deadline: Option<Instant>, io_bytes_per_sec: u64) {
+        let started = Instant::now();
+        let past_deadline = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let mut remaining = self.excess_bytes;
+        let mut pruned = 0u64;
+        let mut interrupted = false;
+
+        let result = (|| -> Result<()> {
+            while remaining > 0 {
+                if past_deadline() {
+                    interrupted = true;
+                    return Ok(());
+                }
+                let batch =
+                    storage.prune_semantic_vectors(remaining.min(COMPACTION_BATCH_BYTES))?;
+                if batch.count == 0 {
+                    break;
+                }
+                pruned += batch.bytes;
+                remaining = remaining.saturating_sub(batch.bytes);
+                std::thread::sleep(io_pause(batch.bytes, io_bytes_per_sec));
+            }
+            if past_deadline() {
+                // sled's background flusher writes it out in smaller steps
+                interrupted = true;
+                return Ok(());
+            }
+            storage.flush()
+        })();
+
+        match result {
+            Ok(()) if interrupted => info!(
+                "✂️  Compaction paused before our leader slot: {} vector bytes pruned, {} left",
+                pruned, remaining
+            ),
+            Ok(()) => info!(
+                "✂️  Compaction done in {:.1}s: {} vector bytes pruned",
+                started.elapsed().as_secs_f64(),
+                pruned
+            ),
+            Err(e) => warn!("Compaction failed after {} vector bytes: {}", pruned, e),
+        }
+        self.stats.finish(pruned, interrupted);
+    }
+}
+
+/// Pause after writing `bytes` to stay under `bytes_per_sec`, 0 = unlimited
+pub fn io_pause(bytes: u64, bytes_per_sec: u64) -> Duration {
+    if bytes_per_sec == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(bytes as f64 / bytes_per_sec as f64)
+}
+
+/// Slots `validator` leads from `current_slot` to the end of the next epoch,
+/// in ascending order
+pub fn upcoming_leader_slots(
+    consensus: &SlotConsensus,
+    validator: &Address,
+    current_slot: u64,
+) -> Vec<u64> {
+    let epoch = SlotConsensus::epoch_of(current_slot);
+    let mut slots: Vec<u64> = [epoch, epoch + 1]
+        .into_iter()
+        .flat_map(|epoch| consensus.leader_schedule(epoch))
+        .filter(|(address, _)| address == validator)
+        .flat_map(|(_, slots)| slots)
+        .filter(|slot| *slot >= current_slot)
+        .collect();
+    slots.sort_unstable();
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compaction_waits_for_a_window() {
+        let mut scheduler = CompactionScheduler::new();
+        let now = Instant::now();
+        assert_eq!(scheduler.decide(100, &[101], now), CompactionDecision::Idle);
+
+        scheduler.request_vector_prune(1_000);
+        // Our slot is next: wait
+        assert_eq!(
+            scheduler.decide(100, &[101, 110], now),
+            CompactionDecision::Defer { leader_slot: 101 }
+        );
+        // Slots 101 and 102 are free before the guard of slot 105
+        assert_eq!(
+            scheduler.decide(100, &[105], now),
+            CompactionDecision::Run {
+                window_slots: Some(2)
+            }
+        );
+        assert_eq!(
+            scheduler.decide(100, &[], now),
+            CompactionDecision::Run { window_slots: None }
+        );
+
+        // Leading every slot: forced once the deferral runs out
+        assert!(matches!(
+            scheduler.decide(100, &[100, 101], now),
+            CompactionDecision::Defer { .. }
+        ));
+        assert!(matches!(
+            scheduler.decide(101, &[101], now + MAX_COMPACTION_DEFERRAL / 2),
+            CompactionDecision::Defer { .. }
+        ));
+        assert_eq!(
+            scheduler.decide(160, &[160], now + MAX_COMPACTION_DEFERRAL),
+            CompactionDecision::Run { window_slots: None }
+        );
+        assert_eq!(scheduler.stats().deferred(), 3);
+
+        // One run at a time; a finished one leaves nothing queued
+        let job = scheduler.start().unwrap();
+        scheduler.request_vector_prune(500);
+        assert!(scheduler.start().is_none());
+        assert_eq!(scheduler.decide(100, &[], now), CompactionDecision::Idle);
+        scheduler
+            .stats()
+            .record_block_stored(Duration::from_millis(40));
+        job.stats.finish(1_000, false);
+        scheduler
+            .stats()
+            .record_block_stored(Duration::from_millis(40));
+        assert_eq!(scheduler.stats().stalls(), 1);
+        assert_eq!(scheduler.stats().runs(), 1);
+        assert!(scheduler.start().is_some());
+    }
+
+    #[test]
+    fn test_io_pause_follows_the_rate() {
+        assert_eq!(io_pause(4 * 1024 * 1024, 0), Duration::ZERO);
+        assert_eq!(
+            io_pause(4 * 1024 * 1024, 16 * 1024 * 1024),
+            Duration::from_millis(250)
+        );
+    }
+
+    #[test]
+    fn test_upcoming_leader_slots_cross_the_epoch() {
+        let mut consensus = SlotConsensus::new("testnet");
+        let ours = Address::new([1u8; 32]);
+        consensus.add_validator(ours);
+        consensus.add_validator(Address::new([2u8; 32]));
+
+        let epoch_end = spirachain_consensus::SLOTS_PER_EPOCH;
+        let slots = upcoming_leader_slots(&consensus, &ours, epoch_end - 3);
+        assert_eq!(&slots[..3], &[epoch_end - 2, epoch_end, epoch_end + 2]);
+        assert!(slots.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -0,0 +1,142 @@
+// Data directory lock
+// Two nodes on one data directory corrupt the database and can sign the same
+// slot twice. A node takes an exclusive OS lock on DATA_DIR_LOCK_FILE at
+// startup and holds it until it exits, recording its PID, start time and
+// command line in DATA_DIR_OWNER_FILE so a second node can say which process
+// to stop. The lock is flock/LockFileEx, released by the OS when the process
+// dies: an owner file left behind by a crash is stale and gets replaced.
+
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Locked for as long as a node runs on the data directory
+pub const DATA_DIR_LOCK_FILE: &str = "node.lock";
+
+/// Who holds the lock; kept apart from the lock file, which Windows does not
+/// let other processes read while it is locked
+pub const DATA_DIR_OWNER_FILE: &str = "node.pid";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    /// Unix seconds
+    pub started_at: u64,
+    pub command: String,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            started_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            command: std::env::args().collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Exclusive hold on a data directory, released on drop
+#[derive(Debug)]
+pub struct DataDirLock {
+    file: File,
+    owner_path: PathBuf,
+}
+
+impl DataDirLock {
+    /// Lock `data_dir`, creating it if needed. Fails with the holder's PID
+    /// when another process runs on it.
+    pub fn acquire(data_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(data_dir)
+            .map_err(|e| SpiraChainError::io(format!("creating {}", data_dir.display()), e))?;
+        let lock_path = data_dir.join(DATA_DIR_LOCK_FILE);
+        let owner_path = data_dir.join(DATA_DIR_OWNER_FILE);
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(|e| SpiraChainError::io(format!("opening {}", lock_path.display()), e))?;
+
+        if file.try_lock_exclusive().is_err() {
+            let holder = match read_owner(&owner_path) {
+                Some(owner) => format!(
+                    "process {} (started at unix time {}: {})",
+                    owner.pid, owner.started_at, owner.command
+                ),
+                None => "another process".to_string(),
+            };
+            return Err(SpiraChainError::Config(format!(
+                "Data directory {} is in use by {}; stop it or start this node with another data directory",
+                data_dir.display(),
+                holder
+            )));
+        }
+
+        if let Some(stale) = read_owner(&owner_path) {
+            warn!(
+                "🔓 Taking over the lock on {} left by process {}, which is no longer running",
+                data_dir.display(),
+                stale.pid
+            );
+        }
+        let owner = serde_json::to_string_pretty(&LockOwner::current())
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        std::fs::write(&owner_path, owner)
+            .map_err(|e| SpiraChainError::io(format!("writing {}", owner_path.display()), e))?;
+
+        Ok(Self { file, owner_path })
+    }
+
+    pub fn owner(&self) -> Option<LockOwner> {
+        read_owner(&self.owner_path)
+    }
+}
+
+impl Drop for DataDirLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.owner_path);
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_holder_is_refused() {
+        let dir = std::env::temp_dir().join(format!("spirachain-lock-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // An owner file without a held lock is what a crash leaves behind
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(DATA_DIR_OWNER_FILE),
+            r#"{"pid": 1, "started_at": 0, "command": "spira node"}"#,
+        )
+        .unwrap();
+
+        let lock = DataDirLock::acquire(&dir).unwrap();
+        assert_eq!(lock.owner().unwrap().pid, std::process::id());
+
+        let error = DataDirLock::acquire(&dir).unwrap_err().to_string();
+        assert!(error.contains(&format!("process {}", std::process::id())));
+
+        drop(lock);
+        assert!(!dir.join(DATA_DIR_OWNER_FILE).exists());
+        DataDirLock::acquire(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::SigningProtection;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Address, Result, SpiraChainError};
+use spirachain_crypto::{KeyPair, PublicKey};
+use std::path::Path;
+use std::time::Duration;
+
+/// Onboarding report written by `spira node --validator --dry-run`, kept in the
+/// node data directory
+pub const DRY_RUN_REPORT_FILE: &str = "dry_run_report.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// What a validator would have done had it been live: the slots it would have
+/// led, how long each block took to build against the slot deadline, and the
+/// blocks it validated while following the chain
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DryRunReport {
+    pub validator: String,
+    pub network: String,
+    pub key_checks: Vec<KeyCheck>,
+    pub synced: bool,
+    pub chain_height: u64,
+    pub blocks_validated: u64,
+    pub invalid_blocks: u64,
+    pub slots_led: u64,
+    /// Blocks built before their slot ended
+    pub slots_on_time: u64,
+    /// Slots where no block could be built at all
+    pub slots_failed: u64,
+    pub max_build_ms: u64,
+    pub total_build_ms: u64,
+    pub last_failure: Option<String>,
+}
+
+impl DryRunReport {
+    pub fn new(validator: &Address, network: &str) -> Self {
+        Self {
+            validator: validator.to_string(),
+            network: network.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Check the key signs and verifies, and that double-sign protection will
+    /// let it sign at the current chain height
+    pub fn check_key(
+        &mut self,
+        keypair: &KeyPair,
+        protection: &SigningProtection,
+        chain_height: u64,
+    ) {
+        let address = keypair.to_address();
+        let message = b"spirachain validator dry run";
+        let signature = keypair.sign(message);
+        self.add_check(
+            "signature",
+            PublicKey::verify(keypair.public_key(), message, &signature),
+            format!("{}-byte signature over a test message", signature.len()),
+        );
+
+        match protection.last_signed(&address) {
+            Some(last) if last.height > chain_height => self.add_check(
+                "double_sign_protection",
+                false,
+                format!(
+                    "key signed height {} but the chain is at {}: production would be refused",
+                    last.height, chain_height
+                ),
+            ),
+            Some(last) => self.add_check(
+                "double_sign_protection",
+                true,
+                format!("last signed height {}, slot {}", last.height, last.slot),
+            ),
+            None => self.add_check(
+                "double_sign_protection",
+                true,
+                "no blocks signed with this key yet".to_string(),
+            ),
+        }
+    }
+
+    fn add_check(&mut self, name: &str, passed: bool, detail: String) {
+        self.key_checks.push(KeyCheck {
+            name: name.to_string(),
+            passed,
+            detail,
+        });
+    }
+
+    /// A slot we would have led; `built` is `Err` when no block could be made
+    pub fn record_slot(
+        &mut self,
+        build_time: Duration,
+        deadline: Duration,
+        built: std::result::Result<(), String>,
+    ) {
+        self.slots_led += 1;
+        match built {
+            Ok(()) => {
+                let build_ms = build_time.as_millis() as u64;
+                self.max_build_ms = self.max_build_ms.max(build_ms);
+                self.total_build_ms += build_ms;
+                if build_time <= deadline {
+                    self.slots_on_time += 1;
+                }
+            }
+            Err(e) => {
+                self.slots_failed += 1;
+                self.last_failure = Some(e);
+            }
+        }
+    }
+
+    pub fn record_validated_block(&mut self, valid: bool) {
+        if valid {
+            self.blocks_validated += 1;
+        } else {
+            self.invalid_blocks += 1;
+        }
+    }
+
+    pub fn average_build_ms(&self) -> u64 {
+        let built = self.slots_led - self.slots_failed;
+        self.total_build_ms.checked_div(built).unwrap_or(0)
+    }
+
+    /// Ready once the key is healthy, the node keeps up with the chain and it
+    /// met every slot it would have led
+    pub fn is_ready(&self) -> bool {
+        self.key_checks.iter().all(|check| check.passed)
+            && self.synced
+            && self.slots_led > 0
+            && self.slots_on_time == self.slots_led
+    }
+
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Validator {} on {}: {}",
+            self.validator,
+            self.network,
+            if self.is_ready() {
+                "READY"
+            } else {
+                "NOT READY"
+            }
+        )];
+        for check in &self.key_checks {
+            lines.push(format!(
+                "  {} {}: {}",
+                if check.passed { "✓" } else { "✗" },
+                check.name,
+                check.detail
+            ));
+        }
+        lines.push(format!(
+            "  {} chain height {} ({} blocks validated, {} invalid)",
+            if self.synced { "✓" } else { "✗" },
+            self.chain_height,
+            self.blocks_validated,
+            self.invalid_blocks
+        ));
+        lines.push(format!(
+            "  {} {}/{} slots on time, {} failed (avg {}ms, max {}ms)",
+            if self.slots_led > 0 && self.slots_on_time == self.slots_led {
+                "✓"
+            } else {
+                "✗"
+            },
+            self.slots_on_time,
+            self.slots_led,
+            self.slots_failed,
+            self.average_build_ms(),
+            self.max_build_ms
+        ));
+        if let Some(failure) = &self.last_failure {
+            lines.push(format!("  last failure: {}", failure));
+        }
+        lines
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_vec_pretty(self)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        std::fs::write(path, data)
+            .map_err(|e| SpiraChainError::StorageError(format!("{:?}: {}", path, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_needs_healthy_key_and_timely_slots() {
+        let path =
+            std::env::temp_dir().join(format!("spirachain-dry-run-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let keypair = KeyPair::generate();
+        let protection = SigningProtection::load(&path, false).unwrap();
+        protection
+            .record(&keypair.to_address(), 50, 7, spirachain_core::Hash::zero())
+            .unwrap();
+
+        let mut report = DryRunReport::new(&keypair.to_address(), "testnet");
+        report.check_key(&keypair, &protection, 10);
+        assert!(report.key_checks[0].passed);
+        assert!(!report.key_checks[1].passed);
+
+        let mut report = DryRunReport::new(&keypair.to_address(), "testnet");
+        report.check_key(&keypair, &protection, 60);
+        report.synced = true;
+        assert!(!report.is_ready());
+
+        let slot = Duration::from_secs(30);
+        report.record_slot(Duration::from_millis(400), slot, Ok(()));
+        assert!(report.is_ready());
+
+        report.record_slot(Duration::from_secs(31), slot, Ok(()));
+        report.record_slot(Duration::ZERO, slot, Err("no genesis".to_string()));
+        assert!(!report.is_ready());
+        assert_eq!(report.slots_on_time, 1);
+        assert_eq!(report.average_build_ms(), 15_700);
+    }
+}
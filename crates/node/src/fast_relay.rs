@@ -0,0 +1,207 @@
+// Fast block relay
+// Full validation of a received block (transactions, coinbase, state root)
+// takes a while, and every hop that waits for it adds to propagation
+// latency. With `fast_block_relay` on, a compact block announcement is passed
+// on as soon as its header checks out: it is the next block on our tip, it is
+// signed by its producer, and that producer leads a current slot. Only one
+// block per height is relayed, so an equivocating producer is not amplified.
+// If the block then fails validation it is revoked, and peers drop it unless
+// they already validated it themselves. Revocations only cancel reconstruction
+// of a block the revoking peer sent us, so they cannot suppress valid blocks
+// from elsewhere.
+
+use spirachain_core::{Address, BlockHeader, Hash, Result, SpiraChainError};
+use spirachain_rpc::server::MetricsSource;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Relayed blocks still undecided this many blocks below the tip are forgotten
+const RELAY_HORIZON: u64 = 16;
+
+/// What became of a block relayed before validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayOutcome {
+    Accepted,
+    /// Another block took its height first; it was not invalid
+    Superseded,
+    Rejected,
+}
+
+/// Blocks relayed, and relayed blocks that turned out invalid
+#[derive(Default)]
+pub struct FastRelayStats {
+    relayed: AtomicU64,
+    revoked: AtomicU64,
+    revocations_received: AtomicU64,
+}
+
+impl FastRelayStats {
+    pub fn relayed(&self) -> u64 {
+        self.relayed.load(Ordering::Relaxed)
+    }
+
+    pub fn revoked(&self) -> u64 {
+        self.revoked.load(Ordering::Relaxed)
+    }
+
+    pub fn record_revocation_received(&self) {
+        self.revocations_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Share of relayed blocks that had to be revoked
+    pub fn misrelay_rate(&self) -> f64 {
+        match self.relayed() {
+            0 => 0.0,
+            relayed => self.revoked() as f64 / relayed as f64,
+        }
+    }
+}
+
+impl MetricsSource for FastRelayStats {
+    fn export_prometheus(&self) -> String {
+        format!(
+            "# HELP spirachain_block_fast_relayed_total Blocks relayed before full validation\n\
+             # TYPE spirachain_block_fast_relayed_total counter\n\
+             spirachain_block_fast_relayed_total {}\n\
+             # HELP spirachain_block_fast_relay_revoked_total Relayed blocks revoked after failing validation\n\
+             # TYPE spirachain_block_fast_relay_revoked_total counter\n\
+             spirachain_block_fast_relay_revoked_total {}\n\
+             # HELP spirachain_block_fast_relay_misrelay_rate Share of relayed blocks that were revoked\n\
+             # TYPE spirachain_block_fast_relay_misrelay_rate gauge\n\
+             spirachain_block_fast_relay_misrelay_rate {:.4}\n\
+             # HELP spirachain_block_revocations_received_total Block revocations received from peers\n\
+             # TYPE spirachain_block_revocations_received_total counter\n\
+             spirachain_block_revocations_received_total {}\n",
+            self.relayed(),
+            self.revoked(),
+            self.misrelay_rate(),
+            self.revocations_received.load(Ordering::Relaxed)
+        )
+    }
+}
+
+/// Blocks we relayed ahead of validation, until the chain decides on them
+#[derive(Default)]
+pub struct FastRelay {
+    /// Height of each block awaiting its verdict
+    pending: HashMap<Hash, u64>,
+    stats: Arc<FastRelayStats>,
+}
+
+impl FastRelay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> Arc<FastRelayStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Whether `header` may be relayed before full validation. `slot_leader`
+    /// gives the expected producer of a slot, `current_slot` is ours; the
+    /// block's slot may be one behind to allow for clock skew.
+    pub fn check(
+        &self,
+        header: &BlockHeader,
+        tip: (u64, Hash),
+        current_slot: u64,
+        block_slot: u64,
+        slot_leader: Option<Address>,
+    ) -> Result<()> {
+        let height = header.block_height;
+        let reject = |reason: String| {
+            Err(SpiraChainError::InvalidBlock(format!(
+                "block {} not relayed early: {}",
+                height, reason
+            )))
+        };
+
+        if height != tip.0 + 1 || header.previous_block_hash != tip.1 {
+            return reject("it does not extend our tip".to_string());
+        }
+        if self.pending.values().any(|relayed| *relayed == height) {
+            return reject("a block for its height was already relayed".to_string());
+        }
+        if block_slot > current_slot || block_slot + 1 < current_slot {
+            return reject(format!(
+                "its slot {} is not current ({})",
+                block_slot, current_slot
+            ));
+        }
+        if slot_leader.is_none() || header.producer_address() != slot_leader {
+            return reject(format!("its producer does not lead slot {}", block_slot));
+        }
+        header.verify_signature()
+    }
+
+    pub fn record_relayed(&mut self, block_hash: Hash, height: u64) {
+        self.pending.insert(block_hash, height);
+        self.stats.relayed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn is_pending(&self, block_hash: &Hash) -> bool {
+        self.pending.contains_key(block_hash)
+    }
+
+    /// Settle a relayed block; true when it has to be revoked
+    pub fn settle(&mut self, block_hash: &Hash, outcome: RelayOutcome) -> bool {
+        if self.pending.remove(block_hash).is_none() {
+            return false;
+        }
+        if outcome == RelayOutcome::Rejected {
+            self.stats.revoked.fetch_add(1, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+
+    /// Forget blocks long below the tip, whose verdict never came back
+    pub fn prune(&mut self, current_height: u64) {
+        self.pending
+            .retain(|_, height| *height + RELAY_HORIZON > current_height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_crypto::KeyPair;
+
+    #[test]
+    fn test_relay_checks_and_revocation() {
+        let keypair = KeyPair::generate();
+        let tip = (4, Hash::new([4u8; 32]));
+        let mut header = BlockHeader::new(tip.1, 5);
+        header.validator_pubkey = keypair.public_key().0.to_vec();
+        header.signature = keypair.sign(header.hash().as_bytes());
+        let producer = header.producer_address();
+
+        let mut relay = FastRelay::new();
+        assert!(relay.check(&header, tip, 10, 10, producer).is_ok());
+        assert!(relay.check(&header, tip, 10, 9, producer).is_ok());
+        assert!(relay.check(&header, tip, 10, 8, producer).is_err());
+        assert!(relay
+            .check(&header, tip, 10, 10, Some(Address::new([1u8; 32])))
+            .is_err());
+        assert!(relay.check(&header, (5, tip.1), 10, 10, producer).is_err());
+
+        let mut forged = header.clone();
+        forged.signature[0] ^= 1;
+        assert!(relay.check(&forged, tip, 10, 10, producer).is_err());
+
+        relay.record_relayed(header.hash(), 5);
+        // One block per height
+        assert!(relay.check(&header, tip, 10, 10, producer).is_err());
+        assert!(relay.settle(&header.hash(), RelayOutcome::Rejected));
+        assert!(!relay.settle(&header.hash(), RelayOutcome::Rejected));
+
+        relay.record_relayed(Hash::new([6u8; 32]), 6);
+        assert!(!relay.settle(&Hash::new([6u8; 32]), RelayOutcome::Accepted));
+        assert_eq!(relay.stats().misrelay_rate(), 0.5);
+        assert!(relay
+            .stats()
+            .export_prometheus()
+            .contains("spirachain_block_fast_relay_revoked_total 1\n"));
+    }
+}
@@ -0,0 +1,170 @@
+// Fork alerting
+// Every time a received block does not build on our chain, the node writes a
+// divergence report: where the chains split, both candidate blocks, who
+// produced them and what we did about it. Reports are appended to a file in
+// the data directory so they survive the restart an operator may do while
+// investigating, served newest first on `/alerts`, and pushed to the runtime
+// config's webhooks as `fork_detected` events.
+
+use parking_lot::Mutex;
+use spirachain_core::{Result, SpiraChainError};
+use spirachain_rpc::server::AlertSource;
+use spirachain_rpc::ForkAlertResponse;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Fork reports, one JSON object per line, kept in the node data directory
+pub const FORK_ALERTS_FILE: &str = "fork_alerts.jsonl";
+/// Reports kept in memory for `/alerts`; the file keeps all of them
+pub const FORK_ALERTS_KEPT: usize = 256;
+
+pub struct ForkAlertLog {
+    path: PathBuf,
+    /// Oldest first
+    alerts: Mutex<VecDeque<ForkAlertResponse>>,
+}
+
+impl ForkAlertLog {
+    /// A missing file means no fork was seen yet. Lines that do not parse
+    /// (a write cut short by a crash) are skipped.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut alerts = VecDeque::new();
+        if path.exists() {
+            let data = std::fs::read_to_string(&path).map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to read {:?}: {}", path, e))
+            })?;
+            for line in data.lines().filter(|line| !line.trim().is_empty()) {
+                match serde_json::from_str(line) {
+                    Ok(alert) => alerts.push_back(alert),
+                    Err(e) => warn!("Skipping unreadable fork alert in {:?}: {}", path, e),
+                }
+                if alerts.len() > FORK_ALERTS_KEPT {
+                    alerts.pop_front();
+                }
+            }
+            // Start the next report on a line of its own
+            if !data.is_empty() && !data.ends_with('\n') {
+                std::fs::OpenOptions::new()
+                    .append(true)
+                    .open(&path)
+                    .and_then(|mut file| writeln!(file))
+                    .map_err(|e| {
+                        SpiraChainError::StorageError(format!("Failed to write {:?}: {}", path, e))
+                    })?;
+            }
+        }
+
+        Ok(Self {
+            path,
+            alerts: Mutex::new(alerts),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `alert` to the file and serve it; it is served even if the
+    /// write fails
+    pub fn record(&self, alert: ForkAlertResponse) -> Result<()> {
+        let line = serde_json::to_string(&alert)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+
+        let mut alerts = self.alerts.lock();
+        alerts.push_back(alert);
+        if alerts.len() > FORK_ALERTS_KEPT {
+            alerts.pop_front();
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to create {:?}: {}", parent, e))
+            })?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to write {:?}: {}", self.path, e))
+            })
+    }
+}
+
+impl AlertSource for ForkAlertLog {
+    fn recent_alerts(&self, limit: usize) -> Vec<ForkAlertResponse> {
+        self.alerts
+            .lock()
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_rpc::ForkDecision;
+
+    fn alert(fork_height: u64, decision: ForkDecision) -> ForkAlertResponse {
+        ForkAlertResponse {
+            detected_at: 1_700_000_000 + fork_height,
+            fork_height,
+            our_height: fork_height + 1,
+            incoming_height: fork_height + 2,
+            our_hash: Some(format!("{:064x}", fork_height)),
+            their_hash: format!("{:064x}", fork_height + 1_000),
+            incoming_block_hash: format!("{:064x}", fork_height + 2_000),
+            our_producer: None,
+            incoming_producer: None,
+            common_ancestor: (decision == ForkDecision::SwitchedChain).then(|| fork_height - 1),
+            decision,
+        }
+    }
+
+    #[test]
+    fn test_fork_alerts_survive_a_restart_newest_first() {
+        let dir =
+            std::env::temp_dir().join(format!("spirachain-fork-alerts-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join(FORK_ALERTS_FILE);
+
+        let log = ForkAlertLog::load(&path).unwrap();
+        assert!(log.recent_alerts(10).is_empty());
+        log.record(alert(10, ForkDecision::KeptOurs)).unwrap();
+        log.record(alert(20, ForkDecision::SwitchedChain)).unwrap();
+
+        // A torn last line from a crash does not lose the others
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "{{\"detected_at\":").unwrap();
+        drop(file);
+
+        let log = ForkAlertLog::load(&path).unwrap();
+        log.record(alert(30, ForkDecision::RefusedBelowCheckpoint))
+            .unwrap();
+        let heights: Vec<u64> = log
+            .recent_alerts(10)
+            .iter()
+            .map(|alert| alert.fork_height)
+            .collect();
+        assert_eq!(heights, vec![30, 20, 10]);
+        assert_eq!(
+            log.recent_alerts(1),
+            vec![alert(30, ForkDecision::RefusedBelowCheckpoint)]
+        );
+        assert_eq!(
+            ForkAlertLog::load(&path).unwrap().recent_alerts(10).len(),
+            3
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
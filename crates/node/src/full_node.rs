@@ -1,13 +1,14 @@
-use crate::{BlockStorage, Mempool, NodeConfig, WorldState};
+use crate::{BlockStorage, DataDirLock, Mempool, NodeConfig, WorldState};
 use parking_lot::RwLock;
 use spirachain_consensus::ProofOfSpiral;
-use spirachain_core::{Address, Amount, Block, Hash, Result, Transaction};
+use spirachain_core::{Address, Amount, Block, ChainParams, Hash, Result, Transaction};
 use std::sync::Arc;
 use tokio::time::{interval, Duration};
 use tracing::{error, info};
 
 pub struct FullNode {
     config: NodeConfig,
+    _data_lock: DataDirLock,
     mempool: Mempool,
     state: Arc<RwLock<WorldState>>,
     storage: BlockStorage,
@@ -17,16 +18,22 @@ pub struct FullNode {
 
 impl FullNode {
     pub fn new(config: NodeConfig) -> Result<Self> {
+        let data_lock = DataDirLock::acquire(&config.data_dir)?;
         let storage = BlockStorage::new(&config.data_dir)?;
         let consensus = ProofOfSpiral::new(
             spirachain_core::MIN_SPIRAL_COMPLEXITY,
             spirachain_core::MAX_SPIRAL_JUMP,
-        );
+        )
+        .with_chain_params(ChainParams::for_network(&config.network));
+        let state = WorldState::for_network(&config.network);
+        let mempool = Mempool::default().with_network(&config.network);
+        mempool.set_chain_height(storage.get_chain_height()?);
 
         Ok(Self {
             config,
-            mempool: Mempool::default(),
-            state: Arc::new(RwLock::new(WorldState::default())),
+            _data_lock: data_lock,
+            mempool,
+            state: Arc::new(RwLock::new(state)),
             storage,
             consensus,
             is_running: Arc::new(RwLock::new(false)),
@@ -99,11 +106,11 @@ impl FullNode {
         {
             let mut state = self.state.write();
             for tx in &block.transactions {
-                state.transfer(&tx.from, &tx.to, tx.amount)?;
-                state.increment_nonce(&tx.from);
+                state.apply_transaction(tx)?;
             }
             state.set_height(block.header.block_height);
         }
+        self.mempool.set_chain_height(block.header.block_height);
 
         info!(
             "✅ Block {} validated and stored",
@@ -122,6 +129,9 @@ impl FullNode {
         );
 
         tx.validate()?;
+        let next_height = self.storage.get_chain_height()? + 1;
+        tx.validate_fork_id(&self.config.network, next_height)?;
+        tx.validate_schedule_admission(next_height)?;
         self.mempool.add_transaction_sync(tx)?;
 
         Ok(())
@@ -0,0 +1,198 @@
+// Chain head cache
+// The validator loop, block production and every received block read the tip
+// several times per slot, and each read walked the height index to its last
+// entry and deserialized the whole block. The tip, and the headers of the last
+// RECENT_HEADERS_CACHED blocks, are kept in memory instead: loaded on the first
+// read, then kept current by every block the storage writes. Bulk rewrites of
+// the database (a snapshot restore) clear it.
+
+use parking_lot::RwLock;
+use spirachain_core::{Block, BlockHeader, Result};
+use std::collections::BTreeMap;
+
+/// Headers below the tip kept in memory
+pub const RECENT_HEADERS_CACHED: usize = 64;
+
+#[derive(Default)]
+pub struct ChainHeadCache {
+    state: RwLock<HeadState>,
+}
+
+#[derive(Default)]
+struct HeadState {
+    /// `None` until loaded, `Some(None)` for an empty chain
+    tip: Option<Option<Block>>,
+    headers: BTreeMap<u64, BlockHeader>,
+}
+
+impl HeadState {
+    fn tip_height(&self) -> Option<u64> {
+        self.tip
+            .as_ref()
+            .and_then(|tip| tip.as_ref())
+            .map(|tip| tip.header.block_height)
+    }
+
+    fn is_recent(&self, height: u64) -> bool {
+        self.tip_height()
+            .is_some_and(|tip| height + RECENT_HEADERS_CACHED as u64 > tip)
+    }
+
+    fn trim(&mut self) {
+        if let Some(tip) = self.tip_height() {
+            let oldest = (tip + 1).saturating_sub(RECENT_HEADERS_CACHED as u64);
+            self.headers = self.headers.split_off(&oldest);
+        }
+    }
+}
+
+impl ChainHeadCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached tip, or the one `load` reads from the database. Loading
+    /// holds the cache, so a block stored meanwhile cannot be overwritten by
+    /// the older tip.
+    pub fn tip_or_load(
+        &self,
+        load: impl FnOnce() -> Result<Option<Block>>,
+    ) -> Result<Option<Block>> {
+        if let Some(tip) = &self.state.read().tip {
+            return Ok(tip.clone());
+        }
+
+        let mut state = self.state.write();
+        if let Some(tip) = &state.tip {
+            return Ok(tip.clone());
+        }
+        let tip = load()?;
+        if let Some(block) = &tip {
+            state
+                .headers
+                .insert(block.header.block_height, block.header.clone());
+        }
+        state.tip = Some(tip.clone());
+        state.trim();
+        Ok(tip)
+    }
+
+    /// Height of the cached tip, `None` if not loaded or the chain is empty
+    pub fn tip_height(&self) -> Option<u64> {
+        self.state.read().tip_height()
+    }
+
+    /// The header at `height` if recent, else the one `load` reads from the
+    /// database
+    pub fn header_or_load(
+        &self,
+        height: u64,
+        load: impl FnOnce() -> Result<Option<BlockHeader>>,
+    ) -> Result<Option<BlockHeader>> {
+        if let Some(header) = self.state.read().headers.get(&height) {
+            return Ok(Some(header.clone()));
+        }
+
+        let mut state = self.state.write();
+        if let Some(header) = state.headers.get(&height) {
+            return Ok(Some(header.clone()));
+        }
+        let header = load()?;
+        if let Some(header) = &header {
+            if state.is_recent(height) {
+                state.headers.insert(height, header.clone());
+            }
+        }
+        Ok(header)
+    }
+
+    /// Follow a block just written to the database. It becomes the tip
+    /// unless a higher block is stored, and replaces any header at its height.
+    pub fn record_stored(&self, block: &Block) {
+        let mut state = self.state.write();
+        let height = block.header.block_height;
+        match &state.tip {
+            // Nothing read yet: the next read loads the tip
+            None => {}
+            Some(Some(tip)) if tip.header.block_height > height => {}
+            Some(_) => state.tip = Some(Some(block.clone())),
+        }
+        if state.is_recent(height) {
+            state.headers.insert(height, block.header.clone());
+        }
+        state.trim();
+    }
+
+    /// Forget everything; the next reads go to the database
+    pub fn clear(&self) {
+        *self.state.write() = HeadState::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{ChainBuilder, SpiraChainError};
+
+    #[test]
+    fn test_tip_follows_stored_blocks() {
+        let chain = ChainBuilder::new("testnet").build(RECENT_HEADERS_CACHED as u64 + 10);
+        let cache = ChainHeadCache::new();
+        let unreachable = || -> Result<Option<Block>> {
+            Err(SpiraChainError::StorageError(
+                "read the database".to_string(),
+            ))
+        };
+
+        // Loaded once, then served from memory
+        let tip = cache
+            .tip_or_load(|| Ok(Some(chain.block(5).clone())))
+            .unwrap();
+        assert_eq!(tip.unwrap().hash(), chain.block(5).hash());
+        assert!(cache.tip_or_load(unreachable).is_ok());
+
+        for height in 6..=chain.tip().header.block_height {
+            cache.record_stored(chain.block(height));
+        }
+        let tip = cache.tip_or_load(unreachable).unwrap().unwrap();
+        assert_eq!(tip.hash(), chain.tip().hash());
+        assert_eq!(cache.tip_height(), Some(chain.tip().header.block_height));
+
+        // A block rewritten below the tip replaces its header only
+        let mut replacement = chain.block(70).clone();
+        replacement.header.nonce += 1;
+        cache.record_stored(&replacement);
+        assert_eq!(cache.tip_height(), Some(chain.tip().header.block_height));
+        let header = cache
+            .header_or_load(70, || panic!("70 is recent"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.hash(), replacement.hash());
+
+        // Old headers fall out of the window and are read again
+        let loaded = cache
+            .header_or_load(5, || Ok(Some(chain.block(5).header.clone())))
+            .unwrap();
+        assert_eq!(loaded.unwrap().hash(), chain.block(5).hash());
+        assert!(cache.header_or_load(5, || Ok(None)).unwrap().is_none());
+
+        cache.clear();
+        assert_eq!(cache.tip_height(), None);
+        assert!(cache.tip_or_load(unreachable).is_err());
+    }
+
+    #[test]
+    fn test_empty_chain_is_cached_until_genesis() {
+        let chain = ChainBuilder::new("testnet").build(0);
+        let cache = ChainHeadCache::new();
+        assert!(cache.tip_or_load(|| Ok(None)).unwrap().is_none());
+        assert!(cache
+            .tip_or_load(|| panic!("empty chain is cached"))
+            .unwrap()
+            .is_none());
+
+        cache.record_stored(chain.genesis());
+        let tip = cache.tip_or_load(|| panic!("genesis is cached")).unwrap();
+        assert_eq!(tip.unwrap().hash(), chain.genesis().hash());
+    }
+}
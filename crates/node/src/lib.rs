@@ -1,17 +1,70 @@
+pub mod admin;
+pub mod analytics;
+pub mod at_rest;
+pub mod backup;
+pub mod block_validation;
+pub mod build_info;
+pub mod chain_verify;
+pub mod compaction;
+pub mod data_lock;
+pub mod dry_run;
+pub mod fast_relay;
+pub mod fork_alerts;
 pub mod full_node;
+pub mod head_cache;
 pub mod light_node;
+pub mod local_model;
+pub mod log_throttle;
 pub mod mempool;
+pub mod misbehavior;
+pub mod pi_service;
+pub mod reset;
+pub mod runtime_config;
+pub mod signing_protection;
+pub mod simulator;
+pub mod slot_schedule;
 pub mod state;
+pub mod state_cache;
+pub mod state_drift;
 pub mod storage;
+pub mod telemetry;
 pub mod validator_node;
 
+pub use admin::*;
+pub use analytics::*;
+pub use at_rest::*;
+pub use backup::*;
+pub use block_validation::*;
+pub use build_info::*;
+pub use chain_verify::*;
+pub use compaction::*;
+pub use data_lock::*;
+pub use dry_run::*;
+pub use fast_relay::*;
+pub use fork_alerts::*;
 pub use full_node::*;
+pub use head_cache::*;
 pub use light_node::*;
+pub use local_model::*;
+pub use log_throttle::*;
 pub use mempool::*;
+pub use misbehavior::*;
+pub use pi_service::*;
+pub use reset::*;
+pub use runtime_config::*;
+pub use signing_protection::*;
+pub use simulator::*;
+pub use slot_schedule::*;
 pub use state::*;
+pub use state_cache::*;
+pub use state_drift::*;
 pub use storage::*;
+pub use telemetry::*;
 pub use validator_node::*;
 
+#[cfg(test)]
+mod byzantine_tests;
+
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -22,12 +75,32 @@ pub enum NodeType {
     Archive,
 }
 
+impl NodeType {
+    /// Role advertised to peers, which decides what they may ask us for
+    pub fn network_role(&self) -> spirachain_network::NodeRole {
+        match self {
+            NodeType::Validator => spirachain_network::NodeRole::Validator,
+            NodeType::Full => spirachain_network::NodeRole::Full,
+            NodeType::Light => spirachain_network::NodeRole::Light,
+            NodeType::Archive => spirachain_network::NodeRole::Archive,
+        }
+    }
+}
+
 pub struct NodeConfig {
     pub node_type: NodeType,
     pub data_dir: PathBuf,
     pub network_addr: String,
     pub rpc_addr: String,
     pub network: String, // "testnet" or "mainnet"
+    /// Sign blocks even when the double-sign protection file says we already did
+    pub allow_double_sign: bool,
+    /// Follow the chain and simulate our slots without signing or broadcasting
+    pub dry_run: bool,
+    /// Seal the persisted mempool and semantic side-stores, see [`AtRestPolicy`]
+    pub encrypt_at_rest: bool,
+    /// Never write semantic fields to disk, see [`AtRestPolicy`]
+    pub privacy_mode: bool,
 }
 
 impl Default for NodeConfig {
@@ -38,6 +111,10 @@ impl Default for NodeConfig {
             network_addr: "0.0.0.0:30303".to_string(),
             rpc_addr: "127.0.0.1:8545".to_string(),
             network: "testnet".to_string(), // Default to testnet
+            allow_double_sign: false,
+            dry_run: false,
+            encrypt_at_rest: false,
+            privacy_mode: false,
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::NodeConfig;
-use spirachain_core::{BlockHeader, Hash, Result};
+use spirachain_core::{Address, BlockHeader, Hash, MerkleProof, Result};
 use std::collections::HashMap;
 use tracing::info;
 
@@ -54,23 +54,45 @@ impl LightNode {
 
     pub fn verify_spv_proof(&self, tx_hash: &Hash, proof: &[Hash], block_height: u64) -> bool {
         if let Some(header) = self.headers.get(&block_height) {
-            let computed_root = Self::compute_merkle_root_from_proof(tx_hash, proof);
+            let computed_root =
+                Self::compute_merkle_root_from_proof(tx_hash, proof, header.version);
             computed_root == header.merkle_root
         } else {
             false
         }
     }
 
-    fn compute_merkle_root_from_proof(tx_hash: &Hash, proof: &[Hash]) -> Hash {
-        let mut current = *tx_hash;
+    /// Check a proof from `/block/:height/proof/:index` against the stored header
+    pub fn verify_tx_proof(&self, tx_hash: &Hash, proof: &MerkleProof, block_height: u64) -> bool {
+        self.headers
+            .get(&block_height)
+            .is_some_and(|header| proof.verify(tx_hash, &header.merkle_root, header.version))
+    }
+
+    /// Stored heights whose event bloom may involve `address`, i.e. the blocks
+    /// worth fetching. Headers without a bloom cannot be ruled out.
+    pub fn heights_for_address(&self, address: &Address) -> Vec<u64> {
+        let mut heights: Vec<u64> = self
+            .headers
+            .iter()
+            .filter(|(_, header)| {
+                header
+                    .event_bloom()
+                    .is_none_or(|bloom| bloom.may_contain_address(address))
+            })
+            .map(|(height, _)| *height)
+            .collect();
+        heights.sort_unstable();
+        heights
+    }
+
+    fn compute_merkle_root_from_proof(tx_hash: &Hash, proof: &[Hash], version: u64) -> Hash {
+        let mut current = spirachain_core::merkle_leaf(tx_hash.as_bytes(), version);
 
         for sibling in proof {
-            let mut combined = Vec::with_capacity(64);
-            combined.extend_from_slice(current.as_bytes());
-            combined.extend_from_slice(sibling.as_bytes());
-            current = Hash::from(blake3::hash(&combined));
+            current = spirachain_core::merkle_node(&current, sibling.as_bytes(), version);
         }
 
-        current
+        Hash::new(current)
     }
 }
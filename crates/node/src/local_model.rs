@@ -0,0 +1,137 @@
+// Local semantic model
+// The embedding model this node scores coherence with, declared by the
+// operator in SEMANTIC_MODEL_FILE in the data directory:
+//   {"name": "...", "version": "...", "weights_hash": "0x<blake3 of the weights>"}
+// Before producing a block the node compares it with the model the guardians
+// approved for the block's epoch and scores neutrally when they differ. A
+// missing file is a model nobody approved.
+
+use serde::Deserialize;
+use spirachain_core::{Hash, Result, SemanticModel, SemanticScoring, SpiraChainError};
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Local embedding model, looked up in the node data directory
+pub const SEMANTIC_MODEL_FILE: &str = "semantic_model.json";
+
+#[derive(Deserialize)]
+struct ModelFile {
+    name: String,
+    version: String,
+    weights_hash: String,
+}
+
+pub struct LocalSemanticModel {
+    model: Option<SemanticModel>,
+    scoring: Option<SemanticScoring>,
+}
+
+impl LocalSemanticModel {
+    pub fn new(model: Option<SemanticModel>) -> Self {
+        Self {
+            model,
+            scoring: None,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(None));
+        }
+        let invalid = |reason: String| {
+            SpiraChainError::Config(format!("Invalid semantic model {:?}: {}", path, reason))
+        };
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to read {:?}: {}", path, e))
+        })?;
+        let file: ModelFile = serde_json::from_str(&data).map_err(|e| invalid(e.to_string()))?;
+        let weights = hex::decode(file.weights_hash.trim_start_matches("0x"))
+            .map_err(|e| invalid(format!("weights_hash: {}", e)))?;
+        let model = SemanticModel {
+            name: file.name,
+            version: file.version,
+            weights_hash: Hash::from_slice(&weights)
+                .map_err(|e| invalid(format!("weights_hash: {}", e)))?,
+        };
+        model.validate().map_err(|e| invalid(e.to_string()))?;
+        Ok(Self::new(Some(model)))
+    }
+
+    pub fn model(&self) -> Option<&SemanticModel> {
+        self.model.as_ref()
+    }
+
+    /// Scoring against `approved`, the model in force for the next block;
+    /// logs whenever it changes
+    pub fn scoring(&mut self, approved: Option<&SemanticModel>) -> SemanticScoring {
+        let scoring = SemanticScoring::for_models(approved, self.model.as_ref());
+        if self.scoring != Some(scoring) {
+            match (scoring, approved) {
+                (SemanticScoring::Neutral, Some(approved)) => warn!(
+                    "🧠 Local embedding model {} does not match the approved {} {} ({}); scoring semantic coherence neutrally",
+                    self.model
+                        .as_ref()
+                        .map(|model| format!("{} {}", model.name, model.version))
+                        .unwrap_or_else(|| "(none declared)".to_string()),
+                    approved.name,
+                    approved.version,
+                    approved.commitment()
+                ),
+                (SemanticScoring::Model, Some(approved)) => info!(
+                    "🧠 Scoring with the approved embedding model {} {}",
+                    approved.name, approved.version
+                ),
+                _ => {}
+            }
+            self.scoring = Some(scoring);
+        }
+        scoring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_model_file() {
+        let dir =
+            std::env::temp_dir().join(format!("spirachain-local-model-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SEMANTIC_MODEL_FILE);
+
+        let mut missing = LocalSemanticModel::load(&path).unwrap();
+        assert!(missing.model().is_none());
+        assert_eq!(missing.scoring(None), SemanticScoring::Model);
+
+        let weights = Hash::from(blake3::hash(b"weights"));
+        std::fs::write(
+            &path,
+            format!(
+                r#"{{"name": "spirapi-embed", "version": "1.2", "weights_hash": "{}"}}"#,
+                weights
+            ),
+        )
+        .unwrap();
+        let mut local = LocalSemanticModel::load(&path).unwrap();
+        let approved = local.model().unwrap().clone();
+        assert_eq!(approved.weights_hash, weights);
+        assert_eq!(local.scoring(Some(&approved)), SemanticScoring::Model);
+        assert_eq!(missing.scoring(Some(&approved)), SemanticScoring::Neutral);
+
+        let upgraded = SemanticModel {
+            version: "1.3".to_string(),
+            ..approved
+        };
+        assert_eq!(local.scoring(Some(&upgraded)), SemanticScoring::Neutral);
+
+        std::fs::write(
+            &path,
+            r#"{"name": "x", "version": "1", "weights_hash": "0x12"}"#,
+        )
+        .unwrap();
+        assert!(LocalSemanticModel::load(&path).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
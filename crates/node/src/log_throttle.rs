@@ -0,0 +1,177 @@
+// Repeated warning throttle
+// During forks and sync the same warning can fire thousands of times a
+// minute ("Failed to replay transaction ..."), burying everything else in the
+// log. Warnings logged through `warn_throttled!` are keyed by their format
+// string: the first occurrence is logged, then only every
+// WARNING_LOG_EVERY-th one or the first after WARNING_LOG_INTERVAL, each
+// carrying the number of occurrences suppressed since the previous line.
+// Every occurrence is still counted and exported on `/metrics`.
+
+use parking_lot::Mutex;
+use spirachain_rpc::server::MetricsSource;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Log one of every this many occurrences of a repeated warning
+pub const WARNING_LOG_EVERY: u64 = 100;
+
+/// Log a repeated warning again once this long has passed since its last line
+pub const WARNING_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+struct WarningCount {
+    total: u64,
+    suppressed: u64,
+    last_logged: Instant,
+}
+
+pub struct WarningThrottle {
+    every: u64,
+    interval: Duration,
+    warnings: Mutex<BTreeMap<&'static str, WarningCount>>,
+}
+
+impl WarningThrottle {
+    pub fn new(every: u64, interval: Duration) -> Self {
+        Self {
+            every: every.max(1),
+            interval,
+            warnings: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Count an occurrence of `key` at `now`. Returns the number of
+    /// occurrences suppressed since the last logged one if this one should be
+    /// logged, `None` if it should be dropped.
+    pub fn note(&self, key: &'static str, now: Instant) -> Option<u64> {
+        let mut warnings = self.warnings.lock();
+        let count = match warnings.get_mut(key) {
+            Some(count) => count,
+            None => {
+                warnings.insert(
+                    key,
+                    WarningCount {
+                        total: 1,
+                        suppressed: 0,
+                        last_logged: now,
+                    },
+                );
+                return Some(0);
+            }
+        };
+        count.total += 1;
+        if count.suppressed + 1 >= self.every
+            || now.saturating_duration_since(count.last_logged) >= self.interval
+        {
+            let suppressed = count.suppressed;
+            count.suppressed = 0;
+            count.last_logged = now;
+            Some(suppressed)
+        } else {
+            count.suppressed += 1;
+            None
+        }
+    }
+
+    /// Occurrences of `key` so far, logged or not
+    pub fn total(&self, key: &str) -> u64 {
+        self.warnings
+            .lock()
+            .get(key)
+            .map(|count| count.total)
+            .unwrap_or(0)
+    }
+}
+
+impl MetricsSource for WarningThrottle {
+    fn export_prometheus(&self) -> String {
+        let warnings = self.warnings.lock();
+        let mut out = String::from(
+            "# HELP spirachain_log_warnings_total Occurrences of each throttled warning\n\
+             # TYPE spirachain_log_warnings_total counter\n",
+        );
+        for (key, count) in warnings.iter() {
+            out.push_str(&format!(
+                "spirachain_log_warnings_total{{message=\"{}\"}} {}\n",
+                escape_label(key),
+                count.total
+            ));
+        }
+        out.push_str(
+            "# HELP spirachain_log_warnings_pending Occurrences suppressed since the warning was last logged\n\
+             # TYPE spirachain_log_warnings_pending gauge\n",
+        );
+        for (key, count) in warnings.iter() {
+            out.push_str(&format!(
+                "spirachain_log_warnings_pending{{message=\"{}\"}} {}\n",
+                escape_label(key),
+                count.suppressed
+            ));
+        }
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Process-wide throttle used by `warn_throttled!`
+pub fn warning_throttle() -> &'static WarningThrottle {
+    static THROTTLE: OnceLock<WarningThrottle> = OnceLock::new();
+    THROTTLE.get_or_init(|| WarningThrottle::new(WARNING_LOG_EVERY, WARNING_LOG_INTERVAL))
+}
+
+/// `warn!` for warnings that can repeat in bursts, throttled per format string
+#[macro_export]
+macro_rules! warn_throttled {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {
+        if let Some(suppressed) =
+            $crate::warning_throttle().note($fmt, ::std::time::Instant::now())
+        {
+            if suppressed > 0 {
+                ::tracing::warn!(
+                    concat!($fmt, " ({} similar warnings suppressed)"),
+                    $($arg,)*
+                    suppressed
+                );
+            } else {
+                ::tracing::warn!($fmt $(, $arg)*);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_warning_is_throttled() {
+        let throttle = WarningThrottle::new(10, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert_eq!(throttle.note("replay", start), Some(0));
+        let logged: Vec<u64> = (0..25)
+            .filter_map(|_| throttle.note("replay", start))
+            .collect();
+        assert_eq!(logged, vec![9, 9]);
+        assert_eq!(throttle.total("replay"), 26);
+
+        // Other warnings are counted separately
+        assert_eq!(throttle.note("fork", start), Some(0));
+
+        // After the interval the next occurrence is logged with what was held back
+        assert_eq!(
+            throttle.note("replay", start + Duration::from_secs(61)),
+            Some(5)
+        );
+
+        let metrics = throttle.export_prometheus();
+        assert!(metrics.contains("spirachain_log_warnings_total{message=\"replay\"} 27"));
+        assert!(metrics.contains("spirachain_log_warnings_pending{message=\"replay\"} 0"));
+    }
+}
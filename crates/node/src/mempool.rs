@@ -1,7 +1,13 @@
 use parking_lot::RwLock;
-use spirachain_core::{Hash, Result, SpiraChainError, Transaction};
+use spirachain_core::{
+    lane_admission, prioritize_lanes, Hash, Result, SpiraChainError, Transaction,
+};
+use spirachain_rpc::{
+    admit_transaction, is_near_duplicate, DropReason, MempoolMonitor, ResourceGuard,
+};
 use spirachain_semantic::SemanticProcessor;
 use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -10,6 +16,9 @@ pub struct Mempool {
     pending_queue: Arc<RwLock<VecDeque<Hash>>>,
     max_size: usize,
     semantic_processor: Arc<SemanticProcessor>,
+    network: String,
+    /// Chain tip; admission applies the rules of the block after it
+    chain_height: Arc<AtomicU64>,
 }
 
 impl Mempool {
@@ -19,13 +28,33 @@ impl Mempool {
             pending_queue: Arc::new(RwLock::new(VecDeque::new())),
             max_size,
             semantic_processor: Arc::new(SemanticProcessor::default()),
+            network: "testnet".to_string(),
+            chain_height: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Admit transactions under `network`'s fork schedule
+    pub fn with_network(mut self, network: &str) -> Self {
+        self.network = network.to_string();
+        self
+    }
+
+    pub fn set_chain_height(&self, height: u64) {
+        self.chain_height.store(height, Ordering::Relaxed);
+    }
+
     pub async fn add_transaction(&self, mut tx: Transaction) -> Result<()> {
+        Self::check_semantic_admission(&tx)?;
+
         // Enrichissement sémantique si purpose présent
         if !tx.purpose.is_empty() {
             match self.semantic_processor.enrich_transaction(tx.clone()).await {
+                // Enrichment must stay within limits and be covered by the fee paid
+                Ok(enriched_tx) if Self::check_semantic_admission(&enriched_tx).is_err() => {
+                    tracing::debug!(
+                        "Fee does not cover semantic enrichment, keeping raw transaction"
+                    );
+                }
                 Ok(enriched_tx) => {
                     tracing::debug!("Transaction enriched with semantic data");
                     tx = enriched_tx;
@@ -37,23 +66,7 @@ impl Mempool {
             }
         }
 
-        let tx_hash = tx.hash();
-
-        let mut txs = self.transactions.write();
-        let mut queue = self.pending_queue.write();
-
-        if txs.len() >= self.max_size {
-            return Err(SpiraChainError::Internal("Mempool full".to_string()));
-        }
-
-        if txs.contains_key(&tx_hash) {
-            return Err(SpiraChainError::InvalidTransaction(
-                "Transaction already in mempool".to_string(),
-            ));
-        }
-
-        txs.insert(tx_hash, tx);
-        queue.push_back(tx_hash);
+        let tx_hash = self.insert(tx)?;
 
         tracing::info!(
             "Added transaction {} to mempool",
@@ -65,28 +78,72 @@ impl Mempool {
 
     pub fn add_transaction_sync(&self, tx: Transaction) -> Result<()> {
         // Version synchrone sans enrichissement pour compatibilité
+        Self::check_semantic_admission(&tx)?;
+        let tx_hash = self.insert(tx)?;
+
+        tracing::info!(
+            "Added transaction {} to mempool (sync)",
+            hex::encode(tx_hash.as_bytes())
+        );
+
+        Ok(())
+    }
+
+    /// Only the highest-fee instance of near-duplicates is kept: a cheaper pending
+    /// one is replaced, otherwise `tx` is rejected
+    fn insert(&self, tx: Transaction) -> Result<Hash> {
+        // Cached, so the block including it does not verify it again
+        let next_height = self.chain_height.load(Ordering::Relaxed) + 1;
+        tx.verify_sender_signature_at(&self.network, next_height)?;
         let tx_hash = tx.hash();
 
         let mut txs = self.transactions.write();
         let mut queue = self.pending_queue.write();
 
-        if txs.len() >= self.max_size {
-            return Err(SpiraChainError::Internal("Mempool full".to_string()));
-        }
-
         if txs.contains_key(&tx_hash) {
             return Err(SpiraChainError::InvalidTransaction(
                 "Transaction already in mempool".to_string(),
             ));
         }
 
+        let duplicate = txs
+            .iter()
+            .find(|(_, pending)| is_near_duplicate(pending, &tx))
+            .map(|(hash, pending)| (*hash, pending.fee));
+        match duplicate {
+            Some((hash, fee)) if fee >= tx.fee => {
+                return Err(SpiraChainError::NearDuplicate(hash.to_string()));
+            }
+            Some((hash, _)) => {
+                txs.remove(&hash);
+                queue.retain(|h| *h != hash);
+                tracing::debug!("Transaction {} superseded by {}", hash, tx_hash);
+            }
+            None => {}
+        }
+
+        // Priority lane transactions are held beyond max_size up to their lane's quota
+        let priority = lane_admission(txs.values(), &tx)?;
+        if !priority && txs.len() >= self.max_size {
+            return Err(SpiraChainError::MempoolFull);
+        }
+
         txs.insert(tx_hash, tx);
         queue.push_back(tx_hash);
+        Ok(tx_hash)
+    }
 
-        tracing::info!(
-            "Added transaction {} to mempool (sync)",
-            hex::encode(tx_hash.as_bytes())
-        );
+    /// Semantic fields within size limits and paid for by the fee
+    fn check_semantic_admission(tx: &Transaction) -> Result<()> {
+        tx.validate_semantic_fields()?;
+
+        let min_fee = tx.min_fee();
+        if tx.fee < min_fee {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Fee too low for semantic data: {} < {}",
+                tx.fee, min_fee
+            )));
+        }
 
         Ok(())
     }
@@ -95,11 +152,13 @@ impl Mempool {
         let txs = self.transactions.read();
         let queue = self.pending_queue.read();
 
-        queue
+        let mut pending: Vec<Transaction> = queue
             .iter()
-            .take(max_count)
             .filter_map(|hash| txs.get(hash).cloned())
-            .collect()
+            .collect();
+        prioritize_lanes(&mut pending);
+        pending.truncate(max_count);
+        pending
     }
 
     pub fn remove_transactions(&self, tx_hashes: &[Hash]) {
@@ -141,3 +200,38 @@ impl Default for Mempool {
         Self::new(10000)
     }
 }
+
+/// Admit a transaction gossiped by a peer into `mempool`, which holds
+/// `max_size` transactions, once it passes the checks a submission would.
+/// Rejections are recorded in `monitor`. A transaction already pending is
+/// ignored: peers relay the same one over and over.
+pub fn admit_network_transaction(
+    mempool: &mut Vec<Transaction>,
+    tx: Transaction,
+    network: &str,
+    next_height: u64,
+    max_size: usize,
+    resource_guard: &ResourceGuard,
+    monitor: &MempoolMonitor,
+) -> Result<()> {
+    if mempool.iter().any(|pending| pending.tx_hash == tx.tx_hash) {
+        return Ok(());
+    }
+
+    if let Err(e) = tx
+        .validate()
+        .and_then(|_| tx.verify_sender_signature_at(network, next_height))
+        .and_then(|_| tx.validate_fork_id(network, next_height))
+        .and_then(|_| tx.validate_schedule_admission(next_height))
+    {
+        monitor.record(&tx.tx_hash, DropReason::Invalid, e.to_string());
+        return Err(e);
+    }
+
+    if let Err(e) = resource_guard.admit_transaction(tx.serialize().len()) {
+        monitor.record(&tx.tx_hash, DropReason::OverQuota, e.to_string());
+        return Err(e);
+    }
+
+    admit_transaction(mempool, tx, max_size, monitor)
+}
@@ -0,0 +1,120 @@
+// Validator misbehavior monitoring
+// Every received block header goes through the equivocation detector before
+// anything else, including blocks we already have: a producer's second block
+// for a height is exactly the one we would otherwise skip. Accepted blocks
+// are compared with the slot schedule, and the leaders of the slots they
+// skipped are counted as having missed them, whether they were offline or
+// withheld their block. Blocks rejected for a wrong state root are counted
+// too. All of it is exported on `/metrics`.
+
+use parking_lot::Mutex;
+use spirachain_consensus::{
+    EquivocationDetector, EquivocationEvidence, SlotConsensus, SLOTS_PER_EPOCH,
+};
+use spirachain_core::{Address, BlockHeader};
+use spirachain_rpc::server::MetricsSource;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[derive(Default)]
+pub struct MisbehaviorStats {
+    equivocations: AtomicU64,
+    state_root_rejections: AtomicU64,
+    missed_slots: Mutex<BTreeMap<Address, u64>>,
+}
+
+impl MisbehaviorStats {
+    pub fn equivocations(&self) -> u64 {
+        self.equivocations.load(Ordering::Relaxed)
+    }
+
+    pub fn state_root_rejections(&self) -> u64 {
+        self.state_root_rejections.load(Ordering::Relaxed)
+    }
+
+    pub fn missed_slots(&self, validator: &Address) -> u64 {
+        self.missed_slots
+            .lock()
+            .get(validator)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl MetricsSource for MisbehaviorStats {
+    fn export_prometheus(&self) -> String {
+        let mut out = format!(
+            "# HELP spirachain_equivocations_detected_total Producers caught signing two blocks for one height\n\
+             # TYPE spirachain_equivocations_detected_total counter\n\
+             spirachain_equivocations_detected_total {}\n\
+             # HELP spirachain_block_state_root_rejections_total Received blocks rejected for a wrong state root\n\
+             # TYPE spirachain_block_state_root_rejections_total counter\n\
+             spirachain_block_state_root_rejections_total {}\n\
+             # HELP spirachain_validator_missed_slots_total Slots a validator led without a block on our chain\n\
+             # TYPE spirachain_validator_missed_slots_total counter\n",
+            self.equivocations(),
+            self.state_root_rejections()
+        );
+        for (validator, missed) in self.missed_slots.lock().iter() {
+            out.push_str(&format!(
+                "spirachain_validator_missed_slots_total{{validator=\"{}\"}} {}\n",
+                validator, missed
+            ));
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+pub struct MisbehaviorMonitor {
+    equivocations: EquivocationDetector,
+    stats: Arc<MisbehaviorStats>,
+}
+
+impl MisbehaviorMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> Arc<MisbehaviorStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Check a received header; evidence when its producer equivocated
+    pub fn observe_header(&mut self, header: &BlockHeader) -> Option<EquivocationEvidence> {
+        let evidence = self.equivocations.observe(header)?;
+        self.stats.equivocations.fetch_add(1, Ordering::Relaxed);
+        Some(evidence)
+    }
+
+    /// Count the slots skipped between `parent` and the accepted `block`
+    /// against their leaders, and return them. Only the last epoch's worth of
+    /// a long gap is counted, and none after genesis.
+    pub fn record_missed_slots(
+        &self,
+        slot_consensus: &SlotConsensus,
+        parent: &BlockHeader,
+        block: &BlockHeader,
+    ) -> Vec<(u64, Address)> {
+        if parent.block_height == 0 {
+            return Vec::new();
+        }
+        let block_slot = slot_consensus.slot_at(block.timestamp);
+        let parent_slot = slot_consensus
+            .slot_at(parent.timestamp)
+            .max(block_slot.saturating_sub(SLOTS_PER_EPOCH));
+        let skipped = slot_consensus.skipped_leaders(parent_slot, block_slot);
+        let mut missed = self.stats.missed_slots.lock();
+        for (_, leader) in &skipped {
+            *missed.entry(*leader).or_default() += 1;
+        }
+        skipped
+    }
+
+    pub fn record_state_root_rejection(&self) {
+        self.stats
+            .state_root_rejections
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,160 @@
+// π identifiers as a service
+// Applications get identifiers from the node instead of running the engine
+// themselves. They are derived the way transaction identifiers are, bound to
+// the current fork and a hash of the entity they name, so anyone can verify
+// them with `verify_pi_identifier`. Anchoring timestamps an identifier by
+// recording its hash in a transaction the validator key signs and pays for,
+// which is why it stays off unless the operator enables it.
+
+use crate::RuntimeConfigManager;
+use spirachain_core::{fork_id, Amount, Hash, Result, SpiraChainError, Transaction};
+use spirachain_crypto::KeyPair;
+use spirachain_rpc::server::PiIdentifierService;
+use spirachain_rpc::{GeneratePiIdentifierRequest, GeneratePiIdentifierResponse};
+use spirapi_bridge::{
+    generate_pi_identifier, pi_identifier_hash, PiIdentifierContext, PI_ANCHOR_KEY,
+};
+use std::sync::Arc;
+
+/// Backs `/pi_identifier/generate`
+pub struct NodePiIdentifierService {
+    runtime: Arc<RuntimeConfigManager>,
+    keypair: KeyPair,
+    network: String,
+}
+
+impl NodePiIdentifierService {
+    pub fn new(runtime: Arc<RuntimeConfigManager>, keypair: KeyPair, network: String) -> Self {
+        Self {
+            runtime,
+            keypair,
+            network,
+        }
+    }
+}
+
+impl PiIdentifierService for NodePiIdentifierService {
+    fn generate(
+        &self,
+        request: &GeneratePiIdentifierRequest,
+        next_height: u64,
+    ) -> Result<GeneratePiIdentifierResponse> {
+        let entity = match &request.entity {
+            Some(entity) => blake3::hash(entity.as_bytes()).into(),
+            None => Hash::new(rand::random()),
+        };
+        let ctx = PiIdentifierContext {
+            fork_id: fork_id(&self.network, next_height),
+            entity,
+            timestamp_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        let id = generate_pi_identifier(&ctx, request.length, request.include_spiral)?;
+
+        Ok(GeneratePiIdentifierResponse {
+            identifier_hash: pi_identifier_hash(&id).to_string(),
+            identifier: id.identifier,
+            pi_sequence: id.pi_sequence,
+            spiral_component: id.spiral_component,
+            timestamp_component: id.timestamp_component,
+            uniqueness_score: id.uniqueness_score,
+            entity: ctx.entity.to_string(),
+            fork_id: ctx.fork_id.to_string(),
+            timestamp_ms: ctx.timestamp_ms,
+            anchor_tx_hash: None,
+        })
+    }
+
+    fn anchor_transaction(
+        &self,
+        identifier: &GeneratePiIdentifierResponse,
+        next_height: u64,
+    ) -> Result<Transaction> {
+        if !self.runtime.pi_identifier_anchoring() {
+            return Err(SpiraChainError::InvalidTransaction(
+                "π identifier anchoring is disabled on this node".to_string(),
+            ));
+        }
+        Ok(anchor_transaction(
+            &self.keypair,
+            &self.network,
+            identifier,
+            next_height,
+        ))
+    }
+}
+
+/// Smallest transfer the chain accepts, from `keypair` to itself, carrying
+/// the identifier's hash under [`PI_ANCHOR_KEY`]
+pub fn anchor_transaction(
+    keypair: &KeyPair,
+    network: &str,
+    identifier: &GeneratePiIdentifierResponse,
+    next_height: u64,
+) -> Transaction {
+    let address = keypair.to_address();
+    let hash = blake3::hash(identifier.identifier.as_bytes());
+    let mut tx = Transaction::new(address, address, Amount::new(1), Amount::zero())
+        .with_fork_id(fork_id(network, next_height))
+        .with_extra_data(PI_ANCHOR_KEY, hash.as_bytes().to_vec());
+    tx.fee = tx.min_fee();
+    tx.compute_hash();
+    keypair.sign_transaction(&mut tx);
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirapi_bridge::{verify_pi_identifier, PiIdentifier};
+
+    #[test]
+    fn test_identifiers_verify_and_anchor() {
+        // No file there: the defaults, anchoring off
+        let path = std::env::temp_dir().join(format!("spirachain-pi-{}", std::process::id()));
+        let runtime = Arc::new(RuntimeConfigManager::load(path.join("runtime.json")).unwrap());
+        let keypair = KeyPair::generate();
+        let service = NodePiIdentifierService::new(runtime, keypair.clone(), "testnet".to_string());
+
+        let request = GeneratePiIdentifierRequest {
+            length: 16,
+            include_spiral: true,
+            entity: Some("invoice-42".to_string()),
+            anchor: true,
+        };
+        let response = service.generate(&request, 10).unwrap();
+
+        let id = PiIdentifier {
+            identifier: response.identifier.clone(),
+            pi_sequence: response.pi_sequence.clone(),
+            spiral_component: response.spiral_component.clone(),
+            timestamp_component: response.timestamp_component.clone(),
+            generation_time: 0.0,
+            uniqueness_score: response.uniqueness_score,
+            total_length: response.identifier.len(),
+        };
+        let ctx = PiIdentifierContext {
+            fork_id: fork_id("testnet", 10),
+            entity: blake3::hash(b"invoice-42").into(),
+            timestamp_ms: response.timestamp_ms,
+        };
+        assert!(verify_pi_identifier(&id, &ctx).is_ok());
+        assert_eq!(
+            response.identifier_hash,
+            pi_identifier_hash(&id).to_string()
+        );
+
+        // Anchoring spends the validator's funds, so it is opt-in
+        assert!(service.anchor_transaction(&response, 10).is_err());
+
+        let tx = anchor_transaction(&keypair, "testnet", &response, 10);
+        assert!(tx.validate().is_ok());
+        assert!(tx.validate_fork_id("testnet", 10).is_ok());
+        assert_eq!(
+            tx.extra_data[PI_ANCHOR_KEY],
+            pi_identifier_hash(&id).as_bytes().to_vec()
+        );
+    }
+}
@@ -0,0 +1,227 @@
+// Coordinated testnet reset
+// `spira admin reset` moves a node's chain data into a sibling archive
+// directory and installs the new genesis as `genesis.json`, which the node
+// then builds and accepts instead of the official one. Node-local settings
+// and keys stay; the libp2p identity only goes when asked, so bootstrap lists
+// pinning PeerIds keep working across resets. Mainnet is never touched.
+// A launch ceremony's signed genesis can be installed as `genesis.json` too;
+// it is checked for every genesis validator's signature on startup.
+
+use crate::{NodeStorage, AT_REST_KEY_FILE, RUNTIME_CONFIG_FILE, TELEMETRY_ID_FILE};
+use spirachain_core::{GenesisConfig, Hash, Result, SignedGenesis, SpiraChainError};
+use spirachain_network::NODE_KEY_FILE;
+use std::path::{Path, PathBuf};
+
+/// Genesis installed by a reset, replacing the official one on test networks
+pub const GENESIS_FILE: &str = "genesis.json";
+
+/// Files a reset leaves in place: settings and keys, not chain data
+const KEPT_FILES: [&str; 3] = [RUNTIME_CONFIG_FILE, AT_REST_KEY_FILE, TELEMETRY_ID_FILE];
+
+#[derive(Debug, Clone)]
+pub struct ResetReport {
+    pub archive_dir: PathBuf,
+    pub archived: Vec<String>,
+    pub kept: Vec<String>,
+    pub genesis_hash: Hash,
+}
+
+/// Genesis installed in `data_dir`, if any. A ceremony's [`SignedGenesis`]
+/// is only accepted with every genesis validator's signature. Mainnet only
+/// ever runs the official genesis, so finding any other one there is an error.
+pub fn load_genesis(data_dir: &Path, network: &str) -> Result<Option<GenesisConfig>> {
+    let path = data_dir.join(GENESIS_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| SpiraChainError::io(format!("reading {}", path.display()), e))?;
+
+    if let Ok(signed) = SignedGenesis::from_json(&json) {
+        signed
+            .verify()
+            .map_err(|e| SpiraChainError::Config(format!("Invalid {}: {}", path.display(), e)))?;
+        if network == "mainnet"
+            && !GenesisConfig::verify_genesis_hash(&signed.genesis.create_genesis_block(), "mainnet")
+        {
+            return Err(SpiraChainError::Config(format!(
+                "{} is not the official mainnet genesis",
+                path.display()
+            )));
+        }
+        return Ok(Some(signed.genesis));
+    }
+
+    if network == "mainnet" {
+        return Err(SpiraChainError::Config(format!(
+            "{} found in a mainnet data dir; mainnet only runs the official genesis",
+            path.display()
+        )));
+    }
+    GenesisConfig::from_json(&json)
+        .map(Some)
+        .map_err(|e| SpiraChainError::Config(format!("Invalid {}: {}", path.display(), e)))
+}
+
+/// Archive the chain data in `data_dir` and install `genesis`. Refuses on
+/// mainnet, on a mainnet chain and while a node holds the database.
+pub fn reset_testnet(
+    data_dir: &Path,
+    network: &str,
+    genesis: &GenesisConfig,
+    reset_identity: bool,
+) -> Result<ResetReport> {
+    let genesis_block = genesis.create_genesis_block();
+    if network == "mainnet" || GenesisConfig::verify_genesis_hash(&genesis_block, "mainnet") {
+        return Err(SpiraChainError::Config(
+            "Refusing to reset mainnet".to_string(),
+        ));
+    }
+
+    if data_dir.exists() {
+        let storage = NodeStorage::new(data_dir).map_err(|e| {
+            SpiraChainError::Config(format!(
+                "Cannot open {} ({}); stop the node before resetting",
+                data_dir.display(),
+                e
+            ))
+        })?;
+        if let Some(block) = storage.get_block_by_height(0)? {
+            if GenesisConfig::verify_genesis_hash(&block, "mainnet") {
+                return Err(SpiraChainError::Config(format!(
+                    "{} holds the mainnet chain; refusing to reset it",
+                    data_dir.display()
+                )));
+            }
+        }
+    }
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| SpiraChainError::io(format!("creating {}", data_dir.display()), e))?;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let dir_name = data_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "data".to_string());
+    let archive_dir = data_dir.with_file_name(format!("{}.archive-{}", dir_name, timestamp));
+    std::fs::create_dir_all(&archive_dir)
+        .map_err(|e| SpiraChainError::io(format!("creating {}", archive_dir.display()), e))?;
+
+    let entries = std::fs::read_dir(data_dir)
+        .map_err(|e| SpiraChainError::io(format!("listing {}", data_dir.display()), e))?;
+    let mut archived = Vec::new();
+    let mut kept = Vec::new();
+    for entry in entries {
+        let entry =
+            entry.map_err(|e| SpiraChainError::io(format!("listing {}", data_dir.display()), e))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if KEPT_FILES.contains(&name.as_str()) || (name == NODE_KEY_FILE && !reset_identity) {
+            kept.push(name);
+            continue;
+        }
+
+        let target = archive_dir.join(&name);
+        std::fs::rename(entry.path(), &target)
+            .map_err(|e| SpiraChainError::io(format!("archiving {}", entry.path().display()), e))?;
+        archived.push(name);
+    }
+    archived.sort();
+    kept.sort();
+
+    std::fs::write(data_dir.join(GENESIS_FILE), genesis.to_json())
+        .map_err(|e| SpiraChainError::io(format!("writing {}", GENESIS_FILE), e))?;
+
+    Ok(ResetReport {
+        archive_dir,
+        archived,
+        kept,
+        genesis_hash: genesis_block.hash(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{assemble_genesis, ValidatorRegistration};
+    use spirachain_crypto::KeyPair;
+
+    #[test]
+    fn test_reset_archives_chain_and_installs_genesis() {
+        let root = std::env::temp_dir().join(format!("spirachain-reset-{}", std::process::id()));
+        let data_dir = root.join("data");
+        {
+            let storage = NodeStorage::new(&data_dir).unwrap();
+            storage
+                .store_block(&GenesisConfig::default().create_genesis_block())
+                .unwrap();
+            storage.flush().unwrap();
+        }
+        std::fs::write(data_dir.join(NODE_KEY_FILE), b"key").unwrap();
+        std::fs::write(data_dir.join(RUNTIME_CONFIG_FILE), b"{}").unwrap();
+        std::fs::write(data_dir.join("signing_protection.json"), b"{}").unwrap();
+
+        let mut genesis = GenesisConfig::default();
+        genesis.timestamp += 1;
+        assert!(reset_testnet(&data_dir, "mainnet", &genesis, false).is_err());
+
+        let report = reset_testnet(&data_dir, "testnet", &genesis, false).unwrap();
+        assert_eq!(report.kept, vec![NODE_KEY_FILE, RUNTIME_CONFIG_FILE]);
+        assert!(report
+            .archived
+            .contains(&"signing_protection.json".to_string()));
+        assert!(report.archive_dir.join("signing_protection.json").exists());
+        assert_eq!(report.genesis_hash, genesis.create_genesis_block().hash());
+
+        let installed = load_genesis(&data_dir, "testnet").unwrap().unwrap();
+        assert_eq!(installed.timestamp, genesis.timestamp);
+        assert!(load_genesis(&data_dir, "mainnet").is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_signed_genesis_needs_every_validator() {
+        let data_dir =
+            std::env::temp_dir().join(format!("spirachain-signed-genesis-{}", std::process::id()));
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let template = GenesisConfig::default();
+        let keys = [KeyPair::from_secret([1; 32]).unwrap(), KeyPair::from_secret([2; 32]).unwrap()];
+        let registrations: Vec<ValidatorRegistration> = keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let mut registration = ValidatorRegistration {
+                    name: format!("validator-{}", index),
+                    pubkey: hex::encode(key.public_key().0),
+                    geographic_region: "Europe".to_string(),
+                    stake: template.constants.min_validator_stake,
+                    signature: String::new(),
+                };
+                registration.signature =
+                    hex::encode(key.sign(&registration.signing_message().unwrap()));
+                registration
+            })
+            .collect();
+        let mut signed = SignedGenesis::new(assemble_genesis(&template, &registrations).unwrap());
+
+        signed
+            .add_signature(&keys[0].public_key().0, &keys[0].sign(&signed.signing_message()))
+            .unwrap();
+        std::fs::write(data_dir.join(GENESIS_FILE), signed.to_json()).unwrap();
+        assert!(load_genesis(&data_dir, "testnet").is_err());
+
+        signed
+            .add_signature(&keys[1].public_key().0, &keys[1].sign(&signed.signing_message()))
+            .unwrap();
+        std::fs::write(data_dir.join(GENESIS_FILE), signed.to_json()).unwrap();
+        let installed = load_genesis(&data_dir, "testnet").unwrap().unwrap();
+        assert_eq!(installed.config_hash(), signed.config_hash);
+        assert!(load_genesis(&data_dir, "mainnet").is_err());
+
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,606 @@
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use spirachain_network::{Compression, GossipParams, PeerDiversityParams};
+use spirachain_rpc::{RateLimiter, ResourceGuard, ResourceLimits};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Runtime config file, looked up in the node data directory
+pub const RUNTIME_CONFIG_FILE: &str = "runtime.json";
+
+const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error", "off"];
+
+/// Operational settings that can change while the node runs (SIGHUP or
+/// `POST /admin/reload_config`). Nothing here affects consensus: network,
+/// genesis, rewards and block limits stay fixed for the life of the process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    pub log_level: String,
+    /// Requests per minute per client IP on the RPC server, 0 = unlimited
+    pub rpc_rate_limit_per_minute: u32,
+    pub max_mempool_size: usize,
+    /// libp2p peer IDs that are disconnected and ignored
+    pub banned_peers: Vec<String>,
+    /// URLs that receive a JSON POST for every new block
+    pub webhook_endpoints: Vec<String>,
+    /// Longest wait for a SpiraPi engine call before it counts as stalled
+    pub spirapi_call_timeout_ms: u64,
+    /// p95 SpiraPi latency above which calls fall back to local derivation
+    /// for a while, 0 = never
+    pub spirapi_latency_slo_ms: u64,
+    /// Quota on the block database in MiB, 0 = unlimited. Over it the node
+    /// stops accepting transactions and pauses background analytics.
+    pub max_db_size_mb: u64,
+    /// Quota on pending transaction bytes in MiB, 0 = unlimited
+    pub max_mempool_mb: u64,
+    /// Quota on stored semantic vectors in MiB, 0 = unlimited. Over it the
+    /// oldest vectors are pruned; blocks keep their on-chain hashes.
+    pub max_vector_store_mb: u64,
+    /// Disk IO budget of background storage compaction in MiB/s,
+    /// 0 = unlimited, see [`crate::CompactionScheduler`]
+    pub compaction_io_mb_per_sec: u64,
+    /// Where anonymous node stats are reported, see [`crate::TelemetryReport`].
+    /// Off unless set; removing it and reloading stops reporting.
+    pub telemetry_endpoint: Option<String>,
+    /// Codec for large gossip payloads: "none", "snappy" or "zstd". Peers
+    /// that cannot read it get the next cheaper codec, or none.
+    pub p2p_compression: Compression,
+    /// Gossipsub mesh parameters; read at startup, a reload only records them
+    pub p2p_gossip: GossipParams,
+    /// Outbound minimum, inbound and per-subnet caps and peer rotation,
+    /// see [`spirachain_network::PeerDiversity`]
+    pub p2p_peers: PeerDiversityParams,
+    /// Requests per minute per client IP on `/pi_identifier/generate`,
+    /// on top of `rpc_rate_limit_per_minute`, 0 = unlimited
+    pub pi_identifier_rate_limit_per_minute: u32,
+    /// Let `/pi_identifier/generate` anchor identifiers on-chain, paid for by
+    /// the validator key
+    pub pi_identifier_anchoring: bool,
+    /// Relay compact blocks whose header checks pass before validating them
+    /// in full, revoking any that then fail, see [`crate::FastRelay`]
+    pub fast_block_relay: bool,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            log_level: "info".to_string(),
+            rpc_rate_limit_per_minute: 600,
+            max_mempool_size: 10_000,
+            banned_peers: Vec::new(),
+            webhook_endpoints: Vec::new(),
+            spirapi_call_timeout_ms: 2_000,
+            spirapi_latency_slo_ms: 500,
+            max_db_size_mb: 0,
+            max_mempool_mb: 256,
+            max_vector_store_mb: 0,
+            compaction_io_mb_per_sec: 16,
+            telemetry_endpoint: None,
+            p2p_compression: Compression::Snappy,
+            p2p_gossip: GossipParams::default(),
+            p2p_peers: PeerDiversityParams::default(),
+            pi_identifier_rate_limit_per_minute: 30,
+            pi_identifier_anchoring: false,
+            fast_block_relay: false,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    /// A missing file means defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| SpiraChainError::io(format!("Failed to read {:?}", path), e))?;
+        let config: Self = serde_json::from_str(&data).map_err(|e| {
+            SpiraChainError::SerializationError(format!("Invalid {:?}: {}", path, e))
+        })?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !LOG_LEVELS.contains(&self.log_level.to_lowercase().as_str()) {
+            return Err(SpiraChainError::Config(format!(
+                "Unknown log level '{}' (expected one of {})",
+                self.log_level,
+                LOG_LEVELS.join(", ")
+            )));
+        }
+
+        if self.max_mempool_size == 0 {
+            return Err(SpiraChainError::Config(
+                "max_mempool_size must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.spirapi_call_timeout_ms == 0 {
+            return Err(SpiraChainError::Config(
+                "spirapi_call_timeout_ms must be greater than zero".to_string(),
+            ));
+        }
+
+        if let Some(url) = self
+            .webhook_endpoints
+            .iter()
+            .find(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            return Err(SpiraChainError::Config(format!(
+                "Webhook endpoint must be an http(s) URL: {}",
+                url
+            )));
+        }
+
+        self.p2p_gossip.validate()?;
+        self.p2p_peers.validate()?;
+
+        if let Some(url) = self
+            .telemetry_endpoint
+            .as_ref()
+            .filter(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            return Err(SpiraChainError::Config(format!(
+                "Telemetry endpoint must be an http(s) URL: {}",
+                url
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn resource_limits(&self) -> ResourceLimits {
+        const MIB: u64 = 1024 * 1024;
+        ResourceLimits {
+            max_db_bytes: self.max_db_size_mb.saturating_mul(MIB),
+            max_mempool_bytes: self.max_mempool_mb.saturating_mul(MIB),
+            max_vector_bytes: self.max_vector_store_mb.saturating_mul(MIB),
+        }
+    }
+
+    fn changed_keys(&self, other: &Self) -> Vec<String> {
+        let mut changed = Vec::new();
+        if self.log_level != other.log_level {
+            changed.push("log_level".to_string());
+        }
+        if self.rpc_rate_limit_per_minute != other.rpc_rate_limit_per_minute {
+            changed.push("rpc_rate_limit_per_minute".to_string());
+        }
+        if self.max_mempool_size != other.max_mempool_size {
+            changed.push("max_mempool_size".to_string());
+        }
+        if self.banned_peers != other.banned_peers {
+            changed.push("banned_peers".to_string());
+        }
+        if self.webhook_endpoints != other.webhook_endpoints {
+            changed.push("webhook_endpoints".to_string());
+        }
+        if self.spirapi_call_timeout_ms != other.spirapi_call_timeout_ms {
+            changed.push("spirapi_call_timeout_ms".to_string());
+        }
+        if self.spirapi_latency_slo_ms != other.spirapi_latency_slo_ms {
+            changed.push("spirapi_latency_slo_ms".to_string());
+        }
+        if self.max_db_size_mb != other.max_db_size_mb {
+            changed.push("max_db_size_mb".to_string());
+        }
+        if self.max_mempool_mb != other.max_mempool_mb {
+            changed.push("max_mempool_mb".to_string());
+        }
+        if self.max_vector_store_mb != other.max_vector_store_mb {
+            changed.push("max_vector_store_mb".to_string());
+        }
+        if self.compaction_io_mb_per_sec != other.compaction_io_mb_per_sec {
+            changed.push("compaction_io_mb_per_sec".to_string());
+        }
+        if self.telemetry_endpoint != other.telemetry_endpoint {
+            changed.push("telemetry_endpoint".to_string());
+        }
+        if self.p2p_compression != other.p2p_compression {
+            changed.push("p2p_compression".to_string());
+        }
+        if self.p2p_gossip != other.p2p_gossip {
+            changed.push("p2p_gossip".to_string());
+        }
+        if self.p2p_peers != other.p2p_peers {
+            changed.push("p2p_peers".to_string());
+        }
+        if self.pi_identifier_rate_limit_per_minute != other.pi_identifier_rate_limit_per_minute {
+            changed.push("pi_identifier_rate_limit_per_minute".to_string());
+        }
+        if self.pi_identifier_anchoring != other.pi_identifier_anchoring {
+            changed.push("pi_identifier_anchoring".to_string());
+        }
+        if self.fast_block_relay != other.fast_block_relay {
+            changed.push("fast_block_relay".to_string());
+        }
+        changed
+    }
+}
+
+/// Applies a new log level, provided by whoever installed the tracing subscriber
+pub type LogLevelSetter = Arc<dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync>;
+
+/// Holds the live runtime config and the handles it drives
+pub struct RuntimeConfigManager {
+    path: PathBuf,
+    current: RwLock<RuntimeConfig>,
+    rate_limiter: Arc<RateLimiter>,
+    pi_identifier_limiter: Arc<RateLimiter>,
+    max_mempool_size: Arc<AtomicUsize>,
+    resource_guard: Arc<ResourceGuard>,
+    log_level_setter: RwLock<Option<LogLevelSetter>>,
+    peer_bans_changed: AtomicBool,
+}
+
+impl RuntimeConfigManager {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let config = RuntimeConfig::load(&path)?;
+
+        Ok(Self {
+            rate_limiter: Arc::new(RateLimiter::new(config.rpc_rate_limit_per_minute)),
+            pi_identifier_limiter: Arc::new(RateLimiter::new(
+                config.pi_identifier_rate_limit_per_minute,
+            )),
+            max_mempool_size: Arc::new(AtomicUsize::new(config.max_mempool_size)),
+            resource_guard: Arc::new(ResourceGuard::new(config.resource_limits())),
+            log_level_setter: RwLock::new(None),
+            peer_bans_changed: AtomicBool::new(!config.banned_peers.is_empty()),
+            current: RwLock::new(config),
+            path,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn current(&self) -> RuntimeConfig {
+        self.current.read().clone()
+    }
+
+    pub fn rate_limiter(&self) -> Arc<RateLimiter> {
+        Arc::clone(&self.rate_limiter)
+    }
+
+    pub fn pi_identifier_rate_limiter(&self) -> Arc<RateLimiter> {
+        Arc::clone(&self.pi_identifier_limiter)
+    }
+
+    pub fn pi_identifier_anchoring(&self) -> bool {
+        self.current.read().pi_identifier_anchoring
+    }
+
+    pub fn fast_block_relay(&self) -> bool {
+        self.current.read().fast_block_relay
+    }
+
+    pub fn mempool_limit(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.max_mempool_size)
+    }
+
+    pub fn max_mempool_size(&self) -> usize {
+        self.max_mempool_size.load(Ordering::Relaxed)
+    }
+
+    pub fn resource_guard(&self) -> Arc<ResourceGuard> {
+        Arc::clone(&self.resource_guard)
+    }
+
+    pub fn compaction_io_bytes_per_sec(&self) -> u64 {
+        self.current
+            .read()
+            .compaction_io_mb_per_sec
+            .saturating_mul(1024 * 1024)
+    }
+
+    pub fn webhook_endpoints(&self) -> Vec<String> {
+        self.current.read().webhook_endpoints.clone()
+    }
+
+    pub fn telemetry_endpoint(&self) -> Option<String> {
+        self.current.read().telemetry_endpoint.clone()
+    }
+
+    pub fn p2p_compression(&self) -> Compression {
+        self.current.read().p2p_compression
+    }
+
+    pub fn p2p_gossip(&self) -> GossipParams {
+        self.current.read().p2p_gossip.clone()
+    }
+
+    pub fn p2p_peers(&self) -> PeerDiversityParams {
+        self.current.read().p2p_peers
+    }
+
+    /// Install the log level hook and apply the configured level right away
+    pub fn set_log_level_setter(&self, setter: LogLevelSetter) {
+        let level = self.current.read().log_level.clone();
+        if let Err(e) = setter(&level) {
+            tracing::warn!("Failed to apply log level '{}': {}", level, e);
+        }
+        *self.log_level_setter.write() = Some(setter);
+    }
+
+    /// Banned peer list, returned once after every change
+    pub fn take_peer_ban_update(&self) -> Option<Vec<String>> {
+        if self.peer_bans_changed.swap(false, Ordering::SeqCst) {
+            Some(self.current.read().banned_peers.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Re-read the config file; an invalid file leaves the running config untouched
+    pub fn reload(&self) -> Result<Vec<String>> {
+        let new_config = RuntimeConfig::load(&self.path)?;
+        let limits = new_config.resource_limits();
+
+        // Hold the lock across the whole apply so a concurrent reload cannot
+        // interleave, and set the log level first: it is the only step that
+        // can fail, and then nothing has been applied yet
+        let mut current = self.current.write();
+        let changed = current.changed_keys(&new_config);
+
+        if changed.iter().any(|key| key == "log_level") {
+            if let Some(setter) = self.log_level_setter.read().as_ref() {
+                setter(&new_config.log_level).map_err(SpiraChainError::Config)?;
+            }
+        }
+        self.rate_limiter
+            .set_limit(new_config.rpc_rate_limit_per_minute);
+        self.pi_identifier_limiter
+            .set_limit(new_config.pi_identifier_rate_limit_per_minute);
+        self.max_mempool_size
+            .store(new_config.max_mempool_size, Ordering::Relaxed);
+        self.resource_guard.set_limits(limits);
+        if changed.iter().any(|key| key == "banned_peers") {
+            self.peer_bans_changed.store(true, Ordering::SeqCst);
+        }
+        if changed.iter().any(|key| key == "p2p_gossip") {
+            tracing::warn!("⚠️  p2p_gossip changes take effect after a restart");
+        }
+        let bridge = spirapi_bridge::bridge_pool();
+        bridge.set_call_timeout(Duration::from_millis(new_config.spirapi_call_timeout_ms));
+        bridge.set_latency_slo(Duration::from_millis(new_config.spirapi_latency_slo_ms));
+
+        *current = new_config;
+        drop(current);
+
+        if changed.is_empty() {
+            tracing::info!(
+                "🔁 Runtime config reloaded from {:?} (no changes)",
+                self.path
+            );
+        } else {
+            tracing::info!(
+                "🔁 Runtime config reloaded from {:?}: {}",
+                self.path,
+                changed.join(", ")
+            );
+        }
+
+        Ok(changed)
+    }
+}
+
+/// Resolves on every SIGHUP; never resolves on platforms without signals
+pub struct ReloadSignal {
+    #[cfg(unix)]
+    inner: Option<tokio::signal::unix::Signal>,
+}
+
+impl ReloadSignal {
+    pub fn new() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let inner = match signal(SignalKind::hangup()) {
+                Ok(signal) => Some(signal),
+                Err(e) => {
+                    tracing::warn!("Failed to install SIGHUP handler: {}", e);
+                    None
+                }
+            };
+            Self { inner }
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self {}
+        }
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.inner.as_mut() {
+            signal.recv().await;
+            return;
+        }
+
+        std::future::pending::<()>().await
+    }
+}
+
+impl Default for ReloadSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fire-and-forget POST of `payload` to every endpoint
+pub fn notify_webhooks(endpoints: Vec<String>, payload: serde_json::Value) {
+    if endpoints.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("Failed to build webhook client: {}", e);
+                return;
+            }
+        };
+
+        for endpoint in endpoints {
+            if let Err(e) = client.post(&endpoint).json(&payload).send().await {
+                tracing::warn!("Webhook {} failed: {}", endpoint, e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    fn config_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "spirachain-runtime-config-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(RUNTIME_CONFIG_FILE)
+    }
+
+    fn write_config(path: &Path, config: &serde_json::Value) {
+        std::fs::write(path, serde_json::to_string(config).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_loads_defaults() {
+        let path = config_path("missing");
+        assert_eq!(RuntimeConfig::load(&path).unwrap(), RuntimeConfig::default());
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        let path = config_path("invalid");
+        for config in [
+            serde_json::json!({ "log_level": "loud" }),
+            serde_json::json!({ "max_mempool_size": 0 }),
+            serde_json::json!({ "webhook_endpoints": ["ftp://example.com/hook"] }),
+        ] {
+            write_config(&path, &config);
+            assert!(
+                matches!(RuntimeConfig::load(&path), Err(SpiraChainError::Config(_))),
+                "{} was accepted",
+                config
+            );
+        }
+
+        write_config(&path, &serde_json::json!({ "log_level": "WARN" }));
+        assert_eq!(RuntimeConfig::load(&path).unwrap().log_level, "WARN");
+    }
+
+    #[test]
+    fn test_changed_keys_reports_exactly_the_changed_keys() {
+        let config = RuntimeConfig::default();
+        assert!(config.changed_keys(&config.clone()).is_empty());
+
+        let other = RuntimeConfig {
+            log_level: "debug".to_string(),
+            max_mempool_size: 5,
+            fast_block_relay: true,
+            ..config.clone()
+        };
+        assert_eq!(
+            config.changed_keys(&other),
+            vec!["log_level", "max_mempool_size", "fast_block_relay"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_reload_leaves_config_unchanged() {
+        let path = config_path("reload-invalid");
+        write_config(&path, &serde_json::json!({ "max_mempool_size": 50 }));
+        let manager = RuntimeConfigManager::load(&path).unwrap();
+        let before = manager.current();
+
+        write_config(
+            &path,
+            &serde_json::json!({ "max_mempool_size": 0, "rpc_rate_limit_per_minute": 1 }),
+        );
+        assert!(manager.reload().is_err());
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(manager.reload().is_err());
+
+        assert_eq!(manager.current(), before);
+        assert_eq!(manager.max_mempool_size(), 50);
+    }
+
+    #[test]
+    fn test_rejected_log_level_applies_nothing() {
+        let path = config_path("reload-log-level");
+        let manager = RuntimeConfigManager::load(&path).unwrap();
+        let applied = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&applied);
+        manager.set_log_level_setter(Arc::new(move |level: &str| {
+            if level == "trace" {
+                return Err("trace is disabled in this build".to_string());
+            }
+            log.lock().push(level.to_string());
+            Ok(())
+        }));
+
+        write_config(
+            &path,
+            &serde_json::json!({ "log_level": "trace", "max_mempool_size": 7 }),
+        );
+        assert!(manager.reload().is_err());
+        assert_eq!(manager.current(), RuntimeConfig::default());
+        assert_eq!(manager.max_mempool_size(), 10_000);
+
+        write_config(
+            &path,
+            &serde_json::json!({ "log_level": "debug", "max_mempool_size": 7 }),
+        );
+        assert_eq!(
+            manager.reload().unwrap(),
+            vec!["log_level", "max_mempool_size"]
+        );
+        assert_eq!(*applied.lock(), vec!["info", "debug"]);
+        assert_eq!(manager.max_mempool_size(), 7);
+    }
+
+    #[test]
+    fn test_peer_ban_update_fires_once_per_change() {
+        let path = config_path("bans");
+        let manager = RuntimeConfigManager::load(&path).unwrap();
+        assert_eq!(manager.take_peer_ban_update(), None);
+
+        write_config(&path, &serde_json::json!({ "banned_peers": ["peer-a"] }));
+        manager.reload().unwrap();
+        assert_eq!(
+            manager.take_peer_ban_update(),
+            Some(vec!["peer-a".to_string()])
+        );
+        assert_eq!(manager.take_peer_ban_update(), None);
+
+        // Reloading the same list is not a change
+        manager.reload().unwrap();
+        assert_eq!(manager.take_peer_ban_update(), None);
+
+        write_config(&path, &serde_json::json!({ "banned_peers": [] }));
+        manager.reload().unwrap();
+        assert_eq!(manager.take_peer_ban_update(), Some(Vec::new()));
+        assert_eq!(manager.take_peer_ban_update(), None);
+    }
+}
@@ -0,0 +1,224 @@
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Address, Hash, Result, SpiraChainError};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::error;
+
+/// Double-sign protection file, kept in the node data directory
+pub const SIGNING_PROTECTION_FILE: &str = "signing_protection.json";
+
+/// Last block a validator key signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBlockRecord {
+    pub height: u64,
+    pub slot: u64,
+    pub block_hash: Hash,
+    /// Unix seconds
+    pub signed_at: u64,
+}
+
+/// Watermark of a key retired by a migration: nothing is above it
+pub const RETIRED_WATERMARK: u64 = u64::MAX;
+
+/// What a validator's old machine hands to the new one when it moves to a new
+/// key: who it was and the last block it signed. Written by `spira validator
+/// migrate export`, read by `migrate import`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningHandover {
+    pub validator: Address,
+    /// Hex Ed25519 public key of the old key
+    pub public_key: String,
+    pub last_signed: Option<SignedBlockRecord>,
+    /// Unix seconds
+    pub exported_at: u64,
+}
+
+/// Local record of the highest height and slot each validator key has signed.
+/// A block is only signed if it is strictly above both, so the same wallet
+/// restarted from a stale data dir, or running twice on one machine, cannot
+/// equivocate. Running the wallet on two machines still needs one of them to
+/// be stopped: each keeps its own file.
+pub struct SigningProtection {
+    path: PathBuf,
+    /// Keyed by address string so the file stays readable
+    records: Mutex<HashMap<String, SignedBlockRecord>>,
+    /// `--i-know-what-im-doing`: conflicts are logged instead of refused
+    allow_conflicts: bool,
+}
+
+impl SigningProtection {
+    /// A missing file means nothing has been signed yet
+    pub fn load(path: impl Into<PathBuf>, allow_conflicts: bool) -> Result<Self> {
+        let path = path.into();
+        let records = if path.exists() {
+            let data = std::fs::read_to_string(&path).map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to read {:?}: {}", path, e))
+            })?;
+            serde_json::from_str(&data).map_err(|e| {
+                SpiraChainError::SerializationError(format!("Invalid {:?}: {}", path, e))
+            })?
+        } else {
+            HashMap::new()
+        };
+
+        if allow_conflicts {
+            error!("🚨 Double-sign protection is DISABLED (--i-know-what-im-doing)");
+            error!("🚨 Running this wallet on more than one node WILL get the validator slashed");
+        }
+
+        Ok(Self {
+            path,
+            records: Mutex::new(records),
+            allow_conflicts,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn last_signed(&self, signer: &Address) -> Option<SignedBlockRecord> {
+        self.records.lock().get(&signer.to_string()).copied()
+    }
+
+    /// Refuse to sign at `height`/`slot` if `signer` already signed at or above either
+    pub fn check(&self, signer: &Address, height: u64, slot: u64) -> Result<()> {
+        let Some(last) = self.last_signed(signer) else {
+            return Ok(());
+        };
+        if height > last.height && slot > last.slot {
+            return Ok(());
+        }
+
+        let reason = if last.height == RETIRED_WATERMARK {
+            format!(
+                "{} was retired by a key migration; refusing height {}, slot {}",
+                signer, height, slot
+            )
+        } else {
+            format!(
+                "{} already signed block {} (height {}, slot {}); refusing height {}, slot {}",
+                signer, last.block_hash, last.height, last.slot, height, slot
+            )
+        };
+        if self.allow_conflicts {
+            error!("🚨 DOUBLE-SIGN OVERRIDE: {}", reason);
+            return Ok(());
+        }
+
+        Err(SpiraChainError::ConsensusError(format!(
+            "Double-sign protection: {}",
+            reason
+        )))
+    }
+
+    /// Record a signed block and sync the file before the block leaves the node
+    pub fn record(&self, signer: &Address, height: u64, slot: u64, block_hash: Hash) -> Result<()> {
+        let mut records = self.records.lock();
+        let signed_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        // An overridden conflict never lowers the watermark
+        let entry = records
+            .entry(signer.to_string())
+            .or_insert(SignedBlockRecord {
+                height,
+                slot,
+                block_hash,
+                signed_at,
+            });
+        if height >= entry.height {
+            entry.block_hash = block_hash;
+        }
+        entry.height = entry.height.max(height);
+        entry.slot = entry.slot.max(slot);
+        entry.signed_at = signed_at;
+
+        self.save(&records)
+    }
+
+    /// Never sign with `signer` from this data dir again, and return what it
+    /// signed last for the key taking over
+    pub fn retire(&self, signer: &Address) -> Result<Option<SignedBlockRecord>> {
+        let mut records = self.records.lock();
+        let last = records
+            .get(&signer.to_string())
+            .copied()
+            .filter(|last| last.height != RETIRED_WATERMARK);
+        records.insert(
+            signer.to_string(),
+            SignedBlockRecord {
+                height: RETIRED_WATERMARK,
+                slot: RETIRED_WATERMARK,
+                block_hash: last.map(|last| last.block_hash).unwrap_or(Hash::zero()),
+                signed_at: last.map(|last| last.signed_at).unwrap_or_default(),
+            },
+        );
+        self.save(&records)?;
+        Ok(last)
+    }
+
+    /// Raise `signer`'s watermark to the height and slot the key it replaces
+    /// signed, so the two can never sign the same slot
+    pub fn inherit(&self, signer: &Address, previous: &SignedBlockRecord) -> Result<()> {
+        let mut records = self.records.lock();
+        let entry = records.entry(signer.to_string()).or_insert(*previous);
+        if previous.height > entry.height {
+            entry.block_hash = previous.block_hash;
+        }
+        entry.height = entry.height.max(previous.height);
+        entry.slot = entry.slot.max(previous.slot);
+        self.save(&records)
+    }
+
+    fn save(&self, records: &HashMap<String, SignedBlockRecord>) -> Result<()> {
+        let data = serde_json::to_vec_pretty(records)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        let storage_error =
+            |e: std::io::Error| SpiraChainError::StorageError(format!("{:?}: {}", self.path, e));
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(storage_error)?;
+        }
+
+        // Write-then-rename so a crash never leaves a truncated file behind
+        let tmp = self.path.with_extension("json.tmp");
+        let mut file = std::fs::File::create(&tmp).map_err(storage_error)?;
+        file.write_all(&data).map_err(storage_error)?;
+        file.sync_all().map_err(storage_error)?;
+        std::fs::rename(&tmp, &self.path).map_err(storage_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrated_key_signs_above_the_retired_one() {
+        let dir = std::env::temp_dir().join(format!("spirachain-migration-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (old, new) = (Address::new([1; 32]), Address::new([2; 32]));
+        let old_node = SigningProtection::load(dir.join("old.json"), false).unwrap();
+        old_node.record(&old, 10, 40, Hash::new([7; 32])).unwrap();
+
+        let last = old_node.retire(&old).unwrap().unwrap();
+        assert_eq!((last.height, last.slot), (10, 40));
+        assert!(old_node.check(&old, 11, 41).is_err());
+        // Survives a restart, and retiring twice keeps the real watermark out
+        let old_node = SigningProtection::load(dir.join("old.json"), false).unwrap();
+        assert!(old_node.check(&old, u64::MAX - 1, 50).is_err());
+        assert_eq!(old_node.retire(&old).unwrap(), None);
+
+        let new_node = SigningProtection::load(dir.join("new.json"), false).unwrap();
+        new_node.inherit(&new, &last).unwrap();
+        assert!(new_node.check(&new, 10, 41).is_err());
+        assert!(new_node.check(&new, 11, 40).is_err());
+        assert!(new_node.check(&new, 11, 41).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
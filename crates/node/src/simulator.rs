@@ -0,0 +1,101 @@
+use crate::WorldState;
+use spirachain_core::{Result, SpiraChainError, Transaction, TransactionPayload};
+use spirachain_rpc::server::TransactionSimulator;
+use spirachain_rpc::{encode_amount, SimulateTransactionResponse};
+use spirachain_vm::SpiraVM;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Backs `/simulate_transaction` with the node's live world state
+pub struct NodeSimulator {
+    state: Arc<RwLock<WorldState>>,
+}
+
+impl NodeSimulator {
+    pub fn new(state: Arc<RwLock<WorldState>>) -> Self {
+        Self { state }
+    }
+}
+
+impl TransactionSimulator for NodeSimulator {
+    fn simulate(&self, tx: &Transaction) -> SimulateTransactionResponse {
+        // Copy under the lock so block application is not held up by the VM
+        let scratch = self.state.blocking_read().clone();
+        simulate_transaction(scratch, tx)
+    }
+}
+
+/// Execute `tx` as the next block's transaction on `scratch`, which is dropped afterwards
+pub fn simulate_transaction(
+    mut scratch: WorldState,
+    tx: &Transaction,
+) -> SimulateTransactionResponse {
+    let sender_balance = scratch.get_balance(&tx.from);
+    let sender_nonce = scratch.get_nonce(&tx.from);
+    let height = scratch.current_height() + 1;
+
+    let result = scratch
+        .apply_transaction(tx)
+        .and_then(|_| execute_contract(&scratch, tx));
+    let gas_used = result.as_ref().ok().copied().flatten();
+
+    SimulateTransactionResponse {
+        success: result.is_ok(),
+        tx_hash: tx.tx_hash.to_string(),
+        error: result.err().map(|e| e.to_string()),
+        height,
+        fee: encode_amount(tx.fee),
+        min_fee: encode_amount(tx.min_fee()),
+        sender_balance: encode_amount(sender_balance),
+        sender_balance_after: encode_amount(scratch.get_balance(&tx.from)),
+        sender_nonce,
+        gas_used,
+        contract_address: tx.contract_address().map(|address| address.to_string()),
+        semantic_coherence: tx.semantic_coherence(),
+    }
+}
+
+/// Run contract code through the VM; `None` for payloads without code
+fn execute_contract(state: &WorldState, tx: &Transaction) -> Result<Option<u64>> {
+    let code = match &tx.payload {
+        TransactionPayload::ContractDeploy { code, .. } => code.as_slice(),
+        TransactionPayload::ContractCall { .. } => state.get_code(&tx.to).ok_or_else(|| {
+            SpiraChainError::InvalidTransaction(format!("No code stored for {}", tx.to))
+        })?,
+        _ => return Ok(None),
+    };
+
+    let mut vm = SpiraVM::default();
+    vm.execute(code)?;
+    Ok(Some(vm.gas_used()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Address, Amount};
+
+    #[test]
+    fn test_simulation_leaves_state_untouched() {
+        let from = Address::new([1u8; 32]);
+        let to = Address::new([2u8; 32]);
+        let mut state = WorldState::new();
+        state.set_balance(from, Amount::qbt(10));
+
+        let tx = Transaction::new(from, to, Amount::qbt(4), Amount::from_millis(1));
+        let outcome = simulate_transaction(state.clone(), &tx);
+        assert!(outcome.success, "{:?}", outcome.error);
+        assert_eq!(
+            outcome.sender_balance_after,
+            encode_amount(Amount::qbt(6).checked_sub(Amount::from_millis(1)).unwrap())
+        );
+        assert_eq!(state.get_balance(&from), Amount::qbt(10));
+        assert_eq!(state.get_balance(&to), Amount::zero());
+
+        let overdraft = Transaction::new(from, to, Amount::qbt(11), Amount::from_millis(1));
+        let outcome = simulate_transaction(state, &overdraft);
+        assert!(!outcome.success);
+        assert!(outcome.error.is_some());
+        assert!(outcome.gas_used.is_none());
+    }
+}
@@ -0,0 +1,92 @@
+// Leader schedule
+// Slot leaders follow from the active validator set alone, so the schedule of
+// the current epoch can be published ahead: operators pick maintenance windows
+// between their slots, and monitors compare it with the blocks that arrive to
+// spot missed slots as they happen. Past epochs are not served, as the set may
+// have changed since; the next one is, as it stands now.
+
+use spirachain_consensus::{SlotConsensus, SLOTS_PER_EPOCH};
+use spirachain_core::{Result, SpiraChainError};
+use spirachain_rpc::server::SlotScheduleSource;
+use spirachain_rpc::{SlotScheduleResponse, ValidatorSlots};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Backs `/validators/schedule` with the node's slot consensus
+pub struct NodeSlotSchedule {
+    slot_consensus: Arc<RwLock<SlotConsensus>>,
+}
+
+impl NodeSlotSchedule {
+    pub fn new(slot_consensus: Arc<RwLock<SlotConsensus>>) -> Self {
+        Self { slot_consensus }
+    }
+}
+
+impl SlotScheduleSource for NodeSlotSchedule {
+    fn slot_schedule(&self, epoch: Option<u64>) -> Result<SlotScheduleResponse> {
+        let consensus = self.slot_consensus.blocking_read();
+        slot_schedule(&consensus, consensus.get_current_slot(), epoch)
+    }
+}
+
+/// Schedule of `epoch` (the current one when `None`) as of `current_slot`
+pub fn slot_schedule(
+    consensus: &SlotConsensus,
+    current_slot: u64,
+    epoch: Option<u64>,
+) -> Result<SlotScheduleResponse> {
+    let current_epoch = SlotConsensus::epoch_of(current_slot);
+    let epoch = epoch.unwrap_or(current_epoch);
+    if epoch < current_epoch || epoch > current_epoch + 1 {
+        return Err(SpiraChainError::ConsensusError(format!(
+            "Only epochs {} and {} are scheduled",
+            current_epoch,
+            current_epoch + 1
+        )));
+    }
+
+    Ok(SlotScheduleResponse {
+        epoch,
+        first_slot: epoch * SLOTS_PER_EPOCH,
+        slots_per_epoch: SLOTS_PER_EPOCH,
+        slot_duration_secs: consensus.slot_duration(),
+        current_slot,
+        leaders: consensus
+            .leader_schedule(epoch)
+            .into_iter()
+            .map(|(address, slots)| ValidatorSlots {
+                address: address.to_string(),
+                slots,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::Address;
+
+    #[test]
+    fn test_schedule_of_current_and_next_epoch() {
+        let mut consensus = SlotConsensus::new("testnet");
+        consensus.add_validator(Address::new([1u8; 32]));
+        consensus.add_validator(Address::new([2u8; 32]));
+        let current_slot = 5 * SLOTS_PER_EPOCH + 7;
+
+        let schedule = slot_schedule(&consensus, current_slot, None).unwrap();
+        assert_eq!(schedule.epoch, 5);
+        assert_eq!(schedule.first_slot, 5 * SLOTS_PER_EPOCH);
+        assert_eq!(schedule.leaders.len(), 2);
+        assert_eq!(
+            schedule.leaders[1].address,
+            Address::new([2u8; 32]).to_string()
+        );
+        assert_eq!(schedule.leaders[1].slots[0], 5 * SLOTS_PER_EPOCH + 1);
+
+        assert!(slot_schedule(&consensus, current_slot, Some(6)).is_ok());
+        assert!(slot_schedule(&consensus, current_slot, Some(4)).is_err());
+        assert!(slot_schedule(&consensus, current_slot, Some(7)).is_err());
+    }
+}
@@ -1,23 +1,161 @@
-use spirachain_core::{Address, Amount, Result, SpiraChainError};
+use crate::StateRootCache;
+use spirachain_consensus::RewardCalculator;
+use spirachain_core::{
+    spiral_kind, Account, Address, Amount, Block, ChainParams, Hash, NameRegistry, PauseMultisig,
+    PauseState, Result, SemanticModelState, SpiraChainError, SpiralDiversity, SpiralRegistry,
+    Transaction, TransactionPayload, VestingSchedule, NO_PAUSE_MULTISIG, STATE_TREE_VERSION,
+    TESTNET_PARAMS,
+};
 use std::collections::HashMap;
 
+#[derive(Clone)]
 pub struct WorldState {
-    accounts: HashMap<Address, AccountState>,
+    accounts: HashMap<Address, Account>,
+    contract_code: HashMap<Hash, Vec<u8>>,
     block_height: u64,
-}
-
-pub struct AccountState {
-    pub balance: Amount,
-    pub nonce: u64,
-    pub stake: Amount,
+    pause: PauseState,
+    pause_multisig: PauseMultisig,
+    semantic_models: SemanticModelState,
+    spirals: SpiralRegistry,
+    diversity: SpiralDiversity,
+    names: NameRegistry,
+    root_cache: StateRootCache,
+    chain_params: &'static ChainParams,
 }
 
 impl WorldState {
     pub fn new() -> Self {
         Self {
             accounts: HashMap::new(),
+            contract_code: HashMap::new(),
             block_height: 0,
+            pause: PauseState::new(),
+            pause_multisig: NO_PAUSE_MULTISIG,
+            semantic_models: SemanticModelState::new(),
+            spirals: SpiralRegistry::new(),
+            diversity: SpiralDiversity::new(),
+            names: NameRegistry::new(),
+            root_cache: StateRootCache::new(),
+            chain_params: &TESTNET_PARAMS,
+        }
+    }
+
+    /// Empty state following `network`'s fork schedule and pause guardians
+    pub fn for_network(network: &str) -> Self {
+        Self::new().with_chain_params(ChainParams::for_network(network))
+    }
+
+    pub fn with_chain_params(mut self, params: &'static ChainParams) -> Self {
+        self.chain_params = params;
+        self.pause_multisig = params.pause_multisig;
+        self
+    }
+
+    pub fn with_pause_multisig(mut self, multisig: PauseMultisig) -> Self {
+        self.pause_multisig = multisig;
+        self
+    }
+
+    pub fn pause_state(&self) -> &PauseState {
+        &self.pause
+    }
+
+    /// Restore the pause state loaded from storage
+    pub fn set_pause_state(&mut self, pause: PauseState) {
+        self.pause = pause;
+    }
+
+    /// Whether `tx` may execute in the next block given the current pause
+    pub fn check_not_paused(&self, tx: &Transaction) -> Result<()> {
+        self.pause.check_transaction(tx, self.block_height + 1)
+    }
+
+    /// Embedding models approved by the guardians
+    pub fn semantic_models(&self) -> &SemanticModelState {
+        &self.semantic_models
+    }
+
+    /// Restore the model approvals loaded from storage
+    pub fn set_semantic_models(&mut self, models: SemanticModelState) {
+        self.semantic_models = models;
+    }
+
+    pub fn spiral_registry(&self) -> &SpiralRegistry {
+        &self.spirals
+    }
+
+    /// Restore the custom spirals loaded from storage
+    pub fn set_spiral_registry(&mut self, spirals: SpiralRegistry) {
+        self.spirals = spirals;
+    }
+
+    pub fn spiral_diversity(&self) -> &SpiralDiversity {
+        &self.diversity
+    }
+
+    /// Restore the spiral history loaded from storage
+    pub fn set_spiral_diversity(&mut self, diversity: SpiralDiversity) {
+        self.diversity = diversity;
+    }
+
+    pub fn name_registry(&self) -> &NameRegistry {
+        &self.names
+    }
+
+    /// Restore the names loaded from storage
+    pub fn set_name_registry(&mut self, names: NameRegistry) {
+        self.names = names;
+    }
+
+    /// Reject blocks whose custom spiral is not registered
+    pub fn check_block_spiral(&self, block: &Block) -> Result<()> {
+        self.spirals.check_block_spiral(&block.header.spiral)
+    }
+
+    pub fn get_account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    /// Replace the whole account, e.g. when loading persisted state
+    pub fn set_account(&mut self, address: Address, account: Account) {
+        self.root_cache.mark_dirty(address);
+        self.accounts.insert(address, account);
+    }
+
+    /// Mutable account, created if missing; every account write goes through
+    /// here so the state root cache knows what to rehash
+    fn account_mut(&mut self, address: Address) -> &mut Account {
+        self.root_cache.mark_dirty(address);
+        self.accounts.entry(address).or_default()
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.accounts.iter()
+    }
+
+    /// Register contract code loaded from storage
+    pub fn insert_code(&mut self, code: Vec<u8>) -> Hash {
+        let code_hash = Hash::from(blake3::hash(&code));
+        self.contract_code.insert(code_hash, code);
+        code_hash
+    }
+
+    /// Balance the account can spend at the current height
+    pub fn spendable_balance(&self, address: &Address) -> Amount {
+        self.accounts
+            .get(address)
+            .map(|acc| acc.spendable_at(self.block_height))
+            .unwrap_or(Amount::zero())
+    }
+
+    /// Lock part of the balance under `vesting`, replacing any earlier schedule
+    pub fn set_vesting(&mut self, address: &Address, vesting: VestingSchedule) -> Result<()> {
+        let acc = self.account_mut(*address);
+        if acc.balance < vesting.amount {
+            return Err(SpiraChainError::InsufficientBalance);
         }
+        acc.vesting = Some(vesting);
+        Ok(())
     }
 
     pub fn get_balance(&self, address: &Address) -> Amount {
@@ -35,36 +173,397 @@ impl WorldState {
     }
 
     pub fn set_balance(&mut self, address: Address, balance: Amount) {
+        self.account_mut(address).balance = balance;
+    }
+
+    pub fn credit_balance(&mut self, address: &Address, amount: Amount) -> Result<()> {
+        let new_balance = self
+            .get_balance(address)
+            .checked_add(amount)
+            .ok_or_else(|| SpiraChainError::BalanceOverflow(address.to_string()))?;
+        self.set_balance(*address, new_balance);
+        Ok(())
+    }
+
+    pub fn get_unclaimed_rewards(&self, address: &Address) -> Amount {
         self.accounts
-            .entry(address)
-            .or_insert(AccountState {
-                balance: Amount::zero(),
-                nonce: 0,
-                stake: Amount::zero(),
-            })
-            .balance = balance;
+            .get(address)
+            .map(|acc| acc.unclaimed_rewards)
+            .unwrap_or(Amount::zero())
+    }
+
+    /// Account credited with `producer`'s block rewards
+    pub fn payout_address(&self, producer: &Address) -> Address {
+        self.accounts
+            .get(producer)
+            .and_then(|acc| acc.payout_address)
+            .unwrap_or(*producer)
+    }
+
+    /// Add block rewards to the producer's claimable bucket
+    pub fn credit_rewards(&mut self, address: &Address, amount: Amount) -> Result<()> {
+        let acc = self.account_mut(*address);
+        acc.unclaimed_rewards = acc
+            .unclaimed_rewards
+            .checked_add(amount)
+            .ok_or_else(|| SpiraChainError::BalanceOverflow(address.to_string()))?;
+        Ok(())
+    }
+
+    /// Pay the sender's unclaimed rewards, less `fee`, to `tx.to`. The fee is
+    /// taken from the rewards so a producer with an empty balance can claim.
+    fn claim_rewards(&mut self, tx: &Transaction, fee: Amount) -> Result<()> {
+        let payout = self
+            .get_unclaimed_rewards(&tx.from)
+            .checked_sub(fee)
+            .ok_or(SpiraChainError::InsufficientBalance)?;
+        self.credit_balance(&tx.to, payout)?;
+        self.account_mut(tx.from).unclaimed_rewards = Amount::zero();
+        self.increment_nonce(&tx.from);
+        Ok(())
     }
 
-    pub fn credit_balance(&mut self, address: &Address, amount: Amount) {
-        let current_balance = self.get_balance(address);
-        if let Some(new_balance) = current_balance.checked_add(amount) {
-            self.set_balance(*address, new_balance);
+    /// Hand `old`'s stake and payout address over to `new`, the validator's new
+    /// key. Balances and unclaimed rewards stay with `old` until swept.
+    fn rotate_validator_key(&mut self, old: Address, new: Address) -> Result<()> {
+        let stake = self.get_stake(&old);
+        if stake.is_zero() {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "{} has no stake to rotate",
+                old
+            )));
+        }
+        if !self.get_stake(&new).is_zero() {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "{} already has stake; rotate to a fresh key",
+                new
+            )));
         }
+
+        let old_account = self.account_mut(old);
+        old_account.stake = Amount::zero();
+        let payout = old_account.payout_address.take().unwrap_or(old);
+        let new_account = self.account_mut(new);
+        new_account.stake = stake;
+        // Rewards keep going where they went, unless that was the new key anyway
+        new_account.payout_address = (payout != new).then_some(payout);
+        Ok(())
     }
 
+    /// Move `amount` between balances; nothing changes unless both sides fit
     pub fn transfer(&mut self, from: &Address, to: &Address, amount: Amount) -> Result<()> {
-        let from_balance = self.get_balance(from);
-        let to_balance = self.get_balance(to);
+        let new_from_balance = self
+            .get_balance(from)
+            .checked_sub(amount)
+            .ok_or(SpiraChainError::InsufficientBalance)?;
+        if from == to {
+            return Ok(());
+        }
+        let new_to_balance = self
+            .get_balance(to)
+            .checked_add(amount)
+            .ok_or_else(|| SpiraChainError::BalanceOverflow(to.to_string()))?;
+
+        self.set_balance(*from, new_from_balance);
+        self.set_balance(*to, new_to_balance);
+        Ok(())
+    }
+
+    /// Apply the transactions of `block` in order, the coinbase last. A failing
+    /// transaction is skipped and returned with its error; the rest of the
+    /// block still applies. Debug builds check that only the coinbase changed
+    /// the total supply.
+    ///
+    /// A block without a coinbase, valid only until
+    /// [`spirachain_core::COINBASE_VERSION`] activates, follows the rules of
+    /// earlier releases: fees stay with their senders and the producer's
+    /// balance is credited the scheduled reward.
+    pub fn apply_block(&mut self, block: &Block) -> Vec<(Hash, SpiraChainError)> {
+        let supply_before = if cfg!(debug_assertions) {
+            self.total_value()
+        } else {
+            None
+        };
+        let mut minted = 0u128;
+        let mut fees = 0u128;
+        let mut failures = Vec::new();
+        let height = block.header.block_height;
+        let legacy = height > 0 && block.coinbase().is_none();
+
+        // The coinbase waits for the fees it collects to be paid
+        let mut coinbases = Vec::new();
+        for tx in &block.transactions {
+            if tx.is_coinbase() {
+                coinbases.push(tx);
+                continue;
+            }
+            match self.apply_transaction_at(tx, height, !legacy) {
+                Ok(()) if !legacy => fees = fees.saturating_add(tx.fee.value()),
+                Ok(()) => {}
+                Err(e) => failures.push((tx.tx_hash, e)),
+            }
+        }
+
+        // A producer gets the scheduled reward, reduced when it repeats a
+        // spiral kind within the epoch, plus the unburned share of the fees
+        // paid above. A coinbase claiming more, or paying anyone but the
+        // producer, does not execute.
+        let producer = block.header.producer_address();
+        let kind = spiral_kind(&block.header.spiral);
+        let mut allowance = producer.map(|producer| {
+            let novel = self.diversity.is_novel(&producer, &kind, height);
+            let reward = RewardCalculator::diversity_reward_at_height(height, novel);
+            let (fees, _) = RewardCalculator::distribute_fees(Amount::new(fees));
+            reward.checked_add(fees).unwrap_or(reward)
+        });
+
+        for tx in coinbases {
+            if let (Some(producer), Some(cap)) = (producer, allowance) {
+                if let Err(e) = self.check_coinbase(tx, &producer, cap) {
+                    failures.push((tx.tx_hash, e));
+                    continue;
+                }
+                allowance = cap.checked_sub(tx.amount);
+            }
+            match self.apply_transaction_at(tx, height, true) {
+                Ok(()) => minted = minted.saturating_add(tx.amount.value()),
+                Err(e) => failures.push((tx.tx_hash, e)),
+            }
+        }
+
+        if let (true, Some(producer)) = (legacy, producer) {
+            let reward = RewardCalculator::block_reward_at_height(height);
+            if self.credit_balance(&producer, reward).is_ok() {
+                minted = minted.saturating_add(reward.value());
+            }
+        }
+
+        // Fees leave their senders; the coinbase mints back the producer's share
+        if let Some(before) = supply_before {
+            let expected = before
+                .checked_add(minted)
+                .and_then(|supply| supply.checked_sub(fees));
+            assert_eq!(
+                self.total_value(),
+                expected,
+                "block {} broke balance conservation",
+                block.header.block_height
+            );
+        }
+
+        if let Some(producer) = producer {
+            self.diversity.record(producer, kind, height);
+        }
+
+        failures
+    }
+
+    /// A coinbase pays `producer`, directly or through its payout address,
+    /// no more than `allowance`
+    fn check_coinbase(&self, tx: &Transaction, producer: &Address, allowance: Amount) -> Result<()> {
+        if tx.to != *producer && tx.to != self.payout_address(producer) {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Coinbase pays {} instead of the block producer {}",
+                tx.to, producer
+            )));
+        }
+        if tx.amount > allowance {
+            return Err(SpiraChainError::InvalidTransaction(format!(
+                "Coinbase {} exceeds the allowance {} of reward plus collected fees",
+                tx.amount, allowance
+            )));
+        }
+        Ok(())
+    }
 
-        if let Some(new_from_balance) = from_balance.checked_sub(amount) {
-            if let Some(new_to_balance) = to_balance.checked_add(amount) {
-                self.set_balance(*from, new_from_balance);
-                self.set_balance(*to, new_to_balance);
-                return Ok(());
+    /// [`Self::apply_block`], kept only if the coinbase applies and the result
+    /// matches the block's state root (unless the block carries none). A
+    /// rejected block leaves the state untouched; a rejected coinbase is
+    /// reported as an invalid transaction, anything else as an invalid block.
+    pub fn apply_verified_block(&mut self, block: &Block) -> Result<Vec<(Hash, SpiraChainError)>> {
+        let mut next = self.clone();
+        let failures = next.apply_block(block);
+        if let Some(coinbase) = block.coinbase() {
+            if let Some((_, e)) = failures.iter().find(|(hash, _)| *hash == coinbase.tx_hash) {
+                return Err(SpiraChainError::InvalidTransaction(format!(
+                    "coinbase of block {} rejected: {}",
+                    block.header.block_height, e
+                )));
             }
         }
+        let root = next.calculate_merkle_root();
+        if !block.header.state_root.is_zero() && root != block.header.state_root {
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "State root mismatch in block {}: it commits to {}, applying it gives {}",
+                block.header.block_height, block.header.state_root, root
+            )));
+        }
+        *self = next;
+        Ok(failures)
+    }
 
-        Err(SpiraChainError::InsufficientBalance)
+    /// Apply a block transaction: value transfer, fee debit, nonce bump and any contract payload.
+    /// Coinbase transactions mint the block reward plus the producer's share of
+    /// the collected fees into its unclaimed rewards.
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<()> {
+        self.apply_transaction_at(tx, self.block_height + 1, true)
+    }
+
+    /// [`Self::apply_transaction`] as part of the block at `height`; only the
+    /// coinbase and guardian votes execute while the chain is paused. Legacy
+    /// blocks do not `charge_fee`.
+    fn apply_transaction_at(
+        &mut self,
+        tx: &Transaction,
+        height: u64,
+        charge_fee: bool,
+    ) -> Result<()> {
+        self.pause.check_transaction(tx, height)?;
+        let fee = if charge_fee { tx.fee } else { Amount::zero() };
+
+        // Claims pay their fee out of the rewards, see `claim_rewards`
+        if !tx.is_protocol() && tx.payload != TransactionPayload::ClaimRewards {
+            let required = tx.amount.checked_add(fee).ok_or_else(|| {
+                SpiraChainError::InvalidAmount("amount plus fee overflows".to_string())
+            })?;
+            if self.spendable_balance(&tx.from) < required {
+                return Err(SpiraChainError::InsufficientBalance);
+            }
+        }
+
+        match &tx.payload {
+            TransactionPayload::Transfer => {
+                self.transfer(&tx.from, &tx.to, tx.amount)?;
+            }
+            TransactionPayload::Coinbase { .. } => {
+                // Minted by the protocol: no sender balance or nonce involved
+                let payout = self.payout_address(&tx.to);
+                return self.credit_rewards(&payout, tx.amount);
+            }
+            // A record of the previous epoch; nodes check it before applying the block
+            TransactionPayload::EpochSummary { .. } => return Ok(()),
+            TransactionPayload::ClaimRewards => return self.claim_rewards(tx, fee),
+            TransactionPayload::EmergencyPause { blocks } => {
+                self.pause
+                    .vote(&self.pause_multisig, tx.from, *blocks, height)?;
+            }
+            TransactionPayload::ApproveSemanticModel { model } => {
+                self.semantic_models
+                    .vote(&self.pause_multisig, tx.from, model.clone(), height)?;
+            }
+            TransactionPayload::RegisterSpiral { definition } => {
+                self.spirals.register(definition.clone(), tx.from, height)?;
+            }
+            TransactionPayload::RegisterName { name } => {
+                self.names.register(name, tx.from, tx.to, height)?;
+            }
+            TransactionPayload::TransferName { name } => {
+                self.names.transfer(name, tx.from, tx.to, height)?;
+            }
+            TransactionPayload::SetPayoutAddress => {
+                self.account_mut(tx.from).payout_address = (tx.to != tx.from).then_some(tx.to);
+            }
+            TransactionPayload::RotateValidatorKey { .. } => {
+                self.rotate_validator_key(tx.from, tx.to)?;
+            }
+            TransactionPayload::ContractDeploy { code, nonce, .. } => {
+                let expected_nonce = self.get_nonce(&tx.from);
+                if *nonce != expected_nonce {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Deployment nonce {} does not match account nonce {}",
+                        nonce, expected_nonce
+                    )));
+                }
+
+                let contract_address = spirachain_core::derive_contract_address(&tx.from, *nonce);
+                if self.is_contract(&contract_address) {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "Contract already deployed at {}",
+                        contract_address
+                    )));
+                }
+
+                if !tx.amount.is_zero() {
+                    self.transfer(&tx.from, &contract_address, tx.amount)?;
+                }
+
+                let code_hash = Hash::from(blake3::hash(code));
+                self.contract_code.insert(code_hash, code.clone());
+                let account = self.account_mut(contract_address);
+                account.code_hash = Some(code_hash);
+                account.storage_root = Hash::zero();
+            }
+            TransactionPayload::ContractCall { .. } => {
+                if !self.is_contract(&tx.to) {
+                    return Err(SpiraChainError::InvalidTransaction(format!(
+                        "No contract deployed at {}",
+                        tx.to
+                    )));
+                }
+
+                if !tx.amount.is_zero() {
+                    self.transfer(&tx.from, &tx.to, tx.amount)?;
+                }
+            }
+        }
+
+        // Fees leave the sender here and are paid out by the block's coinbase
+        if !fee.is_zero() {
+            let balance = self.get_balance(&tx.from);
+            let remaining = balance
+                .checked_sub(fee)
+                .ok_or(SpiraChainError::InsufficientBalance)?;
+            self.set_balance(tx.from, remaining);
+        }
+
+        self.increment_nonce(&tx.from);
+        Ok(())
+    }
+
+    pub fn is_contract(&self, address: &Address) -> bool {
+        self.get_code_hash(address).is_some()
+    }
+
+    pub fn get_code_hash(&self, address: &Address) -> Option<Hash> {
+        self.accounts.get(address).and_then(|acc| acc.code_hash)
+    }
+
+    pub fn get_code(&self, address: &Address) -> Option<&[u8]> {
+        self.get_code_hash(address)
+            .and_then(|hash| self.contract_code.get(&hash))
+            .map(|code| code.as_slice())
+    }
+
+    pub fn get_storage_root(&self, address: &Address) -> Hash {
+        self.accounts
+            .get(address)
+            .map(|acc| acc.storage_root)
+            .unwrap_or(Hash::zero())
+    }
+
+    pub fn set_storage_root(&mut self, address: &Address, root: Hash) -> Result<()> {
+        match self.accounts.get_mut(address) {
+            Some(acc) if acc.code_hash.is_some() => {
+                acc.storage_root = root;
+                self.root_cache.mark_dirty(*address);
+                Ok(())
+            }
+            _ => Err(SpiraChainError::InvalidTransaction(format!(
+                "No contract deployed at {}",
+                address
+            ))),
+        }
+    }
+
+    /// (address, code_hash, storage_root) for every contract account
+    pub fn get_all_contracts(&self) -> Vec<(Address, Hash, Hash)> {
+        self.accounts
+            .iter()
+            .filter_map(|(address, acc)| {
+                acc.code_hash
+                    .map(|code_hash| (*address, code_hash, acc.storage_root))
+            })
+            .collect()
     }
 
     pub fn get_nonce(&self, address: &Address) -> u64 {
@@ -72,14 +571,7 @@ impl WorldState {
     }
 
     pub fn increment_nonce(&mut self, address: &Address) {
-        self.accounts
-            .entry(*address)
-            .or_insert(AccountState {
-                balance: Amount::zero(),
-                nonce: 0,
-                stake: Amount::zero(),
-            })
-            .nonce += 1;
+        self.account_mut(*address).nonce += 1;
     }
 
     pub fn get_stake(&self, address: &Address) -> Amount {
@@ -93,17 +585,13 @@ impl WorldState {
         let balance = self.get_balance(address);
 
         if let Some(new_balance) = balance.checked_sub(amount) {
-            let acc = self.accounts.entry(*address).or_insert(AccountState {
-                balance: Amount::zero(),
-                nonce: 0,
-                stake: Amount::zero(),
-            });
+            let acc = self.account_mut(*address);
 
             acc.balance = new_balance;
             acc.stake = acc
                 .stake
                 .checked_add(amount)
-                .ok_or_else(|| SpiraChainError::Internal("Stake overflow".to_string()))?;
+                .ok_or_else(|| SpiraChainError::BalanceOverflow(address.to_string()))?;
 
             Ok(())
         } else {
@@ -112,6 +600,7 @@ impl WorldState {
     }
 
     pub fn remove_stake(&mut self, address: &Address, amount: Amount) -> Result<()> {
+        self.root_cache.mark_dirty(*address);
         let acc = self
             .accounts
             .get_mut(address)
@@ -122,7 +611,7 @@ impl WorldState {
             acc.balance = acc
                 .balance
                 .checked_add(amount)
-                .ok_or_else(|| SpiraChainError::Internal("Balance overflow".to_string()))?;
+                .ok_or_else(|| SpiraChainError::BalanceOverflow(address.to_string()))?;
             Ok(())
         } else {
             Err(SpiraChainError::InsufficientStake(
@@ -147,12 +636,26 @@ impl WorldState {
     pub fn total_supply(&self) -> Amount {
         self.accounts
             .values()
-            .map(|acc| acc.balance.checked_add(acc.stake).unwrap_or(acc.balance))
+            .map(|acc| {
+                acc.balance
+                    .checked_add(acc.stake)
+                    .and_then(|total| total.checked_add(acc.unclaimed_rewards))
+                    .unwrap_or(acc.balance)
+            })
             .fold(Amount::zero(), |sum, balance| {
                 sum.checked_add(balance).unwrap_or(sum)
             })
     }
 
+    /// Exact sum of balances, stakes and unclaimed rewards, `None` past u128
+    fn total_value(&self) -> Option<u128> {
+        self.accounts.values().try_fold(0u128, |sum, acc| {
+            sum.checked_add(acc.balance.value())?
+                .checked_add(acc.stake.value())?
+                .checked_add(acc.unclaimed_rewards.value())
+        })
+    }
+
     pub fn total_staked(&self) -> Amount {
         self.accounts
             .values()
@@ -163,38 +666,40 @@ impl WorldState {
     }
 
     /// Calculate Merkle root of the complete WorldState
-    /// This creates a deterministic hash of all account balances
-    pub fn calculate_merkle_root(&self) -> spirachain_core::Hash {
-        use spirachain_core::Hash;
-        
-        // If no accounts, return zero hash
-        if self.accounts.is_empty() {
-            return Hash::zero();
-        }
-        
-        // Create sorted list of (address, balance) pairs for deterministic hashing
-        let mut account_data: Vec<_> = self.accounts
-            .iter()
-            .map(|(addr, acc)| {
-                // Create a deterministic string representation
-                format!("{}:{}:{}", 
-                    addr.to_string(), 
-                    acc.balance.value(),
-                    acc.nonce
-                )
-            })
-            .collect();
-        
-        // Sort to ensure deterministic order
-        account_data.sort();
-        
-        // Hash all account data together
-        let mut hasher = blake3::Hasher::new();
-        for data in account_data {
-            hasher.update(data.as_bytes());
+    /// Account hashes are cached, so only accounts changed since the last call
+    /// are rehashed. Before [`STATE_TREE_VERSION`] activates the root stays the
+    /// flat hash earlier releases committed to.
+    pub fn calculate_merkle_root(&mut self) -> spirachain_core::Hash {
+        let mut extras = Vec::new();
+        let pause = self.pause.state_entry(self.block_height);
+        if !pause.is_empty() {
+            extras.push(pause);
+        }
+        let models = self.semantic_models.state_entry(self.block_height);
+        if !models.is_empty() {
+            extras.push(models);
+        }
+        let spirals = self.spirals.state_entry();
+        if !spirals.is_empty() {
+            extras.push(spirals);
+        }
+        let diversity = self.diversity.state_entry();
+        if !diversity.is_empty() {
+            extras.push(diversity);
+        }
+        let names = self.names.state_entry();
+        if !names.is_empty() {
+            extras.push(names);
+        }
+
+        if self
+            .chain_params
+            .is_active(STATE_TREE_VERSION, self.block_height)
+        {
+            self.root_cache.root(&self.accounts, &extras)
+        } else {
+            self.root_cache.flat_root(&self.accounts, &extras)
         }
-        
-        hasher.finalize().into()
     }
 }
 
@@ -203,3 +708,365 @@ impl Default for WorldState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const ACCOUNTS: u8 = 4;
+
+    fn address(index: u8) -> Address {
+        Address::new([index + 1; 32])
+    }
+
+    fn funded_state() -> WorldState {
+        let mut state = WorldState::new();
+        for index in 0..ACCOUNTS {
+            state.set_balance(address(index), Amount::qbt(100));
+        }
+        state
+    }
+
+    fn transfer_strategy() -> impl Strategy<Value = Transaction> {
+        (0..ACCOUNTS, 0..ACCOUNTS, 0..=u64::MAX, 0..=1_000_000u64).prop_map(
+            |(from, to, amount, fee)| {
+                Transaction::new(
+                    address(from),
+                    address(to),
+                    Amount::new(amount as u128 * 1_000),
+                    Amount::new(fee as u128),
+                )
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn prop_blocks_only_mint_rewards(
+            blocks in prop::collection::vec(
+                (prop::collection::vec(transfer_strategy(), 0..12), 0..ACCOUNTS, 0..=u64::MAX),
+                1..8,
+            )
+        ) {
+            let mut state = funded_state();
+            let mut expected = state.total_value().unwrap();
+
+            for (height, (transfers, producer, reward)) in blocks.into_iter().enumerate() {
+                let reward = Amount::new(reward as u128);
+                let coinbase = Transaction::new_coinbase(address(producer), reward, height as u64);
+                let mut transactions = vec![coinbase];
+                transactions.extend(transfers);
+                for (index, tx) in transactions.iter_mut().enumerate() {
+                    tx.tx_hash = Hash::from(blake3::hash(&index.to_le_bytes()));
+                }
+                let block =
+                    Block::new(Hash::zero(), height as u64 + 1).with_transactions(transactions);
+
+                let failed = state.apply_block(&block);
+                let fees: u128 = block
+                    .transactions
+                    .iter()
+                    .filter(|tx| !tx.is_coinbase())
+                    .filter(|tx| !failed.iter().any(|(hash, _)| *hash == tx.tx_hash))
+                    .map(|tx| tx.fee.value())
+                    .sum();
+
+                expected = expected + reward.value() - fees;
+                prop_assert_eq!(state.total_value(), Some(expected));
+            }
+        }
+
+        #[test]
+        fn prop_transfer_is_all_or_nothing(
+            from in 0..ACCOUNTS,
+            to in 0..ACCOUNTS,
+            amount in 0..=u128::MAX,
+        ) {
+            let mut state = funded_state();
+            state.set_balance(address(ACCOUNTS - 1), Amount::new(u128::MAX - 5));
+            let root = state.calculate_merkle_root();
+            let pair = |state: &WorldState| {
+                state
+                    .get_balance(&address(from))
+                    .checked_add(state.get_balance(&address(to)))
+            };
+            let before = pair(&state);
+
+            match state.transfer(&address(from), &address(to), Amount::new(amount)) {
+                Ok(()) => prop_assert_eq!(pair(&state), before),
+                Err(_) => prop_assert_eq!(state.calculate_merkle_root(), root),
+            }
+        }
+    }
+
+    #[test]
+    fn test_cached_root_matches_rebuilt_state() {
+        let mut state = funded_state();
+        let initial = state.calculate_merkle_root();
+
+        state
+            .transfer(&address(0), &address(1), Amount::qbt(5))
+            .unwrap();
+        state.add_stake(&address(2), Amount::qbt(10)).unwrap();
+        state.increment_nonce(&address(3));
+        let root = state.calculate_merkle_root();
+        assert_ne!(root, initial);
+
+        let mut rebuilt = WorldState::new();
+        for (address, account) in state.accounts() {
+            rebuilt.set_account(*address, account.clone());
+        }
+        assert_eq!(rebuilt.calculate_merkle_root(), root);
+        assert_eq!(state.calculate_merkle_root(), root);
+    }
+
+    #[test]
+    fn test_state_root_switches_to_tree_at_fork() {
+        static FORKED: ChainParams = ChainParams {
+            hard_forks: &[spirachain_core::HardFork {
+                name: "state-tree",
+                version: STATE_TREE_VERSION,
+                height: 5,
+            }],
+            ..TESTNET_PARAMS
+        };
+        let mut state = funded_state().with_chain_params(&FORKED);
+
+        // Before the fork: the flat hash over sorted entries of earlier releases
+        let mut entries: Vec<_> = state
+            .accounts()
+            .map(|(address, account)| account.state_entry(address))
+            .collect();
+        entries.sort();
+        let mut hasher = blake3::Hasher::new();
+        for entry in &entries {
+            hasher.update(entry.as_bytes());
+        }
+        let flat: Hash = hasher.finalize().into();
+        state.set_height(4);
+        assert_eq!(state.calculate_merkle_root(), flat);
+
+        state.set_height(5);
+        assert_ne!(state.calculate_merkle_root(), flat);
+    }
+
+    #[test]
+    fn test_credit_overflow_is_an_error() {
+        let mut state = WorldState::new();
+        state.set_balance(address(0), Amount::new(u128::MAX));
+        assert!(matches!(
+            state.credit_balance(&address(0), Amount::new(1)),
+            Err(SpiraChainError::BalanceOverflow(_))
+        ));
+        assert_eq!(state.get_balance(&address(0)), Amount::new(u128::MAX));
+    }
+
+    #[test]
+    fn test_rewards_are_held_until_claimed() {
+        let mut state = WorldState::new();
+        let producer = address(0);
+        let payout = address(1);
+        let fee = Amount::new(spirachain_core::MIN_TX_FEE);
+
+        let coinbase = Transaction::new_coinbase(producer, Amount::qbt(10), 1);
+        state.apply_transaction(&coinbase).unwrap();
+        assert_eq!(state.get_balance(&producer), Amount::zero());
+        assert_eq!(state.get_unclaimed_rewards(&producer), Amount::qbt(10));
+
+        let claim = Transaction::new_claim_rewards(producer, payout, fee);
+        state.apply_transaction(&claim).unwrap();
+        assert_eq!(state.get_unclaimed_rewards(&producer), Amount::zero());
+        assert_eq!(
+            state.get_balance(&payout),
+            Amount::qbt(10).checked_sub(fee).unwrap()
+        );
+        assert_eq!(state.get_nonce(&producer), 1);
+
+        // Nothing left to pay the fee with
+        assert!(state.apply_transaction(&claim).is_err());
+    }
+
+    #[test]
+    fn test_payout_address_receives_rewards() {
+        let mut state = WorldState::new();
+        let validator = address(0);
+        let cold = address(1);
+        let fee = Amount::new(spirachain_core::MIN_TX_FEE);
+        state.set_balance(validator, Amount::qbt(1));
+
+        let set = Transaction::new_set_payout_address(validator, cold, fee);
+        state.apply_transaction(&set).unwrap();
+        assert_eq!(state.payout_address(&validator), cold);
+
+        let coinbase = Transaction::new_coinbase(validator, Amount::qbt(10), 2);
+        state.apply_transaction(&coinbase).unwrap();
+        assert_eq!(state.get_unclaimed_rewards(&validator), Amount::zero());
+        assert_eq!(state.get_unclaimed_rewards(&cold), Amount::qbt(10));
+
+        let reset = Transaction::new_set_payout_address(validator, validator, fee);
+        state.apply_transaction(&reset).unwrap();
+        assert_eq!(state.get_account(&validator).unwrap().payout_address, None);
+    }
+
+    #[test]
+    fn test_coinbase_only_collects_paid_fees() {
+        let mut state = funded_state();
+        let pubkey = vec![9u8; 32];
+        let producer = Address::new(*blake3::hash(&pubkey).as_bytes());
+        let fee = Amount::new(spirachain_core::MIN_TX_FEE);
+        let hashed = |mut tx: Transaction| {
+            tx.compute_hash();
+            tx
+        };
+        let paid = hashed(Transaction::new(address(0), address(1), Amount::qbt(1), fee));
+        let unpayable = hashed(Transaction::new(
+            address(2),
+            address(3),
+            Amount::zero(),
+            Amount::qbt(1_000),
+        ));
+        let block_paying = |to: Address, amount: Amount| {
+            Block::new(Hash::zero(), 1)
+                .with_transactions(vec![
+                    hashed(Transaction::new_coinbase(to, amount, 1)),
+                    paid.clone(),
+                    unpayable.clone(),
+                ])
+                .with_validator(pubkey.clone())
+        };
+        let reward = RewardCalculator::diversity_reward_at_height(1, true);
+        let before = state.total_value().unwrap();
+
+        // Claiming the fee of a transaction that cannot pay it rejects the block
+        let (listed_fees, _) =
+            RewardCalculator::distribute_fees(fee.checked_add(unpayable.fee).unwrap());
+        let greedy = block_paying(producer, reward.checked_add(listed_fees).unwrap());
+        assert!(matches!(
+            state.apply_verified_block(&greedy),
+            Err(SpiraChainError::InvalidTransaction(_))
+        ));
+        assert_eq!(state.total_value(), Some(before));
+
+        // So does paying anyone but the producer
+        let (paid_fees, burned) = RewardCalculator::distribute_fees(fee);
+        let allowance = reward.checked_add(paid_fees).unwrap();
+        assert!(state
+            .apply_verified_block(&block_paying(address(3), allowance))
+            .is_err());
+
+        let failures = state
+            .apply_verified_block(&block_paying(producer, allowance))
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, unpayable.tx_hash);
+        assert_eq!(state.get_unclaimed_rewards(&producer), allowance);
+        // The burned share of the fee is gone for good
+        assert_eq!(
+            state.total_value(),
+            Some(before + reward.value() - burned.value())
+        );
+    }
+
+    #[test]
+    fn test_blocks_without_coinbase_follow_legacy_rules() {
+        let mut state = funded_state();
+        let pubkey = vec![9u8; 32];
+        let producer = Address::new(*blake3::hash(&pubkey).as_bytes());
+        let fee = Amount::new(spirachain_core::MIN_TX_FEE);
+        let mut transfer = Transaction::new(address(0), address(1), Amount::qbt(1), fee);
+        transfer.compute_hash();
+        let sender_before = state.get_balance(&address(0));
+        let before = state.total_value().unwrap();
+
+        let legacy = Block::new(Hash::zero(), 1)
+            .with_transactions(vec![transfer])
+            .with_validator(pubkey);
+        assert!(state.apply_verified_block(&legacy).unwrap().is_empty());
+
+        // The fee stays with the sender and the reward lands in the balance
+        assert_eq!(
+            state.get_balance(&address(0)),
+            sender_before.checked_sub(Amount::qbt(1)).unwrap()
+        );
+        let reward = RewardCalculator::block_reward_at_height(1);
+        assert_eq!(state.get_balance(&producer), reward);
+        assert_eq!(state.get_unclaimed_rewards(&producer), Amount::zero());
+        assert_eq!(state.total_value(), Some(before + reward.value()));
+    }
+
+    #[test]
+    fn test_key_rotation_moves_stake_to_fresh_key() {
+        let mut state = WorldState::new();
+        let (old, new, cold) = (address(0), address(1), address(2));
+        let fee = Amount::new(spirachain_core::MIN_TX_FEE);
+        state.set_balance(old, Amount::qbt(20));
+        state.add_stake(&old, Amount::qbt(10)).unwrap();
+        state
+            .apply_transaction(&Transaction::new_set_payout_address(old, cold, fee))
+            .unwrap();
+
+        let rotate = |from: Address, to: Address| {
+            Transaction::new(from, to, Amount::zero(), fee).with_payload(
+                TransactionPayload::RotateValidatorKey {
+                    public_key: Vec::new(),
+                    proof: Vec::new(),
+                },
+            )
+        };
+        let before = state.total_value();
+        state.apply_transaction(&rotate(old, new)).unwrap();
+        assert_eq!(state.get_stake(&old), Amount::zero());
+        assert_eq!(state.get_stake(&new), Amount::qbt(10));
+        assert_eq!(state.payout_address(&new), cold);
+        assert_eq!(state.payout_address(&old), old);
+        assert_eq!(state.get_nonce(&old), 2);
+        assert_eq!(state.total_value().unwrap() + fee.value(), before.unwrap());
+
+        // Nothing left to rotate, and stake never merges into a key that has some
+        assert!(state.apply_transaction(&rotate(old, address(3))).is_err());
+        state.set_balance(new, Amount::qbt(1));
+        state.set_balance(address(3), Amount::qbt(2));
+        state.add_stake(&address(3), Amount::qbt(1)).unwrap();
+        assert!(state.apply_transaction(&rotate(new, address(3))).is_err());
+    }
+
+    #[test]
+    fn test_pause_halts_all_but_guardian_votes() {
+        const GUARDIANS: PauseMultisig = PauseMultisig {
+            guardians: &[
+                "0x0101010101010101010101010101010101010101010101010101010101010101",
+                "0x0202020202020202020202020202020202020202020202020202020202020202",
+            ],
+            threshold: 2,
+        };
+        let mut state = funded_state().with_pause_multisig(GUARDIANS);
+        let fee = Amount::new(spirachain_core::MIN_TX_FEE);
+        let transfer = Transaction::new(address(2), address(3), Amount::qbt(1), fee);
+        let vote =
+            |guardian: u8, blocks: u64| Transaction::new_pause_vote(address(guardian), blocks, fee);
+
+        let block = Block::new(Hash::zero(), 1).with_transactions(vec![vote(0, 5), vote(1, 5)]);
+        assert!(state.apply_block(&block).is_empty());
+        state.set_height(1);
+        assert!(state.check_not_paused(&transfer).is_err());
+
+        let block = Block::new(Hash::zero(), 2).with_transactions(vec![transfer.clone()]);
+        assert_eq!(state.apply_block(&block).len(), 1);
+        assert_eq!(state.get_balance(&address(3)), Amount::qbt(100));
+
+        // Expires on its own after block 6
+        let block = Block::new(Hash::zero(), 7).with_transactions(vec![transfer]);
+        assert!(state.apply_block(&block).is_empty());
+        assert_eq!(state.get_balance(&address(3)), Amount::qbt(101));
+    }
+
+    #[test]
+    fn test_self_transfer_does_not_mint() {
+        let mut state = funded_state();
+        state
+            .transfer(&address(0), &address(0), Amount::qbt(60))
+            .unwrap();
+        assert_eq!(state.get_balance(&address(0)), Amount::qbt(100));
+    }
+}
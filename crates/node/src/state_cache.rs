@@ -0,0 +1,227 @@
+use spirachain_core::{Account, Address, Hash};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Cached hashes behind the world state root. Accounts are grouped into pages
+/// by their first two address bytes and pages into buckets by the first byte,
+/// so a block only rehashes the pages and buckets of the accounts it touched.
+/// The state entries are kept too, for the flat root of blocks before the fork.
+#[derive(Debug, Clone, Default)]
+pub struct StateRootCache {
+    dirty: HashSet<Address>,
+    entries: BTreeMap<Address, String>,
+    leaves: BTreeMap<Address, Hash>,
+    pages: BTreeMap<[u8; 2], Hash>,
+    buckets: BTreeMap<u8, Hash>,
+}
+
+impl StateRootCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `address` changed since the last root
+    pub fn mark_dirty(&mut self, address: Address) {
+        self.dirty.insert(address);
+    }
+
+    pub fn dirty_count(&self) -> usize {
+        self.dirty.len()
+    }
+
+    /// Flat root of earlier releases: one hash over every account's state entry,
+    /// sorted, then the non-account `extras`. Entries start with the fixed-width
+    /// hex address, so address order is entry order.
+    pub fn flat_root(&mut self, accounts: &HashMap<Address, Account>, extras: &[String]) -> Hash {
+        self.refresh(accounts);
+        if self.entries.is_empty() {
+            return Hash::zero();
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        for entry in self.entries.values().chain(extras) {
+            hasher.update(entry.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    /// Root over every account plus the non-account `extras`, in order
+    pub fn root(&mut self, accounts: &HashMap<Address, Account>, extras: &[String]) -> Hash {
+        self.refresh(accounts);
+        if self.leaves.is_empty() {
+            return Hash::zero();
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        for bucket in self.buckets.values() {
+            hasher.update(bucket.as_bytes());
+        }
+        for extra in extras {
+            hasher.update(extra.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    fn refresh(&mut self, accounts: &HashMap<Address, Account>) {
+        let mut touched_pages = BTreeSet::new();
+        for address in self.dirty.drain() {
+            match accounts.get(&address) {
+                Some(account) => {
+                    let entry = account.state_entry(&address);
+                    self.leaves
+                        .insert(address, blake3::hash(entry.as_bytes()).into());
+                    self.entries.insert(address, entry);
+                }
+                None => {
+                    self.entries.remove(&address);
+                    self.leaves.remove(&address);
+                }
+            }
+            let bytes = address.as_bytes();
+            touched_pages.insert([bytes[0], bytes[1]]);
+        }
+
+        let mut touched_buckets = BTreeSet::new();
+        for page in touched_pages {
+            let mut low = [0u8; 32];
+            let mut high = [0xffu8; 32];
+            low[..2].copy_from_slice(&page);
+            high[..2].copy_from_slice(&page);
+
+            let range = self.leaves.range(Address::new(low)..=Address::new(high));
+            match hash_all(range.map(|(_, leaf)| leaf)) {
+                Some(hash) => self.pages.insert(page, hash),
+                None => self.pages.remove(&page),
+            };
+            touched_buckets.insert(page[0]);
+        }
+
+        for bucket in touched_buckets {
+            let range = self.pages.range([bucket, 0]..=[bucket, 0xff]);
+            match hash_all(range.map(|(_, page)| page)) {
+                Some(hash) => self.buckets.insert(bucket, hash),
+                None => self.buckets.remove(&bucket),
+            };
+        }
+    }
+}
+
+/// Hash of the concatenated hashes, `None` when there are none
+fn hash_all<'a>(hashes: impl Iterator<Item = &'a Hash>) -> Option<Hash> {
+    let mut hasher = blake3::Hasher::new();
+    let mut any = false;
+    for hash in hashes {
+        hasher.update(hash.as_bytes());
+        any = true;
+    }
+    any.then(|| hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::Amount;
+
+    fn account(balance: u128, nonce: u64) -> Account {
+        Account {
+            balance: Amount::new(balance),
+            nonce,
+            ..Account::default()
+        }
+    }
+
+    fn address(first: u8, second: u8, last: u8) -> Address {
+        let mut bytes = [0u8; 32];
+        bytes[0] = first;
+        bytes[1] = second;
+        bytes[31] = last;
+        Address::new(bytes)
+    }
+
+    /// Cache that hashes every account from scratch
+    fn recomputed(accounts: &HashMap<Address, Account>, extras: &[String]) -> (Hash, Hash) {
+        let mut cache = StateRootCache::new();
+        for address in accounts.keys() {
+            cache.mark_dirty(*address);
+        }
+        let root = cache.root(accounts, extras);
+        (root, cache.flat_root(accounts, extras))
+    }
+
+    /// Definition of the state root before the cache existed
+    fn baseline_root(accounts: &HashMap<Address, Account>, extras: &[String]) -> Hash {
+        if accounts.is_empty() {
+            return Hash::zero();
+        }
+        let mut entries: Vec<_> = accounts
+            .iter()
+            .map(|(address, account)| account.state_entry(address))
+            .collect();
+        entries.sort();
+        entries.extend(extras.iter().cloned());
+
+        let mut hasher = blake3::Hasher::new();
+        for entry in entries {
+            hasher.update(entry.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    fn check(cache: &mut StateRootCache, accounts: &HashMap<Address, Account>, extras: &[String]) {
+        let (root, flat) = recomputed(accounts, extras);
+        assert_eq!(cache.root(accounts, extras), root);
+        assert_eq!(cache.flat_root(accounts, extras), flat);
+        assert_eq!(flat, baseline_root(accounts, extras));
+    }
+
+    #[test]
+    fn test_incremental_root_matches_full_recompute() {
+        let extras = vec!["pause:1".to_string()];
+        let mut accounts = HashMap::new();
+        let mut cache = StateRootCache::new();
+        check(&mut cache, &accounts, &extras);
+
+        // Inserts across shared and separate pages and buckets
+        let touched = [
+            address(1, 1, 1),
+            address(1, 1, 2),
+            address(1, 2, 1),
+            address(0xf0, 0, 1),
+            address(0, 0, 0xff),
+        ];
+        for (i, addr) in touched.iter().enumerate() {
+            accounts.insert(*addr, account(100 * (i as u128 + 1), 0));
+            cache.mark_dirty(*addr);
+        }
+        check(&mut cache, &accounts, &extras);
+
+        // Updates
+        accounts.insert(touched[1], account(7, 3));
+        cache.mark_dirty(touched[1]);
+        accounts.get_mut(&touched[3]).unwrap().nonce = 9;
+        cache.mark_dirty(touched[3]);
+        check(&mut cache, &accounts, &extras);
+
+        // Removals, including the last account of a page and of a bucket
+        for addr in [touched[0], touched[2], touched[3]] {
+            accounts.remove(&addr);
+            cache.mark_dirty(addr);
+        }
+        check(&mut cache, &accounts, &extras);
+        assert_eq!(cache.dirty_count(), 0);
+
+        for addr in [touched[1], touched[4]] {
+            accounts.remove(&addr);
+            cache.mark_dirty(addr);
+        }
+        check(&mut cache, &accounts, &extras);
+        assert_eq!(cache.root(&accounts, &extras), Hash::zero());
+    }
+
+    #[test]
+    fn test_flat_and_tree_roots_differ() {
+        let mut accounts = HashMap::new();
+        accounts.insert(address(3, 3, 3), account(5, 1));
+        let (root, flat) = recomputed(&accounts, &[]);
+        assert_ne!(root, flat);
+    }
+}
@@ -0,0 +1,251 @@
+// State root drift monitoring
+// Every accepted block leaves us with a state root. Now and then a few random
+// peers are asked for their tip and the root it left them with, and the
+// answers are checked against the roots we had at the same height. Peers on
+// the same block with another root mean one side's state drifted (a bad
+// migration, a corrupted database, a non-deterministic transaction) long
+// before a block committing to it gets rejected; another block at the same
+// height is a fork. Both are counted on `/metrics` and reported by webhook.
+
+use parking_lot::Mutex;
+use rand::seq::SliceRandom;
+use spirachain_core::Hash;
+use spirachain_network::PeerId;
+use spirachain_rpc::server::MetricsSource;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// How often a round of peers is asked for their state root
+pub const STATE_DRIFT_CHECK_INTERVAL: Duration = Duration::from_secs(120);
+/// Peers asked per round
+pub const STATE_DRIFT_PEERS: usize = 3;
+/// Heights of our own roots kept to compare with peers a few blocks off
+pub const STATE_ROOT_HISTORY: usize = 64;
+
+/// Tip and state root of one node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateRootSample {
+    pub height: u64,
+    pub block_hash: Hash,
+    pub state_root: Hash,
+}
+
+/// Outcome of comparing a peer's answer with our history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftVerdict {
+    Agrees,
+    /// Same block, different state
+    StateDrift {
+        ours: StateRootSample,
+    },
+    /// Another block at the same height
+    ChainDivergence {
+        ours: StateRootSample,
+    },
+    /// Out of our history, so nothing to compare with
+    Unverifiable,
+    /// Not an answer to one of our requests
+    Unsolicited,
+}
+
+impl DriftVerdict {
+    pub fn is_divergence(&self) -> bool {
+        matches!(
+            self,
+            DriftVerdict::StateDrift { .. } | DriftVerdict::ChainDivergence { .. }
+        )
+    }
+}
+
+#[derive(Default)]
+struct DriftCounters {
+    agreements: u64,
+    state_drifts: u64,
+    chain_divergences: u64,
+    unverifiable: u64,
+    /// Whether each peer's latest comparable answer disagreed with us
+    diverging: HashMap<PeerId, bool>,
+}
+
+#[derive(Default)]
+pub struct StateDriftMonitor {
+    history: Mutex<VecDeque<StateRootSample>>,
+    /// Outstanding requests by nonce
+    pending: Mutex<HashMap<u64, (PeerId, Instant)>>,
+    next_nonce: Mutex<u64>,
+    counters: Mutex<DriftCounters>,
+}
+
+impl StateDriftMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the state root a newly accepted block left us with
+    pub fn record_local(&self, sample: StateRootSample) {
+        let mut history = self.history.lock();
+        // A reorg replaces the roots above the fork point
+        history.retain(|known| known.height < sample.height);
+        history.push_back(sample);
+        while history.len() > STATE_ROOT_HISTORY {
+            history.pop_front();
+        }
+    }
+
+    /// Our tip, to answer peers with
+    pub fn latest(&self) -> Option<StateRootSample> {
+        self.history.lock().back().copied()
+    }
+
+    /// Pick up to [`STATE_DRIFT_PEERS`] of `peers` at random and return the
+    /// nonce to ask each of them with. Requests left from the previous round
+    /// are forgotten.
+    pub fn start_round(&self, peers: &[PeerId]) -> Vec<(PeerId, u64)> {
+        let mut pending = self.pending.lock();
+        pending.retain(|_, (_, asked)| asked.elapsed() < STATE_DRIFT_CHECK_INTERVAL);
+
+        let mut next_nonce = self.next_nonce.lock();
+        peers
+            .choose_multiple(&mut rand::thread_rng(), STATE_DRIFT_PEERS)
+            .map(|peer| {
+                *next_nonce += 1;
+                pending.insert(*next_nonce, (*peer, Instant::now()));
+                (*peer, *next_nonce)
+            })
+            .collect()
+    }
+
+    /// Compare `peer`'s answer to request `nonce` with our root at its height
+    pub fn check_report(&self, peer: PeerId, nonce: u64, theirs: StateRootSample) -> DriftVerdict {
+        let mut pending = self.pending.lock();
+        if pending.get(&nonce).map(|(asked, _)| *asked) != Some(peer) {
+            return DriftVerdict::Unsolicited;
+        }
+        pending.remove(&nonce);
+        drop(pending);
+
+        let ours = self
+            .history
+            .lock()
+            .iter()
+            .find(|sample| sample.height == theirs.height)
+            .copied();
+        let verdict = match ours {
+            None => DriftVerdict::Unverifiable,
+            Some(ours) if ours.block_hash != theirs.block_hash => {
+                DriftVerdict::ChainDivergence { ours }
+            }
+            Some(ours) if ours.state_root != theirs.state_root => DriftVerdict::StateDrift { ours },
+            Some(_) => DriftVerdict::Agrees,
+        };
+
+        let mut counters = self.counters.lock();
+        match verdict {
+            DriftVerdict::Agrees => counters.agreements += 1,
+            DriftVerdict::StateDrift { .. } => counters.state_drifts += 1,
+            DriftVerdict::ChainDivergence { .. } => counters.chain_divergences += 1,
+            DriftVerdict::Unverifiable => counters.unverifiable += 1,
+            DriftVerdict::Unsolicited => {}
+        }
+        if verdict != DriftVerdict::Unverifiable {
+            counters.diverging.insert(peer, verdict.is_divergence());
+        }
+        verdict
+    }
+
+    /// Stop tracking a disconnected peer
+    pub fn forget_peer(&self, peer: &PeerId) {
+        self.counters.lock().diverging.remove(peer);
+    }
+}
+
+impl MetricsSource for StateDriftMonitor {
+    fn export_prometheus(&self) -> String {
+        let counters = self.counters.lock();
+        let diverging = counters.diverging.values().filter(|d| **d).count();
+        format!(
+            "# HELP spirachain_state_root_checks_total Peer state roots compared with ours, by outcome\n\
+             # TYPE spirachain_state_root_checks_total counter\n\
+             spirachain_state_root_checks_total{{result=\"agree\"}} {}\n\
+             spirachain_state_root_checks_total{{result=\"state_drift\"}} {}\n\
+             spirachain_state_root_checks_total{{result=\"chain_divergence\"}} {}\n\
+             spirachain_state_root_checks_total{{result=\"unverifiable\"}} {}\n\
+             # HELP spirachain_state_root_diverging_peers Peers whose latest state root disagreed with ours\n\
+             # TYPE spirachain_state_root_diverging_peers gauge\n\
+             spirachain_state_root_diverging_peers {}\n",
+            counters.agreements,
+            counters.state_drifts,
+            counters.chain_divergences,
+            counters.unverifiable,
+            diverging
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(height: u64, block: u8, root: u8) -> StateRootSample {
+        StateRootSample {
+            height,
+            block_hash: Hash::new([block; 32]),
+            state_root: Hash::new([root; 32]),
+        }
+    }
+
+    #[test]
+    fn test_peer_roots_are_compared_at_their_height() {
+        let monitor = StateDriftMonitor::new();
+        for height in 1..=5 {
+            monitor.record_local(sample(height, height as u8, height as u8));
+        }
+        let peers: Vec<PeerId> = (0..5).map(|_| PeerId::random()).collect();
+        let round = monitor.start_round(&peers);
+        assert_eq!(round.len(), STATE_DRIFT_PEERS);
+        let [(a, nonce_a), (b, nonce_b), (c, nonce_c)] = <[_; 3]>::try_from(round).unwrap();
+
+        // Only the asked peer can answer, and only once
+        assert_eq!(
+            monitor.check_report(b, nonce_a, sample(5, 5, 5)),
+            DriftVerdict::Unsolicited
+        );
+        assert_eq!(
+            monitor.check_report(a, nonce_a, sample(4, 4, 4)),
+            DriftVerdict::Agrees
+        );
+        assert_eq!(
+            monitor.check_report(a, nonce_a, sample(4, 4, 9)),
+            DriftVerdict::Unsolicited
+        );
+        assert_eq!(
+            monitor.check_report(b, nonce_b, sample(5, 5, 9)),
+            DriftVerdict::StateDrift {
+                ours: sample(5, 5, 5)
+            }
+        );
+        assert!(monitor
+            .check_report(c, nonce_c, sample(3, 7, 3))
+            .is_divergence());
+        assert!(monitor
+            .export_prometheus()
+            .contains("spirachain_state_root_diverging_peers 2"));
+
+        // A reorg replaces our roots from the fork point
+        monitor.record_local(sample(3, 7, 3));
+        assert_eq!(monitor.latest(), Some(sample(3, 7, 3)));
+        let (_, nonce) = monitor.start_round(&[c])[0];
+        assert_eq!(
+            monitor.check_report(c, nonce, sample(3, 7, 3)),
+            DriftVerdict::Agrees
+        );
+        let (_, nonce) = monitor.start_round(&[c])[0];
+        assert_eq!(
+            monitor.check_report(c, nonce, sample(8, 8, 8)),
+            DriftVerdict::Unverifiable
+        );
+        assert!(monitor
+            .export_prometheus()
+            .contains("spirachain_state_root_diverging_peers 1"));
+    }
+}
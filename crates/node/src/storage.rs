@@ -1,13 +1,92 @@
-use sled::{Db, Tree};
-use spirachain_core::{Address, Amount, Block, Hash, Result, SpiraChainError, Transaction};
+use crate::{AtRestPolicy, ChainHeadCache};
+use serde::{Deserialize, Serialize};
+use sled::{Db, IVec, Tree};
+use spirachain_consensus::Checkpoint;
+use spirachain_core::{
+    Account, Address, Amount, Block, BlockHeader, ContinuityProof, Entity, EpochSummary, Hash,
+    Intent, NameRegistry, PauseState, PiCoordinate, Result, SemanticModelState, SpiraChainError,
+    SpiralDiversity,
+    SpiralMetadata, SpiralPosition, SpiralRegistry, Transaction, TransactionPayload,
+    ValidatorChange, ValidatorSetChange, VestingSchedule, DIVERSITY_EPOCH_BLOCKS,
+};
+use spirachain_rpc::EpochSemanticsResponse;
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
 
+/// On-disk schema written by this binary.
+/// v1: blocks, transactions, balances (unversioned legacy databases)
+/// v2: transactions carry a payload; contract code tree and contract account entries
+/// v3: transactions carry a fork id
+/// v4: one `account:` record per address replaces `balance:` and `contract:` entries
+/// v5: block headers carry extra data
+/// v6: accounts carry unclaimed block rewards
+/// v7: block headers carry an event bloom
+/// v8: semantic vectors move to a side store; transactions carry a commitment
+/// v9: transactions carry an optional execution height
+/// v10: accounts carry a payout address
+/// v11: transactions carry their semantic score on-chain
+/// v12: transactions carry an optional sender nonce
+pub const STORAGE_SCHEMA_VERSION: u32 = 12;
+
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+
+const PAUSE_STATE_KEY: &[u8] = b"pause_state";
+const SEMANTIC_MODELS_KEY: &[u8] = b"semantic_models";
+
+const SPIRAL_REGISTRY_KEY: &[u8] = b"spiral_registry";
+const NAME_REGISTRY_KEY: &[u8] = b"name_registry";
+
+const SPIRAL_DIVERSITY_KEY: &[u8] = b"spiral_diversity";
+
+/// Validators in the set as of the last recorded change
+const VALIDATOR_SET_KEY: &[u8] = b"validator_set";
+
+/// First height whose semantic vectors have not been pruned
+const VECTOR_PRUNE_HEIGHT_KEY: &[u8] = b"vector_prune_height";
+
+/// Migration from `version` to `version + 1`
+type Migration = fn(&NodeStorage) -> Result<()>;
+
+const MIGRATIONS: &[(u32, Migration)] = &[
+    (1, migrate_v1_to_v2),
+    (2, migrate_v2_to_v3),
+    (3, migrate_v3_to_v4),
+    (4, migrate_v4_to_v5),
+    (5, migrate_v5_to_v6),
+    (6, migrate_v6_to_v7),
+    (7, migrate_v7_to_v8),
+    (8, migrate_v8_to_v9),
+    (9, migrate_v9_to_v10),
+    (10, migrate_v10_to_v11),
+    (11, migrate_v11_to_v12),
+];
+
+/// Semantic vectors removed by one pruning pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrunedVectors {
+    pub count: usize,
+    /// Keys included
+    pub bytes: u64,
+}
+
 pub struct NodeStorage {
     db: Db,
     blocks: Tree,
     transactions: Tree,
     state: Tree,
     block_by_height: Tree,
+    code: Tree,
+    meta: Tree,
+    checkpoints: Tree,
+    semantic_vectors: Tree,
+    semantic_analytics: Tree,
+    validator_changes: Tree,
+    /// Validated spiral continuity, keyed by block hash ‖ parent hash
+    continuity_proofs: Tree,
+    /// Sealing and privacy rules for the semantic side-stores
+    at_rest: AtRestPolicy,
+    /// Tip and recent headers, kept current by `store_block`
+    head: ChainHeadCache,
 }
 
 impl NodeStorage {
@@ -46,13 +125,171 @@ impl NodeStorage {
             SpiraChainError::StorageError(format!("Failed to open block_by_height tree: {}", e))
         })?;
 
-        Ok(Self {
+        let code = db.open_tree(b"code").map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to open code tree: {}", e))
+        })?;
+
+        let meta = db.open_tree(b"meta").map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to open meta tree: {}", e))
+        })?;
+
+        let checkpoints = db.open_tree(b"checkpoints").map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to open checkpoints tree: {}", e))
+        })?;
+
+        let semantic_vectors = db.open_tree(b"semantic_vectors").map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to open semantic_vectors tree: {}", e))
+        })?;
+
+        let semantic_analytics = db.open_tree(b"semantic_analytics").map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to open semantic_analytics tree: {}", e))
+        })?;
+
+        let validator_changes = db.open_tree(b"validator_changes").map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to open validator_changes tree: {}", e))
+        })?;
+
+        let continuity_proofs = db.open_tree(b"continuity_proofs").map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to open continuity_proofs tree: {}", e))
+        })?;
+
+        let storage = Self {
             db,
             blocks,
             transactions,
             state,
             block_by_height,
-        })
+            code,
+            meta,
+            checkpoints,
+            semantic_vectors,
+            semantic_analytics,
+            validator_changes,
+            continuity_proofs,
+            at_rest: AtRestPolicy::default(),
+            head: ChainHeadCache::new(),
+        };
+
+        storage.upgrade_schema(path_ref)?;
+        // Migrations rewrite blocks behind the cache
+        storage.head.clear();
+
+        Ok(storage)
+    }
+
+    /// Schema version stamped in the database, `None` for a brand new database
+    pub fn schema_version(&self) -> Result<Option<u32>> {
+        let stored = self
+            .meta
+            .get(SCHEMA_VERSION_KEY)
+            .map_err(|e| SpiraChainError::StorageError(e.to_string()))?;
+
+        match stored {
+            Some(bytes) => {
+                let array: [u8; 4] = bytes.as_ref().try_into().map_err(|_| {
+                    SpiraChainError::StorageError("Corrupted schema version".to_string())
+                })?;
+                Ok(Some(u32::from_be_bytes(array)))
+            }
+            // Databases created before versioning have blocks but no stamp
+            None if !self.blocks.is_empty() => Ok(Some(1)),
+            None => Ok(None),
+        }
+    }
+
+    fn set_schema_version(&self, version: u32) -> Result<()> {
+        self.meta
+            .insert(SCHEMA_VERSION_KEY, &version.to_be_bytes())
+            .map_err(|e| SpiraChainError::StorageError(e.to_string()))?;
+        self.flush()
+    }
+
+    /// Refuse databases from newer binaries, migrate older ones after taking a backup
+    fn upgrade_schema(&self, path: &Path) -> Result<()> {
+        let version = match self.schema_version()? {
+            Some(version) => version,
+            None => return self.set_schema_version(STORAGE_SCHEMA_VERSION),
+        };
+
+        if version > STORAGE_SCHEMA_VERSION {
+            return Err(SpiraChainError::StorageError(format!(
+                "Database at {:?} uses schema v{} but this binary supports up to v{}. Upgrade SpiraChain before starting this node.",
+                path, version, STORAGE_SCHEMA_VERSION
+            )));
+        }
+
+        if version == STORAGE_SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        let backup_path = self.backup(path, version)?;
+        tracing::info!(
+            "💾 Backed up database (schema v{}) to {:?}",
+            version,
+            backup_path
+        );
+
+        for (from, migration) in MIGRATIONS.iter().filter(|(from, _)| *from >= version) {
+            tracing::info!("🔧 Migrating storage schema v{} -> v{}", from, from + 1);
+            migration(self).map_err(|e| {
+                SpiraChainError::StorageError(format!(
+                    "Migration v{} -> v{} failed ({}). Restore the backup at {:?}",
+                    from,
+                    from + 1,
+                    e,
+                    backup_path
+                ))
+            })?;
+            self.set_schema_version(from + 1)?;
+        }
+
+        tracing::info!("✅ Storage schema is now v{}", STORAGE_SCHEMA_VERSION);
+        Ok(())
+    }
+
+    /// Copy every tree into a sibling database before migrating
+    fn backup(&self, path: &Path, version: u32) -> Result<std::path::PathBuf> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut backup_name = path
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_else(|| "data".into());
+        backup_name.push(format!(".backup-v{}-{}", version, timestamp));
+        let backup_path = path.with_file_name(backup_name);
+
+        self.copy_to(&backup_path)?;
+        Ok(backup_path)
+    }
+
+    /// Copy every tree into a new database at `path`. Trees are read one
+    /// after the other, so writes made meanwhile may be partly included.
+    pub fn copy_to(&self, path: &Path) -> Result<()> {
+        self.flush()?;
+        let copy = sled::open(path).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to create copy at {:?}: {}", path, e))
+        })?;
+        copy.import(self.db.export());
+        copy.flush()
+            .map_err(|e| SpiraChainError::StorageError(format!("Failed to flush copy: {}", e)))?;
+        Ok(())
+    }
+
+    /// Replace every tree with the contents of `source`
+    pub fn replace_with(&self, source: &NodeStorage) -> Result<()> {
+        let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+        for name in self.db.tree_names() {
+            self.db
+                .open_tree(name)
+                .and_then(|tree| tree.clear())
+                .map_err(storage_error)?;
+        }
+        self.db.import(source.db.export());
+        self.head.clear();
+        self.flush()
     }
 
     pub fn store_block(&self, block: &Block) -> Result<()> {
@@ -72,16 +309,21 @@ impl NodeStorage {
                 SpiraChainError::StorageError(format!("Failed to index block by height: {}", e))
             })?;
 
+        for tx in block
+            .transactions
+            .iter()
+            .filter(|tx| !tx.semantic_vector.is_empty())
+        {
+            self.store_semantic_vector(&tx.tx_hash, &tx.semantic_vector)?;
+        }
+        self.head.record_stored(block);
+
         tracing::info!("Stored block at height {}", block.header.block_height);
         Ok(())
     }
 
     pub fn get_block(&self, hash: &Hash) -> Result<Option<Block>> {
-        match self
-            .blocks
-            .get(hash.as_bytes())
-            .map_err(|e| SpiraChainError::StorageError(format!("Failed to get block: {}", e)))?
-        {
+        match self.get_block_bytes(hash)? {
             Some(data) => {
                 let block: Block = bincode::deserialize(&data).map_err(|e| {
                     SpiraChainError::SerializationError(format!(
@@ -96,21 +338,74 @@ impl NodeStorage {
     }
 
     pub fn get_block_by_height(&self, height: u64) -> Result<Option<Block>> {
+        match self.block_hash_at(height)? {
+            Some(hash) => self.get_block(&hash),
+            None => Ok(None),
+        }
+    }
+
+    /// Block as stored: the bincode encoding peers receive, shared with
+    /// sled's page cache rather than copied or decoded
+    pub fn get_block_bytes(&self, hash: &Hash) -> Result<Option<IVec>> {
+        self.blocks
+            .get(hash.as_bytes())
+            .map_err(|e| SpiraChainError::StorageError(format!("Failed to get block: {}", e)))
+    }
+
+    /// Stored encoding of the block at `height`, for serving syncing peers
+    pub fn get_block_bytes_by_height(&self, height: u64) -> Result<Option<IVec>> {
+        match self.block_hash_at(height)? {
+            Some(hash) => self.get_block_bytes(&hash),
+            None => Ok(None),
+        }
+    }
+
+    fn block_hash_at(&self, height: u64) -> Result<Option<Hash>> {
         let height_key = height.to_be_bytes();
 
-        match self.block_by_height.get(height_key).map_err(|e| {
-            SpiraChainError::StorageError(format!("Failed to get block hash by height: {}", e))
-        })? {
-            Some(hash_bytes) => {
+        Ok(self
+            .block_by_height
+            .get(height_key)
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to get block hash by height: {}", e))
+            })?
+            .map(|hash_bytes| {
                 let mut hash_array = [0u8; 32];
                 hash_array.copy_from_slice(&hash_bytes);
-                self.get_block(&Hash::from(hash_array))
-            }
-            None => Ok(None),
-        }
+                Hash::from(hash_array)
+            }))
+    }
+
+    /// Summary of `epoch` recomputed from stored blocks, chained to the one
+    /// carried by the epoch's own first block
+    pub fn compute_epoch_summary(&self, epoch: u64) -> Result<EpochSummary> {
+        let start = epoch * DIVERSITY_EPOCH_BLOCKS;
+        let blocks = (start..start + DIVERSITY_EPOCH_BLOCKS)
+            .map(|height| {
+                self.get_block_by_height(height)?
+                    .ok_or_else(|| SpiraChainError::BlockNotFound(height.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let previous = blocks
+            .first()
+            .and_then(|block| block.epoch_summary())
+            .cloned();
+        Ok(EpochSummary::compute(epoch, previous.as_ref(), &blocks))
+    }
+
+    /// Header of the block at `height`, from memory for recent blocks
+    pub fn get_header_by_height(&self, height: u64) -> Result<Option<BlockHeader>> {
+        self.head.header_or_load(height, || {
+            Ok(self.get_block_by_height(height)?.map(|block| block.header))
+        })
     }
 
+    /// The tip, from memory once read
     pub fn get_latest_block(&self) -> Result<Option<Block>> {
+        self.head.tip_or_load(|| self.load_latest_block())
+    }
+
+    fn load_latest_block(&self) -> Result<Option<Block>> {
         let last_entry = self.block_by_height.last().map_err(|e| {
             SpiraChainError::StorageError(format!("Failed to get latest block: {}", e))
         })?;
@@ -126,6 +421,9 @@ impl NodeStorage {
     }
 
     pub fn get_chain_height(&self) -> Result<u64> {
+        if let Some(height) = self.head.tip_height() {
+            return Ok(height);
+        }
         match self.block_by_height.last().map_err(|e| {
             SpiraChainError::StorageError(format!("Failed to get chain height: {}", e))
         })? {
@@ -153,6 +451,91 @@ impl NodeStorage {
         Ok(())
     }
 
+    /// Keep the off-chain semantic vector of the transaction `tx_hash`; a no-op
+    /// in privacy mode
+    pub fn store_semantic_vector(&self, tx_hash: &Hash, vector: &[f32]) -> Result<()> {
+        if self.at_rest.privacy_mode {
+            return Ok(());
+        }
+        let data = bincode::serialize(vector)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        let data = self.at_rest.seal(data)?;
+        self.semantic_vectors
+            .insert(tx_hash.as_bytes(), data)
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to store semantic vector: {}", e))
+            })?;
+        Ok(())
+    }
+
+    pub fn get_semantic_vector(&self, tx_hash: &Hash) -> Result<Option<Vec<f32>>> {
+        match self.semantic_vectors.get(tx_hash.as_bytes()).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get semantic vector: {}", e))
+        })? {
+            Some(data) => bincode::deserialize(&self.at_rest.open(&data)?)
+                .map(Some)
+                .map_err(|e| SpiraChainError::SerializationError(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Bytes held by the semantic vector store, keys included
+    pub fn semantic_vector_bytes(&self) -> Result<u64> {
+        let mut total = 0u64;
+        for entry in self.semantic_vectors.iter() {
+            let (key, value) = entry.map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to read semantic vectors: {}", e))
+            })?;
+            total += (key.len() + value.len()) as u64;
+        }
+        Ok(total)
+    }
+
+    /// Drop semantic vectors oldest block first until at least `bytes` are
+    /// freed, or none are left. Transactions keep their vector commitment, so
+    /// blocks stay valid; only the off-chain copy is lost. Resumes where the
+    /// previous call stopped.
+    pub fn prune_semantic_vectors(&self, bytes: u64) -> Result<PrunedVectors> {
+        let chain_height = self.get_chain_height()?;
+        let mut height = match self.meta.get(VECTOR_PRUNE_HEIGHT_KEY).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get vector prune height: {}", e))
+        })? {
+            Some(bytes) => u64::from_be_bytes(bytes.as_ref().try_into().map_err(|_| {
+                SpiraChainError::StorageError("Corrupt vector prune height".to_string())
+            })?),
+            None => 0,
+        };
+
+        let mut pruned = PrunedVectors::default();
+        while pruned.bytes < bytes && height <= chain_height {
+            if let Some(block) = self.get_block_by_height(height)? {
+                for tx in &block.transactions {
+                    let removed = self
+                        .semantic_vectors
+                        .remove(tx.tx_hash.as_bytes())
+                        .map_err(|e| {
+                            SpiraChainError::StorageError(format!(
+                                "Failed to prune semantic vector: {}",
+                                e
+                            ))
+                        })?;
+                    if let Some(value) = removed {
+                        pruned.bytes += (32 + value.len()) as u64;
+                        pruned.count += 1;
+                    }
+                }
+            }
+            height += 1;
+        }
+
+        self.meta
+            .insert(VECTOR_PRUNE_HEIGHT_KEY, &height.to_be_bytes())
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to store vector prune height: {}", e))
+            })?;
+        Ok(pruned)
+    }
+
     pub fn get_transaction(&self, hash: &Hash) -> Result<Option<Transaction>> {
         match self.transactions.get(hash.as_bytes()).map_err(|e| {
             SpiraChainError::StorageError(format!("Failed to get transaction: {}", e))
@@ -170,20 +553,20 @@ impl NodeStorage {
         }
     }
 
-    pub fn store_balance(&self, address: &Address, balance: Amount) -> Result<()> {
-        let key = format!("balance:{}", address);
-        let value = bincode::serialize(&balance)
+    pub fn store_account(&self, address: &Address, account: &Account) -> Result<()> {
+        let key = format!("account:{}", address);
+        let data = bincode::serialize(account)
             .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
 
         self.state
-            .insert(key.as_bytes(), value.as_slice())
+            .insert(key.as_bytes(), data)
             .map_err(|e| SpiraChainError::StorageError(e.to_string()))?;
 
         Ok(())
     }
 
-    pub fn get_balance(&self, address: &Address) -> Result<Amount> {
-        let key = format!("balance:{}", address);
+    pub fn get_account(&self, address: &Address) -> Result<Option<Account>> {
+        let key = format!("account:{}", address);
 
         match self
             .state
@@ -191,22 +574,46 @@ impl NodeStorage {
             .map_err(|e| SpiraChainError::StorageError(e.to_string()))?
         {
             Some(data) => {
-                let balance: Amount = bincode::deserialize(&data)
+                let account: Account = bincode::deserialize(&data)
                     .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
-                Ok(balance)
+                Ok(Some(account))
             }
-            None => Ok(Amount::zero()),
+            None => Ok(None),
         }
     }
 
-    pub fn set_balance(&self, address: &Address, balance: Amount) -> Result<()> {
-        let key = format!("balance:{}", address);
-        let data = bincode::serialize(&balance)
-            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+    pub fn get_all_accounts(&self) -> Result<Vec<(Address, Account)>> {
+        let mut accounts = Vec::new();
 
-        self.state
-            .insert(key.as_bytes(), data)
-            .map_err(|e| SpiraChainError::StorageError(e.to_string()))?;
+        for item in self.state.scan_prefix(b"account:") {
+            let (key, data) = item.map_err(|e| SpiraChainError::StorageError(e.to_string()))?;
+            let Some(address) = std::str::from_utf8(&key)
+                .ok()
+                .and_then(|key| key.strip_prefix("account:"))
+                .and_then(|address| address.parse::<Address>().ok())
+            else {
+                continue;
+            };
+            let account: Account = bincode::deserialize(&data)
+                .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+            accounts.push((address, account));
+        }
+
+        Ok(accounts)
+    }
+
+    pub fn get_balance(&self, address: &Address) -> Result<Amount> {
+        Ok(self
+            .get_account(address)?
+            .map(|account| account.balance)
+            .unwrap_or(Amount::zero()))
+    }
+
+    /// Update only the balance of an account and flush
+    pub fn set_balance(&self, address: &Address, balance: Amount) -> Result<()> {
+        let mut account = self.get_account(address)?.unwrap_or_default();
+        account.balance = balance;
+        self.store_account(address, &account)?;
 
         // Flush to disk to ensure persistence
         self.flush()?;
@@ -215,91 +622,2442 @@ impl NodeStorage {
     }
 
     pub fn get_all_addresses(&self) -> Result<Vec<Address>> {
-        let mut addresses = Vec::new();
-        let prefix = b"balance:";
-        
-        for item in self.state.scan_prefix(prefix) {
-            if let Ok((key, _value)) = item {
-                // Extract address from key "balance:0x..."
-                if let Ok(key_str) = std::str::from_utf8(&key) {
-                    if let Some(addr_str) = key_str.strip_prefix("balance:0x") {
-                        if let Ok(addr_bytes) = hex::decode(addr_str) {
-                            if addr_bytes.len() == 32 {
-                                let mut addr_array = [0u8; 32];
-                                addr_array.copy_from_slice(&addr_bytes);
-                                addresses.push(Address::new(addr_array));
-                            }
-                        }
-                    }
-                }
+        Ok(self
+            .get_all_accounts()?
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect())
+    }
+
+    /// Contract bytecode is stored once per code hash
+    pub fn store_contract_code(&self, code_hash: &Hash, code: &[u8]) -> Result<()> {
+        self.code
+            .insert(code_hash.as_bytes(), code)
+            .map_err(|e| SpiraChainError::StorageError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub fn get_contract_code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .code
+            .get(code_hash.as_bytes())
+            .map_err(|e| SpiraChainError::StorageError(e.to_string()))?
+            .map(|data| data.to_vec()))
+    }
+
+    /// Store (code_hash, storage_root) for a contract account
+    pub fn set_contract(
+        &self,
+        address: &Address,
+        code_hash: &Hash,
+        storage_root: &Hash,
+    ) -> Result<()> {
+        let mut account = self.get_account(address)?.unwrap_or_default();
+        account.code_hash = Some(*code_hash);
+        account.storage_root = *storage_root;
+        self.store_account(address, &account)
+    }
+
+    /// (code_hash, storage_root) of a contract account
+    pub fn get_contract(&self, address: &Address) -> Result<Option<(Hash, Hash)>> {
+        Ok(self.get_account(address)?.and_then(|account| {
+            account
+                .code_hash
+                .map(|code_hash| (code_hash, account.storage_root))
+        }))
+    }
+
+    /// Checkpoints are keyed by height, so they load back in order
+    pub fn store_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        self.checkpoints
+            .insert(
+                checkpoint.height.to_be_bytes(),
+                checkpoint.block_hash.as_bytes(),
+            )
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to store checkpoint: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    pub fn get_checkpoints(&self) -> Result<Vec<Checkpoint>> {
+        self.checkpoints
+            .iter()
+            .map(|entry| {
+                let (height_bytes, hash_bytes) = entry.map_err(|e| {
+                    SpiraChainError::StorageError(format!("Failed to read checkpoints: {}", e))
+                })?;
+                checkpoint_from_entry(&height_bytes, &hash_bytes)
+            })
+            .collect()
+    }
+
+    pub fn latest_checkpoint(&self) -> Result<Option<Checkpoint>> {
+        match self.checkpoints.last().map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get latest checkpoint: {}", e))
+        })? {
+            Some((height_bytes, hash_bytes)) => {
+                checkpoint_from_entry(&height_bytes, &hash_bytes).map(Some)
             }
+            None => Ok(None),
         }
-        
-        Ok(addresses)
     }
 
-    pub fn flush(&self) -> Result<()> {
-        self.db.flush().map_err(|e| {
-            SpiraChainError::StorageError(format!("Failed to flush database: {}", e))
+    /// Epoch aggregates are keyed by epoch, so the last entry is the latest.
+    /// Privacy mode drops the entity ranking.
+    pub fn store_epoch_semantics(&self, semantics: &EpochSemanticsResponse) -> Result<()> {
+        let mut semantics = semantics.clone();
+        if self.at_rest.privacy_mode {
+            semantics.top_entities.clear();
+        }
+        let bytes = bincode::serialize(&semantics).map_err(|e| {
+            SpiraChainError::SerializationError(format!(
+                "Failed to serialize epoch semantics: {}",
+                e
+            ))
         })?;
+        self.semantic_analytics
+            .insert(semantics.epoch.to_be_bytes(), self.at_rest.seal(bytes)?)
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to store epoch semantics: {}", e))
+            })?;
+
         Ok(())
     }
-}
 
-pub struct BlockStorage {
-    storage: NodeStorage,
-}
+    pub fn get_epoch_semantics(&self, epoch: u64) -> Result<Option<EpochSemanticsResponse>> {
+        let bytes = self
+            .semantic_analytics
+            .get(epoch.to_be_bytes())
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to get epoch semantics: {}", e))
+            })?;
+        bytes
+            .map(|bytes| self.decode_epoch_semantics(&bytes))
+            .transpose()
+    }
 
-impl BlockStorage {
-    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
-        Ok(Self {
-            storage: NodeStorage::new(path)?,
-        })
+    pub fn latest_epoch_semantics(&self) -> Result<Option<EpochSemanticsResponse>> {
+        let entry = self.semantic_analytics.last().map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get latest epoch semantics: {}", e))
+        })?;
+        entry
+            .map(|(_, bytes)| self.decode_epoch_semantics(&bytes))
+            .transpose()
     }
 
-    pub fn store_block(&self, block: &Block) -> Result<()> {
-        self.storage.store_block(block)
+    /// Append `change` to its epoch's history, keyed by epoch. The slot set is
+    /// rebuilt on every start, so a join of a validator already in the
+    /// recorded set, or a departure of one not in it, is a repeat and skipped.
+    /// Returns whether the change was recorded.
+    pub fn record_validator_change(&self, change: &ValidatorSetChange) -> Result<bool> {
+        let mut validators = self.get_validator_set()?;
+        let repeat = match change.change {
+            ValidatorChange::Joined => !validators.insert(change.validator),
+            ValidatorChange::Left => !validators.remove(&change.validator),
+            _ => false,
+        };
+        if repeat {
+            return Ok(false);
+        }
+
+        let mut changes = self.get_validator_changes(change.epoch())?;
+        changes.push(change.clone());
+        let bytes = bincode::serialize(&changes).map_err(|e| {
+            SpiraChainError::SerializationError(format!(
+                "Failed to serialize validator set changes: {}",
+                e
+            ))
+        })?;
+        self.validator_changes
+            .insert(change.epoch().to_be_bytes(), bytes)
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!(
+                    "Failed to store validator set changes: {}",
+                    e
+                ))
+            })?;
+
+        let bytes = bincode::serialize(&validators).map_err(|e| {
+            SpiraChainError::SerializationError(format!("Failed to serialize validator set: {}", e))
+        })?;
+        self.meta.insert(VALIDATOR_SET_KEY, bytes).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to store validator set: {}", e))
+        })?;
+        Ok(true)
     }
 
-    pub fn get_block(&self, hash: &Hash) -> Result<Option<Block>> {
-        self.storage.get_block(hash)
+    pub fn get_validator_changes(&self, epoch: u64) -> Result<Vec<ValidatorSetChange>> {
+        match self
+            .validator_changes
+            .get(epoch.to_be_bytes())
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to get validator set changes: {}", e))
+            })? {
+            Some(bytes) => bincode::deserialize(&bytes).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize validator set changes: {}",
+                    e
+                ))
+            }),
+            None => Ok(Vec::new()),
+        }
     }
 
-    pub fn get_block_by_height(&self, height: u64) -> Result<Option<Block>> {
-        self.storage.get_block_by_height(height)
+    pub fn store_continuity_proof(&self, proof: &ContinuityProof) -> Result<()> {
+        let bytes = bincode::serialize(proof).map_err(|e| {
+            SpiraChainError::SerializationError(format!(
+                "Failed to serialize continuity proof: {}",
+                e
+            ))
+        })?;
+        self.continuity_proofs
+            .insert(continuity_key(&proof.block_hash, &proof.parent_hash), bytes)
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to store continuity proof: {}", e))
+            })?;
+        Ok(())
     }
 
-    pub fn get_latest_block(&self) -> Result<Option<Block>> {
-        self.storage.get_latest_block()
+    pub fn get_continuity_proof(
+        &self,
+        block_hash: &Hash,
+        parent_hash: &Hash,
+    ) -> Result<Option<ContinuityProof>> {
+        match self
+            .continuity_proofs
+            .get(continuity_key(block_hash, parent_hash))
+            .map_err(|e| {
+                SpiraChainError::StorageError(format!("Failed to get continuity proof: {}", e))
+            })? {
+            Some(bytes) => bincode::deserialize(&bytes).map(Some).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize continuity proof: {}",
+                    e
+                ))
+            }),
+            None => Ok(None),
+        }
     }
 
-    pub fn get_chain_height(&self) -> Result<u64> {
-        self.storage.get_chain_height()
+    /// Proof for the block at `height` on this chain, if one was recorded
+    pub fn continuity_proof_at(&self, height: u64) -> Result<Option<ContinuityProof>> {
+        let Some(block) = self.get_block_by_height(height)? else {
+            return Ok(None);
+        };
+        self.get_continuity_proof(&block.hash(), &block.header.previous_block_hash)
     }
 
-    pub fn get_transaction(&self, hash: &Hash) -> Result<Option<Transaction>> {
-        self.storage.get_transaction(hash)
+    fn get_validator_set(&self) -> Result<BTreeSet<Address>> {
+        match self.meta.get(VALIDATOR_SET_KEY).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get validator set: {}", e))
+        })? {
+            Some(bytes) => bincode::deserialize(&bytes).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize validator set: {}",
+                    e
+                ))
+            }),
+            None => Ok(BTreeSet::new()),
+        }
     }
 
-    pub fn get_balance(&self, address: &Address) -> Result<Amount> {
-        self.storage.get_balance(address)
+    fn decode_epoch_semantics(&self, bytes: &[u8]) -> Result<EpochSemanticsResponse> {
+        bincode::deserialize(&self.at_rest.open(bytes)?).map_err(|e| {
+            SpiraChainError::SerializationError(format!(
+                "Failed to deserialize epoch semantics: {}",
+                e
+            ))
+        })
     }
 
-    pub fn set_balance(&self, address: &Address, balance: Amount) -> Result<()> {
-        self.storage.set_balance(address, balance)
+    pub fn store_pause_state(&self, pause: &PauseState) -> Result<()> {
+        let bytes = bincode::serialize(pause).map_err(|e| {
+            SpiraChainError::SerializationError(format!("Failed to serialize pause state: {}", e))
+        })?;
+        self.meta.insert(PAUSE_STATE_KEY, bytes).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to store pause state: {}", e))
+        })?;
+        Ok(())
     }
 
-    pub fn get_all_addresses(&self) -> Result<Vec<Address>> {
-        self.storage.get_all_addresses()
+    /// Empty until guardians first vote
+    pub fn get_pause_state(&self) -> Result<PauseState> {
+        match self.meta.get(PAUSE_STATE_KEY).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get pause state: {}", e))
+        })? {
+            Some(bytes) => bincode::deserialize(&bytes).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize pause state: {}",
+                    e
+                ))
+            }),
+            None => Ok(PauseState::new()),
+        }
     }
-}
 
-impl spirachain_rpc::server::BlockchainStorage for BlockStorage {
-    fn get_block_by_height(&self, height: u64) -> Result<Option<Block>> {
-        BlockStorage::get_block_by_height(self, height)
+    pub fn store_semantic_models(&self, models: &SemanticModelState) -> Result<()> {
+        let bytes = bincode::serialize(models).map_err(|e| {
+            SpiraChainError::SerializationError(format!(
+                "Failed to serialize semantic models: {}",
+                e
+            ))
+        })?;
+        self.meta.insert(SEMANTIC_MODELS_KEY, bytes).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to store semantic models: {}", e))
+        })?;
+        Ok(())
     }
 
-    fn get_balance(&self, address: &Address) -> Result<Amount> {
-        BlockStorage::get_balance(self, address)
+    /// Empty until guardians first approve a model
+    pub fn get_semantic_models(&self) -> Result<SemanticModelState> {
+        match self.meta.get(SEMANTIC_MODELS_KEY).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get semantic models: {}", e))
+        })? {
+            Some(bytes) => bincode::deserialize(&bytes).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize semantic models: {}",
+                    e
+                ))
+            }),
+            None => Ok(SemanticModelState::new()),
+        }
+    }
+
+    pub fn store_spiral_registry(&self, registry: &SpiralRegistry) -> Result<()> {
+        let bytes = bincode::serialize(registry).map_err(|e| {
+            SpiraChainError::SerializationError(format!(
+                "Failed to serialize spiral registry: {}",
+                e
+            ))
+        })?;
+        self.meta.insert(SPIRAL_REGISTRY_KEY, bytes).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to store spiral registry: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Empty until the first custom spiral is registered
+    pub fn get_spiral_registry(&self) -> Result<SpiralRegistry> {
+        match self.meta.get(SPIRAL_REGISTRY_KEY).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get spiral registry: {}", e))
+        })? {
+            Some(bytes) => bincode::deserialize(&bytes).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize spiral registry: {}",
+                    e
+                ))
+            }),
+            None => Ok(SpiralRegistry::new()),
+        }
+    }
+
+    pub fn store_name_registry(&self, registry: &NameRegistry) -> Result<()> {
+        let bytes = bincode::serialize(registry).map_err(|e| {
+            SpiraChainError::SerializationError(format!("Failed to serialize name registry: {}", e))
+        })?;
+        self.meta.insert(NAME_REGISTRY_KEY, bytes).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to store name registry: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Empty until the first name is registered
+    pub fn get_name_registry(&self) -> Result<NameRegistry> {
+        match self.meta.get(NAME_REGISTRY_KEY).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get name registry: {}", e))
+        })? {
+            Some(bytes) => bincode::deserialize(&bytes).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize name registry: {}",
+                    e
+                ))
+            }),
+            None => Ok(NameRegistry::new()),
+        }
+    }
+
+    pub fn store_spiral_diversity(&self, diversity: &SpiralDiversity) -> Result<()> {
+        let bytes = bincode::serialize(diversity).map_err(|e| {
+            SpiraChainError::SerializationError(format!(
+                "Failed to serialize spiral diversity: {}",
+                e
+            ))
+        })?;
+        self.meta.insert(SPIRAL_DIVERSITY_KEY, bytes).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to store spiral diversity: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Empty until the first block after genesis is applied
+    pub fn get_spiral_diversity(&self) -> Result<SpiralDiversity> {
+        match self.meta.get(SPIRAL_DIVERSITY_KEY).map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to get spiral diversity: {}", e))
+        })? {
+            Some(bytes) => bincode::deserialize(&bytes).map_err(|e| {
+                SpiraChainError::SerializationError(format!(
+                    "Failed to deserialize spiral diversity: {}",
+                    e
+                ))
+            }),
+            None => Ok(SpiralDiversity::new()),
+        }
+    }
+
+    /// Bytes the database occupies on disk
+    pub fn size_on_disk(&self) -> Result<u64> {
+        self.db.size_on_disk().map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to measure database size: {}", e))
+        })
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush().map_err(|e| {
+            SpiraChainError::StorageError(format!("Failed to flush database: {}", e))
+        })?;
+        Ok(())
+    }
+}
+
+fn checkpoint_from_entry(height_bytes: &[u8], hash_bytes: &[u8]) -> Result<Checkpoint> {
+    let height: [u8; 8] = height_bytes
+        .try_into()
+        .map_err(|_| SpiraChainError::StorageError("Corrupt checkpoint height".to_string()))?;
+    let hash: [u8; 32] = hash_bytes
+        .try_into()
+        .map_err(|_| SpiraChainError::StorageError("Corrupt checkpoint hash".to_string()))?;
+    Ok(Checkpoint::new(
+        u64::from_be_bytes(height),
+        Hash::from(hash),
+    ))
+}
+
+/// v1 databases predate contracts: the code tree is created on open and no
+/// account carries a contract entry. Blocks and transactions are re-encoded
+/// with the payload field, as plain transfers.
+fn migrate_v1_to_v2(storage: &NodeStorage) -> Result<()> {
+    let decode_error =
+        |e: bincode::Error| SpiraChainError::SerializationError(format!("v1 record: {}", e));
+    let encode_error = |e: bincode::Error| SpiraChainError::SerializationError(e.to_string());
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    let stale_contracts = storage.state.scan_prefix(b"contract:").count();
+    if stale_contracts > 0 {
+        return Err(SpiraChainError::StorageError(format!(
+            "{} contract entries found in a pre-contract database",
+            stale_contracts
+        )));
+    }
+
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV1 = bincode::deserialize(&data).map_err(decode_error)?;
+        let block = BlockV2 {
+            header: legacy.header,
+            transactions: legacy.transactions.into_iter().map(Into::into).collect(),
+        };
+        let data = bincode::serialize(&block).map_err(encode_error)?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    // Transactions are keyed by the hash of their encoding, so they move to a new key
+    let legacy_keys: Vec<sled::IVec> = storage
+        .transactions
+        .iter()
+        .keys()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(storage_error)?;
+    for key in legacy_keys {
+        let Some(data) = storage.transactions.get(&key).map_err(storage_error)? else {
+            continue;
+        };
+        let tx: TransactionV2 = bincode::deserialize::<TransactionV1>(&data)
+            .map_err(decode_error)?
+            .into();
+        let data = bincode::serialize(&tx).map_err(encode_error)?;
+        let new_key = Hash::from(blake3::hash(&data));
+        storage.transactions.remove(&key).map_err(storage_error)?;
+        storage
+            .transactions
+            .insert(new_key.as_bytes(), data)
+            .map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Transaction layout of unversioned databases (no payload)
+#[derive(Serialize, Deserialize)]
+struct TransactionV1 {
+    version: u64,
+    tx_hash: Hash,
+    pi_id: PiCoordinate,
+    from: Address,
+    to: Address,
+    amount: Amount,
+    fee: Amount,
+    timestamp: u64,
+    signature: Vec<u8>,
+    purpose: String,
+    semantic_vector: Vec<f32>,
+    entities: Vec<Entity>,
+    intent: Option<Intent>,
+    related_txs: Vec<Hash>,
+    spiral_position: Option<SpiralPosition>,
+    thread_id: Option<Hash>,
+    extra_data: HashMap<String, Vec<u8>>,
+}
+
+/// Every v1 transaction is a plain transfer, whose hash ignores the payload
+impl From<TransactionV1> for TransactionV2 {
+    fn from(tx: TransactionV1) -> Self {
+        Self {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature,
+            purpose: tx.purpose,
+            semantic_vector: tx.semantic_vector,
+            entities: tx.entities,
+            intent: tx.intent,
+            related_txs: tx.related_txs,
+            spiral_position: tx.spiral_position,
+            thread_id: tx.thread_id,
+            extra_data: tx.extra_data,
+            payload: TransactionPayload::Transfer,
+        }
+    }
+}
+
+/// Block layout of schema v1
+#[derive(Serialize, Deserialize)]
+struct BlockV1 {
+    header: BlockHeaderV4,
+    transactions: Vec<TransactionV1>,
+}
+
+/// Transaction layout of schema v2 (payload, no fork id)
+#[derive(Serialize, Deserialize)]
+struct TransactionV2 {
+    version: u64,
+    tx_hash: Hash,
+    pi_id: PiCoordinate,
+    from: Address,
+    to: Address,
+    amount: Amount,
+    fee: Amount,
+    timestamp: u64,
+    signature: Vec<u8>,
+    purpose: String,
+    semantic_vector: Vec<f32>,
+    entities: Vec<Entity>,
+    intent: Option<Intent>,
+    related_txs: Vec<Hash>,
+    spiral_position: Option<SpiralPosition>,
+    thread_id: Option<Hash>,
+    extra_data: HashMap<String, Vec<u8>>,
+    payload: TransactionPayload,
+}
+
+impl From<TransactionV2> for TransactionV7 {
+    fn from(tx: TransactionV2) -> Self {
+        Self {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature,
+            purpose: tx.purpose,
+            semantic_vector: tx.semantic_vector,
+            entities: tx.entities,
+            intent: tx.intent,
+            related_txs: tx.related_txs,
+            spiral_position: tx.spiral_position,
+            thread_id: tx.thread_id,
+            extra_data: tx.extra_data,
+            payload: tx.payload,
+            fork_id: Hash::zero(),
+        }
+    }
+}
+
+/// Transaction layout of schema v3 to v7 (semantic vector inline, no commitment)
+#[derive(Serialize, Deserialize)]
+struct TransactionV7 {
+    version: u64,
+    tx_hash: Hash,
+    pi_id: PiCoordinate,
+    from: Address,
+    to: Address,
+    amount: Amount,
+    fee: Amount,
+    timestamp: u64,
+    signature: Vec<u8>,
+    purpose: String,
+    semantic_vector: Vec<f32>,
+    entities: Vec<Entity>,
+    intent: Option<Intent>,
+    related_txs: Vec<Hash>,
+    spiral_position: Option<SpiralPosition>,
+    thread_id: Option<Hash>,
+    extra_data: HashMap<String, Vec<u8>>,
+    payload: TransactionPayload,
+    fork_id: Hash,
+}
+
+impl TransactionV7 {
+    /// Key of the record in the transactions tree: the hash of its encoding
+    fn storage_key(&self) -> std::result::Result<Hash, bincode::Error> {
+        Ok(Hash::from(blake3::hash(&bincode::serialize(self)?)))
+    }
+}
+
+/// Block header layout before v5 (no extra data)
+#[derive(Serialize, Deserialize)]
+struct BlockHeaderV4 {
+    version: u64,
+    previous_block_hash: Hash,
+    merkle_root: Hash,
+    spiral_root: Hash,
+    state_root: Hash,
+    timestamp: u64,
+    pi_coordinates: PiCoordinate,
+    spiral: SpiralMetadata,
+    validator_pubkey: Vec<u8>,
+    signature: Vec<u8>,
+    nonce: u64,
+    difficulty_target: u32,
+    tx_count: u32,
+    block_height: u64,
+}
+
+impl From<BlockHeaderV4> for BlockHeaderV6 {
+    fn from(header: BlockHeaderV4) -> Self {
+        Self {
+            version: header.version,
+            previous_block_hash: header.previous_block_hash,
+            merkle_root: header.merkle_root,
+            spiral_root: header.spiral_root,
+            state_root: header.state_root,
+            timestamp: header.timestamp,
+            pi_coordinates: header.pi_coordinates,
+            spiral: header.spiral,
+            validator_pubkey: header.validator_pubkey,
+            signature: header.signature,
+            nonce: header.nonce,
+            difficulty_target: header.difficulty_target,
+            tx_count: header.tx_count,
+            block_height: header.block_height,
+            extra_data: Vec::new(),
+        }
+    }
+}
+
+/// Block layout of schema v2
+#[derive(Serialize, Deserialize)]
+struct BlockV2 {
+    header: BlockHeaderV4,
+    transactions: Vec<TransactionV2>,
+}
+
+/// Block layout of schema v3 and v4
+#[derive(Serialize, Deserialize)]
+struct BlockV4 {
+    header: BlockHeaderV4,
+    transactions: Vec<TransactionV7>,
+}
+
+/// Re-encode blocks and transactions with the (zero) legacy fork id
+fn migrate_v2_to_v3(storage: &NodeStorage) -> Result<()> {
+    let decode_error =
+        |e: bincode::Error| SpiraChainError::SerializationError(format!("v2 record: {}", e));
+    let encode_error = |e: bincode::Error| SpiraChainError::SerializationError(e.to_string());
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV2 = bincode::deserialize(&data).map_err(decode_error)?;
+        let block = BlockV4 {
+            header: legacy.header,
+            transactions: legacy.transactions.into_iter().map(Into::into).collect(),
+        };
+        let data = bincode::serialize(&block).map_err(encode_error)?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    // Transactions are keyed by the hash of their encoding, so they move to a new key
+    let legacy_keys: Vec<sled::IVec> = storage
+        .transactions
+        .iter()
+        .keys()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(storage_error)?;
+    for key in legacy_keys {
+        let Some(data) = storage.transactions.get(&key).map_err(storage_error)? else {
+            continue;
+        };
+        let tx: TransactionV7 = bincode::deserialize::<TransactionV2>(&data)
+            .map_err(decode_error)?
+            .into();
+        let data = bincode::serialize(&tx).map_err(encode_error)?;
+        let new_key = tx.storage_key().map_err(encode_error)?;
+        storage.transactions.remove(&key).map_err(storage_error)?;
+        storage
+            .transactions
+            .insert(new_key.as_bytes(), data)
+            .map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Fold the v3 `balance:` and `contract:` entries into `account:` records
+fn migrate_v3_to_v4(storage: &NodeStorage) -> Result<()> {
+    let decode_error =
+        |e: bincode::Error| SpiraChainError::SerializationError(format!("v3 record: {}", e));
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    let mut accounts: HashMap<Address, Account> = HashMap::new();
+    let mut legacy_keys = Vec::new();
+
+    for prefix in ["balance:", "contract:"] {
+        for entry in storage.state.scan_prefix(prefix.as_bytes()) {
+            let (key, data) = entry.map_err(storage_error)?;
+            let address = std::str::from_utf8(&key)
+                .ok()
+                .and_then(|key| key.strip_prefix(prefix))
+                .and_then(|address| address.parse::<Address>().ok())
+                .ok_or_else(|| {
+                    SpiraChainError::StorageError(format!(
+                        "Malformed state key {}",
+                        String::from_utf8_lossy(&key)
+                    ))
+                })?;
+
+            let account = accounts.entry(address).or_default();
+            if prefix == "balance:" {
+                account.balance = bincode::deserialize(&data).map_err(decode_error)?;
+            } else {
+                let (code_hash, storage_root): (Hash, Hash) =
+                    bincode::deserialize(&data).map_err(decode_error)?;
+                account.code_hash = Some(code_hash);
+                account.storage_root = storage_root;
+            }
+            legacy_keys.push(key);
+        }
+    }
+
+    for (address, account) in &accounts {
+        storage.store_account(address, account)?;
+    }
+    for key in legacy_keys {
+        storage.state.remove(key).map_err(storage_error)?;
+    }
+
+    tracing::info!("   Migrated {} accounts", accounts.len());
+    Ok(())
+}
+
+/// Re-encode blocks with empty header extra data; hashes are unchanged
+fn migrate_v4_to_v5(storage: &NodeStorage) -> Result<()> {
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV4 = bincode::deserialize(&data)
+            .map_err(|e| SpiraChainError::SerializationError(format!("v4 record: {}", e)))?;
+        let block = BlockV6 {
+            header: legacy.header.into(),
+            transactions: legacy.transactions,
+        };
+        let data = bincode::serialize(&block)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Block header layout of schema v5 and v6 (no event bloom)
+#[derive(Serialize, Deserialize)]
+struct BlockHeaderV6 {
+    version: u64,
+    previous_block_hash: Hash,
+    merkle_root: Hash,
+    spiral_root: Hash,
+    state_root: Hash,
+    timestamp: u64,
+    pi_coordinates: PiCoordinate,
+    spiral: SpiralMetadata,
+    validator_pubkey: Vec<u8>,
+    signature: Vec<u8>,
+    nonce: u64,
+    difficulty_target: u32,
+    tx_count: u32,
+    block_height: u64,
+    extra_data: Vec<u8>,
+}
+
+impl From<BlockHeaderV6> for BlockHeader {
+    fn from(header: BlockHeaderV6) -> Self {
+        Self {
+            version: header.version,
+            previous_block_hash: header.previous_block_hash,
+            merkle_root: header.merkle_root,
+            spiral_root: header.spiral_root,
+            state_root: header.state_root,
+            timestamp: header.timestamp,
+            pi_coordinates: header.pi_coordinates,
+            spiral: header.spiral,
+            validator_pubkey: header.validator_pubkey,
+            signature: header.signature,
+            nonce: header.nonce,
+            difficulty_target: header.difficulty_target,
+            tx_count: header.tx_count,
+            block_height: header.block_height,
+            extra_data: header.extra_data,
+            event_bloom: Vec::new(),
+        }
+    }
+}
+
+/// Block layout of schema v5 and v6
+#[derive(Serialize, Deserialize)]
+struct BlockV6 {
+    header: BlockHeaderV6,
+    transactions: Vec<TransactionV7>,
+}
+
+/// Block layout of schema v7 (semantic vectors inline)
+#[derive(Serialize, Deserialize)]
+struct BlockV7 {
+    header: BlockHeader,
+    transactions: Vec<TransactionV7>,
+}
+
+/// Account layout before v6 (no unclaimed rewards)
+#[derive(Serialize, Deserialize)]
+struct AccountV5 {
+    balance: Amount,
+    nonce: u64,
+    stake: Amount,
+    code_hash: Option<Hash>,
+    storage_root: Hash,
+    vesting: Option<VestingSchedule>,
+}
+
+impl From<AccountV5> for Account {
+    fn from(account: AccountV5) -> Self {
+        Self {
+            balance: account.balance,
+            nonce: account.nonce,
+            stake: account.stake,
+            code_hash: account.code_hash,
+            storage_root: account.storage_root,
+            vesting: account.vesting,
+            unclaimed_rewards: Amount::zero(),
+            payout_address: None,
+        }
+    }
+}
+
+/// Re-encode accounts with an empty rewards bucket. Rewards paid so far are
+/// already in balances. Accounts written by the v3 migration are in the new
+/// layout; bincode ignores their trailing zero bucket when reading them as v5.
+fn migrate_v5_to_v6(storage: &NodeStorage) -> Result<()> {
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.state.scan_prefix(b"account:") {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: AccountV5 = bincode::deserialize(&data)
+            .map_err(|e| SpiraChainError::SerializationError(format!("v5 record: {}", e)))?;
+        let data = bincode::serialize(&Account::from(legacy))
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        storage.state.insert(key, data).map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Re-encode blocks with an empty event bloom; their hashes do not change
+fn migrate_v6_to_v7(storage: &NodeStorage) -> Result<()> {
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV6 = bincode::deserialize(&data)
+            .map_err(|e| SpiraChainError::SerializationError(format!("v6 record: {}", e)))?;
+        let block = BlockV7 {
+            header: legacy.header.into(),
+            transactions: legacy.transactions,
+        };
+        let data = bincode::serialize(&block)
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Move semantic vectors out of blocks and transactions into the side store.
+/// Block and transaction hashes do not cover the vector, so they are unchanged.
+fn migrate_v7_to_v8(storage: &NodeStorage) -> Result<()> {
+    let decode_error =
+        |e: bincode::Error| SpiraChainError::SerializationError(format!("v7 record: {}", e));
+    let encode_error = |e: bincode::Error| SpiraChainError::SerializationError(e.to_string());
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    let mut moved = 0usize;
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV7 = bincode::deserialize(&data).map_err(decode_error)?;
+        for tx in legacy
+            .transactions
+            .iter()
+            .filter(|tx| !tx.semantic_vector.is_empty())
+        {
+            storage.store_semantic_vector(&tx.tx_hash, &tx.semantic_vector)?;
+            moved += 1;
+        }
+        let block = BlockV8 {
+            header: legacy.header,
+            transactions: legacy.transactions.into_iter().map(Into::into).collect(),
+        };
+        let data = bincode::serialize(&block).map_err(encode_error)?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    // Transactions are keyed by the hash of their encoding, so they move to a new key
+    let legacy_keys: Vec<sled::IVec> = storage
+        .transactions
+        .iter()
+        .keys()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(storage_error)?;
+    for key in legacy_keys {
+        let Some(data) = storage.transactions.get(&key).map_err(storage_error)? else {
+            continue;
+        };
+        let legacy: TransactionV7 = bincode::deserialize(&data).map_err(decode_error)?;
+        if !legacy.semantic_vector.is_empty() {
+            storage.store_semantic_vector(&legacy.tx_hash, &legacy.semantic_vector)?;
+        }
+        let tx = TransactionV8::from(legacy);
+        storage.transactions.remove(&key).map_err(storage_error)?;
+        storage
+            .transactions
+            .insert(
+                tx.storage_key().map_err(encode_error)?.as_bytes(),
+                bincode::serialize(&tx).map_err(encode_error)?,
+            )
+            .map_err(storage_error)?;
+    }
+
+    tracing::info!("   Moved {} semantic vectors to the side store", moved);
+    Ok(())
+}
+
+/// Transaction layout of schema v8 (vector in the side store, no execution height)
+#[derive(Serialize, Deserialize)]
+struct TransactionV8 {
+    version: u64,
+    tx_hash: Hash,
+    pi_id: PiCoordinate,
+    from: Address,
+    to: Address,
+    amount: Amount,
+    fee: Amount,
+    timestamp: u64,
+    signature: Vec<u8>,
+    purpose: String,
+    /// Encoded as nothing: the vector lives in the side store
+    semantic_vector: (),
+    semantic_commitment: Hash,
+    entities: Vec<Entity>,
+    intent: Option<Intent>,
+    related_txs: Vec<Hash>,
+    spiral_position: Option<SpiralPosition>,
+    thread_id: Option<Hash>,
+    extra_data: HashMap<String, Vec<u8>>,
+    payload: TransactionPayload,
+    fork_id: Hash,
+}
+
+impl TransactionV8 {
+    fn storage_key(&self) -> std::result::Result<Hash, bincode::Error> {
+        Ok(Hash::from(blake3::hash(&bincode::serialize(self)?)))
+    }
+}
+
+/// Legacy transactions never committed to their vector, so the commitment stays
+/// zero and the transaction hash is unchanged
+impl From<TransactionV7> for TransactionV8 {
+    fn from(tx: TransactionV7) -> Self {
+        Self {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature,
+            purpose: tx.purpose,
+            semantic_vector: (),
+            semantic_commitment: Hash::zero(),
+            entities: tx.entities,
+            intent: tx.intent,
+            related_txs: tx.related_txs,
+            spiral_position: tx.spiral_position,
+            thread_id: tx.thread_id,
+            extra_data: tx.extra_data,
+            payload: tx.payload,
+            fork_id: tx.fork_id,
+        }
+    }
+}
+
+/// Legacy transactions are unscheduled, so their hash is unchanged
+impl From<TransactionV8> for TransactionV10 {
+    fn from(tx: TransactionV8) -> Self {
+        Self {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature,
+            purpose: tx.purpose,
+            semantic_vector: (),
+            semantic_commitment: tx.semantic_commitment,
+            entities: tx.entities,
+            intent: tx.intent,
+            related_txs: tx.related_txs,
+            spiral_position: tx.spiral_position,
+            thread_id: tx.thread_id,
+            extra_data: tx.extra_data,
+            payload: tx.payload,
+            fork_id: tx.fork_id,
+            execute_at: None,
+        }
+    }
+}
+
+/// Block layout of schema v8
+#[derive(Serialize, Deserialize)]
+struct BlockV8 {
+    header: BlockHeader,
+    transactions: Vec<TransactionV8>,
+}
+
+/// Re-encode blocks and transactions with an empty `execute_at`
+fn migrate_v8_to_v9(storage: &NodeStorage) -> Result<()> {
+    let decode_error =
+        |e: bincode::Error| SpiraChainError::SerializationError(format!("v8 record: {}", e));
+    let encode_error = |e: bincode::Error| SpiraChainError::SerializationError(e.to_string());
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV8 = bincode::deserialize(&data).map_err(decode_error)?;
+        let block = BlockV10 {
+            header: legacy.header,
+            transactions: legacy.transactions.into_iter().map(Into::into).collect(),
+        };
+        let data = bincode::serialize(&block).map_err(encode_error)?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    // Keyed by the hash of their encoding, so they move to a new key
+    let legacy_keys: Vec<sled::IVec> = storage
+        .transactions
+        .iter()
+        .keys()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(storage_error)?;
+    for key in legacy_keys {
+        let Some(data) = storage.transactions.get(&key).map_err(storage_error)? else {
+            continue;
+        };
+        let tx: TransactionV10 = bincode::deserialize::<TransactionV8>(&data)
+            .map_err(decode_error)?
+            .into();
+        storage.transactions.remove(&key).map_err(storage_error)?;
+        storage
+            .transactions
+            .insert(
+                tx.storage_key().map_err(encode_error)?.as_bytes(),
+                bincode::serialize(&tx).map_err(encode_error)?,
+            )
+            .map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Transaction layout of schemas v9 and v10 (no on-chain semantic score)
+#[derive(Serialize, Deserialize)]
+struct TransactionV10 {
+    version: u64,
+    tx_hash: Hash,
+    pi_id: PiCoordinate,
+    from: Address,
+    to: Address,
+    amount: Amount,
+    fee: Amount,
+    timestamp: u64,
+    signature: Vec<u8>,
+    purpose: String,
+    /// Encoded as nothing: the vector lives in the side store
+    semantic_vector: (),
+    semantic_commitment: Hash,
+    entities: Vec<Entity>,
+    intent: Option<Intent>,
+    related_txs: Vec<Hash>,
+    spiral_position: Option<SpiralPosition>,
+    thread_id: Option<Hash>,
+    extra_data: HashMap<String, Vec<u8>>,
+    payload: TransactionPayload,
+    fork_id: Hash,
+    execute_at: Option<u64>,
+}
+
+impl TransactionV10 {
+    fn storage_key(&self) -> std::result::Result<Hash, bincode::Error> {
+        Ok(Hash::from(blake3::hash(&bincode::serialize(self)?)))
+    }
+}
+
+/// A zero score is not hashed, so legacy transaction hashes are unchanged
+impl From<TransactionV10> for TransactionV11 {
+    fn from(tx: TransactionV10) -> Self {
+        Self {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature,
+            purpose: tx.purpose,
+            semantic_vector: (),
+            semantic_commitment: tx.semantic_commitment,
+            semantic_score: 0,
+            entities: tx.entities,
+            intent: tx.intent,
+            related_txs: tx.related_txs,
+            spiral_position: tx.spiral_position,
+            thread_id: tx.thread_id,
+            extra_data: tx.extra_data,
+            payload: tx.payload,
+            fork_id: tx.fork_id,
+            execute_at: tx.execute_at,
+        }
+    }
+}
+
+/// Block layout of schemas v9 and v10
+#[derive(Serialize, Deserialize)]
+struct BlockV10 {
+    header: BlockHeader,
+    transactions: Vec<TransactionV10>,
+}
+
+/// Account layout of schemas v6 to v9 (no payout address)
+#[derive(Serialize, Deserialize)]
+struct AccountV9 {
+    balance: Amount,
+    nonce: u64,
+    stake: Amount,
+    code_hash: Option<Hash>,
+    storage_root: Hash,
+    vesting: Option<VestingSchedule>,
+    unclaimed_rewards: Amount,
+}
+
+impl From<AccountV9> for Account {
+    fn from(account: AccountV9) -> Self {
+        Self {
+            balance: account.balance,
+            nonce: account.nonce,
+            stake: account.stake,
+            code_hash: account.code_hash,
+            storage_root: account.storage_root,
+            vesting: account.vesting,
+            unclaimed_rewards: account.unclaimed_rewards,
+            payout_address: None,
+        }
+    }
+}
+
+/// Re-encode accounts without a payout address, so rewards keep going to the
+/// producer. Like v5 records, accounts written by earlier migrations in the
+/// current layout read fine as v9.
+fn migrate_v9_to_v10(storage: &NodeStorage) -> Result<()> {
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.state.scan_prefix(b"account:") {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: AccountV9 = bincode::deserialize(&data)
+            .map_err(|e| SpiraChainError::SerializationError(format!("v9 record: {}", e)))?;
+        let data = bincode::serialize(&Account::from(legacy))
+            .map_err(|e| SpiraChainError::SerializationError(e.to_string()))?;
+        storage.state.insert(key, data).map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Re-encode blocks and transactions with a zero semantic score. Blocks
+/// produced before the score was on-chain were scored from vectors only their
+/// producer had; every node now reads them the same way.
+fn migrate_v10_to_v11(storage: &NodeStorage) -> Result<()> {
+    let decode_error =
+        |e: bincode::Error| SpiraChainError::SerializationError(format!("v10 record: {}", e));
+    let encode_error = |e: bincode::Error| SpiraChainError::SerializationError(e.to_string());
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV10 = bincode::deserialize(&data).map_err(decode_error)?;
+        let block = BlockV11 {
+            header: legacy.header,
+            transactions: legacy.transactions.into_iter().map(Into::into).collect(),
+        };
+        let data = bincode::serialize(&block).map_err(encode_error)?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    // Keyed by the hash of their encoding, so they move to a new key
+    let legacy_keys: Vec<sled::IVec> = storage
+        .transactions
+        .iter()
+        .keys()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(storage_error)?;
+    for key in legacy_keys {
+        let Some(data) = storage.transactions.get(&key).map_err(storage_error)? else {
+            continue;
+        };
+        let tx: TransactionV11 = bincode::deserialize::<TransactionV10>(&data)
+            .map_err(decode_error)?
+            .into();
+        storage.transactions.remove(&key).map_err(storage_error)?;
+        storage
+            .transactions
+            .insert(
+                tx.storage_key().map_err(encode_error)?.as_bytes(),
+                bincode::serialize(&tx).map_err(encode_error)?,
+            )
+            .map_err(storage_error)?;
+    }
+
+    Ok(())
+}
+
+/// Transaction layout of schema v11 (no sender nonce)
+#[derive(Serialize, Deserialize)]
+struct TransactionV11 {
+    version: u64,
+    tx_hash: Hash,
+    pi_id: PiCoordinate,
+    from: Address,
+    to: Address,
+    amount: Amount,
+    fee: Amount,
+    timestamp: u64,
+    signature: Vec<u8>,
+    purpose: String,
+    /// Encoded as nothing: the vector lives in the side store
+    semantic_vector: (),
+    semantic_commitment: Hash,
+    semantic_score: u16,
+    entities: Vec<Entity>,
+    intent: Option<Intent>,
+    related_txs: Vec<Hash>,
+    spiral_position: Option<SpiralPosition>,
+    thread_id: Option<Hash>,
+    extra_data: HashMap<String, Vec<u8>>,
+    payload: TransactionPayload,
+    fork_id: Hash,
+    execute_at: Option<u64>,
+}
+
+impl TransactionV11 {
+    fn storage_key(&self) -> std::result::Result<Hash, bincode::Error> {
+        Ok(Hash::from(blake3::hash(&bincode::serialize(self)?)))
+    }
+}
+
+/// A missing nonce is not hashed, so legacy transaction hashes are unchanged
+impl From<TransactionV11> for Transaction {
+    fn from(tx: TransactionV11) -> Self {
+        let mut upgraded = Transaction::new(tx.from, tx.to, tx.amount, tx.fee);
+        upgraded.version = tx.version;
+        upgraded.tx_hash = tx.tx_hash;
+        upgraded.pi_id = tx.pi_id;
+        upgraded.timestamp = tx.timestamp;
+        upgraded.signature = tx.signature;
+        upgraded.purpose = tx.purpose;
+        upgraded.semantic_commitment = tx.semantic_commitment;
+        upgraded.semantic_score = tx.semantic_score;
+        upgraded.entities = tx.entities;
+        upgraded.intent = tx.intent;
+        upgraded.related_txs = tx.related_txs;
+        upgraded.spiral_position = tx.spiral_position;
+        upgraded.thread_id = tx.thread_id;
+        upgraded.extra_data = tx.extra_data;
+        upgraded.payload = tx.payload;
+        upgraded.fork_id = tx.fork_id;
+        upgraded.execute_at = tx.execute_at;
+        upgraded
+    }
+}
+
+/// Block layout of schema v11
+#[derive(Serialize, Deserialize)]
+struct BlockV11 {
+    header: BlockHeader,
+    transactions: Vec<TransactionV11>,
+}
+
+/// Re-encode blocks and transactions without a sender nonce
+fn migrate_v11_to_v12(storage: &NodeStorage) -> Result<()> {
+    let decode_error =
+        |e: bincode::Error| SpiraChainError::SerializationError(format!("v11 record: {}", e));
+    let encode_error = |e: bincode::Error| SpiraChainError::SerializationError(e.to_string());
+    let storage_error = |e: sled::Error| SpiraChainError::StorageError(e.to_string());
+
+    for entry in storage.blocks.iter() {
+        let (key, data) = entry.map_err(storage_error)?;
+        let legacy: BlockV11 = bincode::deserialize(&data).map_err(decode_error)?;
+        let block = Block {
+            header: legacy.header,
+            transactions: legacy.transactions.into_iter().map(Into::into).collect(),
+        };
+        let data = bincode::serialize(&block).map_err(encode_error)?;
+        storage.blocks.insert(key, data).map_err(storage_error)?;
+    }
+
+    // Keyed by the hash of their encoding, so they move to a new key
+    let legacy_keys: Vec<sled::IVec> = storage
+        .transactions
+        .iter()
+        .keys()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(storage_error)?;
+    for key in legacy_keys {
+        let Some(data) = storage.transactions.get(&key).map_err(storage_error)? else {
+            continue;
+        };
+        let tx: Transaction = bincode::deserialize::<TransactionV11>(&data)
+            .map_err(decode_error)?
+            .into();
+        storage.transactions.remove(&key).map_err(storage_error)?;
+        storage.store_transaction(&tx)?;
+    }
+
+    Ok(())
+}
+
+pub struct BlockStorage {
+    storage: NodeStorage,
+}
+
+impl BlockStorage {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            storage: NodeStorage::new(path)?,
+        })
+    }
+
+    /// Seal the semantic side-stores and apply privacy mode per `policy`
+    pub fn with_at_rest(mut self, policy: AtRestPolicy) -> Self {
+        self.storage.at_rest = policy;
+        self
+    }
+
+    pub fn store_block(&self, block: &Block) -> Result<()> {
+        self.storage.store_block(block)
+    }
+
+    pub fn get_block(&self, hash: &Hash) -> Result<Option<Block>> {
+        self.storage.get_block(hash)
+    }
+
+    pub fn get_block_by_height(&self, height: u64) -> Result<Option<Block>> {
+        self.storage.get_block_by_height(height)
+    }
+
+    /// Stored encoding of the block at `height`, for serving syncing peers
+    pub fn get_block_bytes_by_height(&self, height: u64) -> Result<Option<IVec>> {
+        self.storage.get_block_bytes_by_height(height)
+    }
+
+    pub fn compute_epoch_summary(&self, epoch: u64) -> Result<EpochSummary> {
+        self.storage.compute_epoch_summary(epoch)
+    }
+
+    pub fn get_latest_block(&self) -> Result<Option<Block>> {
+        self.storage.get_latest_block()
+    }
+
+    pub fn get_header_by_height(&self, height: u64) -> Result<Option<BlockHeader>> {
+        self.storage.get_header_by_height(height)
+    }
+
+    pub fn get_chain_height(&self) -> Result<u64> {
+        self.storage.get_chain_height()
+    }
+
+    pub fn get_transaction(&self, hash: &Hash) -> Result<Option<Transaction>> {
+        self.storage.get_transaction(hash)
+    }
+
+    pub fn get_semantic_vector(&self, tx_hash: &Hash) -> Result<Option<Vec<f32>>> {
+        self.storage.get_semantic_vector(tx_hash)
+    }
+
+    pub fn store_account(&self, address: &Address, account: &Account) -> Result<()> {
+        self.storage.store_account(address, account)
+    }
+
+    pub fn get_account(&self, address: &Address) -> Result<Option<Account>> {
+        self.storage.get_account(address)
+    }
+
+    pub fn get_all_accounts(&self) -> Result<Vec<(Address, Account)>> {
+        self.storage.get_all_accounts()
+    }
+
+    pub fn get_balance(&self, address: &Address) -> Result<Amount> {
+        self.storage.get_balance(address)
+    }
+
+    pub fn set_balance(&self, address: &Address, balance: Amount) -> Result<()> {
+        self.storage.set_balance(address, balance)
+    }
+
+    pub fn get_all_addresses(&self) -> Result<Vec<Address>> {
+        self.storage.get_all_addresses()
+    }
+
+    pub fn store_contract_code(&self, code_hash: &Hash, code: &[u8]) -> Result<()> {
+        self.storage.store_contract_code(code_hash, code)
+    }
+
+    pub fn get_contract_code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>> {
+        self.storage.get_contract_code(code_hash)
+    }
+
+    pub fn set_contract(
+        &self,
+        address: &Address,
+        code_hash: &Hash,
+        storage_root: &Hash,
+    ) -> Result<()> {
+        self.storage.set_contract(address, code_hash, storage_root)
+    }
+
+    pub fn get_contract(&self, address: &Address) -> Result<Option<(Hash, Hash)>> {
+        self.storage.get_contract(address)
+    }
+
+    pub fn store_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        self.storage.store_checkpoint(checkpoint)
+    }
+
+    pub fn get_checkpoints(&self) -> Result<Vec<Checkpoint>> {
+        self.storage.get_checkpoints()
+    }
+
+    pub fn latest_checkpoint(&self) -> Result<Option<Checkpoint>> {
+        self.storage.latest_checkpoint()
+    }
+
+    pub fn store_pause_state(&self, pause: &PauseState) -> Result<()> {
+        self.storage.store_pause_state(pause)
+    }
+
+    pub fn get_pause_state(&self) -> Result<PauseState> {
+        self.storage.get_pause_state()
+    }
+
+    pub fn store_semantic_models(&self, models: &SemanticModelState) -> Result<()> {
+        self.storage.store_semantic_models(models)
+    }
+
+    pub fn get_semantic_models(&self) -> Result<SemanticModelState> {
+        self.storage.get_semantic_models()
+    }
+
+    pub fn store_spiral_registry(&self, registry: &SpiralRegistry) -> Result<()> {
+        self.storage.store_spiral_registry(registry)
+    }
+
+    pub fn get_spiral_registry(&self) -> Result<SpiralRegistry> {
+        self.storage.get_spiral_registry()
+    }
+
+    pub fn store_name_registry(&self, names: &NameRegistry) -> Result<()> {
+        self.storage.store_name_registry(names)
+    }
+
+    pub fn get_name_registry(&self) -> Result<NameRegistry> {
+        self.storage.get_name_registry()
+    }
+
+    pub fn store_spiral_diversity(&self, diversity: &SpiralDiversity) -> Result<()> {
+        self.storage.store_spiral_diversity(diversity)
+    }
+
+    pub fn get_spiral_diversity(&self) -> Result<SpiralDiversity> {
+        self.storage.get_spiral_diversity()
+    }
+
+    pub fn store_epoch_semantics(&self, semantics: &EpochSemanticsResponse) -> Result<()> {
+        self.storage.store_epoch_semantics(semantics)
+    }
+
+    pub fn get_epoch_semantics(&self, epoch: u64) -> Result<Option<EpochSemanticsResponse>> {
+        self.storage.get_epoch_semantics(epoch)
+    }
+
+    pub fn latest_epoch_semantics(&self) -> Result<Option<EpochSemanticsResponse>> {
+        self.storage.latest_epoch_semantics()
+    }
+
+    pub fn record_validator_change(&self, change: &ValidatorSetChange) -> Result<bool> {
+        self.storage.record_validator_change(change)
+    }
+
+    pub fn get_validator_changes(&self, epoch: u64) -> Result<Vec<ValidatorSetChange>> {
+        self.storage.get_validator_changes(epoch)
+    }
+
+    pub fn store_continuity_proof(&self, proof: &ContinuityProof) -> Result<()> {
+        self.storage.store_continuity_proof(proof)
+    }
+
+    pub fn get_continuity_proof(
+        &self,
+        block_hash: &Hash,
+        parent_hash: &Hash,
+    ) -> Result<Option<ContinuityProof>> {
+        self.storage.get_continuity_proof(block_hash, parent_hash)
+    }
+
+    pub fn continuity_proof_at(&self, height: u64) -> Result<Option<ContinuityProof>> {
+        self.storage.continuity_proof_at(height)
+    }
+
+    pub fn semantic_vector_bytes(&self) -> Result<u64> {
+        self.storage.semantic_vector_bytes()
+    }
+
+    pub fn prune_semantic_vectors(&self, bytes: u64) -> Result<PrunedVectors> {
+        self.storage.prune_semantic_vectors(bytes)
+    }
+
+    pub fn size_on_disk(&self) -> Result<u64> {
+        self.storage.size_on_disk()
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    pub fn schema_version(&self) -> Result<Option<u32>> {
+        self.storage.schema_version()
+    }
+
+    pub fn copy_to(&self, path: &Path) -> Result<()> {
+        self.storage.copy_to(path)
+    }
+
+    pub fn replace_with(&self, source: &BlockStorage) -> Result<()> {
+        self.storage.replace_with(&source.storage)
+    }
+}
+
+/// Validated continuity outlives the process; a failed write only costs a
+/// recomputation later
+impl spirachain_consensus::ContinuityStore for BlockStorage {
+    fn load_continuity(&self, block_hash: &Hash, parent_hash: &Hash) -> Option<ContinuityProof> {
+        self.get_continuity_proof(block_hash, parent_hash)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to read continuity proof: {}", e);
+                None
+            })
+    }
+
+    fn store_continuity(&self, proof: &ContinuityProof) {
+        if let Err(e) = self.store_continuity_proof(proof) {
+            tracing::warn!(
+                "Failed to persist continuity proof for block {}: {}",
+                proof.height,
+                e
+            );
+        }
+    }
+}
+
+fn continuity_key(block_hash: &Hash, parent_hash: &Hash) -> [u8; 64] {
+    let mut key = [0u8; 64];
+    key[..32].copy_from_slice(block_hash.as_bytes());
+    key[32..].copy_from_slice(parent_hash.as_bytes());
+    key
+}
+
+impl spirachain_rpc::server::BlockchainStorage for BlockStorage {
+    fn get_block_by_height(&self, height: u64) -> Result<Option<Block>> {
+        BlockStorage::get_block_by_height(self, height)
+    }
+
+    fn get_balance(&self, address: &Address) -> Result<Amount> {
+        BlockStorage::get_balance(self, address)
+    }
+
+    fn get_unclaimed_rewards(&self, address: &Address) -> Result<Amount> {
+        Ok(self
+            .get_account(address)?
+            .map(|account| account.unclaimed_rewards)
+            .unwrap_or(Amount::zero()))
+    }
+
+    fn get_account(&self, address: &Address) -> Result<Option<Account>> {
+        BlockStorage::get_account(self, address)
+    }
+
+    fn latest_checkpoint(&self) -> Result<Option<(u64, Hash)>> {
+        Ok(BlockStorage::latest_checkpoint(self)?
+            .map(|checkpoint| (checkpoint.height, checkpoint.block_hash)))
+    }
+
+    fn pause_state(&self) -> Result<PauseState> {
+        self.get_pause_state()
+    }
+
+    fn semantic_models(&self) -> Result<SemanticModelState> {
+        self.get_semantic_models()
+    }
+
+    fn spiral_registry(&self) -> Result<SpiralRegistry> {
+        self.get_spiral_registry()
+    }
+
+    fn name_registry(&self) -> Result<NameRegistry> {
+        self.get_name_registry()
+    }
+
+    fn spiral_diversity(&self) -> Result<SpiralDiversity> {
+        self.get_spiral_diversity()
+    }
+
+    fn continuity_proof(&self, height: u64) -> Result<Option<ContinuityProof>> {
+        self.continuity_proof_at(height)
+    }
+
+    fn validator_set_changes(&self, epoch: u64) -> Result<Vec<ValidatorSetChange>> {
+        self.get_validator_changes(epoch)
+    }
+
+    fn epoch_semantics(&self, epoch: u64) -> Result<Option<EpochSemanticsResponse>> {
+        self.get_epoch_semantics(epoch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{GenesisConfig, MIN_TX_FEE};
+    use std::path::PathBuf;
+
+    /// sled's flusher thread releases a closed database's file lock a moment
+    /// after its last handle drops, so reopening right away may find it taken
+    fn reopen(path: &Path) -> Result<NodeStorage> {
+        for _ in 0..200 {
+            match NodeStorage::new(path) {
+                Err(e) if e.to_string().contains("could not acquire lock") => {
+                    std::thread::sleep(std::time::Duration::from_millis(10))
+                }
+                result => return result,
+            }
+        }
+        NodeStorage::new(path)
+    }
+
+    /// [`reopen`] for the raw database
+    fn reopen_sled(path: &Path) -> sled::Db {
+        for _ in 0..200 {
+            match sled::open(path) {
+                Err(sled::Error::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(std::time::Duration::from_millis(10))
+                }
+                result => return result.unwrap(),
+            }
+        }
+        sled::open(path).unwrap()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "spirachain-migration-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Backups `upgrade_schema` left next to `dir/db` before migrating from `version`
+    fn backups(dir: &Path, version: u32) -> Vec<PathBuf> {
+        let prefix = format!("db.backup-v{}-", version);
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix))
+            })
+            .collect()
+    }
+
+    fn sample_tx() -> Transaction {
+        let mut tx = Transaction::new(
+            Address::new([1u8; 32]),
+            Address::new([2u8; 32]),
+            Amount::qbt(3),
+            Amount::new(MIN_TX_FEE),
+        );
+        tx.purpose = "rent".to_string();
+        tx.semantic_vector = vec![0.25; 4];
+        tx.compute_hash();
+        tx
+    }
+
+    fn sample_block(tx: &Transaction) -> Block {
+        let mut block = Block::new(Hash::zero(), 1).with_transactions(vec![tx.clone()]);
+        block.header.extra_data.clear();
+        block.header.event_bloom.clear();
+        block
+    }
+
+    fn tx_v1(tx: &Transaction) -> TransactionV1 {
+        TransactionV1 {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature.clone(),
+            purpose: tx.purpose.clone(),
+            semantic_vector: tx.semantic_vector.clone(),
+            entities: tx.entities.clone(),
+            intent: tx.intent.clone(),
+            related_txs: tx.related_txs.clone(),
+            spiral_position: tx.spiral_position.clone(),
+            thread_id: tx.thread_id,
+            extra_data: tx.extra_data.clone(),
+        }
+    }
+
+    fn tx_v2(tx: &Transaction) -> TransactionV2 {
+        TransactionV2 {
+            version: tx.version,
+            tx_hash: tx.tx_hash,
+            pi_id: tx.pi_id,
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature.clone(),
+            purpose: tx.purpose.clone(),
+            semantic_vector: tx.semantic_vector.clone(),
+            entities: tx.entities.clone(),
+            intent: tx.intent.clone(),
+            related_txs: tx.related_txs.clone(),
+            spiral_position: tx.spiral_position.clone(),
+            thread_id: tx.thread_id,
+            extra_data: tx.extra_data.clone(),
+            payload: tx.payload.clone(),
+        }
+    }
+
+    fn header_v4(header: &BlockHeader) -> BlockHeaderV4 {
+        BlockHeaderV4 {
+            version: header.version,
+            previous_block_hash: header.previous_block_hash,
+            merkle_root: header.merkle_root,
+            spiral_root: header.spiral_root,
+            state_root: header.state_root,
+            timestamp: header.timestamp,
+            pi_coordinates: header.pi_coordinates,
+            spiral: header.spiral.clone(),
+            validator_pubkey: header.validator_pubkey.clone(),
+            signature: header.signature.clone(),
+            nonce: header.nonce,
+            difficulty_target: header.difficulty_target,
+            tx_count: header.tx_count,
+            block_height: header.block_height,
+        }
+    }
+
+    fn block_v1(block: &Block) -> BlockV1 {
+        BlockV1 {
+            header: header_v4(&block.header),
+            transactions: block.transactions.iter().map(tx_v1).collect(),
+        }
+    }
+
+    /// Records as a v1 node wrote them: no schema stamp, payload-less block
+    /// and transaction layouts and `balance:` entries
+    fn write_v1(storage: &NodeStorage, block: &Block, balances: &[(Address, Amount)]) {
+        storage.meta.remove(SCHEMA_VERSION_KEY).unwrap();
+        let legacy = block_v1(block);
+        storage
+            .blocks
+            .insert(block.hash().as_bytes(), bincode::serialize(&legacy).unwrap())
+            .unwrap();
+        storage
+            .block_by_height
+            .insert(block.header.block_height.to_be_bytes(), block.hash().as_bytes())
+            .unwrap();
+        for tx in &legacy.transactions {
+            let data = bincode::serialize(tx).unwrap();
+            storage
+                .transactions
+                .insert(blake3::hash(&data).as_bytes(), data)
+                .unwrap();
+        }
+        for (address, balance) in balances {
+            storage
+                .state
+                .insert(
+                    format!("balance:{}", address).as_bytes(),
+                    bincode::serialize(balance).unwrap(),
+                )
+                .unwrap();
+        }
+        storage.flush().unwrap();
+    }
+
+    /// Block 1 and its transaction as the release before schema versioning
+    /// encoded them, see `golden/`
+    const V1_BLOCK: &[u8] = include_bytes!("../golden/v1_block.bin");
+    const V1_TRANSACTION: &[u8] = include_bytes!("../golden/v1_transaction.bin");
+    const V1_BLOCK_HASH: &str = "bb42609666c80818378a55078ac07f851b632888e72f37b2ec490e6b7cdf956e";
+    const V1_TX_HASH: &str = "95ffa24a5cbced56c02caa3a3865b45151eb83d33c9a1888ceed2b67cd507d40";
+    const V1_TIMESTAMP: u64 = 1_700_000_000_000;
+
+    fn hash_hex(hex_hash: &str) -> Hash {
+        Hash::from_slice(&hex::decode(hex_hash).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_v1_layout_matches_the_previous_release() {
+        let mut tx = sample_tx();
+        tx.timestamp = V1_TIMESTAMP;
+        tx.compute_hash();
+        assert_eq!(tx.tx_hash, hash_hex(V1_TX_HASH));
+        let mut block = sample_block(&tx);
+        block.header.timestamp = V1_TIMESTAMP;
+        block.compute_merkle_root();
+
+        assert_eq!(bincode::serialize(&tx_v1(&tx)).unwrap(), V1_TRANSACTION);
+        assert_eq!(bincode::serialize(&block_v1(&block)).unwrap(), V1_BLOCK);
+        assert_eq!(block.hash(), hash_hex(V1_BLOCK_HASH));
+    }
+
+    #[test]
+    fn test_v1_database_migrates_to_current_schema() {
+        let dir = temp_dir("v1");
+        let path = dir.join("db");
+        let block_hash = hash_hex(V1_BLOCK_HASH);
+        let tx_hash = hash_hex(V1_TX_HASH);
+        let holder = Address::new([5u8; 32]);
+        {
+            // Records exactly as the previous release wrote them
+            let storage = NodeStorage::new(&path).unwrap();
+            storage.meta.remove(SCHEMA_VERSION_KEY).unwrap();
+            storage.blocks.insert(block_hash.as_bytes(), V1_BLOCK).unwrap();
+            storage
+                .block_by_height
+                .insert(1u64.to_be_bytes(), block_hash.as_bytes())
+                .unwrap();
+            storage
+                .transactions
+                .insert(blake3::hash(V1_TRANSACTION).as_bytes(), V1_TRANSACTION)
+                .unwrap();
+            storage
+                .state
+                .insert(
+                    format!("balance:{}", holder).as_bytes(),
+                    bincode::serialize(&Amount::qbt(42)).unwrap(),
+                )
+                .unwrap();
+            storage.flush().unwrap();
+            assert_eq!(storage.schema_version().unwrap(), Some(1));
+        }
+
+        let storage = reopen(&path).unwrap();
+        assert_eq!(
+            storage.schema_version().unwrap(),
+            Some(STORAGE_SCHEMA_VERSION)
+        );
+
+        let migrated = storage.get_block_by_height(1).unwrap().unwrap();
+        assert_eq!(migrated.hash(), block_hash);
+        assert_eq!(migrated.header.merkle_root, tx_hash);
+        assert_eq!(migrated.header.timestamp, V1_TIMESTAMP);
+        assert!(migrated.header.extra_data.is_empty());
+        assert!(migrated.header.event_bloom.is_empty());
+        let migrated_tx = &migrated.transactions[0];
+        assert_eq!(migrated_tx.tx_hash, tx_hash);
+        assert_eq!(migrated_tx.computed_hash(), tx_hash);
+        assert_eq!(migrated_tx.payload, TransactionPayload::Transfer);
+        assert_eq!(migrated_tx.purpose, "rent");
+        assert_eq!(migrated_tx.amount, Amount::qbt(3));
+        assert_eq!(migrated_tx.fork_id, Hash::zero());
+        assert_eq!(migrated_tx.execute_at, None);
+        assert_eq!(
+            storage.get_semantic_vector(&tx_hash).unwrap(),
+            Some(vec![0.25; 4])
+        );
+        let stored = storage.get_transaction(&migrated_tx.hash()).unwrap().unwrap();
+        assert_eq!(stored.tx_hash, tx_hash);
+        assert_eq!(storage.transactions.len(), 1);
+
+        let account = storage.get_account(&holder).unwrap().unwrap();
+        assert_eq!(account.balance, Amount::qbt(42));
+        assert_eq!(account.unclaimed_rewards, Amount::zero());
+        assert_eq!(account.payout_address, None);
+        assert!(storage.state.scan_prefix(b"balance:").next().is_none());
+
+        // The v1 database was copied aside before the first step
+        assert_eq!(backups(&dir, 1).len(), 1);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_backup_intact() {
+        let dir = temp_dir("failed");
+        let path = dir.join("db");
+        let block = sample_block(&sample_tx());
+        {
+            let storage = NodeStorage::new(&path).unwrap();
+            write_v1(&storage, &block, &[]);
+            // No contract entry can predate v2, so the first step refuses to run
+            storage.state.insert(b"contract:stale", vec![0u8; 64]).unwrap();
+            storage.flush().unwrap();
+        }
+
+        let error = match reopen(&path) {
+            Ok(_) => panic!("migration should fail"),
+            Err(e) => e.to_string(),
+        };
+        let backups = backups(&dir, 1);
+        assert_eq!(backups.len(), 1);
+        assert!(error.contains("Migration v1 -> v2 failed"), "{}", error);
+        assert!(error.contains(&backups[0].display().to_string()), "{}", error);
+
+        // Neither the backup nor the database itself was stamped or rewritten
+        let v1_block = bincode::serialize(&block_v1(&block)).unwrap();
+        for db_path in [&path, &backups[0]] {
+            let db = reopen_sled(db_path);
+            let meta = db.open_tree(b"meta").unwrap();
+            assert!(meta.get(SCHEMA_VERSION_KEY).unwrap().is_none());
+            let blocks = db.open_tree(b"blocks").unwrap();
+            assert_eq!(
+                blocks.get(block.hash().as_bytes()).unwrap().as_deref(),
+                Some(v1_block.as_slice())
+            );
+            let state = db.open_tree(b"state").unwrap();
+            assert!(state.contains_key(b"contract:stale").unwrap());
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_newer_schema_is_refused() {
+        let dir = temp_dir("newer");
+        let path = dir.join("db");
+        NodeStorage::new(&path)
+            .unwrap()
+            .set_schema_version(STORAGE_SCHEMA_VERSION + 1)
+            .unwrap();
+
+        let error = match reopen(&path) {
+            Ok(_) => panic!("a newer schema must not open"),
+            Err(e) => e.to_string(),
+        };
+        assert!(
+            error.contains(&format!("uses schema v{}", STORAGE_SCHEMA_VERSION + 1)),
+            "{}",
+            error
+        );
+
+        // Nothing was backed up or touched
+        let entries: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+        let db = sled::open(&path).unwrap();
+        let stamp = db.open_tree(b"meta").unwrap().get(SCHEMA_VERSION_KEY).unwrap();
+        assert_eq!(
+            stamp.as_deref(),
+            Some(&(STORAGE_SCHEMA_VERSION + 1).to_be_bytes()[..])
+        );
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stored_bytes_are_the_wire_encoding() {
+        let dir = std::env::temp_dir().join(format!("spirachain-storage-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = BlockStorage::new(&dir).unwrap();
+        let block = GenesisConfig::default().create_genesis_block();
+        storage.store_block(&block).unwrap();
+
+        let bytes = storage.get_block_bytes_by_height(0).unwrap().unwrap();
+        assert_eq!(&bytes[..], bincode::serialize(&block).unwrap().as_slice());
+        assert!(storage.get_block_bytes_by_height(1).unwrap().is_none());
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v1_to_v2_adds_transfer_payload() {
+        let dir = temp_dir("v1-v2");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let tx = sample_tx();
+        let block = sample_block(&tx);
+        write_v1(&storage, &block, &[]);
+
+        migrate_v1_to_v2(&storage).unwrap();
+
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: BlockV2 = bincode::deserialize(&data).unwrap();
+        assert_eq!(migrated.header.merkle_root, block.header.merkle_root);
+        assert_eq!(migrated.transactions[0].tx_hash, tx.tx_hash);
+        assert_eq!(migrated.transactions[0].payload, TransactionPayload::Transfer);
+
+        // The transaction moved to the hash of its new encoding
+        assert_eq!(storage.transactions.len(), 1);
+        let data = bincode::serialize(&migrated.transactions[0]).unwrap();
+        let stored = storage.transactions.get(blake3::hash(&data).as_bytes()).unwrap();
+        assert_eq!(stored.as_deref(), Some(data.as_slice()));
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v2_to_v3_adds_legacy_fork_id() {
+        let dir = temp_dir("v2-v3");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let tx = sample_tx();
+        let block = sample_block(&tx);
+        write_v1(&storage, &block, &[]);
+        migrate_v1_to_v2(&storage).unwrap();
+
+        migrate_v2_to_v3(&storage).unwrap();
+
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: BlockV4 = bincode::deserialize(&data).unwrap();
+        assert_eq!(migrated.header.merkle_root, block.header.merkle_root);
+        assert_eq!(migrated.transactions[0].tx_hash, tx.tx_hash);
+        assert_eq!(migrated.transactions[0].fork_id, Hash::zero());
+
+        // The transaction moved to the hash of its new encoding
+        assert_eq!(storage.transactions.len(), 1);
+        let key = migrated.transactions[0].storage_key().unwrap();
+        let data = storage.transactions.get(key.as_bytes()).unwrap().unwrap();
+        let stored: TransactionV7 = bincode::deserialize(&data).unwrap();
+        assert_eq!(stored.semantic_vector, tx.semantic_vector);
+        assert_eq!(stored.fork_id, Hash::zero());
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v3_to_v4_folds_state_into_accounts() {
+        let dir = temp_dir("v3-v4");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let holder = Address::new([5u8; 32]);
+        let contract = Address::new([6u8; 32]);
+        let (code_hash, storage_root) = (Hash::from([7u8; 32]), Hash::from([8u8; 32]));
+        let legacy = [
+            (format!("balance:{}", holder), bincode::serialize(&Amount::qbt(3))),
+            (format!("balance:{}", contract), bincode::serialize(&Amount::qbt(1))),
+            (
+                format!("contract:{}", contract),
+                bincode::serialize(&(code_hash, storage_root)),
+            ),
+        ];
+        for (key, data) in legacy {
+            storage.state.insert(key.as_bytes(), data.unwrap()).unwrap();
+        }
+
+        migrate_v3_to_v4(&storage).unwrap();
+
+        let account = storage.get_account(&holder).unwrap().unwrap();
+        assert_eq!(account.balance, Amount::qbt(3));
+        assert_eq!(account.code_hash, None);
+        let account = storage.get_account(&contract).unwrap().unwrap();
+        assert_eq!(account.balance, Amount::qbt(1));
+        assert_eq!(account.code_hash, Some(code_hash));
+        assert_eq!(account.storage_root, storage_root);
+        assert!(storage.state.scan_prefix(b"balance:").next().is_none());
+        assert!(storage.state.scan_prefix(b"contract:").next().is_none());
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v4_to_v5_adds_empty_header_extra_data() {
+        let dir = temp_dir("v4-v5");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let tx = sample_tx();
+        let block = sample_block(&tx);
+        let legacy = BlockV4 {
+            header: header_v4(&block.header),
+            transactions: vec![tx_v2(&tx).into()],
+        };
+        storage
+            .blocks
+            .insert(block.hash().as_bytes(), bincode::serialize(&legacy).unwrap())
+            .unwrap();
+
+        migrate_v4_to_v5(&storage).unwrap();
+
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: BlockV6 = bincode::deserialize(&data).unwrap();
+        assert!(migrated.header.extra_data.is_empty());
+        assert_eq!(migrated.header.state_root, block.header.state_root);
+        assert_eq!(migrated.header.block_height, block.header.block_height);
+        assert_eq!(migrated.transactions[0].tx_hash, tx.tx_hash);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v5_to_v6_adds_empty_rewards_bucket() {
+        let dir = temp_dir("v5-v6");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let legacy_holder = Address::new([5u8; 32]);
+        let legacy = AccountV5 {
+            balance: Amount::qbt(9),
+            nonce: 4,
+            stake: Amount::qbt(2),
+            code_hash: None,
+            storage_root: Hash::zero(),
+            vesting: None,
+        };
+        storage
+            .state
+            .insert(
+                format!("account:{}", legacy_holder).as_bytes(),
+                bincode::serialize(&legacy).unwrap(),
+            )
+            .unwrap();
+        // Written by the v3 -> v4 step, already in the current layout
+        let migrated_holder = Address::new([6u8; 32]);
+        storage
+            .store_account(&migrated_holder, &Account::with_balance(Amount::qbt(1)))
+            .unwrap();
+
+        migrate_v5_to_v6(&storage).unwrap();
+
+        let account = storage.get_account(&legacy_holder).unwrap().unwrap();
+        assert_eq!(
+            (account.balance, account.nonce, account.stake),
+            (Amount::qbt(9), 4, Amount::qbt(2))
+        );
+        assert_eq!(account.unclaimed_rewards, Amount::zero());
+        let account = storage.get_account(&migrated_holder).unwrap().unwrap();
+        assert_eq!(account, Account::with_balance(Amount::qbt(1)));
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v6_to_v7_adds_empty_event_bloom() {
+        let dir = temp_dir("v6-v7");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let tx = sample_tx();
+        let block = sample_block(&tx);
+        let mut header = BlockHeaderV6::from(header_v4(&block.header));
+        header.extra_data = b"v6".to_vec();
+        let legacy = BlockV6 {
+            header,
+            transactions: vec![tx_v2(&tx).into()],
+        };
+        storage
+            .blocks
+            .insert(block.hash().as_bytes(), bincode::serialize(&legacy).unwrap())
+            .unwrap();
+
+        migrate_v6_to_v7(&storage).unwrap();
+
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: BlockV7 = bincode::deserialize(&data).unwrap();
+        assert!(migrated.header.event_bloom.is_empty());
+        assert_eq!(migrated.header.extra_data, b"v6".to_vec());
+        assert_eq!(migrated.header.merkle_root, block.header.merkle_root);
+        assert_eq!(migrated.transactions[0].tx_hash, tx.tx_hash);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn tx_v10(tx: &Transaction) -> TransactionV10 {
+        TransactionV8::from(TransactionV7::from(tx_v2(tx))).into()
+    }
+
+    #[test]
+    fn test_migrate_v7_to_v8_moves_vectors_to_side_store() {
+        let dir = temp_dir("v7-v8");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let tx = sample_tx();
+        let block = sample_block(&tx);
+        let legacy = BlockV7 {
+            header: block.header.clone(),
+            transactions: vec![tx_v2(&tx).into()],
+        };
+        storage
+            .blocks
+            .insert(block.hash().as_bytes(), bincode::serialize(&legacy).unwrap())
+            .unwrap();
+        let legacy_tx = TransactionV7::from(tx_v2(&tx));
+        storage
+            .transactions
+            .insert(
+                legacy_tx.storage_key().unwrap().as_bytes(),
+                bincode::serialize(&legacy_tx).unwrap(),
+            )
+            .unwrap();
+
+        migrate_v7_to_v8(&storage).unwrap();
+
+        assert_eq!(
+            storage.get_semantic_vector(&tx.tx_hash).unwrap(),
+            Some(tx.semantic_vector.clone())
+        );
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: BlockV8 = bincode::deserialize(&data).unwrap();
+        assert_eq!(migrated.transactions[0].tx_hash, tx.tx_hash);
+        assert_eq!(migrated.transactions[0].semantic_commitment, Hash::zero());
+
+        assert_eq!(storage.transactions.len(), 1);
+        let key = migrated.transactions[0].storage_key().unwrap();
+        assert!(storage.transactions.contains_key(key.as_bytes()).unwrap());
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v8_to_v9_adds_empty_execute_at() {
+        let dir = temp_dir("v8-v9");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let tx = sample_tx();
+        let block = sample_block(&tx);
+        let legacy_tx = || TransactionV8::from(TransactionV7::from(tx_v2(&tx)));
+        let legacy = BlockV8 {
+            header: block.header.clone(),
+            transactions: vec![legacy_tx()],
+        };
+        storage
+            .blocks
+            .insert(block.hash().as_bytes(), bincode::serialize(&legacy).unwrap())
+            .unwrap();
+        let legacy_key = legacy_tx().storage_key().unwrap();
+        storage
+            .transactions
+            .insert(
+                legacy_key.as_bytes(),
+                bincode::serialize(&legacy_tx()).unwrap(),
+            )
+            .unwrap();
+
+        migrate_v8_to_v9(&storage).unwrap();
+
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: BlockV10 = bincode::deserialize(&data).unwrap();
+        assert_eq!(migrated.header.merkle_root, block.header.merkle_root);
+        assert_eq!(migrated.transactions[0].execute_at, None);
+        assert_eq!(migrated.transactions[0].tx_hash, tx.tx_hash);
+
+        assert_eq!(storage.transactions.len(), 1);
+        assert!(!storage
+            .transactions
+            .contains_key(legacy_key.as_bytes())
+            .unwrap());
+        let key = migrated.transactions[0].storage_key().unwrap();
+        let data = storage.transactions.get(key.as_bytes()).unwrap().unwrap();
+        let stored: TransactionV10 = bincode::deserialize(&data).unwrap();
+        assert_eq!(stored.tx_hash, tx.tx_hash);
+        assert_eq!(stored.execute_at, None);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v9_to_v10_adds_no_payout_address() {
+        let dir = temp_dir("v9-v10");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let holder = Address::new([7u8; 32]);
+        let legacy = AccountV9 {
+            balance: Amount::qbt(9),
+            nonce: 4,
+            stake: Amount::qbt(2),
+            code_hash: None,
+            storage_root: Hash::zero(),
+            vesting: None,
+            unclaimed_rewards: Amount::qbt(3),
+        };
+        storage
+            .state
+            .insert(
+                format!("account:{}", holder).as_bytes(),
+                bincode::serialize(&legacy).unwrap(),
+            )
+            .unwrap();
+
+        migrate_v9_to_v10(&storage).unwrap();
+
+        let account = storage.get_account(&holder).unwrap().unwrap();
+        assert_eq!(
+            (account.balance, account.nonce, account.stake),
+            (Amount::qbt(9), 4, Amount::qbt(2))
+        );
+        assert_eq!(account.unclaimed_rewards, Amount::qbt(3));
+        assert_eq!(account.payout_address, None);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v10_to_v11_adds_zero_semantic_score() {
+        let dir = temp_dir("v10-v11");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let tx = sample_tx();
+        let block = sample_block(&tx);
+        let legacy = BlockV10 {
+            header: block.header.clone(),
+            transactions: vec![tx_v10(&tx)],
+        };
+        storage
+            .blocks
+            .insert(block.hash().as_bytes(), bincode::serialize(&legacy).unwrap())
+            .unwrap();
+        let legacy_tx = tx_v10(&tx);
+        storage
+            .transactions
+            .insert(
+                legacy_tx.storage_key().unwrap().as_bytes(),
+                bincode::serialize(&legacy_tx).unwrap(),
+            )
+            .unwrap();
+
+        migrate_v10_to_v11(&storage).unwrap();
+
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: BlockV11 = bincode::deserialize(&data).unwrap();
+        assert_eq!(migrated.transactions[0].semantic_score, 0);
+        let migrated_tx = Transaction::from(migrated.transactions.into_iter().next().unwrap());
+        // A zero score is not hashed, so ids and signatures stay valid
+        assert_eq!(migrated_tx.computed_hash(), tx.tx_hash);
+
+        assert_eq!(storage.transactions.len(), 1);
+        let stored_key = TransactionV11::from(tx_v10(&tx)).storage_key().unwrap();
+        assert!(storage
+            .transactions
+            .contains_key(stored_key.as_bytes())
+            .unwrap());
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_v11_to_v12_leaves_nonce_empty() {
+        let dir = temp_dir("v11-v12");
+        let storage = NodeStorage::new(dir.join("db")).unwrap();
+        let mut tx = sample_tx();
+        tx.semantic_score = 7;
+        tx.compute_hash();
+        let block = sample_block(&tx);
+        let legacy_tx = || {
+            let mut legacy = TransactionV11::from(tx_v10(&tx));
+            legacy.semantic_score = tx.semantic_score;
+            legacy
+        };
+        let legacy = BlockV11 {
+            header: block.header.clone(),
+            transactions: vec![legacy_tx()],
+        };
+        storage
+            .blocks
+            .insert(block.hash().as_bytes(), bincode::serialize(&legacy).unwrap())
+            .unwrap();
+        storage
+            .transactions
+            .insert(
+                legacy_tx().storage_key().unwrap().as_bytes(),
+                bincode::serialize(&legacy_tx()).unwrap(),
+            )
+            .unwrap();
+
+        migrate_v11_to_v12(&storage).unwrap();
+
+        let data = storage.blocks.get(block.hash().as_bytes()).unwrap().unwrap();
+        let migrated: Block = bincode::deserialize(&data).unwrap();
+        let migrated_tx = &migrated.transactions[0];
+        assert_eq!(migrated_tx.nonce, None);
+        assert_eq!(migrated_tx.semantic_score, 7);
+        // A missing nonce is not hashed, so ids and signatures stay valid
+        assert_eq!(migrated_tx.computed_hash(), tx.tx_hash);
+
+        assert_eq!(storage.transactions.len(), 1);
+        let stored = storage.get_transaction(&migrated_tx.hash()).unwrap().unwrap();
+        assert_eq!(stored.tx_hash, tx.tx_hash);
+
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,103 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use std::path::Path;
+use std::time::Duration;
+
+/// Random id of this installation, kept in the data directory so the dashboard
+/// can count nodes without learning their peer ID, address or IP
+pub const TELEMETRY_ID_FILE: &str = "telemetry_id";
+
+/// Time between reports while telemetry is on
+pub const TELEMETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Everything a telemetry report contains. Nothing here identifies the
+/// operator: no keys, addresses, peer IDs or hostnames.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryReport {
+    pub node_id: String,
+    pub version: String,
+    pub git_commit: String,
+    pub network: String,
+    pub height: u64,
+    pub peers: usize,
+    pub os: String,
+    pub arch: String,
+    pub uptime_secs: u64,
+}
+
+impl TelemetryReport {
+    pub fn new(node_id: &str, network: &str, height: u64, peers: usize, uptime: Duration) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: crate::GIT_COMMIT.to_string(),
+            network: network.to_string(),
+            height,
+            peers,
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            uptime_secs: uptime.as_secs(),
+        }
+    }
+}
+
+/// Read the installation id, creating it on first use
+pub fn load_or_create_telemetry_id(data_dir: &Path) -> Result<String> {
+    let path = data_dir.join(TELEMETRY_ID_FILE);
+    if let Ok(id) = std::fs::read_to_string(&path) {
+        let id = id.trim();
+        if !id.is_empty() {
+            return Ok(id.to_string());
+        }
+    }
+
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let id = hex::encode(bytes);
+    std::fs::write(&path, &id)
+        .map_err(|e| SpiraChainError::StorageError(format!("{:?}: {}", path, e)))?;
+    Ok(id)
+}
+
+/// Fire-and-forget POST of `report`; a dashboard being down is not worth a warning
+pub fn send_telemetry(endpoint: String, report: TelemetryReport) {
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::debug!("Failed to build telemetry client: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = client.post(&endpoint).json(&report).send().await {
+            tracing::debug!("Telemetry report to {} failed: {}", endpoint, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telemetry_id_is_stable_and_report_anonymous() {
+        let dir = std::env::temp_dir().join(format!("spirachain-telemetry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let _ = std::fs::remove_file(dir.join(TELEMETRY_ID_FILE));
+
+        let id = load_or_create_telemetry_id(&dir).unwrap();
+        assert_eq!(id.len(), 32);
+        assert_eq!(load_or_create_telemetry_id(&dir).unwrap(), id);
+
+        let report = TelemetryReport::new(&id, "testnet", 42, 3, Duration::from_secs(90));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["uptime_secs"], 90);
+        assert_eq!(json.as_object().unwrap().len(), 9);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
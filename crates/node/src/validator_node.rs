@@ -1,16 +1,60 @@
-use crate::{BlockStorage, NodeConfig, WorldState};
-use spirachain_consensus::{ProofOfSpiral, SlotConsensus, Validator};
-use spirachain_core::{Address, Amount, Block, Result, Transaction};
+use crate::{
+    admit_network_transaction, load_genesis, load_mempool, load_or_create_telemetry_id,
+    notify_webhooks, save_mempool, send_telemetry, upcoming_leader_slots, update_epoch_semantics,
+    validate_received_block, validate_unsigned_block, warn_throttled, warning_throttle, AtRestPolicy, BlockStorage,
+    BlockValidationPool, BlockVerdict, CompactionDecision, CompactionScheduler, DataDirLock, DriftVerdict,
+    DryRunReport, FastRelay, ForkAlertLog, LocalSemanticModel, LogLevelSetter, MisbehaviorMonitor, NodeAdmin, NodeConfig,
+    NodePiIdentifierService, NodeSimulator, NodeSlotSchedule, RelayOutcome, ReloadSignal,
+    RuntimeConfigManager, SelfValidationMetrics, SharedTopology, SigningProtection,
+    StateDriftMonitor, StateRootSample, TelemetryReport, ValidationStage, WorldState,
+    DRY_RUN_REPORT_FILE, FORK_ALERTS_FILE, RETIRED_WATERMARK, RUNTIME_CONFIG_FILE,
+    SEMANTIC_MODEL_FILE, SIGNING_PROTECTION_FILE, STATE_DRIFT_CHECK_INTERVAL, TELEMETRY_INTERVAL,
+};
+use spirachain_consensus::{
+    Checkpoint, CheckpointSet, ContinuityStore, EquivocationEvidence, ProofOfSpiral,
+    SlashingReason, SlotConsensus, Validator,
+};
+use spirachain_core::{
+    diversity_epoch, is_epoch_start, prioritize_lanes, signature_cache, Address, Amount, Block,
+    ChainParams, EpochSummary, GenesisConfig, Hash, Result, ScheduleStatus, SpiraChainError,
+    Transaction, TransactionPayload, ValidatorChange, ValidatorSetChange,
+};
 use spirachain_crypto::{KeyPair, PublicKey};
-use spirachain_network::{LibP2PNetworkWithSync, NetworkEvent};
+use spirachain_network::{
+    load_or_create_identity, BlockTransactions, CompactBlock, GossipValidationStats,
+    HandshakeStats, LibP2PNetworkWithSync, LivenessStats, NetworkEvent, OutboundQueueStats,
+    PartialBlock, PeerDiversityStats, PeerId, SyncStats,
+};
+use spirachain_rpc::{
+    admit_transaction, AccountChange, CodecStatsResponse, CompressionStatsResponse, DropReason,
+    ExplorerFeed, ForkAlertResponse, ForkDecision, MempoolMonitor, PropagationStatsResponse, Resource, ResourceUsage,
+    SyncStatusResponse, ValidatorSetChangeResponse,
+};
+use spirachain_rpc::server::VALIDATOR_CHANGES_CHANNEL;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{interval, Duration};
+use tokio::sync::{broadcast, RwLock};
+use tokio::time::{interval, Duration, Instant};
+
+/// How long to wait for missing compact block transactions before fetching the full block
+const COMPACT_BLOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the topology view served to `spira net graph` is refreshed
+const TOPOLOGY_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// How often sync progress is logged while behind and refreshed for `/sync/status`
+const SYNC_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+/// How often applied blocks are folded into the per-epoch semantic analytics
+const SEMANTIC_ANALYTICS_INTERVAL: Duration = Duration::from_secs(300);
+/// Candidates built for one slot before giving up on it: as selected, without
+/// mempool transactions, without the epoch summary
+const CANDIDATE_ATTEMPTS: usize = 3;
+/// How often database, mempool and vector store sizes are checked against their quotas
+const RESOURCE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 use tracing::{debug, error, info, warn};
 
 pub struct ValidatorNode {
     config: NodeConfig,
+    _data_lock: DataDirLock, // Keeps a second node off our data directory until we exit
     keypair: KeyPair,
     validator: Validator,
     mempool: Arc<RwLock<Vec<Transaction>>>,
@@ -25,12 +69,67 @@ pub struct ValidatorNode {
     current_height: Arc<RwLock<u64>>,
     last_produced_slot: Arc<AtomicU64>, // Track last slot we produced a block in
     is_producing: Arc<AtomicBool>, // Flag to prevent concurrent production
+    runtime: Arc<RuntimeConfigManager>, // Hot-reloadable operational settings
+    pending_compact_blocks: HashMap<Hash, (PartialBlock, PeerId, Instant)>, // Waiting on missing txs
+    topology: SharedTopology, // Peer/mesh view for the admin RPC
+    last_topology_refresh: Instant,
+    upgrade_required: bool, // Peers produce blocks for a protocol this binary doesn't know
+    signing_protection: SigningProtection, // Last height/slot signed, guards against equivocation
+    explorer: Arc<ExplorerFeed>, // Applied blocks streamed to explorers over WebSocket
+    validator_changes: broadcast::Sender<ValidatorSetChangeResponse>, // Joins, departures, slashings for subscribers
+    validation_pool: Option<BlockValidationPool>, // Checks received blocks off the event loop
+    validated_ahead: BTreeMap<u64, Block>, // Valid blocks whose parent is still being validated
+    checkpoints: SharedCheckpoints, // Finalized blocks; the chain never reorganizes below them
+    sync_stats: SyncStats,  // Catch-up progress against the best peer
+    sync_status: Arc<RwLock<SyncStatusResponse>>, // Last sync progress, served over RPC
+    propagation_stats: Arc<RwLock<PropagationStatsResponse>>, // Block announcement latency, served over RPC
+    compression_stats: Arc<RwLock<CompressionStatsResponse>>, // Gossip codec savings and CPU, served over RPC
+    mempool_monitor: Arc<MempoolMonitor>, // Why transactions were rejected or evicted
+    self_validation: Arc<SelfValidationMetrics>, // Own candidates failing the checks peers run
+    fast_relay: FastRelay, // Blocks passed on before validation, revoked if they fail
+    misbehavior: MisbehaviorMonitor, // Equivocations, missed slots and bad state roots of other validators
+    state_drift: Arc<StateDriftMonitor>, // Our state roots cross-checked with random peers
+    fork_alerts: Arc<ForkAlertLog>, // Divergence reports of the forks we saw, served on /alerts
+    local_model: LocalSemanticModel, // Embedding model we score with, checked against the approved one
+    compaction: CompactionScheduler, // Heavy storage maintenance, kept away from our leader slots
+    analytics_job: Option<tokio::task::JoinHandle<()>>, // Per-epoch semantic aggregation
+    dry_run: Option<DryRunReport>,        // Set with --dry-run: slots are simulated, never signed
+    at_rest: AtRestPolicy, // Sealing and privacy rules for what the node writes besides the chain
+    installed_genesis: Option<GenesisConfig>, // From `spira admin reset`; replaces the official genesis
+    started_at: Instant,
+    telemetry_id: Option<String>, // Created the first time telemetry is switched on
 }
 
+type SharedCheckpoints = Arc<parking_lot::RwLock<CheckpointSet>>;
+
 impl ValidatorNode {
     pub fn new(config: NodeConfig, keypair: KeyPair) -> Result<Self> {
-        let storage = BlockStorage::new(&config.data_dir)?;
+        let data_lock = DataDirLock::acquire(&config.data_dir)?;
+        let at_rest = AtRestPolicy::for_config(&config)?;
+        let storage = Arc::new(BlockStorage::new(&config.data_dir)?.with_at_rest(at_rest.clone()));
+        let installed_genesis = load_genesis(&config.data_dir, &config.network)?;
+        let runtime = RuntimeConfigManager::load(config.data_dir.join(RUNTIME_CONFIG_FILE))?;
+        let fork_alerts = ForkAlertLog::load(config.data_dir.join(FORK_ALERTS_FILE))?;
+        let local_model = LocalSemanticModel::load(&config.data_dir.join(SEMANTIC_MODEL_FILE))?;
         let address = keypair.to_address();
+        let signing_protection = SigningProtection::load(
+            config.data_dir.join(SIGNING_PROTECTION_FILE),
+            config.allow_double_sign,
+        )?;
+        if let Some(last) = signing_protection.last_signed(&address) {
+            if last.height == RETIRED_WATERMARK {
+                error!(
+                    "🚨 {} was retired by a key migration; this node will not sign with it",
+                    address
+                );
+                error!("🚨 Start the node with the new validator wallet instead");
+            } else {
+                info!(
+                    "🛡️  Double-sign protection: last signed height {}, slot {}",
+                    last.height, last.slot
+                );
+            }
+        }
 
         let validator = Validator {
             address,
@@ -45,14 +144,26 @@ impl ValidatorNode {
             last_block_height: 0,
         };
 
+        let chain_params = ChainParams::for_network(&config.network);
+        chain_params.validate()?;
+
         let mut consensus = ProofOfSpiral::new(
             spirachain_core::MIN_SPIRAL_COMPLEXITY,
             spirachain_core::MAX_SPIRAL_JUMP,
-        );
+        )
+        .with_chain_params(chain_params)
+        .with_continuity_store(Arc::clone(&storage) as Arc<dyn ContinuityStore>);
 
         // Enregistrer ce validator dans le consensus
         consensus.add_validator(validator.clone())?;
 
+        // Python calls run on the bridge's own worker threads, never on the runtime
+        spirapi_bridge::init_bridge_pool(spirapi_bridge::BridgePoolConfig {
+            call_timeout: Duration::from_millis(runtime.current().spirapi_call_timeout_ms),
+            latency_slo: Duration::from_millis(runtime.current().spirapi_latency_slo_ms),
+            ..Default::default()
+        });
+
         // Initialiser SpiraPi AI engine
         let spirapi_path = std::env::current_dir()
             .unwrap_or_else(|_| std::path::PathBuf::from("."))
@@ -83,6 +194,10 @@ impl ValidatorNode {
         let mut slot_consensus = SlotConsensus::new(&config.network);
         // Register ourselves as a validator
         slot_consensus.add_validator(address);
+        let joined = ValidatorSetChange::new(address, ValidatorChange::Joined, initial_height);
+        if let Err(e) = storage.record_validator_change(&joined) {
+            warn!("Failed to record validator set change: {}", e);
+        }
 
         info!("🎰 Slot consensus initialized");
         info!("   Network: {}", config.network);
@@ -90,9 +205,20 @@ impl ValidatorNode {
             "   Slot duration: {}s",
             if config.network == "mainnet" { 60 } else { 30 }
         );
+        info!(
+            "   Protocol: v{} (binary supports up to v{})",
+            chain_params.protocol_version_at(initial_height),
+            spirachain_core::PROTOCOL_VERSION
+        );
+        if let Some(fork) = chain_params.next_fork(initial_height) {
+            info!(
+                "   Next hard fork: {} (v{}) at height {}",
+                fork.name, fork.version, fork.height
+            );
+        }
 
         // Initialize WorldState and load all balances from storage
-        let mut world_state = WorldState::default();
+        let mut world_state = WorldState::for_network(&config.network);
         
         // Load all persisted balances from blockchain history
         info!("🔄 Reconstructing WorldState from blockchain...");
@@ -100,38 +226,69 @@ impl ValidatorNode {
         // Credit initial testnet stake to our validator (1000 QBT) if testnet
         if config.network == "testnet" {
             let initial_stake = Amount::new(1000 * 10u128.pow(18));
-            world_state.credit_balance(&address, initial_stake);
+            if let Err(e) = world_state.credit_balance(&address, initial_stake) {
+                warn!("Failed to credit initial stake: {}", e);
+            }
             info!("💰 Credited initial 1000 QBT testnet stake to our validator");
         }
         
-        // FIRST: Load ALL persisted balances from storage
-        // This ensures we have all accounts before replaying transactions
-        let mut loaded_accounts = 0;
-        if let Ok(all_addresses) = storage.get_all_addresses() {
-            for addr in &all_addresses {
-                if let Ok(stored_balance) = storage.get_balance(addr) {
-                    if !stored_balance.is_zero() {
-                        world_state.set_balance(*addr, stored_balance);
-                        loaded_accounts += 1;
+        // Accounts are persisted after every block, so a stored snapshot is
+        // the state at the tip and replaying on top of it would apply blocks twice
+        let stored_accounts = storage.get_all_accounts().unwrap_or_else(|e| {
+            warn!("Failed to load accounts from storage: {}", e);
+            Vec::new()
+        });
+        let loaded_accounts = stored_accounts.len();
+        for (address, account) in stored_accounts {
+            if let Some(code_hash) = account.code_hash {
+                match storage.get_contract_code(&code_hash) {
+                    Ok(Some(code)) => {
+                        world_state.insert_code(code);
                     }
+                    _ => warn!("Missing code for contract {}", address),
                 }
             }
-            if loaded_accounts > 0 {
-                info!("📥 Loaded {} account balances from storage", loaded_accounts);
+            world_state.set_account(address, account);
+        }
+        if loaded_accounts > 0 {
+            info!("📥 Loaded {} accounts from storage", loaded_accounts);
+            match storage.get_pause_state() {
+                Ok(pause) => world_state.set_pause_state(pause),
+                Err(e) => warn!("Failed to load pause state: {}", e),
+            }
+            match storage.get_semantic_models() {
+                Ok(models) => world_state.set_semantic_models(models),
+                Err(e) => warn!("Failed to load semantic models: {}", e),
+            }
+            match storage.get_spiral_registry() {
+                Ok(spirals) => world_state.set_spiral_registry(spirals),
+                Err(e) => warn!("Failed to load spiral registry: {}", e),
+            }
+            match storage.get_name_registry() {
+                Ok(names) => world_state.set_name_registry(names),
+                Err(e) => warn!("Failed to load name registry: {}", e),
+            }
+            match storage.get_spiral_diversity() {
+                Ok(diversity) => world_state.set_spiral_diversity(diversity),
+                Err(e) => warn!("Failed to load spiral diversity: {}", e),
             }
         }
-        
-        // THEN: Replay ALL blocks from storage to rebuild WorldState
-        // This will update balances based on transaction history
+
+        // Without a snapshot, replay ALL blocks from storage to rebuild WorldState
+        let replay_to = if loaded_accounts > 0 {
+            0
+        } else {
+            initial_height
+        };
         let mut replayed_blocks = 0;
-        for height in 1..=initial_height {
+        for height in 1..=replay_to {
             if let Ok(Some(block)) = storage.get_block_by_height(height) {
                 // Apply all transactions in this block
-                for tx in &block.transactions {
-                    // Apply transaction (accounts already loaded from storage above)
-                    if let Err(e) = world_state.transfer(&tx.from, &tx.to, tx.amount) {
-                        warn!("Failed to replay transaction in block {}: {}", height, e);
-                    }
+                for (tx_hash, e) in world_state.apply_block(&block) {
+                    warn_throttled!(
+                        "Failed to replay transaction {} in block {}: {}",
+                        tx_hash, height, e
+                    );
                 }
                 
                 replayed_blocks += 1;
@@ -145,13 +302,27 @@ impl ValidatorNode {
         
         world_state.set_height(initial_height);
 
+        let checkpoints = CheckpointSet::from_checkpoints(storage.get_checkpoints()?);
+        if let Some(latest) = checkpoints.latest() {
+            info!(
+                "📍 Loaded {} checkpoints (latest at height {})",
+                checkpoints.len(),
+                latest.height
+            );
+        }
+
+        let dry_run = config
+            .dry_run
+            .then(|| DryRunReport::new(&address, &config.network));
+
         Ok(Self {
+            _data_lock: data_lock,
             config,
             keypair,
             validator,
             mempool: Arc::new(RwLock::new(Vec::new())),
             state: Arc::new(RwLock::new(world_state)),
-            storage: Arc::new(storage),
+            storage,
             consensus,
             slot_consensus: Arc::new(RwLock::new(slot_consensus)),
             network: None, // Initialized in start()
@@ -161,17 +332,214 @@ impl ValidatorNode {
             current_height: Arc::new(RwLock::new(initial_height)),
             last_produced_slot: Arc::new(AtomicU64::new(0)),
             is_producing: Arc::new(AtomicBool::new(false)),
+            runtime: Arc::new(runtime),
+            pending_compact_blocks: HashMap::new(),
+            topology: Arc::new(parking_lot::RwLock::new(None)),
+            last_topology_refresh: Instant::now(),
+            upgrade_required: false,
+            signing_protection,
+            explorer: Arc::new(ExplorerFeed::default()),
+            validator_changes: broadcast::channel(VALIDATOR_CHANGES_CHANNEL).0,
+            validation_pool: None,
+            validated_ahead: BTreeMap::new(),
+            checkpoints: Arc::new(parking_lot::RwLock::new(checkpoints)),
+            sync_stats: SyncStats::new(),
+            sync_status: Arc::new(RwLock::new(SyncStatusResponse::default())),
+            propagation_stats: Arc::new(RwLock::new(PropagationStatsResponse::default())),
+            compression_stats: Arc::new(RwLock::new(CompressionStatsResponse::default())),
+            mempool_monitor: Arc::new(MempoolMonitor::new()),
+            self_validation: Arc::new(SelfValidationMetrics::new()),
+            fast_relay: FastRelay::new(),
+            misbehavior: MisbehaviorMonitor::new(),
+            state_drift: Arc::new(StateDriftMonitor::new()),
+            fork_alerts: Arc::new(fork_alerts),
+            local_model,
+            compaction: CompactionScheduler::new(),
+            analytics_job: None,
+            dry_run,
+            at_rest,
+            installed_genesis,
+            started_at: Instant::now(),
+            telemetry_id: None,
         })
     }
 
+    /// Let runtime config reloads change the log level
+    pub fn set_log_level_setter(&self, setter: LogLevelSetter) {
+        self.runtime.set_log_level_setter(setter);
+    }
+
+    pub fn runtime_config(&self) -> Arc<RuntimeConfigManager> {
+        Arc::clone(&self.runtime)
+    }
+
+    async fn notify_new_block(&self, block: &Block) {
+        self.publish_explorer_block(block).await;
+        self.state_drift.record_local(StateRootSample {
+            height: block.header.block_height,
+            block_hash: block.hash(),
+            state_root: self.state.write().await.calculate_merkle_root(),
+        });
+
+        notify_webhooks(
+            self.runtime.webhook_endpoints(),
+            serde_json::json!({
+                "event": "new_block",
+                "height": block.header.block_height,
+                "hash": block.hash().to_string(),
+                "transactions": block.transactions.len(),
+                "timestamp": block.header.timestamp,
+            }),
+        );
+    }
+
+    /// Checkpoint the block that a new tip finalized, if one is due, and
+    /// gossip it to peers
+    async fn update_checkpoints(&self, tip_height: u64) {
+        let Some(checkpoint) = record_due_checkpoint(&self.storage, &self.checkpoints, tip_height)
+        else {
+            return;
+        };
+
+        if let Some(ref network) = self.network {
+            network
+                .write()
+                .await
+                .broadcast_checkpoint(checkpoint.height, &checkpoint.block_hash);
+        }
+    }
+
+    /// Slash a producer caught signing two blocks for one height
+    fn punish_equivocation(&mut self, evidence: EquivocationEvidence) {
+        let Ok(producer) = evidence.verify() else {
+            return;
+        };
+        let height = evidence.height();
+        error!(
+            "🚨 Validator {} signed two blocks for height {}: {} and {}",
+            producer,
+            height,
+            evidence.first.hash(),
+            evidence.second.hash()
+        );
+
+        let timestamp = evidence.second.timestamp;
+        if let Some(amount) = self.consensus.slash_validator(
+            &producer,
+            SlashingReason::DoubleSigning,
+            height,
+            timestamp,
+        ) {
+            self.record_validator_change(
+                producer,
+                ValidatorChange::Slashed {
+                    reason: "DoubleSigning".to_string(),
+                    amount,
+                },
+                height,
+            );
+        }
+    }
+
+    /// Persist a validator set change and push it to live subscribers
+    fn record_validator_change(&self, validator: Address, change: ValidatorChange, height: u64) {
+        let change = ValidatorSetChange::new(validator, change, height);
+        match self.storage.record_validator_change(&change) {
+            Ok(true) => {
+                info!(
+                    "🧭 Validator {} {} at height {}",
+                    validator,
+                    change.change.name(),
+                    height
+                );
+                // No subscribers is not an error
+                let _ = self.validator_changes.send((&change).into());
+            }
+            Ok(false) => {}
+            Err(e) => warn!("Failed to record validator set change: {}", e),
+        }
+    }
+
+    /// Send the block with the resulting account state of every address it touched
+    async fn publish_explorer_block(&self, block: &Block) {
+        let mut touched: Vec<Address> = Vec::new();
+        for tx in &block.transactions {
+            for address in [tx.from, tx.to] {
+                if !touched.contains(&address) {
+                    touched.push(address);
+                }
+            }
+        }
+
+        let state = self.state.read().await;
+        let changes: Vec<AccountChange> = touched
+            .into_iter()
+            .filter_map(|address| {
+                state.get_account(&address).map(|account| AccountChange {
+                    address,
+                    balance: account.balance,
+                    nonce: account.nonce,
+                })
+            })
+            .collect();
+        drop(state);
+
+        let producer = PublicKey::from_bytes(&block.header.validator_pubkey)
+            .map(|key| key.to_address())
+            .unwrap_or_else(|_| Address::new([0u8; 32]));
+        let active_validators = self.slot_consensus.read().await.validator_count();
+
+        self.explorer.publish_block(block, &changes, producer, active_validators);
+    }
+
     pub async fn start(&mut self) -> Result<()> {
         info!("🚀 Starting SpiraChain Validator Node");
         info!("   Address: {}", self.validator.address);
+        info!("   Stake: {} QBT", self.validator.stake.to_qbt_string());
+        info!("   Data dir: {}", self.config.data_dir.display());
         info!(
-            "   Stake: {} QBT",
-            self.validator.stake.value() as f64 / 1e18
+            "   Build: v{} ({}), protocol v{}",
+            env!("CARGO_PKG_VERSION"),
+            crate::GIT_COMMIT,
+            spirachain_core::PROTOCOL_VERSION
         );
-        info!("   Data dir: {}", self.config.data_dir.display());
+
+        let chain_height = *self.current_height.read().await;
+        if let Some(report) = self.dry_run.as_mut() {
+            info!("🧪 Dry run: following the chain without signing or broadcasting blocks");
+            info!(
+                "   Report: {}",
+                self.config.data_dir.join(DRY_RUN_REPORT_FILE).display()
+            );
+            report.check_key(&self.keypair, &self.signing_protection, chain_height);
+        }
+        if self.at_rest.cipher.is_some() {
+            info!("🔐 At-rest encryption on for the mempool and semantic stores");
+        }
+        if self.at_rest.privacy_mode {
+            info!("🕶️  Privacy mode: semantic fields are never written to disk");
+        }
+        if self.installed_genesis.is_some() {
+            info!(
+                "🌱 Following the genesis installed by a testnet reset: {}",
+                self.expected_genesis_hash()
+            );
+        }
+        self.restore_mempool(chain_height).await;
+
+        match self.runtime.telemetry_endpoint() {
+            Some(endpoint) => {
+                info!(
+                    "📡 Telemetry on: anonymous node stats are sent to {}",
+                    endpoint
+                );
+                info!(
+                    "   Remove telemetry_endpoint from {} to turn it off",
+                    self.runtime.path().display()
+                );
+            }
+            None => info!("📡 Telemetry off"),
+        }
 
         // Initialize P2P network with block sync
         info!("🌐 Starting LibP2P network with block synchronization...");
@@ -186,26 +554,58 @@ impl ValidatorNode {
         let current_height = *self.current_height.read().await;
         info!("📊 Current blockchain height: {}", current_height);
 
-        match LibP2PNetworkWithSync::new_with_network(port, &self.config.network, current_height)
-            .await
+        let node_key = load_or_create_identity(&self.config.data_dir)?;
+        // Advertised to peers; a node still waiting for its genesis advertises none
+        let genesis_hash = match self.storage.get_block_by_height(0)? {
+            Some(genesis) => Some(genesis.hash()),
+            None => self
+                .installed_genesis
+                .as_ref()
+                .map(|config| config.create_genesis_block().hash()),
+        };
+        let mut handshake_stats = None;
+        let mut gossip_stats = None;
+        let mut liveness_stats = None;
+        let mut outbound_stats = None;
+        let mut diversity_stats = None;
+        match LibP2PNetworkWithSync::new_with_identity(
+            port,
+            &self.config.network,
+            current_height,
+            node_key,
+            self.config.node_type.network_role(),
+            &self.runtime.p2p_gossip(),
+            genesis_hash,
+        )
+        .await
         {
             Ok(mut network) => {
                 info!(
                     "✅ P2P network with sync created for {}",
                     self.config.network.to_uppercase()
                 );
+                handshake_stats = Some(network.handshake_stats());
+                gossip_stats = Some(network.gossip_validation_stats());
+                liveness_stats = Some(network.liveness_stats());
+                outbound_stats = Some(network.outbound_queue_stats());
+                diversity_stats = Some(network.peer_diversity_stats());
+                network.set_compression(self.runtime.p2p_compression());
+                network.set_peer_diversity(self.runtime.p2p_peers());
 
                 // Set up block storage callback
                 let storage_clone = Arc::clone(&self.storage);
                 let state_clone = Arc::clone(&self.state);
                 let height_clone = Arc::clone(&self.current_height);
+                let checkpoints_clone = Arc::clone(&self.checkpoints);
 
                 network.set_block_store_callback(move |block: Block| {
                     let height = block.header.block_height;
+                    checkpoints_clone.read().verify_block(&block)?;
                     info!("💾 Storing synced block {}", height);
 
                     // Store the block
                     storage_clone.store_block(&block)?;
+                    record_due_checkpoint(&storage_clone, &checkpoints_clone, height);
 
                     // Update height
                     let rt = tokio::runtime::Handle::current();
@@ -217,18 +617,15 @@ impl ValidatorNode {
                     // Update state with block transactions
                     rt.block_on(async {
                         let mut state = state_clone.write().await;
-                        for tx in &block.transactions {
-                            if let Err(e) = state.transfer(&tx.from, &tx.to, tx.amount) {
-                                warn!("Failed to apply transaction in synced block: {}", e);
-                            }
+                        for (tx_hash, e) in state.apply_block(&block) {
+                            warn!(
+                                "Failed to apply transaction {} in synced block: {}",
+                                tx_hash, e
+                            );
                         }
 
-                        // Persist all balances after applying block
-                        for (address, balance) in state.get_all_balances() {
-                            if let Err(e) = storage_clone.set_balance(&address, balance) {
-                                warn!("Failed to persist balance for {}: {}", address, e);
-                            }
-                        }
+                        // Persist all accounts after applying block
+                        persist_accounts(&storage_clone, &state);
                     });
 
                     Ok(())
@@ -241,8 +638,11 @@ impl ValidatorNode {
                         e
                     );
                 } else {
-                    // Announce ourselves as a validator to the network
-                    network.announce_validator(&self.validator.address);
+                    // Announce ourselves as a validator to the network, unless
+                    // this is a dry run and peers must not schedule us
+                    if self.dry_run.is_none() {
+                        network.announce_validator(&self.validator.address);
+                    }
                     
                     #[allow(clippy::arc_with_non_send_sync)]
                     {
@@ -268,7 +668,7 @@ impl ValidatorNode {
                 .get_balance(&self.validator.address)
                 .unwrap_or_default();
             if current_balance.is_zero() {
-                let initial_stake = Amount::new(1000 * 1e18 as u128); // 1000 QBT for testnet only
+                let initial_stake = Amount::qbt(1000); // 1000 QBT for testnet only
                 if let Err(e) = self
                     .storage
                     .set_balance(&self.validator.address, initial_stake)
@@ -277,7 +677,7 @@ impl ValidatorNode {
                 } else {
                     info!(
                         "💰 [TESTNET] Initial staking balance credited: {} QBT",
-                        initial_stake.value() as f64 / 1e18
+                        initial_stake.to_qbt_string()
                     );
 
                     // Verify the balance was actually stored
@@ -285,7 +685,7 @@ impl ValidatorNode {
                         Ok(stored_balance) => {
                             info!(
                                 "✅ Verified stored balance: {} QBT",
-                                stored_balance.value() as f64 / 1e18
+                                stored_balance.to_qbt_string()
                             );
                         }
                         Err(e) => {
@@ -296,7 +696,7 @@ impl ValidatorNode {
             } else {
                 info!(
                     "💰 Existing balance found: {} QBT (skipping initial credit)",
-                    current_balance.value() as f64 / 1e18
+                    current_balance.to_qbt_string()
                 );
             }
         } else {
@@ -315,7 +715,7 @@ impl ValidatorNode {
             state.set_balance(self.validator.address, stored_balance);
             info!(
                 "🔄 Loaded validator balance into WorldState: {} QBT",
-                stored_balance.value() as f64 / 1e18
+                stored_balance.to_qbt_string()
             );
         }
 
@@ -328,16 +728,87 @@ impl ValidatorNode {
         let chain_height = Arc::new(RwLock::new(0u64));
         let chain_height_clone = Arc::clone(&chain_height);
         let connected_peers_clone = Arc::clone(&self.connected_peers);
+        let runtime_clone = Arc::clone(&self.runtime);
+        let network_name = self.config.network.clone();
+        let version = crate::version_info(&self.config.network);
+        let explorer = Arc::clone(&self.explorer);
+        let validator_changes = self.validator_changes.clone();
+        let sync_status = Arc::clone(&self.sync_status);
+        let propagation_stats = Arc::clone(&self.propagation_stats);
+        let compression_stats = Arc::clone(&self.compression_stats);
+        let mempool_monitor = Arc::clone(&self.mempool_monitor);
+        let self_validation = Arc::clone(&self.self_validation);
+        let fast_relay_stats = self.fast_relay.stats();
+        let misbehavior_stats = self.misbehavior.stats();
+        let state_drift = Arc::clone(&self.state_drift);
+        let fork_alerts = Arc::clone(&self.fork_alerts);
+        let compaction_stats = self.compaction.stats();
+        let simulator = NodeSimulator::new(Arc::clone(&self.state));
+        let slot_schedule = NodeSlotSchedule::new(Arc::clone(&self.slot_consensus));
+        let pi_identifiers = NodePiIdentifierService::new(
+            Arc::clone(&self.runtime),
+            self.keypair.clone(),
+            self.config.network.clone(),
+        );
+        let admin = NodeAdmin::new(
+            Arc::clone(&self.runtime),
+            Arc::clone(&self.topology),
+            self.keypair.clone(),
+            Arc::clone(&self.storage),
+            self.config.network.clone(),
+        );
 
         tokio::spawn(async move {
-            let rpc_server = spirachain_rpc::RpcServer::new(
+            let mut rpc_server = spirachain_rpc::RpcServer::new(
                 mempool_clone,
                 storage_clone,
                 chain_height_clone,
                 connected_peers_clone,
                 true,
                 rpc_port,
-            );
+            )
+            .with_network(network_name)
+            .with_rate_limiter(runtime_clone.rate_limiter())
+            .with_mempool_limit(runtime_clone.mempool_limit())
+            .with_admin(Arc::new(admin))
+            .with_simulator(Arc::new(simulator))
+            .with_slot_schedule(Arc::new(slot_schedule))
+            .with_alerts(fork_alerts)
+            .with_pi_identifier_service(
+                Arc::new(pi_identifiers),
+                runtime_clone.pi_identifier_rate_limiter(),
+            )
+            .with_explorer_feed(explorer)
+            .with_validator_changes(validator_changes)
+            .with_sync_status(sync_status)
+            .with_propagation_stats(propagation_stats)
+            .with_compression_stats(compression_stats)
+            .with_mempool_monitor(mempool_monitor)
+            .with_resource_guard(runtime_clone.resource_guard())
+            .with_metrics_source(Arc::new(SpiraPiMetrics))
+            .with_metrics_source(Arc::new(SignatureCacheMetrics))
+            .with_metrics_source(self_validation)
+            .with_metrics_source(fast_relay_stats)
+            .with_metrics_source(misbehavior_stats)
+            .with_metrics_source(state_drift)
+            .with_metrics_source(compaction_stats)
+            .with_metrics_source(Arc::new(WarningThrottleMetrics))
+            .with_version(version);
+            if let Some(stats) = handshake_stats {
+                rpc_server = rpc_server.with_metrics_source(Arc::new(HandshakeMetrics(stats)));
+            }
+            if let Some(stats) = gossip_stats {
+                rpc_server = rpc_server.with_metrics_source(Arc::new(GossipMetrics(stats)));
+            }
+            if let Some(stats) = liveness_stats {
+                rpc_server = rpc_server.with_metrics_source(Arc::new(LivenessMetrics(stats)));
+            }
+            if let Some(stats) = outbound_stats {
+                rpc_server = rpc_server.with_metrics_source(Arc::new(OutboundQueueMetrics(stats)));
+            }
+            if let Some(stats) = diversity_stats {
+                rpc_server = rpc_server.with_metrics_source(Arc::new(PeerDiversityMetrics(stats)));
+            }
 
             if let Err(e) = rpc_server.start().await {
                 error!("RPC server error: {}", e);
@@ -376,16 +847,14 @@ impl ValidatorNode {
             if peer_count == 0 {
                 // We are the FIRST node - create genesis NOW
                 info!("🌱 Creating genesis block (first node in network)...");
-                let config = spirachain_core::GenesisConfig::default();
+                let config = self.installed_genesis.clone().unwrap_or_default();
                 let genesis = config.create_genesis_block();
                 
                 // Apply genesis transactions to WorldState
                 let mut state = self.state.write().await;
                 for tx in &genesis.transactions {
-                    if let Err(e) = state.transfer(&tx.from, &tx.to, tx.amount) {
+                    if let Err(e) = state.apply_transaction(tx) {
                         debug!("Genesis allocation from {} failed (expected): {}", tx.from, e);
-                    } else {
-                        state.increment_nonce(&tx.from);
                     }
                 }
                 drop(state);
@@ -395,21 +864,17 @@ impl ValidatorNode {
                 *chain_height.write().await = 0;
                 self.state.write().await.set_height(0);
                 
-                // Sync all genesis balances to storage
+                // Sync all genesis accounts to storage
                 let state = self.state.read().await;
-                for (address, balance) in state.get_all_balances() {
-                    if let Err(e) = self.storage.set_balance(&address, balance) {
-                        warn!("Failed to sync genesis balance for {}: {}", address, e);
-                    }
-                }
+                persist_accounts(&self.storage, &state);
                 
                 info!("✅ Genesis block created and stored!");
                 info!("   Hash: {}", genesis.hash());
                 
                 // CRITICAL: Verify genesis hash matches the official network genesis
-                if !spirachain_core::GenesisConfig::verify_genesis_hash(&genesis, &self.config.network) {
+                if !self.is_expected_genesis(&genesis) {
                     error!("❌ CRITICAL: Generated genesis hash does NOT match official {} genesis!", self.config.network.to_uppercase());
-                    error!("   Expected: {}", spirachain_core::GenesisConfig::expected_genesis_hash(&self.config.network));
+                    error!("   Expected: {}", self.expected_genesis_hash());
                     error!("   Got:      {}", genesis.hash());
                     error!("   This should NEVER happen! Check genesis.rs for bugs!");
                     return Err(anyhow::anyhow!("Genesis hash mismatch - aborting to prevent network fork").into());
@@ -442,14 +907,39 @@ impl ValidatorNode {
         let mut stats_timer = interval(Duration::from_secs(30));
         let mut mempool_check = interval(Duration::from_secs(5));
         let mut network_tick = interval(Duration::from_millis(100));
+        let mut sync_progress_timer = interval(SYNC_PROGRESS_INTERVAL);
+        let mut analytics_timer = interval(SEMANTIC_ANALYTICS_INTERVAL);
+        let mut resource_timer = interval(RESOURCE_CHECK_INTERVAL);
+        let mut telemetry_timer = interval(TELEMETRY_INTERVAL);
+        let mut state_drift_timer = interval(STATE_DRIFT_CHECK_INTERVAL);
 
         info!("⚡ Validator loop started (slot duration: {}s)", block_interval);
         if self.network.is_some() {
             info!("   P2P network enabled");
         }
 
+        // Service managers (systemd, launchd, WinSW) stop us with SIGTERM or Ctrl-C
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+        let mut reload_signal = ReloadSignal::new();
+
+        let (validation_pool, mut verdicts) = BlockValidationPool::spawn(&self.config.network);
+        self.validation_pool = Some(validation_pool);
+
         loop {
             tokio::select! {
+                _ = &mut shutdown => {
+                    info!("🛑 Shutdown signal received, stopping validator...");
+                    *self.is_running.write().await = false;
+                }
+
+                _ = reload_signal.recv() => {
+                    info!("🔁 SIGHUP received, reloading {:?}", self.runtime.path());
+                    if let Err(e) = self.runtime.reload() {
+                        error!("Config reload failed, keeping current settings: {}", e);
+                    }
+                }
+
                 _ = block_timer.tick() => {
                     // CRITICAL: Only produce blocks if we are fully synced with peers
                     // This prevents fork creation when a new node joins with height=0
@@ -522,7 +1012,9 @@ impl ValidatorNode {
                             // Successfully set is_producing to true
                             info!("✅ Our turn to produce block (slot {}, validators: {}, peers: {})", current_slot, validator_count, peer_count);
                             
-                            if let Err(e) = self.produce_block().await {
+                            if self.dry_run.is_some() {
+                                self.simulate_slot(current_slot).await;
+                            } else if let Err(e) = self.produce_block(current_slot).await {
                                 error!("Failed to produce block: {}", e);
                             }
                             
@@ -540,6 +1032,10 @@ impl ValidatorNode {
                     }
                 }
 
+                Some(verdict) = verdicts.recv() => {
+                    self.handle_block_verdict(verdict).await;
+                }
+
                 _ = stats_timer.tick() => {
                     self.print_stats().await;
                 }
@@ -548,6 +1044,27 @@ impl ValidatorNode {
                     self.check_mempool().await;
                 }
 
+                _ = sync_progress_timer.tick() => {
+                    self.report_sync_progress().await;
+                }
+
+                _ = analytics_timer.tick() => {
+                    self.update_semantic_analytics();
+                }
+
+                _ = resource_timer.tick() => {
+                    self.check_resources().await;
+                    self.schedule_compaction().await;
+                }
+
+                _ = telemetry_timer.tick() => {
+                    self.report_telemetry().await;
+                }
+
+                _ = state_drift_timer.tick() => {
+                    self.check_state_drift().await;
+                }
+
                 _ = network_tick.tick() => {
                     // Poll P2P events and handle network messages
                     if let Some(ref network) = self.network {
@@ -566,6 +1083,36 @@ impl ValidatorNode {
                             // Try to reconnect if no peers connected
                             net.try_reconnect();
 
+                            // Outbound minimum, subnet caps and peer rotation
+                            net.set_peer_diversity(self.runtime.p2p_peers());
+                            net.maintain_peers();
+
+                            // Latency probes and sync request failover
+                            net.maintain_sync();
+
+                            if let Some(banned) = self.runtime.take_peer_ban_update() {
+                                net.set_banned_peers(&banned);
+                            }
+                            net.set_compression(self.runtime.p2p_compression());
+
+                            // Compact blocks whose missing transactions never arrived
+                            self.pending_compact_blocks.retain(|_, (partial, peer, requested_at)| {
+                                if requested_at.elapsed() < COMPACT_BLOCK_TIMEOUT {
+                                    return true;
+                                }
+                                warn!(
+                                    "⏱️ Missing transactions for block {} timed out, fetching full block",
+                                    partial.height()
+                                );
+                                net.request_full_block(*peer, partial.height());
+                                false
+                            });
+
+                            if self.last_topology_refresh.elapsed() >= TOPOLOGY_REFRESH_INTERVAL {
+                                *self.topology.write() = Some(net.topology_snapshot());
+                                self.last_topology_refresh = Instant::now();
+                            }
+
                             evt
                         };
 
@@ -578,6 +1125,15 @@ impl ValidatorNode {
             }
 
             if !*self.is_running.read().await {
+                if let Err(e) = self.storage.flush() {
+                    error!("Failed to flush storage on shutdown: {}", e);
+                }
+                self.persist_mempool().await;
+                if let Some(report) = self.save_dry_run_report().await {
+                    for line in report.summary_lines() {
+                        info!("🧪 {}", line);
+                    }
+                }
                 info!("Validator stopped");
                 break;
             }
@@ -586,12 +1142,152 @@ impl ValidatorNode {
         Ok(())
     }
 
-    async fn produce_block(&mut self) -> Result<()> {
+    /// Dry-run stand-in for `produce_block`: build and apply the block we would
+    /// have produced on a copy of the state, timed against the end of the slot.
+    /// Nothing is signed, stored, recorded for double-sign protection or sent.
+    async fn simulate_slot(&mut self, slot: u64) {
+        let deadline = Duration::from_secs(self.slot_consensus.read().await.time_until_next_slot());
+        let started = Instant::now();
+        let built = self.build_dry_run_block(slot).await;
+        let build_time = started.elapsed();
+
+        match &built {
+            Ok(block) => info!(
+                "🧪 Dry run: would have produced block {} ({} txs) in {}ms, {}s left in slot {}",
+                block.header.block_height,
+                block.transactions.len(),
+                build_time.as_millis(),
+                deadline.as_secs(),
+                slot
+            ),
+            Err(e) => warn!(
+                "🧪 Dry run: could not have produced a block for slot {}: {}",
+                slot, e
+            ),
+        }
+
+        if let Some(report) = self.dry_run.as_mut() {
+            report.record_slot(
+                build_time,
+                deadline,
+                built.map(|_| ()).map_err(|e| e.to_string()),
+            );
+        }
+        self.save_dry_run_report().await;
+    }
+
+    async fn build_dry_run_block(&mut self, slot: u64) -> Result<Block> {
+        let next_height = *self.current_height.read().await + 1;
+        let pause = self.state.read().await.pause_state().clone();
+        let mut pending_txs = self
+            .mempool
+            .read()
+            .await
+            .iter()
+            .filter(|tx| {
+                tx.validate_fork_id(&self.config.network, next_height)
+                    .is_ok()
+            })
+            .filter(|tx| pause.check_transaction(tx, next_height).is_ok())
+            .filter(|tx| tx.validate_schedule(next_height).is_ok())
+            .cloned()
+            .collect::<Vec<_>>();
+        prioritize_lanes(&mut pending_txs);
+        pending_txs.truncate(1000);
+
+        let prev_block = self
+            .storage
+            .get_latest_block()?
+            .ok_or_else(|| anyhow::anyhow!("No genesis block found - node not ready"))?;
+        self.signing_protection.check(
+            &self.validator.address,
+            prev_block.header.block_height + 1,
+            slot,
+        )?;
+
+        let diversity = self.state.read().await.spiral_diversity().clone();
+        self.consensus.set_spiral_diversity(diversity);
+        self.update_semantic_scoring(prev_block.header.block_height + 1)
+            .await;
+        self.consensus
+            .set_epoch_summary(self.epoch_summary_for(prev_block.header.block_height + 1));
+        let mut block =
+            self.consensus
+                .build_block_candidate(&self.validator, pending_txs, &prev_block)?;
+
+        let mut state = self.state.read().await.clone();
+        state.apply_block(&block);
+        block.header.state_root = state.calculate_merkle_root();
+        Ok(block)
+    }
+
+    /// Bring the dry-run report up to date and write it to the data directory
+    async fn save_dry_run_report(&mut self) -> Option<DryRunReport> {
+        let chain_height = *self.current_height.read().await;
+        let synced = self.sync_stats.peers() > 0 && !self.sync_stats.is_syncing();
+        let report = self.dry_run.as_mut()?;
+        report.chain_height = chain_height;
+        report.synced = synced;
+
+        let path = self.config.data_dir.join(DRY_RUN_REPORT_FILE);
+        if let Err(e) = report.save(&path) {
+            warn!("Failed to write dry-run report: {}", e);
+        }
+        Some(report.clone())
+    }
+
+    async fn produce_block(&mut self, slot: u64) -> Result<()> {
+        if self.upgrade_required {
+            warn!("⛔ Block production paused: upgrade required to follow the network");
+            return Ok(());
+        }
+
         info!("🏗️  Producing new block...");
 
-        let mempool_guard = self.mempool.read().await;
-        let pending_txs = mempool_guard.iter().take(1000).cloned().collect::<Vec<_>>();
+        let next_height = *self.current_height.read().await + 1;
+        let pause = self.state.read().await.pause_state().clone();
+        if let Some(until) = pause.paused_until(next_height) {
+            warn!(
+                "⏸️  Chain paused until block {}: only guardian votes are included",
+                until
+            );
+        }
+
+        let mut mempool_guard = self.mempool.write().await;
+        // Transactions signed for another fork can never be included
+        mempool_guard.retain(
+            |tx| match tx.validate_fork_id(&self.config.network, next_height) {
+                Ok(()) => true,
+                Err(e) => {
+                    self.mempool_monitor
+                        .record(&tx.tx_hash, DropReason::WrongFork, e.to_string());
+                    false
+                }
+            },
+        );
+        // Nor can scheduled transactions whose window has passed
+        mempool_guard.retain(|tx| match tx.schedule_status(next_height) {
+            ScheduleStatus::Expired(end) => {
+                self.mempool_monitor.record(
+                    &tx.tx_hash,
+                    DropReason::Expired,
+                    format!("inclusion window ended at block {}", end),
+                );
+                false
+            }
+            _ => true,
+        });
+        // Held back, not dropped: they become includable once the pause expires
+        // or their execution height is reached
+        let mut pending_txs = mempool_guard
+            .iter()
+            .filter(|tx| pause.check_transaction(tx, next_height).is_ok())
+            .filter(|tx| tx.validate_schedule(next_height).is_ok())
+            .cloned()
+            .collect::<Vec<_>>();
         drop(mempool_guard);
+        prioritize_lanes(&mut pending_txs);
+        pending_txs.truncate(1000);
 
         // Get latest block from storage (not state height!)
         let previous_block = self.storage.get_latest_block()?;
@@ -609,41 +1305,111 @@ impl ValidatorNode {
         let prev_block = previous_block
             .ok_or_else(|| anyhow::anyhow!("No genesis block found - node not ready"))?;
 
-        let mut block = self.consensus.generate_block_candidate(
-            &self.validator,
-            &self.keypair,
-            pending_txs.clone(),
-            &prev_block,
-        )?;
+        // Never sign a second block for a height or slot this key already signed
+        self.signing_protection
+            .check(&self.validator.address, current_height + 1, slot)?;
+        if self.cancel_for_competing_block(&prev_block, slot).await? {
+            return Ok(());
+        }
 
-        // Apply transactions to WorldState and calculate state_root
-        {
-            let mut state = self.state.write().await;
+        let diversity = self.state.read().await.spiral_diversity().clone();
+        self.consensus.set_spiral_diversity(diversity);
+        self.update_semantic_scoring(current_height + 1).await;
+        let summary = self.epoch_summary_for(current_height + 1);
+
+        // Run our candidate through the checks a receiving peer runs before
+        // anything is signed into the protection record or broadcast. A retry
+        // drops what peers are most likely to object to: first the mempool
+        // transactions, then the epoch summary.
+        let mut candidate = None;
+        for attempt in 0..CANDIDATE_ATTEMPTS {
+            if attempt == 2 && summary.is_none() {
+                break;
+            }
+            let mut txs = if attempt == 0 {
+                pending_txs.clone()
+            } else {
+                Vec::new()
+            };
+            self.consensus
+                .set_epoch_summary(if attempt < 2 { summary.clone() } else { None });
+
+            // Apply transactions to a copy of WorldState and calculate state_root.
+            // The coinbase may only collect fees that were actually paid, so
+            // transactions that fail are dropped and the candidate rebuilt.
+            let (mut block, mut state) = loop {
+                let block = self.consensus.build_block_candidate(
+                    &self.validator,
+                    txs.clone(),
+                    &prev_block,
+                )?;
+                block.validate_limits(&ChainParams::for_network(&self.config.network).block_limits)?;
+                let mut state = self.state.read().await.clone();
+                let mut failed = HashSet::new();
+                for (tx_hash, e) in state.apply_block(&block) {
+                    warn_throttled!("Failed to apply transaction {} in block: {}", tx_hash, e);
+                    failed.insert(tx_hash);
+                }
+                let before = txs.len();
+                txs.retain(|tx| !failed.contains(&tx.tx_hash));
+                if txs.len() == before {
+                    break (block, state);
+                }
+            };
+            block.header.state_root = state.calculate_merkle_root();
 
-            // Process transactions
-            for tx in &block.transactions {
-                if let Err(e) = state.transfer(&tx.from, &tx.to, tx.amount) {
-                    warn!("Failed to transfer in block: {}", e);
-                } else {
-                    state.increment_nonce(&tx.from);
+            self.self_validation.record_checked();
+            match self.self_validate_candidate(&block, &prev_block, &state) {
+                Ok(()) => {
+                    candidate = Some((block, state));
+                    break;
+                }
+                Err((stage, e)) => {
+                    self.self_validation.record_rejected(stage);
+                    warn!(
+                        "🔁 Own candidate for block {} fails the {} check (attempt {}/{}): {}",
+                        current_height + 1,
+                        stage.name(),
+                        attempt + 1,
+                        CANDIDATE_ATTEMPTS,
+                        e
+                    );
                 }
             }
+        }
+        let Some((mut block, new_state)) = candidate else {
+            self.self_validation.record_abandoned();
+            return Err(SpiraChainError::InvalidBlock(format!(
+                "no candidate for block {} passed self-validation",
+                current_height + 1
+            )));
+        };
+        // Building took a while; nothing is signed, recorded or broadcast if
+        // the slot was filled meanwhile
+        if self.cancel_for_competing_block(&prev_block, slot).await? {
+            return Ok(());
+        }
+        // Signed exactly once, over the final header including the state root:
+        // every signature spends one of the key's one-time leaves
+        block.header.signature = self.keypair.sign(block.hash().as_bytes());
 
-            // Credit block reward to validator
-            let block_reward = Amount::new(spirachain_core::INITIAL_BLOCK_REWARD);
-            state.credit_balance(&self.validator.address, block_reward);
+        {
+            let mut state = self.state.write().await;
+            *state = new_state;
+
+            // Block reward and fees were minted by the coinbase transaction above
+            let block_reward = block
+                .coinbase()
+                .map(|tx| tx.amount)
+                .unwrap_or(Amount::zero());
 
             let new_balance = state.get_balance(&self.validator.address);
             info!(
                 "💰 Crediting {} QBT to validator. New balance: {} QBT",
-                block_reward.value() as f64 / 1e18,
-                new_balance.value() as f64 / 1e18
+                block_reward.to_qbt_string(),
+                new_balance.to_qbt_string()
             );
 
-            // Calculate state root from complete WorldState
-            let state_root = state.calculate_merkle_root();
-            block.header.state_root = state_root;
-            
             // Update block height in state
             state.set_height(block.header.block_height);
 
@@ -657,26 +1423,32 @@ impl ValidatorNode {
                 info!("✅ Balance persisted to storage");
             }
 
-            // Sync all balances from WorldState to BlockStorage
-            for (address, balance) in state.get_all_balances() {
-                if let Err(e) = self.storage.set_balance(&address, balance) {
-                    warn!("Failed to sync balance for {}: {}", address, e);
-                }
-            }
+            // Sync all accounts from WorldState to BlockStorage
+            persist_accounts(&self.storage, &state);
         }
 
-        // Only validate non-genesis blocks
-        if current_height > 0 {
-            if let Some(prev) = self.storage.get_latest_block()? {
-                self.consensus.validate_block(&block, &prev)?;
-            }
-        }
+        // The record must be on disk before the block can reach any peer
+        self.signing_protection.record(
+            &self.validator.address,
+            block.header.block_height,
+            slot,
+            block.hash(),
+        )?;
 
         // Store block with state_root
+        let store_started = Instant::now();
         self.storage.store_block(&block)?;
+        self.compaction
+            .stats()
+            .record_block_stored(store_started.elapsed());
 
         let mut mempool_guard = self.mempool.write().await;
-        mempool_guard.retain(|tx| !pending_txs.iter().any(|ptx| ptx.tx_hash == tx.tx_hash));
+        mempool_guard.retain(|tx| {
+            !block
+                .transactions
+                .iter()
+                .any(|btx| btx.tx_hash == tx.tx_hash)
+        });
         drop(mempool_guard);
 
         self.blocks_produced += 1;
@@ -693,6 +1465,9 @@ impl ValidatorNode {
         info!("   Hash: {}", block.hash());
         info!("   Transactions: {}", block.header.tx_count);
 
+        self.notify_new_block(&block).await;
+        self.update_checkpoints(block.header.block_height).await;
+
         // Broadcast block to P2P network
         if let Some(ref network) = self.network {
             let mut net = network.write().await;
@@ -710,30 +1485,98 @@ impl ValidatorNode {
         Ok(())
     }
 
+    /// Whether to give up producing on `prev_block` in `slot`: the chain moved
+    /// past it, or its tip is another validator's block for the same slot.
+    /// Signing ours anyway would fork the chain.
+    async fn cancel_for_competing_block(&self, prev_block: &Block, slot: u64) -> Result<bool> {
+        let Some(tip) = self.storage.get_latest_block()? else {
+            return Ok(false);
+        };
+        let tip_slot = self
+            .slot_consensus
+            .read()
+            .await
+            .slot_at(tip.header.timestamp);
+        let competing = tip.hash() != prev_block.hash()
+            || (tip.header.block_height > 0
+                && tip_slot == slot
+                && tip.header.producer_address() != Some(self.validator.address));
+        if competing {
+            warn!(
+                "🛑 Cancelling our block for slot {}: block {} ({}) was accepted first",
+                slot,
+                tip.header.block_height,
+                tip.hash()
+            );
+            self.self_validation.record_cancelled();
+        }
+        Ok(competing)
+    }
+
     pub async fn submit_transaction(&mut self, tx: Transaction) -> Result<()> {
         info!(
             "📥 Received transaction: {} → {} ({} QBT)",
             tx.from.to_string()[..16].to_string(),
             tx.to.to_string()[..16].to_string(),
-            tx.amount.value() as f64 / 1e18
+            tx.amount.to_qbt_string()
         );
 
-        tx.validate()?;
+        let next_height = *self.current_height.read().await + 1;
+        if let Err(e) = tx
+            .validate()
+            .and_then(|_| tx.verify_sender_signature_at(&self.config.network, next_height))
+            .and_then(|_| tx.validate_fork_id(&self.config.network, next_height))
+            .and_then(|_| tx.validate_schedule_admission(next_height))
+        {
+            self.mempool_monitor
+                .record(&tx.tx_hash, DropReason::Invalid, e.to_string());
+            return Err(e);
+        }
 
         let state = self.state.read().await;
-        let balance = state.get_balance(&tx.from);
+        if let Err(e) = state.check_not_paused(&tx) {
+            self.mempool_monitor
+                .record(&tx.tx_hash, DropReason::Paused, e.to_string());
+            return Err(e);
+        }
+
+        // Claims pay their fee out of the rewards being claimed
+        let balance = if tx.payload == TransactionPayload::ClaimRewards {
+            state.get_unclaimed_rewards(&tx.from)
+        } else {
+            state.get_balance(&tx.from)
+        };
         drop(state);
 
-        let required = Amount::new(tx.amount.value() + tx.fee.value());
+        let required = tx.amount.checked_add(tx.fee).ok_or_else(|| {
+            spirachain_core::SpiraChainError::InvalidAmount("amount plus fee overflows".to_string())
+        })?;
         if balance < required {
+            self.mempool_monitor.record(
+                &tx.tx_hash,
+                DropReason::InsufficientBalance,
+                format!("balance {} QBT", balance.to_qbt_string()),
+            );
             return Err(spirachain_core::SpiraChainError::InsufficientBalance);
         }
 
-        let mut mempool_guard = self.mempool.write().await;
-        mempool_guard.push(tx);
-        drop(mempool_guard);
+        if let Err(e) = self
+            .runtime
+            .resource_guard()
+            .admit_transaction(tx.serialize().len())
+        {
+            self.mempool_monitor
+                .record(&tx.tx_hash, DropReason::OverQuota, e.to_string());
+            return Err(e);
+        }
 
-        Ok(())
+        let mut mempool_guard = self.mempool.write().await;
+        admit_transaction(
+            &mut mempool_guard,
+            tx,
+            self.runtime.max_mempool_size(),
+            &self.mempool_monitor,
+        )
     }
 
     async fn handle_network_event(&mut self, event: NetworkEvent) {
@@ -743,6 +1586,7 @@ impl ValidatorNode {
             }
             NetworkEvent::PeerDisconnected(peer) => {
                 info!("👋 Peer disconnected: {}", peer);
+                self.state_drift.forget_peer(&peer);
             }
             NetworkEvent::ValidatorAnnouncement(validator_addr) => {
                 // A peer announced itself as a validator
@@ -750,10 +1594,16 @@ impl ValidatorNode {
                 
                 // Add to slot consensus if not already present
                 let mut slot_consensus = self.slot_consensus.write().await;
-                slot_consensus.add_validator(validator_addr);
+                let joined = slot_consensus.add_validator(validator_addr);
                 
                 let total_validators = slot_consensus.validator_count();
                 info!("   Total validators in network: {}", total_validators);
+                drop(slot_consensus);
+
+                if joined {
+                    let height = *self.current_height.read().await;
+                    self.record_validator_change(validator_addr, ValidatorChange::Joined, height);
+                }
             }
             NetworkEvent::PeerHeight { peer, height } => {
                 debug!("📊 Peer {} has height: {}", peer, height);
@@ -766,358 +1616,1191 @@ impl ValidatorNode {
                     );
                 }
             }
-            NetworkEvent::NewBlock(block) => {
-                let height = block.header.block_height;
-                let current_height = *self.current_height.read().await;
-
-                info!(
-                    "📦 Received new block {} from network (current: {})",
-                    height, current_height
-                );
-
-                // AUTO-DISCOVERY: Extract validator address from block and add to slot consensus
-                debug!("🔍 Block validator_pubkey length: {}", block.header.validator_pubkey.len());
-                if !block.header.validator_pubkey.is_empty() {
-                    match PublicKey::from_bytes(&block.header.validator_pubkey) {
-                        Ok(pubkey) => {
-                            let validator_address = pubkey.to_address();
-                            debug!("🔍 Extracted validator address: {}", validator_address);
-
-                            // Add to slot consensus if not already registered
-                            let mut slot_consensus = self.slot_consensus.write().await;
-                            let before_count = slot_consensus.validator_count();
-                            slot_consensus.add_validator(validator_address);
-                            let after_count = slot_consensus.validator_count();
-
-                            if after_count > before_count {
-                                warn!(
-                                    "📝 Discovered new validator: {} (total: {})",
-                                    validator_address, after_count
-                                );
-                            } else {
-                                debug!("Validator already known: {}", validator_address);
-                            }
-                            drop(slot_consensus);
-                        }
-                        Err(e) => {
-                            warn!("Failed to extract validator address from block: {}", e);
-                        }
+            NetworkEvent::Checkpoint { height, block_hash } => {
+                // Only a checkpoint on our own chain is adopted; anything else
+                // is either ahead of us or from a fork we will never join
+                match self.storage.get_block_by_height(height) {
+                    Ok(Some(block)) if block.hash() == block_hash => {
+                        add_checkpoint(
+                            &self.storage,
+                            &self.checkpoints,
+                            Checkpoint::new(height, block_hash),
+                        );
                     }
-                } else {
-                    warn!("⚠️  Block {} has empty validator_pubkey!", height);
+                    Ok(Some(block)) => {
+                        warn!(
+                            "⚠️  Peer checkpoint {} at height {} conflicts with our block {}",
+                            block_hash,
+                            height,
+                            block.hash()
+                        );
+                    }
+                    _ => debug!(
+                        "Ignoring checkpoint at height {} we do not have yet",
+                        height
+                    ),
                 }
-
-                // Skip if we already have this block
-                // EXCEPT: Allow genesis (height 0) if we don't have any blocks yet
-                let has_genesis = self.storage.get_latest_block().ok().flatten().is_some();
-                if height <= current_height && !(height == 0 && !has_genesis) {
-                    debug!(
-                        "⊘ Skipping block {} - we already have it (current: {})",
-                        height, current_height
+            }
+            NetworkEvent::NewBlock(block) => {
+                self.process_received_block(block).await;
+            }
+            NetworkEvent::NewCompactBlock { peer, block } => {
+                self.handle_compact_block(peer, block).await;
+            }
+            NetworkEvent::BlockRevoked {
+                peer,
+                height,
+                block_hash,
+            } => {
+                self.fast_relay.stats().record_revocation_received();
+                // Only the peer that sent us the block can withdraw it
+                if self
+                    .pending_compact_blocks
+                    .get(&block_hash)
+                    .is_some_and(|(_, from, _)| *from == peer)
+                {
+                    self.pending_compact_blocks.remove(&block_hash);
+                    warn!(
+                        "↩️  Dropped block {} ({}): revoked by {}",
+                        height, block_hash, peer
                     );
-                    return;
+                } else {
+                    debug!("Ignoring revocation of block {} from {}", height, peer);
                 }
-
-                // Reject blocks that are too far ahead (we need sequential blocks for sync)
-                // EXCEPT: Allow genesis (height 0) if we don't have any blocks yet
-                if height > current_height + 1 && !(height == 0 && !has_genesis) {
-                    warn!(
-                        "⚠️  Rejecting out-of-order block {} - we are at {} (missing blocks in between)",
-                        height, current_height
-                    );
-                    warn!("   Requesting missing blocks from peers...");
-                    
-                    // Request missing blocks
-                    if let Some(ref network) = self.network {
-                        let mut net = network.write().await;
-                        // The height announcement will trigger block requests automatically
-                        net.set_local_height(current_height);
-                    }
+            }
+            NetworkEvent::StateRootRequested { peer, nonce } => {
+                let Some(tip) = self.state_drift.latest() else {
                     return;
+                };
+                if let Some(ref network) = self.network {
+                    network.write().await.send_state_root(
+                        peer,
+                        nonce,
+                        tip.height,
+                        &tip.block_hash,
+                        &tip.state_root,
+                    );
                 }
-
-                // Basic validation
-                if let Err(e) = block.validate() {
-                    warn!("❌ Invalid block {} from network: {}", height, e);
+            }
+            NetworkEvent::StateRoot {
+                peer,
+                nonce,
+                height,
+                block_hash,
+                state_root,
+            } => {
+                let theirs = StateRootSample {
+                    height,
+                    block_hash,
+                    state_root,
+                };
+                let verdict = self.state_drift.check_report(peer, nonce, theirs);
+                self.report_state_drift(peer, verdict, theirs);
+            }
+            NetworkEvent::BlockTransactions(response) => {
+                let Some((mut partial, peer, _)) =
+                    self.pending_compact_blocks.remove(&response.block_hash)
+                else {
                     return;
-                }
+                };
 
-                // FORK DETECTION: Check if this block connects to our chain
-                let is_fork = if height > 0 {
-                    if let Ok(Some(our_block)) = self.storage.get_block_by_height(height - 1) {
-                        // Check if prev_hash matches
-                        block.header.previous_block_hash != our_block.hash()
-                    } else {
-                        // We don't have the previous block, assume not a fork yet
-                        false
+                partial.fill(response.transactions);
+                self.complete_compact_block(partial, peer).await;
+            }
+            NetworkEvent::BlockTransactionsRequested {
+                peer,
+                block_hash,
+                indexes,
+            } => {
+                let block = match self.storage.get_block(&block_hash) {
+                    Ok(Some(block)) => block,
+                    _ => {
+                        debug!("Peer {} asked for txs of unknown block {}", peer, block_hash);
+                        return;
                     }
-                } else {
-                    false
                 };
 
-                if is_fork {
-                    warn!("⚠️  FORK DETECTED at height {}!", height);
-                    warn!(
-                        "   Our prev block hash: {:?}",
-                        self.storage
-                            .get_block_by_height(height - 1)
-                            .ok()
-                            .flatten()
-                            .map(|b| b.hash())
-                    );
-                    warn!("   Their prev hash: {:?}", block.header.previous_block_hash);
+                let response = BlockTransactions::from_block(&block, &indexes);
+                if let Some(ref network) = self.network {
+                    let mut net = network.write().await;
+                    if let Err(e) = net.send_block_transactions(&response).await {
+                        warn!("Failed to send block transactions to {}: {}", peer, e);
+                    }
+                }
+            }
+            NetworkEvent::NewTransaction(tx) => {
+                debug!("📨 Received new transaction from network");
 
-                    // Check if incoming chain is longer (we only have current_height, they have height)
-                    if height > current_height {
-                        warn!(
-                            "🔄 Incoming chain is longer ({} vs {}). SWITCHING TO LONGEST CHAIN!",
-                            height, current_height
-                        );
+                let next_height = *self.current_height.read().await + 1;
+                let mut mempool = self.mempool.write().await;
+                if let Err(e) = admit_network_transaction(
+                    &mut mempool,
+                    tx,
+                    &self.config.network,
+                    next_height,
+                    self.runtime.max_mempool_size(),
+                    &self.runtime.resource_guard(),
+                    &self.mempool_monitor,
+                ) {
+                    debug!("Dropping transaction from network: {}", e);
+                }
+            }
+            NetworkEvent::BlockRequested(start_height) => {
+                // This is actually a range request from GET_BLOCKS:start-end
+                // We'll send multiple blocks
+                info!("📤 Peer requested blocks starting at {}", start_height);
 
-                        // Find common ancestor by going backwards
-                        let mut common_height = height - 1;
-                        while common_height > 0 {
-                            if let Ok(Some(_our_block)) =
-                                self.storage.get_block_by_height(common_height)
-                            {
-                                // We have this block, this is our common ancestor
-                                info!("✅ Found common ancestor at height {}", common_height);
+                // Send up to 50 blocks
+                let mut blocks_sent = 0;
+                for h in start_height..=(start_height + 50) {
+                    // Stored bytes go out as they are, without decoding the block
+                    if let Ok(Some(bytes)) = self.storage.get_block_bytes_by_height(h) {
+                        // Send it via network
+                        if let Some(ref network) = self.network {
+                            let mut net = network.write().await;
+                            if let Err(e) = net.send_block_bytes(h, &bytes).await {
+                                warn!("Failed to send block {}: {}", h, e);
                                 break;
+                            } else {
+                                blocks_sent += 1;
+                                debug!("📤 Sent block {}", h);
                             }
-                            common_height -= 1;
                         }
+                    } else {
+                        // No more blocks available
+                        break;
+                    }
 
-                        // Rollback: Delete our blocks from common_height+1 to current_height
-                        if common_height < current_height {
-                            warn!(
-                                "🔄 Rolling back blocks {} to {}",
-                                common_height + 1,
-                                current_height
-                            );
-                            // Note: We don't have a delete_block method yet, so we'll rebuild WorldState from scratch
-                        }
+                    // Small delay to avoid flooding
+                    tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+                }
 
-                        // Rebuild WorldState from genesis
-                        warn!(
-                            "🔄 Rebuilding WorldState from genesis (replaying {} blocks)...",
-                            common_height
-                        );
-                        let mut state = self.state.write().await;
-                        *state = WorldState::new(); // Reset to genesis
-
-                        // Credit initial testnet stake to our validator (1000 QBT)
-                        if self.config.network == "testnet" {
-                            let initial_stake = Amount::new(1000 * 1_000_000_000_000_000_000);
-                            state.credit_balance(&self.validator.address, initial_stake);
-                            warn!("💰 Credited initial 1000 QBT stake to our validator");
-                        }
+                if blocks_sent > 0 {
+                    info!("✅ Sent {} blocks to peer", blocks_sent);
+                }
+            }
+        }
+    }
 
-                        // Track all addresses that receive transactions (other validators)
-                        let mut all_addresses = std::collections::HashSet::new();
-                        all_addresses.insert(self.validator.address);
-
-                        // Replay all blocks from 0 to common_height
-                        for h in 0..=common_height {
-                            if let Ok(Some(old_block)) = self.storage.get_block_by_height(h) {
-                                // Skip genesis block (height 0) - already processed its allocations
-                                if h == 0 {
-                                    // Genesis allocations
-                                    for tx in &old_block.transactions {
-                                        all_addresses.insert(tx.to);
-                                        state.credit_balance(&tx.to, tx.amount);
-                                    }
-                                } else {
-                                    // Regular blocks: Apply transactions
-                                    for tx in &old_block.transactions {
-                                        all_addresses.insert(tx.from);
-                                        all_addresses.insert(tx.to);
-
-                                        if let Err(e) = state.transfer(&tx.from, &tx.to, tx.amount) {
-                                            debug!("Replay tx in block {}: {}", h, e);
-                                        } else {
-                                            state.increment_nonce(&tx.from);
-                                        }
-                                    }
-                                    
-                                    // CRITICAL: Also credit block reward to the validator who produced it
-                                    if !old_block.header.validator_pubkey.is_empty() {
-                                        if let Ok(pubkey) = spirachain_crypto::PublicKey::from_bytes(&old_block.header.validator_pubkey) {
-                                            let validator_address = pubkey.to_address();
-                                            all_addresses.insert(validator_address);
-                                            let block_reward = Amount::new(spirachain_core::INITIAL_BLOCK_REWARD);
-                                            state.credit_balance(&validator_address, block_reward);
-                                            debug!("💰 Replayed block {} reward to validator {}", h, validator_address);
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    /// Rebuild a compact block from the mempool, asking the sender for anything missing
+    async fn handle_compact_block(&mut self, peer: PeerId, compact: CompactBlock) {
+        let block_hash = compact.hash();
+        if self.pending_compact_blocks.contains_key(&block_hash)
+            || compact.header.block_height <= *self.current_height.read().await
+        {
+            return;
+        }
 
-                        // Now we need to load the CORRECT balances from the NEW chain's storage
-                        // For all addresses we've seen in transactions
-                        for address in all_addresses {
-                            if let Ok(stored_balance) = self.storage.get_balance(&address) {
-                                if !stored_balance.is_zero() {
-                                    state.set_balance(address, stored_balance);
-                                    debug!("💰 Loaded balance for address {:?}", address);
-                                }
-                            }
-                        }
+        self.fast_relay_compact_block(&compact).await;
 
-                        // Persist all balances to storage
-                        for (address, balance) in state.get_all_balances() {
-                            if let Err(e) = self.storage.set_balance(&address, balance) {
-                                warn!("Failed to persist balance during rollback: {}", e);
-                            }
-                        }
+        let partial = {
+            let mempool = self.mempool.read().await;
+            compact.reconstruct(&mempool)
+        };
+
+        let missing = partial.missing();
+        if missing.is_empty() {
+            self.complete_compact_block(partial, peer).await;
+            return;
+        }
+
+        info!(
+            "🧩 Block {} missing {}/{} transactions, requesting from {}",
+            compact.header.block_height,
+            missing.len(),
+            compact.short_ids.len(),
+            peer
+        );
+        if let Some(ref network) = self.network {
+            network
+                .write()
+                .await
+                .request_block_transactions(peer, &block_hash, &missing);
+        }
+        self.pending_compact_blocks
+            .insert(block_hash, (partial, peer, Instant::now()));
+    }
 
-                        drop(state);
+    /// Pass a compact block on before validating it, when enabled and its
+    /// header checks out
+    async fn fast_relay_compact_block(&mut self, compact: &CompactBlock) {
+        if !self.runtime.fast_block_relay() {
+            return;
+        }
+        let Ok(Some(tip)) = self.storage.get_latest_block() else {
+            return;
+        };
+        let check = {
+            let slot_consensus = self.slot_consensus.read().await;
+            let block_slot = slot_consensus.slot_at(compact.header.timestamp);
+            self.fast_relay.check(
+                &compact.header,
+                (tip.header.block_height, tip.hash()),
+                slot_consensus.get_current_slot(),
+                block_slot,
+                slot_consensus.get_slot_leader(block_slot),
+            )
+        };
+        if let Err(e) = check {
+            debug!("{}", e);
+            return;
+        }
 
-                        // Update current height to common ancestor
-                        *self.current_height.write().await = common_height;
+        let height = compact.header.block_height;
+        if let Some(ref network) = self.network {
+            match network.write().await.relay_compact_block(compact) {
+                Ok(()) => self.fast_relay.record_relayed(compact.hash(), height),
+                Err(e) => debug!("Failed to relay block {}: {}", height, e),
+            }
+        }
+    }
+
+    /// Settle a block relayed before validation, revoking it if it failed
+    async fn settle_fast_relay(&mut self, height: u64, block_hash: Hash, outcome: RelayOutcome) {
+        if self.fast_relay.settle(&block_hash, outcome) {
+            if let Some(ref network) = self.network {
+                network.write().await.revoke_block(height, &block_hash);
+            }
+        }
+        self.fast_relay.prune(*self.current_height.read().await);
+    }
+
+    /// Process a reconstructed block, or fall back to the full block if it doesn't check out
+    async fn complete_compact_block(&mut self, partial: PartialBlock, peer: PeerId) {
+        let height = partial.height();
+
+        if !partial.missing().is_empty() {
+            warn!("Block {} still incomplete after fetching transactions", height);
+        } else if let Some(block) = partial.into_block() {
+            self.process_received_block(block).await;
+            return;
+        } else {
+            warn!("Reconstructed block {} failed merkle check", height);
+        }
+
+        if let Some(ref network) = self.network {
+            network.write().await.request_full_block(peer, height);
+        }
+    }
+
+    /// Validate a block received from the network and append it to the chain
+    async fn process_received_block(&mut self, block: Block) {
+        let height = block.header.block_height;
+        let current_height = *self.current_height.read().await;
+
+        info!(
+            "📦 Received new block {} from network (current: {})",
+            height, current_height
+        );
 
+        // Before the duplicate check: a second block for a height we have is
+        // what an equivocating producer sends
+        if let Some(evidence) = self.misbehavior.observe_header(&block.header) {
+            self.punish_equivocation(evidence);
+        }
+
+        // AUTO-DISCOVERY: Extract validator address from block and add to slot consensus
+        debug!("🔍 Block validator_pubkey length: {}", block.header.validator_pubkey.len());
+        if !block.header.validator_pubkey.is_empty() {
+            match PublicKey::from_bytes(&block.header.validator_pubkey) {
+                Ok(pubkey) => {
+                    let validator_address = pubkey.to_address();
+                    debug!("🔍 Extracted validator address: {}", validator_address);
+
+                    // Add to slot consensus if not already registered
+                    let mut slot_consensus = self.slot_consensus.write().await;
+                    let joined = slot_consensus.add_validator(validator_address);
+                    let after_count = slot_consensus.validator_count();
+                    drop(slot_consensus);
+
+                    if joined {
                         warn!(
-                            "✅ Rollback complete. Now at height {} with correct WorldState",
-                            common_height
+                            "📝 Discovered new validator: {} (total: {})",
+                            validator_address, after_count
                         );
+                        self.record_validator_change(
+                            validator_address,
+                            ValidatorChange::Joined,
+                            height,
+                        );
+                    } else {
+                        debug!("Validator already known: {}", validator_address);
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to extract validator address from block: {}", e);
+                }
+            }
+        } else {
+            warn!("⚠️  Block {} has empty validator_pubkey!", height);
+        }
 
-                        // Announce our new height to peers so they know we rolled back
-                        if let Some(ref network) = self.network {
-                            let mut net = network.write().await;
-                            net.set_local_height(common_height);
-                        }
+        // Skip if we already have this block
+        // EXCEPT: Allow genesis (height 0) if we don't have any blocks yet
+        let has_genesis = self.storage.get_latest_block().ok().flatten().is_some();
+        if height <= current_height && (height != 0 || has_genesis) {
+            debug!(
+                "⊘ Skipping block {} - we already have it (current: {})",
+                height, current_height
+            );
+            return;
+        }
 
-                        // Now we can accept the new block
+        // Reject blocks that are too far ahead (we need sequential blocks for sync)
+        // EXCEPT: Allow genesis (height 0) if we don't have any blocks yet
+        if height > current_height + 1 && (height != 0 || has_genesis) {
+            warn_throttled!(
+                "⚠️  Rejecting out-of-order block {} - we are at {} (missing blocks in between), requesting them from peers",
+                height, current_height
+            );
+            
+            // Request missing blocks
+            if let Some(ref network) = self.network {
+                let mut net = network.write().await;
+                // The height announcement will trigger block requests automatically
+                net.set_local_height(current_height);
+            }
+            return;
+        }
+
+        // Signatures, merkle roots and coinbase are checked by the validation pool;
+        // the verdict comes back through the event loop
+        match &self.validation_pool {
+            Some(pool) => {
+                if let Err(block) = pool.submit(block) {
+                    warn_throttled!(
+                        "⚠️  Validation queue full, dropping block {} (it will be re-requested)",
+                        block.header.block_height
+                    );
+                }
+            }
+            None => {
+                let verdict = validate_received_block(block, &self.config.network);
+                self.handle_block_verdict(verdict).await;
+            }
+        }
+    }
+
+    async fn handle_block_verdict(&mut self, verdict: BlockVerdict) {
+        match verdict {
+            BlockVerdict::Valid(block) => {
+                if let Some(report) = self.dry_run.as_mut() {
+                    report.record_validated_block(true);
+                }
+                let (height, block_hash) = (block.header.block_height, block.hash());
+                self.apply_received_block(*block).await;
+
+                if self.fast_relay.is_pending(&block_hash) {
+                    let outcome = if matches!(self.storage.get_block(&block_hash), Ok(Some(_))) {
+                        Some(RelayOutcome::Accepted)
+                    } else if self.validated_ahead.contains_key(&height) {
+                        None
+                    } else if *self.current_height.read().await >= height {
+                        Some(RelayOutcome::Superseded)
                     } else {
-                        warn!(
-                            "⊘ Our chain is longer or equal. Rejecting fork block {}",
-                            height
-                        );
-                        return;
+                        // Failed a check against our chain state
+                        Some(RelayOutcome::Rejected)
+                    };
+                    if let Some(outcome) = outcome {
+                        self.settle_fast_relay(height, block_hash, outcome).await;
                     }
                 }
 
-                // Accept the block (either no fork, or we rolled back)
-                // Apply transactions to WorldState and verify state_root
-                let mut state = self.state.write().await;
-                
-                if height == 0 {
-                    // Genesis block: Verify it's the OFFICIAL genesis for this network
-                    if !spirachain_core::GenesisConfig::verify_genesis_hash(&block, &self.config.network) {
-                        error!("❌ CRITICAL: Received genesis block with WRONG hash!");
-                        error!("   Expected: {}", spirachain_core::GenesisConfig::expected_genesis_hash(&self.config.network));
-                        error!("   Got:      {}", block.hash());
-                        error!("   This peer is on a different network! Rejecting...");
-                        drop(state);
-                        return;
+                // Children that finished validation before their parent
+                loop {
+                    let current_height = *self.current_height.read().await;
+                    self.validated_ahead.retain(|height, _| *height > current_height);
+                    let Some(block) = self.validated_ahead.remove(&(current_height + 1)) else {
+                        break;
+                    };
+                    self.apply_received_block(block).await;
+                }
+            }
+            BlockVerdict::Invalid {
+                height,
+                block_hash,
+                reason,
+            } => {
+                warn_throttled!("❌ Invalid block {} from network: {}", height, reason);
+                self.settle_fast_relay(height, block_hash, RelayOutcome::Rejected)
+                    .await;
+                if let Some(report) = self.dry_run.as_mut() {
+                    report.record_validated_block(false);
+                }
+            }
+            BlockVerdict::UpgradeRequired { reason, .. } => {
+                if !self.upgrade_required {
+                    error!("⛔ Upgrade required: {}", reason);
+                    error!("   The network activated a hard fork this release does not support.");
+                    error!("   Block production is paused to avoid forking; please upgrade SpiraChain.");
+                }
+                self.upgrade_required = true;
+            }
+        }
+    }
+
+    /// Persist and serve the divergence report of a fork at `block`'s parent,
+    /// and push it to the webhooks
+    fn report_fork(
+        &self,
+        block: &Block,
+        our_height: u64,
+        decision: ForkDecision,
+        common_ancestor: Option<u64>,
+    ) {
+        let fork_height = block.header.block_height.saturating_sub(1);
+        let ours = self.storage.get_header_by_height(fork_height).ok().flatten();
+        let alert = ForkAlertResponse {
+            detected_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            fork_height,
+            our_height,
+            incoming_height: block.header.block_height,
+            our_hash: ours.as_ref().map(|ours| ours.hash().to_string()),
+            their_hash: block.header.previous_block_hash.to_string(),
+            incoming_block_hash: block.hash().to_string(),
+            our_producer: ours
+                .and_then(|ours| ours.producer_address())
+                .map(|address| address.to_string()),
+            incoming_producer: block
+                .header
+                .producer_address()
+                .map(|address| address.to_string()),
+            common_ancestor,
+            decision,
+        };
+
+        let mut payload = serde_json::to_value(&alert).unwrap_or_default();
+        if let Some(fields) = payload.as_object_mut() {
+            fields.insert("event".to_string(), "fork_detected".into());
+        }
+        if let Err(e) = self.fork_alerts.record(alert) {
+            warn!("Failed to persist fork alert: {}", e);
+        }
+        notify_webhooks(self.runtime.webhook_endpoints(), payload);
+    }
+
+    /// Connect a validated block to the chain: fork handling, state application
+    /// and state root check
+    async fn apply_received_block(&mut self, block: Block) {
+        let height = block.header.block_height;
+        let current_height = *self.current_height.read().await;
+
+        // The chain may have moved while the block was being validated
+        let has_genesis = self.storage.get_latest_block().ok().flatten().is_some();
+        if (has_genesis || height != 0) && height <= current_height {
+            debug!("⊘ Block {} was applied while it was being validated", height);
+            return;
+        }
+        if height > current_height + 1 {
+            debug!("⏸️  Holding validated block {} until its parent is applied", height);
+            self.validated_ahead.insert(height, block);
+            return;
+        }
+
+        if let Err(e) = self.checkpoints.read().verify_block(&block) {
+            warn_throttled!("❌ Rejecting block {}: {}", height, e);
+            return;
+        }
+
+        // FORK DETECTION: Check if this block connects to our chain
+        let is_fork = if height > 0 {
+            if let Ok(Some(our_parent)) = self.storage.get_header_by_height(height - 1) {
+                // Check if prev_hash matches
+                block.header.previous_block_hash != our_parent.hash()
+            } else {
+                // We don't have the previous block, assume not a fork yet
+                false
+            }
+        } else {
+            false
+        };
+
+        if is_fork {
+            warn!("⚠️  FORK DETECTED at height {}!", height);
+            warn!(
+                "   Our prev block hash: {:?}",
+                self.storage
+                    .get_header_by_height(height - 1)
+                    .ok()
+                    .flatten()
+                    .map(|header| header.hash())
+            );
+            warn!("   Their prev hash: {:?}", block.header.previous_block_hash);
+
+            // Our block at height - 1 would be replaced
+            if let Err(e) = self.checkpoints.read().check_reorg(height - 1) {
+                warn!("❌ Refusing fork: {}", e);
+                self.report_fork(&block, current_height, ForkDecision::RefusedBelowCheckpoint, None);
+                return;
+            }
+
+            // Check if incoming chain is longer (we only have current_height, they have height)
+            if height > current_height {
+                warn!(
+                    "🔄 Incoming chain is longer ({} vs {}). SWITCHING TO LONGEST CHAIN!",
+                    height, current_height
+                );
+                signature_cache().invalidate();
+
+                // Find common ancestor by going backwards
+                let mut common_height = height - 1;
+                while common_height > 0 {
+                    if let Ok(Some(_our_block)) =
+                        self.storage.get_block_by_height(common_height)
+                    {
+                        // We have this block, this is our common ancestor
+                        info!("✅ Found common ancestor at height {}", common_height);
+                        break;
                     }
-                    
-                    info!("✅ Genesis hash verified - this is the official {} genesis", self.config.network.to_uppercase());
-                    
-                    // Genesis block: Transactions are initial allocations, not transfers
-                    info!("📥 Processing genesis block allocations...");
-                    for tx in &block.transactions {
-                        // Genesis allocations credit to the 'to' address directly (from zero address)
-                        state.credit_balance(&tx.to, tx.amount);
-                        debug!("   Allocated {} to {}", tx.amount.value() as f64 / 1e18, tx.to);
+                    common_height -= 1;
+                }
+
+                // Rollback: Delete our blocks from common_height+1 to current_height
+                if common_height < current_height {
+                    warn!(
+                        "🔄 Rolling back blocks {} to {}",
+                        common_height + 1,
+                        current_height
+                    );
+                    // Note: We don't have a delete_block method yet, so we'll rebuild WorldState from scratch
+                }
+
+                // Rebuild WorldState from genesis
+                warn!(
+                    "🔄 Rebuilding WorldState from genesis (replaying {} blocks)...",
+                    common_height
+                );
+                let mut state = self.state.write().await;
+                *state = WorldState::for_network(&self.config.network); // Reset to genesis
+
+                // Credit initial testnet stake to our validator (1000 QBT)
+                if self.config.network == "testnet" {
+                    let initial_stake = Amount::qbt(1000);
+                    if let Err(e) = state.credit_balance(&self.validator.address, initial_stake) {
+                        warn!("Failed to credit initial stake: {}", e);
                     }
-                    info!("✅ Genesis allocations applied: {} accounts", block.transactions.len());
-                } else {
-                    // Normal block: Apply transactions as transfers
-                    for tx in &block.transactions {
-                        if let Err(e) = state.transfer(&tx.from, &tx.to, tx.amount) {
-                            warn!("Failed to apply transaction in block {}: {}", height, e);
-                            // Continue processing other transactions
+                    warn!("💰 Credited initial 1000 QBT stake to our validator");
+                }
+
+                // Track all addresses that receive transactions (other validators)
+                let mut all_addresses = std::collections::HashSet::new();
+                all_addresses.insert(self.validator.address);
+
+                // Replay all blocks from 0 to common_height
+                for h in 0..=common_height {
+                    if let Ok(Some(old_block)) = self.storage.get_block_by_height(h) {
+                        // Skip genesis block (height 0) - already processed its allocations
+                        if h == 0 {
+                            // Genesis allocations
+                            for tx in &old_block.transactions {
+                                all_addresses.insert(tx.to);
+                                if let Err(e) = state.credit_balance(&tx.to, tx.amount) {
+                                    warn!("Failed to replay genesis allocation: {}", e);
+                                }
+                            }
                         } else {
-                            state.increment_nonce(&tx.from);
+                            // Regular blocks: Apply transactions
+                            for tx in &old_block.transactions {
+                                all_addresses.insert(tx.from);
+                                all_addresses.insert(tx.to);
+                            }
+                            for (tx_hash, e) in state.apply_block(&old_block) {
+                                debug!("Replay tx {} in block {}: {}", tx_hash, h, e);
+                            }
+                            // Block rewards are replayed through each block's coinbase
                         }
                     }
                 }
 
-                // Calculate expected state_root after applying transactions
-                let calculated_state_root = state.calculate_merkle_root();
-                
-                // Verify state_root matches (only for non-genesis blocks)
-                if height > 0 && !block.header.state_root.is_zero() {
-                    if calculated_state_root != block.header.state_root {
-                        warn!("❌ State root mismatch in block {}!", height);
-                        warn!("   Expected: {}", block.header.state_root);
-                        warn!("   Calculated: {}", calculated_state_root);
-                        warn!("   This block has an invalid state! Rejecting...");
-                        drop(state);
-                        return;
-                    } else {
-                        debug!("✅ State root verified for block {}", height);
+                // Now we need to load the CORRECT balances from the NEW chain's storage
+                // For all addresses we've seen in transactions
+                for address in all_addresses {
+                    if let Ok(stored_balance) = self.storage.get_balance(&address) {
+                        if !stored_balance.is_zero() {
+                            state.set_balance(address, stored_balance);
+                            debug!("💰 Loaded balance for address {:?}", address);
+                        }
                     }
                 }
 
-                // Persist all balances
-                for (address, balance) in state.get_all_balances() {
-                    if let Err(e) = self.storage.set_balance(&address, balance) {
-                        warn!("Failed to persist balance for {}: {}", address, e);
-                    }
-                }
-                
-                state.set_height(height);
+                // Persist all accounts to storage
+                persist_accounts(&self.storage, &state);
+
                 drop(state);
 
-                // Store the block after validation
-                if let Err(e) = self.storage.store_block(&block) {
-                    error!("Failed to store block {}: {}", height, e);
-                    return;
+                // Update current height to common ancestor
+                *self.current_height.write().await = common_height;
+
+                warn!(
+                    "✅ Rollback complete. Now at height {} with correct WorldState",
+                    common_height
+                );
+
+                // Announce our new height to peers so they know we rolled back
+                if let Some(ref network) = self.network {
+                    let mut net = network.write().await;
+                    net.set_local_height(common_height);
                 }
 
-                // Update current height
-                *self.current_height.write().await = height;
+                self.report_fork(
+                    &block,
+                    current_height,
+                    ForkDecision::SwitchedChain,
+                    Some(common_height),
+                );
 
-                info!("✅ Block {} accepted and stored", height);
+                // Now we can accept the new block
+            } else {
+                warn!(
+                    "⊘ Our chain is longer or equal. Rejecting fork block {}",
+                    height
+                );
+                self.report_fork(&block, current_height, ForkDecision::KeptOurs, None);
+                return;
             }
-            NetworkEvent::NewTransaction(tx) => {
-                debug!("📨 Received new transaction from network");
+        }
 
-                // Add to mempool if valid
-                if let Err(e) = tx.validate() {
-                    warn!("Invalid transaction from network: {}", e);
+        if let Some(summary) = block.epoch_summary() {
+            match self.storage.compute_epoch_summary(summary.epoch) {
+                Ok(expected) if expected == *summary => {}
+                Ok(_) => {
+                    warn!(
+                        "❌ Rejecting block {}: its epoch {} summary does not match the chain",
+                        height, summary.epoch
+                    );
+                    return;
+                }
+                // A node restored from a snapshot lacks the epoch's blocks and
+                // cannot vouch for the summary, so it does not take it on trust
+                Err(e) => {
+                    warn!(
+                        "❌ Rejecting block {}: cannot check its epoch {} summary: {}",
+                        height, summary.epoch, e
+                    );
                     return;
                 }
+            }
+        }
 
-                let mut mempool = self.mempool.write().await;
-                mempool.push(tx);
+        // Accept the block (either no fork, or we rolled back)
+        // Apply transactions to WorldState and verify state_root
+        let mut state = self.state.write().await;
+        
+        if height == 0 {
+            // Genesis block: Verify it's the OFFICIAL genesis for this network
+            if !self.is_expected_genesis(&block) {
+                error!("❌ CRITICAL: Received genesis block with WRONG hash!");
+                error!("   Expected: {}", self.expected_genesis_hash());
+                error!("   Got:      {}", block.hash());
+                error!("   This peer is on a different network! Rejecting...");
+                drop(state);
+                return;
+            }
+            
+            info!("✅ Genesis hash verified - this is the official {} genesis", self.config.network.to_uppercase());
+            
+            // Genesis block: Transactions are initial allocations, not transfers
+            info!("📥 Processing genesis block allocations...");
+            for tx in &block.transactions {
+                // Genesis allocations credit to the 'to' address directly (from zero address)
+                if let Err(e) = state.credit_balance(&tx.to, tx.amount) {
+                    warn!("Failed to apply genesis allocation to {}: {}", tx.to, e);
+                }
+                debug!("   Allocated {} to {}", tx.amount.to_qbt_string(), tx.to);
+            }
+            info!("✅ Genesis allocations applied: {} accounts", block.transactions.len());
+        } else {
+            if let Err(e) = state.check_block_spiral(&block) {
+                warn_throttled!("❌ Rejecting block {}: {}", height, e);
+                drop(state);
+                return;
             }
-            NetworkEvent::BlockRequested(start_height) => {
-                // This is actually a range request from GET_BLOCKS:start-end
-                // We'll send multiple blocks
-                info!("📤 Peer requested blocks starting at {}", start_height);
 
-                // Send up to 50 blocks
-                let mut blocks_sent = 0;
-                for h in start_height..=(start_height + 50) {
-                    if let Ok(Some(block)) = self.storage.get_block_by_height(h) {
-                        // Send it via network
-                        if let Some(ref network) = self.network {
-                            let mut net = network.write().await;
-                            if let Err(e) = net.send_block(&block).await {
-                                warn!("Failed to send block {}: {}", h, e);
-                                break;
-                            } else {
-                                blocks_sent += 1;
-                                debug!("📤 Sent block {}", h);
-                            }
-                        }
-                    } else {
-                        // No more blocks available
-                        break;
+            // Normal block: Apply transactions as transfers
+            // Failed transactions are skipped, the rest of the block still
+            // applies; a block whose state root does not match is not applied
+            match state.apply_verified_block(&block) {
+                Ok(failures) => {
+                    for (tx_hash, e) in failures {
+                        warn!(
+                            "Failed to apply transaction {} in block {}: {}",
+                            tx_hash, height, e
+                        );
                     }
+                }
+                Err(e) => {
+                    warn_throttled!("❌ Rejecting block {}: {}", height, e);
+                    drop(state);
+                    self.misbehavior.record_state_root_rejection();
+                    return;
+                }
+            }
+        }
 
-                    // Small delay to avoid flooding
-                    tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        // Persist all accounts
+        persist_accounts(&self.storage, &state);
+        
+        state.set_height(height);
+        drop(state);
+
+        // Store the block after validation
+        if let Err(e) = self.storage.store_block(&block) {
+            error!("Failed to store block {}: {}", height, e);
+            return;
+        }
+
+        // Keep its continuity proof for archive re-validation and light
+        // clients; received blocks are not held to the rule here
+        if height > 0 {
+            if let Ok(Some(parent)) = self.storage.get_block_by_height(height - 1) {
+                if let Err(e) = self.consensus.continuity_proof(&block, &parent) {
+                    debug!("No continuity proof for block {}: {}", height, e);
+                }
+                let slot_consensus = self.slot_consensus.read().await;
+                for (slot, leader) in self.misbehavior.record_missed_slots(
+                    &slot_consensus,
+                    &parent.header,
+                    &block.header,
+                ) {
+                    debug!("⏭️  Slot {} of {} passed without a block", slot, leader);
                 }
+            }
+        }
 
-                if blocks_sent > 0 {
-                    info!("✅ Sent {} blocks to peer", blocks_sent);
+        // Update current height
+        *self.current_height.write().await = height;
+
+        info!("✅ Block {} accepted and stored", height);
+        self.notify_new_block(&block).await;
+        self.update_checkpoints(height).await;
+    }
+
+    /// Refresh sync progress for `/sync/status`, block propagation for
+    /// `/network/propagation` and gossip compression for `/network/compression`,
+    /// and log one line while more than a block behind the best peer
+    async fn report_sync_progress(&mut self) {
+        let Some(ref network) = self.network else {
+            return;
+        };
+        let (target_height, peers, propagation, compression) = {
+            let net = network.read().await;
+            let best_peer = net.get_peer_heights().values().copied().max();
+            (
+                best_peer.unwrap_or(0),
+                net.peer_count(),
+                net.propagation_stats(),
+                net.compression_stats(),
+            )
+        };
+        *self.compression_stats.write().await = CompressionStatsResponse {
+            configured: self.runtime.p2p_compression().to_string(),
+            codecs: compression
+                .codecs
+                .iter()
+                .map(|(codec, stats)| CodecStatsResponse {
+                    codec: codec.to_string(),
+                    messages_sent: stats.messages_sent,
+                    raw_bytes: stats.raw_bytes,
+                    wire_bytes: stats.wire_bytes,
+                    ratio: stats.ratio(),
+                    compress_micros: stats.compress_micros,
+                    messages_received: stats.messages_received,
+                    decompress_micros: stats.decompress_micros,
+                })
+                .collect(),
+            fallbacks: compression.fallbacks,
+            relayed_uncompressed: compression.relayed_uncompressed,
+        };
+        *self.propagation_stats.write().await = PropagationStatsResponse {
+            samples: propagation.samples,
+            total: propagation.total,
+            p50_ms: propagation.p50_ms,
+            p90_ms: propagation.p90_ms,
+            p99_ms: propagation.p99_ms,
+            max_ms: propagation.max_ms,
+            relayed_ratio: propagation.relayed_ratio,
+        };
+        let current_height = *self.current_height.read().await;
+
+        let was_behind = self.sync_stats.target_height() > self.sync_stats.current_height() + 1;
+        self.sync_stats.update(current_height, target_height, peers);
+        if self.sync_stats.target_height() > current_height + 1 {
+            info!("🔄 {}", self.sync_stats.status_line());
+        } else if was_behind {
+            info!("✅ Caught up with peers at height {}", current_height);
+        }
+
+        let stats = &self.sync_stats;
+        *self.sync_status.write().await = SyncStatusResponse {
+            syncing: stats.is_syncing(),
+            current_height: stats.current_height(),
+            target_height: stats.target_height(),
+            progress_percent: stats.progress_percent(),
+            eta_seconds: stats.eta().map(|eta| eta.as_secs()),
+            peers: stats.peers(),
+            blocks_per_second: stats.blocks_per_second(),
+        };
+    }
+
+    /// Aggregate applied blocks into per-epoch semantic analytics on a blocking
+    /// thread; a run still in progress is left to finish
+    fn update_semantic_analytics(&mut self) {
+        if self
+            .analytics_job
+            .as_ref()
+            .is_some_and(|job| !job.is_finished())
+        {
+            return;
+        }
+        if self
+            .runtime
+            .resource_guard()
+            .is_exceeded(Resource::Database)
+        {
+            debug!("📊 Semantic analytics paused: database over its quota");
+            return;
+        }
+
+        let storage = Arc::clone(&self.storage);
+        self.analytics_job = Some(tokio::task::spawn_blocking(move || {
+            match storage
+                .get_chain_height()
+                .and_then(|height| update_epoch_semantics(&storage, height))
+            {
+                Ok(epochs) => debug!("📊 Semantic analytics updated for {} epoch(s)", epochs),
+                Err(e) => warn!("Semantic analytics failed: {}", e),
+            }
+        }));
+    }
+
+    /// Send anonymous node stats when `telemetry_endpoint` is set. Read on
+    /// every tick so a config reload switches reporting on or off.
+    async fn report_telemetry(&mut self) {
+        let Some(endpoint) = self.runtime.telemetry_endpoint() else {
+            return;
+        };
+        let node_id = match self.telemetry_id.clone() {
+            Some(id) => id,
+            None => match load_or_create_telemetry_id(&self.config.data_dir) {
+                Ok(id) => self.telemetry_id.insert(id).clone(),
+                Err(e) => {
+                    warn!("Telemetry disabled, no installation id: {}", e);
+                    return;
                 }
+            },
+        };
+
+        let report = TelemetryReport::new(
+            &node_id,
+            &self.config.network,
+            *self.current_height.read().await,
+            *self.connected_peers.read().await,
+            self.started_at.elapsed(),
+        );
+        send_telemetry(endpoint, report);
+    }
+
+    /// Measure the database, mempool and vector store against their quotas.
+    /// Newly exceeded quotas raise an alert; the vector store is pruned back
+    /// under its quota, while the others only stop new transactions.
+    /// Ask a few random peers for their state root
+    async fn check_state_drift(&self) {
+        let Some(ref network) = self.network else {
+            return;
+        };
+        let mut net = network.write().await;
+        for (peer, nonce) in self.state_drift.start_round(&net.connected_peer_ids()) {
+            net.request_state_root(peer, nonce);
+        }
+    }
+
+    fn report_state_drift(&self, peer: PeerId, verdict: DriftVerdict, theirs: StateRootSample) {
+        let (kind, ours) = match verdict {
+            DriftVerdict::StateDrift { ours } => ("state_drift", ours),
+            DriftVerdict::ChainDivergence { ours } => ("chain_divergence", ours),
+            DriftVerdict::Agrees => {
+                debug!(
+                    "🧮 Peer {} agrees on the state at height {}",
+                    peer, theirs.height
+                );
+                return;
+            }
+            DriftVerdict::Unverifiable | DriftVerdict::Unsolicited => return,
+        };
+
+        error!(
+            "🚨 {} with peer {} at height {}: we have block {} with state root {}, it has {} with {}",
+            kind,
+            peer,
+            theirs.height,
+            ours.block_hash,
+            ours.state_root,
+            theirs.block_hash,
+            theirs.state_root
+        );
+        notify_webhooks(
+            self.runtime.webhook_endpoints(),
+            serde_json::json!({
+                "event": "state_root_divergence",
+                "kind": kind,
+                "peer": peer.to_string(),
+                "height": theirs.height,
+                "our_block_hash": ours.block_hash.to_string(),
+                "our_state_root": ours.state_root.to_string(),
+                "their_block_hash": theirs.block_hash.to_string(),
+                "their_state_root": theirs.state_root.to_string(),
+            }),
+        );
+    }
+
+    async fn check_resources(&mut self) {
+        let guard = self.runtime.resource_guard();
+        let mempool_bytes = self
+            .mempool
+            .read()
+            .await
+            .iter()
+            .map(|tx| tx.serialize().len() as u64)
+            .sum();
+
+        let storage = Arc::clone(&self.storage);
+        let measured = tokio::task::spawn_blocking(move || -> Result<(u64, u64)> {
+            Ok((storage.size_on_disk()?, storage.semantic_vector_bytes()?))
+        })
+        .await;
+
+        let (db_bytes, vector_bytes) = match measured {
+            Ok(Ok(measured)) => measured,
+            Ok(Err(e)) => {
+                warn!("Resource check failed: {}", e);
+                return;
+            }
+            Err(e) => {
+                warn!("Resource check task failed: {}", e);
+                return;
+            }
+        };
+
+        // Pruning down to 90% leaves headroom so the next few blocks don't
+        // cross the quota again; it runs when no slot of ours is near
+        let vector_limit = guard.limits().max_vector_bytes;
+        if vector_limit > 0 && vector_bytes > vector_limit {
+            self.compaction
+                .request_vector_prune(vector_bytes - vector_limit / 10 * 9);
+        } else {
+            self.compaction.request_vector_prune(0);
+        }
+
+        let usage = ResourceUsage {
+            db_bytes,
+            mempool_bytes,
+            vector_bytes,
+        };
+        for resource in guard.update(usage) {
+            let limit = guard.limits().limit(resource);
+            warn!(
+                "🚨 {} over its quota: {} of {} bytes",
+                resource,
+                usage.used(resource),
+                limit
+            );
+            notify_webhooks(
+                self.runtime.webhook_endpoints(),
+                serde_json::json!({
+                    "event": "resource_quota_exceeded",
+                    "resource": resource,
+                    "usage": usage.used(resource),
+                    "limit": limit,
+                }),
+            );
+        }
+    }
+
+    /// Start queued compaction work in the background if none of our leader
+    /// slots is near, see [`CompactionScheduler`]
+    async fn schedule_compaction(&mut self) {
+        let slot_consensus = self.slot_consensus.read().await;
+        let current_slot = slot_consensus.get_current_slot();
+        let leader_slots =
+            upcoming_leader_slots(&slot_consensus, &self.validator.address, current_slot);
+        let window_slots = match self
+            .compaction
+            .decide(current_slot, &leader_slots, std::time::Instant::now())
+        {
+            CompactionDecision::Idle => return,
+            CompactionDecision::Defer { leader_slot } => {
+                debug!(
+                    "Compaction deferred: we lead slot {} (now {})",
+                    leader_slot, current_slot
+                );
+                return;
+            }
+            CompactionDecision::Run { window_slots } => window_slots,
+        };
+        let deadline = window_slots.map(|slots| {
+            std::time::Instant::now()
+                + Duration::from_secs(
+                    slot_consensus.time_until_next_slot() + slots * slot_consensus.slot_duration(),
+                )
+        });
+        drop(slot_consensus);
+
+        let Some(job) = self.compaction.start() else {
+            return;
+        };
+        let storage = Arc::clone(&self.storage);
+        let io_bytes_per_sec = self.runtime.compaction_io_bytes_per_sec();
+        tokio::task::spawn_blocking(move || job.run(&storage, deadline, io_bytes_per_sec));
+    }
+
+    /// Hash of the genesis this node follows: the one installed by a testnet
+    /// reset, else the official one for the network
+    fn expected_genesis_hash(&self) -> String {
+        match &self.installed_genesis {
+            Some(config) => config.create_genesis_block().hash().to_string(),
+            None => GenesisConfig::expected_genesis_hash(&self.config.network).to_string(),
+        }
+    }
+
+    fn is_expected_genesis(&self, block: &Block) -> bool {
+        block.hash().to_string() == self.expected_genesis_hash()
+    }
+
+    /// Score the block at `height` with our embedding model only if it is the
+    /// one approved for its epoch
+    async fn update_semantic_scoring(&mut self, height: u64) {
+        let approved = self
+            .state
+            .read()
+            .await
+            .semantic_models()
+            .approved_at(height)
+            .cloned();
+        let scoring = self.local_model.scoring(approved.as_ref());
+        self.consensus.set_semantic_scoring(scoring);
+    }
+
+    /// Summary to include in the block at `height` when it opens an epoch
+    fn epoch_summary_for(&self, height: u64) -> Option<EpochSummary> {
+        if !is_epoch_start(height) {
+            return None;
+        }
+        let epoch = diversity_epoch(height) - 1;
+        match self.storage.compute_epoch_summary(epoch) {
+            Ok(summary) => {
+                info!(
+                    "🧾 Epoch {} summary: {}/{} validators produced, {} QBT in fees",
+                    epoch,
+                    summary.participants().count(),
+                    summary.validators.len(),
+                    summary.total_fees.to_qbt_string()
+                );
+                Some(summary)
+            }
+            Err(e) => {
+                warn!("Leaving out the epoch {} summary: {}", epoch, e);
+                None
+            }
+        }
+    }
+
+    /// The checks a peer receiving `block` on top of `prev_block` runs, with
+    /// `state` as it stands once the block is applied
+    fn self_validate_candidate(
+        &self,
+        block: &Block,
+        prev_block: &Block,
+        state: &WorldState,
+    ) -> std::result::Result<(), (ValidationStage, SpiraChainError)> {
+        // Not signed yet, see `produce_block`
+        validate_unsigned_block(block, &self.config.network)
+            .map_err(|e| (ValidationStage::Stateless, e))?;
+        self.checkpoints
+            .read()
+            .verify_block(block)
+            .map_err(|e| (ValidationStage::Checkpoint, e))?;
+        if let Some(summary) = block.epoch_summary() {
+            let expected = self
+                .storage
+                .compute_epoch_summary(summary.epoch)
+                .map_err(|e| (ValidationStage::EpochSummary, e))?;
+            if expected != *summary {
+                return Err((
+                    ValidationStage::EpochSummary,
+                    SpiraChainError::InvalidBlock(format!(
+                        "epoch {} summary does not match the chain",
+                        summary.epoch
+                    )),
+                ));
             }
         }
+        state
+            .check_block_spiral(block)
+            .map_err(|e| (ValidationStage::Spiral, e))?;
+        // Blocks on top of genesis skip the consensus rules, as they always have
+        if prev_block.header.block_height > 0 {
+            self.consensus
+                .validate_block(block, prev_block)
+                .map_err(|e| (ValidationStage::Consensus, e))?;
+        }
+        Ok(())
+    }
+
+    /// Re-admit the transactions saved by the last shutdown, checked against
+    /// the current chain like any transaction from the network
+    async fn restore_mempool(&self, chain_height: u64) {
+        let saved = match load_mempool(&self.config.data_dir, &self.at_rest) {
+            Ok(saved) => saved,
+            Err(e) => {
+                warn!("Failed to load saved mempool: {}", e);
+                return;
+            }
+        };
+        if saved.is_empty() {
+            return;
+        }
+
+        let total = saved.len();
+        let max_size = self.runtime.max_mempool_size();
+        let mut mempool = self.mempool.write().await;
+        for tx in saved {
+            let admitted = tx
+                .validate()
+                .and_then(|_| tx.verify_sender_signature_at(&self.config.network, chain_height + 1))
+                .and_then(|_| tx.validate_fork_id(&self.config.network, chain_height + 1))
+                .and_then(|_| admit_transaction(&mut mempool, tx, max_size, &self.mempool_monitor));
+            if let Err(e) = admitted {
+                debug!("Dropping saved transaction: {}", e);
+            }
+        }
+        info!(
+            "💾 Restored {}/{} pending transactions from the last shutdown",
+            mempool.len(),
+            total
+        );
+    }
+
+    /// Save pending transactions for the next start; a dry run leaves no trace
+    async fn persist_mempool(&self) {
+        if self.dry_run.is_some() {
+            return;
+        }
+        let mempool = self.mempool.read().await;
+        if mempool.is_empty() {
+            return;
+        }
+        match save_mempool(&self.config.data_dir, &self.at_rest, &mempool) {
+            Ok(saved) if saved < mempool.len() => info!(
+                "💾 Saved {} pending transactions ({} kept in memory only by privacy mode)",
+                saved,
+                mempool.len() - saved
+            ),
+            Ok(saved) => info!("💾 Saved {} pending transactions", saved),
+            Err(e) => error!("Failed to save mempool on shutdown: {}", e),
+        }
     }
 
     async fn check_mempool(&self) {
@@ -1168,3 +2851,178 @@ impl ValidatorNode {
         self.validator.last_block_height
     }
 }
+
+/// Write every account record and its contract code from WorldState to storage
+/// Record and persist a checkpoint; returns whether it was new
+/// SpiraPi bridge latencies, failures and fallback state on `/metrics`
+struct SpiraPiMetrics;
+
+impl spirachain_rpc::server::MetricsSource for SpiraPiMetrics {
+    fn export_prometheus(&self) -> String {
+        spirapi_bridge::bridge_pool().export_prometheus()
+    }
+}
+
+/// Signature cache hit rate on `/metrics`
+struct SignatureCacheMetrics;
+
+impl spirachain_rpc::server::MetricsSource for SignatureCacheMetrics {
+    fn export_prometheus(&self) -> String {
+        signature_cache().export_prometheus()
+    }
+}
+
+/// Throttled warning counts on `/metrics`
+struct WarningThrottleMetrics;
+
+impl spirachain_rpc::server::MetricsSource for WarningThrottleMetrics {
+    fn export_prometheus(&self) -> String {
+        warning_throttle().export_prometheus()
+    }
+}
+
+/// Peers disconnected during the chain handshake on `/metrics`
+struct HandshakeMetrics(Arc<HandshakeStats>);
+
+impl spirachain_rpc::server::MetricsSource for HandshakeMetrics {
+    fn export_prometheus(&self) -> String {
+        self.0.export_prometheus()
+    }
+}
+
+/// Gossip messages accepted and rejected per topic on `/metrics`
+struct GossipMetrics(Arc<GossipValidationStats>);
+
+impl spirachain_rpc::server::MetricsSource for GossipMetrics {
+    fn export_prometheus(&self) -> String {
+        self.0.export_prometheus()
+    }
+}
+
+/// Peer round-trip times and liveness disconnects on `/metrics`
+struct LivenessMetrics(Arc<LivenessStats>);
+
+impl spirachain_rpc::server::MetricsSource for LivenessMetrics {
+    fn export_prometheus(&self) -> String {
+        self.0.export_prometheus()
+    }
+}
+
+/// Gossip messages waiting for peers on `/metrics`
+struct OutboundQueueMetrics(Arc<OutboundQueueStats>);
+
+impl spirachain_rpc::server::MetricsSource for OutboundQueueMetrics {
+    fn export_prometheus(&self) -> String {
+        self.0.export_prometheus()
+    }
+}
+
+/// Connection mix and peer diversity rule outcomes on `/metrics`
+struct PeerDiversityMetrics(Arc<PeerDiversityStats>);
+
+impl spirachain_rpc::server::MetricsSource for PeerDiversityMetrics {
+    fn export_prometheus(&self) -> String {
+        self.0.export_prometheus()
+    }
+}
+
+fn add_checkpoint(
+    storage: &BlockStorage,
+    checkpoints: &SharedCheckpoints,
+    checkpoint: Checkpoint,
+) -> bool {
+    match checkpoints.write().insert(checkpoint) {
+        Ok(true) => {}
+        Ok(false) => return false,
+        Err(e) => {
+            error!("🚨 {}", e);
+            return false;
+        }
+    }
+
+    if let Err(e) = storage.store_checkpoint(&checkpoint) {
+        warn!("Failed to persist checkpoint {}: {}", checkpoint.height, e);
+    }
+    info!(
+        "📍 Checkpoint at height {}: {}",
+        checkpoint.height, checkpoint.block_hash
+    );
+    true
+}
+
+/// Checkpoint the block finalized by a new tip at `tip_height`, if one is due
+fn record_due_checkpoint(
+    storage: &BlockStorage,
+    checkpoints: &SharedCheckpoints,
+    tip_height: u64,
+) -> Option<Checkpoint> {
+    let height = Checkpoint::due_at(tip_height)?;
+    let block = match storage.get_block_by_height(height) {
+        Ok(Some(block)) => block,
+        _ => {
+            warn!("Cannot checkpoint height {}: block not found", height);
+            return None;
+        }
+    };
+
+    let checkpoint = Checkpoint::new(height, block.hash());
+    add_checkpoint(storage, checkpoints, checkpoint).then_some(checkpoint)
+}
+
+fn persist_accounts(storage: &BlockStorage, state: &WorldState) {
+    for (address, account) in state.accounts() {
+        if let (Some(code_hash), Some(code)) = (account.code_hash, state.get_code(address)) {
+            if let Err(e) = storage.store_contract_code(&code_hash, code) {
+                warn!("Failed to persist code for contract {}: {}", address, e);
+            }
+        }
+        if let Err(e) = storage.store_account(address, account) {
+            warn!("Failed to persist account {}: {}", address, e);
+        }
+    }
+    if let Err(e) = storage.store_pause_state(state.pause_state()) {
+        warn!("Failed to persist pause state: {}", e);
+    }
+    if let Err(e) = storage.store_semantic_models(state.semantic_models()) {
+        warn!("Failed to persist semantic models: {}", e);
+    }
+    if let Err(e) = storage.store_spiral_registry(state.spiral_registry()) {
+        warn!("Failed to persist spiral registry: {}", e);
+    }
+    if let Err(e) = storage.store_name_registry(state.name_registry()) {
+        warn!("Failed to persist name registry: {}", e);
+    }
+    if let Err(e) = storage.store_spiral_diversity(state.spiral_diversity()) {
+        warn!("Failed to persist spiral diversity: {}", e);
+    }
+
+    if let Err(e) = storage.flush() {
+        warn!("Failed to flush accounts: {}", e);
+    }
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+            }
+            Err(e) => {
+                warn!("Failed to install SIGTERM handler: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
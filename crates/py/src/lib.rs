@@ -0,0 +1,350 @@
+//! Python bindings, the reverse of `spirapi-bridge`: scripts drive a node
+//! through its RPC, build and sign transactions and manage wallet keys.
+//! Build the wheel with `maturin build --release` in `crates/py`.
+//!
+//! ```python
+//! import spirachain
+//! key = spirachain.KeyPair.load("wallet.json")
+//! node = spirachain.RpcClient("127.0.0.1", 9933)
+//! tx = spirachain.TransactionBuilder(key.address, "0x…", "1.5").with_purpose("data")
+//! node.submit(tx.with_fork_id(node.fork_id()).sign(key))
+//! ```
+
+// pyo3 0.20's `#[new]` expansion trips this lint on current compilers
+#![allow(non_local_definitions)]
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+use spirachain_core::{Address, Amount, Hash, Transaction};
+use spirachain_crypto::{ExportedKey, KeyFormat, KeyKind};
+use std::fmt::Display;
+use std::future::Future;
+
+fn value_error(error: impl Display) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn runtime_error(error: impl Display) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+/// RPC responses become plain dicts and lists
+fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(runtime_error)?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.into())
+}
+
+fn parse_address(address: &str) -> PyResult<Address> {
+    address.parse::<Address>().map_err(value_error)
+}
+
+fn parse_amount(amount: &str) -> PyResult<Amount> {
+    amount.parse::<Amount>().map_err(value_error)
+}
+
+/// Ed25519 wallet key, the same wallet files as `spira wallet`
+#[pyclass(name = "KeyPair")]
+#[derive(Clone)]
+pub struct PyKeyPair {
+    inner: spirachain_crypto::KeyPair,
+}
+
+#[pymethods]
+impl PyKeyPair {
+    #[staticmethod]
+    fn generate() -> Self {
+        Self {
+            inner: spirachain_crypto::KeyPair::generate(),
+        }
+    }
+
+    #[staticmethod]
+    fn from_secret_hex(secret: &str) -> PyResult<Self> {
+        let key = ExportedKey::decode(secret, KeyFormat::Hex, Some(KeyKind::Ed25519Secret))
+            .map_err(value_error)?;
+        Ok(Self {
+            inner: key.to_keypair().map_err(value_error)?,
+        })
+    }
+
+    /// Wallet JSON file written by `spira wallet new`
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        let data = std::fs::read_to_string(path).map_err(runtime_error)?;
+        let wallet: serde_json::Value = serde_json::from_str(&data).map_err(value_error)?;
+        let secret = wallet["secret_key"]
+            .as_str()
+            .ok_or_else(|| value_error("Invalid wallet file"))?;
+        Self::from_secret_hex(secret)
+    }
+
+    /// Secret key in `format` (hex, pem or jwk), as `spira wallet import-key` reads it
+    #[staticmethod]
+    fn import_key(input: &str, format: &str) -> PyResult<Self> {
+        let format = format.parse::<KeyFormat>().map_err(value_error)?;
+        let kind = (format == KeyFormat::Hex).then_some(KeyKind::Ed25519Secret);
+        let key = ExportedKey::decode(input, format, kind).map_err(value_error)?;
+        Ok(Self {
+            inner: key.to_keypair().map_err(value_error)?,
+        })
+    }
+
+    /// Public key, or the secret key with `secret=True`, in hex, pem or jwk
+    #[pyo3(signature = (format = "hex", secret = false))]
+    fn export_key(&self, format: &str, secret: bool) -> PyResult<String> {
+        let format = format.parse::<KeyFormat>().map_err(value_error)?;
+        let key = if secret {
+            ExportedKey::ed25519_secret(&self.inner)
+        } else {
+            ExportedKey::ed25519_public(self.inner.public_key())
+        };
+        Ok(key.encode(format))
+    }
+
+    /// Contents of a wallet file for [`Self::load`]
+    fn to_wallet_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "address": self.address(),
+            "public_key": self.public_key(),
+            "secret_key": hex::encode(self.inner.secret_key().as_bytes()),
+        }))
+        .map_err(runtime_error)
+    }
+
+    #[getter]
+    fn address(&self) -> String {
+        self.inner.to_address().to_string()
+    }
+
+    #[getter]
+    fn public_key(&self) -> String {
+        hex::encode(self.inner.public_key().as_bytes())
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.inner.sign(message)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("KeyPair({})", self.address())
+    }
+}
+
+/// Transfer under construction; the `with_*` methods return the builder for chaining
+#[pyclass(name = "TransactionBuilder")]
+pub struct PyTransactionBuilder {
+    tx: Transaction,
+    fee: Option<Amount>,
+}
+
+#[pymethods]
+impl PyTransactionBuilder {
+    /// `amount` in QBT, e.g. `"1.5"`
+    #[new]
+    fn new(from_address: &str, to: &str, amount: &str) -> PyResult<Self> {
+        Ok(Self {
+            tx: Transaction::new(
+                parse_address(from_address)?,
+                parse_address(to)?,
+                parse_amount(amount)?,
+                Amount::zero(),
+            ),
+            fee: None,
+        })
+    }
+
+    fn with_purpose(mut slf: PyRefMut<'_, Self>, purpose: String) -> PyRefMut<'_, Self> {
+        slf.tx.purpose = purpose;
+        slf
+    }
+
+    /// Fee in QBT. Without one the transaction pays its minimum fee.
+    fn with_fee<'a>(mut slf: PyRefMut<'a, Self>, fee: &str) -> PyResult<PyRefMut<'a, Self>> {
+        slf.fee = Some(parse_amount(fee)?);
+        Ok(slf)
+    }
+
+    /// Fork id from [`PyRpcClient::fork_id`], binding the signature to that chain
+    fn with_fork_id<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        fork_id: &str,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        let bytes = hex::decode(fork_id.trim_start_matches("0x")).map_err(value_error)?;
+        slf.tx.fork_id = Hash::from_slice(&bytes).map_err(value_error)?;
+        Ok(slf)
+    }
+
+    fn min_fee(&self) -> String {
+        self.tx.min_fee().to_qbt_string()
+    }
+
+    /// Hash and sign with `keypair`, which must own the sender address
+    fn sign(&self, keypair: &PyKeyPair) -> PyResult<PySignedTransaction> {
+        let mut tx = self.tx.clone();
+        if keypair.inner.to_address() != tx.from {
+            return Err(value_error("Key does not match the sender address"));
+        }
+
+        tx.fee = self.fee.unwrap_or_else(|| tx.min_fee());
+        tx.compute_hash();
+        keypair.inner.sign_transaction(&mut tx);
+        Ok(PySignedTransaction { tx })
+    }
+}
+
+#[pyclass(name = "SignedTransaction")]
+#[derive(Clone)]
+pub struct PySignedTransaction {
+    tx: Transaction,
+}
+
+#[pymethods]
+impl PySignedTransaction {
+    #[getter]
+    fn hash(&self) -> String {
+        self.tx.tx_hash.to_string()
+    }
+
+    #[getter]
+    fn fee(&self) -> String {
+        self.tx.fee.to_qbt_string()
+    }
+
+    /// `tx_hex` accepted by `/submit_transaction`
+    #[getter]
+    fn raw(&self) -> PyResult<String> {
+        Ok(hex::encode(
+            serde_json::to_vec(&self.tx).map_err(runtime_error)?,
+        ))
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_python(py, &self.tx)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SignedTransaction({})", self.hash())
+    }
+}
+
+/// Blocking client for a node's RPC server. Calls release the GIL while they wait.
+#[pyclass(name = "RpcClient")]
+pub struct PyRpcClient {
+    client: spirachain_rpc::RpcClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl PyRpcClient {
+    fn call<T, F>(&self, py: Python<'_>, request: F) -> PyResult<PyObject>
+    where
+        T: Serialize + Send,
+        F: Future<Output = anyhow::Result<T>> + Send,
+    {
+        let response = py
+            .allow_threads(|| self.runtime.block_on(request))
+            .map_err(runtime_error)?;
+        to_python(py, &response)
+    }
+}
+
+#[pymethods]
+impl PyRpcClient {
+    #[new]
+    #[pyo3(signature = (host = "127.0.0.1", port = 9933))]
+    fn new(host: &str, port: u16) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(runtime_error)?;
+        Ok(Self {
+            client: spirachain_rpc::RpcClient::new(host, port),
+            runtime,
+        })
+    }
+
+    fn status(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.call(py, self.client.get_status())
+    }
+
+    /// Fork id for [`PyTransactionBuilder::with_fork_id`]
+    fn fork_id(&self, py: Python<'_>) -> PyResult<String> {
+        let status = py
+            .allow_threads(|| self.runtime.block_on(self.client.get_status()))
+            .map_err(runtime_error)?;
+        Ok(status.fork_id)
+    }
+
+    fn balance(&self, py: Python<'_>, address: &str) -> PyResult<PyObject> {
+        self.call(py, self.client.get_balance(address))
+    }
+
+    fn rewards(&self, py: Python<'_>, address: &str) -> PyResult<PyObject> {
+        self.call(py, self.client.get_rewards(address))
+    }
+
+    fn block(&self, py: Python<'_>, height: u64) -> PyResult<PyObject> {
+        self.call(py, self.client.get_block(height))
+    }
+
+    fn tx_proof(&self, py: Python<'_>, height: u64, index: usize) -> PyResult<PyObject> {
+        self.call(py, self.client.get_tx_proof(height, index))
+    }
+
+    fn mempool_stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.call(py, self.client.get_mempool_stats())
+    }
+
+    fn chain_limits(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.call(py, self.client.get_chain_limits())
+    }
+
+    fn submit(&self, py: Python<'_>, tx: &PySignedTransaction) -> PyResult<PyObject> {
+        self.call(py, self.client.submit_transaction(&tx.tx))
+    }
+
+    /// Dry-run against the node's latest state without broadcasting
+    fn simulate(&self, py: Python<'_>, tx: &PySignedTransaction) -> PyResult<PyObject> {
+        self.call(py, self.client.simulate_transaction(&tx.tx))
+    }
+
+    fn health_check(&self, py: Python<'_>) -> PyResult<bool> {
+        py.allow_threads(|| self.runtime.block_on(self.client.health_check()))
+            .map_err(runtime_error)
+    }
+}
+
+#[pymodule]
+fn spirachain(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyKeyPair>()?;
+    m.add_class::<PyTransactionBuilder>()?;
+    m.add_class::<PySignedTransaction>()?;
+    m.add_class::<PyRpcClient>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_signs_for_its_sender() {
+        let key = PyKeyPair::from_secret_hex(&"07".repeat(32)).unwrap();
+        let imported = PyKeyPair::import_key(&key.export_key("pem", true).unwrap(), "pem").unwrap();
+        assert_eq!(imported.address(), key.address());
+
+        let to = Address::new([9u8; 32]).to_string();
+        let builder = PyTransactionBuilder::new(&key.address(), &to, "2").unwrap();
+        let signed = builder.sign(&key).unwrap();
+        assert_eq!(signed.tx.fee, signed.tx.min_fee());
+        assert!(spirachain_crypto::PublicKey::verify(
+            key.inner.public_key(),
+            &signed.tx.signing_message(),
+            &signed.tx.signature
+        ));
+        assert!(signed.tx.verify_sender_signature().is_ok());
+
+        let stranger = PyKeyPair::generate();
+        assert!(builder.sign(&stranger).is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::{anyhow, Result};
-use spirachain_core::Transaction;
+use spirachain_core::{IntentType, Transaction};
 use tracing::{error, info};
 
 use crate::types::*;
 
+#[derive(Clone)]
 pub struct RpcClient {
     base_url: String,
     client: reqwest::Client,
@@ -49,6 +50,51 @@ impl RpcClient {
         Ok(result)
     }
 
+    /// Dry-run `tx` against the node's latest state without broadcasting it
+    pub async fn simulate_transaction(
+        &self,
+        tx: &Transaction,
+    ) -> Result<SimulateTransactionResponse> {
+        let req = SubmitTransactionRequest {
+            tx_hex: hex::encode(serde_json::to_vec(tx)?),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/simulate_transaction", self.base_url))
+            .json(&req)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Simulation request failed: {}", error_text));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn estimate_fee(
+        &self,
+        target_blocks: u64,
+        intent: Option<IntentType>,
+    ) -> Result<FeeEstimateResponse> {
+        let mut url = format!(
+            "{}/estimate_fee?target_blocks={}",
+            self.base_url, target_blocks
+        );
+        if let Some(intent) = intent {
+            url.push_str(&format!("&intent={:?}", intent));
+        }
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to estimate fee"));
+        }
+
+        Ok(response.json().await?)
+    }
+
     pub async fn get_status(&self) -> Result<GetStatusResponse> {
         let response = self
             .client
@@ -63,6 +109,238 @@ impl RpcClient {
         Ok(response.json().await?)
     }
 
+    pub async fn sync_status(&self) -> Result<SyncStatusResponse> {
+        let response = self
+            .client
+            .get(format!("{}/sync/status", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get sync status"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Page `page` of the mempool, `MEMPOOL_PAGE_SIZE` transactions per page
+    pub async fn get_mempool_content(&self, page: usize) -> Result<MempoolContentResponse> {
+        let response = self
+            .client
+            .get(format!("{}/mempool/content?page={}", self.base_url, page))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get mempool content"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_mempool_stats(&self) -> Result<MempoolStatsResponse> {
+        let response = self
+            .client
+            .get(format!("{}/mempool/stats", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get mempool stats"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_chain_limits(&self) -> Result<ChainLimitsResponse> {
+        let response = self
+            .client
+            .get(format!("{}/chain/limits", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get chain limits"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_version(&self) -> Result<VersionResponse> {
+        let response = self
+            .client
+            .get(format!("{}/version", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get node version"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// `None` until the chain has a checkpoint
+    pub async fn get_latest_checkpoint(&self) -> Result<Option<CheckpointResponse>> {
+        let response = self
+            .client
+            .get(format!("{}/checkpoint/latest", self.base_url))
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get latest checkpoint"));
+        }
+
+        Ok(Some(response.json().await?))
+    }
+
+    /// Heights in `from..=to` that may hold events for `address` and/or `topic`
+    pub async fn filter_events(
+        &self,
+        from: u64,
+        to: u64,
+        address: Option<&str>,
+        topic: Option<&str>,
+    ) -> Result<EventFilterResponse> {
+        let mut query = vec![("from", from.to_string()), ("to", to.to_string())];
+        if let Some(address) = address {
+            query.push(("address", address.to_string()));
+        }
+        if let Some(topic) = topic {
+            query.push(("topic", topic.to_string()));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/events/filter", self.base_url))
+            .query(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to filter events"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Merkle proof of the transaction at `index` in the block at `height`
+    pub async fn get_tx_proof(&self, height: u64, index: usize) -> Result<TxProofResponse> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/block/{}/proof/{}",
+                self.base_url, height, index
+            ))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get proof for transaction {} of block {}",
+                index,
+                height
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_pause_status(&self) -> Result<PauseStatusResponse> {
+        let response = self
+            .client
+            .get(format!("{}/pause", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get pause status"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_semantic_model(&self) -> Result<SemanticModelStatusResponse> {
+        let response = self
+            .client
+            .get(format!("{}/semantic_model", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get semantic model"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Per-validator spiral diversity for `epoch`, or the current epoch
+    pub async fn get_diversity_stats(&self, epoch: Option<u64>) -> Result<DiversityStatsResponse> {
+        let mut url = format!("{}/explorer/diversity", self.base_url);
+        if let Some(epoch) = epoch {
+            url.push_str(&format!("?epoch={}", epoch));
+        }
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get diversity stats"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Intent, entity and coherence aggregates for `epoch`, or the current epoch
+    pub async fn get_epoch_semantics(&self, epoch: Option<u64>) -> Result<EpochSemanticsResponse> {
+        let mut url = format!("{}/explorer/semantics", self.base_url);
+        if let Some(epoch) = epoch {
+            url.push_str(&format!("?epoch={}", epoch));
+        }
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Failed to get epoch semantics: {}", error_text));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_custom_spirals(&self) -> Result<Vec<CustomSpiralResponse>> {
+        let response = self
+            .client
+            .get(format!("{}/spirals", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get custom spirals"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Address `name` (with or without `.spira`) resolves to; `None` when unregistered
+    pub async fn resolve_name(&self, name: &str) -> Result<Option<NameResponse>> {
+        let response = self
+            .client
+            .get(format!("{}/name/{}", self.base_url, name))
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to resolve name {}", name));
+        }
+
+        Ok(Some(response.json().await?))
+    }
+
     pub async fn get_block(&self, height: u64) -> Result<GetBlockResponse> {
         let response = self
             .client
@@ -91,6 +369,87 @@ impl RpcClient {
         Ok(response.json().await?)
     }
 
+    pub async fn get_rewards(&self, address: &str) -> Result<RewardsResponse> {
+        let response = self
+            .client
+            .get(format!("{}/rewards/{}", self.base_url, address))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get unclaimed rewards"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_validator_info(&self, address: &str) -> Result<ValidatorInfoResponse> {
+        let response = self
+            .client
+            .get(format!("{}/validator/{}", self.base_url, address))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get validator info"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Validator set changes of `from_epoch..=to_epoch`, oldest first
+    pub async fn get_validator_changes(
+        &self,
+        from_epoch: u64,
+        to_epoch: u64,
+    ) -> Result<ValidatorSetHistoryResponse> {
+        let response = self
+            .client
+            .get(format!("{}/validators/changes", self.base_url))
+            .query(&[("from_epoch", from_epoch), ("to_epoch", to_epoch)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get validator set changes"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Leader schedule of `epoch`, the current one when `None`
+    pub async fn get_slot_schedule(&self, epoch: Option<u64>) -> Result<SlotScheduleResponse> {
+        let mut request = self
+            .client
+            .get(format!("{}/validators/schedule", self.base_url));
+        if let Some(epoch) = epoch {
+            request = request.query(&[("epoch", epoch)]);
+        }
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get the leader schedule"));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Up to `limit` fork reports, newest first
+    pub async fn get_alerts(&self, limit: usize) -> Result<Vec<ForkAlertResponse>> {
+        let response = self
+            .client
+            .get(format!("{}/alerts", self.base_url))
+            .query(&[("limit", limit)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get fork alerts"));
+        }
+
+        Ok(response.json().await?)
+    }
+
     pub async fn health_check(&self) -> Result<bool> {
         match self
             .client
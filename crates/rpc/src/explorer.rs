@@ -0,0 +1,411 @@
+use crate::server::BlockchainStorage;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Address, Amount, Block};
+use std::collections::VecDeque;
+use std::ops::Range;
+use tokio::sync::broadcast;
+
+/// Blocks kept in memory for clients resuming from a recent cursor
+pub const EXPLORER_FEED_BLOCKS: usize = 256;
+
+/// Live subscribers further behind than this are dropped and must resume
+const EXPLORER_FEED_CHANNEL: usize = 4096;
+
+/// Position in the explorer feed: block height, then index of the event within
+/// that block (block first, then receipts, state diffs and validator info)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FeedCursor {
+    pub height: u64,
+    pub sequence: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExplorerEvent {
+    Block {
+        hash: String,
+        previous_hash: String,
+        timestamp: u64,
+        validator: String,
+        tx_count: usize,
+        fees: String,
+    },
+    Receipt {
+        tx_hash: String,
+        index: usize,
+        from: String,
+        to: String,
+        amount: String,
+        fee: String,
+        coinbase: bool,
+    },
+    /// Account state after the block, for every address it touched
+    StateDiff {
+        address: String,
+        balance: String,
+        nonce: u64,
+    },
+    Validator {
+        address: String,
+        active_validators: usize,
+    },
+    /// Blocks above `common_height` were replaced; drop anything seen past it
+    Reorg { common_height: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedItem {
+    #[serde(flatten)]
+    pub cursor: FeedCursor,
+    #[serde(flatten)]
+    pub event: ExplorerEvent,
+}
+
+/// Account values the node reports alongside a block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccountChange {
+    pub address: Address,
+    pub balance: Amount,
+    pub nonce: u64,
+}
+
+/// Block and receipt events, the part of the feed that can be rebuilt from storage
+pub fn block_events(block: &Block) -> Vec<ExplorerEvent> {
+    let mut events = Vec::with_capacity(block.transactions.len() + 1);
+    events.push(ExplorerEvent::Block {
+        hash: block.hash().to_string(),
+        previous_hash: block.header.previous_block_hash.to_string(),
+        timestamp: block.header.timestamp,
+        validator: crate::encode_hex(&block.header.validator_pubkey),
+        tx_count: block.transactions.len(),
+        fees: block.total_fees().value().to_string(),
+    });
+
+    for (index, tx) in block.transactions.iter().enumerate() {
+        events.push(ExplorerEvent::Receipt {
+            tx_hash: tx.tx_hash.to_string(),
+            index,
+            from: tx.from.to_string(),
+            to: tx.to.to_string(),
+            amount: tx.amount.value().to_string(),
+            fee: tx.fee.value().to_string(),
+            coinbase: tx.is_coinbase(),
+        });
+    }
+
+    events
+}
+
+fn numbered(height: u64, events: Vec<ExplorerEvent>) -> Vec<FeedItem> {
+    events
+        .into_iter()
+        .enumerate()
+        .map(|(sequence, event)| FeedItem {
+            cursor: FeedCursor {
+                height,
+                sequence: sequence as u32,
+            },
+            event,
+        })
+        .collect()
+}
+
+/// Events of a stored block, without the state diffs and validator info that
+/// only exist while the node applies it
+pub fn stored_block_items(block: &Block) -> Vec<FeedItem> {
+    numbered(block.header.block_height, block_events(block))
+}
+
+/// Heights a client resuming after `cursor` is served from storage: everything
+/// below the oldest buffered block, or up to the tip while the buffer is empty
+pub fn stored_heights(
+    cursor: FeedCursor,
+    oldest_buffered: Option<u64>,
+    chain_height: u64,
+) -> Range<u64> {
+    let until = oldest_buffered.unwrap_or(chain_height + 1);
+    cursor.height..until.max(cursor.height)
+}
+
+/// Items of the stored block at `height` that come after `cursor`, or `None`
+/// once storage has no block there
+pub fn stored_items_after(
+    storage: &dyn BlockchainStorage,
+    height: u64,
+    cursor: FeedCursor,
+) -> spirachain_core::Result<Option<Vec<FeedItem>>> {
+    Ok(storage.get_block_by_height(height)?.map(|block| {
+        stored_block_items(&block)
+            .into_iter()
+            .filter(|item| item.cursor > cursor)
+            .collect()
+    }))
+}
+
+/// Ordered stream of explorer events. Recent blocks are buffered so clients can
+/// resume from a cursor; older history is rebuilt from storage by the server.
+pub struct ExplorerFeed {
+    recent: Mutex<VecDeque<FeedItem>>,
+    max_blocks: usize,
+    sender: broadcast::Sender<FeedItem>,
+}
+
+impl Default for ExplorerFeed {
+    fn default() -> Self {
+        Self::new(EXPLORER_FEED_BLOCKS)
+    }
+}
+
+impl ExplorerFeed {
+    pub fn new(max_blocks: usize) -> Self {
+        let (sender, _) = broadcast::channel(EXPLORER_FEED_CHANNEL);
+        Self {
+            recent: Mutex::new(VecDeque::new()),
+            max_blocks: max_blocks.max(1),
+            sender,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<FeedItem> {
+        self.sender.subscribe()
+    }
+
+    /// Lowest height still held in memory
+    pub fn oldest_height(&self) -> Option<u64> {
+        self.recent.lock().front().map(|item| item.cursor.height)
+    }
+
+    /// Buffered items strictly after `cursor`
+    pub fn items_after(&self, cursor: FeedCursor) -> Vec<FeedItem> {
+        self.recent
+            .lock()
+            .iter()
+            .filter(|item| item.cursor > cursor)
+            .cloned()
+            .collect()
+    }
+
+    /// Publish an applied block. A height at or below the last published one
+    /// means the chain reorganized and a reorg event goes out first.
+    pub fn publish_block(
+        &self,
+        block: &Block,
+        changes: &[AccountChange],
+        validator: Address,
+        active_validators: usize,
+    ) {
+        let height = block.header.block_height;
+        let mut events = block_events(block);
+        events.extend(changes.iter().map(|change| ExplorerEvent::StateDiff {
+            address: change.address.to_string(),
+            balance: change.balance.value().to_string(),
+            nonce: change.nonce,
+        }));
+        events.push(ExplorerEvent::Validator {
+            address: validator.to_string(),
+            active_validators,
+        });
+
+        let items = numbered(height, events);
+        let mut recent = self.recent.lock();
+
+        if recent
+            .back()
+            .is_some_and(|last| last.cursor.height >= height)
+        {
+            recent.retain(|item| item.cursor.height < height);
+            let common_height = height.saturating_sub(1);
+            let _ = self.sender.send(FeedItem {
+                cursor: FeedCursor {
+                    height: common_height,
+                    sequence: u32::MAX,
+                },
+                event: ExplorerEvent::Reorg { common_height },
+            });
+        }
+
+        for item in items {
+            recent.push_back(item.clone());
+            // No subscribers is not an error
+            let _ = self.sender.send(item);
+        }
+
+        // Trim whole blocks so a buffered block is never half missing
+        while let Some(oldest) = recent.front().map(|item| item.cursor.height) {
+            if height - oldest < self.max_blocks as u64 {
+                break;
+            }
+            while recent
+                .front()
+                .is_some_and(|item| item.cursor.height == oldest)
+            {
+                recent.pop_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Hash, Transaction};
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStorage {
+        blocks: HashMap<u64, Block>,
+    }
+
+    impl BlockchainStorage for MemoryStorage {
+        fn get_block_by_height(&self, height: u64) -> spirachain_core::Result<Option<Block>> {
+            Ok(self.blocks.get(&height).cloned())
+        }
+
+        fn get_balance(&self, _address: &Address) -> spirachain_core::Result<Amount> {
+            Ok(Amount::zero())
+        }
+
+        fn get_unclaimed_rewards(&self, _address: &Address) -> spirachain_core::Result<Amount> {
+            Ok(Amount::zero())
+        }
+    }
+
+    fn block(height: u64, transfers: usize) -> Block {
+        let transactions = (0..transfers)
+            .map(|i| {
+                let mut tx = Transaction::new(
+                    Address::new([2u8; 32]),
+                    Address::new([3u8; 32]),
+                    Amount::new(100),
+                    Amount::new(1),
+                );
+                tx.timestamp += i as u64;
+                tx.compute_hash();
+                tx
+            })
+            .collect();
+        Block::new(Hash::new([height as u8; 32]), height).with_transactions(transactions)
+    }
+
+    fn cursor(height: u64, sequence: u32) -> FeedCursor {
+        FeedCursor { height, sequence }
+    }
+
+    fn cursors(items: &[FeedItem]) -> Vec<(u64, u32)> {
+        items
+            .iter()
+            .map(|item| (item.cursor.height, item.cursor.sequence))
+            .collect()
+    }
+
+    fn publish(feed: &ExplorerFeed, block: &Block) {
+        feed.publish_block(block, &[], Address::new([9u8; 32]), 1);
+    }
+
+    #[test]
+    fn test_stored_heights_stop_at_buffer_or_tip() {
+        assert_eq!(stored_heights(cursor(3, u32::MAX), Some(8), 20), 3..8);
+        assert_eq!(stored_heights(cursor(3, u32::MAX), None, 5), 3..6);
+        // Resuming inside the buffer reads nothing from storage
+        assert!(stored_heights(cursor(10, 0), Some(8), 20).is_empty());
+    }
+
+    #[test]
+    fn test_stored_items_resume_mid_block() {
+        let mut storage = MemoryStorage::default();
+        storage.blocks.insert(4, block(4, 2));
+
+        let items = stored_items_after(&storage, 4, cursor(4, 0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cursors(&items), vec![(4, 1), (4, 2)]);
+        assert!(items
+            .iter()
+            .all(|item| matches!(item.event, ExplorerEvent::Receipt { .. })));
+
+        let all = stored_items_after(&storage, 4, cursor(3, u32::MAX))
+            .unwrap()
+            .unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(stored_items_after(&storage, 4, cursor(4, u32::MAX))
+            .unwrap()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_stored_items_missing_block() {
+        let mut storage = MemoryStorage::default();
+        storage.blocks.insert(1, block(1, 0));
+
+        assert_eq!(stored_items_after(&storage, 2, cursor(1, 0)).unwrap(), None);
+
+        // A block without transactions carries no receipts
+        let items = stored_items_after(&storage, 1, cursor(0, u32::MAX))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cursors(&items), vec![(1, 0)]);
+        match &items[0].event {
+            ExplorerEvent::Block { tx_count, fees, .. } => {
+                assert_eq!(*tx_count, 0);
+                assert_eq!(fees, "0");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_items_after_cursor_bounds() {
+        let feed = ExplorerFeed::new(8);
+        assert_eq!(feed.oldest_height(), None);
+        assert!(feed.items_after(cursor(0, 0)).is_empty());
+
+        publish(&feed, &block(1, 1));
+        publish(&feed, &block(2, 0));
+
+        // Block, receipt and validator for 1; block and validator for 2
+        assert_eq!(
+            cursors(&feed.items_after(cursor(0, u32::MAX))),
+            vec![(1, 0), (1, 1), (1, 2), (2, 0), (2, 1)]
+        );
+        assert_eq!(
+            cursors(&feed.items_after(cursor(1, 1))),
+            vec![(1, 2), (2, 0), (2, 1)]
+        );
+        assert!(feed.items_after(cursor(2, 1)).is_empty());
+        assert!(feed.items_after(cursor(2, u32::MAX)).is_empty());
+    }
+
+    #[test]
+    fn test_buffer_trims_whole_blocks() {
+        let feed = ExplorerFeed::new(2);
+        for height in 1..=4 {
+            publish(&feed, &block(height, 1));
+        }
+
+        assert_eq!(feed.oldest_height(), Some(3));
+        let items = feed.items_after(cursor(0, 0));
+        assert_eq!(items.first().map(|item| item.cursor), Some(cursor(3, 0)));
+        assert_eq!(items.len(), 6);
+    }
+
+    #[test]
+    fn test_republished_height_sends_reorg() {
+        let feed = ExplorerFeed::new(8);
+        publish(&feed, &block(1, 0));
+        publish(&feed, &block(2, 0));
+        let mut live = feed.subscribe();
+
+        publish(&feed, &block(2, 1));
+
+        let first = live.try_recv().unwrap();
+        assert_eq!(first.event, ExplorerEvent::Reorg { common_height: 1 });
+        assert_eq!(first.cursor, cursor(1, u32::MAX));
+        // The replaced block is gone from the buffer
+        assert_eq!(
+            cursors(&feed.items_after(cursor(1, u32::MAX))),
+            vec![(2, 0), (2, 1), (2, 2)]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use spirachain_core::{Amount, Block, IntentType, Transaction, BLOCK_TIME_TARGET, MIN_TX_FEE};
+
+use crate::types::{encode_amount, FeeEstimateResponse, IntentFeeEstimate};
+
+/// Recent blocks whose transactions feed the estimate
+pub const FEE_ESTIMATE_BLOCKS: u64 = 50;
+
+/// Furthest inclusion target `/estimate_fee` answers for
+pub const MAX_FEE_TARGET_BLOCKS: u64 = 25;
+
+/// Confidence behind the low, recommended and high fees
+const CONFIDENCE_LEVELS: [f64; 3] = [0.5, 0.8, 0.95];
+
+/// Samples an intent needs before a fee is estimated from its transactions
+/// alone rather than from all of them
+pub const MIN_INTENT_SAMPLES: usize = 10;
+
+/// Intent groups reported, `None` being transactions that declare no intent
+const INTENTS: [Option<IntentType>; 6] = [
+    None,
+    Some(IntentType::Transfer),
+    Some(IntentType::ContractCall),
+    Some(IntentType::DataStorage),
+    Some(IntentType::Governance),
+    Some(IntentType::Social),
+];
+
+/// A transaction seen in a block: what it paid and how many blocks it waited,
+/// counted from its own timestamp to the block's. Packing weighs semantic
+/// coherence besides the fee, so delays are also kept per intent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InclusionSample {
+    fee: u128,
+    delay_blocks: u64,
+    intent: Option<IntentType>,
+}
+
+fn inclusion_samples(blocks: &[Block]) -> Vec<InclusionSample> {
+    let block_time_ms = BLOCK_TIME_TARGET * 1000;
+    blocks
+        .iter()
+        .flat_map(|block| {
+            block
+                .transactions
+                .iter()
+                .filter(|tx| !tx.is_protocol())
+                .map(move |tx| InclusionSample {
+                    fee: tx.fee.value(),
+                    intent: tx.intent.as_ref().map(|intent| intent.intent_type),
+                    delay_blocks: block
+                        .header
+                        .timestamp
+                        .saturating_sub(tx.timestamp)
+                        .div_ceil(block_time_ms)
+                        .max(1),
+                })
+        })
+        .collect()
+}
+
+/// Lowest fee such that transactions paying at least as much were included
+/// within `target_blocks` with at least `confidence`
+fn fee_for_confidence(samples: &[InclusionSample], target_blocks: u64, confidence: f64) -> u128 {
+    let mut by_fee = samples.to_vec();
+    by_fee.sort_by_key(|sample| std::cmp::Reverse(sample.fee));
+
+    let mut fee = MIN_TX_FEE;
+    let mut on_time = 0usize;
+    for (seen, sample) in by_fee.iter().enumerate() {
+        on_time += usize::from(sample.delay_blocks <= target_blocks);
+        if (on_time as f64) < confidence * (seen + 1) as f64 {
+            // The highest fees alone miss the target: pay above all of them
+            if seen == 0 {
+                fee = sample.fee + 1;
+            }
+            break;
+        }
+        fee = sample.fee;
+    }
+    fee.max(MIN_TX_FEE)
+}
+
+/// Fee that outbids enough of the mempool to fit in the next `target_blocks`
+/// blocks, `None` while everything pending fits
+fn mempool_competition(
+    mempool: &[Transaction],
+    target_blocks: u64,
+    block_capacity: usize,
+) -> Option<u128> {
+    let capacity = block_capacity.saturating_mul(target_blocks as usize);
+    if mempool.len() < capacity {
+        return None;
+    }
+    let mut fees: Vec<u128> = mempool.iter().map(|tx| tx.fee.value()).collect();
+    fees.sort_unstable_by_key(|fee| std::cmp::Reverse(*fee));
+    fees.get(capacity.saturating_sub(1)).map(|fee| fee + 1)
+}
+
+fn median_delay(samples: &[InclusionSample]) -> u64 {
+    let mut delays: Vec<u64> = samples.iter().map(|sample| sample.delay_blocks).collect();
+    delays.sort_unstable();
+    delays.get(delays.len() / 2).copied().unwrap_or(0)
+}
+
+/// Recommend a fee for inclusion within `target_blocks`, from what recent
+/// blocks included and how full the mempool is. With an `intent`, from that
+/// intent's transactions once there are [`MIN_INTENT_SAMPLES`] of them. Floors
+/// at the minimum fee; transactions with large semantic fields still owe their
+/// own `min_fee`.
+pub fn estimate_fee(
+    recent_blocks: &[Block],
+    mempool: &[Transaction],
+    target_blocks: u64,
+    block_capacity: usize,
+    intent: Option<IntentType>,
+) -> FeeEstimateResponse {
+    let target_blocks = target_blocks.clamp(1, MAX_FEE_TARGET_BLOCKS);
+    let all_samples = inclusion_samples(recent_blocks);
+    let competition = mempool_competition(mempool, target_blocks, block_capacity).unwrap_or(0);
+    let recommended = |samples: &[InclusionSample]| {
+        fee_for_confidence(samples, target_blocks, CONFIDENCE_LEVELS[1]).max(competition)
+    };
+
+    let by_intent: Vec<(Option<IntentType>, Vec<InclusionSample>)> = INTENTS
+        .iter()
+        .map(|group| {
+            let samples = all_samples.iter().filter(|s| s.intent == *group).copied();
+            (*group, samples.collect::<Vec<_>>())
+        })
+        .filter(|(_, samples)| !samples.is_empty())
+        .collect();
+
+    let intent_samples = intent.and_then(|intent| {
+        by_intent
+            .iter()
+            .find(|(group, samples)| *group == Some(intent) && samples.len() >= MIN_INTENT_SAMPLES)
+            .map(|(_, samples)| samples.as_slice())
+    });
+    let samples = intent_samples.unwrap_or(&all_samples);
+
+    let [low, fee, high] = CONFIDENCE_LEVELS
+        .map(|confidence| fee_for_confidence(samples, target_blocks, confidence).max(competition));
+
+    FeeEstimateResponse {
+        target_blocks,
+        fee: encode_amount(Amount::new(fee)),
+        low: encode_amount(Amount::new(low)),
+        high: encode_amount(Amount::new(high)),
+        confidence: CONFIDENCE_LEVELS[1],
+        samples: samples.len(),
+        pending: mempool.len(),
+        intent,
+        intent_specific: intent_samples.is_some(),
+        by_intent: by_intent
+            .iter()
+            .map(|(group, samples)| IntentFeeEstimate {
+                intent: *group,
+                samples: samples.len(),
+                median_delay_blocks: median_delay(samples),
+                fee: encode_amount(Amount::new(recommended(samples))),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Address, Hash, Intent};
+
+    fn tx(fee: u128, timestamp: u64) -> Transaction {
+        Transaction::new_at(
+            Address::zero(),
+            Address::zero(),
+            Amount::zero(),
+            Amount::new(fee),
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn test_fee_estimate_tracks_inclusion_delay() {
+        let block_time_ms = BLOCK_TIME_TARGET * 1000;
+        let mut block = Block::new(Hash::zero(), 1);
+        block.header.timestamp = 100 * block_time_ms;
+        // High fees got in next block, low fees waited five
+        for fee in [10, 9, 8, 7] {
+            block
+                .transactions
+                .push(tx(fee * MIN_TX_FEE, 99 * block_time_ms));
+        }
+        for fee in [2, 1] {
+            block
+                .transactions
+                .push(tx(fee * MIN_TX_FEE, 95 * block_time_ms));
+        }
+
+        let estimate = estimate_fee(&[block.clone()], &[], 1, 1000, None);
+        assert_eq!(estimate.samples, 6);
+        assert_eq!(estimate.fee, encode_amount(Amount::new(2 * MIN_TX_FEE)));
+        assert_eq!(estimate.high, encode_amount(Amount::new(7 * MIN_TX_FEE)));
+        let patient = estimate_fee(&[block], &[], 5, 1000, None);
+        assert_eq!(patient.high, encode_amount(Amount::new(MIN_TX_FEE)));
+
+        let estimate = estimate_fee(&[], &[], 3, 1000, None);
+        assert_eq!(estimate.fee, encode_amount(Amount::new(MIN_TX_FEE)));
+
+        let mempool: Vec<Transaction> = (1..=4).map(|fee| tx(fee * MIN_TX_FEE, 0)).collect();
+        let crowded = estimate_fee(&[], &mempool, 1, 2, None);
+        assert_eq!(crowded.low, encode_amount(Amount::new(3 * MIN_TX_FEE + 1)));
+    }
+
+    #[test]
+    fn test_fee_estimate_per_intent() {
+        let block_time_ms = BLOCK_TIME_TARGET * 1000;
+        let with_intent = |fee: u128, waited: u64, intent_type| {
+            let mut tx = tx(fee * MIN_TX_FEE, (100 - waited) * block_time_ms);
+            tx.intent = Some(Intent {
+                intent_type,
+                confidence: 0.9,
+            });
+            tx
+        };
+        // Transfers get in at once, data storage waits four blocks at twice the fee
+        let mut block = Block::new(Hash::zero(), 1);
+        block.header.timestamp = 100 * block_time_ms;
+        for _ in 0..MIN_INTENT_SAMPLES {
+            block
+                .transactions
+                .push(with_intent(1, 1, IntentType::Transfer));
+            block
+                .transactions
+                .push(with_intent(2, 4, IntentType::DataStorage));
+        }
+        block
+            .transactions
+            .push(with_intent(3, 1, IntentType::Governance));
+
+        let storage = estimate_fee(
+            &[block.clone()],
+            &[],
+            1,
+            1000,
+            Some(IntentType::DataStorage),
+        );
+        assert!(storage.intent_specific);
+        assert_eq!(storage.samples, MIN_INTENT_SAMPLES);
+        // Nothing stored got in within a block: outbid every storage fee seen
+        assert_eq!(storage.fee, encode_amount(Amount::new(2 * MIN_TX_FEE + 1)));
+        let transfer = estimate_fee(&[block.clone()], &[], 1, 1000, Some(IntentType::Transfer));
+        assert_eq!(transfer.fee, encode_amount(Amount::new(MIN_TX_FEE)));
+
+        // Too few governance samples of its own: all transactions are used
+        let governance = estimate_fee(&[block], &[], 1, 1000, Some(IntentType::Governance));
+        assert!(!governance.intent_specific);
+        assert_eq!(governance.samples, 2 * MIN_INTENT_SAMPLES + 1);
+        let groups: Vec<_> = governance
+            .by_intent
+            .iter()
+            .map(|group| (group.intent, group.samples, group.median_delay_blocks))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (Some(IntentType::Transfer), MIN_INTENT_SAMPLES, 1),
+                (Some(IntentType::DataStorage), MIN_INTENT_SAMPLES, 4),
+                (Some(IntentType::Governance), 1, 1),
+            ]
+        );
+    }
+}
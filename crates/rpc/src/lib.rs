@@ -1,7 +1,21 @@
 pub mod client;
+pub mod explorer;
+pub mod fee_estimate;
+pub mod mempool;
+pub mod rate_limit;
+pub mod resources;
 pub mod server;
+pub mod subscription;
+pub mod tx_input;
 pub mod types;
 
 pub use client::RpcClient;
+pub use explorer::*;
+pub use fee_estimate::*;
+pub use mempool::*;
+pub use rate_limit::RateLimiter;
+pub use resources::*;
 pub use server::RpcServer;
+pub use subscription::*;
+pub use tx_input::*;
 pub use types::*;
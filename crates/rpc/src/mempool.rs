@@ -0,0 +1,327 @@
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{
+    lane_admission, Amount, Hash, Result, SpiraChainError, Transaction, MIN_TX_FEE,
+};
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::types::{encode_amount, FeeBucket, MempoolContentResponse, MempoolTxSummary};
+
+/// Transactions per `/mempool/content` page
+pub const MEMPOOL_PAGE_SIZE: usize = 100;
+
+/// Dropped transactions remembered for `/mempool/stats`
+const RECENT_DROPS: usize = 64;
+
+/// Cosine similarity from which two purpose embeddings say the same thing
+pub const NEAR_DUPLICATE_SIMILARITY: f32 = 0.95;
+
+/// Fee histogram bucket edges, in multiples of the minimum fee
+const FEE_BUCKET_MULTIPLES: [u128; 5] = [1, 2, 5, 10, 100];
+
+/// Why a transaction never entered the mempool or was dropped from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropReason {
+    /// Rejected: failed validation
+    Invalid,
+    /// Rejected: the mempool was at its size limit
+    Full,
+    /// Rejected: the sender cannot pay amount plus fee
+    InsufficientBalance,
+    /// Rejected: the chain is paused and the transaction is not a guardian vote
+    Paused,
+    /// Evicted: signed for a fork the chain has moved past
+    WrongFork,
+    /// Rejected: a pending near-duplicate pays at least as much
+    NearDuplicate,
+    /// Evicted: replaced by a near-duplicate paying a higher fee
+    Superseded,
+    /// Rejected: the node is over a memory or disk quota
+    OverQuota,
+    /// Rejected: its priority lane, or the sender's share of it, is full
+    LaneLimit,
+    /// Evicted: a scheduled transaction missed its inclusion window
+    Expired,
+}
+
+impl DropReason {
+    /// Whether the transaction had been accepted before being dropped
+    pub fn is_eviction(self) -> bool {
+        matches!(
+            self,
+            DropReason::WrongFork | DropReason::Superseded | DropReason::Expired
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolDrop {
+    pub tx_hash: String,
+    pub reason: DropReason,
+    pub detail: String,
+    /// Unix seconds
+    pub at: u64,
+}
+
+/// Rejection and eviction counters, shared by the node and the RPC server so
+/// operators can see why a transaction never made it into a block
+#[derive(Default)]
+pub struct MempoolMonitor {
+    counts: Mutex<BTreeMap<DropReason, u64>>,
+    recent: Mutex<VecDeque<MempoolDrop>>,
+}
+
+impl MempoolMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, tx_hash: &Hash, reason: DropReason, detail: impl Into<String>) {
+        *self.counts.lock().entry(reason).or_default() += 1;
+
+        let mut recent = self.recent.lock();
+        recent.push_back(MempoolDrop {
+            tx_hash: tx_hash.to_string(),
+            reason,
+            detail: detail.into(),
+            at: unix_secs(),
+        });
+        while recent.len() > RECENT_DROPS {
+            recent.pop_front();
+        }
+    }
+
+    /// Totals since start, split into (rejected, evicted)
+    pub fn counters(&self) -> (BTreeMap<DropReason, u64>, BTreeMap<DropReason, u64>) {
+        self.counts
+            .lock()
+            .iter()
+            .partition(|(reason, _)| !reason.is_eviction())
+    }
+
+    /// Most recent first
+    pub fn recent(&self) -> Vec<MempoolDrop> {
+        self.recent.lock().iter().rev().cloned().collect()
+    }
+}
+
+/// Same sender, recipient, amount and payload, with a purpose that means the
+/// same: embeddings at least [`NEAR_DUPLICATE_SIMILARITY`] apart when both carry
+/// one, otherwise the same text up to case and whitespace. Transactions without
+/// a purpose are never near-duplicates, so repeated plain payments still go through.
+pub fn is_near_duplicate(a: &Transaction, b: &Transaction) -> bool {
+    if a.from != b.from || a.to != b.to || a.amount != b.amount || a.payload != b.payload {
+        return false;
+    }
+    if a.purpose.trim().is_empty() && a.semantic_vector.is_empty() {
+        return false;
+    }
+
+    if !a.semantic_vector.is_empty() && !b.semantic_vector.is_empty() {
+        return cosine_similarity(&a.semantic_vector, &b.semantic_vector)
+            >= NEAR_DUPLICATE_SIMILARITY;
+    }
+    normalized_purpose(&a.purpose) == normalized_purpose(&b.purpose)
+}
+
+fn normalized_purpose(purpose: &str) -> String {
+    purpose
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Add `tx` to `mempool`, keeping only the highest-fee instance of near-duplicates:
+/// a cheaper pending one is evicted, otherwise `tx` is rejected with
+/// [`SpiraChainError::NearDuplicate`]. Priority lane transactions are held
+/// beyond `max_size` up to their lane's quota. Drops are recorded in `monitor`.
+pub fn admit_transaction(
+    mempool: &mut Vec<Transaction>,
+    tx: Transaction,
+    max_size: usize,
+    monitor: &MempoolMonitor,
+) -> Result<()> {
+    if let Some(index) = mempool
+        .iter()
+        .position(|pending| is_near_duplicate(pending, &tx))
+    {
+        let pending = &mempool[index];
+        if pending.fee >= tx.fee {
+            let detail = format!("near-duplicate of {}", pending.tx_hash);
+            monitor.record(&tx.tx_hash, DropReason::NearDuplicate, detail);
+            return Err(SpiraChainError::NearDuplicate(pending.tx_hash.to_string()));
+        }
+
+        monitor.record(
+            &pending.tx_hash,
+            DropReason::Superseded,
+            format!("replaced by {}", tx.tx_hash),
+        );
+        mempool[index] = tx;
+        return Ok(());
+    }
+
+    let priority = match lane_admission(mempool.iter(), &tx) {
+        Ok(priority) => priority,
+        Err(e) => {
+            monitor.record(&tx.tx_hash, DropReason::LaneLimit, e.to_string());
+            return Err(e);
+        }
+    };
+    if !priority && mempool.len() >= max_size {
+        monitor.record(&tx.tx_hash, DropReason::Full, "mempool full");
+        return Err(SpiraChainError::MempoolFull);
+    }
+    mempool.push(tx);
+    Ok(())
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// One page of the mempool, in the order the block producer takes transactions
+pub fn mempool_page(mempool: &[Transaction], page: usize) -> MempoolContentResponse {
+    let now_ms = unix_secs() * 1000;
+    let transactions = mempool
+        .iter()
+        .skip(page.saturating_mul(MEMPOOL_PAGE_SIZE))
+        .take(MEMPOOL_PAGE_SIZE)
+        .map(|tx| MempoolTxSummary {
+            hash: tx.tx_hash.to_string(),
+            from: tx.from.to_string(),
+            to: tx.to.to_string(),
+            amount: encode_amount(tx.amount),
+            fee: encode_amount(tx.fee),
+            size: tx.serialize().len(),
+            age_secs: now_ms.saturating_sub(tx.timestamp) / 1000,
+            intent: tx.intent.as_ref().map(|intent| intent.intent_type),
+            purpose: tx.purpose.clone(),
+        })
+        .collect();
+
+    MempoolContentResponse {
+        page,
+        page_size: MEMPOOL_PAGE_SIZE,
+        total: mempool.len(),
+        transactions,
+    }
+}
+
+/// Transaction counts per fee range; the last bucket is open-ended
+pub fn fee_histogram(mempool: &[Transaction]) -> Vec<FeeBucket> {
+    let edges: Vec<u128> = std::iter::once(0)
+        .chain(FEE_BUCKET_MULTIPLES.iter().map(|m| m * MIN_TX_FEE))
+        .collect();
+
+    let mut counts = vec![0usize; edges.len()];
+    for tx in mempool {
+        let bucket = edges.partition_point(|edge| *edge <= tx.fee.value()) - 1;
+        counts[bucket] += 1;
+    }
+
+    edges
+        .iter()
+        .enumerate()
+        .map(|(i, min)| FeeBucket {
+            min_fee: encode_amount(Amount::new(*min)),
+            max_fee: edges.get(i + 1).map(|max| encode_amount(Amount::new(*max))),
+            count: counts[i],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::Address;
+
+    fn tx_with_fee(fee: u128) -> Transaction {
+        Transaction::new(
+            Address::zero(),
+            Address::zero(),
+            Amount::zero(),
+            Amount::new(fee),
+        )
+    }
+
+    #[test]
+    fn test_fee_histogram_buckets() {
+        let mempool = vec![
+            tx_with_fee(MIN_TX_FEE),
+            tx_with_fee(2 * MIN_TX_FEE - 1),
+            tx_with_fee(3 * MIN_TX_FEE),
+            tx_with_fee(1000 * MIN_TX_FEE),
+        ];
+
+        let counts: Vec<usize> = fee_histogram(&mempool)
+            .iter()
+            .map(|bucket| bucket.count)
+            .collect();
+        assert_eq!(counts, vec![0, 2, 1, 0, 0, 1]);
+        assert_eq!(fee_histogram(&mempool).last().unwrap().max_fee, None);
+    }
+
+    #[test]
+    fn test_monitor_splits_rejections_and_evictions() {
+        let monitor = MempoolMonitor::new();
+        monitor.record(&Hash::zero(), DropReason::Full, "mempool full");
+        monitor.record(&Hash::zero(), DropReason::Full, "mempool full");
+        monitor.record(&Hash::zero(), DropReason::WrongFork, "fork moved on");
+
+        let (rejected, evicted) = monitor.counters();
+        assert_eq!(rejected.get(&DropReason::Full), Some(&2));
+        assert_eq!(evicted.get(&DropReason::WrongFork), Some(&1));
+        assert_eq!(monitor.recent()[0].reason, DropReason::WrongFork);
+    }
+
+    #[test]
+    fn test_near_duplicates_keep_highest_fee() {
+        let spam = |fee: u128, purpose: &str| {
+            let mut tx = tx_with_fee(fee).with_purpose(purpose);
+            tx.compute_hash();
+            tx
+        };
+        let monitor = MempoolMonitor::new();
+        let mut mempool = Vec::new();
+
+        admit_transaction(&mut mempool, spam(MIN_TX_FEE, "Buy now"), 10, &monitor).unwrap();
+        let cheaper = spam(MIN_TX_FEE, "buy   NOW");
+        assert!(matches!(
+            admit_transaction(&mut mempool, cheaper, 10, &monitor),
+            Err(SpiraChainError::NearDuplicate(_))
+        ));
+
+        let richer = spam(2 * MIN_TX_FEE, "buy now");
+        admit_transaction(&mut mempool, richer.clone(), 10, &monitor).unwrap();
+        assert_eq!(mempool.len(), 1);
+        assert_eq!(mempool[0].tx_hash, richer.tx_hash);
+
+        admit_transaction(&mut mempool, spam(MIN_TX_FEE, "rent"), 10, &monitor).unwrap();
+        admit_transaction(&mut mempool, tx_with_fee(MIN_TX_FEE), 10, &monitor).unwrap();
+        admit_transaction(&mut mempool, tx_with_fee(MIN_TX_FEE), 10, &monitor).unwrap();
+        assert_eq!(mempool.len(), 4);
+
+        let (rejected, evicted) = monitor.counters();
+        assert_eq!(rejected.get(&DropReason::NearDuplicate), Some(&1));
+        assert_eq!(evicted.get(&DropReason::Superseded), Some(&1));
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Fixed one-minute window per client IP. The limit can be changed at runtime.
+pub struct RateLimiter {
+    limit_per_minute: AtomicU32,
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// `0` disables limiting
+    pub fn new(limit_per_minute: u32) -> Self {
+        Self {
+            limit_per_minute: AtomicU32::new(limit_per_minute),
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit_per_minute.load(Ordering::Relaxed)
+    }
+
+    pub fn set_limit(&self, limit_per_minute: u32) {
+        self.limit_per_minute
+            .store(limit_per_minute, Ordering::Relaxed);
+    }
+
+    /// Count a request from `ip`, returning false once it is over the limit
+    pub fn check(&self, ip: IpAddr) -> bool {
+        let limit = self.limit();
+        if limit == 0 {
+            return true;
+        }
+
+        let mut windows = match self.windows.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let now = Instant::now();
+
+        // Drop stale windows so idle clients don't accumulate
+        if windows.len() > 10_000 {
+            windows.retain(|_, (started, _)| now.duration_since(*started) < WINDOW);
+        }
+
+        let entry = windows.entry(ip).or_insert((now, 0));
+        if now.duration_since(entry.0) >= WINDOW {
+            *entry = (now, 0);
+        }
+
+        entry.1 += 1;
+        entry.1 <= limit
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
@@ -0,0 +1,243 @@
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Result, SpiraChainError};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What the node keeps a byte quota on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Database,
+    Mempool,
+    /// Off-chain semantic vectors kept next to the blocks
+    VectorStore,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 3] = [Resource::Database, Resource::Mempool, Resource::VectorStore];
+}
+
+impl std::fmt::Display for Resource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Resource::Database => write!(f, "database"),
+            Resource::Mempool => write!(f, "mempool"),
+            Resource::VectorStore => write!(f, "vector_store"),
+        }
+    }
+}
+
+/// Byte quotas, 0 = unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    pub max_db_bytes: u64,
+    pub max_mempool_bytes: u64,
+    pub max_vector_bytes: u64,
+}
+
+impl ResourceLimits {
+    pub fn limit(&self, resource: Resource) -> u64 {
+        match resource {
+            Resource::Database => self.max_db_bytes,
+            Resource::Mempool => self.max_mempool_bytes,
+            Resource::VectorStore => self.max_vector_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub db_bytes: u64,
+    pub mempool_bytes: u64,
+    pub vector_bytes: u64,
+}
+
+impl ResourceUsage {
+    pub fn used(&self, resource: Resource) -> u64 {
+        match resource {
+            Resource::Database => self.db_bytes,
+            Resource::Mempool => self.mempool_bytes,
+            Resource::VectorStore => self.vector_bytes,
+        }
+    }
+}
+
+/// Served on `/resources`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceStatusResponse {
+    pub limits: ResourceLimits,
+    pub usage: ResourceUsage,
+    /// Resources currently over their quota
+    pub exceeded: Vec<Resource>,
+    /// Times each quota was crossed since the node started
+    pub breaches: BTreeMap<Resource, u64>,
+}
+
+/// Quota state shared by the node, which measures usage, and the RPC server,
+/// which turns away new transactions while the node is over a quota. Blocks are
+/// never refused: only work that can wait is.
+#[derive(Default)]
+pub struct ResourceGuard {
+    limits: Mutex<ResourceLimits>,
+    usage: Mutex<ResourceUsage>,
+    exceeded: Mutex<BTreeSet<Resource>>,
+    breaches: Mutex<BTreeMap<Resource, u64>>,
+}
+
+impl ResourceGuard {
+    pub fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits: Mutex::new(limits),
+            ..Self::default()
+        }
+    }
+
+    pub fn limits(&self) -> ResourceLimits {
+        *self.limits.lock()
+    }
+
+    pub fn set_limits(&self, limits: ResourceLimits) {
+        *self.limits.lock() = limits;
+        let usage = *self.usage.lock();
+        self.update(usage);
+    }
+
+    /// Record measured usage; returns the resources that just went over quota
+    pub fn update(&self, usage: ResourceUsage) -> Vec<Resource> {
+        let limits = self.limits();
+        *self.usage.lock() = usage;
+
+        let mut exceeded = self.exceeded.lock();
+        let mut crossed = Vec::new();
+        for resource in Resource::ALL {
+            let limit = limits.limit(resource);
+            if limit > 0 && usage.used(resource) > limit {
+                if exceeded.insert(resource) {
+                    *self.breaches.lock().entry(resource).or_default() += 1;
+                    crossed.push(resource);
+                }
+            } else {
+                exceeded.remove(&resource);
+            }
+        }
+        crossed
+    }
+
+    pub fn is_exceeded(&self, resource: Resource) -> bool {
+        self.exceeded.lock().contains(&resource)
+    }
+
+    /// Whether a new transaction of `tx_bytes` may enter the mempool. Admitted
+    /// bytes count until the node next measures the mempool.
+    pub fn admit_transaction(&self, tx_bytes: usize) -> Result<()> {
+        if self.is_exceeded(Resource::Database) {
+            return Err(SpiraChainError::ResourceExhausted(
+                "database is over its size quota".to_string(),
+            ));
+        }
+
+        let limit = self.limits().max_mempool_bytes;
+        let mut usage = self.usage.lock();
+        if limit > 0 && usage.mempool_bytes + tx_bytes as u64 > limit {
+            return Err(SpiraChainError::ResourceExhausted(format!(
+                "mempool memory quota of {} bytes reached",
+                limit
+            )));
+        }
+        usage.mempool_bytes += tx_bytes as u64;
+        Ok(())
+    }
+
+    pub fn status(&self) -> ResourceStatusResponse {
+        ResourceStatusResponse {
+            limits: self.limits(),
+            usage: *self.usage.lock(),
+            exceeded: self.exceeded.lock().iter().copied().collect(),
+            breaches: self.breaches.lock().clone(),
+        }
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        let status = self.status();
+        let mut output = String::new();
+        write_metric(&mut output, "usage_bytes", "gauge", "Bytes in use", |r| {
+            status.usage.used(r)
+        });
+        write_metric(
+            &mut output,
+            "limit_bytes",
+            "gauge",
+            "Quota, 0 = none",
+            |r| status.limits.limit(r),
+        );
+        write_metric(
+            &mut output,
+            "quota_exceeded",
+            "gauge",
+            "1 while over quota",
+            |r| status.exceeded.contains(&r) as u64,
+        );
+        write_metric(
+            &mut output,
+            "quota_breaches_total",
+            "counter",
+            "Quota crossings",
+            |r| status.breaches.get(&r).copied().unwrap_or(0),
+        );
+        output
+    }
+}
+
+fn write_metric(
+    output: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    value: impl Fn(Resource) -> u64,
+) {
+    output.push_str(&format!(
+        "# HELP spirachain_resource_{} {}\n# TYPE spirachain_resource_{} {}\n",
+        name, help, name, kind
+    ));
+    for resource in Resource::ALL {
+        output.push_str(&format!(
+            "spirachain_resource_{}{{resource=\"{}\"}} {}\n",
+            name,
+            resource,
+            value(resource)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_transitions_and_admission() {
+        let guard = ResourceGuard::new(ResourceLimits {
+            max_db_bytes: 1_000,
+            max_mempool_bytes: 100,
+            max_vector_bytes: 0,
+        });
+
+        assert!(guard.admit_transaction(60).is_ok());
+        assert!(guard.admit_transaction(60).is_err());
+
+        let usage = ResourceUsage {
+            db_bytes: 2_000,
+            mempool_bytes: 0,
+            vector_bytes: u64::MAX,
+        };
+        assert_eq!(guard.update(usage), vec![Resource::Database]);
+        assert!(guard.update(usage).is_empty());
+        assert!(guard.admit_transaction(1).is_err());
+
+        guard.update(ResourceUsage::default());
+        assert!(guard.admit_transaction(1).is_ok());
+        assert_eq!(guard.status().breaches.get(&Resource::Database), Some(&1));
+        assert!(guard
+            .export_prometheus()
+            .contains("spirachain_resource_quota_breaches_total{resource=\"database\"} 1"));
+    }
+}
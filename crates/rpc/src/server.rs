@@ -1,22 +1,170 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Query, Request, State,
+    },
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
 use tokio::sync::RwLock;
 use tower_http::cors::CorsLayer;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
 
+use crate::explorer::{
+    stored_heights, stored_items_after, ExplorerEvent, ExplorerFeed, FeedCursor, FeedItem,
+};
+use crate::fee_estimate::{estimate_fee, FEE_ESTIMATE_BLOCKS};
+use crate::mempool::{admit_transaction, fee_histogram, mempool_page, DropReason, MempoolMonitor};
+use crate::rate_limit::RateLimiter;
+use crate::resources::ResourceGuard;
+use crate::tx_input::{describe_field_errors, parse_transaction_input};
 use crate::types::*;
-use spirachain_core::{Address, Amount, Block, Transaction};
+use spirachain_core::{
+    diversity_epoch, event_topic, normalize_name, Account, Address, Amount, Block, ContinuityProof,
+    ErrorCategory, Hash, IntentType, NameRegistry, PauseState, SemanticModel, SemanticModelState,
+    SignedMessage, SpiraChainError, SpiralDiversity, SpiralRegistry, Transaction,
+    ValidatorSetChange, DIVERSITY_EPOCH_BLOCKS, NAME_SUFFIX,
+};
+
+/// Most blocks one `/events/filter` request may scan
+pub const MAX_EVENT_FILTER_RANGE: u64 = 1_000;
+
+/// Most fork reports one `/alerts` request may list
+pub const MAX_ALERTS_PER_REQUEST: usize = 256;
+
+/// Most epochs one `/validators/changes` request may list
+pub const MAX_VALIDATOR_HISTORY_EPOCHS: u64 = 100;
+
+/// Validator set changes a live subscriber may fall behind before it is dropped
+pub const VALIDATOR_CHANGES_CHANNEL: usize = 256;
 
 pub trait BlockchainStorage: Send + Sync {
     fn get_block_by_height(&self, height: u64) -> spirachain_core::Result<Option<Block>>;
     fn get_balance(&self, address: &Address) -> spirachain_core::Result<Amount>;
+    fn get_unclaimed_rewards(&self, address: &Address) -> spirachain_core::Result<Amount>;
+
+    /// Account as of the latest persisted block
+    fn get_account(&self, _address: &Address) -> spirachain_core::Result<Option<Account>> {
+        Ok(None)
+    }
+
+    /// Height and hash of the latest finalized checkpoint
+    fn latest_checkpoint(&self) -> spirachain_core::Result<Option<(u64, Hash)>> {
+        Ok(None)
+    }
+
+    /// Emergency pause as of the latest persisted block
+    fn pause_state(&self) -> spirachain_core::Result<PauseState> {
+        Ok(PauseState::new())
+    }
+
+    /// Embedding model approvals as of the latest persisted block
+    fn semantic_models(&self) -> spirachain_core::Result<SemanticModelState> {
+        Ok(SemanticModelState::new())
+    }
+
+    /// Custom spirals registered as of the latest persisted block
+    fn spiral_registry(&self) -> spirachain_core::Result<SpiralRegistry> {
+        Ok(SpiralRegistry::new())
+    }
+
+    /// Names registered as of the latest persisted block
+    fn name_registry(&self) -> spirachain_core::Result<NameRegistry> {
+        Ok(NameRegistry::new())
+    }
+
+    /// Spiral kinds per validator and epoch
+    fn spiral_diversity(&self) -> spirachain_core::Result<SpiralDiversity> {
+        Ok(SpiralDiversity::new())
+    }
+
+    /// Validator set changes observed during `epoch`, oldest first
+    fn validator_set_changes(
+        &self,
+        _epoch: u64,
+    ) -> spirachain_core::Result<Vec<ValidatorSetChange>> {
+        Ok(Vec::new())
+    }
+
+    /// Validated spiral continuity of the block at `height` on its parent
+    fn continuity_proof(&self, _height: u64) -> spirachain_core::Result<Option<ContinuityProof>> {
+        Ok(None)
+    }
+
+    /// Semantic aggregates of `epoch`, once the node's analytics job reached it
+    fn epoch_semantics(
+        &self,
+        _epoch: u64,
+    ) -> spirachain_core::Result<Option<EpochSemanticsResponse>> {
+        Ok(None)
+    }
+}
+
+/// Dry-runs transactions for `/simulate_transaction`. Called from a blocking
+/// thread, so implementations may block on the node's state lock.
+pub trait TransactionSimulator: Send + Sync {
+    /// Execute `tx` against a copy of the latest state without keeping the result
+    fn simulate(&self, tx: &Transaction) -> SimulateTransactionResponse;
+}
+
+/// Prometheus text from a subsystem this crate does not depend on, appended to `/metrics`
+pub trait MetricsSource: Send + Sync {
+    fn export_prometheus(&self) -> String;
+}
+
+/// π identifiers served on `/pi_identifier/generate`. Called from a blocking
+/// thread, like [`TransactionSimulator`].
+pub trait PiIdentifierService: Send + Sync {
+    /// Identifier bound to the fork active at `next_height`
+    fn generate(
+        &self,
+        request: &GeneratePiIdentifierRequest,
+        next_height: u64,
+    ) -> spirachain_core::Result<GeneratePiIdentifierResponse>;
+
+    /// Signed transaction recording the hash of `identifier`, includable
+    /// from `next_height`
+    fn anchor_transaction(
+        &self,
+        identifier: &GeneratePiIdentifierResponse,
+        next_height: u64,
+    ) -> spirachain_core::Result<Transaction>;
+}
+
+/// Leader schedule served on `/validators/schedule`. Called from a blocking
+/// thread, like [`TransactionSimulator`].
+pub trait SlotScheduleSource: Send + Sync {
+    /// Schedule of `epoch`, the current one when `None`
+    fn slot_schedule(&self, epoch: Option<u64>) -> spirachain_core::Result<SlotScheduleResponse>;
+}
+
+/// Fork divergence reports served on `/alerts`
+pub trait AlertSource: Send + Sync {
+    /// Up to `limit` reports, newest first
+    fn recent_alerts(&self, limit: usize) -> Vec<ForkAlertResponse>;
+}
+
+/// Node operations exposed on the loopback-only admin endpoints
+pub trait AdminHandler: Send + Sync {
+    /// Re-read the runtime config and apply it, returning the keys that changed
+    fn reload_config(&self) -> spirachain_core::Result<Vec<String>>;
+
+    /// Signed snapshot of the node's peer and gossip mesh view
+    fn network_graph(&self) -> spirachain_core::Result<serde_json::Value>;
+
+    /// Copy the database into the new directory `output`, returning its manifest
+    fn backup_database(
+        &self,
+        output: &std::path::Path,
+    ) -> spirachain_core::Result<serde_json::Value>;
 }
 
 pub struct RpcServerState {
@@ -25,10 +173,29 @@ pub struct RpcServerState {
     pub chain_height: Arc<RwLock<u64>>,
     pub connected_peers: Arc<RwLock<usize>>,
     pub is_validator: bool,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub max_mempool_size: Arc<AtomicUsize>,
+    pub admin: Option<Arc<dyn AdminHandler>>,
+    pub simulator: Option<Arc<dyn TransactionSimulator>>,
+    pub slot_schedule: Option<Arc<dyn SlotScheduleSource>>,
+    pub alerts: Option<Arc<dyn AlertSource>>,
+    pub pi_identifiers: Option<Arc<dyn PiIdentifierService>>,
+    /// Separate from `rate_limiter`: identifiers cost more than a lookup
+    pub pi_identifier_limiter: Arc<RateLimiter>,
+    pub network: String,
+    pub explorer: Arc<ExplorerFeed>,
+    pub sync_status: Arc<RwLock<SyncStatusResponse>>,
+    pub propagation_stats: Arc<RwLock<PropagationStatsResponse>>,
+    pub compression_stats: Arc<RwLock<CompressionStatsResponse>>,
+    pub mempool_monitor: Arc<MempoolMonitor>,
+    pub resource_guard: Arc<ResourceGuard>,
+    pub metrics_sources: Vec<Arc<dyn MetricsSource>>,
+    pub validator_changes: broadcast::Sender<ValidatorSetChangeResponse>,
+    pub version: VersionResponse,
 }
 
 pub struct RpcServer {
-    state: Arc<RpcServerState>,
+    state: RpcServerState,
     port: u16,
 }
 
@@ -41,27 +208,205 @@ impl RpcServer {
         is_validator: bool,
         port: u16,
     ) -> Self {
-        let state = Arc::new(RpcServerState {
+        let state = RpcServerState {
             mempool,
             storage,
             chain_height,
             connected_peers,
             is_validator,
-        });
+            rate_limiter: Arc::new(RateLimiter::default()),
+            max_mempool_size: Arc::new(AtomicUsize::new(usize::MAX)),
+            admin: None,
+            simulator: None,
+            slot_schedule: None,
+            alerts: None,
+            pi_identifiers: None,
+            pi_identifier_limiter: Arc::new(RateLimiter::default()),
+            network: "testnet".to_string(),
+            explorer: Arc::new(ExplorerFeed::default()),
+            sync_status: Arc::new(RwLock::new(SyncStatusResponse::default())),
+            propagation_stats: Arc::new(RwLock::new(PropagationStatsResponse::default())),
+            compression_stats: Arc::new(RwLock::new(CompressionStatsResponse::default())),
+            mempool_monitor: Arc::new(MempoolMonitor::new()),
+            resource_guard: Arc::new(ResourceGuard::default()),
+            metrics_sources: Vec::new(),
+            validator_changes: broadcast::channel(VALIDATOR_CHANGES_CHANNEL).0,
+            version: VersionResponse::default(),
+        };
 
         Self { state, port }
     }
 
+    /// Share a limiter whose limit can be changed while the server runs
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.state.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Reject submissions once the mempool holds this many transactions
+    pub fn with_mempool_limit(mut self, max_mempool_size: Arc<AtomicUsize>) -> Self {
+        self.state.max_mempool_size = max_mempool_size;
+        self
+    }
+
+    /// Network whose fork id submitted transactions must carry
+    pub fn with_network(mut self, network: impl Into<String>) -> Self {
+        self.state.network = network.into();
+        self
+    }
+
+    /// Feed the node publishes applied blocks to, served on `/explorer/feed`
+    pub fn with_explorer_feed(mut self, explorer: Arc<ExplorerFeed>) -> Self {
+        self.state.explorer = explorer;
+        self
+    }
+
+    /// Sync progress the node keeps up to date, served on `/sync/status`
+    pub fn with_sync_status(mut self, sync_status: Arc<RwLock<SyncStatusResponse>>) -> Self {
+        self.state.sync_status = sync_status;
+        self
+    }
+
+    /// Block propagation latency the node keeps up to date, served on
+    /// `/network/propagation` and `/metrics`
+    pub fn with_propagation_stats(
+        mut self,
+        propagation_stats: Arc<RwLock<PropagationStatsResponse>>,
+    ) -> Self {
+        self.state.propagation_stats = propagation_stats;
+        self
+    }
+
+    /// Gossip compression counters the node keeps up to date, served on
+    /// `/network/compression` and `/metrics`
+    pub fn with_compression_stats(
+        mut self,
+        compression_stats: Arc<RwLock<CompressionStatsResponse>>,
+    ) -> Self {
+        self.state.compression_stats = compression_stats;
+        self
+    }
+
+    /// Rejection and eviction counters the node records into
+    pub fn with_mempool_monitor(mut self, mempool_monitor: Arc<MempoolMonitor>) -> Self {
+        self.state.mempool_monitor = mempool_monitor;
+        self
+    }
+
+    /// Quotas the node measures; new transactions are refused while one is exceeded
+    pub fn with_resource_guard(mut self, resource_guard: Arc<ResourceGuard>) -> Self {
+        self.state.resource_guard = resource_guard;
+        self
+    }
+
+    /// Channel the node publishes validator set changes on, for live subscribers
+    pub fn with_validator_changes(
+        mut self,
+        validator_changes: broadcast::Sender<ValidatorSetChangeResponse>,
+    ) -> Self {
+        self.state.validator_changes = validator_changes;
+        self
+    }
+
+    pub fn with_metrics_source(mut self, source: Arc<dyn MetricsSource>) -> Self {
+        self.state.metrics_sources.push(source);
+        self
+    }
+
+    /// Build provenance served on `/version`
+    pub fn with_version(mut self, version: VersionResponse) -> Self {
+        self.state.version = version;
+        self
+    }
+
+    /// State access backing `/simulate_transaction`
+    pub fn with_simulator(mut self, simulator: Arc<dyn TransactionSimulator>) -> Self {
+        self.state.simulator = Some(simulator);
+        self
+    }
+
+    /// Leader schedule backing `/validators/schedule`
+    pub fn with_slot_schedule(mut self, slot_schedule: Arc<dyn SlotScheduleSource>) -> Self {
+        self.state.slot_schedule = Some(slot_schedule);
+        self
+    }
+
+    /// Fork reports backing `/alerts`
+    pub fn with_alerts(mut self, alerts: Arc<dyn AlertSource>) -> Self {
+        self.state.alerts = Some(alerts);
+        self
+    }
+
+    /// Generator backing `/pi_identifier/generate`, limited by `rate_limiter`
+    /// on top of the server-wide limit
+    pub fn with_pi_identifier_service(
+        mut self,
+        service: Arc<dyn PiIdentifierService>,
+        rate_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        self.state.pi_identifiers = Some(service);
+        self.state.pi_identifier_limiter = rate_limiter;
+        self
+    }
+
+    pub fn with_admin(mut self, admin: Arc<dyn AdminHandler>) -> Self {
+        self.state.admin = Some(admin);
+        self
+    }
+
     pub async fn start(self) -> Result<(), anyhow::Error> {
+        let state = Arc::new(self.state);
+
         let app = Router::new()
             .route("/health", get(health_check))
             .route("/status", get(get_status))
+            .route("/version", get(get_version))
+            .route("/chain/limits", get(get_chain_limits))
+            .route("/sync/status", get(get_sync_status))
+            .route("/network/propagation", get(get_propagation_stats))
+            .route("/network/compression", get(get_compression_stats))
+            .route("/checkpoint/latest", get(get_latest_checkpoint))
+            .route("/pause", get(get_pause_status))
+            .route("/semantic_model", get(get_semantic_model))
+            .route("/spirals", get(get_custom_spirals))
+            .route("/name/:name", get(resolve_name))
             .route("/submit_transaction", post(submit_transaction))
+            .route("/simulate_transaction", post(simulate_transaction))
+            .route("/pi_identifier/generate", post(generate_pi_identifier))
+            .route("/estimate_fee", get(get_fee_estimate))
+            .route("/mempool/content", get(get_mempool_content))
+            .route("/mempool/stats", get(get_mempool_stats))
+            .route("/resources", get(get_resource_status))
+            .route("/metrics", get(get_metrics))
+            .route("/alerts", get(get_alerts))
             .route("/block/:height", get(get_block))
+            .route("/block/:height/proof/:index", get(get_tx_proof))
+            .route("/block/:height/continuity", get(get_continuity_proof))
+            .route("/epoch/:epoch/summary", get(get_epoch_summary))
+            .route("/message/verify", post(verify_message))
+            .route("/events/filter", get(filter_events))
             .route("/balance/:address", get(get_balance))
+            .route("/rewards/:address", get(get_rewards))
+            .route("/validator/:address", get(get_validator_info))
+            .route("/validators/changes", get(get_validator_changes))
+            .route("/validators/schedule", get(get_slot_schedule))
+            .route(
+                "/validators/changes/subscribe",
+                get(subscribe_validator_changes),
+            )
             .route("/peers", get(get_peers))
+            .route("/explorer/feed", get(explorer_feed))
+            .route("/explorer/diversity", get(get_diversity_stats))
+            .route("/explorer/semantics", get(get_epoch_semantics))
+            .route("/admin/reload_config", post(reload_config))
+            .route("/admin/network_graph", get(network_graph))
+            .route("/admin/db/backup", post(backup_database))
+            .layer(middleware::from_fn_with_state(
+                Arc::clone(&state),
+                rate_limit,
+            ))
             .layer(CorsLayer::permissive())
-            .with_state(self.state);
+            .with_state(state);
 
         let addr = format!("0.0.0.0:{}", self.port);
         info!("🌐 RPC server starting on {}", addr);
@@ -69,12 +414,34 @@ impl RpcServer {
         let listener = tokio::net::TcpListener::bind(&addr).await?;
         info!("✅ RPC server listening on {}", addr);
 
-        axum::serve(listener, app).await?;
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await?;
 
         Ok(())
     }
 }
 
+async fn rate_limit(
+    State(state): State<Arc<RpcServerState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.rate_limiter.check(addr.ip()) {
+        warn!("⛔ Rate limit exceeded for {}", addr.ip());
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ErrorResponse::new("Rate limit exceeded".to_string())),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
 async fn health_check() -> impl IntoResponse {
     Json(json!({
         "status": "ok",
@@ -92,41 +459,218 @@ async fn get_status(State(state): State<Arc<RpcServerState>>) -> impl IntoRespon
         mempool_size: mempool.len(),
         connected_peers,
         is_validator: state.is_validator,
-        is_syncing: false,
+        is_syncing: state.sync_status.read().await.syncing,
+        fork_id: spirachain_core::fork_id(&state.network, chain_height + 1).to_string(),
+    })
+}
+
+/// HTTP status for an error, by category, so every endpoint reports the same
+/// failure the same way
+pub fn error_status(error: &SpiraChainError) -> StatusCode {
+    match error {
+        SpiraChainError::BlockNotFound(_) | SpiraChainError::TransactionNotFound(_) => {
+            StatusCode::NOT_FOUND
+        }
+        SpiraChainError::NearDuplicate(_) => StatusCode::CONFLICT,
+        _ => match error.category() {
+            ErrorCategory::Validation
+            | ErrorCategory::Consensus
+            | ErrorCategory::Crypto
+            | ErrorCategory::Semantic => StatusCode::BAD_REQUEST,
+            ErrorCategory::Resource | ErrorCategory::Network => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCategory::Storage | ErrorCategory::Config | ErrorCategory::Internal => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        },
+    }
+}
+
+/// Log `error` with its causes and answer with its code; node-side failures
+/// are logged as errors, rejected requests only at debug level
+fn error_response(context: &str, error: &SpiraChainError) -> Response {
+    let status = error_status(error);
+    if status.is_server_error() {
+        error!("{}: {}", context, error.chain());
+    } else {
+        debug!("{}: {}", context, error.chain());
+    }
+    (status, Json(ErrorResponse::from_error(error))).into_response()
+}
+
+async fn get_sync_status(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    Json(state.sync_status.read().await.clone())
+}
+
+async fn get_propagation_stats(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    Json(state.propagation_stats.read().await.clone())
+}
+
+async fn get_compression_stats(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    Json(state.compression_stats.read().await.clone())
+}
+
+async fn get_chain_limits(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    let params = spirachain_core::ChainParams::for_network(&state.network);
+    let height = *state.chain_height.read().await;
+    let max_header_extra_data =
+        spirachain_core::HeaderRules::for_version(params.protocol_version_at(height + 1) as u64)
+            .map(|rules| rules.max_extra_data)
+            .unwrap_or(0);
+
+    Json(ChainLimitsResponse {
+        network: params.network.to_string(),
+        max_block_size: params.block_limits.max_block_size,
+        max_tx_per_block: params.block_limits.max_tx_per_block,
+        max_header_extra_data,
+        max_contract_code_size: spirachain_core::MAX_CONTRACT_CODE_SIZE,
+        max_contract_input_size: spirachain_core::MAX_CONTRACT_INPUT_SIZE,
+        min_tx_fee: encode_amount(Amount::new(spirachain_core::MIN_TX_FEE)),
     })
 }
 
+async fn get_version(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    Json(state.version.clone())
+}
+
+async fn get_latest_checkpoint(State(state): State<Arc<RpcServerState>>) -> Response {
+    match state.storage.latest_checkpoint() {
+        Ok(Some((height, block_hash))) => Json(CheckpointResponse {
+            height,
+            block_hash: encode_hex(block_hash.as_bytes()),
+        })
+        .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("No checkpoint yet".to_string())),
+        )
+            .into_response(),
+        Err(e) => error_response("Failed to fetch checkpoint", &e),
+    }
+}
+
+async fn get_pause_status(State(state): State<Arc<RpcServerState>>) -> Response {
+    let next_height = *state.chain_height.read().await + 1;
+    match state.storage.pause_state() {
+        Ok(pause) => Json(PauseStatusResponse {
+            paused: pause.is_paused(next_height),
+            paused_until: pause.paused_until(next_height),
+            votes: pause
+                .votes(next_height)
+                .map(|(guardian, vote)| PauseVoteResponse {
+                    guardian: guardian.to_string(),
+                    blocks: vote.blocks,
+                    height: vote.height,
+                })
+                .collect(),
+        })
+        .into_response(),
+        Err(e) => error_response("Failed to fetch pause state", &e),
+    }
+}
+
+async fn get_semantic_model(State(state): State<Arc<RpcServerState>>) -> Response {
+    let next_height = *state.chain_height.read().await + 1;
+    let model_response = |epoch: u64, model: &SemanticModel| SemanticModelResponse {
+        epoch,
+        name: model.name.clone(),
+        version: model.version.clone(),
+        weights_hash: model.weights_hash.to_string(),
+        commitment: model.commitment().to_string(),
+    };
+    match state.storage.semantic_models() {
+        Ok(models) => {
+            let approvals: Vec<SemanticModelResponse> = models
+                .approvals()
+                .map(|(epoch, model)| model_response(epoch, model))
+                .collect();
+            let current = approvals
+                .iter()
+                .rev()
+                .find(|approval| approval.epoch <= diversity_epoch(next_height))
+                .cloned();
+            Json(SemanticModelStatusResponse {
+                epoch: diversity_epoch(next_height),
+                current,
+                approvals,
+            })
+            .into_response()
+        }
+        Err(e) => error_response("Failed to fetch semantic model approvals", &e),
+    }
+}
+
+async fn get_custom_spirals(State(state): State<Arc<RpcServerState>>) -> Response {
+    match state.storage.spiral_registry() {
+        Ok(registry) => Json(
+            registry
+                .iter()
+                .map(|(id, spiral)| CustomSpiralResponse {
+                    id: id.to_string(),
+                    name: spiral.definition.name.clone(),
+                    formula: spiral.definition.formula.clone(),
+                    turns: spiral.definition.turns,
+                    registrant: spiral.registrant.to_string(),
+                    height: spiral.height,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => error_response("Failed to fetch spiral registry", &e),
+    }
+}
+
+async fn resolve_name(
+    State(state): State<Arc<RpcServerState>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Response {
+    let name = match normalize_name(&name) {
+        Ok(name) => name,
+        Err(e) => return error_response("Invalid name", &e),
+    };
+
+    let height = *state.chain_height.read().await;
+    match state.storage.name_registry() {
+        Ok(names) => match names.resolve(&name, height + 1) {
+            Some(record) => Json(NameResponse {
+                name: format!("{}{}", name, NAME_SUFFIX),
+                address: record.target.to_string(),
+                owner: record.owner.to_string(),
+                registered_at: record.registered_at,
+                expires_at: record.expires_at,
+            })
+            .into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse::new(format!(
+                    "{}{} is not registered",
+                    name, NAME_SUFFIX
+                ))),
+            )
+                .into_response(),
+        },
+        Err(e) => error_response("Failed to fetch name registry", &e),
+    }
+}
+
 async fn submit_transaction(
     State(state): State<Arc<RpcServerState>>,
     Json(req): Json<SubmitTransactionRequest>,
 ) -> impl IntoResponse {
     info!("📥 Received transaction submission: {}", req.tx_hex);
 
-    let tx_bytes = match hex::decode(&req.tx_hex) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            error!("Failed to decode transaction hex: {}", e);
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(SubmitTransactionResponse {
-                    success: false,
-                    tx_hash: String::new(),
-                    message: format!("Invalid hex: {}", e),
-                }),
-            );
-        }
-    };
-
-    let tx: Transaction = match serde_json::from_slice(&tx_bytes) {
+    let tx = match parse_transaction_input(&req.tx_hex) {
         Ok(tx) => tx,
-        Err(e) => {
-            error!("Failed to deserialize transaction: {}", e);
+        Err(field_errors) => {
+            let message = describe_field_errors(&field_errors);
+            error!("Refused transaction input: {}", message);
             return (
                 StatusCode::BAD_REQUEST,
                 Json(SubmitTransactionResponse {
                     success: false,
                     tx_hash: String::new(),
-                    message: format!("Invalid transaction: {}", e),
+                    message,
+                    code: Some(SpiraChainError::InvalidTransaction(String::new()).code()),
+                    field_errors,
                 }),
             );
         }
@@ -134,20 +678,79 @@ async fn submit_transaction(
 
     let tx_hash = tx.tx_hash.to_string();
 
-    if let Err(e) = tx.validate() {
+    let next_height = *state.chain_height.read().await + 1;
+    if let Err(e) = tx
+        .validate()
+        .and_then(|_| tx.verify_sender_signature_at(&state.network, next_height))
+        .and_then(|_| tx.validate_fork_id(&state.network, next_height))
+        .and_then(|_| tx.validate_schedule_admission(next_height))
+    {
         error!("Transaction validation failed: {}", e);
+        state
+            .mempool_monitor
+            .record(&tx.tx_hash, DropReason::Invalid, e.to_string());
         return (
-            StatusCode::BAD_REQUEST,
+            error_status(&e),
             Json(SubmitTransactionResponse {
                 success: false,
                 tx_hash: tx_hash.clone(),
-                message: format!("Validation failed: {}", e),
+                message: format!("Validation failed: {}", e.public_message()),
+                code: Some(e.code()),
+                field_errors: Vec::new(),
+            }),
+        );
+    }
+
+    if let Err(e) = state
+        .storage
+        .pause_state()
+        .and_then(|pause| pause.check_transaction(&tx, next_height))
+    {
+        state
+            .mempool_monitor
+            .record(&tx.tx_hash, DropReason::Paused, e.to_string());
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(SubmitTransactionResponse {
+                success: false,
+                tx_hash,
+                message: e.public_message(),
+                code: Some(e.code()),
+                field_errors: Vec::new(),
+            }),
+        );
+    }
+
+    if let Err(e) = state.resource_guard.admit_transaction(tx.serialize().len()) {
+        state
+            .mempool_monitor
+            .record(&tx.tx_hash, DropReason::OverQuota, e.to_string());
+        return (
+            error_status(&e),
+            Json(SubmitTransactionResponse {
+                success: false,
+                tx_hash,
+                message: e.public_message(),
+                code: Some(e.code()),
+                field_errors: Vec::new(),
             }),
         );
     }
 
     let mut mempool = state.mempool.write().await;
-    mempool.push(tx);
+    let max_size = state.max_mempool_size.load(Ordering::Relaxed);
+    if let Err(e) = admit_transaction(&mut mempool, tx, max_size, &state.mempool_monitor) {
+        return (
+            error_status(&e),
+            Json(SubmitTransactionResponse {
+                success: false,
+                tx_hash,
+                message: e.public_message(),
+                code: Some(e.code()),
+                field_errors: Vec::new(),
+            }),
+        );
+    }
 
     info!("✅ Transaction {} added to mempool", tx_hash);
 
@@ -157,10 +760,307 @@ async fn submit_transaction(
             success: true,
             tx_hash,
             message: "Transaction added to mempool".to_string(),
+            code: None,
+            field_errors: Vec::new(),
         }),
     )
 }
 
+async fn simulate_transaction(
+    State(state): State<Arc<RpcServerState>>,
+    Json(req): Json<SubmitTransactionRequest>,
+) -> Response {
+    let tx = match parse_transaction_input(&req.tx_hex) {
+        Ok(tx) => tx,
+        Err(errors) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(describe_field_errors(&errors))),
+            )
+                .into_response();
+        }
+    };
+
+    let Some(simulator) = state.simulator.clone() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse::new(
+                "Transaction simulation is not available on this node".to_string(),
+            )),
+        )
+            .into_response();
+    };
+
+    let next_height = *state.chain_height.read().await + 1;
+    let stateless = tx
+        .validate()
+        .and_then(|_| tx.validate_fork_id(&state.network, next_height))
+        .and_then(|_| tx.validate_schedule_admission(next_height));
+
+    let mut outcome = match tokio::task::spawn_blocking(move || simulator.simulate(&tx)).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            error!("Transaction simulation panicked: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("Simulation failed".to_string())),
+            )
+                .into_response();
+        }
+    };
+
+    // Nodes would reject it before execution, whatever the state says
+    if let Err(e) = stateless {
+        outcome.success = false;
+        outcome.error = Some(format!("Validation failed: {}", e));
+    }
+
+    debug!(
+        "🧪 Simulated transaction {}: {}",
+        outcome.tx_hash,
+        outcome.error.as_deref().unwrap_or("ok")
+    );
+    Json(outcome).into_response()
+}
+
+async fn generate_pi_identifier(
+    State(state): State<Arc<RpcServerState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<GeneratePiIdentifierRequest>,
+) -> Response {
+    if !state.pi_identifier_limiter.check(addr.ip()) {
+        warn!("⛔ π identifier rate limit exceeded for {}", addr.ip());
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ErrorResponse::new("Rate limit exceeded".to_string())),
+        )
+            .into_response();
+    }
+
+    let Some(service) = state.pi_identifiers.clone() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse::new(
+                "π identifier generation is not available on this node".to_string(),
+            )),
+        )
+            .into_response();
+    };
+
+    let next_height = *state.chain_height.read().await + 1;
+    let generated = tokio::task::spawn_blocking(move || {
+        let mut identifier = service.generate(&req, next_height)?;
+        let anchor = if req.anchor {
+            Some(service.anchor_transaction(&identifier, next_height)?)
+        } else {
+            None
+        };
+        identifier.anchor_tx_hash = anchor.as_ref().map(|tx| tx.tx_hash.to_string());
+        Ok::<_, SpiraChainError>((identifier, anchor))
+    })
+    .await;
+
+    let (identifier, anchor) = match generated {
+        Ok(Ok(generated)) => generated,
+        Ok(Err(e)) => return error_response("π identifier generation failed", &e),
+        Err(e) => {
+            error!("π identifier generation panicked: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(
+                    "π identifier generation failed".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(tx) = anchor {
+        if let Err(e) = admit_local_transaction(&state, tx, next_height).await {
+            return error_response("π identifier anchoring failed", &e);
+        }
+        info!(
+            "⚓ π identifier {} anchored in {}",
+            identifier.identifier_hash,
+            identifier.anchor_tx_hash.as_deref().unwrap_or_default()
+        );
+    }
+
+    Json(identifier).into_response()
+}
+
+/// The checks `/submit_transaction` runs, for transactions the node signs itself
+async fn admit_local_transaction(
+    state: &RpcServerState,
+    tx: Transaction,
+    next_height: u64,
+) -> spirachain_core::Result<()> {
+    let rejected = |reason: DropReason, e: SpiraChainError| {
+        state
+            .mempool_monitor
+            .record(&tx.tx_hash, reason, e.to_string());
+        e
+    };
+    tx.validate()
+        .and_then(|_| tx.verify_sender_signature_at(&state.network, next_height))
+        .and_then(|_| tx.validate_fork_id(&state.network, next_height))
+        .map_err(|e| rejected(DropReason::Invalid, e))?;
+    state
+        .storage
+        .pause_state()
+        .and_then(|pause| pause.check_transaction(&tx, next_height))
+        .map_err(|e| rejected(DropReason::Paused, e))?;
+    state
+        .resource_guard
+        .admit_transaction(tx.serialize().len())
+        .map_err(|e| rejected(DropReason::OverQuota, e))?;
+
+    let mut mempool = state.mempool.write().await;
+    let max_size = state.max_mempool_size.load(Ordering::Relaxed);
+    admit_transaction(&mut mempool, tx, max_size, &state.mempool_monitor)
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct MempoolContentQuery {
+    #[serde(default)]
+    page: usize,
+}
+
+async fn get_mempool_content(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<MempoolContentQuery>,
+) -> impl IntoResponse {
+    let mempool = state.mempool.read().await;
+    Json(mempool_page(&mempool, query.page))
+}
+
+async fn get_mempool_stats(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    let mempool = state.mempool.read().await;
+    let (rejected, evicted) = state.mempool_monitor.counters();
+
+    Json(MempoolStatsResponse {
+        size: mempool.len(),
+        max_size: state.max_mempool_size.load(Ordering::Relaxed),
+        bytes: mempool.iter().map(|tx| tx.serialize().len()).sum(),
+        fee_histogram: fee_histogram(&mempool),
+        rejected,
+        evicted,
+        recent_drops: state.mempool_monitor.recent(),
+    })
+}
+
+/// `?target_blocks=&intent=` of `/estimate_fee`
+#[derive(Debug, serde::Deserialize)]
+struct FeeEstimateQuery {
+    #[serde(default = "default_fee_target")]
+    target_blocks: u64,
+    intent: Option<IntentType>,
+}
+
+fn default_fee_target() -> u64 {
+    3
+}
+
+async fn get_fee_estimate(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<FeeEstimateQuery>,
+) -> impl IntoResponse {
+    let chain_height = *state.chain_height.read().await;
+    let recent_blocks: Vec<Block> = (chain_height.saturating_sub(FEE_ESTIMATE_BLOCKS - 1)
+        ..=chain_height)
+        .filter_map(|height| state.storage.get_block_by_height(height).ok().flatten())
+        .collect();
+    let block_capacity = spirachain_core::ChainParams::for_network(&state.network)
+        .block_limits
+        .max_tx_per_block;
+
+    let mempool = state.mempool.read().await;
+    Json(estimate_fee(
+        &recent_blocks,
+        &mempool,
+        query.target_blocks,
+        block_capacity,
+        query.intent,
+    ))
+}
+
+async fn get_resource_status(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    Json(state.resource_guard.status())
+}
+
+/// Prometheus text exposition of the node's resource quotas
+async fn get_metrics(State(state): State<Arc<RpcServerState>>) -> impl IntoResponse {
+    let mut output = state.resource_guard.export_prometheus();
+    let propagation = state.propagation_stats.read().await.clone();
+    output.push_str(&propagation_metrics(&propagation));
+    let compression = state.compression_stats.read().await.clone();
+    output.push_str(&compression_metrics(&compression));
+    for source in &state.metrics_sources {
+        output.push_str(&source.export_prometheus());
+    }
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        output,
+    )
+}
+
+fn propagation_metrics(stats: &PropagationStatsResponse) -> String {
+    let mut output = String::from(
+        "# HELP spirachain_block_propagation_ms Compact block announcement latency\n\
+         # TYPE spirachain_block_propagation_ms summary\n",
+    );
+    for (quantile, value) in [
+        ("0.5", stats.p50_ms),
+        ("0.9", stats.p90_ms),
+        ("0.99", stats.p99_ms),
+        ("1", stats.max_ms),
+    ] {
+        output.push_str(&format!(
+            "spirachain_block_propagation_ms{{quantile=\"{}\"}} {}\n",
+            quantile, value
+        ));
+    }
+    output.push_str(&format!(
+        "spirachain_block_propagation_ms_count {}\n\
+         # HELP spirachain_block_propagation_relayed_ratio Announcements that came through a relay\n\
+         # TYPE spirachain_block_propagation_relayed_ratio gauge\n\
+         spirachain_block_propagation_relayed_ratio {}\n",
+        stats.total, stats.relayed_ratio
+    ));
+    output
+}
+
+fn compression_metrics(stats: &CompressionStatsResponse) -> String {
+    let mut bytes = String::from(
+        "# HELP spirachain_p2p_compression_bytes_total Large gossip payload bytes before and after compression\n\
+         # TYPE spirachain_p2p_compression_bytes_total counter\n",
+    );
+    let mut cpu = String::from(
+        "# HELP spirachain_p2p_compression_cpu_micros_total Time spent in gossip codecs\n\
+         # TYPE spirachain_p2p_compression_cpu_micros_total counter\n",
+    );
+    for codec in &stats.codecs {
+        bytes.push_str(&format!(
+            "spirachain_p2p_compression_bytes_total{{codec=\"{0}\",stage=\"raw\"}} {1}\n\
+             spirachain_p2p_compression_bytes_total{{codec=\"{0}\",stage=\"wire\"}} {2}\n",
+            codec.codec, codec.raw_bytes, codec.wire_bytes
+        ));
+        cpu.push_str(&format!(
+            "spirachain_p2p_compression_cpu_micros_total{{codec=\"{0}\",op=\"compress\"}} {1}\n\
+             spirachain_p2p_compression_cpu_micros_total{{codec=\"{0}\",op=\"decompress\"}} {2}\n",
+            codec.codec, codec.compress_micros, codec.decompress_micros
+        ));
+    }
+    bytes.push_str(&cpu);
+    bytes.push_str(&format!(
+        "# HELP spirachain_p2p_compression_fallbacks_total Payloads sent with a cheaper codec for a peer\n\
+         # TYPE spirachain_p2p_compression_fallbacks_total counter\n\
+         spirachain_p2p_compression_fallbacks_total {}\n",
+        stats.fallbacks
+    ));
+    bytes
+}
+
 async fn get_block(
     State(state): State<Arc<RpcServerState>>,
     axum::extract::Path(height): axum::extract::Path<u64>,
@@ -169,15 +1069,8 @@ async fn get_block(
 
     match state.storage.get_block_by_height(height) {
         Ok(Some(block)) => {
-            let block_json = json!({
-                "height": block.header.block_height,
-                "hash": block.hash().to_string(),
-                "timestamp": block.header.timestamp,
-                "validator": hex::encode(&block.header.validator_pubkey),
-                "transactions": block.transactions.len(),
-                "spiral_complexity": block.header.spiral.complexity,
-                "semantic_coherence": block.avg_semantic_coherence(),
-            });
+            let block_json = serde_json::to_value(BlockDto::from(&block))
+                .unwrap_or_else(|e| json!({"error": e.to_string()}));
 
             (StatusCode::OK, Json(GetBlockResponse { block: block_json }))
         }
@@ -188,17 +1081,325 @@ async fn get_block(
             }),
         ),
         Err(e) => {
-            error!("Failed to fetch block: {}", e);
+            error!("Failed to fetch block: {}", e.chain());
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error_status(&e),
                 Json(GetBlockResponse {
-                    block: json!({"error": format!("Storage error: {}", e)}),
+                    block: json!({"error": e.public_message(), "code": e.code()}),
                 }),
             )
         }
     }
 }
 
+/// Check an off-chain signed message, e.g. a wallet login. Answers 200 either
+/// way; `valid` carries the verdict.
+async fn verify_message(Json(signed): Json<SignedMessage>) -> Json<VerifyMessageResponse> {
+    let result = signed.verify();
+    Json(VerifyMessageResponse {
+        valid: result.is_ok(),
+        address: signed.address,
+        error: result.err().map(|e| e.to_string()),
+    })
+}
+
+async fn get_tx_proof(
+    State(state): State<Arc<RpcServerState>>,
+    axum::extract::Path((height, index)): axum::extract::Path<(u64, usize)>,
+) -> Response {
+    let block = match state.storage.get_block_by_height(height) {
+        Ok(Some(block)) => block,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse::new("Block not found".to_string())),
+            )
+                .into_response()
+        }
+        Err(e) => return error_response("Failed to fetch block", &e),
+    };
+
+    match block.merkle_proof(index) {
+        Some(proof) => Json(TxProofResponse {
+            block_height: height,
+            block_hash: block.hash().to_string(),
+            merkle_root: block.header.merkle_root.to_string(),
+            tx_hash: block.transactions[index].tx_hash.to_string(),
+            index: proof.index,
+            siblings: proof.siblings.iter().map(Hash::to_string).collect(),
+            block_version: block.header.version,
+        })
+        .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!(
+                "Block {} has no transaction {}",
+                height, index
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_continuity_proof(
+    State(state): State<Arc<RpcServerState>>,
+    axum::extract::Path(height): axum::extract::Path<u64>,
+) -> Response {
+    match state.storage.continuity_proof(height) {
+        Ok(Some(proof)) => Json(ContinuityProofResponse::from(&proof)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!(
+                "No continuity proof recorded for block {}",
+                height
+            ))),
+        )
+            .into_response(),
+        Err(e) => error_response("Failed to fetch continuity proof", &e),
+    }
+}
+
+async fn get_epoch_summary(
+    State(state): State<Arc<RpcServerState>>,
+    axum::extract::Path(epoch): axum::extract::Path<u64>,
+) -> Response {
+    let height = (epoch + 1) * DIVERSITY_EPOCH_BLOCKS;
+    let block = match state.storage.get_block_by_height(height) {
+        Ok(block) => block,
+        Err(e) => return error_response("Failed to fetch block", &e),
+    };
+
+    match block
+        .as_ref()
+        .and_then(|block| Some((block.epoch_summary()?, block)))
+    {
+        Some((summary, block)) => Json(EpochSummaryResponse::new(summary, block)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!(
+                "No summary of epoch {} on chain",
+                epoch
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+/// `?epoch=` of the per-epoch explorer endpoints
+#[derive(Debug, serde::Deserialize)]
+struct DiversityQuery {
+    /// Defaults to the current epoch
+    epoch: Option<u64>,
+}
+
+async fn get_diversity_stats(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<DiversityQuery>,
+) -> Response {
+    let chain_height = *state.chain_height.read().await;
+    let epoch = query.epoch.unwrap_or(diversity_epoch(chain_height));
+
+    match state.storage.spiral_diversity() {
+        Ok(diversity) => {
+            let mut validators: Vec<ValidatorDiversityResponse> = diversity
+                .epoch(epoch)
+                .map(|(address, stats)| ValidatorDiversityResponse {
+                    address: address.to_string(),
+                    blocks: stats.blocks,
+                    distinct_kinds: stats.distinct_kinds(),
+                    score: stats.score(),
+                    kinds: stats.kinds.clone(),
+                })
+                .collect();
+            validators.sort_by_key(|stats| std::cmp::Reverse(stats.blocks));
+
+            Json(DiversityStatsResponse {
+                epoch,
+                epoch_blocks: DIVERSITY_EPOCH_BLOCKS,
+                available_epochs: diversity.epochs().collect(),
+                validators,
+            })
+            .into_response()
+        }
+        Err(e) => error_response("Failed to fetch spiral diversity", &e),
+    }
+}
+
+async fn get_epoch_semantics(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<DiversityQuery>,
+) -> Response {
+    let chain_height = *state.chain_height.read().await;
+    let epoch = query.epoch.unwrap_or(diversity_epoch(chain_height));
+
+    match state.storage.epoch_semantics(epoch) {
+        Ok(Some(semantics)) => Json(semantics).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!(
+                "No semantic analytics for epoch {}",
+                epoch
+            ))),
+        )
+            .into_response(),
+        Err(e) => error_response("Failed to fetch epoch semantics", &e),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ValidatorChangesQuery {
+    /// Both default to the current epoch
+    from_epoch: Option<u64>,
+    to_epoch: Option<u64>,
+}
+
+async fn get_validator_changes(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<ValidatorChangesQuery>,
+) -> Response {
+    let current_epoch = diversity_epoch(*state.chain_height.read().await);
+    let to_epoch = query.to_epoch.unwrap_or(current_epoch);
+    let from_epoch = query.from_epoch.unwrap_or(to_epoch);
+
+    if from_epoch > to_epoch || to_epoch - from_epoch >= MAX_VALIDATOR_HISTORY_EPOCHS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!(
+                "Epoch range must be ascending and span at most {} epochs",
+                MAX_VALIDATOR_HISTORY_EPOCHS
+            ))),
+        )
+            .into_response();
+    }
+
+    let mut changes = Vec::new();
+    for epoch in from_epoch..=to_epoch {
+        match state.storage.validator_set_changes(epoch) {
+            Ok(epoch_changes) => changes.extend(epoch_changes.iter().map(Into::into)),
+            Err(e) => return error_response("Failed to fetch validator set changes", &e),
+        }
+    }
+
+    Json(ValidatorSetHistoryResponse {
+        from_epoch,
+        to_epoch,
+        changes,
+    })
+    .into_response()
+}
+
+/// Live validator set changes; history comes from `/validators/changes`
+async fn subscribe_validator_changes(
+    State(state): State<Arc<RpcServerState>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let changes = state.validator_changes.subscribe();
+    ws.on_upgrade(move |socket| stream_validator_changes(socket, changes))
+}
+
+async fn stream_validator_changes(
+    mut socket: WebSocket,
+    mut changes: broadcast::Receiver<ValidatorSetChangeResponse>,
+) {
+    loop {
+        tokio::select! {
+            received = changes.recv() => match received {
+                Ok(change) => {
+                    let text = match serde_json::to_string(&change) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            error!("Failed to encode validator set change: {}", e);
+                            continue;
+                        }
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    // The client catches up from the history endpoint on reconnect
+                    warn!(
+                        "⚠️  Validator change subscriber fell {} changes behind, closing",
+                        skipped
+                    );
+                    let _ = socket.send(Message::Close(None)).await;
+                    return;
+                }
+                Err(RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => {
+                    debug!("Validator change subscriber disconnected");
+                    return;
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct EventFilterQuery {
+    from: u64,
+    to: Option<u64>,
+    address: Option<String>,
+    /// Topic name, e.g. `payload:contract_call`
+    topic: Option<String>,
+}
+
+/// Heights in a range whose bloom may hold the address and/or topic, so
+/// indexers only fetch those blocks
+async fn filter_events(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<EventFilterQuery>,
+) -> Response {
+    let bad_request =
+        |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(error))).into_response();
+
+    let address = match query.address.as_deref().map(str::parse::<Address>) {
+        Some(Ok(address)) => Some(address),
+        Some(Err(e)) => return bad_request(format!("Invalid address: {}", e)),
+        None => None,
+    };
+    let topic = query.topic.as_deref().map(event_topic);
+    if address.is_none() && topic.is_none() {
+        return bad_request("Filter needs an address or a topic".to_string());
+    }
+
+    let chain_height = *state.chain_height.read().await;
+    let to = query.to.unwrap_or(chain_height).min(chain_height);
+    if to.saturating_sub(query.from) >= MAX_EVENT_FILTER_RANGE {
+        return bad_request(format!(
+            "Range too large: at most {} blocks per request",
+            MAX_EVENT_FILTER_RANGE
+        ));
+    }
+
+    let mut candidates = Vec::new();
+    for height in query.from..=to {
+        let block = match state.storage.get_block_by_height(height) {
+            Ok(Some(block)) => block,
+            Ok(None) => continue,
+            Err(e) => return error_response(&format!("Failed to fetch block {}", height), &e),
+        };
+
+        let matches = block.header.event_bloom().is_none_or(|bloom| {
+            address.is_none_or(|address| bloom.may_contain_address(&address))
+                && topic.is_none_or(|topic| bloom.may_contain_topic(&topic))
+        });
+        if matches {
+            candidates.push(height);
+        }
+    }
+
+    Json(EventFilterResponse {
+        from: query.from,
+        to,
+        candidates,
+    })
+    .into_response()
+}
+
 async fn get_balance(
     State(state): State<Arc<RpcServerState>>,
     axum::extract::Path(address_hex): axum::extract::Path<String>,
@@ -233,7 +1434,7 @@ async fn get_balance(
             }),
         ),
         Err(e) => {
-            error!("Failed to fetch balance: {}", e);
+            error!("Failed to fetch balance: {}", e.chain());
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(GetBalanceResponse {
@@ -245,6 +1446,115 @@ async fn get_balance(
     }
 }
 
+async fn get_rewards(
+    State(state): State<Arc<RpcServerState>>,
+    axum::extract::Path(address): axum::extract::Path<String>,
+) -> Response {
+    let Ok(address) = address.parse::<Address>() else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!("Invalid address: {}", address))),
+        )
+            .into_response();
+    };
+
+    match state.storage.get_unclaimed_rewards(&address) {
+        Ok(unclaimed) => Json(RewardsResponse {
+            address: address.to_string(),
+            unclaimed_rewards: encode_amount(unclaimed),
+        })
+        .into_response(),
+        Err(e) => error_response("Failed to fetch rewards", &e),
+    }
+}
+
+async fn get_validator_info(
+    State(state): State<Arc<RpcServerState>>,
+    axum::extract::Path(address): axum::extract::Path<String>,
+) -> Response {
+    let Ok(address) = address.parse::<Address>() else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!("Invalid address: {}", address))),
+        )
+            .into_response();
+    };
+
+    let info = state.storage.get_account(&address).and_then(|account| {
+        let account = account.unwrap_or_default();
+        let payout = account.payout_address.unwrap_or(address);
+        Ok(ValidatorInfoResponse {
+            address: address.to_string(),
+            stake: encode_amount(account.stake),
+            payout_address: payout.to_string(),
+            unclaimed_rewards: encode_amount(state.storage.get_unclaimed_rewards(&payout)?),
+            nonce: account.nonce,
+        })
+    });
+
+    match info {
+        Ok(info) => Json(info).into_response(),
+        Err(e) => error_response("Failed to fetch validator info", &e),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SlotScheduleQuery {
+    /// Defaults to the current epoch
+    epoch: Option<u64>,
+}
+
+async fn get_slot_schedule(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<SlotScheduleQuery>,
+) -> Response {
+    let Some(schedule) = state.slot_schedule.clone() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse::new(
+                "The leader schedule is not available on this node".to_string(),
+            )),
+        )
+            .into_response();
+    };
+
+    match tokio::task::spawn_blocking(move || schedule.slot_schedule(query.epoch)).await {
+        Ok(Ok(schedule)) => Json(schedule).into_response(),
+        Ok(Err(e)) => error_response("Failed to compute the leader schedule", &e),
+        Err(e) => {
+            error!("Leader schedule panicked: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct AlertsQuery {
+    #[serde(default = "default_alerts_limit")]
+    limit: usize,
+}
+
+fn default_alerts_limit() -> usize {
+    50
+}
+
+async fn get_alerts(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<AlertsQuery>,
+) -> Response {
+    let Some(alerts) = state.alerts.as_ref() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse::new(
+                "Fork alerts are not available on this node".to_string(),
+            )),
+        )
+            .into_response();
+    };
+
+    Json(alerts.recent_alerts(query.limit.min(MAX_ALERTS_PER_REQUEST))).into_response()
+}
+
 async fn get_peers(State(_state): State<Arc<RpcServerState>>) -> impl IntoResponse {
     // For now, return empty list
     // TODO: Get actual connected peers from network layer
@@ -259,3 +1569,221 @@ async fn get_peers(State(_state): State<Arc<RpcServerState>>) -> impl IntoRespon
         })),
     )
 }
+
+async fn reload_config(
+    State(state): State<Arc<RpcServerState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> impl IntoResponse {
+    if !addr.ip().is_loopback() {
+        warn!("⛔ Rejected admin request from {}", addr);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"success": false, "message": "Admin endpoints are local only"})),
+        );
+    }
+
+    let Some(admin) = &state.admin else {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            Json(json!({"success": false, "message": "Config reload not available"})),
+        );
+    };
+
+    match admin.reload_config() {
+        Ok(changed) => {
+            info!(
+                "🔁 Runtime config reloaded via RPC ({} changes)",
+                changed.len()
+            );
+            (
+                StatusCode::OK,
+                Json(json!({"success": true, "changed": changed})),
+            )
+        }
+        Err(e) => {
+            error!("Config reload failed: {}", e.chain());
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"success": false, "message": e.chain(), "code": e.code()})),
+            )
+        }
+    }
+}
+
+async fn network_graph(
+    State(state): State<Arc<RpcServerState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> impl IntoResponse {
+    if !addr.ip().is_loopback() {
+        warn!("⛔ Rejected admin request from {}", addr);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"success": false, "message": "Admin endpoints are local only"})),
+        );
+    }
+
+    let Some(admin) = &state.admin else {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            Json(json!({"success": false, "message": "Network graph not available"})),
+        );
+    };
+
+    match admin.network_graph() {
+        Ok(graph) => (StatusCode::OK, Json(graph)),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"success": false, "message": e.to_string()})),
+        ),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct DatabaseBackupRequest {
+    /// Directory to create on the node's host
+    output: String,
+}
+
+async fn backup_database(
+    State(state): State<Arc<RpcServerState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<DatabaseBackupRequest>,
+) -> impl IntoResponse {
+    if !addr.ip().is_loopback() {
+        warn!("⛔ Rejected admin request from {}", addr);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"success": false, "message": "Admin endpoints are local only"})),
+        );
+    }
+
+    let Some(admin) = state.admin.clone() else {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            Json(json!({"success": false, "message": "Database backup not available"})),
+        );
+    };
+
+    // Copying the whole database takes a while
+    let output = std::path::PathBuf::from(request.output);
+    let result = tokio::task::spawn_blocking(move || admin.backup_database(&output)).await;
+    match result {
+        Ok(Ok(manifest)) => (StatusCode::OK, Json(manifest)),
+        Ok(Err(e)) => {
+            error!("Database backup failed: {}", e.chain());
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"success": false, "message": e.chain(), "code": e.code()})),
+            )
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"success": false, "message": e.to_string()})),
+        ),
+    }
+}
+
+/// Resume point sent by the explorer: the last height and sequence it has seen.
+/// Without one the feed starts at the next block.
+#[derive(Debug, serde::Deserialize)]
+struct ExplorerFeedQuery {
+    height: Option<u64>,
+    sequence: Option<u32>,
+}
+
+async fn explorer_feed(
+    State(state): State<Arc<RpcServerState>>,
+    Query(query): Query<ExplorerFeedQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let cursor = query.height.map(|height| FeedCursor {
+        height,
+        sequence: query.sequence.unwrap_or(u32::MAX),
+    });
+
+    ws.on_upgrade(move |socket| stream_explorer_feed(socket, state, cursor))
+}
+
+async fn send_item(socket: &mut WebSocket, item: &FeedItem) -> bool {
+    match serde_json::to_string(item) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(e) => {
+            error!("Failed to encode explorer event: {}", e);
+            false
+        }
+    }
+}
+
+async fn stream_explorer_feed(
+    mut socket: WebSocket,
+    state: Arc<RpcServerState>,
+    cursor: Option<FeedCursor>,
+) {
+    // Subscribe before catching up so nothing published meanwhile is lost
+    let mut live = state.explorer.subscribe();
+
+    let mut last_sent = match cursor {
+        Some(cursor) => cursor,
+        None => FeedCursor {
+            height: *state.chain_height.read().await,
+            sequence: u32::MAX,
+        },
+    };
+
+    // History older than the in-memory buffer comes from storage, block by block
+    let chain_height = *state.chain_height.read().await;
+    for height in stored_heights(last_sent, state.explorer.oldest_height(), chain_height) {
+        let items = match stored_items_after(state.storage.as_ref(), height, last_sent) {
+            Ok(Some(items)) => items,
+            Ok(None) => break,
+            Err(e) => {
+                error!("Explorer feed failed to read block {}: {}", height, e);
+                return;
+            }
+        };
+        for item in items {
+            if !send_item(&mut socket, &item).await {
+                return;
+            }
+            last_sent = item.cursor;
+        }
+    }
+
+    for item in state.explorer.items_after(last_sent) {
+        if !send_item(&mut socket, &item).await {
+            return;
+        }
+        last_sent = item.cursor;
+    }
+
+    loop {
+        tokio::select! {
+            received = live.recv() => match received {
+                Ok(item) => {
+                    let is_reorg = matches!(item.event, ExplorerEvent::Reorg { .. });
+                    if item.cursor <= last_sent && !is_reorg {
+                        continue;
+                    }
+                    if !send_item(&mut socket, &item).await {
+                        return;
+                    }
+                    last_sent = item.cursor;
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    // The client resumes from its last cursor on reconnect
+                    warn!("⚠️  Explorer client fell {} events behind, closing", skipped);
+                    let _ = socket.send(Message::Close(None)).await;
+                    return;
+                }
+                Err(RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => {
+                    debug!("Explorer client disconnected");
+                    return;
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,502 @@
+// Typed subscriptions for Rust clients
+// Wraps the node's WebSocket endpoints in async streams of typed events:
+// blocks and an address's transactions come from `/explorer/feed`, validator
+// set changes from `/validators/changes/subscribe`. A background task per
+// subscription reconnects with backoff when the socket drops and resumes where
+// the stream left off: from the last feed cursor, or from the validator change
+// history for the epochs it may have missed. Dropping the subscription stops
+// the task.
+
+use futures::{SinkExt, Stream, StreamExt};
+use spirachain_core::{diversity_epoch, Address};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, warn};
+
+use crate::client::RpcClient;
+use crate::explorer::{ExplorerEvent, FeedCursor, FeedItem};
+use crate::server::MAX_VALIDATOR_HISTORY_EPOCHS;
+use crate::types::ValidatorSetChangeResponse;
+
+/// Events a subscription holds for a slow consumer before it stops reading the socket
+const SUBSCRIPTION_BUFFER: usize = 1024;
+
+/// First reconnection delay, doubled after every failed attempt
+pub const RECONNECT_DELAY_MIN: Duration = Duration::from_secs(1);
+
+/// Longest wait between reconnection attempts
+pub const RECONNECT_DELAY_MAX: Duration = Duration::from_secs(30);
+
+/// A block applied by the node
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockNotification {
+    pub height: u64,
+    pub hash: String,
+    pub previous_hash: String,
+    pub timestamp: u64,
+    pub validator: String,
+    pub tx_count: usize,
+    pub fees: String,
+}
+
+/// A transaction in an applied block
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionNotification {
+    pub height: u64,
+    pub tx_hash: String,
+    /// Position in the block
+    pub index: usize,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub fee: String,
+    pub coinbase: bool,
+}
+
+/// An event from the explorer feed, or a reorg that invalidates earlier ones
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedUpdate<T> {
+    Event(T),
+    /// Events above `common_height` were replaced; drop anything seen past it
+    Reorg {
+        common_height: u64,
+    },
+}
+
+fn block_update(item: FeedItem) -> Option<FeedUpdate<BlockNotification>> {
+    match item.event {
+        ExplorerEvent::Block {
+            hash,
+            previous_hash,
+            timestamp,
+            validator,
+            tx_count,
+            fees,
+        } => Some(FeedUpdate::Event(BlockNotification {
+            height: item.cursor.height,
+            hash,
+            previous_hash,
+            timestamp,
+            validator,
+            tx_count,
+            fees,
+        })),
+        ExplorerEvent::Reorg { common_height } => Some(FeedUpdate::Reorg { common_height }),
+        _ => None,
+    }
+}
+
+fn transaction_update(
+    item: FeedItem,
+    address: &str,
+) -> Option<FeedUpdate<TransactionNotification>> {
+    match item.event {
+        ExplorerEvent::Receipt {
+            tx_hash,
+            index,
+            from,
+            to,
+            amount,
+            fee,
+            coinbase,
+        } if from == address || to == address => Some(FeedUpdate::Event(TransactionNotification {
+            height: item.cursor.height,
+            tx_hash,
+            index,
+            from,
+            to,
+            amount,
+            fee,
+            coinbase,
+        })),
+        ExplorerEvent::Reorg { common_height } => Some(FeedUpdate::Reorg { common_height }),
+        _ => None,
+    }
+}
+
+/// Stream of typed events from one subscription. `next()` returns `None` only
+/// once the subscription can no longer deliver anything.
+pub struct Subscription<T> {
+    receiver: mpsc::Receiver<(Option<FeedCursor>, T)>,
+    cursor: Option<FeedCursor>,
+    task: JoinHandle<()>,
+}
+
+impl<T> Subscription<T> {
+    pub async fn next(&mut self) -> Option<T> {
+        let (cursor, event) = self.receiver.recv().await?;
+        if cursor.is_some() {
+            self.cursor = cursor;
+        }
+        Some(event)
+    }
+
+    /// Feed position of the last event returned; pass it to `blocks_from` or
+    /// `transactions_from` to pick up there after a restart. Always `None`
+    /// for validator events.
+    pub fn cursor(&self) -> Option<FeedCursor> {
+        self.cursor
+    }
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.receiver.poll_recv(cx) {
+            Poll::Ready(Some((cursor, event))) => {
+                if cursor.is_some() {
+                    self.cursor = cursor;
+                }
+                Poll::Ready(Some(event))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Clone)]
+pub struct RpcSubscriptionClient {
+    ws_url: String,
+    http: RpcClient,
+    reconnect_min: Duration,
+    reconnect_max: Duration,
+}
+
+impl RpcSubscriptionClient {
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            ws_url: format!("ws://{}:{}", host, port),
+            http: RpcClient::new(host, port),
+            reconnect_min: RECONNECT_DELAY_MIN,
+            reconnect_max: RECONNECT_DELAY_MAX,
+        }
+    }
+
+    /// Bounds of the exponential backoff between reconnection attempts
+    pub fn with_reconnect_delay(mut self, min: Duration, max: Duration) -> Self {
+        self.reconnect_min = min;
+        self.reconnect_max = max.max(min);
+        self
+    }
+
+    /// Blocks applied from now on
+    pub fn blocks(&self) -> Subscription<FeedUpdate<BlockNotification>> {
+        self.feed(None, block_update)
+    }
+
+    /// Blocks after `cursor`, replayed from the node's history first
+    pub fn blocks_from(&self, cursor: FeedCursor) -> Subscription<FeedUpdate<BlockNotification>> {
+        self.feed(Some(cursor), block_update)
+    }
+
+    /// Transactions sent from or to `address` from now on
+    pub fn transactions(
+        &self,
+        address: Address,
+    ) -> Subscription<FeedUpdate<TransactionNotification>> {
+        let address = address.to_string();
+        self.feed(None, move |item| transaction_update(item, &address))
+    }
+
+    /// Transactions sent from or to `address` after `cursor`
+    pub fn transactions_from(
+        &self,
+        address: Address,
+        cursor: FeedCursor,
+    ) -> Subscription<FeedUpdate<TransactionNotification>> {
+        let address = address.to_string();
+        self.feed(Some(cursor), move |item| transaction_update(item, &address))
+    }
+
+    /// Validator set changes from now on
+    pub fn validator_events(&self) -> Subscription<ValidatorSetChangeResponse> {
+        let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let task = tokio::spawn(self.clone().run_validator_events(sender));
+        Subscription {
+            receiver,
+            cursor: None,
+            task,
+        }
+    }
+
+    fn feed<T, F>(&self, cursor: Option<FeedCursor>, filter: F) -> Subscription<T>
+    where
+        T: Send + 'static,
+        F: Fn(FeedItem) -> Option<T> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let task = tokio::spawn(self.clone().run_feed(cursor, filter, sender));
+        Subscription {
+            receiver,
+            cursor,
+            task,
+        }
+    }
+
+    async fn run_feed<T, F>(
+        self,
+        mut cursor: Option<FeedCursor>,
+        filter: F,
+        sender: mpsc::Sender<(Option<FeedCursor>, T)>,
+    ) where
+        F: Fn(FeedItem) -> Option<T>,
+    {
+        let mut delay = self.reconnect_min;
+        loop {
+            let mut url = format!("{}/explorer/feed", self.ws_url);
+            if let Some(cursor) = cursor {
+                url.push_str(&format!(
+                    "?height={}&sequence={}",
+                    cursor.height, cursor.sequence
+                ));
+            }
+
+            match tokio_tungstenite::connect_async(url.as_str()).await {
+                Ok((mut socket, _)) => {
+                    debug!("Subscribed to {}", url);
+                    delay = self.reconnect_min;
+                    while let Some(message) = socket.next().await {
+                        let text = match message {
+                            Ok(Message::Text(text)) => text,
+                            Ok(Message::Ping(payload)) => {
+                                let _ = socket.send(Message::Pong(payload)).await;
+                                continue;
+                            }
+                            Ok(Message::Close(_)) | Err(_) => break,
+                            Ok(_) => continue,
+                        };
+                        let item: FeedItem = match serde_json::from_str(&text) {
+                            Ok(item) => item,
+                            Err(e) => {
+                                warn!("Ignoring malformed explorer event: {}", e);
+                                continue;
+                            }
+                        };
+                        let item_cursor = item.cursor;
+                        cursor = Some(item_cursor);
+                        if let Some(event) = filter(item) {
+                            if sender.send((Some(item_cursor), event)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    warn!("Explorer feed connection closed, reconnecting");
+                }
+                Err(e) => warn!("Failed to connect to {}: {}", url, e),
+            }
+
+            if sender.is_closed() {
+                return;
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(self.reconnect_max);
+        }
+    }
+
+    async fn run_validator_events(
+        self,
+        sender: mpsc::Sender<(Option<FeedCursor>, ValidatorSetChangeResponse)>,
+    ) {
+        let url = format!("{}/validators/changes/subscribe", self.ws_url);
+        let mut seen = ValidatorChangesSeen::default();
+        let mut delay = self.reconnect_min;
+        loop {
+            match tokio_tungstenite::connect_async(url.as_str()).await {
+                Ok((mut socket, _)) => {
+                    debug!("Subscribed to {}", url);
+                    delay = self.reconnect_min;
+
+                    // The socket buffers live changes while the missed ones are fetched
+                    if let Some(height) = seen.height {
+                        match self.validator_changes_since(height).await {
+                            Ok(changes) => {
+                                for change in changes {
+                                    if seen.is_new(&change)
+                                        && sender.send((None, change)).await.is_err()
+                                    {
+                                        return;
+                                    }
+                                }
+                            }
+                            Err(e) => warn!("Failed to fetch missed validator changes: {}", e),
+                        }
+                    }
+
+                    while let Some(message) = socket.next().await {
+                        let text = match message {
+                            Ok(Message::Text(text)) => text,
+                            Ok(Message::Ping(payload)) => {
+                                let _ = socket.send(Message::Pong(payload)).await;
+                                continue;
+                            }
+                            Ok(Message::Close(_)) | Err(_) => break,
+                            Ok(_) => continue,
+                        };
+                        let change: ValidatorSetChangeResponse = match serde_json::from_str(&text) {
+                            Ok(change) => change,
+                            Err(e) => {
+                                warn!("Ignoring malformed validator change: {}", e);
+                                continue;
+                            }
+                        };
+                        if seen.is_new(&change) && sender.send((None, change)).await.is_err() {
+                            return;
+                        }
+                    }
+                    warn!("Validator change subscription closed, reconnecting");
+                }
+                Err(e) => warn!("Failed to connect to {}: {}", url, e),
+            }
+
+            if sender.is_closed() {
+                return;
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(self.reconnect_max);
+        }
+    }
+
+    /// Changes from the epoch of `height` to the current one, oldest first
+    async fn validator_changes_since(
+        &self,
+        height: u64,
+    ) -> anyhow::Result<Vec<ValidatorSetChangeResponse>> {
+        let current_epoch = diversity_epoch(self.http.get_status().await?.chain_height);
+        let mut changes = Vec::new();
+        let mut from_epoch = diversity_epoch(height);
+        while from_epoch <= current_epoch {
+            let to_epoch = (from_epoch + MAX_VALIDATOR_HISTORY_EPOCHS - 1).min(current_epoch);
+            changes.extend(
+                self.http
+                    .get_validator_changes(from_epoch, to_epoch)
+                    .await?
+                    .changes,
+            );
+            from_epoch = to_epoch + 1;
+        }
+        Ok(changes)
+    }
+}
+
+/// Resume point of a validator change subscription: the highest height
+/// delivered and the changes delivered at it, since one block can hold several
+#[derive(Default)]
+struct ValidatorChangesSeen {
+    height: Option<u64>,
+    at_height: Vec<ValidatorSetChangeResponse>,
+}
+
+impl ValidatorChangesSeen {
+    /// Record `change` and return whether it was not delivered before
+    fn is_new(&mut self, change: &ValidatorSetChangeResponse) -> bool {
+        match self.height {
+            Some(height) if change.height < height => false,
+            Some(height) if change.height == height => {
+                if self.at_height.contains(change) {
+                    false
+                } else {
+                    self.at_height.push(change.clone());
+                    true
+                }
+            }
+            _ => {
+                self.height = Some(change.height);
+                self.at_height = vec![change.clone()];
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ValidatorChangeDto;
+
+    fn item(height: u64, sequence: u32, event: ExplorerEvent) -> FeedItem {
+        FeedItem {
+            cursor: FeedCursor { height, sequence },
+            event,
+        }
+    }
+
+    fn receipt(from: &str, to: &str) -> ExplorerEvent {
+        ExplorerEvent::Receipt {
+            tx_hash: "0xab".to_string(),
+            index: 0,
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: "10".to_string(),
+            fee: "1".to_string(),
+            coinbase: false,
+        }
+    }
+
+    #[test]
+    fn test_feed_items_map_to_typed_updates() {
+        let block = item(
+            7,
+            0,
+            ExplorerEvent::Block {
+                hash: "0x01".to_string(),
+                previous_hash: "0x00".to_string(),
+                timestamp: 1,
+                validator: "0xaa".to_string(),
+                tx_count: 1,
+                fees: "1".to_string(),
+            },
+        );
+        match block_update(block.clone()) {
+            Some(FeedUpdate::Event(block)) => assert_eq!(block.height, 7),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(transaction_update(block, "0xaa"), None);
+
+        let reorg = item(5, u32::MAX, ExplorerEvent::Reorg { common_height: 5 });
+        assert_eq!(
+            block_update(reorg.clone()),
+            Some(FeedUpdate::Reorg { common_height: 5 })
+        );
+        assert_eq!(
+            transaction_update(reorg, "0xaa"),
+            Some(FeedUpdate::Reorg { common_height: 5 })
+        );
+
+        assert!(transaction_update(item(7, 1, receipt("0xaa", "0xbb")), "0xbb").is_some());
+        assert!(transaction_update(item(7, 1, receipt("0xaa", "0xbb")), "0xcc").is_none());
+        assert!(block_update(item(7, 1, receipt("0xaa", "0xbb"))).is_none());
+    }
+
+    #[test]
+    fn test_validator_changes_are_delivered_once() {
+        let change = |validator: &str, height: u64| ValidatorSetChangeResponse {
+            validator: validator.to_string(),
+            height,
+            epoch: 0,
+            timestamp: height,
+            change: ValidatorChangeDto::Joined,
+        };
+        let mut seen = ValidatorChangesSeen::default();
+        assert!(seen.is_new(&change("a", 10)));
+        assert!(seen.is_new(&change("b", 10)));
+
+        // Replayed from history after a reconnect
+        assert!(!seen.is_new(&change("a", 10)));
+        assert!(!seen.is_new(&change("b", 10)));
+        assert!(!seen.is_new(&change("c", 9)));
+        assert!(seen.is_new(&change("c", 11)));
+        assert!(!seen.is_new(&change("c", 11)));
+    }
+}
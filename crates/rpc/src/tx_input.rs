@@ -0,0 +1,280 @@
+// Transaction input validation
+// `/submit_transaction` used to take anything serde could turn into a
+// Transaction, so a client bug surfaced as one opaque serde message, or not at
+// all until the mempool or block validation refused the transaction. Raw
+// transactions are now checked field by field at the boundary, before they are
+// decoded: addresses (32 bytes, or hex whose mixed case matches the checksum),
+// amount and fee (whole base units within the supply), purpose length and the
+// semantic vector dimension. Every malformed field is reported, by name.
+
+use crate::types::FieldError;
+use serde_json::{Map, Value};
+use spirachain_core::{
+    Address, Transaction, MAX_PURPOSE_SIZE, MAX_SUPPLY, SEMANTIC_VECTOR_DIM, TOKEN_DECIMALS,
+};
+
+/// Decode a hex-encoded JSON transaction, as sent by [`crate::RpcClient`],
+/// refusing it with every malformed field
+pub fn parse_transaction_input(tx_hex: &str) -> Result<Transaction, Vec<FieldError>> {
+    let tx_bytes = hex::decode(tx_hex.trim())
+        .map_err(|e| vec![field_error("tx_hex", format!("invalid hex: {}", e))])?;
+    let value: Value = serde_json::from_slice(&tx_bytes)
+        .map_err(|e| vec![field_error("tx_hex", format!("not JSON: {}", e))])?;
+    let Some(fields) = value.as_object() else {
+        return Err(vec![field_error(
+            "tx_hex",
+            "expected a JSON object".to_string(),
+        )]);
+    };
+
+    let mut errors = Vec::new();
+    for field in ["from", "to"] {
+        check_address(fields, field, &mut errors);
+    }
+    for field in ["amount", "fee"] {
+        check_amount(fields, field, &mut errors);
+    }
+    check_purpose(fields, &mut errors);
+    check_semantic_vector(fields, &mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Numbers above u64 are floats in `Value`; the exact bounds are checked on
+    // the decoded amounts
+    let tx: Transaction = serde_json::from_slice(&tx_bytes)
+        .map_err(|e| vec![field_error("tx_hex", format!("invalid transaction: {}", e))])?;
+    if tx.amount.value().saturating_add(tx.fee.value()) > MAX_SUPPLY {
+        return Err(vec![field_error(
+            "amount",
+            format!(
+                "amount plus fee is {} base units, more than the supply of {}",
+                tx.amount.value().saturating_add(tx.fee.value()),
+                MAX_SUPPLY
+            ),
+        )]);
+    }
+    Ok(tx)
+}
+
+/// One line listing every error, for responses without a field list
+pub fn describe_field_errors(errors: &[FieldError]) -> String {
+    let fields: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    format!("Invalid transaction input: {}", fields.join("; "))
+}
+
+fn field_error(field: &str, message: String) -> FieldError {
+    FieldError {
+        field: field.to_string(),
+        message,
+    }
+}
+
+fn check_address(fields: &Map<String, Value>, field: &str, errors: &mut Vec<FieldError>) {
+    let message = match fields.get(field) {
+        None => "missing".to_string(),
+        Some(Value::String(hex)) => match Address::from_checksummed(hex) {
+            Ok(_) => return,
+            Err(e) => e,
+        },
+        Some(Value::Array(bytes)) if bytes.len() != 32 => {
+            format!("expected 32 bytes, got {}", bytes.len())
+        }
+        Some(Value::Array(bytes)) => {
+            match bytes
+                .iter()
+                .position(|byte| byte.as_u64().is_none_or(|byte| byte > 255))
+            {
+                None => return,
+                Some(i) => format!("byte {} is {}, not 0 to 255", i, bytes[i]),
+            }
+        }
+        Some(other) => format!(
+            "expected a hex string or 32 bytes, got {}",
+            json_type(other)
+        ),
+    };
+    errors.push(field_error(field, message));
+}
+
+fn check_amount(fields: &Map<String, Value>, field: &str, errors: &mut Vec<FieldError>) {
+    let message = match fields.get(field) {
+        None => "missing".to_string(),
+        Some(Value::Number(number)) if number.is_u64() => return,
+        Some(Value::Number(number)) if number.is_i64() => {
+            format!("{} is negative", number)
+        }
+        Some(Value::Number(number)) => {
+            let value = number.as_f64().unwrap_or(f64::NAN);
+            if value < 0.0 {
+                format!("{} is negative", number)
+            } else if value.fract() != 0.0 {
+                format!(
+                    "{} is not a whole number of base units (10^-{} QBT)",
+                    number, TOKEN_DECIMALS
+                )
+            } else if value > MAX_SUPPLY as f64 {
+                format!(
+                    "{} base units is more than the supply of {}",
+                    number, MAX_SUPPLY
+                )
+            } else {
+                return;
+            }
+        }
+        Some(other) => format!(
+            "expected an integer in base units, got {}",
+            json_type(other)
+        ),
+    };
+    errors.push(field_error(field, message));
+}
+
+fn check_purpose(fields: &Map<String, Value>, errors: &mut Vec<FieldError>) {
+    let message = match fields.get("purpose") {
+        None => "missing".to_string(),
+        Some(Value::String(purpose)) if purpose.len() > MAX_PURPOSE_SIZE => format!(
+            "{} bytes, at most {} allowed",
+            purpose.len(),
+            MAX_PURPOSE_SIZE
+        ),
+        Some(Value::String(_)) => return,
+        Some(other) => format!("expected a string, got {}", json_type(other)),
+    };
+    errors.push(field_error("purpose", message));
+}
+
+/// Optional, but a vector that is sent must be a whole embedding
+fn check_semantic_vector(fields: &Map<String, Value>, errors: &mut Vec<FieldError>) {
+    let message = match fields.get("semantic_vector") {
+        None | Some(Value::Null) => return,
+        Some(Value::Array(vector)) if vector.is_empty() => return,
+        Some(Value::Array(vector)) if vector.len() != SEMANTIC_VECTOR_DIM => format!(
+            "{} dimensions, expected {} or none",
+            vector.len(),
+            SEMANTIC_VECTOR_DIM
+        ),
+        Some(Value::Array(vector)) => {
+            let bad = vector.iter().position(|component| {
+                component
+                    .as_f64()
+                    .is_none_or(|component| !(component as f32).is_finite())
+            });
+            match bad {
+                None => return,
+                Some(i) => format!(
+                    "component {} is {}, not a finite 32-bit float",
+                    i, vector[i]
+                ),
+            }
+        }
+        Some(other) => format!("expected an array of numbers, got {}", json_type(other)),
+    };
+    errors.push(field_error("semantic_vector", message));
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::Amount;
+
+    fn encode(value: &Value) -> String {
+        hex::encode(serde_json::to_vec(value).unwrap())
+    }
+
+    fn sample() -> Transaction {
+        Transaction::new(
+            Address::new([1; 32]),
+            Address::new([0xab; 32]),
+            Amount::qbt(5),
+            Amount::new(spirachain_core::MIN_TX_FEE),
+        )
+        .with_purpose("Rent")
+        .with_semantic_vector(vec![0.5; SEMANTIC_VECTOR_DIM])
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|error| error.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_well_formed_transactions_decode() {
+        let tx = sample();
+        let decoded =
+            parse_transaction_input(&hex::encode(serde_json::to_vec(&tx).unwrap())).unwrap();
+        assert_eq!(decoded.tx_hash, tx.tx_hash);
+
+        // The recipient as a checksummed string is the same transaction
+        let mut value = serde_json::to_value(&tx).unwrap();
+        value["to"] = Value::String(tx.to.to_checksummed());
+        assert_eq!(parse_transaction_input(&encode(&value)).unwrap().to, tx.to);
+    }
+
+    #[test]
+    fn test_every_malformed_field_is_reported() {
+        let mut value = serde_json::to_value(sample()).unwrap();
+        value["from"] = serde_json::json!([1, 2, 3]);
+        let mut typo = sample().to.to_checksummed();
+        let i = typo.rfind(|c: char| c.is_ascii_uppercase()).unwrap();
+        typo.replace_range(i..=i, &typo[i..=i].to_lowercase());
+        value["to"] = Value::String(typo);
+        value["amount"] = serde_json::json!(-5);
+        value["fee"] = serde_json::json!(1.5);
+        value["purpose"] = Value::String("x".repeat(MAX_PURPOSE_SIZE + 1));
+        value["semantic_vector"] = serde_json::json!([0.1, 0.2, 0.3]);
+
+        let errors = parse_transaction_input(&encode(&value)).unwrap_err();
+        assert_eq!(
+            fields(&errors),
+            vec!["from", "to", "amount", "fee", "purpose", "semantic_vector"]
+        );
+        assert_eq!(errors[0].message, "expected 32 bytes, got 3");
+        assert!(errors[1].message.contains("checksum mismatch"));
+        assert!(errors[5].message.contains("3 dimensions"));
+        assert!(describe_field_errors(&errors).contains("purpose: 513 bytes"));
+
+        value = serde_json::to_value(sample()).unwrap();
+        value["from"][7] = serde_json::json!(300);
+        value["semantic_vector"][9] = Value::Null;
+        value.as_object_mut().unwrap().remove("purpose");
+        let errors = parse_transaction_input(&encode(&value)).unwrap_err();
+        assert_eq!(errors[0].message, "byte 7 is 300, not 0 to 255");
+        assert_eq!(errors[1], field_error("purpose", "missing".to_string()));
+        assert!(errors[2].message.starts_with("component 9 is null"));
+
+        assert_eq!(
+            fields(&parse_transaction_input("zz").unwrap_err()),
+            vec!["tx_hex"]
+        );
+        assert_eq!(
+            fields(&parse_transaction_input(&encode(&serde_json::json!([1]))).unwrap_err()),
+            vec!["tx_hex"]
+        );
+    }
+
+    #[test]
+    fn test_amounts_are_bounded_by_the_supply() {
+        let mut tx = sample();
+        tx.amount = Amount::new(MAX_SUPPLY);
+        let errors =
+            parse_transaction_input(&hex::encode(serde_json::to_vec(&tx).unwrap())).unwrap_err();
+        assert_eq!(fields(&errors), vec!["amount"]);
+        assert!(errors[0].message.contains("amount plus fee"));
+
+        tx.amount = Amount::new(u128::MAX);
+        let errors =
+            parse_transaction_input(&hex::encode(serde_json::to_vec(&tx).unwrap())).unwrap_err();
+        assert!(errors[0].message.contains("more than the supply"));
+    }
+}
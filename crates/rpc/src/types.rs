@@ -1,4 +1,40 @@
+//! Request/response types of the JSON APIs.
+//!
+//! Canonical JSON, shared by the RPC server and the REST API:
+//! - hashes, addresses and public keys: `0x`-prefixed lowercase hex
+//! - amounts (u128 base units): decimal strings, never JSON numbers
+//! - heights, timestamps (ms) and nonces: JSON numbers
+//! - opaque byte blobs (signatures, contract code and input, extra data):
+//!   standard base64 with padding
+//!
+//! The DTOs below are frozen by golden files in `crates/rpc/golden`; changing
+//! one is an API break.
+
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use spirachain_core::{
+    Amount, Block, ContinuityProof, EntityType, EpochSummary, ErrorCategory, IntentType,
+    SpiraChainError, SpiralFormula, Transaction, TransactionPayload, ValidatorChange,
+    ValidatorSetChange,
+};
+use std::collections::BTreeMap;
+
+use crate::mempool::{DropReason, MempoolDrop};
+
+/// `0x`-prefixed lowercase hex
+pub fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Standard base64 with padding
+pub fn encode_base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Base units as a decimal string
+pub fn encode_amount(amount: Amount) -> String {
+    amount.value().to_string()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmitTransactionRequest {
@@ -10,6 +46,83 @@ pub struct SubmitTransactionResponse {
     pub success: bool,
     pub tx_hash: String,
     pub message: String,
+    /// Error code when the transaction was refused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+    /// Every malformed field when the input itself was refused
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_errors: Vec<FieldError>,
+}
+
+/// A transaction field refused at the RPC boundary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Outcome of executing a transaction against a copy of the latest state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulateTransactionResponse {
+    pub success: bool,
+    pub tx_hash: String,
+    /// Why the transaction would be rejected or fail to execute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Height the transaction was executed at
+    pub height: u64,
+    pub fee: String,
+    /// Lowest fee the node accepts for this transaction
+    pub min_fee: String,
+    pub sender_balance: String,
+    pub sender_balance_after: String,
+    pub sender_nonce: u64,
+    /// Gas used by the VM, for contract deployments and calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    pub semantic_coherence: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratePiIdentifierRequest {
+    /// Digits of π in the identifier
+    pub length: usize,
+    #[serde(default)]
+    pub include_spiral: bool,
+    /// What the identifier names; identifiers for the same entity in the
+    /// same millisecond are equal. A fresh entity is used when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<String>,
+    /// Record the identifier's hash on-chain to timestamp it
+    #[serde(default)]
+    pub anchor: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratePiIdentifierResponse {
+    pub identifier: String,
+    pub pi_sequence: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spiral_component: Option<String>,
+    pub timestamp_component: String,
+    pub uniqueness_score: f64,
+    /// Entity hash and fork id the identifier is bound to
+    pub entity: String,
+    pub fork_id: String,
+    pub timestamp_ms: u64,
+    /// blake3 of the identifier, what anchoring records
+    pub identifier_hash: String,
+    /// Transaction carrying `identifier_hash`, once it is in the mempool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_tx_hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +146,27 @@ pub struct GetBalanceResponse {
     pub balance: String,
 }
 
+/// Block rewards an address has earned but not claimed yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardsResponse {
+    pub address: String,
+    pub unclaimed_rewards: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorInfoResponse {
+    pub address: String,
+    pub stake: String,
+    /// Where the validator's block rewards are credited; the validator itself
+    /// unless it set a payout address
+    pub payout_address: String,
+    /// Unclaimed rewards held by the payout address
+    pub unclaimed_rewards: String,
+    /// Transactions the validator's account has executed
+    #[serde(default)]
+    pub nonce: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetStatusResponse {
     pub chain_height: u64,
@@ -40,9 +174,849 @@ pub struct GetStatusResponse {
     pub connected_peers: usize,
     pub is_validator: bool,
     pub is_syncing: bool,
+    /// Fork id new transactions must be signed for
+    #[serde(default)]
+    pub fork_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolTxSummary {
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub fee: String,
+    /// Serialized bytes
+    pub size: usize,
+    /// Seconds since the transaction was signed
+    pub age_secs: u64,
+    pub intent: Option<IntentType>,
+    pub purpose: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolContentResponse {
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+    pub transactions: Vec<MempoolTxSummary>,
+}
+
+/// Transactions paying `min_fee <= fee < max_fee`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeBucket {
+    pub min_fee: String,
+    pub max_fee: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MempoolStatsResponse {
+    pub size: usize,
+    pub max_size: usize,
+    pub bytes: usize,
+    pub fee_histogram: Vec<FeeBucket>,
+    /// Transactions turned away since the node started, by reason
+    pub rejected: BTreeMap<DropReason, u64>,
+    /// Accepted transactions dropped before inclusion, by reason
+    pub evicted: BTreeMap<DropReason, u64>,
+    pub recent_drops: Vec<MempoolDrop>,
+}
+
+/// Progress of catching up with the best peer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncStatusResponse {
+    pub syncing: bool,
+    pub current_height: u64,
+    /// Highest height announced by a peer
+    pub target_height: u64,
+    pub progress_percent: f64,
+    /// `None` while no blocks are arriving
+    pub eta_seconds: Option<u64>,
+    pub peers: usize,
+    pub blocks_per_second: f64,
+}
+
+/// How long compact block announcements took to reach this node
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PropagationStatsResponse {
+    /// Announcements the percentiles cover
+    pub samples: usize,
+    /// Announcements measured since the node started
+    pub total: u64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+    /// Share of announcements that came through at least one relay
+    pub relayed_ratio: f64,
+}
+
+/// Bandwidth saved and CPU spent by one gossip compression codec
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodecStatsResponse {
+    pub codec: String,
+    pub messages_sent: u64,
+    pub raw_bytes: u64,
+    pub wire_bytes: u64,
+    /// Wire bytes per raw byte sent
+    pub ratio: f64,
+    pub compress_micros: u64,
+    pub messages_received: u64,
+    pub decompress_micros: u64,
+}
+
+/// Gossip compression since the node started
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompressionStatsResponse {
+    /// Codec the node prefers for large payloads
+    pub configured: String,
+    pub codecs: Vec<CodecStatsResponse>,
+    /// Payloads sent with a cheaper codec because a peer could not read the configured one
+    pub fallbacks: u64,
+    /// Compressed payloads re-published uncompressed for such peers
+    pub relayed_uncompressed: u64,
+}
+
+/// Consensus limits a transaction or block must fit in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainLimitsResponse {
+    pub network: String,
+    pub max_block_size: usize,
+    pub max_tx_per_block: usize,
+    pub max_header_extra_data: usize,
+    pub max_contract_code_size: usize,
+    pub max_contract_input_size: usize,
+    /// Base units, as a decimal string
+    pub min_tx_fee: String,
+}
+
+/// Build provenance of the node binary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionResponse {
+    pub version: String,
+    pub git_commit: String,
+    /// Unix seconds
+    pub build_timestamp: u64,
+    /// Optional components compiled in: `semantic`, `python-bridge`, `quic`
+    pub features: Vec<String>,
+    /// Highest consensus protocol version the binary validates
+    pub protocol_version: u32,
+    pub network: String,
+}
+
+impl Default for VersionResponse {
+    /// What the RPC crate knows on its own; nodes supply the real build info
+    fn default() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: "unknown".to_string(),
+            build_timestamp: 0,
+            features: Vec::new(),
+            protocol_version: spirachain_core::PROTOCOL_VERSION,
+            network: "testnet".to_string(),
+        }
+    }
+}
+
+/// Latest finalized block; light clients can trust the chain up to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointResponse {
+    pub height: u64,
+    pub block_hash: String,
+}
+
+/// Blocks in `from..=to` whose event bloom may match the filter. Blocks without
+/// a bloom are always candidates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventFilterResponse {
+    pub from: u64,
+    pub to: u64,
+    pub candidates: Vec<u64>,
+}
+
+/// Merkle path of one transaction, checkable against the header's merkle root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TxProofResponse {
+    pub block_height: u64,
+    pub block_hash: String,
+    pub merkle_root: String,
+    pub tx_hash: String,
+    pub index: u32,
+    pub siblings: Vec<String>,
+    /// Header version, which selects the merkle hashing rules
+    #[serde(default)]
+    pub block_version: u64,
+}
+
+/// Spiral continuity of a block on its parent, as validated by the node;
+/// light clients re-run the rule on these values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContinuityProofResponse {
+    pub block_height: u64,
+    pub block_hash: String,
+    pub parent_hash: String,
+    pub parent_complexity: f64,
+    pub complexity: f64,
+    pub squared_jump: f64,
+    pub coherence: f64,
+}
+
+impl From<&ContinuityProof> for ContinuityProofResponse {
+    fn from(proof: &ContinuityProof) -> Self {
+        Self {
+            block_height: proof.height,
+            block_hash: proof.block_hash.to_string(),
+            parent_hash: proof.parent_hash.to_string(),
+            parent_complexity: proof.parent_complexity,
+            complexity: proof.child_complexity,
+            squared_jump: proof.squared_jump,
+            coherence: proof.coherence,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorParticipationDto {
+    pub address: String,
+    pub participated: bool,
+}
+
+/// On-chain summary of an epoch, carried as transaction `tx_index` of the
+/// next epoch's first block; `/block/:height/proof/:index` proves it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpochSummaryResponse {
+    pub epoch: u64,
+    pub block_height: u64,
+    pub block_hash: String,
+    pub tx_index: usize,
+    pub validators: Vec<ValidatorParticipationDto>,
+    pub blocks: u64,
+    pub total_fees: String,
+    pub reward_root: String,
+}
+
+impl EpochSummaryResponse {
+    pub fn new(summary: &EpochSummary, block: &Block) -> Self {
+        Self {
+            epoch: summary.epoch,
+            block_height: block.header.block_height,
+            block_hash: block.hash().to_string(),
+            tx_index: 1,
+            validators: summary
+                .validators
+                .iter()
+                .enumerate()
+                .map(|(index, address)| ValidatorParticipationDto {
+                    address: address.to_string(),
+                    participated: summary.participated(index),
+                })
+                .collect(),
+            blocks: summary.blocks,
+            total_fees: encode_amount(summary.total_fees),
+            reward_root: summary.reward_root.to_string(),
+        }
+    }
+}
+
+/// Answer of `/estimate_fee`: the fee recommended for inclusion within
+/// `target_blocks` at `confidence`, bracketed by the 50% and 95% fees
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeEstimateResponse {
+    pub target_blocks: u64,
+    pub fee: String,
+    pub low: String,
+    pub high: String,
+    pub confidence: f64,
+    /// Recently included transactions the estimate is based on
+    pub samples: usize,
+    pub pending: usize,
+    /// Intent the estimate was asked for
+    #[serde(default)]
+    pub intent: Option<IntentType>,
+    /// Whether the estimate used only that intent's transactions; without
+    /// enough of them it falls back to all transactions
+    #[serde(default)]
+    pub intent_specific: bool,
+    #[serde(default)]
+    pub by_intent: Vec<IntentFeeEstimate>,
+}
+
+/// Recommended fee and typical wait of recently included transactions with
+/// one intent, `None` for those declaring none
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntentFeeEstimate {
+    pub intent: Option<IntentType>,
+    pub samples: usize,
+    pub median_delay_blocks: u64,
+    pub fee: String,
+}
+
+/// Result of `/message/verify`; `error` says why an invalid signature failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifyMessageResponse {
+    pub valid: bool,
+    pub address: String,
+    pub error: Option<String>,
+}
+
+/// Spiral kinds one validator produced in the epoch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorDiversityResponse {
+    pub address: String,
+    pub blocks: u64,
+    pub distinct_kinds: usize,
+    /// Distinct kinds per block produced
+    pub score: f64,
+    pub kinds: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiversityStatsResponse {
+    pub epoch: u64,
+    pub epoch_blocks: u64,
+    /// Epochs the node still holds, oldest first
+    pub available_epochs: Vec<u64>,
+    pub validators: Vec<ValidatorDiversityResponse>,
+}
+
+/// Entity named in an epoch's transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityCountResponse {
+    pub name: String,
+    pub entity_type: EntityType,
+    pub count: u64,
+}
+
+/// Semantic aggregates of one epoch's transactions, coinbases excluded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpochSemanticsResponse {
+    pub epoch: u64,
+    pub first_height: u64,
+    /// Last block aggregated so far
+    pub last_height: u64,
+    /// False while the epoch is still being produced
+    pub complete: bool,
+    pub transactions: u64,
+    /// Transactions per intent type
+    pub intents: BTreeMap<String, u64>,
+    /// Transactions without a classified intent
+    pub unclassified: u64,
+    /// Most mentioned entities, most frequent first
+    pub top_entities: Vec<EntityCountResponse>,
+    pub average_coherence: f64,
+}
+
+/// Custom spiral registered on-chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomSpiralResponse {
+    pub id: String,
+    pub name: String,
+    pub formula: SpiralFormula,
+    pub turns: u32,
+    pub registrant: String,
+    pub height: u64,
+}
+
+/// Active registration of a `.spira` name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameResponse {
+    pub name: String,
+    /// Address the name resolves to
+    pub address: String,
+    pub owner: String,
+    pub registered_at: u64,
+    pub expires_at: u64,
+}
+
+/// What happened to a validator, tagged by `kind`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValidatorChangeDto {
+    Joined,
+    Left,
+    Slashed { reason: String, amount: String },
+    KeyRotated { old_key: String, new_key: String },
+}
+
+/// A validator set change, as listed on `/validators/changes` and streamed
+/// on `/validators/changes/subscribe`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorSetChangeResponse {
+    pub validator: String,
+    pub height: u64,
+    pub epoch: u64,
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub change: ValidatorChangeDto,
+}
+
+impl From<&ValidatorSetChange> for ValidatorSetChangeResponse {
+    fn from(change: &ValidatorSetChange) -> Self {
+        Self {
+            validator: change.validator.to_string(),
+            height: change.height,
+            epoch: change.epoch(),
+            timestamp: change.timestamp,
+            change: match &change.change {
+                ValidatorChange::Joined => ValidatorChangeDto::Joined,
+                ValidatorChange::Left => ValidatorChangeDto::Left,
+                ValidatorChange::Slashed { reason, amount } => ValidatorChangeDto::Slashed {
+                    reason: reason.clone(),
+                    amount: encode_amount(*amount),
+                },
+                ValidatorChange::KeyRotated { old_key, new_key } => {
+                    ValidatorChangeDto::KeyRotated {
+                        old_key: encode_hex(old_key),
+                        new_key: encode_hex(new_key),
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Validator set changes of an epoch range, oldest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorSetHistoryResponse {
+    pub from_epoch: u64,
+    pub to_epoch: u64,
+    pub changes: Vec<ValidatorSetChangeResponse>,
+}
+
+/// Slots of a leader schedule epoch led by one validator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorSlots {
+    pub address: String,
+    pub slots: Vec<u64>,
+}
+
+/// Leader schedule of an epoch. Slot `n` starts at `n * slot_duration_secs`
+/// Unix seconds, so a slot without a block is missed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotScheduleResponse {
+    pub epoch: u64,
+    pub first_slot: u64,
+    pub slots_per_epoch: u64,
+    pub slot_duration_secs: u64,
+    pub current_slot: u64,
+    pub leaders: Vec<ValidatorSlots>,
+}
+
+/// What a node did about a received block that did not build on its chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForkDecision {
+    /// The incoming chain was longer: ours was rolled back to the common ancestor
+    SwitchedChain,
+    /// Our chain was as long or longer, the incoming block was dropped
+    KeptOurs,
+    /// Switching would have replaced a finalized block
+    RefusedBelowCheckpoint,
+}
+
+/// Divergence report of one fork, served on `/alerts` and pushed to webhooks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForkAlertResponse {
+    /// Unix seconds
+    pub detected_at: u64,
+    /// Height of the block the two chains disagree on
+    pub fork_height: u64,
+    pub our_height: u64,
+    pub incoming_height: u64,
+    /// Our block at `fork_height`, if we still had it
+    pub our_hash: Option<String>,
+    /// The incoming block's parent, its chain's block at `fork_height`
+    pub their_hash: String,
+    pub incoming_block_hash: String,
+    /// Producers of our block at `fork_height` and of the incoming block
+    pub our_producer: Option<String>,
+    pub incoming_producer: Option<String>,
+    /// Set when the node switched chains
+    pub common_ancestor: Option<u64>,
+    pub decision: ForkDecision,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PauseVoteResponse {
+    pub guardian: String,
+    pub blocks: u64,
+    pub height: u64,
+}
+
+/// Emergency pause status; while paused only the coinbase and guardian votes execute
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PauseStatusResponse {
+    pub paused: bool,
+    /// Last paused block
+    pub paused_until: Option<u64>,
+    /// Guardian votes still counting toward a pause or its lifting
+    pub votes: Vec<PauseVoteResponse>,
+}
+
+/// An embedding model approved by the guardians
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemanticModelResponse {
+    /// First epoch scored with it
+    pub epoch: u64,
+    pub name: String,
+    pub version: String,
+    pub weights_hash: String,
+    /// What validators compare their local model against
+    pub commitment: String,
+}
+
+/// Embedding model validators score semantic coherence with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemanticModelStatusResponse {
+    pub epoch: u64,
+    /// Model in force this epoch, absent before the first approval
+    pub current: Option<SemanticModelResponse>,
+    /// Every approval, oldest first, including one for a coming epoch
+    pub approvals: Vec<SemanticModelResponse>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    /// Stable code of the `SpiraChainError` behind the failure, see `SpiraChainError::code`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<ErrorCategory>,
+}
+
+impl ErrorResponse {
+    pub fn new(error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            code: None,
+            category: None,
+        }
+    }
+
+    /// Client-facing form of `error`; storage and internal details stay in the logs
+    pub fn from_error(error: &SpiraChainError) -> Self {
+        Self {
+            error: error.public_message(),
+            code: Some(error.code()),
+            category: Some(error.category()),
+        }
+    }
+}
+
+/// Transaction payload, tagged by `kind`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PayloadDto {
+    Transfer,
+    ContractDeploy {
+        code: String,
+        constructor_args: String,
+        nonce: u64,
+    },
+    ContractCall {
+        input: String,
+    },
+    Coinbase {
+        height: u64,
+    },
+    ClaimRewards,
+    EmergencyPause {
+        blocks: u64,
+    },
+    RegisterSpiral {
+        spiral_id: String,
+        name: String,
+    },
+    SetPayoutAddress,
+    RegisterName {
+        name: String,
+    },
+    TransferName {
+        name: String,
+    },
+    EpochSummary {
+        epoch: u64,
+        validators: usize,
+        participants: usize,
+        total_fees: String,
+        reward_root: String,
+    },
+    RotateValidatorKey {
+        public_key: String,
+    },
+    ApproveSemanticModel {
+        name: String,
+        version: String,
+        weights_hash: String,
+    },
+}
+
+impl From<&TransactionPayload> for PayloadDto {
+    fn from(payload: &TransactionPayload) -> Self {
+        match payload {
+            TransactionPayload::Transfer => PayloadDto::Transfer,
+            TransactionPayload::ContractDeploy {
+                code,
+                constructor_args,
+                nonce,
+            } => PayloadDto::ContractDeploy {
+                code: encode_base64(code),
+                constructor_args: encode_base64(constructor_args),
+                nonce: *nonce,
+            },
+            TransactionPayload::ContractCall { input } => PayloadDto::ContractCall {
+                input: encode_base64(input),
+            },
+            TransactionPayload::Coinbase { height } => PayloadDto::Coinbase { height: *height },
+            TransactionPayload::ClaimRewards => PayloadDto::ClaimRewards,
+            TransactionPayload::EmergencyPause { blocks } => {
+                PayloadDto::EmergencyPause { blocks: *blocks }
+            }
+            TransactionPayload::RegisterSpiral { definition } => PayloadDto::RegisterSpiral {
+                spiral_id: definition.id().to_string(),
+                name: definition.name.clone(),
+            },
+            TransactionPayload::SetPayoutAddress => PayloadDto::SetPayoutAddress,
+            TransactionPayload::RegisterName { name } => {
+                PayloadDto::RegisterName { name: name.clone() }
+            }
+            TransactionPayload::TransferName { name } => {
+                PayloadDto::TransferName { name: name.clone() }
+            }
+            TransactionPayload::EpochSummary { summary } => PayloadDto::EpochSummary {
+                epoch: summary.epoch,
+                validators: summary.validators.len(),
+                participants: summary.participants().count(),
+                total_fees: encode_amount(summary.total_fees),
+                reward_root: summary.reward_root.to_string(),
+            },
+            TransactionPayload::RotateValidatorKey { public_key, .. } => {
+                PayloadDto::RotateValidatorKey {
+                    public_key: encode_hex(public_key),
+                }
+            }
+            TransactionPayload::ApproveSemanticModel { model } => {
+                PayloadDto::ApproveSemanticModel {
+                    name: model.name.clone(),
+                    version: model.version.clone(),
+                    weights_hash: model.weights_hash.to_string(),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionDto {
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub fee: String,
+    pub timestamp: u64,
+    pub purpose: String,
+    pub fork_id: String,
+    /// Hex commitment to the off-chain semantic vector, absent when there is none
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub semantic_commitment: String,
+    /// Earliest inclusion height of a scheduled transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execute_at: Option<u64>,
+    /// Sender nonce the transaction is ordered by in its block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(flatten)]
+    pub payload: PayloadDto,
+    pub signature: String,
+}
+
+impl From<&Transaction> for TransactionDto {
+    fn from(tx: &Transaction) -> Self {
+        Self {
+            hash: tx.tx_hash.to_string(),
+            from: tx.from.to_string(),
+            to: tx.to.to_string(),
+            amount: encode_amount(tx.amount),
+            fee: encode_amount(tx.fee),
+            timestamp: tx.timestamp,
+            purpose: tx.purpose.clone(),
+            fork_id: tx.fork_id.to_string(),
+            semantic_commitment: if tx.semantic_commitment.is_zero() {
+                String::new()
+            } else {
+                tx.semantic_commitment.to_string()
+            },
+            execute_at: tx.execute_at,
+            nonce: tx.nonce,
+            payload: PayloadDto::from(&tx.payload),
+            signature: encode_base64(&tx.signature),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockDto {
+    pub height: u64,
+    pub hash: String,
+    pub previous_hash: String,
+    pub merkle_root: String,
+    pub state_root: String,
+    pub timestamp: u64,
+    pub version: u64,
+    pub validator_pubkey: String,
+    pub signature: String,
+    pub extra_data: String,
+    /// Hex event bloom, absent for blocks produced before blooms existed
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event_bloom: String,
+    pub fees: String,
+    pub spiral_complexity: f64,
+    pub semantic_coherence: f64,
+    pub transactions: Vec<TransactionDto>,
+}
+
+impl From<&Block> for BlockDto {
+    fn from(block: &Block) -> Self {
+        Self {
+            height: block.header.block_height,
+            hash: block.hash().to_string(),
+            previous_hash: block.header.previous_block_hash.to_string(),
+            merkle_root: block.header.merkle_root.to_string(),
+            state_root: block.header.state_root.to_string(),
+            timestamp: block.header.timestamp,
+            version: block.header.version,
+            validator_pubkey: encode_hex(&block.header.validator_pubkey),
+            signature: encode_base64(&block.header.signature),
+            extra_data: encode_base64(&block.header.extra_data),
+            event_bloom: block
+                .header
+                .event_bloom()
+                .map(|bloom| encode_hex(bloom.as_bytes()))
+                .unwrap_or_default(),
+            fees: encode_amount(block.total_fees()),
+            spiral_complexity: block.header.spiral.complexity,
+            semantic_coherence: block.avg_semantic_coherence(),
+            transactions: block
+                .transactions
+                .iter()
+                .map(TransactionDto::from)
+                .collect(),
+        }
+    }
+}
+
+/// Inclusion record of one transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptDto {
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub block_height: u64,
+    pub index: u32,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub fee: String,
+}
+
+impl ReceiptDto {
+    /// Receipt of the transaction at `index` in `block`
+    pub fn new(block: &Block, index: usize) -> Option<Self> {
+        let tx = block.transactions.get(index)?;
+        Some(Self {
+            tx_hash: tx.tx_hash.to_string(),
+            block_hash: block.hash().to_string(),
+            block_height: block.header.block_height,
+            index: index as u32,
+            from: tx.from.to_string(),
+            to: tx.to.to_string(),
+            amount: encode_amount(tx.amount),
+            fee: encode_amount(tx.fee),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spirachain_core::{Address, Hash};
+
+    fn sample_block() -> Block {
+        let producer = Address::new([7u8; 32]);
+        let mut coinbase = Transaction::new_coinbase(producer, Amount::new(10u128.pow(19)), 5);
+        coinbase.timestamp = 1_700_000_000_000;
+        coinbase.tx_hash = Hash::new([1u8; 32]);
+
+        let mut transfer = Transaction::new(
+            Address::new([2u8; 32]),
+            Address::new([3u8; 32]),
+            Amount::new(340_282_366_920_938_463_463_374_607_431_768_211_455),
+            Amount::new(1_000),
+        );
+        transfer.timestamp = 1_700_000_000_500;
+        transfer.tx_hash = Hash::new([4u8; 32]);
+        transfer.purpose = "rent".to_string();
+        transfer.signature = vec![0xde, 0xad, 0xbe, 0xef];
+
+        let mut call = Transaction::new(
+            Address::new([2u8; 32]),
+            Address::new([5u8; 32]),
+            Amount::zero(),
+            Amount::new(10),
+        );
+        call.timestamp = 1_700_000_000_600;
+        call.tx_hash = Hash::new([6u8; 32]);
+        call.payload = TransactionPayload::ContractCall {
+            input: vec![1, 2, 3],
+        };
+
+        let mut block = Block::new(Hash::new([9u8; 32]), 5)
+            .with_transactions(vec![coinbase, transfer, call])
+            .with_extra_data(b"spira".to_vec());
+        block.header.timestamp = 1_700_000_001_000;
+        block.header.validator_pubkey = vec![0xab; 32];
+        block.header.signature = vec![0xcd; 8];
+        block.compute_merkle_root();
+        block
+    }
+
+    fn assert_golden(value: &impl Serialize, golden: &str) {
+        let json = serde_json::to_string_pretty(value).unwrap();
+        assert_eq!(json, golden.trim_end(), "canonical JSON changed");
+    }
+
+    #[test]
+    fn test_block_json_golden() {
+        assert_golden(
+            &BlockDto::from(&sample_block()),
+            include_str!("../golden/block.json"),
+        );
+    }
+
+    #[test]
+    fn test_transaction_json_golden() {
+        let block = sample_block();
+        assert_golden(
+            &TransactionDto::from(&block.transactions[1]),
+            include_str!("../golden/transaction.json"),
+        );
+    }
+
+    #[test]
+    fn test_receipt_json_golden() {
+        let block = sample_block();
+        assert_golden(
+            &ReceiptDto::new(&block, 1).unwrap(),
+            include_str!("../golden/receipt.json"),
+        );
+        assert!(ReceiptDto::new(&block, 3).is_none());
+    }
+
+    #[test]
+    fn test_dto_roundtrip() {
+        let dto = BlockDto::from(&sample_block());
+        let json = serde_json::to_string(&dto).unwrap();
+        assert_eq!(serde_json::from_str::<BlockDto>(&json).unwrap(), dto);
+    }
 }
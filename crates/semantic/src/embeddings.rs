@@ -26,7 +26,10 @@ impl EmbeddingGenerator {
     }
 
     pub async fn encode(&self, text: &str) -> Result<Vec<f32>> {
-        match spirapi_bridge::semantic_index_content(text, "text") {
+        match spirapi_bridge::bridge_pool()
+            .semantic_index_content(text, "text")
+            .await
+        {
             Ok(result) => Ok(result.semantic_vector),
             Err(_) => Ok(vec![0.0; self.dimensions]),
         }
@@ -52,7 +52,7 @@ impl SemanticProcessor {
         }
 
         // Use local SpiraPi AI
-        match SpiraPiEngine::generate_embedding(text) {
+        match spirapi_bridge::bridge_pool().generate_embedding(text).await {
             Ok(embedding) => {
                 if embedding.iter().any(|&v| v != 0.0) {
                     Ok(embedding)
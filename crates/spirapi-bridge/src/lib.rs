@@ -1,12 +1,20 @@
 // SpiraPi Bridge - Real PyO3 Integration
 // Connects Rust SpiraChain to Python SpiraPi engine
 
+mod metrics;
+mod pi_identifier;
+mod pool;
+
 #[cfg(feature = "pyo3")]
 mod lib_pyo3;
 
 #[cfg(not(feature = "pyo3"))]
 mod lib_stub;
 
+pub use metrics::*;
+pub use pi_identifier::*;
+pub use pool::*;
+
 #[cfg(feature = "pyo3")]
 pub use lib_pyo3::*;
 
@@ -1,3 +1,4 @@
+use crate::PiIdentifier;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use pyo3::prelude::*;
@@ -5,6 +6,7 @@ use pyo3::types::{PyDict, PyList, PyModule};
 use serde::{Deserialize, Serialize};
 use spirachain_core::{PiCoordinate, SpiraChainError};
 use std::path::PathBuf;
+use std::time::Instant;
 use tracing::{error, info, warn};
 
 static PYTHON_ENGINE: Lazy<Mutex<Option<SpiraPiEngine>>> = Lazy::new(|| Mutex::new(None));
@@ -81,7 +83,7 @@ impl SpiraPiEngine {
         })
         .map_err(|e: PyErr| {
             error!("❌ Python initialization failed: {}", e);
-            SpiraChainError::Internal(format!("Python init failed: {}", e))
+            SpiraChainError::SemanticEngine(format!("Python init failed: {}", e))
         })
     }
 
@@ -97,12 +99,14 @@ impl SpiraPiEngine {
         let engine_lock = Self::get_instance();
         let engine_guard = engine_lock.lock();
         let engine = engine_guard.as_ref().ok_or_else(|| {
-            SpiraChainError::Internal(
+            SpiraChainError::SemanticEngine(
                 "Engine not initialized - call initialize_spirapi() first".to_string(),
             )
         })?;
 
+        let waiting = Instant::now();
         Python::with_gil(|py| -> Result<PiCoordinate, SpiraChainError> {
+            crate::bridge_metrics().record_gil_wait("generate_pi_coordinate", waiting.elapsed());
             let _hash_hex = hex::encode(entity_hash);
 
             let result = (|| -> PyResult<String> {
@@ -126,7 +130,7 @@ impl SpiraPiEngine {
 
                 identifier.extract()
             })()
-            .map_err(|e| SpiraChainError::Internal(format!("Python call failed: {}", e)))?;
+            .map_err(|e| SpiraChainError::SemanticEngine(format!("Python call failed: {}", e)))?;
 
             let id_str = result;
 
@@ -157,9 +161,11 @@ impl SpiraPiEngine {
         let engine_guard = engine_lock.lock();
         let engine = engine_guard
             .as_ref()
-            .ok_or_else(|| SpiraChainError::Internal("Engine not initialized".to_string()))?;
+            .ok_or_else(|| SpiraChainError::SemanticEngine("Engine not initialized".to_string()))?;
 
+        let waiting = Instant::now();
         Python::with_gil(|py| -> Result<SemanticIndexResult, SpiraChainError> {
+            crate::bridge_metrics().record_gil_wait("semantic_index_content", waiting.elapsed());
             (|| -> PyResult<SemanticIndexResult> {
                 let request = PyDict::new(py);
                 request.set_item("content", content)?;
@@ -237,7 +243,7 @@ impl SpiraPiEngine {
             })()
             .map_err(|e| {
                 error!("Semantic indexing error: {}", e);
-                SpiraChainError::Internal(format!("Python semantic indexing failed: {}", e))
+                SpiraChainError::SemanticEngine(format!("Python semantic indexing failed: {}", e))
             })
         })
     }
@@ -247,9 +253,11 @@ impl SpiraPiEngine {
         let engine_guard = engine_lock.lock();
         let engine = engine_guard
             .as_ref()
-            .ok_or_else(|| SpiraChainError::Internal("Engine not initialized".to_string()))?;
+            .ok_or_else(|| SpiraChainError::SemanticEngine("Engine not initialized".to_string()))?;
 
+        let waiting = Instant::now();
         Python::with_gil(|py| -> Result<String, SpiraChainError> {
+            crate::bridge_metrics().record_gil_wait("calculate_pi", waiting.elapsed());
             (|| -> PyResult<String> {
                 let result =
                     engine
@@ -267,7 +275,7 @@ impl SpiraPiEngine {
             })()
             .map_err(|e| {
                 error!("π calculation error: {}", e);
-                SpiraChainError::Internal(format!("Python π calculation failed: {}", e))
+                SpiraChainError::SemanticEngine(format!("Python π calculation failed: {}", e))
             })
         })
     }
@@ -285,6 +293,54 @@ pub fn generate_pi_coordinate(
     SpiraPiEngine::generate_pi_coordinate_real(entity_hash, timestamp, nonce)
 }
 
+pub fn generate_batch_identifiers(
+    count: usize,
+    length: usize,
+) -> Result<Vec<PiIdentifier>, SpiraChainError> {
+    let engine_lock = SpiraPiEngine::get_instance();
+    let engine_guard = engine_lock.lock();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| SpiraChainError::SemanticEngine("Engine not initialized".to_string()))?;
+
+    let waiting = Instant::now();
+    Python::with_gil(|py| -> Result<Vec<PiIdentifier>, SpiraChainError> {
+        crate::bridge_metrics().record_gil_wait("generate_batch_identifiers", waiting.elapsed());
+        (|| -> PyResult<Vec<PiIdentifier>> {
+            let result = engine.pi_engine.call_method1(
+                py,
+                "generate_batch_identifiers",
+                (count, length, true),
+            )?;
+
+            let list = result.as_ref(py).downcast::<PyList>()?;
+            list.iter()
+                .map(|item| {
+                    let dict = item.downcast::<PyDict>()?;
+                    Ok(PiIdentifier {
+                        identifier: dict_item(dict, "identifier")?.extract()?,
+                        pi_sequence: dict_item(dict, "pi_sequence")?.extract()?,
+                        spiral_component: dict_item(dict, "spiral_component")?.extract()?,
+                        timestamp_component: dict_item(dict, "timestamp_component")?.extract()?,
+                        generation_time: dict_item(dict, "generation_time")?.extract()?,
+                        uniqueness_score: dict_item(dict, "uniqueness_score")?.extract()?,
+                        total_length: dict_item(dict, "total_length")?.extract()?,
+                    })
+                })
+                .collect()
+        })()
+        .map_err(|e| {
+            error!("π identifier batch error: {}", e);
+            SpiraChainError::SemanticEngine(format!("Python identifier batch failed: {}", e))
+        })
+    })
+}
+
+fn dict_item<'py>(dict: &'py PyDict, key: &str) -> PyResult<&'py PyAny> {
+    dict.get_item(key)?
+        .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err(key.to_string()))
+}
+
 pub fn semantic_index_content(
     content: &str,
     content_type: &str,
@@ -311,33 +367,38 @@ impl SpiraPiEngine {
             return Ok(vec![0.0; 384]);
         }
 
+        let waiting = Instant::now();
         Python::with_gil(|py| {
+            crate::bridge_metrics().record_gil_wait("generate_embedding", waiting.elapsed());
             let embedding_module = PyModule::import(py, "ai.embedding_service").map_err(|e| {
-                SpiraChainError::Internal(format!("Failed to import embedding_service: {}", e))
+                SpiraChainError::SemanticEngine(format!(
+                    "Failed to import embedding_service: {}",
+                    e
+                ))
             })?;
 
             let get_service_fn =
                 embedding_module
                     .getattr("get_embedding_service")
                     .map_err(|e| {
-                        SpiraChainError::Internal(format!(
+                        SpiraChainError::SemanticEngine(format!(
                             "Failed to get get_embedding_service: {}",
                             e
                         ))
                     })?;
 
             let service = get_service_fn.call0().map_err(|e| {
-                SpiraChainError::Internal(format!("Failed to create EmbeddingService: {}", e))
+                SpiraChainError::SemanticEngine(format!("Failed to create EmbeddingService: {}", e))
             })?;
 
             let result = service
                 .call_method1("generate_embedding", (text,))
                 .map_err(|e| {
-                    SpiraChainError::Internal(format!("Failed to generate embedding: {}", e))
+                    SpiraChainError::SemanticEngine(format!("Failed to generate embedding: {}", e))
                 })?;
 
             let embedding: Vec<f32> = result.extract().map_err(|e| {
-                SpiraChainError::Internal(format!("Failed to extract embedding: {}", e))
+                SpiraChainError::SemanticEngine(format!("Failed to extract embedding: {}", e))
             })?;
 
             Ok(embedding)
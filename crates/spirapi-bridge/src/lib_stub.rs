@@ -1,18 +1,8 @@
+use crate::PiIdentifier;
 use serde::{Deserialize, Serialize};
 use spirachain_core::{PiCoordinate, SpiraChainError};
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PiIdentifier {
-    pub identifier: String,
-    pub pi_sequence: String,
-    pub spiral_component: Option<String>,
-    pub timestamp_component: String,
-    pub generation_time: f64,
-    pub uniqueness_score: f64,
-    pub total_length: usize,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PiCalculationResult {
     pub value: String,
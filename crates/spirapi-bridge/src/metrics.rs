@@ -0,0 +1,216 @@
+// SpiraPi bridge metrics
+// Every entry point records how long callers waited end to end, how long the
+// job sat in the worker queue and, with the Python engine, how long it waited
+// for the GIL, plus call, failure, timeout and rejection counters. Exported
+// in the Prometheus text format next to the node's own metrics.
+
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Upper bounds of the latency buckets, in seconds
+pub const LATENCY_BUCKETS: [f64; 10] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    /// Observations per bucket of [`LATENCY_BUCKETS`], not cumulative; the
+    /// last entry counts everything slower
+    pub buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    pub sum_secs: f64,
+    pub count: u64,
+}
+
+impl Histogram {
+    pub fn observe(&mut self, value: Duration) {
+        let secs = value.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.sum_secs += secs;
+        self.count += 1;
+    }
+
+    fn export(&self, out: &mut String, name: &str, entry: &str) {
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{entry=\"{}\",le=\"{}\"}} {}",
+                name, entry, bound, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{entry=\"{}\",le=\"+Inf\"}} {}\n\
+             {}_sum{{entry=\"{}\"}} {}\n\
+             {}_count{{entry=\"{}\"}} {}",
+            name, entry, self.count, name, entry, self.sum_secs, name, entry, self.count
+        );
+    }
+}
+
+/// Counters and histograms of one entry point
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallMetrics {
+    pub latency: Histogram,
+    pub queue_wait: Histogram,
+    pub gil_wait: Histogram,
+    pub calls: u64,
+    /// Calls that returned an error, timed out or were aborted
+    pub failures: u64,
+    pub timeouts: u64,
+    /// Calls refused without reaching the engine (open circuit or fallback mode)
+    pub rejected: u64,
+}
+
+/// Metrics of every bridge entry point, keyed by name
+#[derive(Default)]
+pub struct BridgeMetrics {
+    calls: Mutex<BTreeMap<&'static str, CallMetrics>>,
+}
+
+impl BridgeMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_entry(&self, entry: &'static str, f: impl FnOnce(&mut CallMetrics)) {
+        f(self.calls.lock().entry(entry).or_default());
+    }
+
+    /// A call that reached a worker: end-to-end latency and whether it succeeded
+    pub fn record_call(&self, entry: &'static str, latency: Duration, ok: bool) {
+        self.with_entry(entry, |metrics| {
+            metrics.calls += 1;
+            metrics.latency.observe(latency);
+            if !ok {
+                metrics.failures += 1;
+            }
+        });
+    }
+
+    pub fn record_timeout(&self, entry: &'static str, latency: Duration) {
+        self.with_entry(entry, |metrics| {
+            metrics.calls += 1;
+            metrics.failures += 1;
+            metrics.timeouts += 1;
+            metrics.latency.observe(latency);
+        });
+    }
+
+    pub fn record_rejected(&self, entry: &'static str) {
+        self.with_entry(entry, |metrics| metrics.rejected += 1);
+    }
+
+    pub fn record_queue_wait(&self, entry: &'static str, wait: Duration) {
+        self.with_entry(entry, |metrics| metrics.queue_wait.observe(wait));
+    }
+
+    pub fn record_gil_wait(&self, entry: &'static str, wait: Duration) {
+        self.with_entry(entry, |metrics| metrics.gil_wait.observe(wait));
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<&'static str, CallMetrics> {
+        self.calls.lock().clone()
+    }
+
+    pub fn export_prometheus(&self) -> String {
+        let calls = self.snapshot();
+        let mut out = String::new();
+
+        for (name, help, histogram) in [
+            (
+                "spirachain_spirapi_call_duration_seconds",
+                "SpiraPi bridge call latency, queueing included",
+                (|metrics: &CallMetrics| &metrics.latency) as fn(&CallMetrics) -> &Histogram,
+            ),
+            (
+                "spirachain_spirapi_queue_wait_seconds",
+                "Time SpiraPi jobs waited for a worker thread",
+                |metrics| &metrics.queue_wait,
+            ),
+            (
+                "spirachain_spirapi_gil_wait_seconds",
+                "Time SpiraPi calls waited for the Python GIL",
+                |metrics| &metrics.gil_wait,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} histogram", name, help, name);
+            for (entry, metrics) in &calls {
+                histogram(metrics).export(&mut out, name, entry);
+            }
+        }
+
+        for (name, help, counter) in [
+            (
+                "spirachain_spirapi_calls_total",
+                "SpiraPi bridge calls that reached a worker",
+                (|metrics: &CallMetrics| metrics.calls) as fn(&CallMetrics) -> u64,
+            ),
+            (
+                "spirachain_spirapi_failures_total",
+                "SpiraPi bridge calls that failed, timed out or were aborted",
+                |metrics| metrics.failures,
+            ),
+            (
+                "spirachain_spirapi_timeouts_total",
+                "SpiraPi bridge calls that timed out",
+                |metrics| metrics.timeouts,
+            ),
+            (
+                "spirachain_spirapi_rejected_total",
+                "SpiraPi bridge calls refused by the circuit breaker or fallback mode",
+                |metrics| metrics.rejected,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+            for (entry, metrics) in &calls {
+                let _ = writeln!(out, "{}{{entry=\"{}\"}} {}", name, entry, counter(metrics));
+            }
+        }
+        out
+    }
+}
+
+static BRIDGE_METRICS: OnceLock<BridgeMetrics> = OnceLock::new();
+
+/// Process-wide metrics; the GIL is process-wide too
+pub fn bridge_metrics() -> &'static BridgeMetrics {
+    BRIDGE_METRICS.get_or_init(BridgeMetrics::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histograms_and_prometheus_export() {
+        let metrics = BridgeMetrics::new();
+        metrics.record_call("generate_embedding", Duration::from_millis(3), true);
+        metrics.record_call("generate_embedding", Duration::from_millis(40), false);
+        metrics.record_timeout("generate_embedding", Duration::from_secs(3));
+        metrics.record_rejected("generate_embedding");
+        metrics.record_gil_wait("generate_embedding", Duration::from_micros(200));
+
+        let entry = &metrics.snapshot()["generate_embedding"];
+        assert_eq!((entry.calls, entry.failures, entry.timeouts), (3, 2, 1));
+        assert_eq!(entry.latency.buckets[1], 1);
+        assert_eq!(entry.latency.buckets[LATENCY_BUCKETS.len()], 1);
+
+        let text = metrics.export_prometheus();
+        assert!(text.contains(
+            "spirachain_spirapi_call_duration_seconds_bucket{entry=\"generate_embedding\",le=\"0.05\"} 2"
+        ));
+        assert!(text.contains(
+            "spirachain_spirapi_call_duration_seconds_bucket{entry=\"generate_embedding\",le=\"+Inf\"} 3"
+        ));
+        assert!(text
+            .contains("spirachain_spirapi_gil_wait_seconds_count{entry=\"generate_embedding\"} 1"));
+        assert!(text.contains("spirachain_spirapi_rejected_total{entry=\"generate_embedding\"} 1"));
+    }
+}
@@ -0,0 +1,357 @@
+// π identifier derivation and verification
+// The Python engine generates identifiers for throughput, but nothing it
+// reports can be trusted during block validation. Everything here is
+// recomputed from the π digits and the chain context so any validator reaches
+// the same verdict.
+
+use serde::{Deserialize, Serialize};
+use spirachain_core::{Hash, SpiraChainError, Transaction};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// extra_data key carrying a transaction's JSON-encoded π identifier
+pub const PI_IDENTIFIER_KEY: &str = "pi_identifier";
+
+/// extra_data key carrying the [`pi_identifier_hash`] of an identifier
+/// anchored on-chain for timestamping
+pub const PI_ANCHOR_KEY: &str = "pi_anchor";
+
+/// Decimal digits of π available to identifiers, starting at the leading 3
+pub const PI_DIGIT_COUNT: usize = 2048;
+
+pub const MIN_PI_SEQUENCE_LENGTH: usize = 8;
+pub const MAX_PI_SEQUENCE_LENGTH: usize = 64;
+
+/// Allowed gap between the identifier timestamp and the transaction timestamp
+pub const PI_TIMESTAMP_TOLERANCE_MS: u64 = 60_000;
+
+/// Absorbs libm rounding differences between platforms
+pub const UNIQUENESS_SCORE_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiIdentifier {
+    pub identifier: String,
+    pub pi_sequence: String,
+    pub spiral_component: Option<String>,
+    pub timestamp_component: String,
+    pub generation_time: f64,
+    pub uniqueness_score: f64,
+    pub total_length: usize,
+}
+
+/// Chain context an identifier is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PiIdentifierContext {
+    pub fork_id: Hash,
+    /// Entity the identifier names, the sender for transactions
+    pub entity: Hash,
+    /// Reference time in milliseconds
+    pub timestamp_ms: u64,
+}
+
+impl PiIdentifierContext {
+    pub fn for_transaction(tx: &Transaction) -> Self {
+        Self {
+            fork_id: tx.fork_id,
+            entity: Hash::new(*tx.from.as_bytes()),
+            timestamp_ms: tx.timestamp,
+        }
+    }
+}
+
+/// First PI_DIGIT_COUNT decimal digits of π ("31415...")
+pub fn pi_digits() -> &'static str {
+    static DIGITS: OnceLock<String> = OnceLock::new();
+    DIGITS.get_or_init(|| compute_pi_digits(PI_DIGIT_COUNT))
+}
+
+/// Rabinowitz-Wagon spigot, exact integer arithmetic
+fn compute_pi_digits(count: usize) -> String {
+    // The last few spigot digits can still be corrected by a carry
+    let n = count + 16;
+    let len = n * 10 / 3 + 1;
+    let mut a = vec![2u64; len];
+    let mut digits = String::with_capacity(n + 1);
+    let mut predigit = 0u64;
+    let mut nines = 0usize;
+
+    for j in 0..n {
+        let mut q = 0u64;
+        for i in (1..=len as u64).rev() {
+            let x = 10 * a[i as usize - 1] + q * i;
+            a[i as usize - 1] = x % (2 * i - 1);
+            q = x / (2 * i - 1);
+        }
+        a[0] = q % 10;
+        q /= 10;
+
+        if q == 9 {
+            nines += 1;
+        } else if q == 10 {
+            digits.push(char::from(b'0' + predigit as u8 + 1));
+            digits.push_str(&"0".repeat(nines));
+            predigit = 0;
+            nines = 0;
+        } else {
+            if j > 0 {
+                digits.push(char::from(b'0' + predigit as u8));
+            }
+            predigit = q;
+            digits.push_str(&"9".repeat(nines));
+            nines = 0;
+        }
+    }
+
+    digits.truncate(count);
+    digits
+}
+
+/// Offset into the π digits selected by the context and timestamp
+pub fn pi_start_position(ctx: &PiIdentifierContext, timestamp_micros: u64, length: usize) -> usize {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"spirachain-pi-id");
+    hasher.update(ctx.fork_id.as_bytes());
+    hasher.update(ctx.entity.as_bytes());
+    hasher.update(&timestamp_micros.to_be_bytes());
+    hasher.update(&(length as u64).to_be_bytes());
+
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&hasher.finalize().as_bytes()[..8]);
+
+    let window = (PI_DIGIT_COUNT - length + 1) as u64;
+    (u64::from_be_bytes(seed) % window) as usize
+}
+
+/// Same weighting as the Python engine: entropy, position, length, repetition
+pub fn uniqueness_score(sequence: &str, position: usize) -> f64 {
+    let len = sequence.len() as f64;
+
+    let mut counts = [0usize; 10];
+    for byte in sequence.bytes().filter(u8::is_ascii_digit) {
+        counts[(byte - b'0') as usize] += 1;
+    }
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    let entropy_score = entropy / 10f64.log2();
+
+    let position_score = 1.0 / (1.0 + ((position + 1) as f64).log10());
+
+    let length_score = (len / 20.0).min(1.0);
+
+    let pairs: HashSet<&[u8]> = sequence.as_bytes().windows(2).collect();
+    let max_unique = sequence.len().saturating_sub(1).min(100);
+    let repetition_score = if max_unique > 0 {
+        pairs.len() as f64 / max_unique as f64
+    } else {
+        1.0
+    };
+
+    0.3 * entropy_score + 0.3 * position_score + 0.2 * length_score + 0.2 * repetition_score
+}
+
+pub fn spiral_component(pi_sequence: &str) -> String {
+    let hash = blake3::hash(pi_sequence.as_bytes());
+    hex::encode(&hash.as_bytes()[..8])
+}
+
+fn assemble(pi_sequence: &str, spiral: Option<&str>, timestamp_component: &str) -> String {
+    match spiral {
+        Some(spiral) => format!("{}.{}.{}", pi_sequence, spiral, timestamp_component),
+        None => format!("{}.{}", pi_sequence, timestamp_component),
+    }
+}
+
+fn check_length(length: usize) -> Result<(), SpiraChainError> {
+    if !(MIN_PI_SEQUENCE_LENGTH..=MAX_PI_SEQUENCE_LENGTH).contains(&length) {
+        return Err(SpiraChainError::InvalidTransaction(format!(
+            "π sequence length {} outside {}..={}",
+            length, MIN_PI_SEQUENCE_LENGTH, MAX_PI_SEQUENCE_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Deterministic identifier for `ctx`; `verify_pi_identifier` accepts it
+pub fn generate_pi_identifier(
+    ctx: &PiIdentifierContext,
+    length: usize,
+    include_spiral_component: bool,
+) -> Result<PiIdentifier, SpiraChainError> {
+    check_length(length)?;
+
+    let timestamp_micros = ctx.timestamp_ms.saturating_mul(1000);
+    let position = pi_start_position(ctx, timestamp_micros, length);
+    let pi_sequence = pi_digits()[position..position + length].to_string();
+    let spiral = include_spiral_component.then(|| spiral_component(&pi_sequence));
+    let timestamp_component = format!("{:x}", timestamp_micros);
+    let identifier = assemble(&pi_sequence, spiral.as_deref(), &timestamp_component);
+
+    Ok(PiIdentifier {
+        total_length: identifier.len(),
+        uniqueness_score: uniqueness_score(&pi_sequence, position),
+        identifier,
+        pi_sequence,
+        spiral_component: spiral,
+        timestamp_component,
+        generation_time: 0.0,
+    })
+}
+
+/// blake3 of the identifier string, what an anchoring transaction records
+pub fn pi_identifier_hash(id: &PiIdentifier) -> Hash {
+    blake3::hash(id.identifier.as_bytes()).into()
+}
+
+/// Recompute every component of `id` from the π digits and `ctx`
+pub fn verify_pi_identifier(
+    id: &PiIdentifier,
+    ctx: &PiIdentifierContext,
+) -> Result<(), SpiraChainError> {
+    let invalid = |reason: String| {
+        Err(SpiraChainError::InvalidTransaction(format!(
+            "π identifier {}: {}",
+            id.identifier, reason
+        )))
+    };
+
+    let length = id.pi_sequence.len();
+    check_length(length)?;
+
+    let timestamp_micros = match u64::from_str_radix(&id.timestamp_component, 16) {
+        Ok(micros) if format!("{:x}", micros) == id.timestamp_component => micros,
+        _ => return invalid("malformed timestamp component".to_string()),
+    };
+    let timestamp_ms = timestamp_micros / 1000;
+    if timestamp_ms.abs_diff(ctx.timestamp_ms) > PI_TIMESTAMP_TOLERANCE_MS {
+        return invalid(format!(
+            "timestamp {}ms too far from {}ms",
+            timestamp_ms, ctx.timestamp_ms
+        ));
+    }
+
+    let position = pi_start_position(ctx, timestamp_micros, length);
+    if id.pi_sequence != pi_digits()[position..position + length] {
+        return invalid(format!("sequence does not match π at offset {}", position));
+    }
+
+    if let Some(spiral) = &id.spiral_component {
+        if *spiral != spiral_component(&id.pi_sequence) {
+            return invalid("spiral component mismatch".to_string());
+        }
+    }
+
+    let expected = assemble(
+        &id.pi_sequence,
+        id.spiral_component.as_deref(),
+        &id.timestamp_component,
+    );
+    if id.identifier != expected || id.total_length != expected.len() {
+        return invalid("identifier does not match its components".to_string());
+    }
+
+    let score = uniqueness_score(&id.pi_sequence, position);
+    if (id.uniqueness_score - score).abs() > UNIQUENESS_SCORE_TOLERANCE {
+        return invalid(format!(
+            "uniqueness score {} should be {}",
+            id.uniqueness_score, score
+        ));
+    }
+
+    Ok(())
+}
+
+/// Identifier attached to `tx`, if any
+pub fn transaction_pi_identifier(
+    tx: &Transaction,
+) -> Result<Option<PiIdentifier>, SpiraChainError> {
+    tx.extra_data
+        .get(PI_IDENTIFIER_KEY)
+        .map(|bytes| {
+            serde_json::from_slice(bytes)
+                .map_err(|e| SpiraChainError::SerializationError(e.to_string()))
+        })
+        .transpose()
+}
+
+/// Verify the identifier attached to `tx`. Transactions without one pass.
+pub fn verify_transaction_pi_identifier(
+    tx: &Transaction,
+) -> Result<Option<PiIdentifier>, SpiraChainError> {
+    let id = transaction_pi_identifier(tx)?;
+    if let Some(id) = &id {
+        verify_pi_identifier(id, &PiIdentifierContext::for_transaction(tx))?;
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> PiIdentifierContext {
+        PiIdentifierContext {
+            fork_id: Hash::new([1u8; 32]),
+            entity: Hash::new([2u8; 32]),
+            timestamp_ms: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_pi_digits() {
+        let digits = pi_digits();
+        assert_eq!(digits.len(), PI_DIGIT_COUNT);
+        assert!(digits.starts_with("31415926535897932384626433832795028841971"));
+        // Feynman point: six nines at decimal 762
+        assert_eq!(&digits[762..768], "999999");
+    }
+
+    #[test]
+    fn test_score_matches_python_engine() {
+        let score = uniqueness_score("14159265358979323846", 1);
+        assert!((score - 0.9113296660145149).abs() < UNIQUENESS_SCORE_TOLERANCE);
+    }
+
+    #[test]
+    fn test_generated_identifier_verifies() {
+        let ctx = context();
+        let id = generate_pi_identifier(&ctx, 20, true).unwrap();
+        assert!(verify_pi_identifier(&id, &ctx).is_ok());
+
+        let other = PiIdentifierContext {
+            entity: Hash::new([3u8; 32]),
+            ..ctx
+        };
+        assert!(verify_pi_identifier(&id, &other).is_err());
+
+        let stale = PiIdentifierContext {
+            timestamp_ms: ctx.timestamp_ms + PI_TIMESTAMP_TOLERANCE_MS + 1,
+            ..ctx
+        };
+        assert!(verify_pi_identifier(&id, &stale).is_err());
+    }
+
+    #[test]
+    fn test_tampered_identifier_rejected() {
+        let ctx = context();
+        let id = generate_pi_identifier(&ctx, 16, false).unwrap();
+
+        let mut inflated = id.clone();
+        inflated.uniqueness_score = 0.99;
+        assert!(verify_pi_identifier(&inflated, &ctx).is_err());
+
+        let mut forged = id.clone();
+        forged.pi_sequence = "1".repeat(16);
+        forged.identifier = assemble(&forged.pi_sequence, None, &forged.timestamp_component);
+        assert!(verify_pi_identifier(&forged, &ctx).is_err());
+
+        let mut padded = id;
+        padded.timestamp_component = format!("0{}", padded.timestamp_component);
+        assert!(verify_pi_identifier(&padded, &ctx).is_err());
+    }
+}
@@ -0,0 +1,569 @@
+// SpiraPi worker pool
+// Python calls hold the GIL for as long as the engine runs, so they must not
+// run on async runtime threads. Jobs go to dedicated worker threads and the
+// result comes back over a channel; a circuit breaker fails calls fast once
+// the engine stops answering, so callers can fall back instead of piling up.
+// The same fallback kicks in while the p95 latency of recent calls exceeds
+// the configured SLO: a slow engine holds up blocks as much as a dead one.
+
+use crate::{bridge_metrics, PiIdentifier, SemanticIndexResult};
+use parking_lot::Mutex;
+use spirachain_core::{PiCoordinate, SpiraChainError};
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+type BridgeResult<T> = Result<T, SpiraChainError>;
+type IdentifierWaiter = oneshot::Sender<BridgeResult<PiIdentifier>>;
+
+/// Latest call latencies the SLO percentile is computed over
+const SLO_WINDOW: usize = 100;
+/// Calls needed in the window before the SLO is enforced
+const SLO_MIN_SAMPLES: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct BridgePoolConfig {
+    pub workers: usize,
+    pub call_timeout: Duration,
+    /// Identifier requests merged into one engine call
+    pub max_batch_size: usize,
+    /// How long the first identifier request waits for others to join its batch
+    pub batch_window: Duration,
+    /// Consecutive timeouts that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects calls before letting one through again
+    pub cooldown: Duration,
+    /// p95 call latency above which calls fall back for `cooldown`; zero disables
+    pub latency_slo: Duration,
+}
+
+impl Default for BridgePoolConfig {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            call_timeout: Duration::from_secs(2),
+            max_batch_size: 64,
+            batch_window: Duration::from_millis(5),
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+            latency_slo: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CircuitBreaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// Opened because of latency rather than stalls
+    slow: bool,
+}
+
+struct Inner {
+    config: BridgePoolConfig,
+    call_timeout_ms: AtomicU64,
+    latency_slo_ms: AtomicU64,
+    recent_latencies: Mutex<VecDeque<Duration>>,
+    jobs: Mutex<mpsc::Sender<Job>>,
+    breaker: Mutex<CircuitBreaker>,
+    pending_identifiers: Mutex<HashMap<usize, Vec<IdentifierWaiter>>>,
+}
+
+/// Runs bridge calls on dedicated threads. Cheap to clone.
+#[derive(Clone)]
+pub struct BridgePool {
+    inner: Arc<Inner>,
+}
+
+impl BridgePool {
+    pub fn new(config: BridgePoolConfig) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = config.workers.max(1);
+        for i in 0..workers {
+            let receiver = Arc::clone(&receiver);
+            thread::Builder::new()
+                .name(format!("spirapi-worker-{}", i))
+                .spawn(move || loop {
+                    let job = receiver.lock().recv();
+                    match job {
+                        // A panicking call drops its reply channel; the worker survives
+                        Ok(job) => {
+                            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                        }
+                        Err(_) => break,
+                    }
+                })
+                .expect("failed to spawn SpiraPi worker thread");
+        }
+
+        info!(
+            "🐍 SpiraPi worker pool started: {} workers, {:?} timeout",
+            workers, config.call_timeout
+        );
+
+        Self {
+            inner: Arc::new(Inner {
+                call_timeout_ms: AtomicU64::new(config.call_timeout.as_millis() as u64),
+                latency_slo_ms: AtomicU64::new(config.latency_slo.as_millis() as u64),
+                recent_latencies: Mutex::new(VecDeque::with_capacity(SLO_WINDOW)),
+                config,
+                jobs: Mutex::new(sender),
+                breaker: Mutex::new(CircuitBreaker::default()),
+                pending_identifiers: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn call_timeout(&self) -> Duration {
+        Duration::from_millis(self.inner.call_timeout_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn set_call_timeout(&self, timeout: Duration) {
+        self.inner
+            .call_timeout_ms
+            .store(timeout.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn latency_slo(&self) -> Duration {
+        Duration::from_millis(self.inner.latency_slo_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn set_latency_slo(&self, slo: Duration) {
+        self.inner
+            .latency_slo_ms
+            .store(slo.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn is_circuit_open(&self) -> bool {
+        self.inner
+            .breaker
+            .lock()
+            .open_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Whether calls are rejected because the engine is over its latency SLO
+    pub fn is_fallback_active(&self) -> bool {
+        let breaker = self.inner.breaker.lock();
+        breaker.slow
+            && breaker
+                .open_until
+                .is_some_and(|until| Instant::now() < until)
+    }
+
+    fn check_circuit(&self, entry: &'static str) -> BridgeResult<()> {
+        let mut breaker = self.inner.breaker.lock();
+        if let Some(until) = breaker.open_until {
+            if Instant::now() < until {
+                bridge_metrics().record_rejected(entry);
+                let reason = if breaker.slow {
+                    "SpiraPi fallback: engine above latency SLO"
+                } else {
+                    "SpiraPi circuit open: engine unresponsive"
+                };
+                return Err(SpiraChainError::SemanticEngine(reason.to_string()));
+            }
+            // Half-open: this call probes the engine, one more failure re-opens
+            breaker.open_until = None;
+            breaker.slow = false;
+        }
+        Ok(())
+    }
+
+    /// Track the latency of a finished call and fall back while the recent
+    /// p95 is above the SLO
+    fn observe_latency(&self, latency: Duration) {
+        let slo = self.latency_slo();
+        if slo.is_zero() {
+            return;
+        }
+
+        let p95 = {
+            let mut recent = self.inner.recent_latencies.lock();
+            if recent.len() == SLO_WINDOW {
+                recent.pop_front();
+            }
+            recent.push_back(latency);
+            if recent.len() < SLO_MIN_SAMPLES {
+                return;
+            }
+
+            let mut sorted: Vec<Duration> = recent.iter().copied().collect();
+            sorted.sort_unstable();
+            let p95 = sorted[(sorted.len() * 95 / 100).min(sorted.len() - 1)];
+            if p95 <= slo {
+                return;
+            }
+            // Start over after the cooldown rather than judging the engine on stale calls
+            recent.clear();
+            p95
+        };
+
+        let mut breaker = self.inner.breaker.lock();
+        breaker.open_until = Some(Instant::now() + self.inner.config.cooldown);
+        breaker.slow = true;
+        warn!(
+            "🐢 SpiraPi p95 latency {:?} above the {:?} SLO, falling back for {:?}",
+            p95, slo, self.inner.config.cooldown
+        );
+    }
+
+    fn finished(&self, entry: &'static str, latency: Duration, ok: bool) {
+        self.record(true);
+        bridge_metrics().record_call(entry, latency, ok);
+        self.observe_latency(latency);
+    }
+
+    /// Engine errors still count as answers; only stalls and dead calls trip the breaker
+    fn record(&self, answered: bool) {
+        let mut breaker = self.inner.breaker.lock();
+        if answered {
+            breaker.consecutive_failures = 0;
+            return;
+        }
+
+        breaker.consecutive_failures += 1;
+        if breaker.consecutive_failures >= self.inner.config.failure_threshold {
+            breaker.open_until = Some(Instant::now() + self.inner.config.cooldown);
+            breaker.slow = false;
+            warn!(
+                "⚠️ SpiraPi stalled {} times in a row, rejecting calls for {:?}",
+                breaker.consecutive_failures, self.inner.config.cooldown
+            );
+        }
+    }
+
+    /// Queue `f`, recording how long it waits for a worker under `entry`
+    fn submit<T, F>(
+        &self,
+        entry: &'static str,
+        f: F,
+        reply: impl FnOnce(T) + Send + 'static,
+    ) -> BridgeResult<()>
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let queued = Instant::now();
+        let job: Job = Box::new(move || {
+            bridge_metrics().record_queue_wait(entry, queued.elapsed());
+            reply(f());
+        });
+        self.inner
+            .jobs
+            .lock()
+            .send(job)
+            .map_err(|_| SpiraChainError::SemanticEngine("SpiraPi worker pool stopped".to_string()))
+    }
+
+    fn timed_out(&self, entry: &'static str, timeout: Duration) -> SpiraChainError {
+        self.record(false);
+        bridge_metrics().record_timeout(entry, timeout);
+        self.observe_latency(timeout);
+        SpiraChainError::SemanticEngine(format!("SpiraPi call timed out after {:?}", timeout))
+    }
+
+    fn dropped(&self, entry: &'static str, latency: Duration) -> SpiraChainError {
+        self.record(false);
+        bridge_metrics().record_call(entry, latency, false);
+        SpiraChainError::SemanticEngine("SpiraPi call aborted".to_string())
+    }
+
+    /// Run `f` on a worker and wait for it without blocking the async runtime.
+    /// `entry` names the call in metrics.
+    pub async fn run<T, F>(&self, entry: &'static str, f: F) -> BridgeResult<T>
+    where
+        F: FnOnce() -> BridgeResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.check_circuit(entry)?;
+
+        let (reply, response) = oneshot::channel();
+        let started = Instant::now();
+        self.submit(entry, f, move |result| {
+            let _ = reply.send(result);
+        })?;
+
+        let timeout = self.call_timeout();
+        match tokio::time::timeout(timeout, response).await {
+            Ok(Ok(result)) => {
+                self.finished(entry, started.elapsed(), result.is_ok());
+                result
+            }
+            Ok(Err(_)) => Err(self.dropped(entry, started.elapsed())),
+            Err(_) => Err(self.timed_out(entry, timeout)),
+        }
+    }
+
+    /// `run` for synchronous callers; still bounded by the timeout and the breaker
+    pub fn run_blocking<T, F>(&self, entry: &'static str, f: F) -> BridgeResult<T>
+    where
+        F: FnOnce() -> BridgeResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.check_circuit(entry)?;
+
+        let (reply, response) = mpsc::sync_channel(1);
+        let started = Instant::now();
+        self.submit(entry, f, move |result| {
+            let _ = reply.send(result);
+        })?;
+
+        let timeout = self.call_timeout();
+        match response.recv_timeout(timeout) {
+            Ok(result) => {
+                self.finished(entry, started.elapsed(), result.is_ok());
+                result
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(self.dropped(entry, started.elapsed()))
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(self.timed_out(entry, timeout)),
+        }
+    }
+
+    /// Bridge metrics plus the breaker state, in the Prometheus text format
+    pub fn export_prometheus(&self) -> String {
+        let mut out = bridge_metrics().export_prometheus();
+        out.push_str(&format!(
+            "# HELP spirachain_spirapi_circuit_open Whether SpiraPi calls are being rejected\n\
+             # TYPE spirachain_spirapi_circuit_open gauge\n\
+             spirachain_spirapi_circuit_open {}\n\
+             # HELP spirachain_spirapi_fallback_active Whether SpiraPi calls fall back for exceeding the latency SLO\n\
+             # TYPE spirachain_spirapi_fallback_active gauge\n\
+             spirachain_spirapi_fallback_active {}\n",
+            self.is_circuit_open() as u8,
+            self.is_fallback_active() as u8
+        ));
+        out
+    }
+
+    pub async fn generate_pi_coordinate(
+        &self,
+        entity_hash: &[u8],
+        timestamp: u64,
+        nonce: u64,
+    ) -> BridgeResult<PiCoordinate> {
+        let entity_hash = entity_hash.to_vec();
+        self.run("generate_pi_coordinate", move || {
+            crate::generate_pi_coordinate(&entity_hash, timestamp, nonce)
+        })
+        .await
+    }
+
+    pub async fn semantic_index_content(
+        &self,
+        content: &str,
+        content_type: &str,
+    ) -> BridgeResult<SemanticIndexResult> {
+        let content = content.to_string();
+        let content_type = content_type.to_string();
+        self.run("semantic_index_content", move || {
+            crate::semantic_index_content(&content, &content_type)
+        })
+        .await
+    }
+
+    pub async fn generate_embedding(&self, text: &str) -> BridgeResult<Vec<f32>> {
+        let text = text.to_string();
+        self.run("generate_embedding", move || {
+            crate::SpiraPiEngine::generate_embedding(&text)
+        })
+        .await
+    }
+
+    /// One identifier; concurrent requests of the same length share an engine call
+    pub async fn generate_identifier(&self, length: usize) -> BridgeResult<PiIdentifier> {
+        self.check_circuit("generate_identifier")?;
+
+        let (reply, response) = oneshot::channel();
+        let (full, first) = {
+            let mut pending = self.inner.pending_identifiers.lock();
+            let waiters = pending.entry(length).or_default();
+            waiters.push(reply);
+            (
+                waiters.len() >= self.inner.config.max_batch_size,
+                waiters.len() == 1,
+            )
+        };
+
+        if full {
+            self.flush_identifiers(length);
+        } else if first {
+            let pool = self.clone();
+            let window = self.inner.config.batch_window;
+            tokio::spawn(async move {
+                tokio::time::sleep(window).await;
+                pool.flush_identifiers(length);
+            });
+        }
+
+        let timeout = self.call_timeout() + self.inner.config.batch_window;
+        match tokio::time::timeout(timeout, response).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(SpiraChainError::SemanticEngine(
+                "SpiraPi call aborted".to_string(),
+            )),
+            Err(_) => Err(SpiraChainError::SemanticEngine(format!(
+                "SpiraPi call timed out after {:?}",
+                timeout
+            ))),
+        }
+    }
+
+    fn flush_identifiers(&self, length: usize) {
+        let waiters = self
+            .inner
+            .pending_identifiers
+            .lock()
+            .remove(&length)
+            .unwrap_or_default();
+        if waiters.is_empty() {
+            return;
+        }
+
+        let pool = self.clone();
+        tokio::spawn(async move {
+            let count = waiters.len();
+            match pool
+                .run("generate_batch_identifiers", move || {
+                    crate::generate_batch_identifiers(count, length)
+                })
+                .await
+            {
+                Ok(ids) if ids.len() >= count => {
+                    for (waiter, id) in waiters.into_iter().zip(ids) {
+                        let _ = waiter.send(Ok(id));
+                    }
+                }
+                Ok(ids) => {
+                    let message =
+                        format!("SpiraPi returned {} of {} identifiers", ids.len(), count);
+                    for waiter in waiters {
+                        let _ = waiter.send(Err(SpiraChainError::SemanticEngine(message.clone())));
+                    }
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    for waiter in waiters {
+                        let _ = waiter.send(Err(SpiraChainError::SemanticEngine(message.clone())));
+                    }
+                }
+            }
+        });
+    }
+}
+
+static BRIDGE_POOL: OnceLock<BridgePool> = OnceLock::new();
+
+/// Start the shared pool with `config`. Has no effect once the pool is running.
+pub fn init_bridge_pool(config: BridgePoolConfig) -> &'static BridgePool {
+    BRIDGE_POOL.get_or_init(|| BridgePool::new(config))
+}
+
+/// Shared pool, started with the default config on first use
+pub fn bridge_pool() -> &'static BridgePool {
+    BRIDGE_POOL.get_or_init(|| BridgePool::new(BridgePoolConfig::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_pool() -> BridgePool {
+        BridgePool::new(BridgePoolConfig {
+            workers: 1,
+            call_timeout: Duration::from_millis(50),
+            failure_threshold: 2,
+            ..BridgePoolConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_calls_run_off_the_caller_thread() {
+        let pool = test_pool();
+        let caller = thread::current().id();
+        let worker = pool
+            .run("test", || Ok(thread::current().id()))
+            .await
+            .unwrap();
+        assert_ne!(worker, caller);
+
+        let coord = pool.generate_pi_coordinate(b"entity", 42, 0).await.unwrap();
+        assert_eq!(
+            coord,
+            crate::generate_pi_coordinate(b"entity", 42, 0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stalled_engine_opens_circuit() {
+        let pool = test_pool();
+        for _ in 0..2 {
+            let result = pool
+                .run("test", || {
+                    thread::sleep(Duration::from_millis(200));
+                    Ok(())
+                })
+                .await;
+            assert!(result.is_err());
+        }
+
+        assert!(pool.is_circuit_open());
+        assert!(!pool.is_fallback_active());
+        assert!(pool.run("test", || Ok(())).await.is_err());
+        assert!(pool.run_blocking("test", || Ok(())).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slow_engine_triggers_fallback() {
+        let pool = BridgePool::new(BridgePoolConfig {
+            workers: 1,
+            latency_slo: Duration::from_millis(1),
+            ..BridgePoolConfig::default()
+        });
+        for _ in 0..SLO_MIN_SAMPLES {
+            pool.run("test_slow", || {
+                thread::sleep(Duration::from_millis(5));
+                Ok(())
+            })
+            .await
+            .unwrap();
+        }
+
+        assert!(pool.is_fallback_active());
+        assert!(pool.run("test_slow", || Ok(())).await.is_err());
+        assert_eq!(bridge_metrics().snapshot()["test_slow"].rejected, 1);
+        assert!(pool
+            .export_prometheus()
+            .contains("spirachain_spirapi_fallback_active 1"));
+    }
+
+    #[tokio::test]
+    async fn test_identifier_requests_are_batched() {
+        let pool = BridgePool::new(BridgePoolConfig {
+            batch_window: Duration::from_millis(20),
+            ..BridgePoolConfig::default()
+        });
+
+        let (a, b, c) = tokio::join!(
+            pool.generate_identifier(12),
+            pool.generate_identifier(12),
+            pool.generate_identifier(12)
+        );
+        let sequences: Vec<String> = [a, b, c]
+            .into_iter()
+            .map(|id| id.unwrap().pi_sequence)
+            .collect();
+
+        // The stub numbers identifiers by position within a batch
+        assert_eq!(sequences, ["000000000000", "000000000001", "000000000002"]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Browser bindings for SpiraChain wallets: keys, addresses, transaction
+//! building and signing, and header/merkle verification against the rules in
+//! `spirachain_core::light`. Build with `wasm-pack build crates/wasm`.
+//!
+//! Only ed25519 is exposed; the post-quantum keys in `spirachain-crypto` need C
+//! code that does not target wasm.
+
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use spirachain_core::{Address, Amount, BlockHeader, Hash, HeaderView, MerkleProof, Transaction};
+use std::fmt::Display;
+use wasm_bindgen::prelude::*;
+
+fn js_error(error: impl Display) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, JsError> {
+    hex::decode(value.trim_start_matches("0x")).map_err(js_error)
+}
+
+fn parse_hash(value: &str) -> Result<Hash, JsError> {
+    Hash::from_slice(&decode_hex(value)?).map_err(js_error)
+}
+
+fn address_of(key: &VerifyingKey) -> Address {
+    Address::new(*blake3::hash(key.as_bytes()).as_bytes())
+}
+
+/// Ed25519 wallet key, same scheme and address derivation as `spira wallet`
+#[wasm_bindgen]
+pub struct WalletKey {
+    signing_key: SigningKey,
+}
+
+#[wasm_bindgen]
+impl WalletKey {
+    /// From 32 secret bytes, e.g. `crypto.getRandomValues(new Uint8Array(32))`
+    #[wasm_bindgen(js_name = fromSecret)]
+    pub fn from_secret(secret: &[u8]) -> Result<WalletKey, JsError> {
+        let secret: [u8; 32] = secret
+            .try_into()
+            .map_err(|_| js_error("Secret key must be 32 bytes"))?;
+        Ok(WalletKey {
+            signing_key: SigningKey::from_bytes(&secret),
+        })
+    }
+
+    #[wasm_bindgen(js_name = fromSecretHex)]
+    pub fn from_secret_hex(secret: &str) -> Result<WalletKey, JsError> {
+        Self::from_secret(&decode_hex(secret)?)
+    }
+
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> String {
+        hex::encode(self.signing_key.verifying_key().as_bytes())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        address_of(&self.signing_key.verifying_key()).to_string()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.signing_key.sign(message).to_bytes().to_vec()
+    }
+}
+
+/// Transfer under construction. The `with*` methods consume the builder and
+/// return it, so calls chain from JS.
+#[wasm_bindgen]
+pub struct TransactionBuilder {
+    tx: Transaction,
+    fee: Option<Amount>,
+}
+
+#[wasm_bindgen]
+impl TransactionBuilder {
+    /// `amount` in QBT, e.g. `"1.5"`; `timestamp` in milliseconds, e.g. `Date.now()`
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        from: &str,
+        to: &str,
+        amount: &str,
+        timestamp: f64,
+    ) -> Result<TransactionBuilder, JsError> {
+        let from = from.parse::<Address>().map_err(js_error)?;
+        let to = to.parse::<Address>().map_err(js_error)?;
+        let amount = amount.parse::<Amount>().map_err(js_error)?;
+
+        Ok(TransactionBuilder {
+            tx: Transaction::new_at(from, to, amount, Amount::zero(), timestamp as u64),
+            fee: None,
+        })
+    }
+
+    #[wasm_bindgen(js_name = withPurpose)]
+    pub fn with_purpose(mut self, purpose: String) -> TransactionBuilder {
+        self.tx = self.tx.with_purpose(purpose);
+        self
+    }
+
+    /// Fee in QBT. Without one the transaction pays its minimum fee.
+    #[wasm_bindgen(js_name = withFee)]
+    pub fn with_fee(mut self, fee: &str) -> Result<TransactionBuilder, JsError> {
+        self.fee = Some(fee.parse::<Amount>().map_err(js_error)?);
+        Ok(self)
+    }
+
+    /// Fork id reported by the node's `/status`, binding the signature to that chain
+    #[wasm_bindgen(js_name = withForkId)]
+    pub fn with_fork_id(mut self, fork_id: &str) -> Result<TransactionBuilder, JsError> {
+        self.tx = self.tx.with_fork_id(parse_hash(fork_id)?);
+        Ok(self)
+    }
+
+    #[wasm_bindgen(js_name = minFee)]
+    pub fn min_fee(&self) -> String {
+        self.tx.min_fee().to_qbt_string()
+    }
+
+    /// Hash and sign with `key`, which must own the sender address
+    pub fn sign(self, key: &WalletKey) -> Result<SignedTransaction, JsError> {
+        let mut tx = self.tx;
+        if address_of(&key.signing_key.verifying_key()) != tx.from {
+            return Err(js_error("Key does not match the sender address"));
+        }
+
+        tx.fee = self.fee.unwrap_or_else(|| tx.min_fee());
+        tx.compute_hash();
+        tx.signature = key.sign(&tx.signing_message());
+        tx = tx.with_sender_public_key(key.signing_key.verifying_key().to_bytes());
+        Ok(SignedTransaction { tx })
+    }
+}
+
+#[wasm_bindgen]
+pub struct SignedTransaction {
+    tx: Transaction,
+}
+
+#[wasm_bindgen]
+impl SignedTransaction {
+    #[wasm_bindgen(getter)]
+    pub fn hash(&self) -> String {
+        self.tx.tx_hash.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn fee(&self) -> String {
+        self.tx.fee.to_qbt_string()
+    }
+
+    /// `tx_hex` for the node's `/submit_transaction` and `/simulate_transaction`
+    #[wasm_bindgen(getter)]
+    pub fn raw(&self) -> Result<String, JsError> {
+        Ok(hex::encode(serde_json::to_vec(&self.tx).map_err(js_error)?))
+    }
+
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.tx).map_err(js_error)
+    }
+}
+
+/// Canonical `0x…` form of an address, or an error if it is malformed
+#[wasm_bindgen(js_name = parseAddress)]
+pub fn parse_address(address: &str) -> Result<String, JsError> {
+    Ok(address.parse::<Address>().map_err(js_error)?.to_string())
+}
+
+#[wasm_bindgen(js_name = addressFromPublicKey)]
+pub fn address_from_public_key(public_key: &str) -> Result<String, JsError> {
+    let bytes: [u8; 32] = decode_hex(public_key)?
+        .try_into()
+        .map_err(|_| js_error("Public key must be 32 bytes"))?;
+    let key = VerifyingKey::from_bytes(&bytes).map_err(js_error)?;
+    Ok(address_of(&key).to_string())
+}
+
+/// Whether a raw transaction is intact and signed by the owner of its sender
+/// address
+#[wasm_bindgen(js_name = verifyTransaction)]
+pub fn verify_transaction(raw: &str, public_key: &str) -> Result<bool, JsError> {
+    let tx: Transaction = serde_json::from_slice(&decode_hex(raw)?).map_err(js_error)?;
+    let key = VerifyingKey::try_from(decode_hex(public_key)?.as_slice()).map_err(js_error)?;
+    let Ok(signature) = ed25519_dalek::Signature::from_slice(&tx.signature) else {
+        return Ok(false);
+    };
+
+    Ok(tx.computed_hash() == tx.tx_hash
+        && address_of(&key) == tx.from
+        && key.verify(&tx.signing_message(), &signature).is_ok())
+}
+
+/// Hash of a block header as returned by the node's block endpoints
+#[wasm_bindgen(js_name = blockHeaderHash)]
+pub fn block_header_hash(header_json: &str) -> Result<String, JsError> {
+    let header: BlockHeader = serde_json::from_str(header_json).map_err(js_error)?;
+    Ok(header.hash().to_string())
+}
+
+/// Check a JSON array of consecutive headers: producer signatures, hash links
+/// and spiral continuity
+#[wasm_bindgen(js_name = verifyHeaderChain)]
+pub fn verify_header_chain(headers_json: &str, max_spiral_jump: f64) -> Result<(), JsError> {
+    let headers: Vec<BlockHeader> = serde_json::from_str(headers_json).map_err(js_error)?;
+    let views: Vec<HeaderView> = headers.iter().map(BlockHeader::view).collect();
+    spirachain_core::verify_header_chain(&views, max_spiral_jump).map_err(js_error)
+}
+
+/// Whether `tx_hash` at `index` is included under the `merkle_root` of a block
+/// with header `block_version`
+#[wasm_bindgen(js_name = verifyMerkleProof)]
+pub fn verify_merkle_proof(
+    tx_hash: &str,
+    index: u32,
+    siblings: Vec<String>,
+    merkle_root: &str,
+    block_version: u64,
+) -> Result<bool, JsError> {
+    let proof = MerkleProof {
+        index,
+        siblings: siblings
+            .iter()
+            .map(|sibling| parse_hash(sibling))
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(proof.verify(
+        &parse_hash(tx_hash)?,
+        &parse_hash(merkle_root)?,
+        block_version,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_transaction_round_trip() {
+        let key = WalletKey::from_secret(&[3u8; 32]).unwrap();
+        assert_eq!(
+            address_from_public_key(&key.public_key()).unwrap(),
+            key.address()
+        );
+
+        let to = Address::new([9u8; 32]).to_string();
+        let signed = TransactionBuilder::new(&key.address(), &to, "1.5", 1_700_000_000_000.0)
+            .unwrap()
+            .with_purpose("coffee".to_string())
+            .sign(&key)
+            .unwrap();
+
+        let raw = signed.raw().unwrap();
+        assert!(verify_transaction(&raw, &key.public_key()).unwrap());
+
+        let other = WalletKey::from_secret(&[4u8; 32]).unwrap();
+        assert!(!verify_transaction(&raw, &other.public_key()).unwrap());
+
+        let tx: Transaction = serde_json::from_slice(&hex::decode(raw).unwrap()).unwrap();
+        assert_eq!(tx.timestamp, 1_700_000_000_000);
+        assert_eq!(tx.fee, tx.min_fee());
+        assert!(tx.verify_sender_signature().is_ok());
+    }
+}